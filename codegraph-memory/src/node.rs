@@ -2,6 +2,7 @@
 //!
 //! Core types for representing memories in CodeGraph.
 
+use chrono::DateTime;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -424,6 +425,31 @@ impl MemoryNodeBuilder {
         self
     }
 
+    /// Set the version tag in temporal metadata
+    pub fn at_version(mut self, tag: impl Into<String>) -> Self {
+        let mut temporal = self
+            .temporal
+            .take()
+            .unwrap_or_else(TemporalMetadata::new_current);
+        temporal.version_tag = Some(tag.into());
+        self.temporal = Some(temporal);
+        self
+    }
+
+    /// Set when the knowledge became true, in unix seconds (an out of range
+    /// timestamp leaves it at now)
+    pub fn valid_since(mut self, timestamp: i64) -> Self {
+        let mut temporal = self
+            .temporal
+            .take()
+            .unwrap_or_else(TemporalMetadata::new_current);
+        if let Some(valid_at) = DateTime::from_timestamp(timestamp, 0) {
+            temporal.valid_at = valid_at;
+        }
+        self.temporal = Some(temporal);
+        self
+    }
+
     /// Set confidence score
     pub fn confidence(mut self, confidence: f32) -> Self {
         self.confidence = confidence.clamp(0.0, 1.0);
@@ -546,6 +572,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_builder_release_context() {
+        let memory = MemoryNode::builder()
+            .project_context("Release v2.3", "Removed the legacy auth flow")
+            .title("Release v2.3")
+            .content("Removed the legacy auth flow")
+            .at_commit("abc123")
+            .at_version("v2.3")
+            .valid_since(1_700_000_000)
+            .build()
+            .unwrap();
+
+        assert_eq!(memory.temporal.valid_at.timestamp(), 1_700_000_000);
+        assert_eq!(memory.temporal.version_tag.as_deref(), Some("v2.3"));
+        assert_eq!(memory.temporal.commit_hash.as_deref(), Some("abc123"));
+        assert!(memory.is_current());
+    }
+
     #[test]
     fn test_builder_missing_required() {
         let result = MemoryNode::builder()
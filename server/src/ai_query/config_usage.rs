@@ -0,0 +1,285 @@
+//! Configuration Usage Index
+//!
+//! Finds reads of environment variables and configuration keys in source files
+//! (`process.env.X`, `os.environ['X']`, `std::env::var("X")`, `@Value("${x}")`, ...).
+//!
+//! Unlike the other QueryEngine indexes this one needs file contents, so it is not
+//! rebuilt with the graph on every edit. It is refreshed lazily per query instead:
+//! only files whose modification time changed since the last scan are re-read.
+
+use super::primitives::ConfigSource;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use std::time::SystemTime;
+
+/// Accessor patterns as (label, source, regex). The key is capture group 1.
+///
+/// More specific accessors come first: when several patterns match the same key
+/// on one line (e.g. `os.getenv` and plain `getenv`), only the first is kept.
+const CONFIG_PATTERNS: &[(&str, ConfigSource, &str)] = &[
+    // JavaScript / TypeScript
+    (
+        "process.env",
+        ConfigSource::Env,
+        r"process\.env\.([A-Za-z_][A-Za-z0-9_]*)",
+    ),
+    (
+        "process.env",
+        ConfigSource::Env,
+        r#"process\.env\[\s*['"`]([^'"`]+)['"`]\s*\]"#,
+    ),
+    (
+        "import.meta.env",
+        ConfigSource::Env,
+        r"import\.meta\.env\.([A-Za-z_][A-Za-z0-9_]*)",
+    ),
+    // Python
+    (
+        "os.environ",
+        ConfigSource::Env,
+        r#"os\.environ(?:\.get)?[\[(]\s*['"]([^'"]+)['"]"#,
+    ),
+    (
+        "os.getenv",
+        ConfigSource::Env,
+        r#"os\.getenv\(\s*['"]([^'"]+)['"]"#,
+    ),
+    // Rust
+    (
+        "env::var",
+        ConfigSource::Env,
+        r#"env::var(?:_os)?\(\s*"([^"]+)""#,
+    ),
+    (
+        "env!",
+        ConfigSource::Env,
+        r#"\b(?:option_)?env!\(\s*"([^"]+)""#,
+    ),
+    // Go
+    (
+        "os.Getenv",
+        ConfigSource::Env,
+        r#"os\.(?:Getenv|LookupEnv)\(\s*"([^"]+)""#,
+    ),
+    // Java / C#
+    (
+        "System.getenv",
+        ConfigSource::Env,
+        r#"System\.getenv\(\s*"([^"]+)""#,
+    ),
+    (
+        "Environment.GetEnvironmentVariable",
+        ConfigSource::Env,
+        r#"Environment\.GetEnvironmentVariable\(\s*"([^"]+)""#,
+    ),
+    // Ruby / PHP
+    (
+        "ENV",
+        ConfigSource::Env,
+        r#"\bENV(?:\.fetch\(|\[)\s*['"]([^'"]+)['"]"#,
+    ),
+    (
+        "$_ENV",
+        ConfigSource::Env,
+        r#"\$_(?:ENV|SERVER)\[\s*['"]([^'"]+)['"]"#,
+    ),
+    // C / C++ / PHP
+    (
+        "getenv",
+        ConfigSource::Env,
+        r#"\bgetenv\(\s*['"]([^'"]+)['"]"#,
+    ),
+    // Config keys
+    (
+        "@Value",
+        ConfigSource::Config,
+        r#"@Value\(\s*"\$\{([^}:"]+)"#,
+    ),
+    (
+        "getProperty",
+        ConfigSource::Config,
+        r#"\bgetProperty\(\s*"([^"]+)""#,
+    ),
+    (
+        "viper.Get",
+        ConfigSource::Config,
+        r#"viper\.Get[A-Za-z]*\(\s*"([^"]+)""#,
+    ),
+    (
+        "config.get",
+        ConfigSource::Config,
+        r#"\bconfig\.get\(\s*['"]([^'"]+)['"]"#,
+    ),
+    (
+        "app.config",
+        ConfigSource::Config,
+        r#"\bapp\.config\[\s*['"]([^'"]+)['"]"#,
+    ),
+    (
+        "settings",
+        ConfigSource::Config,
+        r"\bsettings\.([A-Z][A-Z0-9_]+)\b",
+    ),
+];
+
+/// A single config read found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ConfigReference {
+    pub key: String,
+    pub source: ConfigSource,
+    pub accessor: &'static str,
+    /// Line number (1-indexed)
+    pub line: u32,
+    /// Column number (0-indexed)
+    pub column: u32,
+}
+
+fn compiled_patterns() -> &'static [(&'static str, ConfigSource, Regex)] {
+    static PATTERNS: OnceLock<Vec<(&'static str, ConfigSource, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        CONFIG_PATTERNS
+            .iter()
+            .map(|(accessor, source, pattern)| {
+                let regex = Regex::new(pattern).expect("config pattern must compile");
+                (*accessor, *source, regex)
+            })
+            .collect()
+    })
+}
+
+/// Find every config read in `content`.
+pub(crate) fn scan_config_references(content: &str) -> Vec<ConfigReference> {
+    let patterns = compiled_patterns();
+    let mut references = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let mut seen: HashSet<&str> = HashSet::new();
+        for (accessor, source, regex) in patterns {
+            for captures in regex.captures_iter(line) {
+                let Some(key) = captures.get(1) else {
+                    continue;
+                };
+                if !seen.insert(key.as_str()) {
+                    continue;
+                }
+                references.push(ConfigReference {
+                    key: key.as_str().to_string(),
+                    source: *source,
+                    accessor: *accessor,
+                    line: idx as u32 + 1,
+                    column: captures.get(0).map_or(0, |m| m.start()) as u32,
+                });
+            }
+        }
+    }
+
+    references.sort_by_key(|r| (r.line, r.column));
+    references
+}
+
+/// Config reads per file, with the modification time each file was scanned at.
+#[derive(Debug, Default)]
+pub(crate) struct ConfigIndex {
+    files: HashMap<String, (Option<SystemTime>, Vec<ConfigReference>)>,
+}
+
+impl ConfigIndex {
+    /// Bring the index in line with `paths`: rescan files that are new or whose
+    /// modification time changed, and drop files no longer listed.
+    ///
+    /// Returns the number of files (re)scanned.
+    pub(crate) fn refresh(&mut self, paths: &[String]) -> usize {
+        let wanted: HashSet<&str> = paths.iter().map(String::as_str).collect();
+        self.files.retain(|path, _| wanted.contains(path.as_str()));
+
+        let mut scanned = 0;
+        for path in paths {
+            let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+            if let Some((indexed_at, _)) = self.files.get(path) {
+                if modified.is_some() && *indexed_at == modified {
+                    continue;
+                }
+            }
+
+            let references = std::fs::read_to_string(path)
+                .map(|content| scan_config_references(&content))
+                .unwrap_or_default();
+            self.files.insert(path.clone(), (modified, references));
+            scanned += 1;
+        }
+        scanned
+    }
+
+    /// All indexed references as (file, reference).
+    pub(crate) fn references(&self) -> impl Iterator<Item = (&str, &ConfigReference)> {
+        self.files
+            .iter()
+            .flat_map(|(path, (_, refs))| refs.iter().map(move |r| (path.as_str(), r)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(content: &str) -> Vec<(String, &'static str)> {
+        scan_config_references(content)
+            .into_iter()
+            .map(|r| (r.key, r.accessor))
+            .collect()
+    }
+
+    #[test]
+    fn test_scan_env_reads_across_languages() {
+        assert_eq!(
+            keys("const url = process.env.DATABASE_URL || process.env['API_KEY'];"),
+            vec![
+                ("DATABASE_URL".to_string(), "process.env"),
+                ("API_KEY".to_string(), "process.env")
+            ]
+        );
+        assert_eq!(
+            keys("host = os.environ['DB_HOST']\nport = os.getenv(\"DB_PORT\", 5432)"),
+            vec![
+                ("DB_HOST".to_string(), "os.environ"),
+                ("DB_PORT".to_string(), "os.getenv")
+            ]
+        );
+        assert_eq!(
+            keys("let url = std::env::var(\"DATABASE_URL\")?;"),
+            vec![("DATABASE_URL".to_string(), "env::var")]
+        );
+        assert_eq!(
+            keys("addr := os.Getenv(\"LISTEN_ADDR\")"),
+            vec![("LISTEN_ADDR".to_string(), "os.Getenv")]
+        );
+    }
+
+    #[test]
+    fn test_scan_config_keys_and_positions() {
+        let refs = scan_config_references("class A {\n  @Value(\"${server.port:8080}\")\n}");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].key, "server.port");
+        assert_eq!(refs[0].source, ConfigSource::Config);
+        assert_eq!(refs[0].line, 2);
+        assert_eq!(refs[0].column, 2);
+    }
+
+    #[test]
+    fn test_refresh_rescans_only_changed_files() {
+        let tmp = tempfile::tempdir().unwrap();
+        let file = tmp.path().join("app.js");
+        std::fs::write(&file, "process.env.PORT").unwrap();
+        let paths = vec![file.to_str().unwrap().to_string()];
+
+        let mut index = ConfigIndex::default();
+        assert_eq!(index.refresh(&paths), 1);
+        assert_eq!(index.refresh(&paths), 0);
+        assert_eq!(index.references().count(), 1);
+
+        // Files that leave the graph leave the index
+        assert_eq!(index.refresh(&[]), 0);
+        assert_eq!(index.references().count(), 0);
+    }
+}
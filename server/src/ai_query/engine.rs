@@ -3,26 +3,41 @@
 //! Main query engine that provides fast, composable query primitives for AI agents.
 //! Integrates with CodeGraph for graph-based code intelligence.
 
+use super::config_usage::ConfigIndex;
+use super::entry_rules::EntryRules;
+use super::persist::{file_hashes, graph_fingerprint, IndexSnapshot, SNAPSHOT_VERSION};
 use super::primitives::{
-    truncate_string, CallInfo, ClusterMember, ClusterResult, DetailedSymbolInfo, DuplicatePair,
-    DuplicateResult, EntryPoint, EntryType, ImportMatchMode, ImportSearchOptions, SearchOptions,
-    SignaturePattern, StructuralComparison, SymbolCluster, SymbolComparison, SymbolInfo,
-    SymbolLocation, SymbolMatch, SymbolSearchResult, SymbolType, TraversalDirection,
-    TraversalFilter, TraversalNode, MAX_SIGNATURE_LENGTH,
+    sink_category, truncate_string, AnnotatedSymbol, AnnotationSearchResult, CallInfo,
+    CentralityMetric, CentralityScores, ClusterMember, ClusterResult, ConfigSource, ConfigUsage,
+    ConfigUsageResult, CycleLevel, CycleMember, CycleResult, DependencyCycle, DetailedSymbolInfo,
+    DuplicatePair, DuplicateResult, EntryPoint, EntryType, FileOutline, HotSymbol,
+    HotSymbolsResult, Implementation, ImplementationDirection, ImplementationResult,
+    ImportMatchMode, ImportSearchOptions, IndexSizes, OutlineNode, PathResult, PathStep,
+    PipelineMatch, PipelineResult, PipelineStageCount, ReachabilityPredicate, ReachabilityResult,
+    RenameCollision, RenameMemory, RenamePreview, RenameReference, RiskHotspot, RiskHotspotsResult,
+    SearchMode, SearchOptions, SignaturePattern, SimilarCode, SimilarCodeResult, SimilarityMode,
+    SinkMatch, StructuralComparison, SymbolCluster, SymbolComparison, SymbolInfo, SymbolLocation,
+    SymbolMatch, SymbolSearchResult, SymbolType, TodoComment, TodoResult, TodoTag,
+    TraversalDirection, TraversalFilter, TraversalNode, TypeHierarchyDirection, TypeHierarchyNode,
+    TypeHierarchyResult, TypeUsage, TypeUsageResult, MAX_SIGNATURE_LENGTH, SINK_CATEGORIES,
 };
+use super::query_dsl::{parse_pipeline, PipelineStage, MAX_WORKING_SET};
 use super::text_index::{TextIndex, TextIndexBuilder};
+use crate::cancellation::CancellationToken;
 use crate::domain::node_props;
+use crate::header_links::{is_c_family_path, is_header_path};
 use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
-use codegraph_memory::VectorEngine;
+use codegraph_memory::{MemoryNode, VectorEngine};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Instant, SystemTime};
 use tokio::sync::RwLock;
 
-/// Weight for BM25 score in hybrid search (0-1)
-const BM25_WEIGHT: f32 = 0.4;
-/// Weight for semantic similarity score in hybrid search (0-1)
-const SEMANTIC_WEIGHT: f32 = 0.6;
+/// Default weight for BM25 score in hybrid search (0-1)
+pub const BM25_WEIGHT: f32 = 0.4;
+/// Default weight for semantic similarity score in hybrid search (0-1)
+pub const SEMANTIC_WEIGHT: f32 = 0.6;
 
 /// AI Query Engine for fast code exploration.
 pub struct QueryEngine {
@@ -36,6 +51,10 @@ pub struct QueryEngine {
     caller_index: Arc<RwLock<HashMap<NodeId, Vec<NodeId>>>>,
     /// Callee index: function -> list of callees
     callee_index: Arc<RwLock<HashMap<NodeId, Vec<NodeId>>>>,
+    /// Call graph centrality scores, recomputed by build_indexes
+    centrality: Arc<RwLock<HashMap<NodeId, CentralityScores>>>,
+    /// Environment variable / config key reads, refreshed lazily per query
+    config_index: Arc<RwLock<ConfigIndex>>,
     /// Shared vector engine for semantic embedding (set after memory init)
     vector_engine: Arc<RwLock<Option<Arc<VectorEngine>>>>,
     /// Symbol embeddings: NodeId -> 768-dim vector (Jina Code V2)
@@ -44,8 +63,67 @@ pub struct QueryEngine {
     symbol_texts: Arc<RwLock<HashMap<NodeId, String>>>,
     /// Embed full function body (true) or just name+signature (false, default)
     full_body_embedding: std::sync::atomic::AtomicBool,
+    /// Entry point detection rules (built-ins plus workspace rules)
+    entry_rules: std::sync::RwLock<Arc<EntryRules>>,
+    /// Hybrid search weights: (BM25, semantic)
+    search_weights: std::sync::RwLock<(f32, f32)>,
+    /// When the indexes were last built or loaded
+    indexes_updated_at: std::sync::RwLock<Option<SystemTime>>,
+    /// Labels of the workspace roots, when there are several
+    workspace_roots: std::sync::RwLock<Vec<(PathBuf, String)>>,
 }
 
+/// Labels for `folders` when there are several: each folder's name, or its
+/// full path if another folder has the same name.
+fn root_labels(folders: &[PathBuf]) -> Vec<(PathBuf, String)> {
+    if folders.len() < 2 {
+        return Vec::new();
+    }
+    let name = |folder: &PathBuf| {
+        folder
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| folder.to_string_lossy().into_owned())
+    };
+    folders
+        .iter()
+        .map(|folder| {
+            let label = name(folder);
+            let shared = folders.iter().filter(|other| name(other) == label).count() > 1;
+            let label = if shared {
+                folder.to_string_lossy().into_owned()
+            } else {
+                label
+            };
+            (folder.clone(), label)
+        })
+        .collect()
+}
+
+/// Minimum query length (chars) before fuzzy name matching kicks in.
+const FUZZY_MIN_QUERY_LEN: usize = 4;
+/// Minimum fuzzy score (0-1) for a name match to join symbol search results.
+const FUZZY_MIN_SCORE: f32 = 0.6;
+
+/// Maximum length of a user-supplied search regex.
+const REGEX_MAX_PATTERN_LEN: usize = 1000;
+/// Compiled program and lazy DFA size limit for search regexes (bytes).
+const REGEX_SIZE_LIMIT: usize = 1 << 20;
+/// Wall-clock budget for a regex symbol scan.
+const REGEX_SEARCH_BUDGET: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Maximum number of types returned by a type hierarchy walk.
+const TYPE_HIERARCHY_MAX_NODES: usize = 1000;
+
+/// Tokens per shingle for structural similarity.
+const SHINGLE_SIZE: usize = 4;
+/// Functions with fewer normalized tokens are too small to compare structurally.
+const STRUCTURAL_MIN_TOKENS: usize = 12;
+/// Default score cutoff for structural (token shingle) similarity.
+const STRUCTURAL_MIN_SIMILARITY: f32 = 0.5;
+/// Default score cutoff for semantic (embedding) similarity.
+const SEMANTIC_MIN_SIMILARITY: f32 = 0.1;
+
 /// Max characters of function body for full-body embedding.
 /// ~512 tokens ≈ first 40-50 lines of code.
 const FULL_BODY_MAX_CHARS: usize = 2048;
@@ -59,10 +137,16 @@ impl QueryEngine {
             import_index: Arc::new(RwLock::new(HashMap::new())),
             caller_index: Arc::new(RwLock::new(HashMap::new())),
             callee_index: Arc::new(RwLock::new(HashMap::new())),
+            centrality: Arc::new(RwLock::new(HashMap::new())),
+            config_index: Arc::new(RwLock::new(ConfigIndex::default())),
             vector_engine: Arc::new(RwLock::new(None)),
             symbol_vectors: Arc::new(RwLock::new(HashMap::new())),
             symbol_texts: Arc::new(RwLock::new(HashMap::new())),
             full_body_embedding: std::sync::atomic::AtomicBool::new(false),
+            entry_rules: std::sync::RwLock::new(Arc::new(EntryRules::default())),
+            search_weights: std::sync::RwLock::new((BM25_WEIGHT, SEMANTIC_WEIGHT)),
+            indexes_updated_at: std::sync::RwLock::new(None),
+            workspace_roots: std::sync::RwLock::new(Vec::new()),
         }
     }
 
@@ -71,6 +155,31 @@ impl QueryEngine {
         self.full_body_embedding.store(enabled, std::sync::atomic::Ordering::Relaxed);
     }
 
+    /// Set the BM25 and semantic weights of hybrid symbol search (each 0-1).
+    pub fn set_search_weights(&self, bm25: f32, semantic: f32) {
+        if let Ok(mut guard) = self.search_weights.write() {
+            *guard = (bm25.clamp(0.0, 1.0), semantic.clamp(0.0, 1.0));
+        }
+    }
+
+    fn search_weights(&self) -> (f32, f32) {
+        self.search_weights
+            .read()
+            .map(|weights| *weights)
+            .unwrap_or((BM25_WEIGHT, SEMANTIC_WEIGHT))
+    }
+
+    /// Load entry point rules from `<workspace>/.codegraph/entry_points.json`.
+    ///
+    /// Falls back to the built-in rules when the workspace has no rule file.
+    pub fn load_entry_rules(&self, workspace: &Path) -> Result<(), String> {
+        let rules = EntryRules::load(workspace)?;
+        if let Ok(mut guard) = self.entry_rules.write() {
+            *guard = Arc::new(rules);
+        }
+        Ok(())
+    }
+
     /// Build the embedding text for a symbol node.
     /// In signature mode: "name: signature — docstring"
     /// In full-body mode: "name: signature\n<truncated source body>"
@@ -127,6 +236,10 @@ impl QueryEngine {
 
         // Iterate over all nodes using iter_nodes()
         for (node_id, node) in graph.iter_nodes() {
+            // TODO comments are found through find_todos, not symbol search
+            if node_props::is_todo_comment(node) {
+                continue;
+            }
             let name = node_props::name(node).to_string();
             let docstring = node.properties.get_string("doc").map(|s| s.to_string());
 
@@ -153,8 +266,9 @@ impl QueryEngine {
                                             }
                                         }
                                     }
-                                    EdgeType::Calls => {
-                                        // Build caller/callee indexes
+                                    EdgeType::Calls | EdgeType::RuntimeCalls => {
+                                        // Build caller/callee indexes (RuntimeCalls
+                                        // link HTTP clients to route handlers)
                                         callee_map.entry(node_id).or_default().push(neighbor_id);
                                         caller_map.entry(neighbor_id).or_default().push(node_id);
                                     }
@@ -167,11 +281,112 @@ impl QueryEngine {
             }
         }
 
+        let centrality = compute_centrality(&callee_map);
+
         // Store built indexes
         *self.text_index.write().await = text_builder.build();
         *self.import_index.write().await = import_map;
         *self.caller_index.write().await = caller_map;
         *self.callee_index.write().await = callee_map;
+        *self.centrality.write().await = centrality;
+        self.mark_indexes_updated();
+    }
+
+    fn mark_indexes_updated(&self) {
+        if let Ok(mut guard) = self.indexes_updated_at.write() {
+            *guard = Some(SystemTime::now());
+        }
+    }
+
+    /// Set the workspace roots indexed into the graph. With more than one,
+    /// symbol locations are labeled with the root they belong to.
+    pub fn set_workspace_roots(&self, folders: &[PathBuf]) {
+        if let Ok(mut guard) = self.workspace_roots.write() {
+            *guard = root_labels(folders);
+        }
+    }
+
+    /// Label of the innermost workspace root containing `file`
+    fn root_label(&self, file: &str) -> Option<String> {
+        let roots = self.workspace_roots.read().ok()?;
+        roots
+            .iter()
+            .filter(|(folder, _)| Path::new(file).starts_with(folder))
+            .max_by_key(|(folder, _)| folder.components().count())
+            .map(|(_, label)| label.clone())
+    }
+
+    /// When the indexes were last built or loaded, if ever.
+    pub fn indexes_updated_at(&self) -> Option<SystemTime> {
+        self.indexes_updated_at.read().ok().and_then(|at| *at)
+    }
+
+    /// Number of entries in each index.
+    pub async fn index_sizes(&self) -> IndexSizes {
+        let text_index = self.text_index.read().await;
+        IndexSizes {
+            text_documents: text_index.document_count(),
+            text_tokens: text_index.token_count(),
+            imported_modules: self.import_index.read().await.len(),
+            functions_with_callers: self.caller_index.read().await.len(),
+            functions_with_callees: self.callee_index.read().await.len(),
+            symbol_vectors: self.symbol_vectors.read().await.len(),
+        }
+    }
+
+    /// Symbols whose name starts with `prefix`, shortest name first.
+    pub async fn complete_symbols(&self, prefix: &str, limit: usize) -> Vec<NodeId> {
+        self.text_index.read().await.prefix_search(prefix, limit)
+    }
+
+    /// Persist the text, import, caller and callee indexes to `dir`.
+    /// Records a graph fingerprint and per-file content hashes for staleness checks.
+    pub async fn save_indexes(&self, dir: &Path) -> Result<(), String> {
+        let graph = self.graph.read().await;
+        let text_index = self.text_index.read().await;
+        let import_index = self.import_index.read().await;
+        let caller_index = self.caller_index.read().await;
+        let callee_index = self.callee_index.read().await;
+
+        let snapshot = IndexSnapshot {
+            version: SNAPSHOT_VERSION,
+            graph_fingerprint: graph_fingerprint(&graph),
+            file_hashes: file_hashes(&graph),
+            text_index: text_index.clone(),
+            import_index: import_index.clone(),
+            caller_index: caller_index.clone(),
+            callee_index: callee_index.clone(),
+        };
+        snapshot.save(dir)
+    }
+
+    /// Load indexes persisted by [`save_indexes`](Self::save_indexes) instead of rebuilding.
+    ///
+    /// Returns false (leaving the current indexes untouched) when there is no snapshot,
+    /// or when it no longer matches the graph or the files on disk. Callers should
+    /// fall back to [`build_indexes`](Self::build_indexes) in that case.
+    pub async fn load_indexes(&self, dir: &Path) -> bool {
+        let Some(snapshot) = IndexSnapshot::load(dir) else {
+            return false;
+        };
+
+        {
+            let graph = self.graph.read().await;
+            if let Err(reason) = snapshot.check_fresh(&graph) {
+                tracing::info!("[QueryEngine] Persisted indexes are stale: {:?}", reason);
+                return false;
+            }
+        }
+
+        let centrality = compute_centrality(&snapshot.callee_index);
+
+        *self.text_index.write().await = snapshot.text_index;
+        *self.import_index.write().await = snapshot.import_index;
+        *self.caller_index.write().await = snapshot.caller_index;
+        *self.callee_index.write().await = snapshot.callee_index;
+        *self.centrality.write().await = centrality;
+        self.mark_indexes_updated();
+        true
     }
 
     /// Set the shared vector engine for semantic search.
@@ -373,8 +588,17 @@ impl QueryEngine {
 
     /// Search for symbols by name, docstring, or comments.
     /// Uses hybrid BM25 + semantic scoring when vector engine is available.
+    /// In `SearchMode::Regex`, the query is a regular expression matched
+    /// against symbol names and signatures instead.
     pub async fn symbol_search(&self, query: &str, options: &SearchOptions) -> SymbolSearchResult {
+        if options.mode == SearchMode::Regex {
+            return self.regex_symbol_search(query, options).await;
+        }
+
         let start = Instant::now();
+        let cancel = options.cancel.clone().with_budget(options.time_budget);
+        let out_of_time = || cancel.is_cancelled();
+        let mut truncated = false;
 
         let text_index = self.text_index.read().await;
         let graph = self.graph.read().await;
@@ -385,10 +609,44 @@ impl QueryEngine {
         } else {
             10
         };
-        let text_results = text_index.search(query, options.limit * fetch_multiplier);
+        let mut text_results = text_index.search(query, options.limit * fetch_multiplier);
+
+        // Typo-tolerant name matching for identifier-like queries, so that a
+        // misspelled or partial name still finds the symbol it refers to
+        let trimmed = query.trim();
+        if trimmed.chars().count() >= FUZZY_MIN_QUERY_LEN && !trimmed.contains(char::is_whitespace)
+        {
+            let max_text_score = text_results.iter().map(|r| r.score).fold(0.0f32, f32::max);
+            // Fold fuzzy scores onto the BM25 scale so an exact name ties the best keyword hit
+            let scale = if max_text_score > 0.0 {
+                max_text_score
+            } else {
+                1.0
+            };
+            for fuzzy in text_index.fuzzy_search(trimmed, options.limit * fetch_multiplier) {
+                if fuzzy.score < FUZZY_MIN_SCORE {
+                    continue;
+                }
+                let scaled = fuzzy.score * scale;
+                match text_results.iter_mut().find(|r| r.node_id == fuzzy.node_id) {
+                    Some(existing) => existing.score = existing.score.max(scaled),
+                    None => text_results.push(super::text_index::TextSearchResult {
+                        score: scaled,
+                        ..fuzzy
+                    }),
+                }
+            }
+        }
 
-        // Compute semantic scores if vector engine is available
-        let semantic_scores = self.compute_semantic_scores(query).await;
+        // Compute semantic scores if vector engine is available, unless the
+        // keyword search already used up the budget
+        let (bm25_weight, semantic_weight) = self.search_weights();
+        let semantic_scores = if out_of_time() {
+            truncated = true;
+            HashMap::new()
+        } else {
+            self.compute_semantic_scores(query).await
+        };
         let has_semantic = !semantic_scores.is_empty();
 
         // Find max BM25 score for normalization
@@ -427,6 +685,7 @@ impl QueryEngine {
                         super::text_index::MatchReason::Docstring => "Docstring",
                         super::text_index::MatchReason::Comment => "Comment",
                         super::text_index::MatchReason::Multiple => "Multiple",
+                        super::text_index::MatchReason::Fuzzy => "Fuzzy",
                     },
                 )
             })
@@ -436,7 +695,11 @@ impl QueryEngine {
 
         // Score and filter all candidates
         let mut scored_results = Vec::new();
-        for &node_id in &all_candidate_ids {
+        for (scored, &node_id) in all_candidate_ids.iter().enumerate() {
+            if scored % 256 == 0 && out_of_time() {
+                truncated = true;
+                break;
+            }
             if let Ok(node) = graph.get_node(node_id) {
                 // Apply symbol type filter
                 if !options.symbol_types.is_empty() {
@@ -467,7 +730,7 @@ impl QueryEngine {
                     let semantic_sim = semantic_scores.get(&node_id).copied().unwrap_or(0.0);
 
                     let score = if has_semantic {
-                        BM25_WEIGHT * bm25_norm + SEMANTIC_WEIGHT * semantic_sim
+                        bm25_weight * bm25_norm + semantic_weight * semantic_sim
                     } else {
                         bm25_norm // pure BM25 fallback
                     };
@@ -506,6 +769,134 @@ impl QueryEngine {
             results: scored_results,
             total_matches,
             query_time_ms,
+            truncated,
+        }
+    }
+
+    /// Compile a user-supplied search regex with size limits.
+    ///
+    /// The regex crate guarantees linear-time matching, so the remaining risk
+    /// is pathological compile size; patterns are capped in length and in
+    /// compiled/DFA size. Returns a human-readable error for invalid input.
+    pub fn compile_search_regex(pattern: &str) -> Result<regex::Regex, String> {
+        if pattern.len() > REGEX_MAX_PATTERN_LEN {
+            return Err(format!(
+                "Regex pattern too long ({} chars, max {REGEX_MAX_PATTERN_LEN})",
+                pattern.len()
+            ));
+        }
+        regex::RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .dfa_size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| format!("Invalid regex: {e}"))
+    }
+
+    /// Regex-mode symbol search over names and signatures.
+    ///
+    /// Name matches rank above signature-only matches; shorter names rank
+    /// first within each group. The scan stops once REGEX_SEARCH_BUDGET, or
+    /// the query's own shorter budget, has elapsed so a huge workspace cannot
+    /// stall the server.
+    async fn regex_symbol_search(
+        &self,
+        pattern: &str,
+        options: &SearchOptions,
+    ) -> SymbolSearchResult {
+        let start = Instant::now();
+        let Ok(regex) = Self::compile_search_regex(pattern) else {
+            return SymbolSearchResult {
+                results: Vec::new(),
+                total_matches: 0,
+                query_time_ms: start.elapsed().as_millis() as u64,
+                truncated: false,
+            };
+        };
+
+        let budget = options.time_budget.map_or(REGEX_SEARCH_BUDGET, |budget| {
+            budget.min(REGEX_SEARCH_BUDGET)
+        });
+        let graph = self.graph.read().await;
+        let mut scored_results = Vec::new();
+        let mut truncated = false;
+        for (scanned, (node_id, node)) in graph.iter_nodes().enumerate() {
+            if scanned % 1024 == 0 && (start.elapsed() > budget || options.cancel.is_cancelled()) {
+                tracing::warn!(
+                    "[QueryEngine] Regex search stopped after {}ms ({} nodes scanned)",
+                    budget.as_millis(),
+                    scanned
+                );
+                truncated = true;
+                break;
+            }
+            if node_props::is_todo_comment(node) {
+                continue;
+            }
+
+            if !options.symbol_types.is_empty() {
+                let node_type_matches = options.symbol_types.iter().any(|st| {
+                    matches!(
+                        (st, &node.node_type),
+                        (SymbolType::Function, NodeType::Function)
+                            | (SymbolType::Class, NodeType::Class)
+                            | (SymbolType::Variable, NodeType::Variable)
+                            | (SymbolType::Module, NodeType::Module)
+                            | (SymbolType::Interface, NodeType::Interface)
+                            | (SymbolType::Type, NodeType::Type)
+                    )
+                });
+                if !node_type_matches {
+                    continue;
+                }
+            }
+
+            let name = node_props::name(node);
+            if name.is_empty() {
+                continue;
+            }
+            let (score, match_reason) = if regex.is_match(name) {
+                (1.0, "RegexName")
+            } else if node
+                .properties
+                .get_string("signature")
+                .is_some_and(|sig| regex.is_match(sig))
+            {
+                (0.8, "RegexSignature")
+            } else {
+                continue;
+            };
+
+            let Some(symbol) = self.node_to_symbol_info_opts(&graph, node_id, options.compact)
+            else {
+                continue;
+            };
+            if !options.include_private && !symbol.is_public {
+                continue;
+            }
+
+            scored_results.push(SymbolMatch {
+                node_id,
+                symbol,
+                score,
+                match_reason: match_reason.to_string(),
+            });
+        }
+
+        scored_results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.symbol.name.len().cmp(&b.symbol.name.len()))
+                .then_with(|| a.symbol.name.cmp(&b.symbol.name))
+        });
+        let total_matches = scored_results.len();
+        scored_results.truncate(options.limit);
+
+        SymbolSearchResult {
+            results: scored_results,
+            total_matches,
+            query_time_ms: start.elapsed().as_millis() as u64,
+            truncated,
         }
     }
 
@@ -606,12 +997,15 @@ impl QueryEngine {
     }
 
     /// Traverse the graph from a starting node with filters.
+    ///
+    /// Stops early when `cancel` is cancelled, returning the nodes found so far.
     pub async fn traverse_graph(
         &self,
         start_node: NodeId,
         direction: TraversalDirection,
         max_depth: u32,
         filter: &TraversalFilter,
+        cancel: &CancellationToken,
     ) -> Vec<TraversalNode> {
         let graph = self.graph.read().await;
         let mut results = Vec::new();
@@ -628,7 +1022,7 @@ impl QueryEngine {
         };
 
         while let Some((current, depth, path, incoming_edge_type)) = queue.pop_front() {
-            if depth > max_depth {
+            if depth > max_depth || cancel.is_cancelled() {
                 break;
             }
 
@@ -662,8 +1056,15 @@ impl QueryEngine {
                 }
             }
 
-            // Phase 2: Always expand neighbors (edge type filter still applies)
-            if let Ok(neighbors) = graph.get_neighbors(current, codegraph_direction) {
+            // Enough nodes: anything further would be cut by the cap anyway
+            if results.len() >= filter.max_nodes {
+                break;
+            }
+
+            // Phase 2: Always expand neighbors (edge type filter still applies).
+            // Sorted so the visiting order, and with it result pages, is stable.
+            if let Ok(mut neighbors) = graph.get_neighbors(current, codegraph_direction) {
+                neighbors.sort_unstable();
                 for neighbor in neighbors {
                     if visited.contains(&neighbor) {
                         continue;
@@ -770,7 +1171,7 @@ impl QueryEngine {
         let is_public = node_props::is_public(node);
 
         // Check if deprecated
-        let is_deprecated = node.properties.get_bool("deprecated").unwrap_or(false);
+        let is_deprecated = node_props::is_deprecated(node);
 
         // Collect dependencies (outgoing import edges)
         let mut dependencies = Vec::new();
@@ -1305,6 +1706,7 @@ impl QueryEngine {
         let end_column = node_props::col_end_from_props(&node.properties);
 
         let file = node_props::path(node).to_string();
+        let root = self.root_label(&file);
 
         let location = SymbolLocation {
             file,
@@ -1312,6 +1714,7 @@ impl QueryEngine {
             column,
             end_line,
             end_column,
+            root,
         };
 
         // In compact mode, omit signature and docstring
@@ -1338,6 +1741,8 @@ impl QueryEngine {
             .unwrap_or(if is_public { "public" } else { "private" })
             .to_string();
 
+        let moniker = node.properties.get_string("moniker").map(String::from);
+
         Some(SymbolInfo {
             name,
             kind,
@@ -1346,6 +1751,7 @@ impl QueryEngine {
             docstring,
             is_public,
             visibility,
+            moniker,
         })
     }
 
@@ -1393,67 +1799,22 @@ impl QueryEngine {
             }
         }
 
+        // Index order depends on graph iteration order; keep results stable for paging
+        results.sort_by_key(|c| (c.depth, c.node_id));
         results
     }
 
-    fn detect_entry_type(&self, node: &codegraph::Node, name: &str) -> Option<EntryType> {
-        let name_lower = name.to_lowercase();
-
-        // Check for HTTP handlers
-        if node.properties.get_string("route").is_some()
-            || node.properties.get_string("http_method").is_some()
-        {
-            return Some(EntryType::HttpHandler);
-        }
-
-        // Check for main function
-        if name == "main" || name == "__main__" {
-            return Some(EntryType::Main);
-        }
-
-        // Check for test functions (by name, property, or file path)
-        if name_lower.starts_with("test_")
-            || name_lower.ends_with("_test")
-            || name.starts_with("Test")
-            || node.properties.get_bool("is_test").unwrap_or(false)
-        {
-            return Some(EntryType::TestEntry);
-        }
-        // Check if function lives in a test file (path-based detection)
-        let path = node_props::path(node);
-        if path.contains("/test/")
-            || path.contains("/tests/")
-            || path.contains("/__tests__/")
-            || path.contains(".test.")
-            || path.contains(".spec.")
-            || path.contains("_test.")
-        {
-            return Some(EntryType::TestEntry);
-        }
-
-        // Check for CLI commands
-        if name_lower.contains("command")
-            || name_lower.contains("cli")
-            || node.properties.get_bool("is_cli").unwrap_or(false)
-        {
-            return Some(EntryType::CliCommand);
-        }
-
-        // Check for event handlers
-        if name_lower.starts_with("on_")
-            || name_lower.starts_with("handle_")
-            || name_lower.ends_with("_handler")
-            || name_lower.ends_with("_callback")
-        {
-            return Some(EntryType::EventHandler);
-        }
-
-        // Check for public API (exported functions)
-        if node_props::is_public(node) {
-            return Some(EntryType::PublicApi);
-        }
-
-        None
+    /// Classify a function as an entry point using the configured rules.
+    pub(crate) fn detect_entry_type(
+        &self,
+        node: &codegraph::Node,
+        name: &str,
+    ) -> Option<EntryType> {
+        let rules = match self.entry_rules.read() {
+            Ok(guard) => Arc::clone(&guard),
+            Err(_) => return None,
+        };
+        rules.detect(node, name)
     }
 
     /// Find duplicate/similar functions across the codebase.
@@ -1619,6 +1980,232 @@ impl QueryEngine {
         }
     }
 
+    /// Find reads of environment variables and config keys matching `key`.
+    ///
+    /// `key` is a glob (e.g. "DATABASE_*"). Source files whose modification time
+    /// changed since the last query are rescanned first. Each usage is attributed to
+    /// the innermost function or class whose line range contains it.
+    pub async fn find_config_usages(
+        &self,
+        key: &str,
+        source: Option<ConfigSource>,
+        limit: usize,
+    ) -> Result<ConfigUsageResult, String> {
+        let start = Instant::now();
+        let matcher = globset::Glob::new(key)
+            .map_err(|e| format!("Invalid key pattern '{key}': {e}"))?
+            .compile_matcher();
+
+        let paths: Vec<String> = {
+            let graph = self.graph.read().await;
+            graph
+                .iter_nodes()
+                .filter(|(_, node)| node.node_type == NodeType::CodeFile)
+                .map(|(_, node)| node_props::path(node).to_string())
+                .filter(|path| !path.is_empty())
+                .collect()
+        };
+
+        let (files_scanned, mut hits) = {
+            let mut index = self.config_index.write().await;
+            let files_scanned = index.refresh(&paths);
+            let hits: Vec<(String, super::config_usage::ConfigReference)> = index
+                .references()
+                .filter(|(_, r)| source.is_none_or(|s| s == r.source))
+                .filter(|(_, r)| matcher.is_match(&r.key))
+                .map(|(file, r)| (file.to_string(), r.clone()))
+                .collect();
+            (files_scanned, hits)
+        };
+        hits.sort_by(|a, b| (&a.0, a.1.line, a.1.column).cmp(&(&b.0, b.1.line, b.1.column)));
+
+        let total_usages = hits.len();
+        let mut keys: Vec<String> = hits.iter().map(|(_, r)| r.key.clone()).collect();
+        keys.sort();
+        keys.dedup();
+        hits.truncate(limit);
+
+        let graph = self.graph.read().await;
+        let hit_files: HashSet<&str> = hits.iter().map(|(file, _)| file.as_str()).collect();
+        let scopes = symbol_scopes(&graph, &hit_files);
+
+        let usages = hits
+            .iter()
+            .map(|(file, r)| {
+                let node_id = innermost_scope(&scopes, file, r.line);
+                ConfigUsage {
+                    key: r.key.clone(),
+                    source: r.source,
+                    accessor: r.accessor.to_string(),
+                    file: file.clone(),
+                    line: r.line,
+                    column: r.column,
+                    node_id,
+                    symbol: node_id.and_then(|id| self.node_to_symbol_info(&graph, id)),
+                }
+            })
+            .collect();
+
+        Ok(ConfigUsageResult {
+            key: key.to_string(),
+            usages,
+            keys,
+            total_usages,
+            files_scanned,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Find TODO/FIXME/HACK/SAFETY comments, optionally restricted to some tags
+    /// and to files whose path contains `path_filter`.
+    ///
+    /// Reads the comment nodes recorded at parse time, each with the innermost
+    /// function or class it is linked to. Results are ordered by file and
+    /// line; authorship filters are applied by callers that have access to git.
+    pub async fn find_todos(&self, tags: &[TodoTag], path_filter: Option<&str>) -> TodoResult {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+
+        let mut todos: Vec<TodoComment> = graph
+            .iter_nodes()
+            .filter_map(|(node_id, node)| {
+                let todo = super::todo_index::todo_of_node(node)?;
+                let file = node_props::path(node);
+                if !tags.is_empty() && !tags.contains(&todo.tag) {
+                    return None;
+                }
+                if path_filter.is_some_and(|filter| !file.contains(filter)) {
+                    return None;
+                }
+                let symbol_id = super::todo_index::enclosing_symbol(&graph, node_id);
+                Some(TodoComment {
+                    tag: todo.tag,
+                    text: todo.text,
+                    owner: todo.owner,
+                    file: file.to_string(),
+                    line: todo.line,
+                    column: todo.column,
+                    node_id: symbol_id,
+                    symbol: symbol_id.and_then(|id| self.node_to_symbol_info(&graph, id)),
+                })
+            })
+            .collect();
+        todos.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+        TodoResult {
+            todos,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Find functions similar to a given function.
+    ///
+    /// Semantic mode ranks by embedding cosine similarity. Structural mode compares
+    /// normalized token shingles of the function bodies: identifiers and literals are
+    /// abstracted away, so renamed-variable copy-paste variants score near 1.0 without
+    /// an embedding model. `Auto` uses embeddings when the target has one.
+    pub async fn find_similar_code(
+        &self,
+        target: NodeId,
+        mode: SimilarityMode,
+        min_similarity: Option<f32>,
+        limit: usize,
+    ) -> Result<SimilarCodeResult, String> {
+        let start = Instant::now();
+        let symbol_vecs = self.symbol_vectors.read().await;
+        let graph = self.graph.read().await;
+
+        let target_node = graph
+            .get_node(target)
+            .map_err(|_| "Symbol not found".to_string())?;
+        if target_node.node_type != NodeType::Function {
+            return Err(format!(
+                "'{}' is not a function",
+                node_props::name(target_node)
+            ));
+        }
+        let target_symbol = self
+            .node_to_symbol_info(&graph, target)
+            .ok_or_else(|| "Symbol not found".to_string())?;
+
+        let semantic = match mode {
+            SimilarityMode::Semantic => true,
+            SimilarityMode::Structural => false,
+            SimilarityMode::Auto => symbol_vecs.contains_key(&target),
+        };
+
+        let candidates = graph
+            .iter_nodes()
+            .filter(|(id, node)| *id != target && node.node_type == NodeType::Function);
+
+        let mut scored: Vec<(NodeId, f32)> = Vec::new();
+        let mut total_compared = 0;
+        if semantic {
+            let target_vec = symbol_vecs.get(&target).ok_or_else(|| {
+                "No embedding for this function (embeddings may still be building); \
+                 use mode \"structural\""
+                    .to_string()
+            })?;
+            let threshold = min_similarity.unwrap_or(SEMANTIC_MIN_SIMILARITY);
+            for (id, _) in candidates {
+                if let Some(vec) = symbol_vecs.get(&id) {
+                    total_compared += 1;
+                    let sim = cosine_similarity(target_vec, vec);
+                    if sim >= threshold {
+                        scored.push((id, sim));
+                    }
+                }
+            }
+        } else {
+            let mut files: HashMap<String, Option<String>> = HashMap::new();
+            let target_shingles = cached_symbol_source(target_node, &mut files)
+                .map(|source| code_shingles(&normalize_code_tokens(&source)))
+                .filter(|shingles| !shingles.is_empty())
+                .ok_or_else(|| "Source not available for this function".to_string())?;
+            let threshold = min_similarity.unwrap_or(STRUCTURAL_MIN_SIMILARITY);
+            for (id, node) in candidates {
+                let Some(source) = cached_symbol_source(node, &mut files) else {
+                    continue;
+                };
+                let tokens = normalize_code_tokens(&source);
+                if tokens.len() < STRUCTURAL_MIN_TOKENS {
+                    continue;
+                }
+                total_compared += 1;
+                let sim = jaccard_similarity(&target_shingles, &code_shingles(&tokens));
+                if sim >= threshold {
+                    scored.push((id, sim));
+                }
+            }
+        }
+
+        scored.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        scored.truncate(limit);
+
+        let matches = scored
+            .into_iter()
+            .filter_map(|(node_id, similarity)| {
+                Some(SimilarCode {
+                    node_id,
+                    symbol: self.node_to_symbol_info(&graph, node_id)?,
+                    similarity,
+                })
+            })
+            .collect();
+
+        Ok(SimilarCodeResult {
+            target: target_symbol,
+            mode: if semantic {
+                SimilarityMode::Semantic
+            } else {
+                SimilarityMode::Structural
+            },
+            matches,
+            total_compared,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
     /// Cluster functions into semantic groups using greedy clustering.
     ///
     /// Picks an unassigned function, finds all functions within `threshold`
@@ -1750,14 +2337,8 @@ impl QueryEngine {
         let lang_a = path_a.rsplit('.').next().unwrap_or("");
         let lang_b = path_b.rsplit('.').next().unwrap_or("");
 
-        let complexity_a = node_a
-            .properties
-            .get_int("complexity_cyclomatic")
-            .unwrap_or(0) as u32;
-        let complexity_b = node_b
-            .properties
-            .get_int("complexity_cyclomatic")
-            .unwrap_or(0) as u32;
+        let complexity_a = node_a.properties.get_int("complexity").unwrap_or(0) as u32;
+        let complexity_b = node_b.properties.get_int("complexity").unwrap_or(0) as u32;
 
         let structural = StructuralComparison {
             same_file: path_a == path_b,
@@ -1834,579 +2415,3105 @@ impl QueryEngine {
             shared_callees,
         })
     }
-}
 
-/// Common boilerplate function names across all supported languages.
-/// These produce false positive duplicates/clusters because they share identical
-/// signatures despite being unrelated implementations.
-const BOILERPLATE_NAMES: &[&str] = &[
-    // Rust traits
-    "default", "new", "from", "fmt", "clone", "drop", "deref", "eq",
-    "hash", "into", "try_from", "try_into", "serialize", "deserialize",
-    // Java/Kotlin/C#
-    "tostring", "hashcode", "equals", "compareto", "getclass", "finalize",
-    "dispose", "close", "gettype", "gethashcode",
-    // Python
-    "__init__", "__str__", "__repr__", "__eq__", "__hash__", "__len__",
-    "__iter__", "__next__", "__enter__", "__exit__", "__del__",
-    // JavaScript/TypeScript
-    "constructor", "tostring", "valueof", "tolocalestring", "tojson",
-    // C/C++
-    "main", "init", "destroy", "free", "malloc", "realloc",
-    // Go
-    "string", "error", "len", "close",
-    // PHP
-    "__construct", "__destruct", "__tostring", "__clone", "__get", "__set",
-    // Ruby
-    "initialize", "to_s", "to_str", "inspect", "hash", "eql?",
-    // Swift
-    "init", "deinit", "description",
-    // Common getters/setters (all languages)
+    /// Detect dependency cycles using strongly connected components.
+    ///
+    /// Only edges whose type name matches one of `edge_types` (case-insensitive,
+    /// typically Imports and Calls) participate. At `CycleLevel::File`, symbols are collapsed into
+    /// their containing file so results describe module-level cycles; edges
+    /// within a single file are ignored. Components smaller than `min_size`
+    /// are dropped, and a single symbol only counts when it calls itself.
+    pub async fn find_cycles(
+        &self,
+        edge_types: &[String],
+        level: CycleLevel,
+        min_size: usize,
+        limit: usize,
+    ) -> CycleResult {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+
+        // Map graph nodes onto dense vertex indices for the chosen level
+        let vertex_key = |node_id: NodeId| -> Option<String> {
+            match level {
+                CycleLevel::Symbol => Some(node_id.to_string()),
+                CycleLevel::File => {
+                    let node = graph.get_node(node_id).ok()?;
+                    let path = node_props::path(node);
+                    (!path.is_empty()).then(|| path.to_string())
+                }
+            }
+        };
+
+        let mut index_of: HashMap<String, usize> = HashMap::new();
+        let mut vertices: Vec<NodeId> = Vec::new();
+        let mut adjacency: Vec<Vec<usize>> = Vec::new();
+        let mut edge_kinds: HashMap<(usize, usize), HashSet<String>> = HashMap::new();
+
+        for (_eid, edge) in graph.iter_edges() {
+            let edge_type = edge.edge_type.to_string();
+            if !edge_types
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(&edge_type))
+            {
+                continue;
+            }
+            let (Some(src_key), Some(dst_key)) =
+                (vertex_key(edge.source_id), vertex_key(edge.target_id))
+            else {
+                continue;
+            };
+            if level == CycleLevel::File && src_key == dst_key {
+                continue;
+            }
+
+            let src = *index_of.entry(src_key).or_insert_with(|| {
+                vertices.push(edge.source_id);
+                vertices.len() - 1
+            });
+            let dst = *index_of.entry(dst_key).or_insert_with(|| {
+                vertices.push(edge.target_id);
+                vertices.len() - 1
+            });
+            if adjacency.len() < vertices.len() {
+                adjacency.resize_with(vertices.len(), Vec::new);
+            }
+
+            let kinds = edge_kinds.entry((src, dst)).or_default();
+            if kinds.is_empty() {
+                adjacency[src].push(dst);
+            }
+            kinds.insert(edge_type);
+        }
+
+        let mut cycles: Vec<DependencyCycle> = Vec::new();
+        for component in strongly_connected_components(&adjacency) {
+            let is_cycle = component.len() > 1 || adjacency[component[0]].contains(&component[0]);
+            if !is_cycle || component.len() < min_size {
+                continue;
+            }
+
+            // Edge types that connect members of this component
+            let in_component: HashSet<usize> = component.iter().copied().collect();
+            let mut kinds: Vec<String> = component
+                .iter()
+                .flat_map(|&v| adjacency[v].iter().map(move |&w| (v, w)))
+                .filter(|(_, w)| in_component.contains(w))
+                .filter_map(|key| edge_kinds.get(&key))
+                .flatten()
+                .cloned()
+                .collect::<HashSet<_>>()
+                .into_iter()
+                .collect();
+            kinds.sort();
+
+            let mut members: Vec<CycleMember> = component
+                .iter()
+                .filter_map(|&v| {
+                    let node_id = vertices[v];
+                    let node = graph.get_node(node_id).ok()?;
+                    let path = node_props::path(node).to_string();
+                    match level {
+                        CycleLevel::Symbol => Some(CycleMember {
+                            node_id: Some(node_id),
+                            name: node_props::name(node).to_string(),
+                            file: path,
+                            line: node_props::line_start(node),
+                        }),
+                        CycleLevel::File => {
+                            let file_node = codegraph::helpers::find_file_by_path(&graph, &path)
+                                .ok()
+                                .flatten();
+                            let name = std::path::Path::new(&path)
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_else(|| path.clone());
+                            Some(CycleMember {
+                                node_id: file_node,
+                                name,
+                                file: path,
+                                line: 1,
+                            })
+                        }
+                    }
+                })
+                .collect();
+            members.sort_by(|a, b| a.file.cmp(&b.file).then(a.line.cmp(&b.line)));
+
+            let mut files: Vec<String> = members.iter().map(|m| m.file.clone()).collect();
+            files.dedup();
+
+            cycles.push(DependencyCycle {
+                size: members.len(),
+                members,
+                files,
+                edge_types: kinds,
+            });
+        }
+
+        // Largest cycles first; they are usually the most costly to untangle
+        cycles.sort_by(|a, b| {
+            b.size
+                .cmp(&a.size)
+                .then_with(|| a.files.first().cmp(&b.files.first()))
+        });
+
+        let total_cycles = cycles.len();
+        let members_in_cycles = cycles.iter().map(|c| c.size).sum();
+        cycles.truncate(limit);
+
+        CycleResult {
+            cycles,
+            total_cycles,
+            members_in_cycles,
+            level,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Rank symbols by structural importance in the call graph.
+    ///
+    /// Scores are computed once per `build_indexes` and normalized to 0.0-1.0,
+    /// so this is only a sort over cached values. `uri_filter` restricts
+    /// results to symbols whose path contains the given string.
+    pub async fn get_hot_symbols(
+        &self,
+        metric: CentralityMetric,
+        limit: usize,
+        uri_filter: Option<&str>,
+    ) -> HotSymbolsResult {
+        let start = Instant::now();
+        let centrality = self.centrality.read().await;
+        let graph = self.graph.read().await;
+        let caller_index = self.caller_index.read().await;
+        let callee_index = self.callee_index.read().await;
+
+        let mut ranked: Vec<(NodeId, CentralityScores)> = centrality
+            .iter()
+            .map(|(id, scores)| (*id, *scores))
+            .collect();
+        ranked.sort_by(|a, b| {
+            b.1.score(metric)
+                .partial_cmp(&a.1.score(metric))
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+
+        let mut symbols = Vec::new();
+        for (node_id, scores) in ranked {
+            if symbols.len() >= limit {
+                break;
+            }
+            if let Some(filter) = uri_filter {
+                let Ok(node) = graph.get_node(node_id) else {
+                    continue;
+                };
+                if !node_props::path(node).contains(filter) {
+                    continue;
+                }
+            }
+            let Some(symbol) = self.node_to_symbol_info(&graph, node_id) else {
+                continue;
+            };
+            symbols.push(HotSymbol {
+                node_id,
+                symbol,
+                score: scores.score(metric),
+                pagerank: scores.pagerank,
+                betweenness: scores.betweenness,
+                caller_count: caller_index.get(&node_id).map_or(0, |v| v.len()),
+                callee_count: callee_index.get(&node_id).map_or(0, |v| v.len()),
+            });
+        }
+
+        HotSymbolsResult {
+            symbols,
+            metric,
+            total_ranked: centrality.len(),
+            query_time_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Rank functions by change risk: churn of their file combined with call
+    /// graph centrality.
+    ///
+    /// `file_churn` maps absolute file paths to the number of commits that touched
+    /// them; a symbol whose file is missing takes the churn of its directory,
+    /// so the map may hold directories instead. Churn is log-scaled against the most changed file and combined with
+    /// the cached centrality as a geometric mean, so only symbols that are both
+    /// frequently changed and structurally central rank high.
+    pub async fn get_risk_hotspots(
+        &self,
+        file_churn: &HashMap<String, usize>,
+        limit: usize,
+        uri_filter: Option<&str>,
+    ) -> RiskHotspotsResult {
+        let start = Instant::now();
+        let centrality = self.centrality.read().await;
+        let graph = self.graph.read().await;
+        let caller_index = self.caller_index.read().await;
+
+        let max_churn = file_churn.values().copied().max().unwrap_or(0);
+        let churn_scale = ((max_churn + 1) as f32).ln();
+
+        let mut ranked: Vec<(NodeId, usize, f32, f32, f32)> = Vec::new();
+        for (&node_id, scores) in centrality.iter() {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            let path = node_props::path(node);
+            if uri_filter.is_some_and(|filter| !path.contains(filter)) {
+                continue;
+            }
+            let churn = file_churn
+                .get(path)
+                .or_else(|| {
+                    let dir = Path::new(path).parent()?;
+                    file_churn.get(dir.to_str()?)
+                })
+                .copied()
+                .unwrap_or(0);
+            if churn == 0 {
+                continue;
+            }
+            let churn_score = ((churn + 1) as f32).ln() / churn_scale;
+            let centrality_score = scores.score(CentralityMetric::Combined);
+            let risk = (churn_score * centrality_score).sqrt();
+            ranked.push((node_id, churn, churn_score, centrality_score, risk));
+        }
+        ranked.sort_by(|a, b| {
+            b.4.partial_cmp(&a.4)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.cmp(&b.0))
+        });
+
+        let total_ranked = ranked.len();
+        let hotspots = ranked
+            .into_iter()
+            .filter_map(|(node_id, churn, churn_score, centrality, risk)| {
+                Some(RiskHotspot {
+                    node_id,
+                    symbol: self.node_to_symbol_info(&graph, node_id)?,
+                    risk,
+                    churn,
+                    churn_score,
+                    centrality,
+                    caller_count: caller_index.get(&node_id).map_or(0, |v| v.len()),
+                })
+            })
+            .take(limit)
+            .collect();
+
+        RiskHotspotsResult {
+            hotspots,
+            files_with_churn: file_churn.len(),
+            total_ranked,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        }
+    }
+
+    /// Preview the impact of renaming a symbol to `new_name`.
+    ///
+    /// Reports every graph reference to the symbol, existing symbols that
+    /// already use `new_name` in an enclosing scope (containing class or module,
+    /// the defining file) or in a file that references the symbol, and the
+    /// `memories` that link to the symbol or mention its current name.
+    pub async fn preview_rename(
+        &self,
+        node_id: NodeId,
+        new_name: &str,
+        memories: &[MemoryNode],
+    ) -> Result<RenamePreview, String> {
+        let start = Instant::now();
+        let new_name = new_name.trim();
+        if !is_identifier(new_name) {
+            return Err(format!("'{new_name}' is not a valid identifier"));
+        }
+
+        let graph = self.graph.read().await;
+        let node = graph
+            .get_node(node_id)
+            .map_err(|_| format!("Node {node_id} not found"))?;
+        let old_name = node_props::name(node).to_string();
+        if old_name == new_name {
+            return Err(format!("Symbol is already named '{new_name}'"));
+        }
+        let symbol = self
+            .node_to_symbol_info(&graph, node_id)
+            .ok_or_else(|| format!("Node {node_id} is not a named symbol"))?;
+        let def_file = node_props::path(node).to_string();
+
+        let mut references = Vec::new();
+        let referrers = graph
+            .get_neighbors(node_id, Direction::Incoming)
+            .unwrap_or_default();
+        for referrer in referrers {
+            if referrer == node_id {
+                continue;
+            }
+            let (Ok(ref_node), Some(ref_symbol)) = (
+                graph.get_node(referrer),
+                self.node_to_symbol_info(&graph, referrer),
+            ) else {
+                continue;
+            };
+            let edges = graph
+                .get_edges_between(referrer, node_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|eid| graph.get_edge(eid).ok());
+            for edge in edges {
+                if edge.edge_type == EdgeType::Contains {
+                    continue;
+                }
+                let line = edge
+                    .properties
+                    .get_int("line")
+                    .map(|l| l as u32)
+                    .unwrap_or_else(|| node_props::line_start(ref_node))
+                    .max(1);
+                references.push(RenameReference {
+                    node_id: referrer,
+                    symbol: ref_symbol.clone(),
+                    reference_kind: edge.edge_type.to_string(),
+                    file: node_props::path(ref_node).to_string(),
+                    line,
+                });
+            }
+        }
+        references.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+
+        let mut affected_files: Vec<String> = references.iter().map(|r| r.file.clone()).collect();
+        affected_files.push(def_file.clone());
+        affected_files.sort();
+        affected_files.dedup();
+
+        let mut collisions = Vec::new();
+        let mut seen: HashSet<NodeId> = HashSet::from([node_id]);
+        let mut push_collision = |id: NodeId, scope: String, in_referencing_file: bool| {
+            if !seen.insert(id) {
+                return;
+            }
+            if let Some(symbol) = self.node_to_symbol_info(&graph, id) {
+                collisions.push(RenameCollision {
+                    node_id: id,
+                    symbol,
+                    scope,
+                    in_referencing_file,
+                });
+            }
+        };
+
+        // Members of the containing class/module, innermost first
+        let containers = graph
+            .get_neighbors(node_id, Direction::Incoming)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|&c| Self::has_edge(&graph, c, node_id, EdgeType::Contains));
+        for container in containers {
+            let Ok(container_node) = graph.get_node(container) else {
+                continue;
+            };
+            if container_node.node_type == NodeType::CodeFile {
+                continue;
+            }
+            let scope = format!(
+                "{} {}",
+                container_node.node_type.to_string().to_lowercase(),
+                node_props::name(container_node)
+            );
+            let members = graph
+                .get_neighbors(container, Direction::Outgoing)
+                .unwrap_or_default();
+            for member in members {
+                let is_clash = graph
+                    .get_node(member)
+                    .is_ok_and(|m| node_props::name(m) == new_name)
+                    && Self::has_edge(&graph, container, member, EdgeType::Contains);
+                if is_clash {
+                    push_collision(member, scope.clone(), false);
+                }
+            }
+        }
+
+        // The defining file, then files that would have to refer to the new name
+        let mut clashes: Vec<(NodeId, String)> = graph
+            .iter_nodes()
+            .filter(|(_, n)| n.node_type != NodeType::CodeFile && node_props::name(n) == new_name)
+            .map(|(id, n)| (id, node_props::path(n).to_string()))
+            .filter(|(_, path)| affected_files.binary_search(path).is_ok())
+            .collect();
+        clashes.sort_by_key(|(id, path)| (path != &def_file, path.clone(), *id));
+        for (id, path) in clashes {
+            let in_referencing_file = path != def_file;
+            push_collision(id, format!("file {path}"), in_referencing_file);
+        }
+
+        let node_key = node_id.to_string();
+        let memories = memories
+            .iter()
+            .filter_map(|m| {
+                let linked = m.code_links.iter().any(|l| l.node_id == node_key);
+                let mentioned = mentions_identifier(&m.title, &old_name)
+                    || mentions_identifier(&m.content, &old_name);
+                (linked || mentioned).then(|| RenameMemory {
+                    id: m.id.to_string(),
+                    title: m.title.clone(),
+                    kind: m.kind.discriminant_name().to_string(),
+                    linked,
+                })
+            })
+            .collect();
+
+        Ok(RenamePreview {
+            node_id,
+            symbol,
+            old_name,
+            new_name: new_name.to_string(),
+            references,
+            collisions,
+            memories,
+            affected_files,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    fn has_edge(graph: &CodeGraph, from: NodeId, to: NodeId, edge_type: EdgeType) -> bool {
+        graph
+            .get_edges_between(from, to)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|eid| graph.get_edge(eid).ok())
+            .any(|e| e.edge_type == edge_type)
+    }
+
+    /// Find functions, classes and interfaces carrying a decorator or attribute.
+    ///
+    /// `annotation` is matched against the recorded `attributes` of each symbol
+    /// (see `annotations::annotation_matches`): `@app.route`, `tokio::test` and
+    /// `Deprecated` match by name, `@app.get("/users")` by prefix. Results are
+    /// ordered by file and line.
+    pub async fn find_by_annotation(
+        &self,
+        annotation: &str,
+        symbol_types: &[SymbolType],
+        limit: usize,
+    ) -> Result<AnnotationSearchResult, String> {
+        let start = Instant::now();
+        if crate::annotations::annotation_name(annotation).is_empty() {
+            return Err(format!("Invalid annotation '{annotation}'"));
+        }
+
+        let graph = self.graph.read().await;
+        let mut matches: Vec<(NodeId, Vec<String>)> = Vec::new();
+        for (node_id, node) in graph.iter_nodes() {
+            if !matches!(
+                node.node_type,
+                NodeType::Function | NodeType::Class | NodeType::Interface
+            ) {
+                continue;
+            }
+            if !symbol_types.is_empty()
+                && !symbol_types
+                    .iter()
+                    .any(|t| symbol_type_matches(*t, node.node_type))
+            {
+                continue;
+            }
+            let Some(attrs) = node.properties.get_string_list_compat("attributes") else {
+                continue;
+            };
+            let matching: Vec<String> = attrs
+                .into_iter()
+                .filter(|attr| crate::annotations::annotation_matches(attr, annotation))
+                .collect();
+            if !matching.is_empty() {
+                matches.push((node_id, matching));
+            }
+        }
+
+        matches.sort_by_cached_key(|(id, _)| {
+            graph
+                .get_node(*id)
+                .map(|n| (node_props::path(n).to_string(), node_props::line_start(n)))
+                .unwrap_or_default()
+        });
+        let total = matches.len();
+
+        let results = matches
+            .into_iter()
+            .filter_map(|(node_id, annotations)| {
+                Some(AnnotatedSymbol {
+                    node_id,
+                    symbol: self.node_to_symbol_info(&graph, node_id)?,
+                    annotations,
+                })
+            })
+            .take(limit)
+            .collect();
+
+        Ok(AnnotationSearchResult {
+            annotation: annotation.to_string(),
+            results,
+            total,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Run a pipeline query such as
+    /// `symbol_search("auth") | callers(depth=2) | filter(kind=Function) | limit(20)`.
+    ///
+    /// The first stage produces a working set of symbols that each following
+    /// stage expands or narrows (see `query_dsl` for the stages). Returns the
+    /// final symbols and the working set size after every stage, or an error
+    /// describing why the query could not be parsed.
+    pub async fn run_query(&self, query: &str) -> Result<PipelineResult, String> {
+        let start = Instant::now();
+        let stages = parse_pipeline(query)?;
+
+        let mut working: Vec<NodeId> = Vec::new();
+        let mut counts = Vec::with_capacity(stages.len());
+        let mut truncated = false;
+
+        for stage in &stages {
+            working = match stage {
+                PipelineStage::SymbolSearch { query, limit } => {
+                    let options = SearchOptions::new().with_limit(*limit).include_private();
+                    self.symbol_search(query, &options)
+                        .await
+                        .results
+                        .into_iter()
+                        .map(|m| m.node_id)
+                        .collect()
+                }
+                PipelineStage::EntryPoints { entry_types } => self
+                    .find_entry_points(entry_types)
+                    .await
+                    .into_iter()
+                    .map(|e| e.node_id)
+                    .collect(),
+                PipelineStage::HotSymbols { limit } => self
+                    .get_hot_symbols(CentralityMetric::Combined, *limit, None)
+                    .await
+                    .symbols
+                    .into_iter()
+                    .map(|s| s.node_id)
+                    .collect(),
+                PipelineStage::Callers { depth } | PipelineStage::Callees { depth } => {
+                    let index = match stage {
+                        PipelineStage::Callers { .. } => self.caller_index.read().await,
+                        _ => self.callee_index.read().await,
+                    };
+                    let graph = self.graph.read().await;
+                    let mut seen: HashSet<NodeId> = HashSet::new();
+                    let mut expanded = Vec::new();
+                    for &node_id in &working {
+                        for call in self.get_call_chain(&graph, &index, node_id, *depth) {
+                            if seen.insert(call.node_id) {
+                                expanded.push(call.node_id);
+                            }
+                        }
+                    }
+                    expanded
+                }
+                PipelineStage::Filter {
+                    kinds,
+                    name,
+                    file,
+                    public,
+                } => {
+                    let graph = self.graph.read().await;
+                    let name = name.as_deref().map(str::to_lowercase);
+                    working
+                        .into_iter()
+                        .filter(|&id| {
+                            let Ok(node) = graph.get_node(id) else {
+                                return false;
+                            };
+                            (kinds.is_empty()
+                                || kinds
+                                    .iter()
+                                    .any(|k| symbol_type_matches(*k, node.node_type)))
+                                && name.as_deref().map_or(true, |n| {
+                                    node_props::name(node).to_lowercase().contains(n)
+                                })
+                                && file
+                                    .as_deref()
+                                    .map_or(true, |f| node_props::path(node).contains(f))
+                                && public.map_or(true, |p| node_props::is_public(node) == p)
+                        })
+                        .collect()
+                }
+                PipelineStage::Limit(n) => {
+                    working.truncate(*n);
+                    working
+                }
+            };
+            if working.len() > MAX_WORKING_SET {
+                working.truncate(MAX_WORKING_SET);
+                truncated = true;
+            }
+            counts.push(PipelineStageCount {
+                stage: stage.name().to_string(),
+                count: working.len(),
+            });
+        }
+
+        let graph = self.graph.read().await;
+        let results = working
+            .into_iter()
+            .filter_map(|node_id| {
+                Some(PipelineMatch {
+                    node_id,
+                    symbol: self.node_to_symbol_info(&graph, node_id)?,
+                })
+            })
+            .collect();
+
+        Ok(PipelineResult {
+            query: query.to_string(),
+            results,
+            stages: counts,
+            truncated,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Build the symbol tree of one file: classes nesting their methods and
+    /// modules nesting their functions, each with its source range.
+    ///
+    /// A symbol is placed under the innermost symbol whose line range encloses
+    /// it, so nesting does not depend on parsers emitting Contains edges.
+    /// Returns None if no symbols are indexed for `path`.
+    pub async fn get_file_outline(&self, path: &str) -> Option<FileOutline> {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+
+        let mut entries: Vec<(NodeId, u32, u32)> = graph
+            .iter_nodes()
+            .filter(|(_, n)| {
+                !matches!(
+                    n.node_type,
+                    NodeType::CodeFile | NodeType::File | NodeType::Import
+                ) && node_props::path(n) == path
+            })
+            .map(|(id, n)| {
+                let line_start = node_props::line_start(n);
+                (id, line_start, node_props::line_end(n).max(line_start))
+            })
+            .collect();
+        if entries.is_empty() {
+            return None;
+        }
+        // Source order, with the outer symbol first when two start on the same line
+        entries.sort_by(|a, b| a.1.cmp(&b.1).then(b.2.cmp(&a.2)).then(a.0.cmp(&b.0)));
+
+        // Innermost enclosing entry of each entry, using a stack of open ranges
+        let mut parents: Vec<Option<usize>> = Vec::with_capacity(entries.len());
+        let mut open: Vec<usize> = Vec::new();
+        for &(_, _, line_end) in &entries {
+            while open.last().is_some_and(|&top| entries[top].2 < line_end) {
+                open.pop();
+            }
+            parents.push(open.last().copied());
+            open.push(parents.len() - 1);
+        }
+
+        // Children always follow their parent, so attach them back to front
+        let mut built: Vec<Option<OutlineNode>> = entries
+            .iter()
+            .map(|&(node_id, ..)| {
+                Some(OutlineNode {
+                    node_id,
+                    symbol: self.node_to_symbol_info(&graph, node_id)?,
+                    children: Vec::new(),
+                })
+            })
+            .collect();
+        let total_symbols = built.iter().flatten().count();
+        let mut symbols = Vec::new();
+        for idx in (0..entries.len()).rev() {
+            let Some(mut node) = built[idx].take() else {
+                continue;
+            };
+            node.children.reverse();
+            match parents[idx].and_then(|parent| built[parent].as_mut()) {
+                Some(parent) => parent.children.push(node),
+                None => symbols.push(node),
+            }
+        }
+        symbols.reverse();
+
+        Some(FileOutline {
+            file: path.to_string(),
+            symbols,
+            total_symbols,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Find all symbols that reference a type/class node.
+    ///
+    /// Follows incoming References, Uses and Instantiates edges rather than
+    /// matching text. When the referencing node is a field or variable, the
+    /// symbol that contains it is reported as well, with `via` naming the
+    /// field. Returns None if the type node does not exist.
+    pub async fn find_type_usages(
+        &self,
+        type_node: NodeId,
+        limit: usize,
+    ) -> Option<TypeUsageResult> {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+        let type_name = node_props::name(graph.get_node(type_node).ok()?).to_string();
+
+        let mut usages: Vec<TypeUsage> = Vec::new();
+        let mut seen: HashSet<(NodeId, Option<String>)> = HashSet::new();
+        let mut push_usage = |node_id: NodeId, usage_kind: &str, via: Option<String>| {
+            if !seen.insert((node_id, via.clone())) {
+                return;
+            }
+            if let Some(symbol) = self.node_to_symbol_info(&graph, node_id) {
+                usages.push(TypeUsage {
+                    node_id,
+                    symbol,
+                    usage_kind: usage_kind.to_string(),
+                    via,
+                });
+            }
+        };
+
+        let users = graph
+            .get_neighbors(type_node, Direction::Incoming)
+            .unwrap_or_default();
+        for user in users {
+            if user == type_node {
+                continue;
+            }
+            let Some(usage_kind) = Self::type_usage_edge(&graph, user, type_node) else {
+                continue;
+            };
+            let Ok(node) = graph.get_node(user) else {
+                continue;
+            };
+
+            push_usage(user, &usage_kind, None);
+
+            // Fields and variables: attribute the usage to their container
+            if node.node_type == NodeType::Variable {
+                let var_name = node_props::name(node).to_string();
+                let containers = graph
+                    .get_neighbors(user, Direction::Incoming)
+                    .unwrap_or_default();
+                for container in containers {
+                    let contains = graph
+                        .get_edges_between(container, user)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|eid| graph.get_edge(eid).ok())
+                        .any(|e| e.edge_type == EdgeType::Contains);
+                    if contains {
+                        push_usage(container, &usage_kind, Some(var_name.clone()));
+                    }
+                }
+            }
+        }
+
+        usages.sort_by(|a, b| {
+            a.symbol
+                .location
+                .file
+                .cmp(&b.symbol.location.file)
+                .then(a.symbol.location.line.cmp(&b.symbol.location.line))
+        });
+        let total_usages = usages.len();
+        usages.truncate(limit);
+
+        Some(TypeUsageResult {
+            type_name,
+            usages,
+            total_usages,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Return the typed usage edge (References/Uses/Instantiates) from `user` to `type_node`.
+    fn type_usage_edge(graph: &CodeGraph, user: NodeId, type_node: NodeId) -> Option<String> {
+        graph
+            .get_edges_between(user, type_node)
+            .ok()?
+            .into_iter()
+            .filter_map(|eid| graph.get_edge(eid).ok())
+            .find(|e| {
+                matches!(
+                    e.edge_type,
+                    EdgeType::References | EdgeType::Uses | EdgeType::Instantiates
+                ) && !node_props::is_co_change(e.edge_type, &e.properties)
+            })
+            .map(|e| e.edge_type.to_string())
+    }
+
+    /// Find implementations of a trait/interface (or subclasses of a base class),
+    /// or in the `Supertypes` direction, the interfaces and base types `type_node`
+    /// implements or extends. Follows direct Implements/Extends edges only.
+    pub async fn find_implementations(
+        &self,
+        type_node: NodeId,
+        direction: ImplementationDirection,
+        limit: usize,
+    ) -> Option<ImplementationResult> {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+        let target_name = node_props::name(graph.get_node(type_node).ok()?).to_string();
+
+        let graph_direction = match direction {
+            ImplementationDirection::Implementations => Direction::Incoming,
+            ImplementationDirection::Supertypes => Direction::Outgoing,
+        };
+        let neighbors = graph
+            .get_neighbors(type_node, graph_direction)
+            .unwrap_or_default();
+
+        let mut seen: HashSet<NodeId> = HashSet::new();
+        let mut implementations: Vec<Implementation> = Vec::new();
+        for neighbor in neighbors {
+            if neighbor == type_node || !seen.insert(neighbor) {
+                continue;
+            }
+            let (from, to) = match direction {
+                ImplementationDirection::Implementations => (neighbor, type_node),
+                ImplementationDirection::Supertypes => (type_node, neighbor),
+            };
+            let Some(relation) = Self::inheritance_edge(&graph, from, to) else {
+                continue;
+            };
+            if let Some(symbol) = self.node_to_symbol_info(&graph, neighbor) {
+                implementations.push(Implementation {
+                    node_id: neighbor,
+                    symbol,
+                    relation,
+                });
+            }
+        }
+
+        implementations.sort_by(|a, b| {
+            a.symbol
+                .location
+                .file
+                .cmp(&b.symbol.location.file)
+                .then(a.symbol.location.line.cmp(&b.symbol.location.line))
+        });
+        let total_implementations = implementations.len();
+        implementations.truncate(limit);
+
+        Some(ImplementationResult {
+            target_name,
+            direction,
+            implementations,
+            total_implementations,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Resolve a member of an interface, trait or base class to the members of
+    /// the same name in every type that implements or extends its container,
+    /// transitively.
+    ///
+    /// Returns None if `member` is not a function contained in a type.
+    pub async fn find_member_implementations(&self, member: NodeId) -> Option<Vec<NodeId>> {
+        let graph = self.graph.read().await;
+        let member_node = graph.get_node(member).ok()?;
+        if member_node.node_type != NodeType::Function {
+            return None;
+        }
+        let name = node_props::name(member_node).to_string();
+
+        let container = graph
+            .get_neighbors(member, Direction::Incoming)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|&c| {
+                Self::has_edge(&graph, c, member, EdgeType::Contains)
+                    && graph.get_node(c).is_ok_and(|n| {
+                        matches!(
+                            n.node_type,
+                            NodeType::Interface | NodeType::Trait | NodeType::Class
+                        )
+                    })
+            })?;
+
+        let mut implementations = Vec::new();
+        let mut visited: HashSet<NodeId> = HashSet::from([container]);
+        let mut queue: VecDeque<NodeId> = VecDeque::from([container]);
+        while let Some(type_node) = queue.pop_front() {
+            let subtypes = graph
+                .get_neighbors(type_node, Direction::Incoming)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&sub| Self::inheritance_edge(&graph, sub, type_node).is_some());
+            for subtype in subtypes {
+                if !visited.insert(subtype) {
+                    continue;
+                }
+                queue.push_back(subtype);
+                let overrides = graph
+                    .get_neighbors(subtype, Direction::Outgoing)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|&m| {
+                        graph.get_node(m).is_ok_and(|n| {
+                            n.node_type == NodeType::Function && node_props::name(n) == name
+                        }) && Self::has_edge(&graph, subtype, m, EdgeType::Contains)
+                    });
+                implementations.extend(overrides);
+            }
+        }
+
+        implementations.sort_by_cached_key(|&id| {
+            graph.get_node(id).map_or((String::new(), 0), |n| {
+                (node_props::path(n).to_string(), node_props::line_start(n))
+            })
+        });
+        implementations.dedup();
+        Some(implementations)
+    }
+
+    /// Declarations of the symbol defined at `definition`, as seen from `from_file`:
+    ///
+    /// - members of the same name in the interfaces, traits and base classes
+    ///   the containing type implements or extends, nearest first
+    /// - prototypes in C/C++ header files
+    /// - import or re-export statements through which `from_file` brings the
+    ///   symbol in
+    ///
+    /// The definition itself is never included; an empty list means the
+    /// definition is the only declaration.
+    pub async fn find_declarations(&self, definition: NodeId, from_file: &str) -> Vec<NodeId> {
+        let graph = self.graph.read().await;
+        let Ok(def_node) = graph.get_node(definition) else {
+            return Vec::new();
+        };
+        let name = node_props::name(def_node).to_string();
+        let def_path = node_props::path(def_node).to_string();
+        let mut declarations = Vec::new();
+
+        // Overridden members, walking up Implements/Extends edges
+        if def_node.node_type == NodeType::Function {
+            let container = graph
+                .get_neighbors(definition, Direction::Incoming)
+                .unwrap_or_default()
+                .into_iter()
+                .find(|&c| Self::has_edge(&graph, c, definition, EdgeType::Contains));
+            let mut visited: HashSet<NodeId> = container.into_iter().collect();
+            let mut queue: VecDeque<NodeId> = visited.iter().copied().collect();
+            while let Some(type_node) = queue.pop_front() {
+                let supertypes = graph
+                    .get_neighbors(type_node, Direction::Outgoing)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|&sup| Self::inheritance_edge(&graph, type_node, sup).is_some());
+                for supertype in supertypes {
+                    if !visited.insert(supertype) {
+                        continue;
+                    }
+                    queue.push_back(supertype);
+                    declarations.extend(
+                        graph
+                            .get_neighbors(supertype, Direction::Outgoing)
+                            .unwrap_or_default()
+                            .into_iter()
+                            .filter(|&m| {
+                                graph.get_node(m).is_ok_and(|n| {
+                                    n.node_type == NodeType::Function && node_props::name(n) == name
+                                }) && Self::has_edge(&graph, supertype, m, EdgeType::Contains)
+                            }),
+                    );
+                }
+            }
+        }
+
+        // Header prototypes of a C/C++ function, in headers its file includes
+        if def_node.node_type == NodeType::Function
+            && is_c_family_path(&def_path)
+            && !is_header_path(&def_path)
+        {
+            let included: HashSet<String> = graph
+                .query()
+                .node_type(NodeType::CodeFile)
+                .property("path", def_path.as_str())
+                .execute()
+                .unwrap_or_default()
+                .into_iter()
+                .flat_map(|file| {
+                    graph
+                        .get_neighbors(file, Direction::Outgoing)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|&header| Self::has_edge(&graph, file, header, EdgeType::Imports))
+                        .collect::<Vec<_>>()
+                })
+                .filter_map(|header| {
+                    let node = graph.get_node(header).ok()?;
+                    (node.node_type == NodeType::CodeFile)
+                        .then(|| node_props::path(node).to_string())
+                })
+                .collect();
+            let mut prototypes: Vec<(String, NodeId)> = graph
+                .query()
+                .node_type(NodeType::Function)
+                .property("name", name.as_str())
+                .execute()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&id| id != definition)
+                .filter_map(|id| {
+                    let path = node_props::path(graph.get_node(id).ok()?).to_string();
+                    let linked = Self::has_edge(&graph, definition, id, EdgeType::Implements);
+                    (is_header_path(&path) && (linked || included.contains(&path)))
+                        .then_some((path, id))
+                })
+                .collect();
+            prototypes.sort();
+            declarations.extend(prototypes.into_iter().map(|(_, id)| id));
+        }
+
+        // Import and re-export statements in the requesting file
+        let files = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .property("path", from_file)
+            .execute()
+            .unwrap_or_default();
+        for file in files {
+            for target in graph
+                .get_neighbors(file, Direction::Outgoing)
+                .unwrap_or_default()
+            {
+                let is_statement = target != definition
+                    && graph.get_node(target).is_ok_and(|n| {
+                        matches!(n.node_type, NodeType::Import | NodeType::Module)
+                            && node_props::path(n) != def_path
+                    });
+                if is_statement && Self::imports_symbol(&graph, file, target, &name) {
+                    declarations.push(target);
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        declarations.retain(|id| seen.insert(*id));
+        declarations
+    }
+
+    /// Whether an Imports edge from `from` to `to` lists `symbol`.
+    fn imports_symbol(graph: &CodeGraph, from: NodeId, to: NodeId, symbol: &str) -> bool {
+        graph
+            .get_edges_between(from, to)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|eid| graph.get_edge(eid).ok())
+            .any(|e| {
+                e.edge_type == EdgeType::Imports
+                    && e.properties
+                        .get_string_list_compat("symbols")
+                        .is_some_and(|symbols| symbols.iter().any(|s| s == symbol))
+            })
+    }
+
+    /// Walk Extends/Implements edges transitively from `type_node`.
+    ///
+    /// Diamond inheritance reports each type once, at its shallowest depth.
+    /// Edges that lead back to an ancestor are cut and flagged with `has_cycle`.
+    pub async fn get_type_hierarchy(
+        &self,
+        type_node: NodeId,
+        direction: TypeHierarchyDirection,
+        max_depth: Option<u32>,
+    ) -> Option<TypeHierarchyResult> {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+        let root = self.node_to_symbol_info(&graph, type_node)?;
+        let depth_limit = max_depth.unwrap_or(u32::MAX);
+
+        let graph_direction = match direction {
+            TypeHierarchyDirection::Subtypes => Direction::Incoming,
+            TypeHierarchyDirection::Supertypes => Direction::Outgoing,
+        };
+
+        let mut parents: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut visited: HashSet<NodeId> = HashSet::from([type_node]);
+        let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(type_node, 0)]);
+        let mut nodes: Vec<TypeHierarchyNode> = Vec::new();
+        let mut has_cycle = false;
+        let mut truncated = false;
+
+        while let Some((current, depth)) = queue.pop_front() {
+            let neighbors = graph
+                .get_neighbors(current, graph_direction)
+                .unwrap_or_default();
+            for neighbor in neighbors {
+                let (from, to) = match direction {
+                    TypeHierarchyDirection::Subtypes => (neighbor, current),
+                    TypeHierarchyDirection::Supertypes => (current, neighbor),
+                };
+                let Some(relation) = Self::inheritance_edge(&graph, from, to) else {
+                    continue;
+                };
+
+                if visited.contains(&neighbor) {
+                    // Revisiting an ancestor on this branch means a cycle; anything else is a diamond
+                    let mut ancestor = Some(current);
+                    while let Some(a) = ancestor {
+                        if a == neighbor {
+                            has_cycle = true;
+                            break;
+                        }
+                        ancestor = parents.get(&a).copied();
+                    }
+                    continue;
+                }
+
+                if depth + 1 > depth_limit || nodes.len() >= TYPE_HIERARCHY_MAX_NODES {
+                    truncated = true;
+                    continue;
+                }
+
+                let Some(symbol) = self.node_to_symbol_info(&graph, neighbor) else {
+                    continue;
+                };
+                visited.insert(neighbor);
+                parents.insert(neighbor, current);
+                nodes.push(TypeHierarchyNode {
+                    node_id: neighbor,
+                    symbol,
+                    depth: depth + 1,
+                    parent: current,
+                    relation,
+                });
+                queue.push_back((neighbor, depth + 1));
+            }
+        }
+
+        let max_depth = nodes.iter().map(|n| n.depth).max().unwrap_or(0);
+        Some(TypeHierarchyResult {
+            root,
+            direction,
+            nodes,
+            max_depth,
+            has_cycle,
+            truncated,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Find sink symbols transitively reachable from `from`.
+    ///
+    /// Walks outgoing edges of the predicate's edge types breadth-first. A visited
+    /// symbol is a sink if its name matches a sink glob; external library calls
+    /// (recorded as `unresolved_calls` on the caller) are matched the same way, so
+    /// e.g. `fs.readFile` is found even though it is not a graph node. When
+    /// `cancel` is cancelled the search stops and the result is truncated.
+    pub async fn reaches(
+        &self,
+        from: NodeId,
+        predicate: &ReachabilityPredicate,
+        cancel: &CancellationToken,
+    ) -> Result<ReachabilityResult, String> {
+        let start = Instant::now();
+
+        // Compile sink patterns, remembering which category each came from
+        let mut patterns: Vec<(String, Option<String>)> =
+            predicate.sinks.iter().map(|p| (p.clone(), None)).collect();
+        for category in &predicate.categories {
+            let sinks = sink_category(category).ok_or_else(|| {
+                let known: Vec<&str> = SINK_CATEGORIES.iter().map(|(name, _)| *name).collect();
+                format!(
+                    "Unknown sink category: {category}. Expected one of: {}",
+                    known.join(", ")
+                )
+            })?;
+            for pattern in sinks {
+                patterns.push((pattern.to_string(), Some(category.to_lowercase())));
+            }
+        }
+        if patterns.is_empty() {
+            return Err("No sinks given: provide sink patterns or categories".to_string());
+        }
+        let mut builder = globset::GlobSetBuilder::new();
+        for (pattern, _) in &patterns {
+            let glob = globset::Glob::new(pattern)
+                .map_err(|e| format!("Invalid sink pattern '{pattern}': {e}"))?;
+            builder.add(glob);
+        }
+        let sink_set = builder
+            .build()
+            .map_err(|e| format!("Invalid sink patterns: {e}"))?;
+
+        let graph = self.graph.read().await;
+        let from_symbol = self
+            .node_to_symbol_info(&graph, from)
+            .ok_or_else(|| format!("Symbol {from} not found"))?;
+
+        let mut matches: Vec<SinkMatch> = Vec::new();
+        let mut parents: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut visited: HashSet<NodeId> = HashSet::from([from]);
+        let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(from, 0)]);
+        let mut truncated = false;
+
+        let path_to = |node: NodeId, parents: &HashMap<NodeId, NodeId>| -> Vec<String> {
+            let mut chain = vec![node];
+            let mut current = node;
+            while let Some(&parent) = parents.get(&current) {
+                chain.push(parent);
+                current = parent;
+            }
+            chain
+                .iter()
+                .rev()
+                .filter_map(|id| graph.get_node(*id).ok())
+                .map(|n| node_props::name(n).to_string())
+                .collect()
+        };
+
+        'search: while let Some((current, depth)) = queue.pop_front() {
+            if cancel.is_cancelled() {
+                truncated = true;
+                break;
+            }
+            let Ok(node) = graph.get_node(current) else {
+                continue;
+            };
+
+            let mut candidates: Vec<(String, bool)> = Vec::new();
+            if current != from {
+                candidates.push((node_props::name(node).to_string(), false));
+            }
+            if let Some(external) = node.properties.get_string_list_compat("unresolved_calls") {
+                candidates.extend(external.into_iter().map(|name| (name, true)));
+            }
+
+            for (name, external) in candidates {
+                let Some(&index) = sink_set.matches(&name).first() else {
+                    continue;
+                };
+                if matches.len() >= predicate.max_matches {
+                    truncated = true;
+                    break 'search;
+                }
+                let Some(symbol) = self.node_to_symbol_info(&graph, current) else {
+                    continue;
+                };
+                let (pattern, category) = patterns[index].clone();
+                matches.push(SinkMatch {
+                    node_id: current,
+                    symbol,
+                    sink: name,
+                    pattern,
+                    category,
+                    external,
+                    depth,
+                    path: path_to(current, &parents),
+                });
+            }
+
+            let neighbors = graph
+                .get_neighbors(current, Direction::Outgoing)
+                .unwrap_or_default();
+            for neighbor in neighbors {
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                let follows = graph
+                    .get_edges_between(current, neighbor)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|eid| graph.get_edge(eid).ok())
+                    .any(|e| {
+                        let edge_type = e.edge_type.to_string();
+                        predicate
+                            .edge_types
+                            .iter()
+                            .any(|t| t.eq_ignore_ascii_case(&edge_type))
+                    });
+                if !follows {
+                    continue;
+                }
+                if depth >= predicate.max_depth {
+                    truncated = true;
+                    continue;
+                }
+                visited.insert(neighbor);
+                parents.insert(neighbor, current);
+                queue.push_back((neighbor, depth + 1));
+            }
+        }
+
+        Ok(ReachabilityResult {
+            from: from_symbol,
+            reachable: !matches.is_empty(),
+            matches,
+            nodes_visited: visited.len(),
+            truncated,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Find a shortest path from `from` to `to`, following edges of
+    /// `edge_types` (any type when empty) in their direction. The search is
+    /// breadth-first and gives up past `max_depth` edges or when `cancel` is
+    /// cancelled, marking the result truncated.
+    pub async fn find_path(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        edge_types: &[String],
+        max_depth: u32,
+        cancel: &CancellationToken,
+    ) -> Result<PathResult, String> {
+        let start = Instant::now();
+        let graph = self.graph.read().await;
+        let symbol = |id: NodeId| {
+            self.node_to_symbol_info(&graph, id)
+                .ok_or_else(|| format!("Symbol {id} not found"))
+        };
+        let from_symbol = symbol(from)?;
+        let to_symbol = symbol(to)?;
+
+        // Each visited node's parent, with the type of the edge between them
+        let mut parents: HashMap<NodeId, (NodeId, String)> = HashMap::new();
+        let mut visited: HashSet<NodeId> = HashSet::from([from]);
+        let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(from, 0)]);
+        let mut found = from == to;
+        let mut truncated = false;
+
+        while let Some((current, depth)) = queue.pop_front() {
+            if found {
+                break;
+            }
+            if cancel.is_cancelled() {
+                truncated = true;
+                break;
+            }
+            let neighbors = graph
+                .get_neighbors(current, Direction::Outgoing)
+                .unwrap_or_default();
+            for neighbor in neighbors {
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                let edge_type = graph
+                    .get_edges_between(current, neighbor)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|eid| graph.get_edge(eid).ok())
+                    .map(|e| e.edge_type.to_string())
+                    .find(|edge_type| {
+                        edge_types.is_empty()
+                            || edge_types.iter().any(|t| t.eq_ignore_ascii_case(edge_type))
+                    });
+                let Some(edge_type) = edge_type else {
+                    continue;
+                };
+                if depth >= max_depth {
+                    truncated = true;
+                    continue;
+                }
+                visited.insert(neighbor);
+                parents.insert(neighbor, (current, edge_type));
+                if neighbor == to {
+                    found = true;
+                    break;
+                }
+                queue.push_back((neighbor, depth + 1));
+            }
+        }
+
+        let mut path = Vec::new();
+        if found {
+            let mut current = to;
+            let mut steps = Vec::new();
+            while let Some((parent, edge_type)) = parents.get(&current) {
+                steps.push((current, Some(edge_type.clone())));
+                current = *parent;
+            }
+            steps.push((from, None));
+            path = steps
+                .into_iter()
+                .rev()
+                .filter_map(|(node_id, edge_type)| {
+                    Some(PathStep {
+                        node_id,
+                        symbol: self.node_to_symbol_info(&graph, node_id)?,
+                        edge_type,
+                    })
+                })
+                .collect();
+        }
+
+        Ok(PathResult {
+            from: from_symbol,
+            to: to_symbol,
+            found,
+            path,
+            nodes_visited: visited.len(),
+            truncated: truncated && !found,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Return the Implements/Extends edge type from `from` to `to`, if any.
+    fn inheritance_edge(graph: &CodeGraph, from: NodeId, to: NodeId) -> Option<String> {
+        graph
+            .get_edges_between(from, to)
+            .ok()?
+            .into_iter()
+            .filter_map(|eid| graph.get_edge(eid).ok())
+            .find(|e| matches!(e.edge_type, EdgeType::Implements | EdgeType::Extends))
+            .map(|e| e.edge_type.to_string())
+    }
+}
+
+/// Common boilerplate function names across all supported languages.
+/// These produce false positive duplicates/clusters because they share identical
+/// signatures despite being unrelated implementations.
+const BOILERPLATE_NAMES: &[&str] = &[
+    // Rust traits
+    "default", "new", "from", "fmt", "clone", "drop", "deref", "eq",
+    "hash", "into", "try_from", "try_into", "serialize", "deserialize",
+    // Java/Kotlin/C#
+    "tostring", "hashcode", "equals", "compareto", "getclass", "finalize",
+    "dispose", "close", "gettype", "gethashcode",
+    // Python
+    "__init__", "__str__", "__repr__", "__eq__", "__hash__", "__len__",
+    "__iter__", "__next__", "__enter__", "__exit__", "__del__",
+    // JavaScript/TypeScript
+    "constructor", "tostring", "valueof", "tolocalestring", "tojson",
+    // C/C++
+    "main", "init", "destroy", "free", "malloc", "realloc",
+    // Go
+    "string", "error", "len", "close",
+    // PHP
+    "__construct", "__destruct", "__tostring", "__clone", "__get", "__set",
+    // Ruby
+    "initialize", "to_s", "to_str", "inspect", "hash", "eql?",
+    // Swift
+    "init", "deinit", "description",
+    // Common getters/setters (all languages)
     "get", "set", "getvalue", "setvalue",
 ];
 
-/// Cosine similarity between two vectors. Returns 0.0 for zero-length vectors.
-fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
-    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
-    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
-    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
-    if norm_a == 0.0 || norm_b == 0.0 {
-        0.0
-    } else {
-        dot / (norm_a * norm_b)
+/// Cosine similarity between two vectors. Returns 0.0 for zero-length vectors.
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b.iter()).map(|(x, y)| x * y).sum();
+    let norm_a: f32 = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b: f32 = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Keywords kept verbatim by [`normalize_code_tokens`]; they carry control-flow shape.
+const CODE_KEYWORDS: &[&str] = &[
+    "if", "else", "elif", "for", "while", "loop", "do", "match", "switch", "case", "default",
+    "return", "break", "continue", "try", "catch", "except", "finally", "throw", "raise", "await",
+    "async", "yield", "in", "not", "and", "or", "new", "let", "var", "const", "mut",
+];
+
+/// Reduce source code to a language-agnostic token stream.
+///
+/// Identifiers become `ID`, numbers `NUM` and string/char literals `STR`; keywords
+/// and punctuation are kept. Comments and whitespace are dropped.
+fn normalize_code_tokens(source: &str) -> Vec<String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if (c == '/' && chars.get(i + 1) == Some(&'/'))
+            || (c == '#' && chars.get(i + 1).is_some_and(|n| n.is_whitespace()))
+        {
+            // Line comment
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' || c == '`' {
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i += 1;
+            tokens.push("STR".to_string());
+        } else if c == '\'' && (chars.get(i + 2) == Some(&'\'') || chars.get(i + 1) == Some(&'\\'))
+        {
+            // Char literal ('a' or '\n'); a bare quote is a lifetime or Python string
+            i += if chars[i + 1] == '\\' { 4 } else { 3 };
+            tokens.push("STR".to_string());
+        } else if c.is_ascii_digit() {
+            while i < chars.len()
+                && (chars[i].is_alphanumeric() || chars[i] == '.' || chars[i] == '_')
+            {
+                i += 1;
+            }
+            tokens.push("NUM".to_string());
+        } else if c.is_alphabetic() || c == '_' {
+            let begin = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[begin..i].iter().collect();
+            if CODE_KEYWORDS.contains(&word.as_str()) {
+                tokens.push(word);
+            } else {
+                tokens.push("ID".to_string());
+            }
+        } else {
+            tokens.push(c.to_string());
+            i += 1;
+        }
+    }
+
+    tokens
+}
+
+/// Hash every `SHINGLE_SIZE`-token window. Short streams hash as a single shingle.
+fn code_shingles(tokens: &[String]) -> HashSet<u64> {
+    use std::hash::{Hash, Hasher};
+
+    let hash = |window: &[String]| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        window.hash(&mut hasher);
+        hasher.finish()
+    };
+    if tokens.is_empty() {
+        HashSet::new()
+    } else if tokens.len() < SHINGLE_SIZE {
+        std::iter::once(hash(tokens)).collect()
+    } else {
+        tokens.windows(SHINGLE_SIZE).map(hash).collect()
+    }
+}
+
+/// Jaccard similarity of two shingle sets. Returns 0.0 if both are empty.
+fn jaccard_similarity(a: &HashSet<u64>, b: &HashSet<u64>) -> f32 {
+    let union = a.union(b).count();
+    if union == 0 {
+        0.0
+    } else {
+        a.intersection(b).count() as f32 / union as f32
+    }
+}
+
+/// Source of a symbol, reading each file at most once per `files` cache.
+fn cached_symbol_source(
+    node: &codegraph::Node,
+    files: &mut HashMap<String, Option<String>>,
+) -> Option<String> {
+    if let Some(source) = node.properties.get_string("source") {
+        return Some(source.to_string());
+    }
+
+    let path = node_props::path(node);
+    let start = node_props::line_start_opt(node)? as usize;
+    let end = node_props::line_end_opt(node)? as usize;
+    if path.is_empty() || start == 0 || end < start {
+        return None;
+    }
+
+    let content = files
+        .entry(path.to_string())
+        .or_insert_with(|| std::fs::read_to_string(path).ok())
+        .as_deref()?;
+    let body: Vec<&str> = content
+        .lines()
+        .skip(start - 1)
+        .take(end + 1 - start)
+        .collect();
+    (!body.is_empty()).then(|| body.join("\n"))
+}
+
+/// Whether `name` can be used as an identifier in the indexed languages.
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Whether `text` contains `name` as a whole word.
+fn mentions_identifier(text: &str, name: &str) -> bool {
+    if name.is_empty() {
+        return false;
+    }
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    text.match_indices(name).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + name.len()..].chars().next();
+        !before.is_some_and(is_word) && !after.is_some_and(is_word)
+    })
+}
+
+/// Whether a node of `node_type` is a symbol of the queried `symbol_type`.
+fn symbol_type_matches(symbol_type: SymbolType, node_type: NodeType) -> bool {
+    matches!(
+        (symbol_type, node_type),
+        (SymbolType::Function, NodeType::Function)
+            | (SymbolType::Class, NodeType::Class)
+            | (SymbolType::Variable, NodeType::Variable)
+            | (SymbolType::Module, NodeType::Module)
+            | (SymbolType::Interface, NodeType::Interface)
+            | (SymbolType::Type, NodeType::Type)
+    )
+}
+
+/// PageRank damping factor.
+const PAGERANK_DAMPING: f64 = 0.85;
+/// Maximum PageRank power iterations.
+const PAGERANK_MAX_ITERATIONS: usize = 50;
+/// PageRank stops early once the total rank change drops below this.
+const PAGERANK_TOLERANCE: f64 = 1e-6;
+/// Maximum number of BFS sources for betweenness; larger graphs are sampled.
+const BETWEENNESS_MAX_SOURCES: usize = 256;
+
+/// Function and class line ranges per file: file -> (start, end, id).
+fn symbol_scopes<'a>(
+    graph: &CodeGraph,
+    files: &HashSet<&'a str>,
+) -> HashMap<&'a str, Vec<(u32, u32, NodeId)>> {
+    let mut scopes: HashMap<&str, Vec<(u32, u32, NodeId)>> = HashMap::new();
+    for (node_id, node) in graph.iter_nodes() {
+        if !matches!(node.node_type, NodeType::Function | NodeType::Class) {
+            continue;
+        }
+        if let Some(file) = files.get(node_props::path(node)) {
+            let line_start = node_props::line_start(node);
+            let line_end = node_props::line_end(node).max(line_start);
+            scopes
+                .entry(*file)
+                .or_default()
+                .push((line_start, line_end, node_id));
+        }
+    }
+    scopes
+}
+
+/// Innermost symbol of `scopes` whose range contains `line` in `file`.
+fn innermost_scope(
+    scopes: &HashMap<&str, Vec<(u32, u32, NodeId)>>,
+    file: &str,
+    line: u32,
+) -> Option<NodeId> {
+    scopes.get(file).and_then(|candidates| {
+        candidates
+            .iter()
+            .filter(|(s, e, _)| *s <= line && line <= *e)
+            .min_by_key(|(s, e, _)| e - s)
+            .map(|(_, _, id)| *id)
+    })
+}
+
+/// Compute normalized PageRank and betweenness for every symbol in the call graph.
+fn compute_centrality(
+    callee_map: &HashMap<NodeId, Vec<NodeId>>,
+) -> HashMap<NodeId, CentralityScores> {
+    // Dense indices, sorted so that betweenness sampling is deterministic
+    let mut nodes: Vec<NodeId> = callee_map
+        .iter()
+        .flat_map(|(caller, callees)| std::iter::once(*caller).chain(callees.iter().copied()))
+        .collect();
+    nodes.sort_unstable();
+    nodes.dedup();
+
+    let index: HashMap<NodeId, usize> = nodes.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); nodes.len()];
+    for (caller, callees) in callee_map {
+        let from = index[caller];
+        for callee in callees {
+            let to = index[callee];
+            if from != to && !adjacency[from].contains(&to) {
+                adjacency[from].push(to);
+            }
+        }
+    }
+
+    let pagerank = normalize_scores(pagerank(&adjacency));
+    let betweenness = normalize_scores(betweenness(&adjacency, BETWEENNESS_MAX_SOURCES));
+
+    nodes
+        .into_iter()
+        .enumerate()
+        .map(|(i, id)| {
+            (
+                id,
+                CentralityScores {
+                    pagerank: pagerank[i] as f32,
+                    betweenness: betweenness[i] as f32,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Scale scores so the maximum is 1.0 (all-zero input is returned unchanged).
+fn normalize_scores(mut scores: Vec<f64>) -> Vec<f64> {
+    let max = scores.iter().cloned().fold(0.0, f64::max);
+    if max > 0.0 {
+        for score in &mut scores {
+            *score /= max;
+        }
+    }
+    scores
+}
+
+/// PageRank by power iteration. Rank held by nodes without outgoing edges
+/// is redistributed evenly so the total stays at 1.0.
+fn pagerank(adjacency: &[Vec<usize>]) -> Vec<f64> {
+    let n = adjacency.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let base = (1.0 - PAGERANK_DAMPING) / n as f64;
+    let mut rank = vec![1.0 / n as f64; n];
+    for _ in 0..PAGERANK_MAX_ITERATIONS {
+        let dangling: f64 = adjacency
+            .iter()
+            .zip(&rank)
+            .filter(|(out, _)| out.is_empty())
+            .map(|(_, r)| r)
+            .sum();
+
+        let mut next = vec![base + PAGERANK_DAMPING * dangling / n as f64; n];
+        for (v, out) in adjacency.iter().enumerate() {
+            if out.is_empty() {
+                continue;
+            }
+            let share = PAGERANK_DAMPING * rank[v] / out.len() as f64;
+            for &w in out {
+                next[w] += share;
+            }
+        }
+
+        let delta: f64 = next.iter().zip(&rank).map(|(a, b)| (a - b).abs()).sum();
+        rank = next;
+        if delta < PAGERANK_TOLERANCE {
+            break;
+        }
+    }
+
+    rank
+}
+
+/// Brandes betweenness centrality on an unweighted directed graph.
+///
+/// Uses at most `max_sources` evenly spaced BFS sources, which keeps
+/// re-indexing fast on large call graphs while preserving the ranking.
+fn betweenness(adjacency: &[Vec<usize>], max_sources: usize) -> Vec<f64> {
+    let n = adjacency.len();
+    let mut centrality = vec![0.0; n];
+    if n == 0 || max_sources == 0 {
+        return centrality;
+    }
+
+    let step = n.div_ceil(max_sources);
+    let mut sigma = vec![0.0f64; n];
+    let mut dist = vec![-1i64; n];
+    let mut delta = vec![0.0f64; n];
+    let mut preds: Vec<Vec<usize>> = vec![Vec::new(); n];
+    let mut order: Vec<usize> = Vec::with_capacity(n);
+    let mut queue: VecDeque<usize> = VecDeque::new();
+
+    for source in (0..n).step_by(step) {
+        sigma.fill(0.0);
+        dist.fill(-1);
+        delta.fill(0.0);
+        preds.iter_mut().for_each(Vec::clear);
+        order.clear();
+
+        sigma[source] = 1.0;
+        dist[source] = 0;
+        queue.push_back(source);
+        while let Some(v) = queue.pop_front() {
+            order.push(v);
+            for &w in &adjacency[v] {
+                if dist[w] < 0 {
+                    dist[w] = dist[v] + 1;
+                    queue.push_back(w);
+                }
+                if dist[w] == dist[v] + 1 {
+                    sigma[w] += sigma[v];
+                    preds[w].push(v);
+                }
+            }
+        }
+
+        // Accumulate dependencies in reverse BFS order
+        while let Some(w) = order.pop() {
+            for &v in &preds[w] {
+                delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+            }
+            if w != source {
+                centrality[w] += delta[w];
+            }
+        }
+    }
+
+    centrality
+}
+
+/// Tarjan's strongly connected components over an adjacency list.
+///
+/// Iterative so that long call chains cannot overflow the stack.
+fn strongly_connected_components(adjacency: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+    let n = adjacency.len();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0usize; n];
+    let mut on_stack = vec![false; n];
+    let mut stack: Vec<usize> = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+
+        index[root] = next_index;
+        lowlink[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        // Explicit call stack of (vertex, next neighbor position)
+        let mut call_stack: Vec<(usize, usize)> = vec![(root, 0)];
+        while let Some(frame) = call_stack.last_mut() {
+            let v = frame.0;
+            if let Some(&w) = adjacency[v].get(frame.1) {
+                frame.1 += 1;
+                if index[w] == UNVISITED {
+                    index[w] = next_index;
+                    lowlink[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    call_stack.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+            if lowlink[v] == index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::PropertyMap;
+
+    async fn create_test_engine() -> (QueryEngine, Arc<RwLock<CodeGraph>>) {
+        let graph = Arc::new(RwLock::new(
+            CodeGraph::in_memory().expect("Failed to create in-memory graph"),
+        ));
+        let engine = QueryEngine::new(Arc::clone(&graph));
+        (engine, graph)
+    }
+
+    #[tokio::test]
+    async fn test_engine_creation() {
+        let (engine, _) = create_test_engine().await;
+        // Engine should be created successfully
+        let text_index = engine.text_index.read().await;
+        assert_eq!(text_index.document_count(), 0);
+    }
+
+    #[test]
+    fn test_root_labels() {
+        let single = vec![PathBuf::from("/work/api")];
+        assert!(root_labels(&single).is_empty());
+
+        let folders = vec![
+            PathBuf::from("/work/api"),
+            PathBuf::from("/work/shared"),
+            PathBuf::from("/vendor/shared"),
+        ];
+        let labels: Vec<String> = root_labels(&folders)
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect();
+        assert_eq!(labels, vec!["api", "/work/shared", "/vendor/shared"]);
+    }
+
+    #[tokio::test]
+    async fn test_root_label_of_file() {
+        let (engine, _) = create_test_engine().await;
+        assert_eq!(engine.root_label("/work/api/src/main.rs"), None);
+
+        engine.set_workspace_roots(&[PathBuf::from("/work/api"), PathBuf::from("/work/lib")]);
+        assert_eq!(
+            engine.root_label("/work/lib/src/parse.rs").as_deref(),
+            Some("lib")
+        );
+        assert_eq!(engine.root_label("/elsewhere/main.rs"), None);
+    }
+
+    #[tokio::test]
+    async fn test_symbol_search_empty() {
+        let (engine, _) = create_test_engine().await;
+
+        let results = engine.symbol_search("test", &SearchOptions::new()).await;
+
+        assert_eq!(results.results.len(), 0);
+        assert_eq!(results.total_matches, 0);
+    }
+
+    #[tokio::test]
+    async fn test_symbol_search_with_data() {
+        let (engine, graph) = create_test_engine().await;
+
+        // Add a function node to the graph
+        {
+            let mut g = graph.write().await;
+            let mut props = PropertyMap::new();
+            props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("validateEmail".to_string()),
+            );
+            props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(20));
+
+            let node_id = g
+                .add_node(NodeType::Function, props)
+                .expect("Failed to add node");
+
+            // Node ID is always valid (can be 0 for first node)
+            let _ = node_id;
+        }
+
+        // Build indexes
+        engine.build_indexes().await;
+
+        // Search should find the function
+        let results = engine
+            .symbol_search("validate", &SearchOptions::new())
+            .await;
+
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].symbol.name, "validateEmail");
+        assert!(!results.truncated);
+
+        // An exhausted budget returns what was scored so far, flagged
+        let options = SearchOptions::new().with_time_budget(Some(std::time::Duration::ZERO));
+        let results = engine.symbol_search("validate", &options).await;
+        assert!(results.truncated);
+        assert!(results.results.is_empty());
+
+        // So does a cancelled search
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let options = SearchOptions::new().with_cancellation(cancel);
+        let results = engine.symbol_search("validate", &options).await;
+        assert!(results.truncated);
+        assert!(results.results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_symbol_search_with_type_filter() {
+        let (engine, graph) = create_test_engine().await;
+
+        // Add a function and a class
+        {
+            let mut g = graph.write().await;
+
+            let mut func_props = PropertyMap::new();
+            func_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("processData".to_string()),
+            );
+            func_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            func_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, func_props)
+                .expect("Failed to add function");
+
+            let mut class_props = PropertyMap::new();
+            class_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("DataProcessor".to_string()),
+            );
+            class_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            class_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            g.add_node(NodeType::Class, class_props)
+                .expect("Failed to add class");
+        }
+
+        engine.build_indexes().await;
+
+        // Search with function type filter
+        let options = SearchOptions::new().with_symbol_types(vec![SymbolType::Function]);
+        let results = engine.symbol_search("data", &options).await;
+
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].symbol.kind, "Function");
+    }
+
+    #[tokio::test]
+    async fn test_traverse_graph() {
+        let (engine, graph) = create_test_engine().await;
+
+        // Create a simple call chain: A -> B -> C
+        let (a, b, c);
+        {
+            let mut g = graph.write().await;
+
+            let mut props_a = PropertyMap::new();
+            props_a.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("functionA".to_string()),
+            );
+            props_a.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            a = g
+                .add_node(NodeType::Function, props_a)
+                .expect("Failed to add node");
+
+            let mut props_b = PropertyMap::new();
+            props_b.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("functionB".to_string()),
+            );
+            props_b.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            b = g
+                .add_node(NodeType::Function, props_b)
+                .expect("Failed to add node");
+
+            let mut props_c = PropertyMap::new();
+            props_c.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("functionC".to_string()),
+            );
+            props_c.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            c = g
+                .add_node(NodeType::Function, props_c)
+                .expect("Failed to add node");
+
+            // A calls B, B calls C
+            g.add_edge(a, b, EdgeType::Calls, PropertyMap::new())
+                .expect("Failed to add edge");
+            g.add_edge(b, c, EdgeType::Calls, PropertyMap::new())
+                .expect("Failed to add edge");
+        }
+
+        engine.build_indexes().await;
+
+        // Traverse from A with depth 2
+        let filter = TraversalFilter::new().with_max_nodes(100);
+        let results = engine
+            .traverse_graph(
+                a,
+                TraversalDirection::Outgoing,
+                2,
+                &filter,
+                &CancellationToken::new(),
+            )
+            .await;
+
+        // Should find B and C
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"functionB"));
+        assert!(names.contains(&"functionC"));
+
+        // A cancelled traversal stops before visiting anything
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let results = engine
+            .traverse_graph(a, TraversalDirection::Outgoing, 2, &filter, &cancel)
+            .await;
+        assert!(results.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_traverse_graph_node_type_filter() {
+        let (engine, graph) = create_test_engine().await;
+
+        // Graph: functionA -> ClassB -> functionC
+        // With nodeTypes: [Function], ClassB should be traversed through but not in results
+        let (a, _b, c);
+        {
+            let mut g = graph.write().await;
+
+            let mut props_a = PropertyMap::new();
+            props_a.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("functionA".to_string()),
+            );
+            props_a.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            a = g.add_node(NodeType::Function, props_a).expect("add node");
+
+            let mut props_b = PropertyMap::new();
+            props_b.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("ClassB".to_string()),
+            );
+            props_b.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            _b = g.add_node(NodeType::Class, props_b).expect("add node");
+
+            let mut props_c = PropertyMap::new();
+            props_c.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("functionC".to_string()),
+            );
+            props_c.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            c = g.add_node(NodeType::Function, props_c).expect("add node");
+
+            g.add_edge(a, _b, EdgeType::Calls, PropertyMap::new())
+                .expect("add edge");
+            g.add_edge(_b, c, EdgeType::Calls, PropertyMap::new())
+                .expect("add edge");
+        }
+
+        engine.build_indexes().await;
+
+        // Filter for only Function nodes — should still reach functionC through ClassB
+        let filter = TraversalFilter::new()
+            .with_max_nodes(100)
+            .with_symbol_types(vec![SymbolType::Function]);
+        let results = engine
+            .traverse_graph(
+                a,
+                TraversalDirection::Outgoing,
+                3,
+                &filter,
+                &CancellationToken::new(),
+            )
+            .await;
+
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(
+            names.contains(&"functionC"),
+            "functionC should be reachable through filtered ClassB"
+        );
+        assert!(
+            !names.contains(&"ClassB"),
+            "ClassB should be filtered from results"
+        );
+        assert_eq!(results.len(), 1, "only functionC should be in results");
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use codegraph::PropertyMap;
+    #[tokio::test]
+    async fn test_traverse_graph_edge_type_filter() {
+        let (engine, graph) = create_test_engine().await;
 
-    async fn create_test_engine() -> (QueryEngine, Arc<RwLock<CodeGraph>>) {
-        let graph = Arc::new(RwLock::new(
-            CodeGraph::in_memory().expect("Failed to create in-memory graph"),
-        ));
-        let engine = QueryEngine::new(Arc::clone(&graph));
-        (engine, graph)
+        // Graph: A -calls-> B -imports-> C
+        // With edgeTypes: ["Calls"], should only reach B (not C via Imports)
+        let (a, _b, _c);
+        {
+            let mut g = graph.write().await;
+
+            let mut props_a = PropertyMap::new();
+            props_a.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("funcA".to_string()),
+            );
+            props_a.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            a = g.add_node(NodeType::Function, props_a).expect("add node");
+
+            let mut props_b = PropertyMap::new();
+            props_b.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("funcB".to_string()),
+            );
+            props_b.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            _b = g.add_node(NodeType::Function, props_b).expect("add node");
+
+            let mut props_c = PropertyMap::new();
+            props_c.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("funcC".to_string()),
+            );
+            props_c.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            _c = g.add_node(NodeType::Function, props_c).expect("add node");
+
+            g.add_edge(a, _b, EdgeType::Calls, PropertyMap::new())
+                .expect("add edge");
+            g.add_edge(_b, _c, EdgeType::Imports, PropertyMap::new())
+                .expect("add edge");
+        }
+
+        engine.build_indexes().await;
+
+        // Only follow Calls edges
+        let filter = TraversalFilter::new()
+            .with_max_nodes(100)
+            .with_edge_types(vec!["Calls".to_string()]);
+        let results = engine
+            .traverse_graph(
+                a,
+                TraversalDirection::Outgoing,
+                3,
+                &filter,
+                &CancellationToken::new(),
+            )
+            .await;
+
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"funcB"), "funcB reachable via Calls edge");
+        assert!(
+            !names.contains(&"funcC"),
+            "funcC should not be reachable via Imports edge when filtering for Calls"
+        );
+        assert_eq!(results.len(), 1);
     }
 
     #[tokio::test]
-    async fn test_engine_creation() {
-        let (engine, _) = create_test_engine().await;
-        // Engine should be created successfully
-        let text_index = engine.text_index.read().await;
-        assert_eq!(text_index.document_count(), 0);
+    async fn test_get_callers() {
+        let (engine, graph) = create_test_engine().await;
+
+        let (a, b, c);
+        {
+            let mut g = graph.write().await;
+
+            let mut props_a = PropertyMap::new();
+            props_a.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("caller1".to_string()),
+            );
+            props_a.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            a = g
+                .add_node(NodeType::Function, props_a)
+                .expect("Failed to add node");
+
+            let mut props_b = PropertyMap::new();
+            props_b.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("caller2".to_string()),
+            );
+            props_b.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            b = g
+                .add_node(NodeType::Function, props_b)
+                .expect("Failed to add node");
+
+            let mut props_c = PropertyMap::new();
+            props_c.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("target".to_string()),
+            );
+            props_c.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            c = g
+                .add_node(NodeType::Function, props_c)
+                .expect("Failed to add node");
+
+            // A and B both call C
+            g.add_edge(a, c, EdgeType::Calls, PropertyMap::new())
+                .expect("Failed to add edge");
+            g.add_edge(b, c, EdgeType::Calls, PropertyMap::new())
+                .expect("Failed to add edge");
+        }
+
+        engine.build_indexes().await;
+
+        // Get callers of C
+        let callers = engine.get_callers(c, 1).await;
+
+        assert_eq!(callers.len(), 2);
+        let caller_names: Vec<&str> = callers.iter().map(|c| c.symbol.name.as_str()).collect();
+        assert!(caller_names.contains(&"caller1"));
+        assert!(caller_names.contains(&"caller2"));
     }
 
-    #[tokio::test]
-    async fn test_symbol_search_empty() {
-        let (engine, _) = create_test_engine().await;
+    #[tokio::test]
+    async fn test_find_entry_points() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+
+            // Main function
+            let mut main_props = PropertyMap::new();
+            main_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("main".to_string()),
+            );
+            main_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/main.rs".to_string()),
+            );
+            main_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, main_props)
+                .expect("Failed to add main");
+
+            // Test function
+            let mut test_props = PropertyMap::new();
+            test_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("test_something".to_string()),
+            );
+            test_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+            );
+            test_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            g.add_node(NodeType::Function, test_props)
+                .expect("Failed to add test");
+
+            // Regular function (not an entry point)
+            let mut helper_props = PropertyMap::new();
+            helper_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("helper".to_string()),
+            );
+            helper_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+            );
+            helper_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            g.add_node(NodeType::Function, helper_props)
+                .expect("Failed to add helper");
+        }
+
+        engine.build_indexes().await;
 
-        let results = engine.symbol_search("test", &SearchOptions::new()).await;
+        // Find main entry points
+        let mains = engine.find_entry_points(&[EntryType::Main]).await;
+        assert_eq!(mains.len(), 1);
+        assert_eq!(mains[0].symbol.name, "main");
 
-        assert_eq!(results.results.len(), 0);
-        assert_eq!(results.total_matches, 0);
+        // Find test entry points
+        let tests = engine.find_entry_points(&[EntryType::TestEntry]).await;
+        assert_eq!(tests.len(), 1);
+        assert_eq!(tests[0].symbol.name, "test_something");
     }
 
     #[tokio::test]
-    async fn test_symbol_search_with_data() {
+    async fn test_get_symbol_info() {
         let (engine, graph) = create_test_engine().await;
 
-        // Add a function node to the graph
+        let node_id;
         {
             let mut g = graph.write().await;
             let mut props = PropertyMap::new();
             props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("validateEmail".to_string()),
+                codegraph::PropertyValue::String("processData".to_string()),
             );
             props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
             props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(20));
+            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(25));
+            props.insert(
+                "doc".to_string(),
+                codegraph::PropertyValue::String("Processes input data".to_string()),
+            );
+            props.insert(
+                "is_public".to_string(),
+                codegraph::PropertyValue::Bool(true),
+            );
 
-            let node_id = g
+            node_id = g
                 .add_node(NodeType::Function, props)
                 .expect("Failed to add node");
+        }
 
-            // Node ID is always valid (can be 0 for first node)
-            let _ = node_id;
+        engine.build_indexes().await;
+
+        let info = engine.get_symbol_info(node_id).await;
+
+        assert!(info.is_some());
+        let info = info.unwrap();
+        assert_eq!(info.symbol.name, "processData");
+        assert_eq!(info.lines_of_code, 16); // 25 - 10 + 1
+        assert!(info.is_public);
+    }
+
+    #[tokio::test]
+    async fn test_query_performance() {
+        let (engine, graph) = create_test_engine().await;
+
+        // Add 1000 nodes using camelCase so tokens are properly split
+        {
+            let mut g = graph.write().await;
+            for i in 0..1000 {
+                let mut props = PropertyMap::new();
+                // Use functionXXX format like "functionProcess0" so "function" is a separate token
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(format!("functionProcess{i}")),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/test.rs".to_string()),
+                );
+                props.insert(
+                    "line_start".to_string(),
+                    codegraph::PropertyValue::Int(i as i64),
+                );
+
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
+        }
+
+        engine.build_indexes().await;
+
+        // Search should complete quickly (< 10ms)
+        let start = Instant::now();
+        let results = engine
+            .symbol_search("function", &SearchOptions::new())
+            .await;
+        let duration = start.elapsed();
+
+        assert!(
+            duration.as_millis() < 10,
+            "Search took too long: {duration:?}"
+        );
+        assert!(!results.results.is_empty());
+    }
+
+    // ==========================================
+    // find_by_signature tests
+    // ==========================================
+
+    #[tokio::test]
+    async fn test_find_by_signature_name_pattern() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+
+            // Add functions with different names
+            for (name, is_async) in [
+                ("getUserById", false),
+                ("getOrderById", false),
+                ("createUser", true),
+                ("deleteUser", false),
+                ("processData", false),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/api.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "is_async".to_string(),
+                    codegraph::PropertyValue::Bool(is_async),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
+        }
+
+        engine.build_indexes().await;
+
+        // Search for functions matching "get.*ById" pattern
+        let pattern = SignaturePattern {
+            name_pattern: Some("get.*ById".to_string()),
+            return_type: None,
+            param_count: None,
+            modifiers: vec![],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"getUserById"));
+        assert!(names.contains(&"getOrderById"));
+    }
+
+    #[tokio::test]
+    async fn test_find_by_signature_return_type() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+
+            // Add functions with different return types
+            for (name, return_type) in [
+                ("getString", "String"),
+                ("getInt", "i32"),
+                ("getBool", "bool"),
+                ("getResult", "Result<String, Error>"),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "return_type".to_string(),
+                    codegraph::PropertyValue::String(return_type.to_string()),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
+        }
+
+        engine.build_indexes().await;
+
+        // Search for functions returning String
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: Some("String".to_string()),
+            param_count: None,
+            modifiers: vec![],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "getString");
+    }
+
+    #[tokio::test]
+    async fn test_find_by_signature_return_type_normalized() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+
+            // Add functions with equivalent return types
+            for (name, return_type) in [
+                ("fn1", "boolean"),
+                ("fn2", "bool"),
+                ("fn3", "void"),
+                ("fn4", "()"),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "return_type".to_string(),
+                    codegraph::PropertyValue::String(return_type.to_string()),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
         }
 
-        // Build indexes
-        engine.build_indexes().await;
+        engine.build_indexes().await;
+
+        // Search for boolean (should match both "boolean" and "bool")
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: Some("bool".to_string()),
+            param_count: None,
+            modifiers: vec![],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"fn1"));
+        assert!(names.contains(&"fn2"));
+
+        // Search for void (should match "void" and "()")
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: Some("void".to_string()),
+            param_count: None,
+            modifiers: vec![],
+        };
 
-        // Search should find the function
-        let results = engine
-            .symbol_search("validate", &SearchOptions::new())
-            .await;
+        let results = engine.find_by_signature(&pattern, None).await;
 
-        assert_eq!(results.results.len(), 1);
-        assert_eq!(results.results[0].symbol.name, "validateEmail");
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"fn3"));
+        assert!(names.contains(&"fn4"));
     }
 
     #[tokio::test]
-    async fn test_symbol_search_with_type_filter() {
+    async fn test_find_by_signature_param_count() {
         let (engine, graph) = create_test_engine().await;
 
-        // Add a function and a class
         {
             let mut g = graph.write().await;
 
-            let mut func_props = PropertyMap::new();
-            func_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("processData".to_string()),
-            );
-            func_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            func_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, func_props)
-                .expect("Failed to add function");
-
-            let mut class_props = PropertyMap::new();
-            class_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("DataProcessor".to_string()),
-            );
-            class_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            class_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            g.add_node(NodeType::Class, class_props)
-                .expect("Failed to add class");
+            // Add functions with different param counts
+            for (name, param_count) in [
+                ("noParams", 0),
+                ("oneParam", 1),
+                ("twoParams", 2),
+                ("threeParams", 3),
+                ("manyParams", 5),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "param_count".to_string(),
+                    codegraph::PropertyValue::Int(param_count),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
         }
 
         engine.build_indexes().await;
 
-        // Search with function type filter
-        let options = SearchOptions::new().with_symbol_types(vec![SymbolType::Function]);
-        let results = engine.symbol_search("data", &options).await;
+        // Search for functions with 1-2 parameters
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: None,
+            param_count: Some((1, 2)),
+            modifiers: vec![],
+        };
 
-        assert_eq!(results.results.len(), 1);
-        assert_eq!(results.results[0].symbol.kind, "Function");
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"oneParam"));
+        assert!(names.contains(&"twoParams"));
+
+        // Search for functions with exactly 0 parameters
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: None,
+            param_count: Some((0, 0)),
+            modifiers: vec![],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "noParams");
     }
 
     #[tokio::test]
-    async fn test_traverse_graph() {
+    async fn test_find_by_signature_modifiers() {
         let (engine, graph) = create_test_engine().await;
 
-        // Create a simple call chain: A -> B -> C
-        let (a, b, c);
         {
             let mut g = graph.write().await;
 
-            let mut props_a = PropertyMap::new();
-            props_a.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("functionA".to_string()),
-            );
-            props_a.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            a = g
-                .add_node(NodeType::Function, props_a)
-                .expect("Failed to add node");
-
-            let mut props_b = PropertyMap::new();
-            props_b.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("functionB".to_string()),
-            );
-            props_b.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            b = g
-                .add_node(NodeType::Function, props_b)
-                .expect("Failed to add node");
-
-            let mut props_c = PropertyMap::new();
-            props_c.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("functionC".to_string()),
-            );
-            props_c.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            c = g
-                .add_node(NodeType::Function, props_c)
-                .expect("Failed to add node");
+            // Add functions with different modifiers
+            let configs = [
+                ("syncPublic", false, true, false),
+                ("asyncPublic", true, true, false),
+                ("syncPrivate", false, false, false),
+                ("asyncPrivate", true, false, false),
+                ("staticFunc", false, true, true),
+            ];
 
-            // A calls B, B calls C
-            g.add_edge(a, b, EdgeType::Calls, PropertyMap::new())
-                .expect("Failed to add edge");
-            g.add_edge(b, c, EdgeType::Calls, PropertyMap::new())
-                .expect("Failed to add edge");
+            for (name, is_async, is_public, is_static) in configs {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "is_async".to_string(),
+                    codegraph::PropertyValue::Bool(is_async),
+                );
+                props.insert(
+                    "is_public".to_string(),
+                    codegraph::PropertyValue::Bool(is_public),
+                );
+                props.insert(
+                    "is_static".to_string(),
+                    codegraph::PropertyValue::Bool(is_static),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
         }
 
         engine.build_indexes().await;
 
-        // Traverse from A with depth 2
-        let filter = TraversalFilter::new().with_max_nodes(100);
-        let results = engine
-            .traverse_graph(a, TraversalDirection::Outgoing, 2, &filter)
-            .await;
+        // Search for async functions
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: None,
+            param_count: None,
+            modifiers: vec!["async".to_string()],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
 
-        // Should find B and C
         assert_eq!(results.len(), 2);
         let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"functionB"));
-        assert!(names.contains(&"functionC"));
-    }
-
-    #[tokio::test]
-    async fn test_traverse_graph_node_type_filter() {
-        let (engine, graph) = create_test_engine().await;
-
-        // Graph: functionA -> ClassB -> functionC
-        // With nodeTypes: [Function], ClassB should be traversed through but not in results
-        let (a, _b, c);
-        {
-            let mut g = graph.write().await;
-
-            let mut props_a = PropertyMap::new();
-            props_a.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("functionA".to_string()),
-            );
-            props_a.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            a = g.add_node(NodeType::Function, props_a).expect("add node");
-
-            let mut props_b = PropertyMap::new();
-            props_b.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("ClassB".to_string()),
-            );
-            props_b.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            _b = g.add_node(NodeType::Class, props_b).expect("add node");
+        assert!(names.contains(&"asyncPublic"));
+        assert!(names.contains(&"asyncPrivate"));
 
-            let mut props_c = PropertyMap::new();
-            props_c.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("functionC".to_string()),
-            );
-            props_c.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            c = g.add_node(NodeType::Function, props_c).expect("add node");
+        // Search for public async functions
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: None,
+            param_count: None,
+            modifiers: vec!["async".to_string(), "public".to_string()],
+        };
 
-            g.add_edge(a, _b, EdgeType::Calls, PropertyMap::new())
-                .expect("add edge");
-            g.add_edge(_b, c, EdgeType::Calls, PropertyMap::new())
-                .expect("add edge");
-        }
+        let results = engine.find_by_signature(&pattern, None).await;
 
-        engine.build_indexes().await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "asyncPublic");
 
-        // Filter for only Function nodes — should still reach functionC through ClassB
-        let filter = TraversalFilter::new()
-            .with_max_nodes(100)
-            .with_symbol_types(vec![SymbolType::Function]);
-        let results = engine
-            .traverse_graph(a, TraversalDirection::Outgoing, 3, &filter)
-            .await;
+        // Search for static functions
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: None,
+            param_count: None,
+            modifiers: vec!["static".to_string()],
+        };
 
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(
-            names.contains(&"functionC"),
-            "functionC should be reachable through filtered ClassB"
-        );
-        assert!(
-            !names.contains(&"ClassB"),
-            "ClassB should be filtered from results"
-        );
-        assert_eq!(results.len(), 1, "only functionC should be in results");
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "staticFunc");
     }
 
     #[tokio::test]
-    async fn test_traverse_graph_edge_type_filter() {
+    async fn test_find_by_signature_combined_filters() {
         let (engine, graph) = create_test_engine().await;
 
-        // Graph: A -calls-> B -imports-> C
-        // With edgeTypes: ["Calls"], should only reach B (not C via Imports)
-        let (a, _b, _c);
         {
             let mut g = graph.write().await;
 
-            let mut props_a = PropertyMap::new();
-            props_a.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("funcA".to_string()),
-            );
-            props_a.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            a = g.add_node(NodeType::Function, props_a).expect("add node");
-
-            let mut props_b = PropertyMap::new();
-            props_b.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("funcB".to_string()),
-            );
-            props_b.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            _b = g.add_node(NodeType::Function, props_b).expect("add node");
-
-            let mut props_c = PropertyMap::new();
-            props_c.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("funcC".to_string()),
-            );
-            props_c.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            _c = g.add_node(NodeType::Function, props_c).expect("add node");
+            // Add various functions
+            let configs = [
+                ("getUserById", "User", 1, true, true),
+                ("getOrderById", "Order", 1, true, false),
+                ("fetchUserData", "User", 2, true, true),
+                ("createUser", "User", 3, false, true),
+                ("processRequest", "Response", 1, true, true),
+            ];
 
-            g.add_edge(a, _b, EdgeType::Calls, PropertyMap::new())
-                .expect("add edge");
-            g.add_edge(_b, _c, EdgeType::Imports, PropertyMap::new())
-                .expect("add edge");
+            for (name, return_type, param_count, is_async, is_public) in configs {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    codegraph::PropertyValue::String(name.to_string()),
+                );
+                props.insert(
+                    "path".to_string(),
+                    codegraph::PropertyValue::String("/src/api.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+                props.insert(
+                    "return_type".to_string(),
+                    codegraph::PropertyValue::String(return_type.to_string()),
+                );
+                props.insert(
+                    "param_count".to_string(),
+                    codegraph::PropertyValue::Int(param_count),
+                );
+                props.insert(
+                    "is_async".to_string(),
+                    codegraph::PropertyValue::Bool(is_async),
+                );
+                props.insert(
+                    "is_public".to_string(),
+                    codegraph::PropertyValue::Bool(is_public),
+                );
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
         }
 
         engine.build_indexes().await;
 
-        // Only follow Calls edges
-        let filter = TraversalFilter::new()
-            .with_max_nodes(100)
-            .with_edge_types(vec!["Calls".to_string()]);
-        let results = engine
-            .traverse_graph(a, TraversalDirection::Outgoing, 3, &filter)
-            .await;
+        // Search for async public functions returning User with 1 param
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: Some("User".to_string()),
+            param_count: Some((1, 1)),
+            modifiers: vec!["async".to_string(), "public".to_string()],
+        };
+
+        let results = engine.find_by_signature(&pattern, None).await;
 
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"funcB"), "funcB reachable via Calls edge");
-        assert!(
-            !names.contains(&"funcC"),
-            "funcC should not be reachable via Imports edge when filtering for Calls"
-        );
         assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "getUserById");
     }
 
     #[tokio::test]
-    async fn test_get_callers() {
+    async fn test_find_by_signature_no_matches() {
         let (engine, graph) = create_test_engine().await;
 
-        let (a, b, c);
         {
             let mut g = graph.write().await;
-
-            let mut props_a = PropertyMap::new();
-            props_a.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("caller1".to_string()),
-            );
-            props_a.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_a.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            a = g
-                .add_node(NodeType::Function, props_a)
-                .expect("Failed to add node");
-
-            let mut props_b = PropertyMap::new();
-            props_b.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("caller2".to_string()),
-            );
-            props_b.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
-            );
-            props_b.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            b = g
-                .add_node(NodeType::Function, props_b)
-                .expect("Failed to add node");
-
-            let mut props_c = PropertyMap::new();
-            props_c.insert(
+            let mut props = PropertyMap::new();
+            props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("target".to_string()),
+                codegraph::PropertyValue::String("someFunction".to_string()),
             );
-            props_c.insert(
+            props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
-            props_c.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            c = g
-                .add_node(NodeType::Function, props_c)
+            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, props)
                 .expect("Failed to add node");
-
-            // A and B both call C
-            g.add_edge(a, c, EdgeType::Calls, PropertyMap::new())
-                .expect("Failed to add edge");
-            g.add_edge(b, c, EdgeType::Calls, PropertyMap::new())
-                .expect("Failed to add edge");
         }
 
         engine.build_indexes().await;
 
-        // Get callers of C
-        let callers = engine.get_callers(c, 1).await;
+        // Search for a pattern that won't match
+        let pattern = SignaturePattern {
+            name_pattern: Some("nonexistent.*".to_string()),
+            return_type: None,
+            param_count: None,
+            modifiers: vec![],
+        };
 
-        assert_eq!(callers.len(), 2);
-        let caller_names: Vec<&str> = callers.iter().map(|c| c.symbol.name.as_str()).collect();
-        assert!(caller_names.contains(&"caller1"));
-        assert!(caller_names.contains(&"caller2"));
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert!(results.is_empty());
     }
 
     #[tokio::test]
-    async fn test_find_entry_points() {
+    async fn test_find_by_signature_only_matches_functions() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
 
-            // Main function
-            let mut main_props = PropertyMap::new();
-            main_props.insert(
+            // Add a function
+            let mut func_props = PropertyMap::new();
+            func_props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("main".to_string()),
+                codegraph::PropertyValue::String("myFunction".to_string()),
             );
-            main_props.insert(
+            func_props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/main.rs".to_string()),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
-            main_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, main_props)
-                .expect("Failed to add main");
+            func_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, func_props)
+                .expect("Failed to add function");
 
-            // Test function
-            let mut test_props = PropertyMap::new();
-            test_props.insert(
+            // Add a class with similar name
+            let mut class_props = PropertyMap::new();
+            class_props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("test_something".to_string()),
+                codegraph::PropertyValue::String("myClass".to_string()),
             );
-            test_props.insert(
+            class_props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/test.rs".to_string()),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
-            test_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            g.add_node(NodeType::Function, test_props)
-                .expect("Failed to add test");
-
-            // Regular function (not an entry point)
-            let mut helper_props = PropertyMap::new();
-            helper_props.insert(
+            class_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            g.add_node(NodeType::Class, class_props)
+                .expect("Failed to add class");
+
+            // Add a variable with similar name
+            let mut var_props = PropertyMap::new();
+            var_props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("helper".to_string()),
+                codegraph::PropertyValue::String("myVariable".to_string()),
             );
-            helper_props.insert(
+            var_props.insert(
                 "path".to_string(),
                 codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
-            helper_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            g.add_node(NodeType::Function, helper_props)
-                .expect("Failed to add helper");
+            var_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            g.add_node(NodeType::Variable, var_props)
+                .expect("Failed to add variable");
         }
 
         engine.build_indexes().await;
 
-        // Find main entry points
-        let mains = engine.find_entry_points(&[EntryType::Main]).await;
-        assert_eq!(mains.len(), 1);
-        assert_eq!(mains[0].symbol.name, "main");
+        // Search with pattern matching all "my*"
+        let pattern = SignaturePattern {
+            name_pattern: Some("my.*".to_string()),
+            return_type: None,
+            param_count: None,
+            modifiers: vec![],
+        };
 
-        // Find test entry points
-        let tests = engine.find_entry_points(&[EntryType::TestEntry]).await;
-        assert_eq!(tests.len(), 1);
-        assert_eq!(tests[0].symbol.name, "test_something");
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        // Should only match the function
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "myFunction");
+        assert_eq!(results[0].symbol.kind, "Function");
     }
 
     #[tokio::test]
-    async fn test_get_symbol_info() {
+    async fn test_find_by_signature_match_reason() {
         let (engine, graph) = create_test_engine().await;
 
-        let node_id;
         {
             let mut g = graph.write().await;
             let mut props = PropertyMap::new();
             props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("processData".to_string()),
+                codegraph::PropertyValue::String("testFunc".to_string()),
             );
             props.insert(
                 "path".to_string(),
                 codegraph::PropertyValue::String("/src/lib.rs".to_string()),
             );
-            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(25));
-            props.insert(
-                "doc".to_string(),
-                codegraph::PropertyValue::String("Processes input data".to_string()),
-            );
+            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
             props.insert(
-                "is_public".to_string(),
-                codegraph::PropertyValue::Bool(true),
+                "return_type".to_string(),
+                codegraph::PropertyValue::String("bool".to_string()),
             );
-
-            node_id = g
-                .add_node(NodeType::Function, props)
+            props.insert("param_count".to_string(), codegraph::PropertyValue::Int(2));
+            props.insert("is_async".to_string(), codegraph::PropertyValue::Bool(true));
+            g.add_node(NodeType::Function, props)
                 .expect("Failed to add node");
         }
 
         engine.build_indexes().await;
 
-        let info = engine.get_symbol_info(node_id).await;
+        let pattern = SignaturePattern {
+            name_pattern: Some("test.*".to_string()),
+            return_type: Some("bool".to_string()),
+            param_count: Some((2, 2)),
+            modifiers: vec!["async".to_string()],
+        };
 
-        assert!(info.is_some());
-        let info = info.unwrap();
-        assert_eq!(info.symbol.name, "processData");
-        assert_eq!(info.lines_of_code, 16); // 25 - 10 + 1
-        assert!(info.is_public);
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 1);
+        let match_reason = &results[0].match_reason;
+        assert!(match_reason.contains("name matches /test.*/"));
+        assert!(match_reason.contains("returns bool"));
+        assert!(match_reason.contains("2 parameters"));
+        assert!(match_reason.contains("modifiers: async"));
     }
 
     #[tokio::test]
-    async fn test_query_performance() {
+    async fn test_type_matches_wildcard() {
         let (engine, graph) = create_test_engine().await;
 
-        // Add 1000 nodes using camelCase so tokens are properly split
         {
             let mut g = graph.write().await;
-            for i in 0..1000 {
+
+            for (name, return_type) in [
+                ("fn1", "Result<String, Error>"),
+                ("fn2", "Result<i32, Error>"),
+                ("fn3", "Option<String>"),
+            ] {
                 let mut props = PropertyMap::new();
-                // Use functionXXX format like "functionProcess0" so "function" is a separate token
                 props.insert(
                     "name".to_string(),
-                    codegraph::PropertyValue::String(format!("functionProcess{i}")),
+                    codegraph::PropertyValue::String(name.to_string()),
                 );
                 props.insert(
                     "path".to_string(),
-                    codegraph::PropertyValue::String("/src/test.rs".to_string()),
+                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
                 );
+                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
                 props.insert(
-                    "line_start".to_string(),
-                    codegraph::PropertyValue::Int(i as i64),
+                    "return_type".to_string(),
+                    codegraph::PropertyValue::String(return_type.to_string()),
                 );
-
                 g.add_node(NodeType::Function, props)
                     .expect("Failed to add node");
             }
@@ -2414,138 +5521,127 @@ mod tests {
 
         engine.build_indexes().await;
 
-        // Search should complete quickly (< 10ms)
-        let start = Instant::now();
-        let results = engine
-            .symbol_search("function", &SearchOptions::new())
-            .await;
-        let duration = start.elapsed();
+        // Search for Result<*, Error> pattern
+        let pattern = SignaturePattern {
+            name_pattern: None,
+            return_type: Some("Result<*, Error>".to_string()),
+            param_count: None,
+            modifiers: vec![],
+        };
 
-        assert!(
-            duration.as_millis() < 10,
-            "Search took too long: {duration:?}"
-        );
-        assert!(!results.results.is_empty());
+        let results = engine.find_by_signature(&pattern, None).await;
+
+        assert_eq!(results.len(), 2);
+        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
+        assert!(names.contains(&"fn1"));
+        assert!(names.contains(&"fn2"));
     }
 
     // ==========================================
-    // find_by_signature tests
+    // detect_entry_type tests
     // ==========================================
 
     #[tokio::test]
-    async fn test_find_by_signature_name_pattern() {
+    async fn test_detect_entry_type_http_handler() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
-
-            // Add functions with different names
-            for (name, is_async) in [
-                ("getUserById", false),
-                ("getOrderById", false),
-                ("createUser", true),
-                ("deleteUser", false),
-                ("processData", false),
-            ] {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/api.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "is_async".to_string(),
-                    codegraph::PropertyValue::Bool(is_async),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
-            }
+            let mut props = PropertyMap::new();
+            props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("getUsers".to_string()),
+            );
+            props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/handlers.rs".to_string()),
+            );
+            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            props.insert(
+                "route".to_string(),
+                codegraph::PropertyValue::String("/api/users".to_string()),
+            );
+            props.insert(
+                "http_method".to_string(),
+                codegraph::PropertyValue::String("GET".to_string()),
+            );
+            g.add_node(NodeType::Function, props)
+                .expect("Failed to add node");
         }
 
         engine.build_indexes().await;
 
-        // Search for functions matching "get.*ById" pattern
-        let pattern = SignaturePattern {
-            name_pattern: Some("get.*ById".to_string()),
-            return_type: None,
-            param_count: None,
-            modifiers: vec![],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
+        let results = engine.find_entry_points(&[EntryType::HttpHandler]).await;
 
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"getUserById"));
-        assert!(names.contains(&"getOrderById"));
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].symbol.name, "getUsers");
+        assert!(matches!(results[0].entry_type, EntryType::HttpHandler));
+        assert_eq!(results[0].route, Some("/api/users".to_string()));
+        assert_eq!(results[0].method, Some("GET".to_string()));
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_return_type() {
+    async fn test_detect_entry_type_cli_command() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
 
-            // Add functions with different return types
-            for (name, return_type) in [
-                ("getString", "String"),
-                ("getInt", "i32"),
-                ("getBool", "bool"),
-                ("getResult", "Result<String, Error>"),
-            ] {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "return_type".to_string(),
-                    codegraph::PropertyValue::String(return_type.to_string()),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
-            }
+            // CLI command by name
+            let mut props = PropertyMap::new();
+            props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("runCommand".to_string()),
+            );
+            props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/cli.rs".to_string()),
+            );
+            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, props)
+                .expect("Failed to add node");
+
+            // CLI command by property
+            let mut props2 = PropertyMap::new();
+            props2.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("execute".to_string()),
+            );
+            props2.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/cli.rs".to_string()),
+            );
+            props2.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            props2.insert("is_cli".to_string(), codegraph::PropertyValue::Bool(true));
+            g.add_node(NodeType::Function, props2)
+                .expect("Failed to add node");
         }
 
         engine.build_indexes().await;
 
-        // Search for functions returning String
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: Some("String".to_string()),
-            param_count: None,
-            modifiers: vec![],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
+        let results = engine.find_entry_points(&[EntryType::CliCommand]).await;
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "getString");
+        assert_eq!(results.len(), 2);
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_return_type_normalized() {
+    async fn test_detect_entry_type_event_handler() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
 
-            // Add functions with equivalent return types
-            for (name, return_type) in [
-                ("fn1", "boolean"),
-                ("fn2", "bool"),
-                ("fn3", "void"),
-                ("fn4", "()"),
+            // Use names that match the actual detection patterns:
+            // - starts_with("on_")
+            // - starts_with("handle_")
+            // - ends_with("_handler")
+            // - ends_with("_callback")
+            // Note: Avoid "cli" in names as it triggers CliCommand detection first
+            for name in [
+                "on_submit",
+                "handle_submit",
+                "button_handler",
+                "data_callback",
             ] {
                 let mut props = PropertyMap::new();
                 props.insert(
@@ -2554,13 +5650,9 @@ mod tests {
                 );
                 props.insert(
                     "path".to_string(),
-                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                    codegraph::PropertyValue::String("/src/events.rs".to_string()),
                 );
                 props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "return_type".to_string(),
-                    codegraph::PropertyValue::String(return_type.to_string()),
-                );
                 g.add_node(NodeType::Function, props)
                     .expect("Failed to add node");
             }
@@ -2568,655 +5660,1299 @@ mod tests {
 
         engine.build_indexes().await;
 
-        // Search for boolean (should match both "boolean" and "bool")
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: Some("bool".to_string()),
-            param_count: None,
-            modifiers: vec![],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
-
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"fn1"));
-        assert!(names.contains(&"fn2"));
-
-        // Search for void (should match "void" and "()")
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: Some("void".to_string()),
-            param_count: None,
-            modifiers: vec![],
-        };
+        let results = engine.find_entry_points(&[EntryType::EventHandler]).await;
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        assert_eq!(results.len(), 4);
 
-        assert_eq!(results.len(), 2);
+        // Verify all expected patterns are detected
         let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"fn3"));
-        assert!(names.contains(&"fn4"));
+        assert!(
+            names.contains(&"on_submit"),
+            "on_submit should be detected (starts with on_)"
+        );
+        assert!(
+            names.contains(&"handle_submit"),
+            "handle_submit should be detected (starts with handle_)"
+        );
+        assert!(
+            names.contains(&"button_handler"),
+            "button_handler should be detected (ends with _handler)"
+        );
+        assert!(
+            names.contains(&"data_callback"),
+            "data_callback should be detected (ends with _callback)"
+        );
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_param_count() {
+    async fn test_find_entry_points_all_types() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
 
-            // Add functions with different param counts
-            for (name, param_count) in [
-                ("noParams", 0),
-                ("oneParam", 1),
-                ("twoParams", 2),
-                ("threeParams", 3),
-                ("manyParams", 5),
-            ] {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "param_count".to_string(),
-                    codegraph::PropertyValue::Int(param_count),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
-            }
-        }
+            // Main
+            let mut main_props = PropertyMap::new();
+            main_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("main".to_string()),
+            );
+            main_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/main.rs".to_string()),
+            );
+            main_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
+            g.add_node(NodeType::Function, main_props)
+                .expect("Failed to add main");
 
-        engine.build_indexes().await;
+            // Test
+            let mut test_props = PropertyMap::new();
+            test_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("test_something".to_string()),
+            );
+            test_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/tests.rs".to_string()),
+            );
+            test_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
+            g.add_node(NodeType::Function, test_props)
+                .expect("Failed to add test");
 
-        // Search for functions with 1-2 parameters
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: None,
-            param_count: Some((1, 2)),
-            modifiers: vec![],
-        };
+            // Public API
+            let mut api_props = PropertyMap::new();
+            api_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("someApi".to_string()),
+            );
+            api_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+            );
+            api_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
+            api_props.insert("exported".to_string(), codegraph::PropertyValue::Bool(true));
+            g.add_node(NodeType::Function, api_props)
+                .expect("Failed to add api");
+        }
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        engine.build_indexes().await;
 
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"oneParam"));
-        assert!(names.contains(&"twoParams"));
+        // Find all entry points (empty filter)
+        let results = engine.find_entry_points(&[]).await;
 
-        // Search for functions with exactly 0 parameters
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: None,
-            param_count: Some((0, 0)),
-            modifiers: vec![],
-        };
+        // Should find main, test, and public API
+        assert!(results.len() >= 3);
+    }
 
-        let results = engine.find_by_signature(&pattern, None).await;
+    fn add_function(g: &mut CodeGraph, name: &str, path: &str, line: i64) -> NodeId {
+        let mut props = PropertyMap::new();
+        props.insert(
+            "name".to_string(),
+            codegraph::PropertyValue::String(name.to_string()),
+        );
+        props.insert(
+            "path".to_string(),
+            codegraph::PropertyValue::String(path.to_string()),
+        );
+        props.insert(
+            "line_start".to_string(),
+            codegraph::PropertyValue::Int(line),
+        );
+        g.add_node(NodeType::Function, props)
+            .expect("Failed to add function")
+    }
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "noParams");
+    fn add_type(g: &mut CodeGraph, node_type: NodeType, name: &str, path: &str) -> NodeId {
+        let mut props = PropertyMap::new();
+        props.insert(
+            "name".to_string(),
+            codegraph::PropertyValue::String(name.to_string()),
+        );
+        props.insert(
+            "path".to_string(),
+            codegraph::PropertyValue::String(path.to_string()),
+        );
+        g.add_node(node_type, props).expect("Failed to add type")
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_modifiers() {
+    async fn test_find_cycles_symbol_level() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
+            let a = add_function(&mut g, "parse", "/src/a.ts", 1);
+            let b = add_function(&mut g, "resolve", "/src/b.ts", 5);
+            let c = add_function(&mut g, "load", "/src/c.ts", 9);
+            let d = add_function(&mut g, "main", "/src/main.ts", 1);
 
-            // Add functions with different modifiers
-            let configs = [
-                ("syncPublic", false, true, false),
-                ("asyncPublic", true, true, false),
-                ("syncPrivate", false, false, false),
-                ("asyncPrivate", true, false, false),
-                ("staticFunc", false, true, true),
-            ];
-
-            for (name, is_async, is_public, is_static) in configs {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "is_async".to_string(),
-                    codegraph::PropertyValue::Bool(is_async),
-                );
-                props.insert(
-                    "is_public".to_string(),
-                    codegraph::PropertyValue::Bool(is_public),
-                );
-                props.insert(
-                    "is_static".to_string(),
-                    codegraph::PropertyValue::Bool(is_static),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
-            }
+            // a -> b -> c -> a, plus an acyclic entry d -> a
+            g.add_edge(a, b, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(b, c, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(c, a, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(d, a, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
         }
 
-        engine.build_indexes().await;
-
-        // Search for async functions
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: None,
-            param_count: None,
-            modifiers: vec!["async".to_string()],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
+        let result = engine
+            .find_cycles(&["calls".to_string()], CycleLevel::Symbol, 2, 10)
+            .await;
 
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"asyncPublic"));
-        assert!(names.contains(&"asyncPrivate"));
+        assert_eq!(result.total_cycles, 1);
+        let cycle = &result.cycles[0];
+        assert_eq!(cycle.size, 3);
+        assert_eq!(cycle.files, vec!["/src/a.ts", "/src/b.ts", "/src/c.ts"]);
+        assert_eq!(cycle.edge_types, vec![EdgeType::Calls.to_string()]);
+        assert!(!cycle.members.iter().any(|m| m.name == "main"));
+    }
 
-        // Search for public async functions
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: None,
-            param_count: None,
-            modifiers: vec!["async".to_string(), "public".to_string()],
-        };
+    #[tokio::test]
+    async fn test_find_cycles_file_level_ignores_intra_file_edges() {
+        let (engine, graph) = create_test_engine().await;
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        let (a1, a2);
+        {
+            let mut g = graph.write().await;
+            a1 = add_function(&mut g, "a1", "/src/a.ts", 1);
+            a2 = add_function(&mut g, "a2", "/src/a.ts", 10);
+
+            // Mutual recursion inside a.ts is not a module cycle on its own
+            g.add_edge(a1, a2, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(a2, a1, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+        }
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "asyncPublic");
+        let intra_file = engine
+            .find_cycles(&["calls".to_string()], CycleLevel::File, 2, 10)
+            .await;
+        assert_eq!(intra_file.total_cycles, 0);
 
-        // Search for static functions
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: None,
-            param_count: None,
-            modifiers: vec!["static".to_string()],
-        };
+        {
+            let mut g = graph.write().await;
+            let b1 = add_function(&mut g, "b1", "/src/b.ts", 1);
 
-        let results = engine.find_by_signature(&pattern, None).await;
+            // a.ts -> b.ts -> a.ts through different symbols
+            g.add_edge(a1, b1, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(b1, a2, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+        }
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "staticFunc");
+        let result = engine
+            .find_cycles(&["calls".to_string()], CycleLevel::File, 2, 10)
+            .await;
+
+        assert_eq!(result.total_cycles, 1);
+        assert_eq!(result.cycles[0].size, 2);
+        assert_eq!(result.cycles[0].members[0].name, "a.ts");
+        assert_eq!(result.cycles[0].members[1].name, "b.ts");
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_combined_filters() {
+    async fn test_find_cycles_respects_edge_type_filter() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
+            let a = add_function(&mut g, "a", "/src/a.ts", 1);
+            let b = add_function(&mut g, "b", "/src/b.ts", 1);
+            g.add_edge(a, b, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(b, a, EdgeType::References, PropertyMap::new())
+                .unwrap();
+        }
 
-            // Add various functions
-            let configs = [
-                ("getUserById", "User", 1, true, true),
-                ("getOrderById", "Order", 1, true, false),
-                ("fetchUserData", "User", 2, true, true),
-                ("createUser", "User", 3, false, true),
-                ("processRequest", "Response", 1, true, true),
-            ];
+        let calls_only = engine
+            .find_cycles(&["calls".to_string()], CycleLevel::Symbol, 2, 10)
+            .await;
+        assert_eq!(calls_only.total_cycles, 0);
+
+        let both = engine
+            .find_cycles(
+                &["calls".to_string(), "references".to_string()],
+                CycleLevel::Symbol,
+                2,
+                10,
+            )
+            .await;
+        assert_eq!(both.total_cycles, 1);
+        assert_eq!(both.cycles[0].edge_types.len(), 2);
+    }
 
-            for (name, return_type, param_count, is_async, is_public) in configs {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/api.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "return_type".to_string(),
-                    codegraph::PropertyValue::String(return_type.to_string()),
-                );
-                props.insert(
-                    "param_count".to_string(),
-                    codegraph::PropertyValue::Int(param_count),
-                );
-                props.insert(
-                    "is_async".to_string(),
-                    codegraph::PropertyValue::Bool(is_async),
-                );
-                props.insert(
-                    "is_public".to_string(),
-                    codegraph::PropertyValue::Bool(is_public),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
+    #[test]
+    fn test_strongly_connected_components() {
+        // 0 -> 1 -> 2 -> 0, 2 -> 3, 3 -> 3
+        let adjacency = vec![vec![1], vec![2], vec![0, 3], vec![3]];
+        let mut components: Vec<Vec<usize>> = strongly_connected_components(&adjacency)
+            .into_iter()
+            .map(|mut c| {
+                c.sort();
+                c
+            })
+            .collect();
+        components.sort();
+        assert_eq!(components, vec![vec![0, 1, 2], vec![3]]);
+    }
+
+    #[tokio::test]
+    async fn test_get_hot_symbols_ranks_hub_first() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+            let hub = add_function(&mut g, "dispatch", "/src/core.ts", 1);
+            let sink = add_function(&mut g, "log", "/src/util.ts", 1);
+            for i in 0..4 {
+                let caller = add_function(&mut g, &format!("handler{i}"), "/src/api.ts", i * 10);
+                g.add_edge(caller, hub, EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
             }
+            g.add_edge(hub, sink, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
         }
 
         engine.build_indexes().await;
 
-        // Search for async public functions returning User with 1 param
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: Some("User".to_string()),
-            param_count: Some((1, 1)),
-            modifiers: vec!["async".to_string(), "public".to_string()],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
-
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "getUserById");
+        let result = engine
+            .get_hot_symbols(CentralityMetric::Betweenness, 3, None)
+            .await;
+        assert_eq!(result.total_ranked, 6);
+        assert_eq!(result.symbols[0].symbol.name, "dispatch");
+        assert_eq!(result.symbols[0].caller_count, 4);
+        assert_eq!(result.symbols[0].callee_count, 1);
+        assert!((result.symbols[0].betweenness - 1.0).abs() < 1e-6);
+
+        let result = engine
+            .get_hot_symbols(CentralityMetric::Combined, 10, Some("api.ts"))
+            .await;
+        assert_eq!(result.symbols.len(), 4);
+        assert!(result
+            .symbols
+            .iter()
+            .all(|s| s.symbol.name.starts_with("handler")));
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_no_matches() {
+    async fn test_get_risk_hotspots_joins_churn_and_centrality() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
-            let mut props = PropertyMap::new();
-            props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("someFunction".to_string()),
-            );
-            props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-            );
-            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, props)
-                .expect("Failed to add node");
+            let hub = add_function(&mut g, "dispatch", "/src/core.ts", 1);
+            let leaf = add_function(&mut g, "format", "/src/fmt.ts", 1);
+            let stable = add_function(&mut g, "log", "/src/util.ts", 1);
+            for i in 0..3 {
+                let caller = add_function(&mut g, &format!("handler{i}"), "/src/api.ts", i * 10);
+                g.add_edge(caller, hub, EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
+            }
+            g.add_edge(hub, leaf, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(hub, stable, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
         }
-
         engine.build_indexes().await;
 
-        // Search for a pattern that won't match
-        let pattern = SignaturePattern {
-            name_pattern: Some("nonexistent.*".to_string()),
-            return_type: None,
-            param_count: None,
-            modifiers: vec![],
-        };
-
-        let results = engine.find_by_signature(&pattern, None).await;
-
-        assert!(results.is_empty());
+        let churn = HashMap::from([
+            ("/src/core.ts".to_string(), 20),
+            ("/src/fmt.ts".to_string(), 20),
+            ("/src/api.ts".to_string(), 1),
+        ]);
+        let result = engine.get_risk_hotspots(&churn, 10, None).await;
+
+        // util.ts never changed, so "log" is not ranked despite its centrality
+        assert_eq!(result.files_with_churn, 3);
+        assert_eq!(result.total_ranked, 5);
+        assert!(result.hotspots.iter().all(|h| h.symbol.name != "log"));
+        assert_eq!(result.hotspots[0].symbol.name, "dispatch");
+        assert_eq!(result.hotspots[0].churn, 20);
+        assert!((result.hotspots[0].churn_score - 1.0).abs() < 1e-6);
+        assert!(result.hotspots[0].risk > result.hotspots[1].risk);
+
+        let result = engine.get_risk_hotspots(&churn, 10, Some("api.ts")).await;
+        assert_eq!(result.hotspots.len(), 3);
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_only_matches_functions() {
+    async fn test_preview_rename_reports_references_collisions_and_memories() {
         let (engine, graph) = create_test_engine().await;
 
-        {
+        let (target, caller) = {
             let mut g = graph.write().await;
+            let class = add_type(&mut g, NodeType::Class, "UserService", "/src/user.ts");
+            let target = add_function(&mut g, "load", "/src/user.ts", 5);
+            let sibling = add_function(&mut g, "fetch", "/src/user.ts", 20);
+            for member in [target, sibling] {
+                g.add_edge(class, member, EdgeType::Contains, PropertyMap::new())
+                    .unwrap();
+            }
+            let caller = add_function(&mut g, "showUser", "/src/page.ts", 3);
+            add_function(&mut g, "fetch", "/src/page.ts", 30);
+            add_function(&mut g, "fetch", "/src/unrelated.ts", 1);
+            let mut props = PropertyMap::new();
+            props.insert("line".to_string(), codegraph::PropertyValue::Int(7));
+            g.add_edge(caller, target, EdgeType::Calls, props).unwrap();
+            (target, caller)
+        };
 
-            // Add a function
-            let mut func_props = PropertyMap::new();
-            func_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("myFunction".to_string()),
-            );
-            func_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-            );
-            func_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, func_props)
-                .expect("Failed to add function");
+        let memories = vec![
+            MemoryNode::builder()
+                .convention("Loading", "Always call load() through the cache")
+                .title("Cache user loads")
+                .content("UserService.load must go through the cache")
+                .build()
+                .unwrap(),
+            MemoryNode::builder()
+                .convention("Naming", "Use loader suffixes")
+                .title("Loaders")
+                .content("Every loader lives in src/loaders")
+                .build()
+                .unwrap(),
+        ];
+
+        let preview = engine
+            .preview_rename(target, "fetch", &memories)
+            .await
+            .unwrap();
+        assert_eq!(preview.old_name, "load");
+        assert_eq!(preview.references.len(), 1);
+        assert_eq!(preview.references[0].node_id, caller);
+        assert_eq!(preview.references[0].line, 7);
+        assert_eq!(preview.affected_files, vec!["/src/page.ts", "/src/user.ts"]);
+
+        // The sibling method clashes in the class; unrelated.ts is not touched
+        assert_eq!(preview.collisions.len(), 2);
+        assert_eq!(preview.collisions[0].scope, "class UserService");
+        assert!(!preview.collisions[0].in_referencing_file);
+        assert_eq!(preview.collisions[1].scope, "file /src/page.ts");
+        assert!(preview.collisions[1].in_referencing_file);
+
+        // "loader" is not a mention of "load"
+        assert_eq!(preview.memories.len(), 1);
+        assert_eq!(preview.memories[0].title, "Cache user loads");
+        assert!(!preview.memories[0].linked);
+
+        assert!(engine.preview_rename(target, "load", &[]).await.is_err());
+        assert!(engine.preview_rename(target, "1bad", &[]).await.is_err());
+    }
 
-            // Add a class with similar name
-            let mut class_props = PropertyMap::new();
-            class_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("myClass".to_string()),
-            );
-            class_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-            );
-            class_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            g.add_node(NodeType::Class, class_props)
-                .expect("Failed to add class");
+    #[tokio::test]
+    async fn test_run_query_pipeline() {
+        let (engine, graph) = create_test_engine().await;
 
-            // Add a variable with similar name
-            let mut var_props = PropertyMap::new();
-            var_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("myVariable".to_string()),
-            );
-            var_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-            );
-            var_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            g.add_node(NodeType::Variable, var_props)
-                .expect("Failed to add variable");
+        {
+            let mut g = graph.write().await;
+            let validate = add_function(&mut g, "validate", "/src/auth.rs", 1);
+            let login = add_function(&mut g, "login", "/src/api/session.rs", 1);
+            let refresh = add_function(&mut g, "refresh", "/src/api/session.rs", 20);
+            let main = add_function(&mut g, "main", "/src/main.rs", 1);
+            let config = add_type(&mut g, NodeType::Class, "AuthConfig", "/src/api/config.rs");
+            for (from, to) in [(login, validate), (refresh, validate), (main, login)] {
+                g.add_edge(from, to, EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
+            }
+            g.add_edge(config, validate, EdgeType::Uses, PropertyMap::new())
+                .unwrap();
         }
-
         engine.build_indexes().await;
 
-        // Search with pattern matching all "my*"
-        let pattern = SignaturePattern {
-            name_pattern: Some("my.*".to_string()),
-            return_type: None,
-            param_count: None,
-            modifiers: vec![],
-        };
+        let result = engine
+            .run_query(
+                r#"symbol_search("validate") | callers(depth=2) | filter(file="/src/api/") | limit(1)"#,
+            )
+            .await
+            .unwrap();
+        let counts: Vec<(&str, usize)> = result
+            .stages
+            .iter()
+            .map(|s| (s.stage.as_str(), s.count))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("symbol_search", 1),
+                ("callers", 3),
+                ("filter", 2),
+                ("limit", 1)
+            ]
+        );
+        assert_eq!(result.results.len(), 1);
+        assert!(result.results[0].symbol.location.file.contains("/src/api/"));
+        assert!(!result.truncated);
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        assert!(engine.run_query("callers(depth=1)").await.is_err());
+    }
 
-        // Should only match the function
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "myFunction");
-        assert_eq!(results[0].symbol.kind, "Function");
+    #[tokio::test]
+    async fn test_find_by_annotation() {
+        let (engine, graph) = create_test_engine().await;
+
+        {
+            let mut g = graph.write().await;
+            let list = add_function(&mut g, "list_users", "/src/api.py", 10);
+            let create = add_function(&mut g, "create_user", "/src/api.py", 3);
+            let helper = add_function(&mut g, "helper", "/src/api.py", 20);
+            let legacy = add_type(&mut g, NodeType::Class, "LegacyClient", "/src/Client.java");
+            for (id, attrs) in [
+                (list, vec!["@app.get(\"/users\")"]),
+                (create, vec!["@requires_auth", "@app.post(\"/users\")"]),
+                (helper, vec!["@staticmethod"]),
+                (legacy, vec!["@java.lang.Deprecated"]),
+            ] {
+                let attrs = attrs.into_iter().map(str::to_string).collect();
+                g.update_node_properties(
+                    id,
+                    PropertyMap::new()
+                        .with("attributes", codegraph::PropertyValue::StringList(attrs)),
+                )
+                .unwrap();
+            }
+        }
+
+        let result = engine
+            .find_by_annotation("app.post", &[], 10)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 1);
+        assert_eq!(result.results[0].symbol.name, "create_user");
+        assert_eq!(result.results[0].annotations, vec!["@app.post(\"/users\")"]);
+
+        let result = engine
+            .find_by_annotation("@Deprecated", &[], 10)
+            .await
+            .unwrap();
+        assert_eq!(result.results.len(), 1);
+        assert_eq!(result.results[0].symbol.name, "LegacyClient");
+
+        let result = engine
+            .find_by_annotation("@Deprecated", &[SymbolType::Function], 10)
+            .await
+            .unwrap();
+        assert_eq!(result.total, 0);
+
+        assert!(engine.find_by_annotation("@", &[], 10).await.is_err());
     }
 
     #[tokio::test]
-    async fn test_find_by_signature_match_reason() {
+    async fn test_get_file_outline() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
-            let mut props = PropertyMap::new();
-            props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("testFunc".to_string()),
-            );
-            props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-            );
-            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            props.insert(
-                "return_type".to_string(),
-                codegraph::PropertyValue::String("bool".to_string()),
-            );
-            props.insert("param_count".to_string(), codegraph::PropertyValue::Int(2));
-            props.insert("is_async".to_string(), codegraph::PropertyValue::Bool(true));
-            g.add_node(NodeType::Function, props)
-                .expect("Failed to add node");
+            let shape = add_type(&mut g, NodeType::Class, "Shape", "/src/shapes.py");
+            let area = add_function(&mut g, "area", "/src/shapes.py", 3);
+            let scale = add_function(&mut g, "scale", "/src/shapes.py", 7);
+            let helper = add_function(&mut g, "helper", "/src/shapes.py", 8);
+            let main = add_function(&mut g, "main", "/src/shapes.py", 22);
+            add_function(&mut g, "other", "/src/other.py", 1);
+            for (id, line_start, line_end) in [
+                (shape, 1, 20),
+                (area, 3, 5),
+                (scale, 7, 12),
+                (helper, 8, 10),
+                (main, 22, 30),
+            ] {
+                g.update_node_properties(
+                    id,
+                    PropertyMap::new()
+                        .with("line_start", codegraph::PropertyValue::Int(line_start))
+                        .with("line_end", codegraph::PropertyValue::Int(line_end)),
+                )
+                .unwrap();
+            }
         }
 
-        engine.build_indexes().await;
+        let outline = engine.get_file_outline("/src/shapes.py").await.unwrap();
+        assert_eq!(outline.total_symbols, 5);
 
-        let pattern = SignaturePattern {
-            name_pattern: Some("test.*".to_string()),
-            return_type: Some("bool".to_string()),
-            param_count: Some((2, 2)),
-            modifiers: vec!["async".to_string()],
-        };
+        fn names(nodes: &[OutlineNode]) -> Vec<&str> {
+            nodes.iter().map(|n| n.symbol.name.as_str()).collect()
+        }
+        assert_eq!(names(&outline.symbols), vec!["Shape", "main"]);
+        let shape = &outline.symbols[0];
+        assert_eq!(names(&shape.children), vec!["area", "scale"]);
+        assert_eq!(names(&shape.children[1].children), vec!["helper"]);
+        assert_eq!(shape.symbol.location.end_line, 20);
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        assert!(engine.get_file_outline("/src/missing.py").await.is_none());
+    }
 
-        assert_eq!(results.len(), 1);
-        let match_reason = &results[0].match_reason;
-        assert!(match_reason.contains("name matches /test.*/"));
-        assert!(match_reason.contains("returns bool"));
-        assert!(match_reason.contains("2 parameters"));
-        assert!(match_reason.contains("modifiers: async"));
+    #[test]
+    fn test_pagerank_sums_to_one_and_favors_sinks() {
+        // 0 -> 2, 1 -> 2, 2 -> 3
+        let adjacency = vec![vec![2], vec![2], vec![3], vec![]];
+        let ranks = pagerank(&adjacency);
+        let total: f64 = ranks.iter().sum();
+        assert!((total - 1.0).abs() < 1e-6);
+        assert!(ranks[3] > ranks[2]);
+        assert!(ranks[2] > ranks[0]);
+    }
+
+    #[test]
+    fn test_betweenness_chain() {
+        // 0 -> 1 -> 2: only the middle node lies on a shortest path
+        let adjacency = vec![vec![1], vec![2], vec![]];
+        let scores = betweenness(&adjacency, BETWEENNESS_MAX_SOURCES);
+        assert_eq!(scores, vec![0.0, 1.0, 0.0]);
     }
 
     #[tokio::test]
-    async fn test_type_matches_wildcard() {
+    async fn test_find_type_usages() {
         let (engine, graph) = create_test_engine().await;
 
+        let user_type;
         {
             let mut g = graph.write().await;
 
-            for (name, return_type) in [
-                ("fn1", "Result<String, Error>"),
-                ("fn2", "Result<i32, Error>"),
-                ("fn3", "Option<String>"),
-            ] {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/lib.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                props.insert(
-                    "return_type".to_string(),
-                    codegraph::PropertyValue::String(return_type.to_string()),
-                );
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
-            }
+            let mut class_props = PropertyMap::new();
+            class_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("User".to_string()),
+            );
+            class_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/models.ts".to_string()),
+            );
+            user_type = g.add_node(NodeType::Class, class_props).unwrap();
+
+            let save = add_function(&mut g, "saveUser", "/src/repo.ts", 10);
+            let create = add_function(&mut g, "createUser", "/src/service.ts", 5);
+            let unrelated = add_function(&mut g, "userCount", "/src/stats.ts", 1);
+            g.add_edge(save, user_type, EdgeType::References, PropertyMap::new())
+                .unwrap();
+            g.add_edge(
+                create,
+                user_type,
+                EdgeType::Instantiates,
+                PropertyMap::new(),
+            )
+            .unwrap();
+            g.add_edge(unrelated, save, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+
+            // A class field typed as User
+            let mut session_props = PropertyMap::new();
+            session_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("Session".to_string()),
+            );
+            session_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/session.ts".to_string()),
+            );
+            let session = g.add_node(NodeType::Class, session_props).unwrap();
+            let mut field_props = PropertyMap::new();
+            field_props.insert(
+                "name".to_string(),
+                codegraph::PropertyValue::String("owner".to_string()),
+            );
+            field_props.insert(
+                "path".to_string(),
+                codegraph::PropertyValue::String("/src/session.ts".to_string()),
+            );
+            field_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(3));
+            let field = g.add_node(NodeType::Variable, field_props).unwrap();
+            g.add_edge(session, field, EdgeType::Contains, PropertyMap::new())
+                .unwrap();
+            g.add_edge(field, user_type, EdgeType::Uses, PropertyMap::new())
+                .unwrap();
         }
 
-        engine.build_indexes().await;
-
-        // Search for Result<*, Error> pattern
-        let pattern = SignaturePattern {
-            name_pattern: None,
-            return_type: Some("Result<*, Error>".to_string()),
-            param_count: None,
-            modifiers: vec![],
-        };
+        let result = engine
+            .find_type_usages(user_type, 50)
+            .await
+            .expect("type node exists");
 
-        let results = engine.find_by_signature(&pattern, None).await;
+        assert_eq!(result.type_name, "User");
+        let names: Vec<&str> = result
+            .usages
+            .iter()
+            .map(|u| u.symbol.name.as_str())
+            .collect();
+        assert!(names.contains(&"saveUser"));
+        assert!(names.contains(&"createUser"));
+        assert!(names.contains(&"owner"));
+        assert!(!names.contains(&"userCount"));
 
-        assert_eq!(results.len(), 2);
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(names.contains(&"fn1"));
-        assert!(names.contains(&"fn2"));
+        let via_field = result
+            .usages
+            .iter()
+            .find(|u| u.symbol.name == "Session")
+            .expect("containing class reported");
+        assert_eq!(via_field.via.as_deref(), Some("owner"));
+        assert_eq!(result.total_usages, 4);
     }
 
-    // ==========================================
-    // detect_entry_type tests
-    // ==========================================
-
     #[tokio::test]
-    async fn test_detect_entry_type_http_handler() {
+    async fn test_symbol_search_regex_mode() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
+            add_function(&mut g, "useUserController", "/src/a.ts", 1);
+            add_function(&mut g, "useController", "/src/b.ts", 1);
+            add_function(&mut g, "UserController", "/src/c.ts", 1);
+
             let mut props = PropertyMap::new();
             props.insert(
                 "name".to_string(),
-                codegraph::PropertyValue::String("getUsers".to_string()),
+                codegraph::PropertyValue::String("loadAll".to_string()),
             );
             props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/handlers.rs".to_string()),
-            );
-            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            props.insert(
-                "route".to_string(),
-                codegraph::PropertyValue::String("/api/users".to_string()),
+                codegraph::PropertyValue::String("/src/d.ts".to_string()),
             );
             props.insert(
-                "http_method".to_string(),
-                codegraph::PropertyValue::String("GET".to_string()),
+                "signature".to_string(),
+                codegraph::PropertyValue::String(
+                    "function loadAll(ctrl: useController)".to_string(),
+                ),
             );
-            g.add_node(NodeType::Function, props)
-                .expect("Failed to add node");
+            g.add_node(NodeType::Function, props).unwrap();
         }
 
-        engine.build_indexes().await;
+        let options = SearchOptions::new()
+            .with_mode(SearchMode::Regex)
+            .include_private();
+        let result = engine.symbol_search("^use.*Controller$", &options).await;
 
-        let results = engine.find_entry_points(&[EntryType::HttpHandler]).await;
+        let names: Vec<&str> = result
+            .results
+            .iter()
+            .map(|m| m.symbol.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["useController", "useUserController"]);
+        assert!(result.results.iter().all(|m| m.match_reason == "RegexName"));
+
+        // Signature-only matches rank below name matches
+        let result = engine.symbol_search("useController", &options).await;
+        assert_eq!(result.results.len(), 2);
+        assert_eq!(result.results[0].symbol.name, "useController");
+        assert_eq!(result.results[1].symbol.name, "loadAll");
+        assert_eq!(result.results[1].match_reason, "RegexSignature");
+    }
 
-        assert_eq!(results.len(), 1);
-        assert_eq!(results[0].symbol.name, "getUsers");
-        assert!(matches!(results[0].entry_type, EntryType::HttpHandler));
-        assert_eq!(results[0].route, Some("/api/users".to_string()));
-        assert_eq!(results[0].method, Some("GET".to_string()));
+    #[test]
+    fn test_compile_search_regex_limits() {
+        assert!(QueryEngine::compile_search_regex("^get[A-Z]\\w+$").is_ok());
+        assert!(QueryEngine::compile_search_regex("(unclosed").is_err());
+        assert!(QueryEngine::compile_search_regex(&"a".repeat(REGEX_MAX_PATTERN_LEN + 1)).is_err());
+        // Huge counted repetition exceeds the compiled size limit
+        assert!(QueryEngine::compile_search_regex("\\w{1000}{1000}").is_err());
     }
 
     #[tokio::test]
-    async fn test_detect_entry_type_cli_command() {
+    async fn test_symbol_search_fuzzy_misspelling() {
         let (engine, graph) = create_test_engine().await;
 
         {
             let mut g = graph.write().await;
+            add_function(&mut g, "getUserAccount", "/src/accounts.ts", 1);
+            add_function(&mut g, "renderSidebar", "/src/ui.ts", 1);
+        }
+        engine.build_indexes().await;
 
-            // CLI command by name
+        let options = SearchOptions::new().include_private();
+        let results = engine.symbol_search("getUserAcount", &options).await;
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].symbol.name, "getUserAccount");
+
+        // No BM25 token overlap at all: only the fuzzy path can find it
+        let results = engine.symbol_search("usraccount", &options).await;
+        assert_eq!(results.results.len(), 1);
+        assert_eq!(results.results[0].match_reason, "Fuzzy");
+    }
+
+    #[tokio::test]
+    async fn test_persisted_indexes_roundtrip_and_staleness() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("accounts.ts");
+        std::fs::write(&src, "function getUserAccount() {}").unwrap();
+        let src_path = src.to_str().unwrap();
+        let index_dir = crate::ai_query::index_dir(tmp.path());
+
+        let (engine, graph) = create_test_engine().await;
+        {
+            let mut g = graph.write().await;
             let mut props = PropertyMap::new();
             props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("runCommand".to_string()),
-            );
-            props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/cli.rs".to_string()),
-            );
-            props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, props)
-                .expect("Failed to add node");
-
-            // CLI command by property
-            let mut props2 = PropertyMap::new();
-            props2.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("execute".to_string()),
-            );
-            props2.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/cli.rs".to_string()),
+                codegraph::PropertyValue::String(src_path.to_string()),
             );
-            props2.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            props2.insert("is_cli".to_string(), codegraph::PropertyValue::Bool(true));
-            g.add_node(NodeType::Function, props2)
-                .expect("Failed to add node");
+            g.add_node(NodeType::CodeFile, props).unwrap();
+            add_function(&mut g, "getUserAccount", src_path, 1);
         }
-
         engine.build_indexes().await;
+        engine.save_indexes(&index_dir).await.unwrap();
 
-        let results = engine.find_entry_points(&[EntryType::CliCommand]).await;
+        // A fresh engine over the same graph loads the snapshot without rebuilding
+        let restored = QueryEngine::new(Arc::clone(&graph));
+        assert!(restored.load_indexes(&index_dir).await);
+        let options = SearchOptions::new().include_private();
+        let results = restored.symbol_search("getUserAccount", &options).await;
+        assert_eq!(results.results.len(), 1);
 
-        assert_eq!(results.len(), 2);
+        // Editing a source file invalidates the snapshot
+        std::fs::write(&src, "function getUserAccount(id) {}").unwrap();
+        let stale = QueryEngine::new(Arc::clone(&graph));
+        assert!(!stale.load_indexes(&index_dir).await);
+
+        // A changed graph invalidates it too
+        engine.save_indexes(&index_dir).await.unwrap();
+        add_function(&mut *graph.write().await, "renderSidebar", src_path, 9);
+        assert!(!stale.load_indexes(&index_dir).await);
     }
 
     #[tokio::test]
-    async fn test_detect_entry_type_event_handler() {
+    async fn test_find_member_implementations() {
         let (engine, graph) = create_test_engine().await;
 
+        let (find, sql_find, cached_find, helper);
         {
             let mut g = graph.write().await;
-
-            // Use names that match the actual detection patterns:
-            // - starts_with("on_")
-            // - starts_with("handle_")
-            // - ends_with("_handler")
-            // - ends_with("_callback")
-            // Note: Avoid "cli" in names as it triggers CliCommand detection first
-            for name in [
-                "on_submit",
-                "handle_submit",
-                "button_handler",
-                "data_callback",
+            let repo = add_type(&mut g, NodeType::Interface, "Repository", "/src/repo.ts");
+            let sql = add_type(&mut g, NodeType::Class, "SqlRepository", "/src/sql.ts");
+            let cached = add_type(&mut g, NodeType::Class, "CachedRepository", "/src/cache.ts");
+            find = add_function(&mut g, "find", "/src/repo.ts", 2);
+            sql_find = add_function(&mut g, "find", "/src/sql.ts", 5);
+            cached_find = add_function(&mut g, "find", "/src/cache.ts", 8);
+            let sql_save = add_function(&mut g, "save", "/src/sql.ts", 9);
+            helper = add_function(&mut g, "find", "/src/util.ts", 1);
+            for (container, member) in [
+                (repo, find),
+                (sql, sql_find),
+                (sql, sql_save),
+                (cached, cached_find),
             ] {
-                let mut props = PropertyMap::new();
-                props.insert(
-                    "name".to_string(),
-                    codegraph::PropertyValue::String(name.to_string()),
-                );
-                props.insert(
-                    "path".to_string(),
-                    codegraph::PropertyValue::String("/src/events.rs".to_string()),
-                );
-                props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-                g.add_node(NodeType::Function, props)
-                    .expect("Failed to add node");
+                g.add_edge(container, member, EdgeType::Contains, PropertyMap::new())
+                    .unwrap();
             }
+            g.add_edge(sql, repo, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+            g.add_edge(cached, sql, EdgeType::Extends, PropertyMap::new())
+                .unwrap();
         }
 
-        engine.build_indexes().await;
+        let implementations = engine.find_member_implementations(find).await.unwrap();
+        assert_eq!(implementations, vec![cached_find, sql_find]);
+        // A free function is not a member
+        assert!(engine.find_member_implementations(helper).await.is_none());
+    }
 
-        let results = engine.find_entry_points(&[EntryType::EventHandler]).await;
+    #[tokio::test]
+    async fn test_find_declarations() {
+        let (engine, graph) = create_test_engine().await;
 
-        assert_eq!(results.len(), 4);
+        let (find, sql, sql_find, parse, prototype, ts_parse, barrel_import);
+        {
+            let mut g = graph.write().await;
+            let repo = add_type(&mut g, NodeType::Interface, "Repository", "/src/repo.ts");
+            sql = add_type(&mut g, NodeType::Class, "SqlRepository", "/src/sql.ts");
+            find = add_function(&mut g, "find", "/src/repo.ts", 2);
+            sql_find = add_function(&mut g, "find", "/src/sql.ts", 5);
+            g.add_edge(repo, find, EdgeType::Contains, PropertyMap::new())
+                .unwrap();
+            g.add_edge(sql, sql_find, EdgeType::Contains, PropertyMap::new())
+                .unwrap();
+            g.add_edge(sql, repo, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+
+            parse = add_function(&mut g, "parse", "/src/parser.c", 10);
+            prototype = add_function(&mut g, "parse", "/include/parser.h", 3);
+            let parser_c = add_type(&mut g, NodeType::CodeFile, "parser.c", "/src/parser.c");
+            let parser_h = add_type(&mut g, NodeType::CodeFile, "parser.h", "/include/parser.h");
+            g.add_edge(parser_c, parser_h, EdgeType::Imports, PropertyMap::new())
+                .unwrap();
+            // Same name in a header parser.c does not include
+            add_function(&mut g, "parse", "/vendor/json.h", 7);
+            // and a definition outside C/C++
+            ts_parse = add_function(&mut g, "parse", "/src/parse.ts", 1);
+
+            let app_file = add_type(&mut g, NodeType::CodeFile, "app.ts", "/src/app.ts");
+            barrel_import = add_type(&mut g, NodeType::Import, "./index", "/src/app.ts");
+            let props = PropertyMap::new().with(
+                "symbols",
+                codegraph::PropertyValue::StringList(vec!["SqlRepository".to_string()]),
+            );
+            g.add_edge(app_file, barrel_import, EdgeType::Imports, props)
+                .unwrap();
+            g.add_edge(app_file, sql, EdgeType::Imports, PropertyMap::new())
+                .unwrap();
+        }
 
-        // Verify all expected patterns are detected
-        let names: Vec<&str> = results.iter().map(|r| r.symbol.name.as_str()).collect();
-        assert!(
-            names.contains(&"on_submit"),
-            "on_submit should be detected (starts with on_)"
+        assert_eq!(
+            engine.find_declarations(sql_find, "/src/sql.ts").await,
+            vec![find]
         );
-        assert!(
-            names.contains(&"handle_submit"),
-            "handle_submit should be detected (starts with handle_)"
+        assert_eq!(
+            engine.find_declarations(parse, "/src/main.c").await,
+            vec![prototype]
         );
-        assert!(
-            names.contains(&"button_handler"),
-            "button_handler should be detected (ends with _handler)"
+        assert!(engine
+            .find_declarations(ts_parse, "/src/parse.ts")
+            .await
+            .is_empty());
+        // A header prototype is a declaration, not a definition with one
+        assert!(engine
+            .find_declarations(prototype, "/src/main.c")
+            .await
+            .is_empty());
+
+        // The import statement in app.ts, not the direct file-to-symbol link
+        assert_eq!(
+            engine.find_declarations(sql, "/src/app.ts").await,
+            vec![barrel_import]
         );
-        assert!(
-            names.contains(&"data_callback"),
-            "data_callback should be detected (ends with _callback)"
+    }
+
+    #[tokio::test]
+    async fn test_find_implementations_both_directions() {
+        let (engine, graph) = create_test_engine().await;
+
+        let (repo, sql, cached);
+        {
+            let mut g = graph.write().await;
+            repo = add_type(&mut g, NodeType::Interface, "Repository", "/src/repo.ts");
+            sql = add_type(&mut g, NodeType::Class, "SqlRepository", "/src/sql.ts");
+            let mem = add_type(&mut g, NodeType::Class, "MemoryRepository", "/src/mem.ts");
+            cached = add_type(
+                &mut g,
+                NodeType::Class,
+                "CachedSqlRepository",
+                "/src/cache.ts",
+            );
+            let client = add_function(&mut g, "loadUsers", "/src/app.ts", 1);
+            g.add_edge(sql, repo, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+            g.add_edge(mem, repo, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+            g.add_edge(cached, sql, EdgeType::Extends, PropertyMap::new())
+                .unwrap();
+            g.add_edge(client, repo, EdgeType::References, PropertyMap::new())
+                .unwrap();
+        }
+
+        let result = engine
+            .find_implementations(repo, ImplementationDirection::Implementations, 10)
+            .await
+            .unwrap();
+        assert_eq!(result.target_name, "Repository");
+        assert_eq!(result.total_implementations, 2);
+        let names: Vec<&str> = result
+            .implementations
+            .iter()
+            .map(|i| i.symbol.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["MemoryRepository", "SqlRepository"]);
+
+        let subclasses = engine
+            .find_implementations(sql, ImplementationDirection::Implementations, 10)
+            .await
+            .unwrap();
+        assert_eq!(subclasses.implementations.len(), 1);
+        assert_eq!(subclasses.implementations[0].node_id, cached);
+        assert_eq!(
+            subclasses.implementations[0].relation,
+            EdgeType::Extends.to_string()
         );
+
+        let supertypes = engine
+            .find_implementations(sql, ImplementationDirection::Supertypes, 10)
+            .await
+            .unwrap();
+        assert_eq!(supertypes.implementations.len(), 1);
+        assert_eq!(supertypes.implementations[0].node_id, repo);
     }
 
     #[tokio::test]
-    async fn test_find_entry_points_all_types() {
+    async fn test_get_type_hierarchy_transitive_with_cycle() {
         let (engine, graph) = create_test_engine().await;
 
+        let (shape, polygon, square);
         {
             let mut g = graph.write().await;
+            shape = add_type(&mut g, NodeType::Interface, "Shape", "/src/shape.ts");
+            let drawable = add_type(&mut g, NodeType::Interface, "Drawable", "/src/draw.ts");
+            polygon = add_type(&mut g, NodeType::Class, "Polygon", "/src/polygon.ts");
+            square = add_type(&mut g, NodeType::Class, "Square", "/src/square.ts");
+            g.add_edge(polygon, shape, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+            g.add_edge(polygon, drawable, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+            g.add_edge(square, polygon, EdgeType::Extends, PropertyMap::new())
+                .unwrap();
+            // Diamond: Square also implements Shape directly
+            g.add_edge(square, shape, EdgeType::Implements, PropertyMap::new())
+                .unwrap();
+        }
 
-            // Main
-            let mut main_props = PropertyMap::new();
-            main_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("main".to_string()),
-            );
-            main_props.insert(
-                "path".to_string(),
-                codegraph::PropertyValue::String("/src/main.rs".to_string()),
-            );
-            main_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(1));
-            g.add_node(NodeType::Function, main_props)
-                .expect("Failed to add main");
+        let down = engine
+            .get_type_hierarchy(shape, TypeHierarchyDirection::Subtypes, None)
+            .await
+            .unwrap();
+        assert_eq!(down.root.name, "Shape");
+        assert_eq!(down.nodes.len(), 2);
+        assert!(down.nodes.iter().all(|n| n.depth == 1));
+        assert!(!down.has_cycle);
+
+        let up = engine
+            .get_type_hierarchy(square, TypeHierarchyDirection::Supertypes, None)
+            .await
+            .unwrap();
+        let names: HashSet<&str> = up.nodes.iter().map(|n| n.symbol.name.as_str()).collect();
+        assert_eq!(names, HashSet::from(["Polygon", "Shape", "Drawable"]));
+        assert_eq!(up.max_depth, 2);
+        assert!(!up.has_cycle);
+
+        let shallow = engine
+            .get_type_hierarchy(square, TypeHierarchyDirection::Supertypes, Some(1))
+            .await
+            .unwrap();
+        assert_eq!(shallow.nodes.len(), 2);
+        assert!(shallow.truncated);
+
+        // Malformed input: Polygon extends Square, closing a loop
+        graph
+            .write()
+            .await
+            .add_edge(polygon, square, EdgeType::Extends, PropertyMap::new())
+            .unwrap();
+        let looped = engine
+            .get_type_hierarchy(square, TypeHierarchyDirection::Supertypes, None)
+            .await
+            .unwrap();
+        assert!(looped.has_cycle);
+        assert_eq!(looped.nodes.len(), 3);
+    }
 
-            // Test
-            let mut test_props = PropertyMap::new();
-            test_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("test_something".to_string()),
+    #[tokio::test]
+    async fn test_reaches_graph_and_external_sinks() {
+        let (engine, graph) = create_test_engine().await;
+
+        let (handler, save, render);
+        {
+            let mut g = graph.write().await;
+            handler = add_function(&mut g, "handleUpload", "/src/api.ts", 1);
+            let validate = add_function(&mut g, "validate", "/src/api.ts", 20);
+            save = add_function(&mut g, "saveBlob", "/src/storage.ts", 1);
+            render = add_function(&mut g, "render", "/src/view.ts", 1);
+            g.add_edge(handler, validate, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(validate, save, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(save, handler, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            let mut props = PropertyMap::new();
+            props.insert(
+                "unresolved_calls".to_string(),
+                codegraph::PropertyValue::StringList(vec!["fs.writeFile".to_string()]),
             );
-            test_props.insert(
+            g.update_node_properties(save, props).unwrap();
+        }
+
+        let predicate = ReachabilityPredicate::new().with_categories(vec!["filesystem".into()]);
+        let result = engine
+            .reaches(handler, &predicate, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(result.reachable);
+        assert_eq!(result.matches.len(), 1);
+        let hit = &result.matches[0];
+        assert_eq!(hit.node_id, save);
+        assert!(hit.external);
+        assert_eq!(hit.sink, "fs.writeFile");
+        assert_eq!(hit.category.as_deref(), Some("filesystem"));
+        assert_eq!(hit.path, vec!["handleUpload", "validate", "saveBlob"]);
+
+        // Graph-level sink by name, cut off by the depth cap
+        let predicate = ReachabilityPredicate::new()
+            .with_sinks(vec!["save*".into()])
+            .with_max_depth(1);
+        let result = engine
+            .reaches(handler, &predicate, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(!result.reachable);
+        assert!(result.truncated);
+
+        // Edge filter excludes Calls entirely
+        let predicate = ReachabilityPredicate::new()
+            .with_sinks(vec!["saveBlob".into()])
+            .with_edge_types(vec!["imports".into()]);
+        let result = engine
+            .reaches(handler, &predicate, &CancellationToken::new())
+            .await
+            .unwrap();
+        assert!(!result.reachable);
+
+        assert!(engine
+            .reaches(
+                render,
+                &ReachabilityPredicate::new(),
+                &CancellationToken::new()
+            )
+            .await
+            .is_err());
+        let unknown = ReachabilityPredicate::new().with_categories(vec!["telepathy".into()]);
+        assert!(engine
+            .reaches(render, &unknown, &CancellationToken::new())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_path_shortest_by_edge_type() {
+        let (engine, graph) = create_test_engine().await;
+
+        let (handler, validate, save, render);
+        {
+            let mut g = graph.write().await;
+            handler = add_function(&mut g, "handleUpload", "/src/api.ts", 1);
+            validate = add_function(&mut g, "validate", "/src/api.ts", 20);
+            save = add_function(&mut g, "saveBlob", "/src/storage.ts", 1);
+            render = add_function(&mut g, "render", "/src/view.ts", 1);
+            g.add_edge(handler, validate, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(validate, save, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            g.add_edge(handler, save, EdgeType::References, PropertyMap::new())
+                .unwrap();
+        }
+        let cancel = CancellationToken::new();
+
+        // Any edge type: the direct reference wins
+        let result = engine
+            .find_path(handler, save, &[], 10, &cancel)
+            .await
+            .unwrap();
+        assert!(result.found);
+        let steps: Vec<(&str, Option<&str>)> = result
+            .path
+            .iter()
+            .map(|s| (s.symbol.name.as_str(), s.edge_type.as_deref()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![("handleUpload", None), ("saveBlob", Some("References"))]
+        );
+
+        // Calls only: through validate
+        let calls = vec!["calls".to_string()];
+        let result = engine
+            .find_path(handler, save, &calls, 10, &cancel)
+            .await
+            .unwrap();
+        let names: Vec<&str> = result.path.iter().map(|s| s.symbol.name.as_str()).collect();
+        assert_eq!(names, vec!["handleUpload", "validate", "saveBlob"]);
+
+        // Cut off by the depth cap
+        let result = engine
+            .find_path(handler, save, &calls, 1, &cancel)
+            .await
+            .unwrap();
+        assert!(!result.found);
+        assert!(result.truncated);
+        assert!(result.path.is_empty());
+
+        // Edges are followed in their direction only
+        let result = engine
+            .find_path(save, handler, &[], 10, &cancel)
+            .await
+            .unwrap();
+        assert!(!result.found);
+        assert!(!result.truncated);
+        let result = engine
+            .find_path(handler, render, &[], 10, &cancel)
+            .await
+            .unwrap();
+        assert!(!result.found);
+    }
+
+    #[test]
+    fn test_normalize_code_tokens() {
+        let tokens = normalize_code_tokens("if x > 10 { return \"a\\\"b\"; } // done\n'c'");
+        assert_eq!(
+            tokens,
+            vec!["if", "ID", ">", "NUM", "{", "return", "STR", ";", "}", "STR"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_similar_code_structural() {
+        let (engine, graph) = create_test_engine().await;
+
+        let (original, renamed, unrelated);
+        {
+            let mut g = graph.write().await;
+            original = add_function(&mut g, "sum_prices", "/src/cart.rs", 1);
+            renamed = add_function(&mut g, "total_weight", "/src/ship.rs", 1);
+            unrelated = add_function(&mut g, "parse_args", "/src/cli.rs", 1);
+            for (id, source) in [
+                (
+                    original,
+                    "fn sum_prices(items: &[Item]) -> u64 {\n    let mut total = 0;\n    \
+                     for item in items {\n        if item.active {\n            \
+                     total += item.price * 2;\n        }\n    }\n    total\n}",
+                ),
+                (
+                    renamed,
+                    "fn total_weight(parcels: &[Parcel]) -> u64 {\n    // weigh it all\n    \
+                     let mut acc = 0;\n    for p in parcels {\n        if p.ready {\n            \
+                     acc += p.weight * 3;\n        }\n    }\n    acc\n}",
+                ),
+                (
+                    unrelated,
+                    "fn parse_args() -> Vec<String> {\n    \
+                     std::env::args().skip(1).collect::<Vec<_>>()\n}",
+                ),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "source".to_string(),
+                    codegraph::PropertyValue::String(source.to_string()),
+                );
+                g.update_node_properties(id, props).unwrap();
+            }
+        }
+
+        // No embeddings, so auto falls back to structural
+        let result = engine
+            .find_similar_code(original, SimilarityMode::Auto, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(result.mode, SimilarityMode::Structural);
+        assert_eq!(result.total_compared, 2);
+        assert_eq!(result.matches.len(), 1);
+        assert_eq!(result.matches[0].node_id, renamed);
+        assert!(result.matches[0].similarity > 0.99);
+
+        assert!(engine
+            .find_similar_code(original, SimilarityMode::Semantic, None, 10)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_config_usages() {
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("db.js");
+        std::fs::write(
+            &src,
+            "const port = process.env.PORT;
+             function connect() {
+               return open(process.env.DATABASE_URL, process.env.DATABASE_POOL);
+             }
+",
+        )
+        .unwrap();
+        let src_path = src.to_str().unwrap();
+
+        let (engine, graph) = create_test_engine().await;
+        let connect;
+        {
+            let mut g = graph.write().await;
+            let mut props = PropertyMap::new();
+            props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/tests.rs".to_string()),
+                codegraph::PropertyValue::String(src_path.to_string()),
             );
-            test_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(10));
-            g.add_node(NodeType::Function, test_props)
-                .expect("Failed to add test");
+            g.add_node(NodeType::CodeFile, props).unwrap();
+            connect = add_function(&mut g, "connect", src_path, 2);
+            let mut props = PropertyMap::new();
+            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(4));
+            g.update_node_properties(connect, props).unwrap();
+        }
 
-            // Public API
-            let mut api_props = PropertyMap::new();
-            api_props.insert(
-                "name".to_string(),
-                codegraph::PropertyValue::String("someApi".to_string()),
-            );
-            api_props.insert(
+        let result = engine
+            .find_config_usages("DATABASE_*", None, 10)
+            .await
+            .unwrap();
+        assert_eq!(result.files_scanned, 1);
+        assert_eq!(result.keys, vec!["DATABASE_POOL", "DATABASE_URL"]);
+        assert_eq!(result.usages.len(), 2);
+        assert_eq!(result.usages[0].key, "DATABASE_URL");
+        assert_eq!(result.usages[0].line, 3);
+        assert_eq!(result.usages[0].node_id, Some(connect));
+
+        // Top-level read has no enclosing symbol; unchanged files are not rescanned
+        let result = engine
+            .find_config_usages("PORT", Some(ConfigSource::Env), 10)
+            .await
+            .unwrap();
+        assert_eq!(result.files_scanned, 0);
+        assert_eq!(result.usages.len(), 1);
+        assert!(result.usages[0].node_id.is_none());
+
+        assert!(engine
+            .find_config_usages("PORT", Some(ConfigSource::Config), 10)
+            .await
+            .unwrap()
+            .usages
+            .is_empty());
+        assert!(engine.find_config_usages("[", None, 10).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_find_todos() {
+        let source = "// TODO: split this module
+fn sync() {
+    // FIXME(bob): retry on timeout
+    // SAFETY: buffer outlives the call
+}
+";
+        let src_path = "/src/sync.rs";
+
+        let (engine, graph) = create_test_engine().await;
+        let sync;
+        {
+            let mut g = graph.write().await;
+            let mut props = PropertyMap::new();
+            props.insert(
                 "path".to_string(),
-                codegraph::PropertyValue::String("/src/lib.rs".to_string()),
+                codegraph::PropertyValue::String(src_path.to_string()),
             );
-            api_props.insert("line_start".to_string(), codegraph::PropertyValue::Int(20));
-            api_props.insert("exported".to_string(), codegraph::PropertyValue::Bool(true));
-            g.add_node(NodeType::Function, api_props)
-                .expect("Failed to add api");
+            let file_id = g.add_node(NodeType::CodeFile, props).unwrap();
+            sync = add_function(&mut g, "sync", src_path, 2);
+            let mut props = PropertyMap::new();
+            props.insert("line_end".to_string(), codegraph::PropertyValue::Int(5));
+            g.update_node_properties(sync, props).unwrap();
+            let file_info = codegraph_parser_api::FileInfo {
+                file_path: src_path.into(),
+                file_id,
+                functions: vec![sync],
+                classes: vec![],
+                traits: vec![],
+                imports: vec![],
+                parse_time: std::time::Duration::ZERO,
+                line_count: 0,
+                byte_count: 0,
+            };
+            super::super::record_todos(&mut g, &file_info, Path::new(src_path), source);
         }
-
         engine.build_indexes().await;
 
-        // Find all entry points (empty filter)
-        let results = engine.find_entry_points(&[]).await;
-
-        // Should find main, test, and public API
-        assert!(results.len() >= 3);
+        let result = engine.find_todos(&[], None).await;
+        let tags: Vec<TodoTag> = result.todos.iter().map(|t| t.tag).collect();
+        assert_eq!(tags, vec![TodoTag::Todo, TodoTag::Fixme, TodoTag::Safety]);
+        assert!(result.todos[0].node_id.is_none());
+        assert_eq!(result.todos[1].node_id, Some(sync));
+        assert_eq!(result.todos[1].owner.as_deref(), Some("bob"));
+
+        let result = engine.find_todos(&[TodoTag::Fixme], Some("sync.rs")).await;
+        assert_eq!(result.todos.len(), 1);
+        assert_eq!(result.todos[0].text, "retry on timeout");
+        assert!(engine
+            .find_todos(&[], Some("/other/"))
+            .await
+            .todos
+            .is_empty());
+
+        // Comment nodes are not symbols
+        let results = engine.symbol_search("retry", &SearchOptions::new()).await;
+        assert!(results.results.is_empty());
     }
 }
@@ -0,0 +1,530 @@
+//! Entry point detection rules.
+//!
+//! `detect_entry_type` walks an ordered rule list and returns the entry type of
+//! the first rule that matches. The built-in rules cover common frameworks
+//! (Express, FastAPI/Flask, actix, Spring, Go net/http, test runners) followed by the
+//! generic naming heuristics. Workspaces add their own rules in
+//! `<workspace>/.codegraph/entry_points.json`; they are tried before the
+//! built-ins:
+//!
+//! ```json
+//! {
+//!   "rules": [
+//!     { "entryType": "http_handler", "framework": "internal-rpc",
+//!       "attributes": ["^@rpc\\.method\\("] },
+//!     { "entryType": "event_handler", "names": ["^on[A-Z]"],
+//!       "paths": ["/listeners/"] }
+//!   ]
+//! }
+//! ```
+//!
+//! Each criterion is a list of regexes of which any may match; a rule matches
+//! when every criterion it sets matches. `properties` maps a node property to
+//! `true` (set and non-empty/true), `false` (unset) or a regex for its value.
+//! Set `"replaceDefaults": true` to drop the built-in rules entirely.
+
+use super::primitives::EntryType;
+use crate::domain::node_props;
+use regex::{Regex, RegexSet};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+// ============================================================
+// Configuration
+// ============================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub(crate) enum PropertyMatcher {
+    /// Property must be set (true) or unset (false)
+    Present(bool),
+    /// Regex matched against the string value of the property
+    Pattern(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EntryRuleConfig {
+    pub entry_type: EntryType,
+    /// Informational framework label, e.g. "fastapi"
+    #[serde(default)]
+    pub framework: Option<String>,
+    /// Regexes matched against the symbol name
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Regexes matched against decorators/attributes recorded by the parser
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    /// Regexes matched against the file path
+    #[serde(default)]
+    pub paths: Vec<String>,
+    /// Node property matchers, any of which may match
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyMatcher>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct EntryRulesConfig {
+    #[serde(default)]
+    pub rules: Vec<EntryRuleConfig>,
+    /// Use only the workspace rules, without the built-in ones
+    #[serde(default)]
+    pub replace_defaults: bool,
+}
+
+/// Location of the entry point rules for a workspace.
+pub(crate) fn config_path(workspace: &Path) -> PathBuf {
+    workspace.join(".codegraph").join("entry_points.json")
+}
+
+/// Load the workspace entry point rules. Returns Ok(None) if there are none.
+pub(crate) fn load_config(workspace: &Path) -> Result<Option<EntryRulesConfig>, String> {
+    let path = config_path(workspace);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+// ============================================================
+// Built-in rules
+// ============================================================
+
+fn rule(
+    entry_type: EntryType,
+    framework: Option<&str>,
+    names: &[&str],
+    attributes: &[&str],
+    paths: &[&str],
+    properties: &[(&str, PropertyMatcher)],
+) -> EntryRuleConfig {
+    let strings = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect();
+    EntryRuleConfig {
+        entry_type,
+        framework: framework.map(str::to_string),
+        names: strings(names),
+        attributes: strings(attributes),
+        paths: strings(paths),
+        properties: properties
+            .iter()
+            .map(|(key, matcher)| (key.to_string(), matcher.clone()))
+            .collect(),
+    }
+}
+
+/// Built-in rules, in priority order.
+pub(crate) fn default_rules() -> Vec<EntryRuleConfig> {
+    use EntryType::*;
+    use PropertyMatcher::{Pattern, Present};
+
+    vec![
+        // Route metadata set by runtime_deps::detect_route_handlers
+        rule(
+            HttpHandler,
+            None,
+            &[],
+            &[],
+            &[],
+            &[("route", Present(true)), ("http_method", Present(true))],
+        ),
+        rule(
+            HttpHandler,
+            Some("fastapi"),
+            &[],
+            &[
+                r"^@?(app|router|api|bp|blueprint)\.(get|post|put|patch|delete|head|options|route|api_route|websocket)\(",
+            ],
+            &[],
+            &[],
+        ),
+        rule(
+            HttpHandler,
+            Some("actix"),
+            &[],
+            &[r"^(#\[)?(actix_web::)?(get|post|put|patch|delete|head|route)\("],
+            &[],
+            &[],
+        ),
+        rule(
+            HttpHandler,
+            Some("spring"),
+            &[],
+            &[r"^@?(Get|Post|Put|Patch|Delete|Request)Mapping\b"],
+            &[],
+            &[],
+        ),
+        // Express handlers are plain functions taking (req, res[, next])
+        rule(
+            HttpHandler,
+            Some("express"),
+            &[],
+            &[],
+            &[],
+            &[("signature", Pattern(r"\(\s*req\b[^)]*\bres\b".to_string()))],
+        ),
+        // Go net/http handlers take (http.ResponseWriter, *http.Request)
+        rule(
+            HttpHandler,
+            Some("net/http"),
+            &[],
+            &[],
+            &[],
+            &[(
+                "signature",
+                Pattern(r"\bhttp\.ResponseWriter\b[^)]*\*http\.Request\b".to_string()),
+            )],
+        ),
+        rule(
+            Main,
+            Some("tokio/actix"),
+            &[],
+            &[r"^(#\[)?(tokio|actix_web|async_std)::main\b"],
+            &[],
+            &[],
+        ),
+        rule(Main, None, &[r"^(main|__main__)$"], &[], &[], &[]),
+        rule(
+            TestEntry,
+            None,
+            &[],
+            &[
+                r"^(#\[)?((tokio|async_std)::)?test\b",
+                r"^@(Test|ParameterizedTest)\b",
+                r"^@pytest\.",
+            ],
+            &[],
+            &[],
+        ),
+        rule(
+            TestEntry,
+            None,
+            &[r"(?i)^test_", r"(?i)_test$", r"^Test"],
+            &[],
+            &[],
+            &[],
+        ),
+        rule(
+            TestEntry,
+            None,
+            &[],
+            &[],
+            &[],
+            &[("is_test", Present(true))],
+        ),
+        rule(
+            TestEntry,
+            None,
+            &[],
+            &[],
+            &[r"/(test|tests|__tests__)/", r"\.(test|spec)\.", r"_test\."],
+            &[],
+        ),
+        rule(
+            CliCommand,
+            Some("click/typer"),
+            &[],
+            &[r"^@?\w+\.command\b"],
+            &[],
+            &[],
+        ),
+        rule(CliCommand, None, &[r"(?i)command|cli"], &[], &[], &[]),
+        rule(
+            CliCommand,
+            None,
+            &[],
+            &[],
+            &[],
+            &[("is_cli", Present(true))],
+        ),
+        rule(
+            EventHandler,
+            Some("spring"),
+            &[],
+            &[r"^@(EventListener|KafkaListener|RabbitListener|JmsListener|Scheduled)\b"],
+            &[],
+            &[],
+        ),
+        rule(
+            EventHandler,
+            None,
+            &[r"(?i)^(on_|handle_)", r"(?i)(_handler|_callback)$"],
+            &[],
+            &[],
+            &[],
+        ),
+        rule(
+            PublicApi,
+            None,
+            &[],
+            &[],
+            &[],
+            &[
+                ("is_public", Present(true)),
+                ("exported", Present(true)),
+                ("visibility", Pattern("^(public|pub)$".to_string())),
+            ],
+        ),
+    ]
+}
+
+// ============================================================
+// Compiled rules
+// ============================================================
+
+struct CompiledRule {
+    entry_type: EntryType,
+    names: Option<RegexSet>,
+    attributes: Option<RegexSet>,
+    paths: Option<RegexSet>,
+    properties: Vec<(String, Option<Regex>, bool)>,
+}
+
+impl CompiledRule {
+    fn compile(index: usize, config: &EntryRuleConfig) -> Result<Self, String> {
+        let label = match &config.framework {
+            Some(framework) => format!("rule {index} ({framework})"),
+            None => format!("rule {index}"),
+        };
+        let set = |field: &str, patterns: &[String]| -> Result<Option<RegexSet>, String> {
+            if patterns.is_empty() {
+                return Ok(None);
+            }
+            RegexSet::new(patterns)
+                .map(Some)
+                .map_err(|e| format!("Invalid {field} pattern in {label}: {e}"))
+        };
+
+        let mut properties = Vec::with_capacity(config.properties.len());
+        for (key, matcher) in &config.properties {
+            properties.push(match matcher {
+                PropertyMatcher::Present(present) => (key.clone(), None, *present),
+                PropertyMatcher::Pattern(pattern) => {
+                    let regex = Regex::new(pattern).map_err(|e| {
+                        format!("Invalid pattern for property '{key}' in {label}: {e}")
+                    })?;
+                    (key.clone(), Some(regex), true)
+                }
+            });
+        }
+
+        let rule = Self {
+            entry_type: config.entry_type,
+            names: set("name", &config.names)?,
+            attributes: set("attribute", &config.attributes)?,
+            paths: set("path", &config.paths)?,
+            properties,
+        };
+        if rule.names.is_none()
+            && rule.attributes.is_none()
+            && rule.paths.is_none()
+            && rule.properties.is_empty()
+        {
+            return Err(format!(
+                "{label} has no names, attributes, paths or properties"
+            ));
+        }
+        Ok(rule)
+    }
+
+    fn matches(&self, node: &codegraph::Node, name: &str) -> bool {
+        if let Some(names) = &self.names {
+            if !names.is_match(name) {
+                return false;
+            }
+        }
+        if let Some(paths) = &self.paths {
+            if !paths.is_match(node_props::path(node)) {
+                return false;
+            }
+        }
+        if let Some(attributes) = &self.attributes {
+            let attrs = node
+                .properties
+                .get_string_list_compat("attributes")
+                .unwrap_or_default();
+            if !attrs.iter().any(|attr| attributes.is_match(attr.trim())) {
+                return false;
+            }
+        }
+        self.properties.is_empty()
+            || self
+                .properties
+                .iter()
+                .any(|(key, regex, present)| property_matches(node, key, regex.as_ref(), *present))
+    }
+}
+
+fn property_matches(
+    node: &codegraph::Node,
+    key: &str,
+    regex: Option<&Regex>,
+    present: bool,
+) -> bool {
+    if let Some(regex) = regex {
+        return node
+            .properties
+            .get_string(key)
+            .is_some_and(|value| regex.is_match(value));
+    }
+    let is_set = node.properties.get_bool(key).unwrap_or_else(|| {
+        node.properties
+            .get_string(key)
+            .is_some_and(|value| !value.is_empty())
+    });
+    is_set == present
+}
+
+/// Ordered, compiled entry point rules.
+pub(crate) struct EntryRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl Default for EntryRules {
+    fn default() -> Self {
+        Self::from_config(&EntryRulesConfig::default()).expect("built-in entry rules compile")
+    }
+}
+
+impl EntryRules {
+    /// Compile workspace rules followed by the built-in rules (unless replaced).
+    pub(crate) fn from_config(config: &EntryRulesConfig) -> Result<Self, String> {
+        let defaults = if config.replace_defaults {
+            Vec::new()
+        } else {
+            default_rules()
+        };
+        let rules = config
+            .rules
+            .iter()
+            .chain(defaults.iter())
+            .enumerate()
+            .map(|(index, rule)| CompiledRule::compile(index, rule))
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { rules })
+    }
+
+    /// Load and compile the rules for a workspace, falling back to the built-ins.
+    pub(crate) fn load(workspace: &Path) -> Result<Self, String> {
+        match load_config(workspace)? {
+            Some(config) => Self::from_config(&config),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Entry type of the first matching rule.
+    pub(crate) fn detect(&self, node: &codegraph::Node, name: &str) -> Option<EntryType> {
+        self.rules
+            .iter()
+            .find(|rule| rule.matches(node, name))
+            .map(|rule| rule.entry_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::{CodeGraph, NodeType, PropertyMap};
+
+    fn detect(rules: &EntryRules, props: PropertyMap) -> Option<EntryType> {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let id = graph.add_node(NodeType::Function, props).unwrap();
+        let node = graph.get_node(id).unwrap();
+        rules.detect(node, node_props::name(node))
+    }
+
+    fn function(name: &str) -> PropertyMap {
+        PropertyMap::new()
+            .with("name", name)
+            .with("path", "/src/app.py")
+    }
+
+    #[test]
+    fn test_default_rules_cover_framework_handlers() {
+        let rules = EntryRules::default();
+        let cases = [
+            (
+                function("list_users").with("attributes", "@router.get(\"/users\")"),
+                EntryType::HttpHandler,
+            ),
+            (
+                function("index").with("attributes", "#[get(\"/\")]"),
+                EntryType::HttpHandler,
+            ),
+            (
+                function("getUser").with("attributes", "@GetMapping(\"/users/{id}\")"),
+                EntryType::HttpHandler,
+            ),
+            (
+                function("listUsers").with("signature", "function listUsers(req, res)"),
+                EntryType::HttpHandler,
+            ),
+            (
+                function("getUser").with(
+                    "signature",
+                    "func (h *Handlers) getUser(w http.ResponseWriter, r *http.Request)",
+                ),
+                EntryType::HttpHandler,
+            ),
+            (
+                function("run").with("attributes", "#[tokio::main]"),
+                EntryType::Main,
+            ),
+            (
+                function("checks_parse").with("attributes", "#[tokio::test]"),
+                EntryType::TestEntry,
+            ),
+            (
+                function("consume").with("attributes", "@KafkaListener(topics = \"orders\")"),
+                EntryType::EventHandler,
+            ),
+            (function("main"), EntryType::Main),
+            (function("on_submit"), EntryType::EventHandler),
+        ];
+        for (props, expected) in cases {
+            assert_eq!(detect(&rules, props), Some(expected));
+        }
+        assert_eq!(detect(&rules, function("helper")), None);
+        assert_eq!(
+            detect(&rules, function("helper").with("visibility", "pub")),
+            Some(EntryType::PublicApi)
+        );
+    }
+
+    #[test]
+    fn test_workspace_rules_take_precedence() {
+        let config: EntryRulesConfig = serde_json::from_value(serde_json::json!({
+            "rules": [
+                { "entryType": "http_handler", "framework": "rpc", "attributes": ["^@rpc\\.method"] },
+                { "entryType": "event_handler", "names": ["^handle_"], "paths": ["/jobs/"] }
+            ]
+        }))
+        .unwrap();
+        let rules = EntryRules::from_config(&config).unwrap();
+
+        let rpc = function("get_user").with("attributes", "@rpc.method(\"user.get\")");
+        assert_eq!(detect(&rules, rpc), Some(EntryType::HttpHandler));
+        let job = function("handle_cli_job").with("path", "/src/jobs/sync.py");
+        assert_eq!(detect(&rules, job), Some(EntryType::EventHandler));
+        // Built-ins still apply after the workspace rules
+        assert_eq!(detect(&rules, function("main")), Some(EntryType::Main));
+
+        let replaced = EntryRules::from_config(&EntryRulesConfig {
+            replace_defaults: true,
+            ..config
+        })
+        .unwrap();
+        assert_eq!(detect(&replaced, function("main")), None);
+
+        let invalid: EntryRulesConfig = serde_json::from_value(serde_json::json!({
+            "rules": [{ "entryType": "main", "names": ["("] }]
+        }))
+        .unwrap();
+        assert!(EntryRules::from_config(&invalid).is_err());
+    }
+}
@@ -8,10 +8,18 @@
 //! - **Rich metadata**: Structural information over similarity scores
 //! - **Explainability**: Clear reasons for why results match
 
+mod config_usage;
 mod engine;
+mod entry_rules;
+mod persist;
 mod primitives;
+mod query_dsl;
 mod text_index;
+mod todo_index;
 
-pub use engine::QueryEngine;
+pub use engine::{QueryEngine, BM25_WEIGHT, SEMANTIC_WEIGHT};
+pub use persist::index_dir;
+pub(crate) use persist::content_hash;
+pub(crate) use todo_index::record_todos;
 pub use primitives::*;
 pub use text_index::{Posting, TextIndex, TextIndexBuilder};
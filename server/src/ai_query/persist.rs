@@ -0,0 +1,179 @@
+//! Index Persistence
+//!
+//! Serializes the QueryEngine's derived indexes (text, import, caller, callee)
+//! to `<workspace>/.codegraph/index/` so a restart can skip rebuilding them.
+//!
+//! A snapshot is only reused when it is provably fresh:
+//! - the format version matches
+//! - the graph fingerprint (node ids, types and names) matches the live graph
+//! - every indexed source file still hashes to the recorded content hash
+
+use super::text_index::TextIndex;
+use crate::domain::node_props;
+use codegraph::{CodeGraph, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+/// Bump whenever the snapshot layout or index semantics change.
+pub(crate) const SNAPSHOT_VERSION: u32 = 1;
+
+/// File name of the snapshot inside the index directory.
+const SNAPSHOT_FILE: &str = "query_indexes.json";
+
+/// On-disk snapshot of the QueryEngine indexes.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct IndexSnapshot {
+    pub version: u32,
+    /// Fingerprint of the graph the indexes were built from
+    pub graph_fingerprint: u64,
+    /// Source file path -> content hash at save time
+    pub file_hashes: BTreeMap<String, u64>,
+    pub text_index: TextIndex,
+    pub import_index: HashMap<String, Vec<NodeId>>,
+    pub caller_index: HashMap<NodeId, Vec<NodeId>>,
+    pub callee_index: HashMap<NodeId, Vec<NodeId>>,
+}
+
+/// Why a persisted snapshot could not be reused.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Staleness {
+    /// Snapshot was written by a different format version
+    Version,
+    /// Graph contents differ from the graph the snapshot was built from
+    Graph,
+    /// A source file was added, removed or modified since the snapshot
+    File(String),
+}
+
+/// Directory holding persisted indexes for a workspace.
+pub fn index_dir(workspace: &Path) -> PathBuf {
+    workspace.join(".codegraph").join("index")
+}
+
+/// Path of the snapshot file within `dir`.
+pub(crate) fn snapshot_path(dir: &Path) -> PathBuf {
+    dir.join(SNAPSHOT_FILE)
+}
+
+/// FNV-1a 64-bit hash. Stable across Rust versions, unlike `DefaultHasher`.
+pub(crate) fn content_hash(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+/// Fingerprint the graph by node id, type and name, plus the edge count.
+///
+/// NodeIds are assigned at insertion time, so two graphs built from the same files
+/// in a different order produce different fingerprints and the snapshot is rejected.
+pub(crate) fn graph_fingerprint(graph: &CodeGraph) -> u64 {
+    let mut nodes: Vec<(NodeId, String)> = graph
+        .iter_nodes()
+        .map(|(id, node)| (id, format!("{}:{}", node.node_type, node_props::name(node))))
+        .collect();
+    nodes.sort_by_key(|(id, _)| *id);
+
+    let mut buf = Vec::new();
+    for (id, key) in &nodes {
+        buf.extend_from_slice(&id.to_le_bytes());
+        buf.extend_from_slice(key.as_bytes());
+        buf.push(0);
+    }
+    buf.extend_from_slice(&(graph.edge_count() as u64).to_le_bytes());
+    content_hash(&buf)
+}
+
+/// Hash the current contents of every source file in the graph.
+///
+/// Files that can no longer be read are omitted, which makes them show up as
+/// removed when compared against a snapshot.
+pub(crate) fn file_hashes(graph: &CodeGraph) -> BTreeMap<String, u64> {
+    graph
+        .iter_nodes()
+        .filter(|(_, node)| node.node_type == NodeType::CodeFile)
+        .filter_map(|(_, node)| {
+            let path = node_props::path(node);
+            if path.is_empty() {
+                return None;
+            }
+            let bytes = std::fs::read(path).ok()?;
+            Some((path.to_string(), content_hash(&bytes)))
+        })
+        .collect()
+}
+
+impl IndexSnapshot {
+    /// Check the snapshot against the live graph and the files on disk.
+    pub(crate) fn check_fresh(&self, graph: &CodeGraph) -> Result<(), Staleness> {
+        if self.version != SNAPSHOT_VERSION {
+            return Err(Staleness::Version);
+        }
+        if self.graph_fingerprint != graph_fingerprint(graph) {
+            return Err(Staleness::Graph);
+        }
+
+        let current = file_hashes(graph);
+        for (path, hash) in &self.file_hashes {
+            if current.get(path) != Some(hash) {
+                return Err(Staleness::File(path.clone()));
+            }
+        }
+        if let Some(path) = current.keys().find(|p| !self.file_hashes.contains_key(*p)) {
+            return Err(Staleness::File(path.clone()));
+        }
+        Ok(())
+    }
+
+    /// Write the snapshot to `dir`, creating it if needed.
+    ///
+    /// Writes to a temporary file first so a crash mid-write never leaves a
+    /// truncated snapshot behind.
+    pub(crate) fn save(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+        let json = serde_json::to_vec(self).map_err(|e| format!("Failed to serialize: {e}"))?;
+        let path = snapshot_path(dir);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        Ok(())
+    }
+
+    /// Read a snapshot from `dir`. Returns `None` if absent or unreadable.
+    pub(crate) fn load(dir: &Path) -> Option<Self> {
+        let bytes = std::fs::read(snapshot_path(dir)).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!("[QueryEngine] Ignoring corrupt index snapshot: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_content_hash_stable() {
+        // Reference FNV-1a values
+        assert_eq!(content_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(content_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_ne!(content_hash(b"fn a() {}"), content_hash(b"fn b() {}"));
+    }
+
+    #[test]
+    fn test_index_dir() {
+        let dir = index_dir(Path::new("/ws"));
+        assert_eq!(dir, PathBuf::from("/ws/.codegraph/index"));
+    }
+}
@@ -8,9 +8,26 @@
 //! - traverse_graph: Custom graph traversal with filters
 //! - get_callers/callees: Fast relationship queries
 //! - get_symbol_info: Rich metadata retrieval
+//! - find_cycles: Circular dependency detection over import/call edges
+//! - get_hot_symbols: Structural importance ranking (PageRank/betweenness)
+//! - find_type_usages: Symbols that reference a type through typed edges
+//! - find_implementations: Implementors of a trait/interface, or a type's supertypes
+//! - get_type_hierarchy: Transitive inheritance tree over Extends/Implements edges
+//! - reaches: Transitive reachability of sink symbols (filesystem, network, ...)
+//! - find_similar_code: Functions similar to a given one (embeddings or token structure)
+//! - find_config_usages: Where environment variables and config keys are read
+//! - get_risk_hotspots: Functions ranked by file churn combined with call graph centrality
+//! - preview_rename: References, naming collisions and memories affected by a rename
+//! - find_todos: TODO/FIXME/HACK/SAFETY comments and their enclosing symbols
+//! - find_by_annotation: Functions/classes carrying a decorator or attribute
+//! - run_query: Pipeline of primitives in one call (`symbol_search("auth") | callers | limit(20)`)
+//! - get_file_outline: Hierarchical symbol tree of one file (classes nesting methods)
 
+use crate::cancellation::CancellationToken;
 use codegraph::NodeId;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
 
 /// Maximum length for signatures before truncation (default: 500 chars)
 pub const MAX_SIGNATURE_LENGTH: usize = 500;
@@ -54,6 +71,17 @@ pub enum SymbolType {
     Type,
 }
 
+/// How the symbol search query is interpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode {
+    /// Hybrid BM25 + semantic search over names and docstrings
+    #[default]
+    Text,
+    /// Regular expression evaluated against symbol names and signatures
+    Regex,
+}
+
 /// Options for symbol search queries.
 #[derive(Debug, Clone, Default)]
 pub struct SearchOptions {
@@ -69,6 +97,12 @@ pub struct SearchOptions {
     pub include_private: bool,
     /// Compact mode: omit signatures and docstrings for smaller responses
     pub compact: bool,
+    /// Query interpretation (text or regex)
+    pub mode: SearchMode,
+    /// Stop scoring candidates after this long and return what was found
+    pub time_budget: Option<std::time::Duration>,
+    /// Stop scoring candidates once cancelled and return what was found
+    pub cancel: CancellationToken,
 }
 
 impl SearchOptions {
@@ -81,6 +115,9 @@ impl SearchOptions {
             limit: 20,
             include_private: false,
             compact: false,
+            mode: SearchMode::Text,
+            time_budget: None,
+            cancel: CancellationToken::new(),
         }
     }
 
@@ -131,6 +168,24 @@ impl SearchOptions {
         self.include_private = include_private;
         self
     }
+
+    /// Set the query mode.
+    pub fn with_mode(mut self, mode: SearchMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Set the time budget; `None` means unbounded.
+    pub fn with_time_budget(mut self, budget: Option<std::time::Duration>) -> Self {
+        self.time_budget = budget;
+        self
+    }
+
+    /// Stop the search when `cancel` is cancelled.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
 }
 
 /// Location information for a symbol.
@@ -146,6 +201,9 @@ pub struct SymbolLocation {
     pub end_line: u32,
     /// End column number (0-indexed)
     pub end_column: u32,
+    /// Workspace root containing the file, when several are indexed together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root: Option<String>,
 }
 
 /// Basic symbol information.
@@ -165,6 +223,9 @@ pub struct SymbolInfo {
     pub is_public: bool,
     /// Visibility level: "public", "private", "protected", "pub", "pub(crate)", etc.
     pub visibility: String,
+    /// Stable identifier that survives reindexing; accepted wherever a node ID is
+    #[serde(default)]
+    pub moniker: Option<String>,
 }
 
 /// A match result from symbol search.
@@ -206,6 +267,101 @@ pub struct SymbolSearchResult {
     pub total_matches: usize,
     /// Query execution time
     pub query_time_ms: u64,
+    /// Whether the time budget ran out before all candidates were scored
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+/// Number of entries in each QueryEngine index.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndexSizes {
+    /// Documents (symbols) in the text index
+    pub text_documents: usize,
+    /// Distinct tokens in the text index
+    pub text_tokens: usize,
+    /// Modules in the import index
+    pub imported_modules: usize,
+    pub functions_with_callers: usize,
+    pub functions_with_callees: usize,
+    /// Symbols with a semantic embedding
+    pub symbol_vectors: usize,
+}
+
+/// How to aggregate symbol_search/traverse_graph results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    /// One group per source file
+    File,
+    /// One group per containing directory (module)
+    Directory,
+}
+
+/// Maximum number of sample symbol names listed per result group.
+pub const GROUP_SAMPLE_SIZE: usize = 5;
+
+/// Number of ranked matches a grouped symbol search aggregates over.
+pub const MAX_GROUPED_MATCHES: usize = 500;
+
+/// Results aggregated under one file or directory.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultGroup {
+    /// File path or directory
+    pub key: String,
+    /// Number of results in the group
+    pub count: usize,
+    /// Sum of result scores (equals `count` for unscored results)
+    pub score: f32,
+    /// Names of the highest-ranked results in the group
+    pub top_symbols: Vec<String>,
+}
+
+/// Group ranked results by file or directory.
+///
+/// `items` are (symbol, score) pairs in rank order, so the first names seen in a
+/// group become its samples. Groups are ordered by total score, then count.
+pub fn group_results<'a>(
+    items: impl IntoIterator<Item = (&'a SymbolInfo, f32)>,
+    group_by: GroupBy,
+) -> Vec<ResultGroup> {
+    let mut groups: Vec<ResultGroup> = Vec::new();
+    let mut index: HashMap<String, usize> = HashMap::new();
+
+    for (symbol, score) in items {
+        let key = match group_by {
+            GroupBy::File => symbol.location.file.clone(),
+            GroupBy::Directory => Path::new(&symbol.location.file)
+                .parent()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+        let slot = *index.entry(key.clone()).or_insert_with(|| {
+            groups.push(ResultGroup {
+                key,
+                count: 0,
+                score: 0.0,
+                top_symbols: Vec::new(),
+            });
+            groups.len() - 1
+        });
+
+        let group = &mut groups[slot];
+        group.count += 1;
+        group.score += score;
+        if group.top_symbols.len() < GROUP_SAMPLE_SIZE && !group.top_symbols.contains(&symbol.name)
+        {
+            group.top_symbols.push(symbol.name.clone());
+        }
+    }
+
+    groups.sort_by(|a, b| {
+        b.score
+            .partial_cmp(&a.score)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.count.cmp(&a.count))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    groups
 }
 
 /// A pair of similar/duplicate functions.
@@ -285,6 +441,229 @@ pub struct StructuralComparison {
     pub param_count_b: usize,
 }
 
+/// Granularity for dependency cycle detection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CycleLevel {
+    /// Collapse symbols into their containing file (module-level cycles)
+    #[default]
+    File,
+    /// Report cycles between individual symbols
+    Symbol,
+}
+
+/// A member of a dependency cycle (a file or a symbol, depending on level).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleMember {
+    /// Graph node for this member (file node or symbol), if one exists
+    pub node_id: Option<NodeId>,
+    pub name: String,
+    pub file: String,
+    pub line: u32,
+}
+
+/// A strongly connected component of the dependency graph.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyCycle {
+    /// Number of members in the cycle
+    pub size: usize,
+    /// Members of the cycle
+    pub members: Vec<CycleMember>,
+    /// Distinct files touched by the cycle
+    pub files: Vec<String>,
+    /// Edge types that participate in the cycle
+    pub edge_types: Vec<String>,
+}
+
+/// Result of dependency cycle detection.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleResult {
+    /// Cycles sorted by size (largest first)
+    pub cycles: Vec<DependencyCycle>,
+    /// Total number of cycles found (before limit)
+    pub total_cycles: usize,
+    /// Total number of members across all cycles (before limit)
+    pub members_in_cycles: usize,
+    pub level: CycleLevel,
+    pub query_time_ms: u64,
+}
+
+/// Centrality metric used to rank symbols.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum CentralityMetric {
+    /// PageRank over call edges: symbols that many important callers depend on
+    PageRank,
+    /// Betweenness: symbols that sit on many shortest call paths (chokepoints)
+    Betweenness,
+    /// Average of normalized PageRank and betweenness
+    #[default]
+    Combined,
+}
+
+/// Cached centrality scores for a symbol, normalized to 0.0-1.0.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct CentralityScores {
+    pub pagerank: f32,
+    pub betweenness: f32,
+}
+
+impl CentralityScores {
+    /// Score for the given metric.
+    pub fn score(&self, metric: CentralityMetric) -> f32 {
+        match metric {
+            CentralityMetric::PageRank => self.pagerank,
+            CentralityMetric::Betweenness => self.betweenness,
+            CentralityMetric::Combined => (self.pagerank + self.betweenness) / 2.0,
+        }
+    }
+}
+
+/// A symbol ranked by structural importance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotSymbol {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Score for the requested metric
+    pub score: f32,
+    pub pagerank: f32,
+    pub betweenness: f32,
+    /// Number of direct callers
+    pub caller_count: usize,
+    /// Number of direct callees
+    pub callee_count: usize,
+}
+
+/// Result of a hot symbol query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotSymbolsResult {
+    pub symbols: Vec<HotSymbol>,
+    pub metric: CentralityMetric,
+    /// Number of symbols with centrality scores
+    pub total_ranked: usize,
+    pub query_time_ms: u64,
+}
+
+/// A symbol ranked by change risk (churn x centrality).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskHotspot {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Geometric mean of churn_score and centrality (0.0-1.0)
+    pub risk: f32,
+    /// Commits that touched the symbol's file
+    pub churn: usize,
+    /// Churn normalized (log-scaled) against the most changed file
+    pub churn_score: f32,
+    /// Combined PageRank/betweenness centrality
+    pub centrality: f32,
+    /// Number of direct callers
+    pub caller_count: usize,
+}
+
+/// Result of a risk hotspot query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskHotspotsResult {
+    pub hotspots: Vec<RiskHotspot>,
+    /// Files with recorded churn
+    pub files_with_churn: usize,
+    /// Symbols with both churn and a centrality score
+    pub total_ranked: usize,
+    pub query_time_ms: u64,
+}
+
+/// A symbol that references a type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeUsage {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Edge type that links the symbol to the type (References, Uses, Instantiates)
+    pub usage_kind: String,
+    /// Field/variable through which a containing symbol uses the type
+    pub via: Option<String>,
+}
+
+/// Result of a type usage query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeUsageResult {
+    pub type_name: String,
+    pub usages: Vec<TypeUsage>,
+    /// Total number of usages (before limit)
+    pub total_usages: usize,
+    pub query_time_ms: u64,
+}
+
+/// Which side of an Implements/Extends relationship to return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImplementationDirection {
+    /// Types that implement or extend the target (trait/interface/base class)
+    #[default]
+    Implementations,
+    /// Interfaces and base types the target implements or extends
+    Supertypes,
+}
+
+/// A type on the other end of an Implements/Extends edge.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Implementation {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Edge type linking the two types (Implements or Extends)
+    pub relation: String,
+}
+
+/// Result of a find_implementations query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplementationResult {
+    pub target_name: String,
+    pub direction: ImplementationDirection,
+    pub implementations: Vec<Implementation>,
+    /// Total number of implementations (before limit)
+    pub total_implementations: usize,
+    pub query_time_ms: u64,
+}
+
+/// Direction of a type hierarchy walk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TypeHierarchyDirection {
+    /// Base classes and interfaces, up to the roots
+    Supertypes,
+    /// Subclasses and implementors, down to the leaves
+    #[default]
+    Subtypes,
+}
+
+/// A type reached while walking a type hierarchy.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeHierarchyNode {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Distance from the starting type (1 = direct)
+    pub depth: u32,
+    /// The type this one was reached from
+    pub parent: NodeId,
+    /// Edge type linking it to its parent (Implements or Extends)
+    pub relation: String,
+}
+
+/// Result of a type hierarchy query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TypeHierarchyResult {
+    pub root: SymbolInfo,
+    pub direction: TypeHierarchyDirection,
+    /// Reachable types in breadth-first order
+    pub nodes: Vec<TypeHierarchyNode>,
+    /// Deepest level reached
+    pub max_depth: u32,
+    /// True if an inheritance cycle was found (and cut)
+    pub has_cycle: bool,
+    /// True if the walk stopped at the node cap or depth limit
+    pub truncated: bool,
+    pub query_time_ms: u64,
+}
+
 /// Import match mode for find_by_imports.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -438,6 +817,462 @@ pub struct TraversalNode {
     pub symbol: SymbolInfo,
 }
 
+/// Built-in sink sets for reachability queries: category -> symbol name globs.
+///
+/// Patterns match graph symbol names as well as unresolved (external) call names,
+/// e.g. `fs.readFile` or `requests.get`.
+pub const SINK_CATEGORIES: &[(&str, &[&str])] = &[
+    (
+        "filesystem",
+        &[
+            "fs.*",
+            "readFile*",
+            "writeFile*",
+            "appendFile*",
+            "unlink*",
+            "mkdir*",
+            "rmdir*",
+            "open",
+            "fopen",
+            "os.remove",
+            "os.Open*",
+            "os.Create*",
+            "os.ReadFile",
+            "os.WriteFile",
+            "shutil.*",
+            "ioutil.*",
+            "std::fs::*",
+            "fs::*",
+            "File::open",
+            "File::create",
+            "read_to_string",
+            "remove_file",
+            "create_dir*",
+        ],
+    ),
+    (
+        "network",
+        &[
+            "fetch",
+            "axios*",
+            "XMLHttpRequest",
+            "http.Get",
+            "http.Post",
+            "http.NewRequest",
+            "http.request",
+            "https.request",
+            "requests.*",
+            "urllib*",
+            "urlopen",
+            "reqwest::*",
+            "TcpStream::*",
+            "UdpSocket::*",
+            "net.Dial*",
+            "socket*",
+            "HttpClient*",
+        ],
+    ),
+    (
+        "process",
+        &[
+            "exec*",
+            "spawn*",
+            "fork",
+            "system",
+            "popen",
+            "os.system",
+            "subprocess.*",
+            "child_process.*",
+            "Command::new",
+            "exec.Command",
+            "Runtime.exec",
+        ],
+    ),
+    (
+        "database",
+        &[
+            "*.query",
+            "*.execute",
+            "executemany",
+            "cursor*",
+            "db.Query*",
+            "db.Exec*",
+            "sqlx::*",
+            "diesel::*",
+            "knex*",
+            "mongoose.*",
+        ],
+    ),
+];
+
+/// Look up a built-in sink category (case-insensitive).
+pub fn sink_category(name: &str) -> Option<&'static [&'static str]> {
+    SINK_CATEGORIES
+        .iter()
+        .find(|(category, _)| category.eq_ignore_ascii_case(name))
+        .map(|(_, patterns)| *patterns)
+}
+
+/// What counts as a "sink" for a reachability query, and how far to look.
+#[derive(Debug, Clone)]
+pub struct ReachabilityPredicate {
+    /// Symbol name globs (e.g. "db.*", "send_email")
+    pub sinks: Vec<String>,
+    /// Built-in sink categories (see [`SINK_CATEGORIES`])
+    pub categories: Vec<String>,
+    /// Edge types to follow (default: Calls)
+    pub edge_types: Vec<String>,
+    /// Maximum call depth to explore
+    pub max_depth: u32,
+    /// Stop after this many sink matches
+    pub max_matches: usize,
+}
+
+impl ReachabilityPredicate {
+    /// Create a predicate with no sinks that follows Calls edges up to depth 10.
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            categories: Vec::new(),
+            edge_types: vec!["Calls".to_string()],
+            max_depth: 10,
+            max_matches: 20,
+        }
+    }
+
+    /// Add sink symbol name globs.
+    pub fn with_sinks(mut self, sinks: Vec<String>) -> Self {
+        self.sinks = sinks;
+        self
+    }
+
+    /// Add built-in sink categories.
+    pub fn with_categories(mut self, categories: Vec<String>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Follow these edge types instead of Calls.
+    pub fn with_edge_types(mut self, types: Vec<String>) -> Self {
+        if !types.is_empty() {
+            self.edge_types = types;
+        }
+        self
+    }
+
+    /// Set maximum depth.
+    pub fn with_max_depth(mut self, depth: u32) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Set maximum number of matches.
+    pub fn with_max_matches(mut self, max: usize) -> Self {
+        self.max_matches = max;
+        self
+    }
+}
+
+impl Default for ReachabilityPredicate {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A sink reached from the start symbol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SinkMatch {
+    /// The sink symbol, or for external sinks the function making the call
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Name that matched (symbol name or external call name)
+    pub sink: String,
+    /// Pattern that matched
+    pub pattern: String,
+    /// Built-in category the pattern came from, if any
+    pub category: Option<String>,
+    /// True if the sink is an unresolved call outside the graph (library/runtime API)
+    pub external: bool,
+    /// Number of edges from the start symbol to `node_id`
+    pub depth: u32,
+    /// Symbol names from the start symbol to `node_id`
+    pub path: Vec<String>,
+}
+
+/// Result of a reachability query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReachabilityResult {
+    pub from: SymbolInfo,
+    /// True if any sink is reachable
+    pub reachable: bool,
+    pub matches: Vec<SinkMatch>,
+    pub nodes_visited: usize,
+    /// True if the depth or match cap cut the search short
+    pub truncated: bool,
+    pub query_time_ms: u64,
+}
+
+/// A symbol on a path between two symbols.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathStep {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Type of the edge from the previous step (none for the start)
+    pub edge_type: Option<String>,
+}
+
+/// Result of a shortest path query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PathResult {
+    pub from: SymbolInfo,
+    pub to: SymbolInfo,
+    /// True if `to` is reachable from `from`
+    pub found: bool,
+    /// Shortest path from `from` to `to`, both included; empty if not found
+    pub path: Vec<PathStep>,
+    pub nodes_visited: usize,
+    /// True if the depth cap or cancellation cut the search short
+    pub truncated: bool,
+    pub query_time_ms: u64,
+}
+
+/// How find_similar_code compares functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SimilarityMode {
+    /// Semantic if the target has an embedding, structural otherwise
+    #[default]
+    Auto,
+    /// Cosine similarity of code embeddings
+    Semantic,
+    /// Jaccard similarity of normalized token shingles (copy-paste variants)
+    Structural,
+}
+
+/// A function similar to the queried one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarCode {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Similarity score (0.0-1.0)
+    pub similarity: f32,
+}
+
+/// Result of a similar-code search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SimilarCodeResult {
+    pub target: SymbolInfo,
+    /// Mode actually used (never `Auto`)
+    pub mode: SimilarityMode,
+    pub matches: Vec<SimilarCode>,
+    /// Number of candidate functions scored
+    pub total_compared: usize,
+    pub query_time_ms: u64,
+}
+
+/// Where a configuration value is read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigSource {
+    /// Environment variable (process.env.X, os.environ['X'], std::env::var("X"))
+    Env,
+    /// Application config key (@Value("${x}"), config.get("x"), viper.GetString("x"))
+    Config,
+}
+
+/// A read of an environment variable or config key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigUsage {
+    pub key: String,
+    pub source: ConfigSource,
+    /// Accessor that matched (e.g. "process.env", "os.getenv")
+    pub accessor: String,
+    pub file: String,
+    /// Line number (1-indexed)
+    pub line: u32,
+    /// Column number (0-indexed)
+    pub column: u32,
+    /// Innermost function or class containing the read
+    pub node_id: Option<NodeId>,
+    pub symbol: Option<SymbolInfo>,
+}
+
+/// Result of a config usage query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigUsageResult {
+    /// Key glob that was queried
+    pub key: String,
+    pub usages: Vec<ConfigUsage>,
+    /// Distinct keys matched by the query
+    pub keys: Vec<String>,
+    /// Number of usages before the limit was applied
+    pub total_usages: usize,
+    /// Files rescanned to refresh the index for this query
+    pub files_scanned: usize,
+    pub query_time_ms: u64,
+}
+
+/// Tag of a structured code comment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TodoTag {
+    Todo,
+    Fixme,
+    Hack,
+    /// `// SAFETY:` justification of an unsafe block
+    Safety,
+}
+
+impl TodoTag {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TodoTag::Todo => "TODO",
+            TodoTag::Fixme => "FIXME",
+            TodoTag::Hack => "HACK",
+            TodoTag::Safety => "SAFETY",
+        }
+    }
+}
+
+/// A TODO/FIXME/HACK/SAFETY comment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoComment {
+    pub tag: TodoTag,
+    /// Comment text after the tag
+    pub text: String,
+    /// Owner named in the comment, e.g. `TODO(alice): ...`
+    pub owner: Option<String>,
+    pub file: String,
+    /// Line number (1-indexed)
+    pub line: u32,
+    /// Column of the tag (0-indexed)
+    pub column: u32,
+    /// Innermost function or class containing the comment
+    pub node_id: Option<NodeId>,
+    pub symbol: Option<SymbolInfo>,
+}
+
+/// Result of a TODO comment query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoResult {
+    /// Matching comments ordered by file and line
+    pub todos: Vec<TodoComment>,
+    pub query_time_ms: u64,
+}
+
+/// A reference to a symbol that a rename has to update.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameReference {
+    /// Symbol containing the reference
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Edge type of the reference (Calls, Imports, References, ...)
+    pub reference_kind: String,
+    pub file: String,
+    /// Line of the reference (1-indexed), or the referencing symbol's start line
+    pub line: u32,
+}
+
+/// An existing symbol that already uses the new name in a scope the rename touches.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameCollision {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Scope both names would share (e.g. "class UserService", "file src/user.ts")
+    pub scope: String,
+    /// True if the clash is in a file that only references the renamed symbol
+    pub in_referencing_file: bool,
+}
+
+/// A memory that mentions or links to the symbol being renamed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenameMemory {
+    pub id: String,
+    pub title: String,
+    pub kind: String,
+    /// Linked to the symbol's node rather than only mentioning its name
+    pub linked: bool,
+}
+
+/// Result of a rename preview.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenamePreview {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    pub old_name: String,
+    pub new_name: String,
+    pub references: Vec<RenameReference>,
+    pub collisions: Vec<RenameCollision>,
+    pub memories: Vec<RenameMemory>,
+    /// Files that need edits: the definition plus every referencing file
+    pub affected_files: Vec<String>,
+    pub query_time_ms: u64,
+}
+
+/// A symbol carrying a matching decorator or attribute.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotatedSymbol {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Matching annotations as written in source (e.g. `@app.route("/users")`)
+    pub annotations: Vec<String>,
+}
+
+/// Result of an annotation search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnotationSearchResult {
+    pub annotation: String,
+    pub results: Vec<AnnotatedSymbol>,
+    /// Number of matching symbols before the limit was applied
+    pub total: usize,
+    pub query_time_ms: u64,
+}
+
+/// A symbol produced by a query pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineMatch {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+}
+
+/// Working set size after one pipeline stage.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineStageCount {
+    pub stage: String,
+    pub count: usize,
+}
+
+/// Result of a pipeline query.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineResult {
+    pub query: String,
+    pub results: Vec<PipelineMatch>,
+    pub stages: Vec<PipelineStageCount>,
+    /// An expansion stage hit the working set cap and dropped symbols
+    pub truncated: bool,
+    pub query_time_ms: u64,
+}
+
+/// A symbol in a file outline, with the symbols declared inside it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineNode {
+    pub node_id: NodeId,
+    pub symbol: SymbolInfo,
+    /// Nested symbols in source order
+    pub children: Vec<OutlineNode>,
+}
+
+/// Hierarchical outline of a file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileOutline {
+    pub file: String,
+    /// Top-level symbols in source order
+    pub symbols: Vec<OutlineNode>,
+    /// Number of symbols at all levels
+    pub total_symbols: usize,
+    pub query_time_ms: u64,
+}
+
 /// Information about a caller/callee relationship.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CallInfo {
@@ -526,6 +1361,51 @@ impl SignaturePattern {
 mod tests {
     use super::*;
 
+    fn symbol_at(name: &str, file: &str) -> SymbolInfo {
+        SymbolInfo {
+            name: name.to_string(),
+            kind: "function".to_string(),
+            location: SymbolLocation {
+                file: file.to_string(),
+                line: 1,
+                column: 0,
+                end_line: 1,
+                end_column: 0,
+                root: None,
+            },
+            signature: None,
+            docstring: None,
+            is_public: true,
+            visibility: "public".to_string(),
+            moniker: None,
+        }
+    }
+
+    #[test]
+    fn test_group_results_by_file_and_directory() {
+        let symbols = [
+            symbol_at("login", "/src/auth/session.rs"),
+            symbol_at("logout", "/src/auth/session.rs"),
+            symbol_at("hash_password", "/src/auth/crypto.rs"),
+            symbol_at("render", "/src/ui/view.rs"),
+        ];
+        let scores = [3.0, 2.0, 1.0, 4.0];
+        let items = || symbols.iter().zip(scores);
+
+        let by_file = group_results(items(), GroupBy::File);
+        assert_eq!(by_file.len(), 3);
+        assert_eq!(by_file[0].key, "/src/auth/session.rs");
+        assert_eq!(by_file[0].count, 2);
+        assert_eq!(by_file[0].top_symbols, vec!["login", "logout"]);
+
+        let by_dir = group_results(items(), GroupBy::Directory);
+        assert_eq!(by_dir.len(), 2);
+        assert_eq!(by_dir[0].key, "/src/auth");
+        assert_eq!(by_dir[0].count, 3);
+        assert_eq!(by_dir[0].score, 6.0);
+        assert_eq!(by_dir[1].key, "/src/ui");
+    }
+
     #[test]
     fn test_search_options_builder() {
         let options = SearchOptions::new()
@@ -550,6 +1430,7 @@ mod tests {
         assert!(!options.include_private);
         assert!(options.symbol_types.is_empty());
         assert!(options.languages.is_empty());
+        assert_eq!(options.mode, SearchMode::Text);
     }
 
     #[test]
@@ -577,6 +1458,18 @@ mod tests {
         assert_eq!(pattern.modifiers, vec!["async", "public"]);
     }
 
+    #[test]
+    fn test_centrality_scores_combined() {
+        let scores = CentralityScores {
+            pagerank: 0.8,
+            betweenness: 0.2,
+        };
+        assert_eq!(scores.score(CentralityMetric::PageRank), 0.8);
+        assert_eq!(scores.score(CentralityMetric::Betweenness), 0.2);
+        assert!((scores.score(CentralityMetric::Combined) - 0.5).abs() < f32::EPSILON);
+        assert_eq!(CentralityMetric::default(), CentralityMetric::Combined);
+    }
+
     #[test]
     fn test_import_match_mode_default() {
         let mode = ImportMatchMode::default();
@@ -588,4 +1481,11 @@ mod tests {
         let scope = SearchScope::default();
         assert_eq!(scope, SearchScope::Workspace);
     }
+
+    #[test]
+    fn test_sink_category_lookup() {
+        assert!(sink_category("filesystem").unwrap().contains(&"fs.*"));
+        assert!(sink_category("Network").is_some());
+        assert!(sink_category("telepathy").is_none());
+    }
 }
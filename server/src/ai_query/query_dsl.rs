@@ -0,0 +1,511 @@
+//! Pipeline Query DSL
+//!
+//! Parses multi-step explorations into stages that
+//! [`QueryEngine::run_query`](super::QueryEngine::run_query) executes in one
+//! round trip:
+//!
+//! ```text
+//! symbol_search("auth") | callers(depth=2) | filter(kind=Function) | limit(20)
+//! ```
+//!
+//! The first stage produces a set of symbols, every following stage expands or
+//! narrows it:
+//!
+//! - Sources: `symbol_search(query, limit=20)`, `entry_points(type=http_handler)`,
+//!   `hot_symbols(limit=20)`
+//! - Expansion: `callers(depth=1)`, `callees(depth=1)`
+//! - Narrowing: `filter(kind=, name=, file=, public=)`, `limit(n)`
+//!
+//! `symbol_search` includes private symbols; narrow with `filter(public=true)`.
+
+use super::primitives::{EntryType, SymbolType};
+
+/// Upper bound for callers/callees depth: deeper chains flood the working set.
+const MAX_DEPTH: u32 = 5;
+
+/// Working set size at which expansion stages stop adding symbols.
+pub(crate) const MAX_WORKING_SET: usize = 1000;
+
+/// A literal argument value.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum DslValue {
+    Str(String),
+    Int(i64),
+    Bool(bool),
+    /// Bare word, e.g. `Function` in `filter(kind=Function)`
+    Ident(String),
+}
+
+impl DslValue {
+    fn describe(&self) -> String {
+        match self {
+            DslValue::Str(s) => format!("\"{s}\""),
+            DslValue::Int(i) => i.to_string(),
+            DslValue::Bool(b) => b.to_string(),
+            DslValue::Ident(s) => s.clone(),
+        }
+    }
+}
+
+/// A parsed but unvalidated stage: `name(positional..., key=value...)`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct RawStage {
+    pub name: String,
+    pub positional: Vec<DslValue>,
+    pub named: Vec<(String, DslValue)>,
+}
+
+/// A validated pipeline stage.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum PipelineStage {
+    SymbolSearch {
+        query: String,
+        limit: usize,
+    },
+    EntryPoints {
+        entry_types: Vec<EntryType>,
+    },
+    HotSymbols {
+        limit: usize,
+    },
+    Callers {
+        depth: u32,
+    },
+    Callees {
+        depth: u32,
+    },
+    Filter {
+        kinds: Vec<SymbolType>,
+        name: Option<String>,
+        file: Option<String>,
+        public: Option<bool>,
+    },
+    Limit(usize),
+}
+
+impl PipelineStage {
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            PipelineStage::SymbolSearch { .. } => "symbol_search",
+            PipelineStage::EntryPoints { .. } => "entry_points",
+            PipelineStage::HotSymbols { .. } => "hot_symbols",
+            PipelineStage::Callers { .. } => "callers",
+            PipelineStage::Callees { .. } => "callees",
+            PipelineStage::Filter { .. } => "filter",
+            PipelineStage::Limit(_) => "limit",
+        }
+    }
+
+    fn is_source(&self) -> bool {
+        matches!(
+            self,
+            PipelineStage::SymbolSearch { .. }
+                | PipelineStage::EntryPoints { .. }
+                | PipelineStage::HotSymbols { .. }
+        )
+    }
+}
+
+/// Parse and validate a pipeline.
+pub(crate) fn parse_pipeline(input: &str) -> Result<Vec<PipelineStage>, String> {
+    let raw = Parser::new(input).parse()?;
+    let stages = raw
+        .iter()
+        .map(compile_stage)
+        .collect::<Result<Vec<_>, String>>()?;
+
+    match stages.first() {
+        None => return Err("Empty query".to_string()),
+        Some(first) if !first.is_source() => {
+            return Err(format!(
+                "Query must start with symbol_search, entry_points or hot_symbols, not {}",
+                raw[0].name
+            ))
+        }
+        _ => {}
+    }
+    if let Some(i) = stages.iter().skip(1).position(PipelineStage::is_source) {
+        return Err(format!(
+            "{} can only be the first stage of a query",
+            raw[i + 1].name
+        ));
+    }
+    Ok(stages)
+}
+
+// ============================================================
+// Validation
+// ============================================================
+
+struct Args<'a> {
+    stage: &'a RawStage,
+}
+
+impl<'a> Args<'a> {
+    /// Argument by name, or by position when not named.
+    fn get(&self, name: &str, position: usize) -> Option<&'a DslValue> {
+        self.stage
+            .named
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value)
+            .or_else(|| self.stage.positional.get(position))
+    }
+
+    fn string(&self, name: &str, position: usize) -> Result<Option<String>, String> {
+        match self.get(name, position) {
+            None => Ok(None),
+            Some(DslValue::Str(s) | DslValue::Ident(s)) => Ok(Some(s.clone())),
+            Some(other) => Err(self.invalid(name, other, "a string")),
+        }
+    }
+
+    fn uint(&self, name: &str, position: usize) -> Result<Option<u64>, String> {
+        match self.get(name, position) {
+            None => Ok(None),
+            Some(DslValue::Int(i)) if *i >= 0 => Ok(Some(*i as u64)),
+            Some(other) => Err(self.invalid(name, other, "a non-negative integer")),
+        }
+    }
+
+    fn bool(&self, name: &str, position: usize) -> Result<Option<bool>, String> {
+        match self.get(name, position) {
+            None => Ok(None),
+            Some(DslValue::Bool(b)) => Ok(Some(*b)),
+            Some(other) => Err(self.invalid(name, other, "true or false")),
+        }
+    }
+
+    fn depth(&self) -> Result<u32, String> {
+        let depth = self.uint("depth", 0)?.unwrap_or(1);
+        if depth == 0 || depth > MAX_DEPTH as u64 {
+            return Err(format!(
+                "{}: depth must be between 1 and {MAX_DEPTH}",
+                self.stage.name
+            ));
+        }
+        Ok(depth as u32)
+    }
+
+    /// Reject named arguments the stage does not know.
+    fn only(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .stage
+            .named
+            .iter()
+            .find(|(key, _)| !known.contains(&key.as_str()))
+        {
+            Some((key, _)) => Err(format!(
+                "{}: unknown argument '{key}'. Expected one of: {}",
+                self.stage.name,
+                known.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn invalid(&self, name: &str, value: &DslValue, expected: &str) -> String {
+        format!(
+            "{}: {name} must be {expected}, got {}",
+            self.stage.name,
+            value.describe()
+        )
+    }
+}
+
+fn compile_stage(stage: &RawStage) -> Result<PipelineStage, String> {
+    let args = Args { stage };
+    match stage.name.as_str() {
+        "symbol_search" | "search" => {
+            args.only(&["query", "limit"])?;
+            let query = args
+                .string("query", 0)?
+                .ok_or_else(|| format!("{}: missing query", stage.name))?;
+            let limit = args.uint("limit", 1)?.unwrap_or(20) as usize;
+            Ok(PipelineStage::SymbolSearch { query, limit })
+        }
+        "entry_points" => {
+            args.only(&["type"])?;
+            let entry_types = match args.string("type", 0)?.as_deref() {
+                None | Some("all") => vec![],
+                Some(t) => vec![parse_entry_type(t)
+                    .ok_or_else(|| format!("entry_points: unknown type '{t}'"))?],
+            };
+            Ok(PipelineStage::EntryPoints { entry_types })
+        }
+        "hot_symbols" => {
+            args.only(&["limit"])?;
+            let limit = args.uint("limit", 0)?.unwrap_or(20) as usize;
+            Ok(PipelineStage::HotSymbols { limit })
+        }
+        "callers" => {
+            args.only(&["depth"])?;
+            Ok(PipelineStage::Callers {
+                depth: args.depth()?,
+            })
+        }
+        "callees" => {
+            args.only(&["depth"])?;
+            Ok(PipelineStage::Callees {
+                depth: args.depth()?,
+            })
+        }
+        "filter" => {
+            args.only(&["kind", "name", "file", "public"])?;
+            if !stage.positional.is_empty() {
+                return Err("filter: arguments must be named, e.g. filter(kind=Function)".into());
+            }
+            let kinds = match args.string("kind", usize::MAX)? {
+                None => vec![],
+                Some(kind) => kind
+                    .split(',')
+                    .map(|k| {
+                        parse_symbol_type(k.trim())
+                            .ok_or_else(|| format!("filter: unknown kind '{}'", k.trim()))
+                    })
+                    .collect::<Result<Vec<_>, String>>()?,
+            };
+            Ok(PipelineStage::Filter {
+                kinds,
+                name: args.string("name", usize::MAX)?,
+                file: args.string("file", usize::MAX)?,
+                public: args.bool("public", usize::MAX)?,
+            })
+        }
+        "limit" => {
+            args.only(&["n"])?;
+            let n = args
+                .uint("n", 0)?
+                .ok_or_else(|| "limit: missing count".to_string())?;
+            Ok(PipelineStage::Limit(n as usize))
+        }
+        other => Err(format!(
+            "Unknown stage '{other}'. Expected one of: symbol_search, entry_points, \
+             hot_symbols, callers, callees, filter, limit"
+        )),
+    }
+}
+
+fn parse_symbol_type(kind: &str) -> Option<SymbolType> {
+    match kind.to_lowercase().as_str() {
+        "function" | "method" => Some(SymbolType::Function),
+        "class" => Some(SymbolType::Class),
+        "variable" => Some(SymbolType::Variable),
+        "module" => Some(SymbolType::Module),
+        "interface" => Some(SymbolType::Interface),
+        "type" => Some(SymbolType::Type),
+        _ => None,
+    }
+}
+
+fn parse_entry_type(entry_type: &str) -> Option<EntryType> {
+    match entry_type {
+        "http_handler" => Some(EntryType::HttpHandler),
+        "cli_command" => Some(EntryType::CliCommand),
+        "public_api" => Some(EntryType::PublicApi),
+        "event_handler" => Some(EntryType::EventHandler),
+        "test_entry" => Some(EntryType::TestEntry),
+        "main" => Some(EntryType::Main),
+        _ => None,
+    }
+}
+
+// ============================================================
+// Parser
+// ============================================================
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self { input, pos: 0 }
+    }
+
+    fn parse(mut self) -> Result<Vec<RawStage>, String> {
+        let mut stages = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.peek().is_none() && stages.is_empty() {
+                return Ok(stages);
+            }
+            stages.push(self.stage()?);
+            self.skip_whitespace();
+            match self.peek() {
+                None => return Ok(stages),
+                Some('|') => self.pos += 1,
+                Some(c) => return Err(self.error(&format!("expected '|', found '{c}'"))),
+            }
+        }
+    }
+
+    fn stage(&mut self) -> Result<RawStage, String> {
+        let name = self.ident()?;
+        let mut stage = RawStage {
+            name,
+            positional: Vec::new(),
+            named: Vec::new(),
+        };
+
+        self.skip_whitespace();
+        if self.peek() != Some('(') {
+            return Ok(stage);
+        }
+        self.pos += 1;
+
+        self.skip_whitespace();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+            return Ok(stage);
+        }
+        loop {
+            self.skip_whitespace();
+            let value = self.value()?;
+            self.skip_whitespace();
+            if let (DslValue::Ident(key), Some('=')) = (&value, self.peek()) {
+                self.pos += 1;
+                self.skip_whitespace();
+                let value = self.value()?;
+                stage.named.push((key.clone(), value));
+            } else if stage.named.is_empty() {
+                stage.positional.push(value);
+            } else {
+                return Err(self.error("positional argument after named argument"));
+            }
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(')') => {
+                    self.pos += 1;
+                    return Ok(stage);
+                }
+                Some(c) => return Err(self.error(&format!("expected ',' or ')', found '{c}'"))),
+                None => return Err(self.error("unclosed '('")),
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<DslValue, String> {
+        match self.peek() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let mut value = String::new();
+                while let Some(c) = self.peek() {
+                    self.pos += c.len_utf8();
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = self.peek() {
+                                self.pos += escaped.len_utf8();
+                                value.push(escaped);
+                            }
+                        }
+                        c if c == quote => return Ok(DslValue::Str(value)),
+                        c => value.push(c),
+                    }
+                }
+                Err(self.error("unterminated string"))
+            }
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                let start = self.pos;
+                self.pos += 1;
+                while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                    self.pos += 1;
+                }
+                self.input[start..self.pos]
+                    .parse()
+                    .map(DslValue::Int)
+                    .map_err(|_| self.error("invalid number"))
+            }
+            Some(_) => {
+                let ident = self.ident()?;
+                Ok(match ident.as_str() {
+                    "true" => DslValue::Bool(true),
+                    "false" => DslValue::Bool(false),
+                    _ => DslValue::Ident(ident),
+                })
+            }
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '.')
+        {
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+        if start == self.pos {
+            return Err(match self.peek() {
+                Some(c) => self.error(&format!("expected a name, found '{c}'")),
+                None => self.error("expected a name"),
+            });
+        }
+        Ok(self.input[start..self.pos].to_string())
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += self.peek().map_or(1, char::len_utf8);
+        }
+    }
+
+    fn error(&self, message: &str) -> String {
+        format!("Query syntax error at column {}: {message}", self.pos + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_pipeline_stages_and_arguments() {
+        let stages = parse_pipeline(
+            r#"symbol_search("auth") | callers(depth=2) | filter(kind=Function, file='src/api') | limit(20)"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stages,
+            vec![
+                PipelineStage::SymbolSearch {
+                    query: "auth".to_string(),
+                    limit: 20
+                },
+                PipelineStage::Callers { depth: 2 },
+                PipelineStage::Filter {
+                    kinds: vec![SymbolType::Function],
+                    name: None,
+                    file: Some("src/api".to_string()),
+                    public: None
+                },
+                PipelineStage::Limit(20),
+            ]
+        );
+
+        let stages = parse_pipeline("entry_points(type=http_handler) | callees").unwrap();
+        assert_eq!(stages[1], PipelineStage::Callees { depth: 1 });
+    }
+
+    #[test]
+    fn test_parse_pipeline_rejects_invalid_queries() {
+        let err = |query: &str| parse_pipeline(query).unwrap_err();
+
+        assert_eq!(err(""), "Empty query");
+        assert!(err("callers(depth=2)").contains("must start with"));
+        assert!(err("symbol_search(\"a\") | hot_symbols").contains("first stage"));
+        assert!(err("symbol_search(\"a\") | callers(depth=9)").contains("depth"));
+        assert!(err("symbol_search(\"a\") | filter(kind=Widget)").contains("unknown kind"));
+        assert!(err("symbol_search(\"a\") | sort(name)").contains("Unknown stage"));
+        assert!(err("symbol_search(\"a\" | limit(1)").contains("column"));
+        assert!(err("symbol_search(\"a) | limit(1)").contains("unterminated"));
+    }
+}
@@ -1,6 +1,7 @@
 //! Text Index with BM25 Scoring
 //!
-//! Provides fast text-based symbol search using an inverted index with BM25 ranking.
+//! Provides fast text-based symbol search using an inverted index with BM25 ranking,
+//! plus a trigram index over symbol names for typo-tolerant fuzzy matching.
 //!
 //! Performance targets:
 //! - Build time: < 5 seconds for 10K symbols
@@ -8,6 +9,7 @@
 //! - Memory: ~50 bytes per token occurrence
 
 use codegraph::NodeId;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// BM25 parameter: term frequency saturation
@@ -21,8 +23,11 @@ const WEIGHT_SYMBOL_NAME: f32 = 3.0;
 const WEIGHT_DOCSTRING: f32 = 2.0;
 const WEIGHT_COMMENT: f32 = 1.0;
 
+/// Minimum fraction of query trigrams a name must contain to be a fuzzy candidate
+const FUZZY_MIN_TRIGRAM_OVERLAP: f32 = 0.5;
+
 /// A posting in the inverted index, representing one occurrence of a term.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Posting {
     /// The node that contains this term
     pub node_id: NodeId,
@@ -46,7 +51,7 @@ pub struct TextSearchResult {
 }
 
 /// Why a result matched the query (for explainability).
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MatchReason {
     /// Matched in symbol name
     SymbolName,
@@ -56,10 +61,12 @@ pub enum MatchReason {
     Comment,
     /// Matched in multiple fields
     Multiple,
+    /// Approximate symbol name match (trigram + edit distance)
+    Fuzzy,
 }
 
 /// Text index using BM25 ranking for symbol search.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TextIndex {
     /// Token -> List of postings (node_id, term_frequency, weight)
     inverted_index: HashMap<String, Vec<Posting>>,
@@ -71,6 +78,10 @@ pub struct TextIndex {
     total_docs: usize,
     /// NodeId -> Primary match reason (for explainability)
     node_match_types: HashMap<NodeId, MatchReason>,
+    /// Trigram -> Nodes whose (lowercased) name contains it
+    trigram_index: HashMap<String, Vec<NodeId>>,
+    /// NodeId -> Lowercased symbol name (for edit distance)
+    names: HashMap<NodeId, String>,
 }
 
 impl TextIndex {
@@ -82,6 +93,8 @@ impl TextIndex {
             avg_document_length: 0.0,
             total_docs: 0,
             node_match_types: HashMap::new(),
+            trigram_index: HashMap::new(),
+            names: HashMap::new(),
         }
     }
 
@@ -151,6 +164,75 @@ impl TextIndex {
         results
     }
 
+    /// Fuzzy search over symbol names using the trigram index.
+    ///
+    /// Candidates must contain at least half of the query's trigrams. They are
+    /// ranked by a blend of trigram overlap and normalized edit distance, so a
+    /// misspelling (`getUserAcount`) and a partial name (`userAcc`) both find
+    /// `getUserAccount`. Scores are in 0.0-1.0, with 1.0 for an exact name.
+    pub fn fuzzy_search(&self, query: &str, limit: usize) -> Vec<TextSearchResult> {
+        let query = query.trim().to_lowercase();
+        let query_trigrams = trigrams(&query);
+        if query_trigrams.is_empty() {
+            return Vec::new();
+        }
+
+        let mut shared: HashMap<NodeId, usize> = HashMap::new();
+        for trigram in &query_trigrams {
+            if let Some(nodes) = self.trigram_index.get(trigram) {
+                for &node_id in nodes {
+                    *shared.entry(node_id).or_insert(0) += 1;
+                }
+            }
+        }
+
+        let query_len = query.chars().count();
+        let mut results: Vec<TextSearchResult> = shared
+            .into_iter()
+            .filter_map(|(node_id, count)| {
+                let overlap = count as f32 / query_trigrams.len() as f32;
+                if overlap < FUZZY_MIN_TRIGRAM_OVERLAP {
+                    return None;
+                }
+                let name = self.names.get(&node_id)?;
+                let max_len = query_len.max(name.chars().count()).max(1);
+                let similarity = 1.0 - edit_distance(&query, name) as f32 / max_len as f32;
+                Some(TextSearchResult {
+                    node_id,
+                    score: 0.5 * overlap + 0.5 * similarity,
+                    match_reason: MatchReason::Fuzzy,
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        results.truncate(limit);
+        results
+    }
+
+    /// Symbols whose name starts with `prefix` (case-insensitive), shortest
+    /// name first, for completing a partially typed symbol name.
+    pub fn prefix_search(&self, prefix: &str, limit: usize) -> Vec<NodeId> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return Vec::new();
+        }
+
+        let mut matches: Vec<(&String, NodeId)> = self
+            .names
+            .iter()
+            .filter(|(_, name)| name.starts_with(&prefix))
+            .map(|(&node_id, name)| (name, node_id))
+            .collect();
+        matches.sort_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.cmp(b)));
+        matches.truncate(limit);
+        matches.into_iter().map(|(_, node_id)| node_id).collect()
+    }
+
     /// Compute inverse document frequency for a term.
     fn compute_idf(&self, term: &str) -> f32 {
         let doc_freq = self
@@ -203,6 +285,8 @@ pub struct TextIndexBuilder {
     inverted_index: HashMap<String, Vec<Posting>>,
     doc_lengths: HashMap<NodeId, f32>,
     node_match_types: HashMap<NodeId, MatchReason>,
+    trigram_index: HashMap<String, Vec<NodeId>>,
+    names: HashMap<NodeId, String>,
     total_length: f32,
     total_docs: usize,
 }
@@ -214,6 +298,8 @@ impl TextIndexBuilder {
             inverted_index: HashMap::new(),
             doc_lengths: HashMap::new(),
             node_match_types: HashMap::new(),
+            trigram_index: HashMap::new(),
+            names: HashMap::new(),
             total_length: 0.0,
             total_docs: 0,
         }
@@ -240,6 +326,15 @@ impl TextIndexBuilder {
             has_name_match = true;
         }
 
+        // Index name trigrams for fuzzy matching
+        let lowered = name.to_lowercase();
+        for trigram in trigrams(&lowered) {
+            self.trigram_index.entry(trigram).or_default().push(node_id);
+        }
+        if !lowered.is_empty() {
+            self.names.insert(node_id, lowered);
+        }
+
         // Index docstring with medium weight
         if let Some(doc) = docstring {
             let doc_tokens = tokenize(doc);
@@ -314,6 +409,8 @@ impl TextIndexBuilder {
             avg_document_length,
             total_docs: self.total_docs,
             node_match_types: self.node_match_types,
+            trigram_index: self.trigram_index,
+            names: self.names,
         }
     }
 }
@@ -364,6 +461,49 @@ fn tokenize(text: &str) -> Vec<String> {
         .collect()
 }
 
+/// Distinct character trigrams of a string, padded with `$` at both ends
+/// so that short names and word boundaries still produce trigrams.
+fn trigrams(text: &str) -> Vec<String> {
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let padded: Vec<char> = std::iter::once('$')
+        .chain(text.chars())
+        .chain(std::iter::once('$'))
+        .collect();
+    let mut result: Vec<String> = padded.windows(3).map(|w| w.iter().collect()).collect();
+    result.sort();
+    result.dedup();
+    result
+}
+
+/// Optimal string alignment distance: Levenshtein plus adjacent transpositions.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (n, m) = (a.len(), b.len());
+    let mut dp = vec![vec![0usize; m + 1]; n + 1];
+    for (i, row) in dp.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for j in 0..=m {
+        dp[0][j] = j;
+    }
+
+    for i in 1..=n {
+        for j in 1..=m {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            dp[i][j] = (dp[i - 1][j] + 1)
+                .min(dp[i][j - 1] + 1)
+                .min(dp[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                dp[i][j] = dp[i][j].min(dp[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    dp[n][m]
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -745,4 +885,76 @@ mod tests {
         let results = index.search("validate", 10);
         assert_eq!(results.len(), 1);
     }
+
+    // ============================================
+    // Fuzzy Search Tests
+    // ============================================
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("account", "account"), 0);
+        assert_eq!(edit_distance("acount", "account"), 1);
+        assert_eq!(edit_distance("acocunt", "account"), 1); // transposition
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn test_trigrams_padded_and_distinct() {
+        assert_eq!(trigrams("ab"), vec!["$ab", "ab$"]);
+        assert_eq!(trigrams("aaaa"), vec!["$aa", "aa$", "aaa"]);
+        assert!(trigrams("").is_empty());
+    }
+
+    #[test]
+    fn test_prefix_search() {
+        let index = TextIndex::build(&[
+            (1, "getUserAccount".to_string(), None, vec![]),
+            (2, "getUser".to_string(), None, vec![]),
+            (3, "deleteAccount".to_string(), None, vec![]),
+        ]);
+
+        assert_eq!(index.prefix_search("getuser", 10), vec![2, 1]);
+        assert_eq!(index.prefix_search("GetUser", 1), vec![2]);
+        assert!(index.prefix_search("", 10).is_empty());
+        assert!(index.prefix_search("account", 10).is_empty());
+    }
+
+    #[test]
+    fn test_fuzzy_search_misspelling() {
+        let index = TextIndex::build(&[
+            (1, "getUserAccount".to_string(), None, vec![]),
+            (2, "getUserProfile".to_string(), None, vec![]),
+            (3, "deleteAccount".to_string(), None, vec![]),
+        ]);
+
+        let results = index.fuzzy_search("getUserAcount", 10);
+        assert!(!results.is_empty());
+        assert_eq!(results[0].node_id, 1);
+        assert_eq!(results[0].match_reason, MatchReason::Fuzzy);
+    }
+
+    #[test]
+    fn test_fuzzy_search_partial_name() {
+        let index = TextIndex::build(&[
+            (1, "getUserAccount".to_string(), None, vec![]),
+            (2, "renderSidebar".to_string(), None, vec![]),
+        ]);
+
+        let results = index.fuzzy_search("userAcc", 10);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].node_id, 1);
+    }
+
+    #[test]
+    fn test_fuzzy_search_exact_scores_highest() {
+        let index = TextIndex::build(&[
+            (1, "parseConfig".to_string(), None, vec![]),
+            (2, "parseConfigs".to_string(), None, vec![]),
+        ]);
+
+        let results = index.fuzzy_search("parseConfig", 10);
+        assert_eq!(results[0].node_id, 1);
+        assert!((results[0].score - 1.0).abs() < f32::EPSILON);
+        assert!(index.fuzzy_search("zzzz", 10).is_empty());
+    }
 }
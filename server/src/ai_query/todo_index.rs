@@ -0,0 +1,285 @@
+//! TODO Comment Index
+//!
+//! Finds structured code comments (`TODO`, `FIXME`, `HACK`, `SAFETY`) in source
+//! files, including an optional owner (`// TODO(alice): ...`).
+//!
+//! Comments are recorded as graph nodes when a file is parsed, each linked by a
+//! `Contains` edge from its innermost enclosing function or class, or from the
+//! file when it sits outside of any. The nodes carry the path of their file, so
+//! they are replaced along with its symbols, and are marked with a `kind`
+//! property that keeps them out of symbol queries.
+
+use super::primitives::TodoTag;
+use crate::domain::node_props;
+use codegraph::{
+    CodeGraph, Direction, EdgeType, Node, NodeId, NodeType, PropertyMap, PropertyValue,
+};
+use codegraph_parser_api::FileInfo;
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Comment marker followed by a tag, an optional `(owner)` and the text.
+const TODO_PATTERN: &str =
+    r"(?://[/!]?|#|/\*+|^\s*\*|--|;|<!--)\s*(TODO|FIXME|HACK|SAFETY)\b(?:\(([^)]*)\))?:?\s*(.*)";
+
+/// A single TODO comment found in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct TodoReference {
+    pub tag: TodoTag,
+    pub text: String,
+    pub owner: Option<String>,
+    /// Line number (1-indexed)
+    pub line: u32,
+    /// Column of the tag (0-indexed)
+    pub column: u32,
+}
+
+fn todo_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(TODO_PATTERN).expect("todo pattern must compile"))
+}
+
+fn parse_tag(tag: &str) -> Option<TodoTag> {
+    match tag {
+        "TODO" => Some(TodoTag::Todo),
+        "FIXME" => Some(TodoTag::Fixme),
+        "HACK" => Some(TodoTag::Hack),
+        "SAFETY" => Some(TodoTag::Safety),
+        _ => None,
+    }
+}
+
+/// Find every TODO comment in `content` (at most one per line).
+pub(crate) fn scan_todo_comments(content: &str) -> Vec<TodoReference> {
+    let regex = todo_regex();
+    let mut references = Vec::new();
+
+    for (idx, line) in content.lines().enumerate() {
+        let Some(captures) = regex.captures(line) else {
+            continue;
+        };
+        let (Some(tag), Some(text)) = (captures.get(1), captures.get(3)) else {
+            continue;
+        };
+        let Some(todo_tag) = parse_tag(tag.as_str()) else {
+            continue;
+        };
+        let text = text
+            .as_str()
+            .trim_end()
+            .trim_end_matches("*/")
+            .trim_end_matches("-->")
+            .trim_end();
+        references.push(TodoReference {
+            tag: todo_tag,
+            text: text.to_string(),
+            owner: captures
+                .get(2)
+                .map(|m| m.as_str().trim().to_string())
+                .filter(|owner| !owner.is_empty()),
+            line: idx as u32 + 1,
+            column: tag.start() as u32,
+        });
+    }
+
+    references
+}
+
+/// Add a node for each TODO comment of a freshly parsed file and link it to
+/// its innermost enclosing function or class.
+///
+/// Returns the number of comments recorded.
+pub(crate) fn record_todos(
+    graph: &mut CodeGraph,
+    file_info: &FileInfo,
+    path: &Path,
+    source: &str,
+) -> usize {
+    let references = scan_todo_comments(source);
+    if references.is_empty() {
+        return 0;
+    }
+
+    let scopes: Vec<(u32, u32, NodeId)> = file_info
+        .functions
+        .iter()
+        .chain(file_info.classes.iter())
+        .filter_map(|&node_id| {
+            let node = graph.get_node(node_id).ok()?;
+            let line_start = node_props::line_start(node);
+            Some((
+                line_start,
+                node_props::line_end(node).max(line_start),
+                node_id,
+            ))
+        })
+        .collect();
+    let path = path.to_string_lossy().to_string();
+
+    let mut recorded = 0;
+    for reference in references {
+        let container = scopes
+            .iter()
+            .filter(|(start, end, _)| *start <= reference.line && reference.line <= *end)
+            .min_by_key(|(start, end, _)| end - start)
+            .map_or(file_info.file_id, |(_, _, id)| *id);
+
+        let mut props = PropertyMap::new()
+            .with(
+                "name",
+                format!("{}: {}", reference.tag.as_str(), reference.text),
+            )
+            .with("kind", node_props::TODO_COMMENT_KIND)
+            .with("path", path.as_str())
+            .with("line_start", PropertyValue::Int(reference.line as i64))
+            .with("line_end", PropertyValue::Int(reference.line as i64))
+            .with("col_start", PropertyValue::Int(reference.column as i64))
+            .with("tag", reference.tag.as_str())
+            .with("text", reference.text.as_str());
+        if let Some(owner) = &reference.owner {
+            props = props.with("owner", owner.as_str());
+        }
+        // Outlines and symbol listings skip File nodes, like the file itself
+        let Ok(node_id) = graph.add_node(NodeType::File, props) else {
+            continue;
+        };
+        let _ = graph.add_edge(container, node_id, EdgeType::Contains, PropertyMap::new());
+        recorded += 1;
+    }
+    recorded
+}
+
+/// The comment recorded by a TODO node, if `node` is one.
+pub(crate) fn todo_of_node(node: &Node) -> Option<TodoReference> {
+    if !node_props::is_todo_comment(node) {
+        return None;
+    }
+    Some(TodoReference {
+        tag: parse_tag(node.properties.get_string("tag")?)?,
+        text: node
+            .properties
+            .get_string("text")
+            .unwrap_or_default()
+            .to_string(),
+        owner: node.properties.get_string("owner").map(str::to_string),
+        line: node_props::line_start(node),
+        column: node_props::col_start_from_props(&node.properties),
+    })
+}
+
+/// Function or class containing the TODO node `node_id`; None for comments
+/// outside of any symbol.
+pub(crate) fn enclosing_symbol(graph: &CodeGraph, node_id: NodeId) -> Option<NodeId> {
+    graph
+        .get_neighbors(node_id, Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .find(|&container| {
+            graph
+                .get_node(container)
+                .is_ok_and(|node| matches!(node.node_type, NodeType::Function | NodeType::Class))
+                && graph
+                    .get_edges_between(container, node_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .any(|edge_id| {
+                        graph
+                            .get_edge(edge_id)
+                            .is_ok_and(|edge| edge.edge_type == EdgeType::Contains)
+                    })
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_todo_comments_across_languages() {
+        let content = "\
+fn load() {
+    // TODO(alice): cache the parsed config
+    let x = 1; // FIXME handle overflow
+    // SAFETY: the pointer is valid for the lifetime of the buffer
+}
+# HACK: work around upstream bug
+/* TODO: remove after migration */
+let todos = 0; // not a TODOS marker
+";
+        let refs = scan_todo_comments(content);
+        let summary: Vec<(TodoTag, &str, Option<&str>, u32)> = refs
+            .iter()
+            .map(|r| (r.tag, r.text.as_str(), r.owner.as_deref(), r.line))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (TodoTag::Todo, "cache the parsed config", Some("alice"), 2),
+                (TodoTag::Fixme, "handle overflow", None, 3),
+                (
+                    TodoTag::Safety,
+                    "the pointer is valid for the lifetime of the buffer",
+                    None,
+                    4
+                ),
+                (TodoTag::Hack, "work around upstream bug", None, 6),
+                (TodoTag::Todo, "remove after migration", None, 7),
+            ]
+        );
+        assert_eq!(refs[0].column, 7);
+    }
+
+    #[test]
+    fn test_record_todos_links_enclosing_symbols() {
+        let source = "\
+# TODO: split this module
+def sync():
+    # FIXME(bob): retry on timeout
+    pass
+";
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let file_id = graph
+            .add_node(
+                NodeType::CodeFile,
+                PropertyMap::new().with("path", "/src/sync.py"),
+            )
+            .unwrap();
+        let sync = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "sync")
+                    .with("path", "/src/sync.py")
+                    .with("line_start", PropertyValue::Int(2))
+                    .with("line_end", PropertyValue::Int(4)),
+            )
+            .unwrap();
+        let file_info = FileInfo {
+            file_path: "/src/sync.py".into(),
+            file_id,
+            functions: vec![sync],
+            classes: vec![],
+            traits: vec![],
+            imports: vec![],
+            parse_time: std::time::Duration::ZERO,
+            line_count: 0,
+            byte_count: 0,
+        };
+
+        let recorded = record_todos(&mut graph, &file_info, Path::new("/src/sync.py"), source);
+        assert_eq!(recorded, 2);
+
+        let mut todos: Vec<(TodoReference, Option<NodeId>)> = graph
+            .iter_nodes()
+            .filter_map(|(id, node)| Some((todo_of_node(node)?, enclosing_symbol(&graph, id))))
+            .collect();
+        todos.sort_by_key(|(todo, _)| todo.line);
+        assert_eq!(todos[0].0.tag, TodoTag::Todo);
+        assert_eq!(todos[0].1, None);
+        assert_eq!(todos[1].0.text, "retry on timeout");
+        assert_eq!(todos[1].0.owner.as_deref(), Some("bob"));
+        assert_eq!(todos[1].1, Some(sync));
+        assert!(todo_of_node(graph.get_node(sync).unwrap()).is_none());
+    }
+}
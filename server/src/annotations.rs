@@ -0,0 +1,323 @@
+//! Decorator and attribute recording for functions and classes.
+//!
+//! Some parsers already store decorators in the `attributes` node property;
+//! others drop them. After a file is parsed, [`record_annotations`] fills in
+//! `attributes` for every function, class and trait in the file that has none,
+//! by reading the annotation lines around the symbol's declaration:
+//!
+//! - Python/TypeScript decorators: `@app.route("/x")`, `@Injectable()`
+//! - Java/Kotlin/C# annotations: `@Deprecated`, `@GetMapping("/users")`
+//! - Rust attributes: `#[tokio::test]`, `#[get("/")]`
+//! - C# attributes: `[HttpGet("/users")]`
+//!
+//! Route detection, entry point rules and `findByAnnotation` all read the
+//! recorded property.
+
+use codegraph::{CodeGraph, NodeId, PropertyMap, PropertyValue};
+use codegraph_parser_api::FileInfo;
+
+/// Maximum number of annotation lines read on either side of a declaration.
+const MAX_ANNOTATION_LINES: usize = 16;
+
+/// Record annotations for the symbols of a freshly parsed file.
+///
+/// Returns the number of symbols that received an `attributes` property.
+pub fn record_annotations(graph: &mut CodeGraph, file_info: &FileInfo, source: &str) -> usize {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut updates: Vec<(NodeId, Vec<String>)> = Vec::new();
+
+    for &node_id in file_info
+        .functions
+        .iter()
+        .chain(file_info.classes.iter())
+        .chain(file_info.traits.iter())
+    {
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        if node
+            .properties
+            .get_string_list_compat("attributes")
+            .is_some_and(|attrs| !attrs.is_empty())
+        {
+            continue;
+        }
+        let line_start = crate::domain::node_props::line_start(node);
+        let annotations = scan_annotations(&lines, line_start);
+        if !annotations.is_empty() {
+            updates.push((node_id, annotations));
+        }
+    }
+
+    let count = updates.len();
+    for (node_id, annotations) in updates {
+        let props = PropertyMap::new().with("attributes", PropertyValue::StringList(annotations));
+        let _ = graph.update_node_properties(node_id, props);
+    }
+    count
+}
+
+/// Annotations attached to the declaration starting at `line_start` (1-indexed).
+///
+/// Reads annotation lines directly above the declaration, skipping comments,
+/// and annotation lines at the start of the declaration itself (parsers that
+/// include modifiers in the symbol range start at the first annotation). The
+/// upward scan stops at a line with code after its annotations, such as
+/// `@Autowired private Repo repo;`, whose annotations belong to that line.
+pub(crate) fn scan_annotations(lines: &[&str], line_start: u32) -> Vec<String> {
+    let Some(decl) = (line_start as usize).checked_sub(1) else {
+        return Vec::new();
+    };
+    if decl >= lines.len() {
+        return Vec::new();
+    }
+
+    let mut above = Vec::new();
+    for line in lines[..decl].iter().rev().take(MAX_ANNOTATION_LINES) {
+        let trimmed = line.trim();
+        if trimmed.starts_with("//") || is_python_comment(trimmed) {
+            continue;
+        }
+        let (found, rest) = split_annotations(trimmed);
+        if found.is_empty() || is_code(rest) {
+            break;
+        }
+        above.push(found);
+    }
+
+    let mut annotations: Vec<String> = above.into_iter().rev().flatten().collect();
+    for line in lines[decl..].iter().take(MAX_ANNOTATION_LINES) {
+        let (found, rest) = split_annotations(line.trim());
+        annotations.extend(found);
+        if !rest.is_empty() {
+            break;
+        }
+    }
+    annotations
+}
+
+/// Whether the text after a line's annotations is code rather than nothing
+/// or a trailing comment.
+fn is_code(rest: &str) -> bool {
+    let rest = rest.trim();
+    !(rest.is_empty()
+        || rest.starts_with("//")
+        || rest.starts_with("/*")
+        || is_python_comment(rest))
+}
+
+fn is_python_comment(line: &str) -> bool {
+    line.starts_with('#') && !line.starts_with("#[") && !line.starts_with("#!")
+}
+
+/// Leading annotations on a line: `@Name(args)`, `#[path(args)]` or `[Name(args)]`.
+///
+/// Stops at the first token that is not an annotation, so `@Override public void
+/// run()` yields `["@Override"]`.
+pub(crate) fn extract_annotations(line: &str) -> Vec<String> {
+    split_annotations(line).0
+}
+
+/// Leading annotations on a line and the text that follows them.
+fn split_annotations(line: &str) -> (Vec<String>, &str) {
+    let mut found = Vec::new();
+    let mut rest = line;
+    loop {
+        rest = rest.trim_start();
+        let len = if rest.starts_with('@') {
+            let name_len = rest[1..]
+                .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+                .unwrap_or(rest.len() - 1);
+            if name_len == 0 {
+                break;
+            }
+            let after = 1 + name_len;
+            match balanced_len(&rest[after..], '(', ')') {
+                Some(args) => after + args,
+                None => after,
+            }
+        } else if rest.starts_with("#[") {
+            1 + balanced_len(&rest[1..], '[', ']').unwrap_or(rest.len() - 1)
+        } else if rest.starts_with('[') && rest[1..].starts_with(|c: char| c.is_alphabetic()) {
+            match balanced_len(rest, '[', ']') {
+                Some(len) => len,
+                None => break,
+            }
+        } else {
+            break;
+        };
+        found.push(rest[..len].trim_end().to_string());
+        rest = &rest[len..];
+    }
+    (found, rest)
+}
+
+/// Length of a bracketed group at the start of `text`, including both brackets.
+///
+/// An unterminated group (arguments continuing on the next line) spans the rest
+/// of the line. Returns None if `text` does not start with `open`.
+fn balanced_len(text: &str, open: char, close: char) -> Option<usize> {
+    if !text.starts_with(open) {
+        return None;
+    }
+    let mut depth = 0usize;
+    let mut quote: Option<char> = None;
+    for (i, c) in text.char_indices() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == open => depth += 1,
+            None if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + c.len_utf8());
+                }
+            }
+            None => {}
+        }
+    }
+    Some(text.len())
+}
+
+/// Annotation name without its sigil, brackets or arguments.
+///
+/// `@app.route("/x")` -> `app.route`, `#[tokio::test]` -> `tokio::test`,
+/// `[HttpGet("/users")]` -> `HttpGet`.
+pub(crate) fn annotation_name(annotation: &str) -> &str {
+    let trimmed = strip_sigil(annotation);
+    let end = trimmed
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.' || c == ':'))
+        .unwrap_or(trimmed.len());
+    &trimmed[..end]
+}
+
+fn strip_sigil(annotation: &str) -> &str {
+    let trimmed = annotation.trim();
+    trimmed
+        .strip_prefix('@')
+        .or_else(|| trimmed.strip_prefix("#["))
+        .or_else(|| trimmed.strip_prefix('['))
+        .unwrap_or(trimmed)
+}
+
+/// Whether an annotation matches a query such as `@app.route`, `tokio::test` or
+/// `Deprecated`.
+///
+/// The query's name must equal the annotation's name or a trailing segment of
+/// it, so `Deprecated` also matches `@java.lang.Deprecated` and `test` matches
+/// `#[tokio::test]`. A query with arguments (`@app.get("/users")`) must match
+/// the start of the annotation text instead.
+pub(crate) fn annotation_matches(annotation: &str, query: &str) -> bool {
+    let query_name = annotation_name(query);
+    if query_name.is_empty() {
+        return false;
+    }
+    if query.contains('(') {
+        let normalize = |s: &str| strip_sigil(s).replace(' ', "");
+        let query = normalize(query);
+        return normalize(annotation).starts_with(query.trim_end_matches([')', ']']));
+    }
+
+    let name = annotation_name(annotation);
+    name == query_name
+        || name
+            .strip_suffix(query_name)
+            .is_some_and(|prefix| prefix.ends_with('.') || prefix.ends_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_annotations() {
+        assert_eq!(
+            extract_annotations("@app.get(\"/users\", response_model=List[User])"),
+            vec!["@app.get(\"/users\", response_model=List[User])"]
+        );
+        assert_eq!(
+            extract_annotations("@Override public void run() {"),
+            vec!["@Override"]
+        );
+        assert_eq!(
+            extract_annotations("#[tokio::test] #[ignore = \"slow\"]"),
+            vec!["#[tokio::test]", "#[ignore = \"slow\"]"]
+        );
+        assert_eq!(
+            extract_annotations("[HttpGet(\"{id}\")]"),
+            vec!["[HttpGet(\"{id}\")]"]
+        );
+        assert!(extract_annotations("def handler(request):").is_empty());
+        assert!(extract_annotations("user@example.com").is_empty());
+    }
+
+    #[test]
+    fn test_scan_annotations_around_declaration() {
+        let source = "\
+import os
+
+@requires_auth
+# comment between decorators
+@router.post(\"/items\")
+def create_item():
+    pass
+
+@Deprecated
+@GetMapping(\"/users\")
+public List<User> list() {}
+";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(
+            scan_annotations(&lines, 6),
+            vec!["@requires_auth", "@router.post(\"/items\")"]
+        );
+        // Symbol range starting at its first annotation
+        assert_eq!(
+            scan_annotations(&lines, 9),
+            vec!["@Deprecated", "@GetMapping(\"/users\")"]
+        );
+        assert!(scan_annotations(&lines, 1).is_empty());
+    }
+
+    #[test]
+    fn test_scan_annotations_stops_at_annotated_code() {
+        let source = "\
+class UserService {
+    @Autowired private Repo repo;
+    @Transactional // writes
+    public void save() {}
+}
+";
+        let lines: Vec<&str> = source.lines().collect();
+        assert_eq!(scan_annotations(&lines, 4), vec!["@Transactional"]);
+
+        let source = "\
+@Input() name: string;
+ngOnInit() {}
+";
+        let lines: Vec<&str> = source.lines().collect();
+        assert!(scan_annotations(&lines, 2).is_empty());
+    }
+
+    #[test]
+    fn test_annotation_matches() {
+        assert!(annotation_matches("@app.route(\"/x\")", "@app.route"));
+        assert!(annotation_matches("@app.route(\"/x\")", "app.route"));
+        assert!(annotation_matches("#[tokio::test]", "#[tokio::test]"));
+        assert!(annotation_matches("#[tokio::test]", "test"));
+        assert!(annotation_matches("@java.lang.Deprecated", "@Deprecated"));
+        assert!(annotation_matches("[HttpGet(\"{id}\")]", "HttpGet"));
+        assert!(annotation_matches(
+            "@app.get(\"/users\")",
+            "@app.get(\"/users\")"
+        ));
+        assert!(!annotation_matches(
+            "@app.get(\"/users\")",
+            "@app.get(\"/items\")"
+        ));
+        assert!(!annotation_matches("@app.routes", "@app.route"));
+        assert!(!annotation_matches("@myapp.route", "app.route"));
+        assert!(!annotation_matches("#[test]", "tokio::test"));
+    }
+}
@@ -5,23 +5,42 @@
 use crate::ai_query::QueryEngine;
 use crate::branch_watcher::BranchWatcher;
 use crate::cache::QueryCache;
+use crate::cancellation::{run_cancellable, CancellationToken};
+use crate::document::{changed_rows, OpenDocument};
 use crate::domain::node_props;
 use crate::error::{LspError, LspResult};
+use crate::graph_snapshot::{graph_dir, GraphSnapshot};
+use crate::handlers::StaleMemory;
 use crate::index::SymbolIndex;
+use crate::index_pipeline::{parse_concurrency, parse_files, FileFragment};
+use crate::index_scheduler::{import_candidates, IndexScheduler};
 use crate::memory::MemoryManager;
 use crate::parser_registry::ParserRegistry;
+use crate::progress::{ProgressTracker, WorkProgress};
 use crate::watcher::{FileWatcher, GraphUpdater};
+use crate::workspace_walk::WorkspaceWalk;
 use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType};
 use codegraph_parser_api::FileInfo;
 use dashmap::DashMap;
+use futures::StreamExt;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::{Mutex, RwLock};
 use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::request::{
+    GotoDeclarationParams, GotoDeclarationResponse, GotoImplementationParams,
+    GotoImplementationResponse,
+};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
-/// Configuration for indexing behavior, populated from VS Code settings.
+/// Delay after the last edit before an open document is re-parsed.
+const REPARSE_DEBOUNCE_MS: u64 = 300;
+
+/// Server settings, populated from the `codegraph` section of VS Code settings.
+///
+/// Received in the initialization options and again on every
+/// `workspace/didChangeConfiguration`; see [`CodeGraphBackend::apply_configuration`].
 #[derive(Debug, Clone, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct CodeGraphConfig {
@@ -29,28 +48,154 @@ pub struct CodeGraphConfig {
     pub index_on_startup: bool,
     #[serde(default)]
     pub exclude_patterns: Vec<String>,
+    /// Exclude patterns set on individual workspace folders, keyed by folder
+    /// path. They only apply to files inside that folder.
+    #[serde(default)]
+    pub folder_excludes: std::collections::HashMap<String, Vec<String>>,
     #[serde(default)]
     pub index_paths: Vec<String>,
-    #[serde(default = "default_max_file_size_kb")]
+    #[serde(
+        rename = "maxFileSizeKB",
+        alias = "maxFileSizeKb",
+        default = "default_max_file_size_kb"
+    )]
     pub max_file_size_kb: u64,
+    #[serde(default)]
+    pub inlay_hints: InlayHintConfig,
+    #[serde(default)]
+    pub search: SearchWeightsConfig,
+    #[serde(default)]
+    pub memory: MemoryConfig,
+    /// Time budget of graph traversals and searches, in milliseconds. Past
+    /// it they return what they found so far, marked truncated. 0 disables it.
+    #[serde(default = "default_query_timeout_ms")]
+    pub query_timeout_ms: u64,
 }
 
 fn default_max_file_size_kb() -> u64 {
     1024
 }
 
+fn default_query_timeout_ms() -> u64 {
+    10_000
+}
+
 impl Default for CodeGraphConfig {
     fn default() -> Self {
         Self {
             index_on_startup: false,
             exclude_patterns: Vec::new(),
+            folder_excludes: std::collections::HashMap::new(),
             index_paths: Vec::new(),
             max_file_size_kb: 1024,
+            inlay_hints: InlayHintConfig::default(),
+            search: SearchWeightsConfig::default(),
+            memory: MemoryConfig::default(),
+            query_timeout_ms: default_query_timeout_ms(),
+        }
+    }
+}
+
+impl CodeGraphConfig {
+    /// Time budget of a graph traversal or search, if any.
+    pub fn query_budget(&self) -> Option<std::time::Duration> {
+        (self.query_timeout_ms > 0).then(|| std::time::Duration::from_millis(self.query_timeout_ms))
+    }
+
+    /// All exclude globs: the global patterns, plus each folder's patterns
+    /// anchored to the folder path.
+    pub fn exclude_globs(&self) -> Vec<String> {
+        let mut globs = self.exclude_patterns.clone();
+        for (folder, patterns) in &self.folder_excludes {
+            let root = globset::escape(folder.trim_end_matches(['/', '\\']));
+            globs.extend(
+                patterns
+                    .iter()
+                    .map(|pattern| format!("{root}/{}", pattern.trim_start_matches("./"))),
+            );
+        }
+        globs
+    }
+}
+
+/// Weights of the hybrid (BM25 + semantic) symbol search.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SearchWeightsConfig {
+    pub bm25_weight: f32,
+    pub semantic_weight: f32,
+}
+
+impl Default for SearchWeightsConfig {
+    fn default() -> Self {
+        Self {
+            bm25_weight: crate::ai_query::BM25_WEIGHT,
+            semantic_weight: crate::ai_query::SEMANTIC_WEIGHT,
+        }
+    }
+}
+
+/// Memory layer options.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MemoryConfig {
+    /// Invalidate memories when their linked code changes
+    pub auto_invalidate: bool,
+    pub git_mining: GitMiningDefaults,
+}
+
+impl Default for MemoryConfig {
+    fn default() -> Self {
+        Self {
+            auto_invalidate: true,
+            git_mining: GitMiningDefaults::default(),
+        }
+    }
+}
+
+/// Defaults for git mining requests that don't set their own limits.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct GitMiningDefaults {
+    pub max_commits: usize,
+    pub min_confidence: f32,
+}
+
+impl Default for GitMiningDefaults {
+    fn default() -> Self {
+        Self {
+            max_commits: 500,
+            min_confidence: 0.7,
+        }
+    }
+}
+
+/// Inlay hints shown at function definitions.
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InlayHintConfig {
+    /// Show "↖ N callers"
+    pub callers: bool,
+    /// Show "complexity N" for functions at or above the threshold
+    pub complexity: bool,
+    pub complexity_threshold: u32,
+}
+
+impl Default for InlayHintConfig {
+    fn default() -> Self {
+        Self {
+            callers: true,
+            complexity: true,
+            complexity_threshold: 10,
         }
     }
 }
 
 /// CodeGraph Language Server backend.
+///
+/// Cloning is cheap: all state is shared, so clones can be moved into
+/// background tasks.
+#[derive(Clone)]
 pub struct CodeGraphBackend {
     /// LSP client for sending notifications.
     pub client: Client,
@@ -64,6 +209,9 @@ pub struct CodeGraphBackend {
     /// File cache: URI -> FileInfo.
     pub file_cache: Arc<DashMap<Url, FileInfo>>,
 
+    /// Documents open in the editor, kept in sync with incremental changes.
+    pub open_documents: Arc<DashMap<Url, OpenDocument>>,
+
     /// Query cache for performance.
     pub query_cache: Arc<QueryCache>,
 
@@ -80,13 +228,53 @@ pub struct CodeGraphBackend {
     pub workspace_folders: Arc<RwLock<Vec<std::path::PathBuf>>>,
 
     /// File system watcher for incremental updates.
-    file_watcher: Arc<Mutex<Option<FileWatcher>>>,
+    pub(crate) file_watcher: Arc<Mutex<Option<FileWatcher>>>,
 
     /// Branch watcher for detecting git branch switches.
     branch_watcher: Arc<Mutex<Option<BranchWatcher>>>,
 
     /// Indexing configuration from VS Code settings.
     pub config: Arc<RwLock<CodeGraphConfig>>,
+
+    /// Diagnostics currently published, by file and by the code of the
+    /// feature that produced them (layer violations, stale memories).
+    pub diagnostics: Arc<Mutex<PublishedDiagnostics>>,
+
+    /// Auto-invalidated memories by the file they were linked into.
+    pub stale_memories: Arc<Mutex<std::collections::HashMap<PathBuf, Vec<StaleMemory>>>>,
+
+    /// Work-done progress of indexing and git mining.
+    pub progress: Arc<ProgressTracker>,
+
+    /// Puts open documents and their imports ahead of bulk indexing.
+    pub index_scheduler: Arc<IndexScheduler>,
+}
+
+/// Diagnostics published per file, by the code of the feature that produced
+/// them.
+pub type PublishedDiagnostics =
+    std::collections::HashMap<Url, std::collections::BTreeMap<String, Vec<Diagnostic>>>;
+
+/// Replace the `set` of diagnostics of `uri` in `published` and return all of
+/// the file's diagnostics, which is what the client must be sent: each
+/// publication replaces every diagnostic of the file.
+pub(crate) fn merge_diagnostic_set(
+    published: &mut PublishedDiagnostics,
+    uri: &Url,
+    set: &str,
+    diagnostics: Vec<Diagnostic>,
+) -> Vec<Diagnostic> {
+    let sets = published.entry(uri.clone()).or_default();
+    if diagnostics.is_empty() {
+        sets.remove(set);
+    } else {
+        sets.insert(set.to_string(), diagnostics);
+    }
+    let merged = sets.values().flatten().cloned().collect();
+    if sets.is_empty() {
+        published.remove(uri);
+    }
+    merged
 }
 
 impl CodeGraphBackend {
@@ -100,8 +288,9 @@ impl CodeGraphBackend {
             client,
             query_engine: Arc::new(QueryEngine::new(Arc::clone(&graph))),
             graph,
-            parsers: Arc::new(ParserRegistry::new()),
+            parsers: Arc::new(ParserRegistry::with_installed_plugins()),
             file_cache: Arc::new(DashMap::new()),
+            open_documents: Arc::new(DashMap::new()),
             query_cache: Arc::new(QueryCache::new(1000)),
             symbol_index: Arc::new(SymbolIndex::new()),
             memory_manager: Arc::new(MemoryManager::new(None)),
@@ -109,6 +298,10 @@ impl CodeGraphBackend {
             file_watcher: Arc::new(Mutex::new(None)),
             branch_watcher: Arc::new(Mutex::new(None)),
             config: Arc::new(RwLock::new(CodeGraphConfig::default())),
+            diagnostics: Arc::new(Mutex::new(PublishedDiagnostics::new())),
+            stale_memories: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            progress: Arc::new(ProgressTracker::new()),
+            index_scheduler: Arc::new(IndexScheduler::new()),
         }
     }
 
@@ -128,6 +321,7 @@ impl CodeGraphBackend {
             graph,
             parsers: Arc::new(ParserRegistry::new()),
             file_cache: Arc::new(DashMap::new()),
+            open_documents: Arc::new(DashMap::new()),
             query_cache: Arc::new(QueryCache::new(1000)),
             symbol_index: Arc::new(SymbolIndex::new()),
             memory_manager: Arc::new(MemoryManager::new(None)),
@@ -135,6 +329,10 @@ impl CodeGraphBackend {
             file_watcher: Arc::new(Mutex::new(None)),
             branch_watcher: Arc::new(Mutex::new(None)),
             config: Arc::new(RwLock::new(CodeGraphConfig::default())),
+            diagnostics: Arc::new(Mutex::new(PublishedDiagnostics::new())),
+            stale_memories: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            progress: Arc::new(ProgressTracker::new()),
+            index_scheduler: Arc::new(IndexScheduler::new()),
         }
     }
 
@@ -146,8 +344,12 @@ impl CodeGraphBackend {
             Arc::clone(&self.parsers),
             self.client.clone(),
             Arc::clone(&self.memory_manager),
+            Arc::clone(&self.query_cache),
         ) {
             Ok(mut watcher) => {
+                let config = self.config.read().await.clone();
+                watcher.set_workspace_walk(self.workspace_walk(&config));
+
                 // Start watching each folder
                 for folder in folders {
                     if let Err(e) = watcher.watch(folder) {
@@ -201,6 +403,43 @@ impl CodeGraphBackend {
         }
     }
 
+    /// Replace the settings and push them to the components that read them at
+    /// runtime, so changes take effect without a restart.
+    pub async fn apply_configuration(&self, config: CodeGraphConfig) {
+        tracing::info!("Configuration updated: {:?}", config);
+        *self.config.write().await = config;
+        self.propagate_configuration().await;
+        self.refresh_code_lenses_and_hints();
+    }
+
+    /// Push the current settings to the query engine, memory manager and
+    /// file watcher.
+    async fn propagate_configuration(&self) {
+        let config = self.config.read().await.clone();
+        self.query_engine
+            .set_search_weights(config.search.bm25_weight, config.search.semantic_weight);
+        self.memory_manager
+            .set_auto_invalidate(config.memory.auto_invalidate);
+        if let Some(watcher) = self.file_watcher.lock().await.as_ref() {
+            watcher.set_workspace_walk(self.workspace_walk(&config));
+        }
+    }
+
+    /// Ask the editor to re-request code lenses and inlay hints, whose counts
+    /// can change with any file. Sent in the background so the caller never
+    /// waits on the client.
+    pub fn refresh_code_lenses_and_hints(&self) {
+        let client = self.client.clone();
+        tokio::spawn(async move {
+            if let Err(e) = client.code_lens_refresh().await {
+                tracing::debug!("Code lens refresh not supported by client: {}", e);
+            }
+            if let Err(e) = client.inlay_hint_refresh().await {
+                tracing::debug!("Inlay hint refresh not supported by client: {}", e);
+            }
+        });
+    }
+
     /// Start the branch watcher for git-aware re-indexing on branch switches.
     pub async fn start_branch_watcher(&self, workspace_root: &Path) {
         match BranchWatcher::new(
@@ -230,10 +469,425 @@ impl CodeGraphBackend {
         }
     }
 
+    /// Load entry point detection rules for the first workspace folder.
+    async fn load_entry_rules(&self) {
+        let Some(workspace) = self.workspace_folders.read().await.first().cloned() else {
+            return;
+        };
+        if let Err(e) = self.query_engine.load_entry_rules(&workspace) {
+            self.client.log_message(MessageType::WARNING, e).await;
+        }
+    }
+
+    /// The workspace folder containing `path` (the innermost one when folders
+    /// are nested), or the first folder when `path` is outside all of them.
+    pub(crate) async fn workspace_folder_for(&self, path: Option<&Path>) -> Option<PathBuf> {
+        let folders = self.workspace_folders.read().await;
+        path.and_then(|path| {
+            folders
+                .iter()
+                .filter(|folder| path.starts_with(folder))
+                .max_by_key(|folder| folder.components().count())
+        })
+        .or_else(|| folders.first())
+        .cloned()
+    }
+
+    /// Initialize the memory store of one workspace folder. Returns whether
+    /// it succeeded.
+    async fn initialize_folder_memory(&self, folder: &Path) -> bool {
+        tracing::info!(
+            "Starting memory store initialization for workspace: {}",
+            folder.display()
+        );
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!(
+                    "[DEBUG] Initializing memory store for: {}",
+                    folder.display()
+                ),
+            )
+            .await;
+
+        match self.memory_manager.initialize(folder).await {
+            Ok(_) => {
+                tracing::info!("Memory store initialization succeeded");
+                self.client
+                    .log_message(
+                        MessageType::INFO,
+                        format!("✓ Memory store initialized for {}", folder.display()),
+                    )
+                    .await;
+                true
+            }
+            Err(e) => {
+                tracing::error!("Memory store initialization failed: {:?}", e);
+                self.client
+                    .log_message(
+                        MessageType::ERROR,
+                        format!("✗ Failed to initialize memory store for {}: {}. Memory features will be disabled for this folder.", folder.display(), e),
+                    )
+                    .await;
+                false
+            }
+        }
+    }
+
+    /// Re-parse an open document from its in-memory text, unless it has
+    /// changed again (or been closed) since `version`.
+    async fn reparse_open_document(&self, uri: &Url, version: i32) {
+        let Ok(path) = uri.to_file_path() else {
+            return;
+        };
+        if self.parsers.parser_for_path(&path).is_none() {
+            return;
+        }
+        let grammar = self.parsers.grammar_for_path(&path);
+        let (text, edits, syntax_rows) = match self.open_documents.get_mut(uri) {
+            Some(mut doc) if doc.version == version => {
+                // Reparse the edited syntax tree; an edit can change the
+                // syntax of rows it did not touch, e.g. by opening a comment
+                let syntax_rows = grammar.and_then(|grammar| doc.parse_tree(&grammar));
+                (doc.text(), doc.take_edits(), syntax_rows)
+            }
+            _ => return,
+        };
+        let fragment = match FileFragment::parse_source(&self.parsers, &path, &text) {
+            Ok(fragment) => fragment,
+            Err(e) => {
+                // Keep the last good parse; the edits apply to the next one
+                tracing::debug!("Failed to reparse {:?}: {}", path, e);
+                if let Some(mut doc) = self.open_documents.get_mut(uri) {
+                    doc.restore_edits(edits);
+                }
+                return;
+            }
+        };
+
+        // Update only the symbols in the edited lines
+        let mut rows = changed_rows(&edits);
+        rows.extend(syntax_rows.into_iter().flatten());
+        let anchors = self.memory_link_anchors(&path).await;
+        let path_str = path.to_string_lossy().to_string();
+        let update = {
+            let mut graph = self.graph.write().await;
+            let update = crate::incremental::apply_fragment(&mut graph, &path_str, fragment, &rows);
+            // Resolve cross-file imports after parsing
+            GraphUpdater::resolve_cross_file_imports(&mut graph);
+
+            self.symbol_index.remove_file(&path);
+            self.symbol_index
+                .add_file(path.clone(), &update.file_info, &graph);
+            update
+        };
+        tracing::debug!(
+            "Reparsed {:?}: {} symbols kept, {} replaced or removed",
+            path,
+            update.kept,
+            update.removed.len()
+        );
+        self.query_cache.invalidate_file(&path);
+        self.file_cache.insert(uri.clone(), update.file_info);
+
+        let removed: Vec<String> = update.removed.iter().map(|id| id.to_string()).collect();
+        self.invalidate_node_memories(&path, &anchors, &removed)
+            .await;
+        self.publish_stale_memory_diagnostics(&path).await;
+
+        // Rebuild AI query engine indexes so callee/caller indexes reflect new node IDs
+        self.query_engine.build_indexes().await;
+    }
+
+    /// Ask the client to report changes to source files and deletions of
+    /// anything (a deleted directory arrives as one event). Covers setups where
+    /// the native watcher misses events: remote, WSL and containers.
+    async fn register_watched_files(&self) {
+        let extensions: Vec<&str> = self
+            .parsers
+            .supported_extensions()
+            .into_iter()
+            .map(|e| e.trim_start_matches('.'))
+            .collect();
+        let watchers = vec![
+            FileSystemWatcher {
+                glob_pattern: GlobPattern::String(format!("**/*.{{{}}}", extensions.join(","))),
+                kind: None,
+            },
+            FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/.codegraph/entry_points.json".to_string()),
+                kind: None,
+            },
+            FileSystemWatcher {
+                glob_pattern: GlobPattern::String("**/*".to_string()),
+                kind: Some(WatchKind::Delete),
+            },
+        ];
+        let registration = Registration {
+            id: "codegraph-watched-files".to_string(),
+            method: "workspace/didChangeWatchedFiles".to_string(),
+            register_options: serde_json::to_value(DidChangeWatchedFilesRegistrationOptions {
+                watchers,
+            })
+            .ok(),
+        };
+        if let Err(e) = self.client.register_capability(vec![registration]).await {
+            tracing::debug!("Client does not support watched files registration: {}", e);
+        }
+    }
+
+    /// Re-index a file from disk after an external change. Returns whether the
+    /// graph changed; cross-file resolution is left to the caller so a batch
+    /// of changes is resolved once.
+    async fn reindex_file_from_disk(&self, path: &Path) -> bool {
+        let Some(parser) = self.parsers.parser_for_path(path) else {
+            return false;
+        };
+
+        // Same rules as indexing: excludes, size limit, and indexOnStartup=false
+        // only keeps already indexed files up to date
+        let config = self.config.read().await.clone();
+        let exclude_set = Self::build_exclude_set(&config.exclude_globs());
+        if exclude_set.is_match(path) {
+            return false;
+        }
+        if !config.index_on_startup && self.symbol_index.get_file_symbols(path).is_empty() {
+            return false;
+        }
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() <= config.max_file_size_kb * 1024 => {}
+            _ => return false,
+        }
+        let Ok(text) = tokio::fs::read_to_string(path).await else {
+            return false;
+        };
+
+        self.remove_file_from_graph(path).await;
+        let mut graph = self.graph.write().await;
+        match parser.parse_source(&text, path, &mut graph) {
+            Ok(file_info) => {
+                self.parsers
+                    .enrich_parsed_file(&mut graph, &file_info, path, &text);
+                self.symbol_index
+                    .add_file(path.to_path_buf(), &file_info, &graph);
+                if let Ok(uri) = Url::from_file_path(path) {
+                    self.file_cache.insert(uri, file_info);
+                }
+            }
+            Err(e) => tracing::warn!("Failed to parse {:?}: {}", path, e),
+        }
+        drop(graph);
+        self.publish_stale_memory_diagnostics(path).await;
+        true
+    }
+
+    /// Files that `path` imports through relative imports, as far as they
+    /// exist on disk.
+    fn direct_import_files(&self, graph: &CodeGraph, path: &Path) -> Vec<PathBuf> {
+        let Ok(Some(file_node)) =
+            codegraph::helpers::find_file_by_path(graph, &path.to_string_lossy())
+        else {
+            return Vec::new();
+        };
+        let extensions: Vec<&str> = self
+            .parsers
+            .supported_extensions()
+            .into_iter()
+            .map(|e| e.trim_start_matches('.'))
+            .collect();
+
+        let mut files = Vec::new();
+        for target in graph
+            .get_neighbors(file_node, Direction::Outgoing)
+            .unwrap_or_default()
+        {
+            let Ok(node) = graph.get_node(target) else {
+                continue;
+            };
+            if node.node_type != NodeType::Import {
+                continue;
+            }
+            let resolved = import_candidates(path, node_props::name(node), &extensions)
+                .into_iter()
+                .find(|candidate| candidate.is_file());
+            if let Some(file) = resolved {
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+            }
+        }
+        files
+    }
+
+    /// Index the files the scheduler put ahead of the walk: open documents
+    /// from their editor text and then the files they import. Links them
+    /// right away so navigation works before the walk finishes. Returns the
+    /// number of files indexed.
+    async fn index_prioritized_files(&self) -> usize {
+        if !self.index_scheduler.has_priority() {
+            return 0;
+        }
+        let config = self.config.read().await.clone();
+        let exclude_set = Self::build_exclude_set(&config.exclude_globs());
+
+        let mut indexed = 0;
+        while let Some(path) = self.index_scheduler.next_priority() {
+            if exclude_set.is_match(&path) || !self.index_scheduler.claim(&path) {
+                continue;
+            }
+            let Some(parser) = self.parsers.parser_for_path(&path) else {
+                continue;
+            };
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            let open_text = self.open_documents.get(&uri).map(|doc| doc.text());
+            let is_open = open_text.is_some();
+            let text = match open_text {
+                Some(text) => text,
+                None => match std::fs::metadata(&path) {
+                    Ok(metadata) if metadata.len() <= config.max_file_size_kb * 1024 => {
+                        match tokio::fs::read_to_string(&path).await {
+                            Ok(text) => text,
+                            Err(_) => continue,
+                        }
+                    }
+                    _ => continue,
+                },
+            };
+
+            self.forget_file(&path).await;
+            let mut graph = self.graph.write().await;
+            match parser.parse_source(&text, &path, &mut graph) {
+                Ok(file_info) => {
+                    self.parsers
+                        .enrich_parsed_file(&mut graph, &file_info, &path, &text);
+                    self.symbol_index.add_file(path.clone(), &file_info, &graph);
+                    self.file_cache.insert(uri, file_info);
+                    indexed += 1;
+                    if is_open {
+                        for file in self.direct_import_files(&graph, &path) {
+                            self.index_scheduler.prioritize(file);
+                        }
+                    }
+                }
+                Err(e) => tracing::warn!("Failed to parse {:?}: {}", path, e),
+            }
+        }
+
+        if indexed > 0 {
+            let mut graph = self.graph.write().await;
+            GraphUpdater::resolve_cross_file_imports(&mut graph);
+        }
+        indexed
+    }
+
+    /// Paths of open documents whose text differs from the file on disk.
+    fn unsaved_open_files(&self) -> std::collections::HashSet<String> {
+        self.open_documents
+            .iter()
+            .filter_map(|entry| {
+                let path = entry.key().to_file_path().ok()?;
+                let on_disk = std::fs::read(&path).ok();
+                let text = entry.value().text();
+                (on_disk.as_deref() != Some(text.as_bytes()))
+                    .then(|| path.to_string_lossy().to_string())
+            })
+            .collect()
+    }
+
+    /// Publish `diagnostics` as the `set` of diagnostics of `uri`, merged
+    /// with the file's other sets so that no feature clears another's.
+    pub(crate) async fn publish_diagnostic_set(
+        &self,
+        uri: Url,
+        set: &str,
+        diagnostics: Vec<Diagnostic>,
+    ) {
+        // Held while sending, so publications reach the client in order
+        let mut published = self.diagnostics.lock().await;
+        let merged = merge_diagnostic_set(&mut published, &uri, set, diagnostics);
+        self.client.publish_diagnostics(uri, merged, None).await;
+    }
+
+    /// Replace the graph with the one persisted at the last shutdown, keeping
+    /// the files that are unchanged on disk. Returns those files, which
+    /// indexing can skip.
+    async fn restore_graph(&self, workspace: &Path) -> Vec<PathBuf> {
+        let Some(restored) =
+            GraphSnapshot::load(&graph_dir(workspace)).and_then(GraphSnapshot::restore)
+        else {
+            return Vec::new();
+        };
+
+        self.symbol_index.clear();
+        self.file_cache.clear();
+        self.query_cache.invalidate_all();
+        let mut paths = Vec::with_capacity(restored.files.len());
+        {
+            let mut graph = self.graph.write().await;
+            *graph = restored.graph;
+            for (path, file_info) in restored.files {
+                self.symbol_index.add_file(path.clone(), &file_info, &graph);
+                if let Ok(uri) = Url::from_file_path(&path) {
+                    self.file_cache.insert(uri, file_info);
+                }
+                paths.push(path);
+            }
+        }
+
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!(
+                    "Restored {} files from the saved graph; {} changed files will be parsed again",
+                    paths.len(),
+                    restored.stale
+                ),
+            )
+            .await;
+        paths
+    }
+
     /// Remove all nodes associated with a file from the graph.
     ///
-    /// Also auto-invalidates any memories linked to the removed nodes.
+    /// Also auto-invalidates any memories linked to the removed nodes, and
+    /// remembers them for stale memory hints.
     async fn remove_file_from_graph(&self, path: &std::path::Path) {
+        let anchors = self.memory_link_anchors(path).await;
+        let node_id_strings = self.forget_file(path).await;
+
+        // Auto-invalidate memories linked to these nodes (after releasing graph lock)
+        self.invalidate_node_memories(path, &anchors, &node_id_strings)
+            .await;
+    }
+
+    /// Invalidate the memories linked to `node_ids`, nodes of `path` that
+    /// changed or were removed, and remember them as stale.
+    async fn invalidate_node_memories(
+        &self,
+        path: &std::path::Path,
+        anchors: &std::collections::HashMap<String, (String, Range)>,
+        node_ids: &[String],
+    ) {
+        if node_ids.is_empty() {
+            return;
+        }
+        let path_str = path.to_string_lossy();
+        let reason = format!("Code changed: {}", path_str);
+        match self
+            .memory_manager
+            .invalidate_for_code_nodes(node_ids, &reason)
+            .await
+        {
+            Ok(invalidated) => self.record_stale_memories(path, anchors, invalidated).await,
+            Err(e) => tracing::warn!("Failed to invalidate memories for {}: {}", path_str, e),
+        }
+    }
+
+    /// Drop a file's nodes, symbol index entries and cached query results,
+    /// leaving linked memories alone. Returns the IDs of the removed nodes.
+    async fn forget_file(&self, path: &std::path::Path) -> Vec<String> {
         let path_str = path.to_string_lossy().to_string();
         let node_id_strings: Vec<String>;
 
@@ -255,48 +909,26 @@ impl CodeGraphBackend {
             }
         }
 
-        // Auto-invalidate memories linked to these nodes (after releasing graph lock)
-        if !node_id_strings.is_empty() {
-            let reason = format!("Code changed: {}", path_str);
-            if let Err(e) = self
-                .memory_manager
-                .invalidate_for_code_nodes(&node_id_strings, &reason)
-                .await
-            {
-                tracing::warn!("Failed to invalidate memories for {}: {}", path_str, e);
-            }
-        }
-
         // Invalidate caches
         self.query_cache.invalidate_file(&path.to_path_buf());
         self.symbol_index.remove_file(path);
+        node_id_strings
     }
 
     /// Maximum recursion depth for directory traversal. Prevents runaway
     /// indexing into deeply nested result/log directory trees.
-    const MAX_INDEX_DEPTH: u32 = 20;
+    const MAX_INDEX_DEPTH: usize = 20;
 
     /// Maximum number of files to index per workspace. Acts as a safety valve
     /// so the server cannot OOM on huge directory trees.
     const MAX_INDEXED_FILES: usize = 5_000;
 
-    /// Directories that should be skipped during indexing.
-    const SKIP_DIRECTORIES: &'static [&'static str] = &[
-        "node_modules",
-        "target",
-        "dist",
-        "build",
-        "out",
-        ".git",
-        "__pycache__",
-        "vendor",
-        "DerivedData",
-        "tmp",
-        "coverage",
-        "htmlcov",
-        "results",
-        "logs",
-    ];
+    /// Report the running file count every this many indexed files.
+    const INDEX_PROGRESS_INTERVAL: usize = 50;
+
+    /// Share of indexing progress spent parsing files; the rest covers import
+    /// resolution and the query indexes.
+    const INDEX_PROGRESS_FILES_PERCENT: u32 = 80;
 
     /// Build a `GlobSet` from user-configured exclude patterns.
     /// Uses the `globset` crate which properly supports `**` (globstar).
@@ -319,160 +951,220 @@ impl CodeGraphBackend {
         })
     }
 
+    /// The walk over workspace files to index, with the configured
+    /// exclusions and limits.
+    fn workspace_walk(&self, config: &CodeGraphConfig) -> WorkspaceWalk {
+        Self::configured_walk(&self.parsers, config)
+    }
+
+    /// The walk over the files `parsers` can parse, with the exclusions and
+    /// limits of `config`.
+    pub(crate) fn configured_walk(
+        parsers: &ParserRegistry,
+        config: &CodeGraphConfig,
+    ) -> WorkspaceWalk {
+        WorkspaceWalk::new(&parsers.supported_extensions())
+            .max_depth(Self::MAX_INDEX_DEPTH)
+            .max_file_size(config.max_file_size_kb * 1024)
+            .exclude(Self::build_exclude_set(&config.exclude_globs()))
+    }
+
     /// Index all supported files in a directory
     pub fn index_directory<'a>(
         &'a self,
         dir: &'a std::path::Path,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = usize> + Send + 'a>> {
-        let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
-        self.index_directory_inner(dir, 0, counter)
+        Box::pin(self.index_directory_inner(dir, None))
     }
 
-    /// Inner recursive implementation with depth tracking and a shared file counter.
-    fn index_directory_inner<'a>(
+    /// Index all supported files in a directory, reporting the number of files
+    /// indexed to `progress` and stopping early if it is cancelled.
+    pub fn index_directory_with_progress<'a>(
         &'a self,
         dir: &'a std::path::Path,
-        depth: u32,
-        total_files: std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        progress: &'a WorkProgress,
     ) -> std::pin::Pin<Box<dyn std::future::Future<Output = usize> + Send + 'a>> {
-        Box::pin(async move {
-            use std::fs;
-            use std::sync::atomic::Ordering;
+        Box::pin(self.index_directory_inner(dir, Some(progress)))
+    }
 
-            if depth > Self::MAX_INDEX_DEPTH {
-                tracing::warn!(
-                    "Skipping {:?}: exceeded max indexing depth of {}",
-                    dir,
-                    Self::MAX_INDEX_DEPTH
-                );
-                return 0;
-            }
+    /// Index each of `paths` in turn, after the open documents and the files
+    /// they import, skipping the `restored` files that are already in the
+    /// graph. File indexing is reported as the first
+    /// [`Self::INDEX_PROGRESS_FILES_PERCENT`] percent of `progress`.
+    pub async fn index_paths_with_progress(
+        &self,
+        paths: &[PathBuf],
+        restored: &[PathBuf],
+        progress: &WorkProgress,
+    ) -> usize {
+        let open_files: Vec<PathBuf> = self
+            .open_documents
+            .iter()
+            .filter_map(|entry| entry.key().to_file_path().ok())
+            .collect();
+        self.index_scheduler
+            .begin_pass(open_files, restored.iter().cloned());
+        progress.report("Indexing open files", Some(0));
+        let mut total_indexed = self.index_prioritized_files().await;
+        if total_indexed > 0 {
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("Indexed {total_indexed} open and imported files first"),
+                )
+                .await;
+        }
 
-            if total_files.load(Ordering::Relaxed) >= Self::MAX_INDEXED_FILES {
-                return 0;
+        for (i, folder) in paths.iter().enumerate() {
+            if progress.is_cancelled() {
+                break;
             }
+            let percentage = Self::INDEX_PROGRESS_FILES_PERCENT * i as u32 / paths.len() as u32;
+            progress.report(format!("Indexing {}", folder.display()), Some(percentage));
+            let count = self.index_directory_with_progress(folder, progress).await;
+            total_indexed += count;
+            self.client
+                .log_message(
+                    MessageType::INFO,
+                    format!("Indexed {} files from {}", count, folder.display()),
+                )
+                .await;
+        }
+        self.index_scheduler.end_pass();
+        total_indexed
+    }
 
-            // Read config for exclude patterns and max file size
-            let config = self.config.read().await.clone();
-            let max_file_size = config.max_file_size_kb * 1024;
-            let exclude_set = Self::build_exclude_set(&config.exclude_patterns);
-
-            let mut indexed_count = 0;
-            let supported_extensions = self.parsers.supported_extensions();
-
-            tracing::info!("Indexing directory: {:?}", dir);
-
-            if let Ok(entries) = fs::read_dir(dir) {
-                for entry in entries.flatten() {
-                    if total_files.load(Ordering::Relaxed) >= Self::MAX_INDEXED_FILES {
-                        tracing::warn!(
-                            "Reached max indexed file limit of {}; stopping",
-                            Self::MAX_INDEXED_FILES
-                        );
-                        break;
-                    }
-
-                    let path = entry.path();
+    /// Drop the graph and index the workspace again: the configured index
+    /// paths, or else the workspace folders. If `progress` is cancelled,
+    /// indexing stops and the files indexed so far are still linked.
+    pub async fn reindex_workspace(&self, progress: &WorkProgress) -> usize {
+        // Clear graph and caches
+        {
+            let mut graph = self.graph.write().await;
+            *graph = CodeGraph::in_memory().expect("Failed to create graph");
+        }
+        self.symbol_index.clear();
+        self.file_cache.clear();
+        self.query_cache.invalidate_all();
 
-                    // Skip hidden files and directories
-                    if let Some(name) = path.file_name() {
-                        if name.to_string_lossy().starts_with('.') {
-                            continue;
-                        }
-                    }
+        self.client
+            .log_message(MessageType::INFO, "Reindexing workspace...")
+            .await;
 
-                    if path.is_dir() {
-                        let dir_name = path.file_name().unwrap().to_string_lossy();
+        // Use indexPaths if configured, otherwise fall back to workspace folders
+        let config = self.config.read().await.clone();
+        let paths_to_index: Vec<std::path::PathBuf> = if config.index_paths.is_empty() {
+            self.workspace_folders.read().await.clone()
+        } else {
+            config.index_paths.iter().map(std::path::PathBuf::from).collect()
+        };
+        let total_indexed = self
+            .index_paths_with_progress(&paths_to_index, &[], progress)
+            .await;
+        if progress.is_cancelled() {
+            self.client
+                .log_message(
+                    MessageType::WARNING,
+                    format!("Reindexing cancelled after {total_indexed} files"),
+                )
+                .await;
+        }
 
-                        // Skip hardcoded default directories
-                        if Self::SKIP_DIRECTORIES
-                            .iter()
-                            .any(|&s| s == dir_name.as_ref())
-                        {
-                            continue;
-                        }
+        // Resolve cross-file imports and calls before building indexes
+        progress.report(
+            "Resolving cross-file imports",
+            Some(Self::INDEX_PROGRESS_FILES_PERCENT),
+        );
+        {
+            let mut graph = self.graph.write().await;
+            GraphUpdater::resolve_cross_file_imports(&mut graph);
+            crate::runtime_deps::link_runtime_calls(&mut graph);
+        }
 
-                        // Skip directories matching user-configured exclude globs
-                        let path_str = path.to_string_lossy();
-                        if exclude_set.is_match(path_str.as_ref())
-                            || exclude_set.is_match(dir_name.as_ref())
-                        {
-                            tracing::info!("Skipping {:?}: matched exclude pattern", path);
-                            continue;
-                        }
+        // Rebuild AI query engine indexes
+        progress.report("Building semantic search index", Some(90));
+        self.query_engine.build_indexes().await;
+        self.query_engine.build_symbol_vectors().await;
+        self.refresh_code_lenses_and_hints();
 
-                        // Recursively index subdirectories
-                        indexed_count += self
-                            .index_directory_inner(&path, depth + 1, total_files.clone())
-                            .await;
-                    } else if path.is_file() {
-                        // Skip files matching exclude globs
-                        let path_str = path.to_string_lossy();
-                        if exclude_set.is_match(path_str.as_ref()) {
-                            continue;
-                        }
+        self.client
+            .log_message(
+                MessageType::INFO,
+                format!("Workspace reindexed: {total_indexed} files"),
+            )
+            .await;
+        total_indexed
+    }
 
-                        // Skip files that exceed the configurable size limit
-                        if let Ok(metadata) = fs::metadata(&path) {
-                            if metadata.len() > max_file_size {
-                                tracing::info!(
-                                    "Skipping {:?}: file size {} exceeds limit of {}",
-                                    path,
-                                    metadata.len(),
-                                    max_file_size
-                                );
-                                continue;
-                            }
-                        }
+    /// Index the files of `dir` that the workspace walk visits, parsing them
+    /// in parallel, reporting progress and stopping early if it is cancelled.
+    async fn index_directory_inner(
+        &self,
+        dir: &std::path::Path,
+        progress: Option<&WorkProgress>,
+    ) -> usize {
+        // Read config for exclude patterns and max file size
+        let config = self.config.read().await.clone();
+        let walk = self.workspace_walk(&config);
 
-                        // Check if file has supported extension
-                        if let Some(ext) = path.extension() {
-                            let ext_str = ext.to_string_lossy();
-                            if supported_extensions
-                                .iter()
-                                .any(|&e| e.trim_start_matches('.') == ext_str)
-                            {
-                                // Parse the file using parse_file (which updates metrics)
-                                if let Some(parser) = self.parsers.parser_for_path(&path) {
-                                    let mut graph = self.graph.write().await;
-
-                                    // Remove old nodes for this file to prevent duplicates on re-index
-                                    let path_str = path.to_string_lossy().to_string();
-                                    if let Ok(old_nodes) =
-                                        graph.query().property("path", path_str).execute()
-                                    {
-                                        for old_id in old_nodes {
-                                            let _ = graph.delete_node(old_id);
-                                        }
-                                    }
-
-                                    match parser.parse_file(&path, &mut graph) {
-                                        Ok(file_info) => {
-                                            self.symbol_index.add_file(
-                                                path.clone(),
-                                                &file_info,
-                                                &graph,
-                                            );
-                                            self.file_cache.insert(
-                                                Url::from_file_path(&path).unwrap(),
-                                                file_info,
-                                            );
-                                            indexed_count += 1;
-                                            total_files.fetch_add(1, Ordering::Relaxed);
-                                        }
-                                        Err(e) => {
-                                            tracing::warn!("Failed to parse {:?}: {}", path, e);
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
+        tracing::info!("Indexing directory: {:?}", dir);
+
+        // Files opened meanwhile go first; files already indexed out of order
+        // are skipped. The walk advances as files are parsed, so it stops with
+        // the file limit.
+        let scheduler = &self.index_scheduler;
+        let claimed = walk.files(dir).filter(move |path| scheduler.claim(path));
+        let mut parsed = std::pin::pin!(parse_files(
+            Arc::clone(&self.parsers),
+            claimed,
+            parse_concurrency()
+        ));
+
+        // Parsing runs ahead in parallel; fragments enter the graph one by one
+        let mut indexed_count = 0;
+        while let Some((path, fragment)) = parsed.next().await {
+            if indexed_count >= Self::MAX_INDEXED_FILES {
+                tracing::warn!(
+                    "Reached max indexed file limit of {}; stopping",
+                    Self::MAX_INDEXED_FILES
+                );
+                break;
+            }
+            if progress.is_some_and(WorkProgress::is_cancelled) {
+                break;
+            }
+            indexed_count += self.index_prioritized_files().await;
+
+            let fragment = match fragment {
+                Ok(fragment) => fragment,
+                Err(e) => {
+                    tracing::warn!("Failed to parse {:?}: {}", path, e);
+                    continue;
+                }
+            };
+            let mut graph = self.graph.write().await;
+
+            // Remove old nodes for this file to prevent duplicates on re-index
+            let path_str = path.to_string_lossy().to_string();
+            if let Ok(old_nodes) = graph.query().property("path", path_str).execute() {
+                for old_id in old_nodes {
+                    let _ = graph.delete_node(old_id);
                 }
             }
 
-            indexed_count
-        })
+            let file_info = fragment.insert(&mut graph);
+            self.symbol_index.add_file(path.clone(), &file_info, &graph);
+            self.file_cache
+                .insert(Url::from_file_path(&path).unwrap(), file_info);
+            indexed_count += 1;
+            if let Some(progress) = progress {
+                if indexed_count % Self::INDEX_PROGRESS_INTERVAL == 0 {
+                    progress.report(format!("{indexed_count} files indexed"), None);
+                }
+            }
+        }
+
+        indexed_count
     }
 
     /// Find node at the given position.
@@ -623,6 +1315,9 @@ impl CodeGraphBackend {
     }
 
     /// Find all edges connected to a node.
+    ///
+    /// Co-change edges are left out: they record history, not a relationship
+    /// in code, so they are never a definition, reference or call.
     pub fn get_connected_edges(
         &self,
         graph: &CodeGraph,
@@ -630,11 +1325,18 @@ impl CodeGraphBackend {
         direction: Direction,
     ) -> Vec<(NodeId, NodeId, EdgeType)> {
         let mut edges = Vec::new();
+        let mut push = |edge_id| {
+            if let Ok(edge) = graph.get_edge(edge_id) {
+                if !node_props::is_co_change(edge.edge_type, &edge.properties) {
+                    edges.push((edge.source_id, edge.target_id, edge.edge_type));
+                }
+            }
+        };
 
         // Get neighbors in the specified direction
         let neighbors = match graph.get_neighbors(node_id, direction) {
             Ok(n) => n,
-            Err(_) => return edges,
+            Err(_) => return Vec::new(),
         };
 
         for neighbor_id in neighbors {
@@ -645,29 +1347,17 @@ impl CodeGraphBackend {
                 Direction::Both => {
                     // Try both directions
                     if let Ok(edge_ids) = graph.get_edges_between(node_id, neighbor_id) {
-                        for edge_id in edge_ids {
-                            if let Ok(edge) = graph.get_edge(edge_id) {
-                                edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                            }
-                        }
+                        edge_ids.into_iter().for_each(&mut push);
                     }
                     if let Ok(edge_ids) = graph.get_edges_between(neighbor_id, node_id) {
-                        for edge_id in edge_ids {
-                            if let Ok(edge) = graph.get_edge(edge_id) {
-                                edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                            }
-                        }
+                        edge_ids.into_iter().for_each(&mut push);
                     }
                     continue;
                 }
             };
 
             if let Ok(edge_ids) = graph.get_edges_between(source, target) {
-                for edge_id in edge_ids {
-                    if let Ok(edge) = graph.get_edge(edge_id) {
-                        edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                    }
-                }
+                edge_ids.into_iter().for_each(&mut push);
             }
         }
 
@@ -694,6 +1384,29 @@ impl CodeGraphBackend {
         Ok(None)
     }
 
+    /// The definition the cursor node stands for: the node itself when it
+    /// defines a symbol, otherwise the symbol it references. A definition is
+    /// not followed, as its outgoing calls lead to other symbols.
+    fn definition_at(&self, graph: &CodeGraph, node_id: NodeId) -> LspResult<NodeId> {
+        let is_definition = graph.get_node(node_id).is_ok_and(|node| {
+            matches!(
+                node.node_type,
+                NodeType::Function
+                    | NodeType::Class
+                    | NodeType::Interface
+                    | NodeType::Trait
+                    | NodeType::Type
+                    | NodeType::Variable
+            )
+        });
+        if is_definition {
+            return Ok(node_id);
+        }
+        Ok(self
+            .find_definition_for_reference(graph, node_id)?
+            .unwrap_or(node_id))
+    }
+
     /// Convert a node to an LSP Location.
     pub fn node_to_location(&self, graph: &CodeGraph, node_id: NodeId) -> LspResult<Location> {
         let node = graph
@@ -817,6 +1530,15 @@ impl LanguageServer for CodeGraphBackend {
     async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         tracing::info!("Initializing CodeGraph LSP server");
 
+        self.progress.set_supported(
+            params
+                .capabilities
+                .window
+                .as_ref()
+                .and_then(|w| w.work_done_progress)
+                .unwrap_or(false),
+        );
+
         // Extract extension path and config from initialization options
         let init_opts = params.initialization_options;
 
@@ -826,26 +1548,13 @@ impl LanguageServer for CodeGraphBackend {
                 .map(std::path::PathBuf::from)
         });
 
-        // Parse indexing configuration
+        // Parse settings; the memory manager and watcher pick them up below
         if let Some(ref opts) = init_opts {
-            let config = CodeGraphConfig {
-                index_on_startup: opts
-                    .get("indexOnStartup")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false),
-                exclude_patterns: opts
-                    .get("excludePatterns")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok())
-                    .unwrap_or_default(),
-                index_paths: opts
-                    .get("indexPaths")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok())
-                    .unwrap_or_default(),
-                max_file_size_kb: opts
-                    .get("maxFileSizeKB")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or_else(default_max_file_size_kb),
-            };
+            let config: CodeGraphConfig =
+                serde_json::from_value(opts.clone()).unwrap_or_else(|e| {
+                    tracing::warn!("Invalid CodeGraph settings, using defaults: {}", e);
+                    CodeGraphConfig::default()
+                });
             tracing::info!("CodeGraph config: index_on_startup={}, exclude_patterns={:?}, index_paths={:?}, max_file_size_kb={}",
                 config.index_on_startup, config.exclude_patterns, config.index_paths, config.max_file_size_kb);
             *self.config.write().await = config;
@@ -916,14 +1625,17 @@ impl LanguageServer for CodeGraphBackend {
                     workspace_folders.push(path);
                 }
             }
+            self.query_engine.set_workspace_roots(&workspace_folders);
         }
 
+        self.propagate_configuration().await;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Options(
                     TextDocumentSyncOptions {
                         open_close: Some(true),
-                        change: Some(TextDocumentSyncKind::FULL),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
                         save: Some(TextDocumentSyncSaveOptions::SaveOptions(SaveOptions {
                             include_text: Some(true),
                         })),
@@ -931,16 +1643,61 @@ impl LanguageServer for CodeGraphBackend {
                     },
                 )),
                 definition_provider: Some(OneOf::Left(true)),
+                declaration_provider: Some(DeclarationCapability::Simple(true)),
                 references_provider: Some(OneOf::Left(true)),
+                implementation_provider: Some(ImplementationProviderCapability::Simple(true)),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
                 document_symbol_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: SemanticTokensLegend {
+                                token_types: crate::domain::semantic_tokens::TOKEN_TYPES
+                                    .iter()
+                                    .copied()
+                                    .map(SemanticTokenType::new)
+                                    .collect(),
+                                token_modifiers: crate::domain::semantic_tokens::TOKEN_MODIFIERS
+                                    .iter()
+                                    .copied()
+                                    .map(SemanticTokenModifier::new)
+                                    .collect(),
+                            },
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                folding_range_provider: Some(FoldingRangeProviderCapability::Simple(true)),
+                completion_provider: Some(CompletionOptions::default()),
+                moniker_provider: Some(OneOf::Left(true)),
+                document_link_provider: Some(DocumentLinkOptions {
+                    resolve_provider: Some(false),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![
+                            CodeActionKind::EMPTY,
+                            CodeActionKind::QUICKFIX,
+                        ]),
+                        ..Default::default()
+                    },
+                )),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
                 call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec![
                         "codegraph.getDependencyGraph".to_string(),
                         "codegraph.getCallGraph".to_string(),
                         "codegraph.analyzeImpact".to_string(),
+                        "codegraph.exportGraphVisualization".to_string(),
                         "codegraph.getParserMetrics".to_string(),
+                        "codegraph.getCacheStats".to_string(),
+                        "codegraph.getStatistics".to_string(),
                         "codegraph.reindexWorkspace".to_string(),
                         "codegraph.getAIContext".to_string(),
                         "codegraph.getEditContext".to_string(),
@@ -951,6 +1708,7 @@ impl LanguageServer for CodeGraphBackend {
                         "codegraph.analyzeComplexity".to_string(),
                         "codegraph.findUnusedCode".to_string(),
                         "codegraph.analyzeCoupling".to_string(),
+                        "codegraph.checkArchitecture".to_string(),
                         // AI Agent Query Primitives
                         "codegraph.symbolSearch".to_string(),
                         "codegraph.findByImports".to_string(),
@@ -960,11 +1718,28 @@ impl LanguageServer for CodeGraphBackend {
                         "codegraph.getCallees".to_string(),
                         "codegraph.getDetailedSymbolInfo".to_string(),
                         "codegraph.findBySignature".to_string(),
+                        "codegraph.findCycles".to_string(),
+                        "codegraph.getHotSymbols".to_string(),
+                        "codegraph.getRiskHotspots".to_string(),
+                        "codegraph.getCodeOwnership".to_string(),
+                        "codegraph.findTypeUsages".to_string(),
+                        "codegraph.findImplementations".to_string(),
+                        "codegraph.getTypeHierarchy".to_string(),
+                        "codegraph.reaches".to_string(),
+                        "codegraph.findSimilarCode".to_string(),
+                        "codegraph.findConfigUsages".to_string(),
+                        "codegraph.findTodos".to_string(),
+                        "codegraph.findByAnnotation".to_string(),
+                        "codegraph.previewRename".to_string(),
+                        "codegraph.query".to_string(),
+                        "codegraph.getFileOutline".to_string(),
+                        "codegraph.batchQuery".to_string(),
                         // Memory Layer Commands
                         "codegraph.memoryStore".to_string(),
                         "codegraph.memorySearch".to_string(),
                         "codegraph.memoryGet".to_string(),
                         "codegraph.memoryInvalidate".to_string(),
+                        "codegraph.memoryRevalidate".to_string(),
                         "codegraph.memoryList".to_string(),
                         "codegraph.memoryUpdate".to_string(),
                         "codegraph.memoryContext".to_string(),
@@ -980,6 +1755,13 @@ impl LanguageServer for CodeGraphBackend {
                     ],
                     work_done_progress_options: WorkDoneProgressOptions::default(),
                 }),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -991,10 +1773,12 @@ impl LanguageServer for CodeGraphBackend {
             .log_message(MessageType::INFO, "CodeGraph LSP server initialized")
             .await;
 
+        self.load_entry_rules().await;
+        self.register_watched_files().await;
+
         // Index workspace folders only if configured to do so
         let folders = self.workspace_folders.read().await.clone();
         let config = self.config.read().await.clone();
-        let mut total_indexed = 0;
 
         if config.index_on_startup {
             // Determine which paths to index
@@ -1008,13 +1792,22 @@ impl LanguageServer for CodeGraphBackend {
                     .collect()
             };
 
-            for folder in &paths_to_index {
-                let count = self.index_directory(folder).await;
-                total_indexed += count;
+            let progress = self
+                .progress
+                .begin(&self.client, "CodeGraph: Indexing workspace", true)
+                .await;
+            let restored = match folders.first() {
+                Some(folder) => self.restore_graph(folder).await,
+                None => Vec::new(),
+            };
+            let total_indexed = self
+                .index_paths_with_progress(&paths_to_index, &restored, &progress)
+                .await;
+            if progress.is_cancelled() {
                 self.client
                     .log_message(
-                        MessageType::INFO,
-                        format!("Indexed {} files from {}", count, folder.display()),
+                        MessageType::WARNING,
+                        format!("Indexing cancelled after {total_indexed} files"),
                     )
                     .await;
             }
@@ -1026,10 +1819,16 @@ impl LanguageServer for CodeGraphBackend {
                 )
                 .await;
 
-            // Resolve cross-file imports after all files are indexed
+            // Resolve cross-file imports after all files are indexed, then link
+            // HTTP client calls to route handlers across services
+            progress.report(
+                "Resolving cross-file imports",
+                Some(Self::INDEX_PROGRESS_FILES_PERCENT),
+            );
             {
                 let mut graph = self.graph.write().await;
                 GraphUpdater::resolve_cross_file_imports(&mut graph);
+                crate::runtime_deps::link_runtime_calls(&mut graph);
             }
             self.client
                 .log_message(MessageType::INFO, "Cross-file imports resolved")
@@ -1042,59 +1841,31 @@ impl LanguageServer for CodeGraphBackend {
                     format!("Building semantic search index ({total_indexed} files)... This may take a moment."),
                 )
                 .await;
-            self.query_engine.build_indexes().await;
+            progress.report("Building semantic search index", Some(90));
+            let index_dir = folders.first().map(|f| crate::ai_query::index_dir(f));
+            let restored = match &index_dir {
+                Some(dir) => self.query_engine.load_indexes(dir).await,
+                None => false,
+            };
+            if restored {
+                self.client
+                    .log_message(MessageType::INFO, "Loaded persisted query indexes")
+                    .await;
+            } else {
+                self.query_engine.build_indexes().await;
+            }
             self.client
                 .log_message(MessageType::INFO, "Semantic search index ready")
                 .await;
+            progress.finish(format!("Indexed {total_indexed} files"));
         } else {
             self.client
                 .log_message(MessageType::INFO, "Skipping auto-index (indexOnStartup=false). Use 'Index Directory' command to index specific paths.")
                 .await;
         }
 
-        // Initialize memory store for persistent AI context
-        if let Some(first_folder) = folders.first() {
-            tracing::info!(
-                "Starting memory store initialization for workspace: {}",
-                first_folder.display()
-            );
-            self.client
-                .log_message(
-                    MessageType::INFO,
-                    format!(
-                        "[DEBUG] Initializing memory store for: {}",
-                        first_folder.display()
-                    ),
-                )
-                .await;
-
-            match self.memory_manager.initialize(first_folder).await {
-                Ok(_) => {
-                    tracing::info!("Memory store initialization succeeded");
-                    self.client
-                        .log_message(MessageType::INFO, "✓ Memory store initialized successfully")
-                        .await;
-
-                    // Share vector engine with query engine for semantic symbol search
-                    if let Some(engine) = self.memory_manager.get_vector_engine().await {
-                        self.query_engine.set_vector_engine(engine).await;
-                        self.query_engine.build_symbol_vectors().await;
-                        self.client
-                            .log_message(MessageType::INFO, "✓ Semantic symbol search initialized")
-                            .await;
-                    }
-                }
-                Err(e) => {
-                    tracing::error!("Memory store initialization failed: {:?}", e);
-                    self.client
-                        .log_message(
-                            MessageType::ERROR,
-                            format!("✗ Failed to initialize memory store: {}. Memory features will be disabled.", e),
-                        )
-                        .await;
-                }
-            }
-        } else {
+        // Initialize a memory store per workspace folder for persistent AI context
+        if folders.is_empty() {
             tracing::warn!("No workspace folders available for memory initialization");
             self.client
                 .log_message(
@@ -1103,6 +1874,21 @@ impl LanguageServer for CodeGraphBackend {
                 )
                 .await;
         }
+        let mut memory_ready = false;
+        for folder in &folders {
+            memory_ready |= self.initialize_folder_memory(folder).await;
+        }
+
+        // Share vector engine with query engine for semantic symbol search
+        if memory_ready {
+            if let Some(engine) = self.memory_manager.get_vector_engine().await {
+                self.query_engine.set_vector_engine(engine).await;
+                self.query_engine.build_symbol_vectors().await;
+                self.client
+                    .log_message(MessageType::INFO, "✓ Semantic symbol search initialized")
+                    .await;
+            }
+        }
 
         // Start file watcher for incremental updates (only if we indexed something)
         if config.index_on_startup && !folders.is_empty() {
@@ -1126,12 +1912,196 @@ impl LanguageServer for CodeGraphBackend {
 
     async fn shutdown(&self) -> Result<()> {
         tracing::info!("Shutting down CodeGraph LSP server");
+
+        // Persist the graph and query indexes so the next startup can skip
+        // re-parsing unchanged files and rebuilding the indexes
+        if let Some(first_folder) = self.workspace_folders.read().await.first() {
+            let graph_dir = graph_dir(first_folder);
+            let snapshot = {
+                let unsaved = self.unsaved_open_files();
+                let graph = self.graph.read().await;
+                GraphSnapshot::capture(
+                    &graph,
+                    |path| self.symbol_index.get_file_symbols(path),
+                    &unsaved,
+                )
+            };
+            match snapshot.save(&graph_dir) {
+                Ok(()) => tracing::info!("Saved graph to {}", graph_dir.display()),
+                Err(e) => tracing::warn!("Failed to save graph: {}", e),
+            }
+
+            let index_dir = crate::ai_query::index_dir(first_folder);
+            match self.query_engine.save_indexes(&index_dir).await {
+                Ok(()) => tracing::info!("Saved query indexes to {}", index_dir.display()),
+                Err(e) => tracing::warn!("Failed to save query indexes: {}", e),
+            }
+        }
         Ok(())
     }
 
+    async fn did_change_watched_files(&self, params: DidChangeWatchedFilesParams) {
+        let mut changed = false;
+        for event in params.changes {
+            let Ok(path) = event.uri.to_file_path() else {
+                continue;
+            };
+            if path.ends_with(".codegraph/entry_points.json") {
+                self.load_entry_rules().await;
+                continue;
+            }
+
+            if event.typ == FileChangeType::DELETED {
+                let files = if self.parsers.can_parse(&path) {
+                    vec![path]
+                } else {
+                    self.symbol_index.files_under(&path)
+                };
+                for file in files {
+                    tracing::debug!("Removing deleted file from index: {:?}", file);
+                    self.remove_file_from_graph(&file).await;
+                    if let Ok(uri) = Url::from_file_path(&file) {
+                        self.file_cache.remove(&uri);
+                    }
+                    changed = true;
+                }
+            } else if !self.open_documents.contains_key(&event.uri) {
+                // Open documents are kept in sync from the editor's text instead
+                changed |= self.reindex_file_from_disk(&path).await;
+            }
+        }
+
+        if changed {
+            {
+                let mut graph = self.graph.write().await;
+                GraphUpdater::resolve_cross_file_imports(&mut graph);
+                crate::runtime_deps::link_runtime_calls(&mut graph);
+            }
+            self.query_engine.build_indexes().await;
+            self.refresh_code_lenses_and_hints();
+        }
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let to_paths = |folders: Vec<WorkspaceFolder>| -> Vec<PathBuf> {
+            folders
+                .into_iter()
+                .filter_map(|folder| folder.uri.to_file_path().ok())
+                .collect()
+        };
+        let added = to_paths(params.event.added);
+        let removed = to_paths(params.event.removed);
+
+        {
+            let mut folders = self.workspace_folders.write().await;
+            folders.retain(|folder| !removed.contains(folder));
+            for folder in &added {
+                if !folders.contains(folder) {
+                    folders.push(folder.clone());
+                }
+            }
+            self.query_engine.set_workspace_roots(&folders);
+        }
+
+        // Forget everything indexed from removed folders. Their code didn't
+        // change, so linked memories stay valid.
+        let mut changed = false;
+        for folder in &removed {
+            tracing::info!("Workspace folder removed: {}", folder.display());
+            self.memory_manager.remove_workspace_folder(folder).await;
+            if let Some(watcher) = self.file_watcher.lock().await.as_mut() {
+                let _ = watcher.unwatch(folder);
+            }
+            for file in self.symbol_index.files_under(folder) {
+                self.forget_file(&file).await;
+                if let Ok(uri) = Url::from_file_path(&file) {
+                    self.file_cache.remove(&uri);
+                }
+                changed = true;
+            }
+        }
+
+        for folder in &added {
+            tracing::info!("Workspace folder added: {}", folder.display());
+            self.initialize_folder_memory(folder).await;
+        }
+        if !added.is_empty() && self.config.read().await.index_on_startup {
+            let progress = self
+                .progress
+                .begin(&self.client, "CodeGraph: Indexing workspace folders", true)
+                .await;
+            let indexed = self.index_paths_with_progress(&added, &[], &progress).await;
+            progress.finish(format!("Indexed {indexed} files"));
+            self.watch_directories(&added).await;
+            changed |= indexed > 0;
+        }
+
+        if changed {
+            {
+                let mut graph = self.graph.write().await;
+                GraphUpdater::resolve_cross_file_imports(&mut graph);
+                crate::runtime_deps::link_runtime_calls(&mut graph);
+            }
+            self.query_engine.build_indexes().await;
+            self.refresh_code_lenses_and_hints();
+        }
+    }
+
+    async fn did_change_configuration(&self, params: DidChangeConfigurationParams) {
+        // Push-style clients send the settings, either the `codegraph` section
+        // or the whole tree; otherwise pull the section from the client
+        let settings = match params.settings {
+            serde_json::Value::Null => {
+                let item = ConfigurationItem {
+                    scope_uri: None,
+                    section: Some("codegraph".to_string()),
+                };
+                match self.client.configuration(vec![item]).await {
+                    Ok(mut values) if !values.is_empty() => values.swap_remove(0),
+                    Ok(_) => return,
+                    Err(e) => {
+                        tracing::warn!("Failed to fetch configuration: {}", e);
+                        return;
+                    }
+                }
+            }
+            settings => match settings.get("codegraph") {
+                Some(section) => section.clone(),
+                None => settings,
+            },
+        };
+
+        match serde_json::from_value::<CodeGraphConfig>(settings) {
+            Ok(config) => {
+                self.apply_configuration(config).await;
+                self.client
+                    .log_message(MessageType::INFO, "Configuration updated")
+                    .await;
+            }
+            Err(e) => {
+                self.client
+                    .log_message(
+                        MessageType::WARNING,
+                        format!("Invalid CodeGraph settings: {e}"),
+                    )
+                    .await;
+            }
+        }
+    }
+
     async fn did_open(&self, params: DidOpenTextDocumentParams) {
         let uri = params.text_document.uri;
         let text = params.text_document.text;
+        let mut document = OpenDocument::new(&text, params.text_document.version);
+        if let Some(grammar) = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| self.parsers.grammar_for_path(&path))
+        {
+            // Parse once up front so the first edit reuses the tree
+            document.parse_tree(&grammar);
+        }
+        self.open_documents.insert(uri.clone(), document);
 
         tracing::info!("did_open called for: {}", uri);
 
@@ -1155,7 +2125,7 @@ impl LanguageServer for CodeGraphBackend {
         }
 
         // Skip files matching exclude patterns
-        let exclude_set = Self::build_exclude_set(&config.exclude_patterns);
+        let exclude_set = Self::build_exclude_set(&config.exclude_globs());
         let path_str = path.to_string_lossy();
         if exclude_set.is_match(path_str.as_ref()) {
             tracing::info!("Skipping did_open for {:?}: matched exclude pattern", path);
@@ -1173,6 +2143,8 @@ impl LanguageServer for CodeGraphBackend {
             match parser.parse_source(&text, &path, &mut graph) {
                 Ok(file_info) => {
                     tracing::info!("Parse succeeded for: {:?}", path);
+                    self.parsers
+                        .enrich_parsed_file(&mut graph, &file_info, &path, &text);
 
                     // Resolve cross-file imports after parsing
                     GraphUpdater::resolve_cross_file_imports(&mut graph);
@@ -1183,6 +2155,15 @@ impl LanguageServer for CodeGraphBackend {
                     // Update file cache
                     self.file_cache.insert(uri.clone(), file_info);
 
+                    // During bulk indexing, the walk skips this file and
+                    // indexes its imports next
+                    if self.index_scheduler.is_active() {
+                        self.index_scheduler.claim(&path);
+                        for file in self.direct_import_files(&graph, &path) {
+                            self.index_scheduler.prioritize(file);
+                        }
+                    }
+
                     self.client
                         .log_message(MessageType::INFO, format!("Indexed: {uri}"))
                         .await;
@@ -1197,6 +2178,7 @@ impl LanguageServer for CodeGraphBackend {
 
             // Drop graph write lock before rebuilding query indexes
             drop(graph);
+            self.publish_stale_memory_diagnostics(&path).await;
 
             // Rebuild AI query engine indexes so callee/caller indexes reflect new node IDs
             self.query_engine.build_indexes().await;
@@ -1207,34 +2189,26 @@ impl LanguageServer for CodeGraphBackend {
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         let uri = params.text_document.uri;
+        let version = params.text_document.version;
 
-        let path = match uri.to_file_path() {
-            Ok(p) => p,
-            Err(_) => return,
-        };
-
-        // Get the full text (assuming full sync mode)
-        if let Some(change) = params.content_changes.into_iter().next() {
-            if let Some(parser) = self.parsers.parser_for_path(&path) {
-                // Remove old entries
-                self.remove_file_from_graph(&path).await;
-
-                // Re-parse with new content
-                {
-                    let mut graph = self.graph.write().await;
-                    if let Ok(file_info) = parser.parse_source(&change.text, &path, &mut graph) {
-                        // Resolve cross-file imports after parsing
-                        GraphUpdater::resolve_cross_file_imports(&mut graph);
-
-                        self.symbol_index.add_file(path.clone(), &file_info, &graph);
-                        self.file_cache.insert(uri, file_info);
-                    }
-                }
-
-                // Rebuild AI query engine indexes so callee/caller indexes reflect new node IDs
-                self.query_engine.build_indexes().await;
+        // Apply the edits before anything awaits so they keep the editor's order
+        {
+            let Some(mut doc) = self.open_documents.get_mut(&uri) else {
+                tracing::warn!("did_change for a document that is not open: {}", uri);
+                return;
+            };
+            for change in &params.content_changes {
+                doc.apply_change(change);
             }
+            doc.version = version;
         }
+
+        // Re-parse once typing pauses rather than on every keystroke
+        let backend = self.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(REPARSE_DEBOUNCE_MS)).await;
+            backend.reparse_open_document(&uri, version).await;
+        });
     }
 
     async fn did_save(&self, params: DidSaveTextDocumentParams) {
@@ -1246,6 +2220,11 @@ impl LanguageServer for CodeGraphBackend {
             Err(_) => return,
         };
 
+        if path.ends_with(".codegraph/entry_points.json") {
+            self.load_entry_rules().await;
+            return;
+        }
+
         if let Some(parser) = self.parsers.parser_for_path(&path) {
             if let Some(text) = params.text {
                 tracing::info!("did_save has text, re-parsing + re-embedding: {}", uri);
@@ -1254,13 +2233,17 @@ impl LanguageServer for CodeGraphBackend {
                 {
                     let mut graph = self.graph.write().await;
                     if let Ok(file_info) = parser.parse_source(&text, &path, &mut graph) {
+                        self.parsers
+                            .enrich_parsed_file(&mut graph, &file_info, &path, &text);
                         // Resolve cross-file imports after parsing
                         GraphUpdater::resolve_cross_file_imports(&mut graph);
+                        crate::runtime_deps::link_runtime_calls(&mut graph);
 
                         self.symbol_index.add_file(path.clone(), &file_info, &graph);
                         self.file_cache.insert(uri, file_info);
                     }
                 }
+                self.publish_stale_memory_diagnostics(&path).await;
 
                 // Rebuild AI query engine indexes
                 self.query_engine.build_indexes().await;
@@ -1268,6 +2251,8 @@ impl LanguageServer for CodeGraphBackend {
                 // Incrementally re-embed only this file's symbols (not the whole codebase)
                 let path_str = path.to_string_lossy().to_string();
                 self.query_engine.update_file_vectors(&path_str).await;
+
+                self.refresh_code_lenses_and_hints();
             }
         }
     }
@@ -1275,6 +2260,7 @@ impl LanguageServer for CodeGraphBackend {
     async fn did_close(&self, params: DidCloseTextDocumentParams) {
         // Keep in graph for cross-file references, but remove from file cache
         self.file_cache.remove(&params.text_document.uri);
+        self.open_documents.remove(&params.text_document.uri);
     }
 
     async fn goto_definition(
@@ -1321,10 +2307,53 @@ impl LanguageServer for CodeGraphBackend {
         Ok(Some(GotoDefinitionResponse::Scalar(location)))
     }
 
-    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
-        let uri = &params.text_document_position.text_document.uri;
-        let position = params.text_document_position.position;
-        let include_declaration = params.context.include_declaration;
+    async fn goto_declaration(
+        &self,
+        params: GotoDeclarationParams,
+    ) -> Result<Option<GotoDeclarationResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let definition = {
+            let graph = self.graph.read().await;
+            let node_id = match self.find_node_at_position(&graph, &path, position)? {
+                Some(id) => id,
+                None => return Ok(None),
+            };
+            self.definition_at(&graph, node_id)?
+        };
+
+        let path_str = path.to_string_lossy();
+        let mut declarations = self
+            .query_engine
+            .find_declarations(definition, &path_str)
+            .await;
+        // Without a separate declaration, the definition declares the symbol
+        if declarations.is_empty() {
+            declarations.push(definition);
+        }
+
+        let graph = self.graph.read().await;
+        let mut locations: Vec<Location> = declarations
+            .iter()
+            .filter_map(|&id| self.node_to_location(&graph, id).ok())
+            .collect();
+
+        Ok(match locations.len() {
+            0 => None,
+            1 => Some(GotoDeclarationResponse::Scalar(locations.remove(0))),
+            _ => Some(GotoDeclarationResponse::Array(locations)),
+        })
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let include_declaration = params.context.include_declaration;
 
         let path = uri
             .to_file_path()
@@ -1368,6 +2397,65 @@ impl LanguageServer for CodeGraphBackend {
         }
     }
 
+    async fn goto_implementation(
+        &self,
+        params: GotoImplementationParams,
+    ) -> Result<Option<GotoImplementationResponse>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let type_node = {
+            let graph = self.graph.read().await;
+            let node_id = match self.find_node_at_position(&graph, &path, position)? {
+                Some(id) => id,
+                None => return Ok(None),
+            };
+            self.definition_at(&graph, node_id)?
+        };
+
+        // Interface/trait members resolve to the overriding members, not their
+        // types. Members nothing overrides fall through to the type lookup.
+        if let Some(members) = self
+            .query_engine
+            .find_member_implementations(type_node)
+            .await
+            .filter(|members| !members.is_empty())
+        {
+            let graph = self.graph.read().await;
+            let locations: Vec<Location> = members
+                .iter()
+                .filter_map(|&id| self.node_to_location(&graph, id).ok())
+                .collect();
+            return Ok(Some(GotoImplementationResponse::Array(locations)));
+        }
+
+        let result = match self
+            .query_engine
+            .find_implementations(
+                type_node,
+                crate::ai_query::ImplementationDirection::Implementations,
+                usize::MAX,
+            )
+            .await
+        {
+            Some(r) if !r.implementations.is_empty() => r,
+            _ => return Ok(None),
+        };
+
+        let graph = self.graph.read().await;
+        let locations: Vec<Location> = result
+            .implementations
+            .iter()
+            .filter_map(|i| self.node_to_location(&graph, i.node_id).ok())
+            .collect();
+
+        Ok(Some(GotoImplementationResponse::Array(locations)))
+    }
+
     async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
         let uri = &params.text_document_position_params.text_document.uri;
         let position = params.text_document_position_params.position;
@@ -1478,6 +2566,90 @@ impl LanguageServer for CodeGraphBackend {
         }
     }
 
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let uri = &params.text_document.uri;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        // Prefer the editor's text so positions match unsaved edits
+        let text = match self.open_documents.get(uri) {
+            Some(doc) => doc.text(),
+            None => match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(_) => return Ok(None),
+            },
+        };
+
+        let graph = self.graph.read().await;
+        let tokens = crate::domain::semantic_tokens::compute_semantic_tokens(&graph, &path, &text);
+
+        // LSP encodes each token relative to the previous one
+        let mut data = Vec::with_capacity(tokens.len());
+        let (mut prev_line, mut prev_start) = (0, 0);
+        for token in tokens {
+            let delta_line = token.line - prev_line;
+            data.push(SemanticToken {
+                delta_line,
+                delta_start: if delta_line == 0 {
+                    token.start - prev_start
+                } else {
+                    token.start
+                },
+                length: token.length,
+                token_type: token.token_type,
+                token_modifiers_bitset: token.modifiers,
+            });
+            prev_line = token.line;
+            prev_start = token.start;
+        }
+
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data,
+        })))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        self.handle_code_lens(&params.text_document.uri).await
+    }
+
+    async fn code_lens_resolve(&self, params: CodeLens) -> Result<CodeLens> {
+        self.handle_code_lens_resolve(params).await
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        self.handle_code_action(params).await
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        self.handle_inlay_hint(&params.text_document.uri, params.range)
+            .await
+    }
+
+    async fn work_done_progress_cancel(&self, params: WorkDoneProgressCancelParams) {
+        self.progress.cancel(&params.token);
+    }
+
+    async fn folding_range(&self, params: FoldingRangeParams) -> Result<Option<Vec<FoldingRange>>> {
+        self.handle_folding_range(&params.text_document.uri).await
+    }
+
+    async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
+        self.handle_completion(params).await
+    }
+
+    async fn document_link(&self, params: DocumentLinkParams) -> Result<Option<Vec<DocumentLink>>> {
+        self.handle_document_link(&params.text_document.uri).await
+    }
+
+    async fn moniker(&self, params: MonikerParams) -> Result<Option<Vec<Moniker>>> {
+        self.handle_moniker(params).await
+    }
+
     async fn prepare_call_hierarchy(
         &self,
         params: CallHierarchyPrepareParams,
@@ -1537,27 +2709,11 @@ impl LanguageServer for CodeGraphBackend {
 
         for (source, _, edge_type) in edges {
             if edge_type == EdgeType::Calls {
-                if let Ok(node) = graph.get_node(source) {
-                    let name = node_props::name(node).to_string();
-
-                    if let Ok(location) = self.node_to_location(&graph, source) {
-                        calls.push(CallHierarchyIncomingCall {
-                            from: CallHierarchyItem {
-                                name,
-                                kind: SymbolKind::FUNCTION,
-                                tags: None,
-                                detail: node
-                                    .properties
-                                    .get_string("signature")
-                                    .map(|s| s.to_string()),
-                                uri: location.uri.clone(),
-                                range: location.range,
-                                selection_range: location.range,
-                                data: Some(serde_json::json!({ "nodeId": source.to_string() })),
-                            },
-                            from_ranges: vec![location.range],
-                        });
-                    }
+                if let Some(from) = self.call_hierarchy_item(&graph, source) {
+                    calls.push(CallHierarchyIncomingCall {
+                        from_ranges: vec![from.range],
+                        from,
+                    });
                 }
             }
         }
@@ -1583,27 +2739,11 @@ impl LanguageServer for CodeGraphBackend {
 
         for (_, target, edge_type) in edges {
             if edge_type == EdgeType::Calls {
-                if let Ok(node) = graph.get_node(target) {
-                    let name = node_props::name(node).to_string();
-
-                    if let Ok(location) = self.node_to_location(&graph, target) {
-                        calls.push(CallHierarchyOutgoingCall {
-                            to: CallHierarchyItem {
-                                name,
-                                kind: SymbolKind::FUNCTION,
-                                tags: None,
-                                detail: node
-                                    .properties
-                                    .get_string("signature")
-                                    .map(|s| s.to_string()),
-                                uri: location.uri.clone(),
-                                range: location.range,
-                                selection_range: location.range,
-                                data: Some(serde_json::json!({ "nodeId": target.to_string() })),
-                            },
-                            from_ranges: vec![location.range],
-                        });
-                    }
+                if let Some(to) = self.call_hierarchy_item(&graph, target) {
+                    calls.push(CallHierarchyOutgoingCall {
+                        from_ranges: vec![to.range],
+                        to,
+                    });
                 }
             }
         }
@@ -1658,6 +2798,18 @@ impl LanguageServer for CodeGraphBackend {
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
+            "codegraph.exportGraphVisualization" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::ExportGraphParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_export_graph_visualization(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
             "codegraph.getParserMetrics" => {
                 let response = self
                     .handle_get_parser_metrics(crate::handlers::ParserMetricsParams {
@@ -1667,257 +2819,433 @@ impl LanguageServer for CodeGraphBackend {
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
+            "codegraph.getCacheStats" => {
+                let response = self.handle_get_cache_stats().await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.getStatistics" => {
+                let response = self.handle_get_statistics().await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
             "codegraph.reindexWorkspace" => {
-                // Clear graph and caches
-                {
-                    let mut graph = self.graph.write().await;
-                    *graph = CodeGraph::in_memory().expect("Failed to create graph");
-                }
-                self.symbol_index.clear();
-                self.file_cache.clear();
-                self.query_cache.invalidate_all();
+                let total_indexed = self.handle_reindex_workspace().await?;
+                Ok(Some(serde_json::json!({
+                    "status": "success",
+                    "message": format!("Workspace reindexed: {total_indexed} files"),
+                    "files_indexed": total_indexed
+                })))
+            }
 
-                self.client
-                    .log_message(MessageType::INFO, "Reindexing workspace...")
-                    .await;
+            "codegraph.getAIContext" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::AIContextParams = serde_json::from_value(args.clone())
+                    .map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_ai_context(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
 
-                // Use indexPaths if configured, otherwise fall back to workspace folders
-                let config = self.config.read().await.clone();
-                let paths_to_index: Vec<std::path::PathBuf> = if config.index_paths.is_empty() {
-                    self.workspace_folders.read().await.clone()
-                } else {
-                    config.index_paths.iter().map(std::path::PathBuf::from).collect()
-                };
-                let mut total_indexed = 0;
+            "codegraph.getEditContext" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let edit_params: serde_json::Value = serde_json::from_value(args.clone())
+                    .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
+                let response = self.handle_get_edit_context(edit_params).await?;
+                Ok(Some(response))
+            }
 
-                for folder in paths_to_index {
-                    tracing::info!("Indexing folder: {:?}", folder);
-                    let count = self.index_directory(&folder).await;
-                    total_indexed += count;
-                }
+            "codegraph.getCuratedContext" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let curated_params: serde_json::Value = serde_json::from_value(args.clone())
+                    .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
+                let response = self.handle_get_curated_context(curated_params).await?;
+                Ok(Some(response))
+            }
 
-                // Resolve cross-file imports and calls before building indexes
-                {
-                    let mut graph = self.graph.write().await;
-                    GraphUpdater::resolve_cross_file_imports(&mut graph);
-                }
+            "codegraph.findRelatedTests" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::RelatedTestsParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_find_related_tests(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
 
-                // Rebuild AI query engine indexes
-                self.query_engine.build_indexes().await;
-                self.query_engine.build_symbol_vectors().await;
+            "codegraph.getNodeLocation" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::GetNodeLocationParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_node_location(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
 
-                self.client
-                    .log_message(
-                        MessageType::INFO,
-                        format!("Workspace reindexed: {total_indexed} files"),
-                    )
-                    .await;
+            "codegraph.getWorkspaceSymbols" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::WorkspaceSymbolsParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_workspace_symbols(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
 
-                Ok(Some(serde_json::json!({
-                    "status": "success",
-                    "message": format!("Workspace reindexed: {total_indexed} files"),
-                    "files_indexed": total_indexed
-                })))
+            "codegraph.analyzeComplexity" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::ComplexityParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_analyze_complexity(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.findUnusedCode" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::UnusedCodeParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_find_unused_code(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.analyzeCoupling" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::CouplingParams = serde_json::from_value(args.clone())
+                    .map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_analyze_coupling(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.checkArchitecture" => {
+                let args = params
+                    .arguments
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({}));
+                let params: crate::handlers::CheckArchitectureParams = serde_json::from_value(args)
+                    .map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_check_architecture(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            // AI Agent Query Primitives
+            "codegraph.symbolSearch" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::SymbolSearchParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_symbol_search(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.findByImports" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::FindByImportsParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_find_by_imports(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.findEntryPoints" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::FindEntryPointsParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_find_entry_points(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.traverseGraph" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::TraverseGraphParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_traverse_graph(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getAIContext" => {
+            "codegraph.getCallers" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::AIContextParams = serde_json::from_value(args.clone())
-                    .map_err(|e| {
+                let params: crate::handlers::GetCallersParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_ai_context(params).await?;
+                let response = self.handle_get_callers(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getEditContext" => {
+            "codegraph.getCallees" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let edit_params: serde_json::Value = serde_json::from_value(args.clone())
-                    .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
-                let response = self.handle_get_edit_context(edit_params).await?;
-                Ok(Some(response))
+                let params: crate::handlers::GetCallersParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_callees(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getCuratedContext" => {
+            "codegraph.getDetailedSymbolInfo" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let curated_params: serde_json::Value = serde_json::from_value(args.clone())
-                    .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
-                let response = self.handle_get_curated_context(curated_params).await?;
-                Ok(Some(response))
+                let params: crate::handlers::GetDetailedInfoParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_detailed_symbol_info(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.findRelatedTests" => {
+            "codegraph.findBySignature" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::RelatedTestsParams =
+                let params: crate::handlers::FindBySignatureParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_find_related_tests(params).await?;
+                let response = self.handle_find_by_signature(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getNodeLocation" => {
+            "codegraph.findCycles" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::GetNodeLocationParams =
+                let params: crate::handlers::FindCyclesParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_node_location(params).await?;
+                let response = self.handle_find_cycles(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getWorkspaceSymbols" => {
+            "codegraph.getHotSymbols" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::WorkspaceSymbolsParams =
+                let params: crate::handlers::GetHotSymbolsParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_workspace_symbols(params).await?;
+                let response = self.handle_get_hot_symbols(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.analyzeComplexity" => {
+            "codegraph.getRiskHotspots" => {
+                let args = params
+                    .arguments
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({}));
+                let params: crate::handlers::GetRiskHotspotsParams = serde_json::from_value(args)
+                    .map_err(|e| {
+                    tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                })?;
+                let response = self.handle_get_risk_hotspots(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.getCodeOwnership" => {
+                let args = params
+                    .arguments
+                    .first()
+                    .cloned()
+                    .unwrap_or_else(|| serde_json::json!({}));
+                let params: crate::handlers::GetCodeOwnershipParams = serde_json::from_value(args)
+                    .map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_get_code_ownership(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.findTypeUsages" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::ComplexityParams =
+                let params: crate::handlers::FindTypeUsagesParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_analyze_complexity(params).await?;
+                let response = self.handle_find_type_usages(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.findUnusedCode" => {
+            "codegraph.findImplementations" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::UnusedCodeParams =
+                let params: crate::handlers::FindImplementationsParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_find_unused_code(params).await?;
+                let response = self.handle_find_implementations(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.analyzeCoupling" => {
+            "codegraph.getTypeHierarchy" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::CouplingParams = serde_json::from_value(args.clone())
-                    .map_err(|e| {
+                let params: crate::handlers::GetTypeHierarchyParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_analyze_coupling(params).await?;
+                let response = self.handle_get_type_hierarchy(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            // AI Agent Query Primitives
-            "codegraph.symbolSearch" => {
+            "codegraph.reaches" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::SymbolSearchParams =
+                let params: crate::handlers::ReachesParams = serde_json::from_value(args.clone())
+                    .map_err(|e| {
+                    tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                })?;
+                let response = self.handle_reaches(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
+            "codegraph.findSimilarCode" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::FindSimilarCodeParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_symbol_search(params).await?;
+                let response = self.handle_find_similar_code(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.findByImports" => {
+            "codegraph.findConfigUsages" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::FindByImportsParams =
+                let params: crate::handlers::FindConfigUsagesParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_find_by_imports(params).await?;
+                let response = self.handle_find_config_usages(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.findEntryPoints" => {
+            "codegraph.previewRename" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::FindEntryPointsParams =
+                let params: crate::handlers::PreviewRenameParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_find_entry_points(params).await?;
+                let response = self.handle_preview_rename(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.traverseGraph" => {
+            "codegraph.findTodos" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::TraverseGraphParams =
-                    serde_json::from_value(args.clone()).map_err(|e| {
+                let params: crate::handlers::FindTodosParams = serde_json::from_value(args.clone())
+                    .map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_traverse_graph(params).await?;
+                let response = self.handle_find_todos(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getCallers" => {
+            "codegraph.findByAnnotation" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::GetCallersParams =
+                let params: crate::handlers::FindByAnnotationParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_callers(params).await?;
+                let response = self.handle_find_by_annotation(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getCallees" => {
+            "codegraph.query" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::GetCallersParams =
+                let params: crate::handlers::QueryPipelineParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_callees(params).await?;
+                let response = self.handle_query_pipeline(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.getDetailedSymbolInfo" => {
+            "codegraph.getFileOutline" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::GetDetailedInfoParams =
+                let params: crate::handlers::GetFileOutlineParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_get_detailed_symbol_info(params).await?;
+                let response = self.handle_get_file_outline(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
-            "codegraph.findBySignature" => {
+            "codegraph.batchQuery" => {
                 let args = params.arguments.first().ok_or_else(|| {
                     tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
                 })?;
-                let params: crate::handlers::FindBySignatureParams =
+                let params: crate::handlers::BatchQueryParams =
                     serde_json::from_value(args.clone()).map_err(|e| {
                         tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
                     })?;
-                let response = self.handle_find_by_signature(params).await?;
+                let response = self.handle_batch_query(params).await?;
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
@@ -1970,6 +3298,18 @@ impl LanguageServer for CodeGraphBackend {
                 Ok(Some(serde_json::to_value(response).unwrap()))
             }
 
+            "codegraph.memoryRevalidate" => {
+                let args = params.arguments.first().ok_or_else(|| {
+                    tower_lsp::jsonrpc::Error::invalid_params("Missing arguments")
+                })?;
+                let params: crate::handlers::MemoryRevalidateParams =
+                    serde_json::from_value(args.clone()).map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid params: {e}"))
+                    })?;
+                let response = self.handle_memory_revalidate(params).await?;
+                Ok(Some(serde_json::to_value(response).unwrap()))
+            }
+
             "codegraph.memoryList" => {
                 let args = params
                     .arguments
@@ -2020,7 +3360,12 @@ impl LanguageServer for CodeGraphBackend {
                 })?;
                 let params: serde_json::Value = serde_json::from_value(args.clone())
                     .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
-                let response = self.handle_mine_git_history(params).await?;
+                let backend = self.clone();
+                let response = run_cancellable(move |cancel| async move {
+                    backend.handle_mine_git_history(params, cancel).await
+                })
+                .await
+                .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())??;
                 Ok(Some(response))
             }
 
@@ -2030,7 +3375,14 @@ impl LanguageServer for CodeGraphBackend {
                 })?;
                 let file_params: serde_json::Value = serde_json::from_value(args.clone())
                     .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(e.to_string()))?;
-                let response = self.handle_mine_git_history_for_file(file_params).await?;
+                let backend = self.clone();
+                let response = run_cancellable(move |cancel| async move {
+                    backend
+                        .handle_mine_git_history_for_file(file_params, cancel)
+                        .await
+                })
+                .await
+                .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())??;
                 Ok(Some(response))
             }
 
@@ -2050,8 +3402,7 @@ impl LanguageServer for CodeGraphBackend {
                 })?;
                 let new_config: CodeGraphConfig = serde_json::from_value(args.clone())
                     .map_err(|e| tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid config: {e}")))?;
-                tracing::info!("Configuration updated: {:?}", new_config);
-                *self.config.write().await = new_config;
+                self.apply_configuration(new_config).await;
                 self.client
                     .log_message(MessageType::INFO, "Configuration updated")
                     .await;
@@ -2065,7 +3416,7 @@ impl LanguageServer for CodeGraphBackend {
 
 impl CodeGraphBackend {
     /// Extract node ID from CallHierarchyItem data.
-    fn extract_node_id_from_item(&self, item: &CallHierarchyItem) -> Result<NodeId> {
+    pub(crate) fn extract_node_id_from_item(&self, item: &CallHierarchyItem) -> Result<NodeId> {
         let data = item
             .data
             .as_ref()
@@ -2508,8 +3859,6 @@ impl CodeGraphBackend {
         &self,
         params: crate::handlers::MemoryUpdateParams,
     ) -> Result<crate::handlers::MemoryUpdateResponse> {
-        use crate::memory::{CodeLink, LinkedNodeType};
-
         // Get existing memory
         let existing = self
             .memory_manager
@@ -2524,45 +3873,7 @@ impl CodeGraphBackend {
             });
         };
 
-        // Apply updates
-        if let Some(title) = params.title {
-            memory.title = title;
-        }
-        if let Some(content) = params.content {
-            memory.content = content;
-        }
-        if let Some(tags) = params.tags {
-            memory.tags = tags;
-        }
-        if let Some(confidence) = params.confidence {
-            memory.confidence = confidence;
-        }
-
-        // Handle code link additions
-        for link_param in params.add_code_links {
-            let node_type = match link_param.node_type.as_str() {
-                "function" => LinkedNodeType::Function,
-                "class" => LinkedNodeType::Class,
-                "module" => LinkedNodeType::Module,
-                "file" => LinkedNodeType::File,
-                "variable" => LinkedNodeType::Variable,
-                "import" => LinkedNodeType::Import,
-                "interface" => LinkedNodeType::Interface,
-                "trait" => LinkedNodeType::Trait,
-                _ => LinkedNodeType::Function, // Default fallback
-            };
-            memory
-                .code_links
-                .push(CodeLink::new(link_param.node_id, node_type));
-        }
-
-        // Handle code link removals
-        for node_id in params.remove_code_links {
-            memory.code_links.retain(|link| link.node_id != node_id);
-        }
-
-        // Clear embeddings so they get regenerated
-        memory.embedding = None;
+        params.apply_to(&mut memory);
 
         // Store updated memory
         let id = self
@@ -2807,58 +4118,22 @@ impl CodeGraphBackend {
     pub async fn handle_mine_git_history(
         &self,
         params: serde_json::Value,
+        cancel: CancellationToken,
     ) -> Result<serde_json::Value> {
-        use crate::git_mining::{GitMiner, MiningConfig};
+        use crate::git_mining::{GitMiner, HotspotOptions, MiningConfig};
 
-        // Get workspace folder
-        let workspace_folders = self.workspace_folders.read().await;
-        let workspace_path = workspace_folders
-            .first()
+        // Mine the workspace folder given in params, or the first one
+        let folder = params
+            .get("folder")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from);
+        let workspace_path = self
+            .workspace_folder_for(folder.as_deref())
+            .await
             .ok_or_else(tower_lsp::jsonrpc::Error::invalid_request)?;
 
-        // Parse configuration from params
-        let max_commits = params
-            .get("maxCommits")
-            .and_then(|v| v.as_u64())
-            .map(|v| v as usize)
-            .unwrap_or(500);
-
-        let min_confidence = params
-            .get("minConfidence")
-            .and_then(|v| v.as_f64())
-            .map(|v| v as f32)
-            .unwrap_or(0.7);
-
-        let mine_bug_fixes = params
-            .get("mineBugFixes")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        let mine_arch_decisions = params
-            .get("mineArchDecisions")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        let mine_breaking_changes = params
-            .get("mineBreakingChanges")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        let mine_reverts = params
-            .get("mineReverts")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
-        let mine_features = params
-            .get("mineFeatures")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(false);
-
-        let mine_deprecations = params
-            .get("mineDeprecations")
-            .and_then(|v| v.as_bool())
-            .unwrap_or(true);
-
+        // Parse configuration from params, falling back to the settings
+        let defaults = self.config.read().await.memory.git_mining.clone();
         let include_hotspots = params
             .get("includeHotspots")
             .and_then(|v| v.as_bool())
@@ -2869,23 +4144,31 @@ impl CodeGraphBackend {
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
 
-        let config = MiningConfig {
-            max_commits,
-            min_confidence,
-            mine_bug_fixes,
-            mine_arch_decisions,
-            mine_breaking_changes,
-            mine_reverts,
-            mine_features,
-            mine_deprecations,
-        };
+        let include_ownership = params
+            .get("includeOwnership")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let config = MiningConfig::from_params(
+            &params,
+            MiningConfig {
+                max_commits: defaults.max_commits,
+                min_confidence: defaults.min_confidence,
+                mine_features: false,
+                ..MiningConfig::default()
+            },
+        );
 
         // Create miner and run
-        let miner = GitMiner::new(workspace_path)
+        let miner = GitMiner::new(&workspace_path)
             .map_err(|_| tower_lsp::jsonrpc::Error::invalid_request())?;
 
+        let progress = self
+            .progress
+            .begin_cancellable(&self.client, "CodeGraph: Mining git history", cancel)
+            .await;
         let mut result = miner
-            .mine_repository(&self.memory_manager, &self.graph, &config)
+            .mine_repository(&self.memory_manager, &self.graph, &config, Some(&progress))
             .await
             .map_err(|e| {
                 tracing::error!("Git mining failed: {}", e);
@@ -2894,109 +4177,58 @@ impl CodeGraphBackend {
 
         // Detect hotspots if requested
         let mut hotspots_created = 0;
-        if include_hotspots {
-            match miner.detect_hotspots(10).await {
-                Ok(hotspots) => {
-                    for hotspot in hotspots.iter().take(20) {
-                        // Create memory for each hotspot
-                        let memory = codegraph_memory::MemoryNode::builder()
-                            .project_context(
-                                format!("High-activity file: {}", hotspot.file_path),
-                                format!(
-                                    "Modified {} times across {} commits. This file shows high churn, \
-                                     indicating active development or potential complexity.",
-                                    hotspot.change_count, hotspot.unique_commits
-                                ),
-                            )
-                            .title(format!("Hotspot: {}", hotspot.file_path))
-                            .content(format!(
-                                "**Change Count:** {}\n**Unique Commits:** {}\n**Recent Changes:**\n{}",
-                                hotspot.change_count,
-                                hotspot.unique_commits,
-                                hotspot.recent_changes.join("\n- ")
-                            ))
-                            .tag("hotspot")
-                            .tag("git-mined")
-                            .confidence(0.7)
-                            .build()
-                            .ok();
-
-                        if let Some(m) = memory {
-                            if let Ok(id) = self.memory_manager.put(m).await {
-                                result.memory_ids.push(id);
-                                hotspots_created += 1;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    result
-                        .warnings
-                        .push(format!("Failed to detect hotspots: {}", e));
-                }
-            }
+        if include_hotspots && !progress.is_cancelled() {
+            progress.report("Detecting hotspots", None);
+            let options = HotspotOptions::from_params(&params, HotspotOptions::for_memories());
+            hotspots_created = miner
+                .mine_hotspots(&self.memory_manager, &options, &mut result)
+                .await;
         }
 
         // Detect coupling if requested
         let mut couplings_created = 0;
-        if include_coupling {
-            match miner.detect_coupling(0.7).await {
-                Ok(couplings) => {
-                    for coupling in couplings.iter().take(15) {
-                        // Create convention memory for strong couplings
-                        let memory = codegraph_memory::MemoryNode::builder()
-                            .convention(
-                                format!("Co-change: {} ↔ {}", coupling.file_a, coupling.file_b),
-                                format!(
-                                    "These files change together {:.0}% of the time ({} of {} changes). \
-                                     When modifying one, consider checking the other.",
-                                    coupling.coupling_strength * 100.0,
-                                    coupling.co_change_count,
-                                    coupling.total_changes
-                                ),
-                            )
-                            .title(format!("Coupling: {} ↔ {}", 
-                                coupling.file_a.split('/').next_back().unwrap_or(&coupling.file_a),
-                                coupling.file_b.split('/').next_back().unwrap_or(&coupling.file_b)))
-                            .content(format!(
-                                "**File A:** {}\n**File B:** {}\n**Coupling Strength:** {:.1}%\n\
-                                 **Co-changes:** {} out of {} total changes",
-                                coupling.file_a,
-                                coupling.file_b,
-                                coupling.coupling_strength * 100.0,
-                                coupling.co_change_count,
-                                coupling.total_changes
-                            ))
-                            .tag("coupling")
-                            .tag("git-mined")
-                            .confidence(coupling.coupling_strength)
-                            .build()
-                            .ok();
-
-                        if let Some(m) = memory {
-                            if let Ok(id) = self.memory_manager.put(m).await {
-                                result.memory_ids.push(id);
-                                couplings_created += 1;
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    result
-                        .warnings
-                        .push(format!("Failed to detect coupling: {}", e));
-                }
-            }
+        if include_coupling && !progress.is_cancelled() {
+            progress.report("Detecting co-change coupling", None);
+            couplings_created = miner
+                .mine_couplings(&self.memory_manager, &self.graph, &mut result)
+                .await;
+        }
+
+        // Map code ownership if requested
+        let mut ownership_created = 0;
+        if include_ownership && !progress.is_cancelled() {
+            progress.report("Mapping code ownership", None);
+            ownership_created = miner
+                .mine_ownership(&self.memory_manager, &mut result)
+                .await;
         }
 
+        let memories_created =
+            result.memories_created + hotspots_created + couplings_created + ownership_created;
+        progress.finish(match &result.candidates {
+            Some(candidates) => format!("Dry run: {} candidate memories", candidates.len()),
+            None => format!("Created {} memories", memories_created),
+        });
+
         Ok(serde_json::json!({
             "commitsProcessed": result.commits_processed,
-            "memoriesCreated": result.memories_created + hotspots_created + couplings_created,
+            "memoriesCreated": memories_created,
             "commitsSkipped": result.commits_skipped,
+            "commitsGrouped": result.commits_grouped,
+            "pullRequestsEnriched": result.pull_requests_enriched,
+            "memoriesReverted": result.memories_reverted,
+            "memoriesRestored": result.memories_restored,
+            "squashMergesSplit": result.squash_merges_split,
+            "trailersMined": result.trailers_mined,
+            "dryRun": config.dry_run,
+            "candidates": result.candidates,
+            "releasesMined": result.releases_mined,
+            "sinceCommit": result.since_commit,
             "memoryIds": result.memory_ids,
             "warnings": result.warnings,
             "hotspotsDetected": hotspots_created,
             "couplingsDetected": couplings_created,
+            "ownershipDetected": ownership_created,
         }))
     }
 
@@ -3004,6 +4236,7 @@ impl CodeGraphBackend {
     pub async fn handle_mine_git_history_for_file(
         &self,
         params: serde_json::Value,
+        cancel: CancellationToken,
     ) -> Result<serde_json::Value> {
         use crate::git_mining::{GitMiner, MiningConfig};
 
@@ -3018,42 +4251,61 @@ impl CodeGraphBackend {
             .and_then(|url| url.to_file_path().ok())
             .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Invalid file URI"))?;
 
-        // Get workspace folder
-        let workspace_folders = self.workspace_folders.read().await;
-        let workspace_path = workspace_folders
-            .first()
+        // Mine the repository of the workspace folder containing the file
+        let workspace_path = self
+            .workspace_folder_for(Some(&file_path))
+            .await
             .ok_or_else(tower_lsp::jsonrpc::Error::invalid_request)?;
 
-        // Parse configuration from params
-        let max_commits = params
-            .get("maxCommits")
-            .and_then(|v| v.as_u64())
-            .map(|v| v as usize)
-            .unwrap_or(100);
-
-        let config = MiningConfig {
-            max_commits,
-            min_confidence: 0.7,
-            ..Default::default()
-        };
+        // Parse configuration from params, falling back to the settings
+        let config = MiningConfig::from_params(
+            &params,
+            MiningConfig {
+                max_commits: 100,
+                min_confidence: self.config.read().await.memory.git_mining.min_confidence,
+                ..Default::default()
+            },
+        );
 
         // Create miner and run for specific file
-        let miner = GitMiner::new(workspace_path)
+        let miner = GitMiner::new(&workspace_path)
             .map_err(|_| tower_lsp::jsonrpc::Error::invalid_request())?;
 
+        let progress = self
+            .progress
+            .begin_cancellable(&self.client, "CodeGraph: Mining file history", cancel)
+            .await;
         let result = miner
-            .mine_file(&file_path, &self.memory_manager, &self.graph, &config)
+            .mine_file(
+                &file_path,
+                &self.memory_manager,
+                &self.graph,
+                &config,
+                Some(&progress),
+            )
             .await
             .map_err(|e| {
                 tracing::error!("Git mining for file failed: {}", e);
                 tower_lsp::jsonrpc::Error::internal_error()
             })?;
+        progress.finish(match &result.candidates {
+            Some(candidates) => format!("Dry run: {} candidate memories", candidates.len()),
+            None => format!("Created {} memories", result.memories_created),
+        });
 
         Ok(serde_json::json!({
             "file": file_path.to_string_lossy(),
             "commitsProcessed": result.commits_processed,
             "memoriesCreated": result.memories_created,
             "commitsSkipped": result.commits_skipped,
+            "commitsGrouped": result.commits_grouped,
+            "pullRequestsEnriched": result.pull_requests_enriched,
+            "memoriesReverted": result.memories_reverted,
+            "memoriesRestored": result.memories_restored,
+            "squashMergesSplit": result.squash_merges_split,
+            "trailersMined": result.trailers_mined,
+            "dryRun": config.dry_run,
+            "candidates": result.candidates,
             "memoryIds": result.memory_ids,
             "warnings": result.warnings
         }))
@@ -3836,6 +5088,43 @@ mod tests {
         assert_eq!(edges.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_goto_skips_co_change_edges() {
+        let (backend, func1_id, func2_id) = create_backend_with_nodes().await;
+        {
+            let mut g = backend.graph.write().await;
+            let props = PropertyMap::new().with("kind", node_props::CO_CHANGE_KIND);
+            g.add_edge(func1_id, func2_id, EdgeType::References, props)
+                .unwrap();
+        }
+        let graph = backend.graph.read().await;
+
+        // func1 only co-changes with func2; it references nothing in code
+        let edges = backend.get_connected_edges(&graph, func1_id, Direction::Outgoing);
+        assert!(edges.is_empty());
+        let definition = backend.find_definition_for_reference(&graph, func1_id);
+        assert_eq!(definition.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_definition_at_keeps_definitions() {
+        let (backend, func1_id, func2_id) = create_backend_with_nodes().await;
+        let import_id = {
+            let mut g = backend.graph.write().await;
+            let import_id = g
+                .add_node(NodeType::Import, PropertyMap::new().with("name", "target"))
+                .unwrap();
+            g.add_edge(import_id, func1_id, EdgeType::Imports, PropertyMap::new())
+                .unwrap();
+            import_id
+        };
+        let graph = backend.graph.read().await;
+
+        // func2 calls func1, but on func2 the cursor is on a definition
+        assert_eq!(backend.definition_at(&graph, func2_id).unwrap(), func2_id);
+        assert_eq!(backend.definition_at(&graph, import_id).unwrap(), func1_id);
+    }
+
     #[tokio::test]
     async fn test_node_to_location() {
         let (backend, func_id, _) = create_backend_with_nodes().await;
@@ -4057,4 +5346,112 @@ mod tests {
         let file_symbols = backend.symbol_index.get_file_symbols(path);
         assert!(file_symbols.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_removed_workspace_folder_is_forgotten() {
+        let (backend, func_id, _) = create_backend_with_nodes().await;
+        let path = Path::new("/test/file.rs");
+        add_func_to_index(&backend, path, func_id, "test_function", 10, 20);
+        *backend.workspace_folders.write().await =
+            vec![PathBuf::from("/test"), PathBuf::from("/other")];
+
+        backend
+            .did_change_workspace_folders(DidChangeWorkspaceFoldersParams {
+                event: WorkspaceFoldersChangeEvent {
+                    added: vec![],
+                    removed: vec![WorkspaceFolder {
+                        uri: Url::from_file_path("/test").unwrap(),
+                        name: "test".to_string(),
+                    }],
+                },
+            })
+            .await;
+
+        assert_eq!(
+            *backend.workspace_folders.read().await,
+            vec![PathBuf::from("/other")]
+        );
+        assert!(backend.symbol_index.get_file_symbols(path).is_empty());
+        assert!(backend.graph.read().await.get_node(func_id).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_apply_configuration_from_settings() {
+        let backend = create_test_backend();
+        // Shape of `workspace.getConfiguration('codegraph')` in VS Code
+        let settings = serde_json::json!({
+            "indexOnStartup": true,
+            "excludePatterns": ["**/vendor/**"],
+            "maxFileSizeKB": 256,
+            "inlayHints": { "complexityThreshold": 15 },
+            "search": { "bm25Weight": 0.7, "semanticWeight": 0.3 },
+            "memory": {
+                "enabled": true,
+                "autoInvalidate": false,
+                "gitMining": { "maxCommits": 50 }
+            },
+            "debug": false
+        });
+        let config: CodeGraphConfig = serde_json::from_value(settings).unwrap();
+        backend.apply_configuration(config).await;
+
+        let config = backend.config.read().await.clone();
+        assert!(config.index_on_startup);
+        assert_eq!(config.exclude_patterns, vec!["**/vendor/**"]);
+        assert_eq!(config.max_file_size_kb, 256);
+        assert_eq!(config.inlay_hints.complexity_threshold, 15);
+        assert!(config.inlay_hints.callers);
+        assert_eq!(config.search.bm25_weight, 0.7);
+        assert!(!config.memory.auto_invalidate);
+        assert_eq!(config.memory.git_mining.max_commits, 50);
+        assert_eq!(config.memory.git_mining.min_confidence, 0.7);
+    }
+
+    #[test]
+    fn test_folder_excludes_only_apply_inside_their_folder() {
+        let config: CodeGraphConfig = serde_json::from_value(serde_json::json!({
+            "excludePatterns": ["**/node_modules/**"],
+            "folderExcludes": { "/ws/api/": ["**/generated/**", "./fixtures/**"] }
+        }))
+        .unwrap();
+        let set = CodeGraphBackend::build_exclude_set(&config.exclude_globs());
+
+        assert!(set.is_match("/ws/web/node_modules/x.js"));
+        assert!(set.is_match("/ws/api/src/generated/types.ts"));
+        assert!(set.is_match("/ws/api/fixtures/data.ts"));
+        assert!(!set.is_match("/ws/web/src/generated/types.ts"));
+        assert!(!set.is_match("/ws/api/src/fixtures/data.ts"));
+    }
+
+    #[test]
+    fn test_diagnostic_sets_are_published_merged() {
+        let uri = Url::parse("file:///ws/src/api.rs").unwrap();
+        let diagnostic = |code: &str| Diagnostic {
+            code: Some(NumberOrString::String(code.to_string())),
+            ..Default::default()
+        };
+        let mut published = PublishedDiagnostics::new();
+
+        merge_diagnostic_set(
+            &mut published,
+            &uri,
+            "layer-violation",
+            vec![diagnostic("layer-violation")],
+        );
+        let merged = merge_diagnostic_set(
+            &mut published,
+            &uri,
+            "stale-memory",
+            vec![diagnostic("stale-memory")],
+        );
+        assert_eq!(merged.len(), 2);
+
+        // Clearing one set keeps the other
+        let merged = merge_diagnostic_set(&mut published, &uri, "stale-memory", Vec::new());
+        assert_eq!(merged, vec![diagnostic("layer-violation")]);
+
+        let merged = merge_diagnostic_set(&mut published, &uri, "layer-violation", Vec::new());
+        assert!(merged.is_empty());
+        assert!(published.is_empty());
+    }
 }
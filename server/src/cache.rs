@@ -1,12 +1,16 @@
 //! Query caching for performance optimization.
 
+use crate::ai_query::TraversalNode;
+use crate::domain::callers::{CalleesResult, CallersResult};
 use codegraph::NodeId;
 use dashmap::DashMap;
 use lru::LruCache;
+use std::collections::HashSet;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Mutex;
-use tower_lsp::lsp_types::{Location, Range};
+use tower_lsp::lsp_types::{Command, Location, Range};
 
 /// Cache for definition lookups.
 type DefinitionCache = DashMap<(PathBuf, u32, u32), NodeId>;
@@ -14,6 +18,12 @@ type DefinitionCache = DashMap<(PathBuf, u32, u32), NodeId>;
 /// Cache for references lookups.
 type ReferencesCache = DashMap<NodeId, Vec<Location>>;
 
+/// Cache for resolved code lens commands, keyed by (node, lens kind).
+type CodeLensCache = DashMap<(NodeId, &'static str), Command>;
+
+/// Key for AI query primitive results: (primitive kind, node, normalized params).
+type PrimitiveKey = (&'static str, NodeId, String);
+
 /// Caches for expensive queries.
 pub struct QueryCache {
     /// Fast lookup cache for definitions.
@@ -22,6 +32,9 @@ pub struct QueryCache {
     /// Fast lookup cache for references.
     references: ReferencesCache,
 
+    /// Resolved code lens commands.
+    code_lenses: CodeLensCache,
+
     /// LRU cache for call hierarchy results.
     call_hierarchies: Mutex<LruCache<NodeId, CallHierarchyCache>>,
 
@@ -36,6 +49,18 @@ pub struct QueryCache {
 
     /// LRU cache for AI agent graph traversal results.
     traversals: Mutex<LruCache<(NodeId, String, u32), TraversalCache>>,
+
+    /// LRU cache for AI query primitive results (callers, callees, traversals).
+    primitives: Mutex<LruCache<PrimitiveKey, PrimitiveCacheEntry>>,
+
+    /// Primitive cache lookups that returned a result.
+    primitive_hits: AtomicU64,
+
+    /// Primitive cache lookups that found nothing.
+    primitive_misses: AtomicU64,
+
+    /// Primitive cache entries dropped by file invalidation.
+    primitive_invalidations: AtomicU64,
 }
 
 /// Cached call hierarchy data.
@@ -74,6 +99,32 @@ pub struct TraversalCache {
     pub query_time_ms: u64,
 }
 
+/// Result of an AI query primitive.
+#[derive(Clone)]
+pub(crate) enum PrimitiveResult {
+    Callers(CallersResult),
+    Callees(CalleesResult),
+    Traversal(Vec<TraversalNode>),
+}
+
+/// Cached AI query primitive result with the files it was derived from.
+#[derive(Clone)]
+pub(crate) struct PrimitiveCacheEntry {
+    pub result: PrimitiveResult,
+    /// Source files of the queried node and every node in the result
+    pub files: HashSet<PathBuf>,
+    /// Set for incoming queries: a new caller can appear in any file,
+    /// so the entry is dropped on every file change.
+    pub any_file: bool,
+}
+
+impl PrimitiveCacheEntry {
+    /// Whether a change to `path` can affect this entry.
+    fn depends_on(&self, path: &Path) -> bool {
+        self.any_file || self.files.contains(path)
+    }
+}
+
 impl QueryCache {
     /// Create a new cache with the specified capacity.
     pub fn new(capacity: usize) -> Self {
@@ -82,6 +133,7 @@ impl QueryCache {
         Self {
             definitions: DashMap::new(),
             references: DashMap::new(),
+            code_lenses: DashMap::new(),
             call_hierarchies: Mutex::new(LruCache::new(capacity)),
             dependency_graphs: Mutex::new(LruCache::new(
                 NonZeroUsize::new(capacity.get() / 2).unwrap_or(NonZeroUsize::new(50).unwrap()),
@@ -89,6 +141,10 @@ impl QueryCache {
             ai_contexts: Mutex::new(LruCache::new(capacity)),
             symbol_searches: Mutex::new(LruCache::new(capacity)),
             traversals: Mutex::new(LruCache::new(capacity)),
+            primitives: Mutex::new(LruCache::new(capacity)),
+            primitive_hits: AtomicU64::new(0),
+            primitive_misses: AtomicU64::new(0),
+            primitive_invalidations: AtomicU64::new(0),
         }
     }
 
@@ -122,6 +178,20 @@ impl QueryCache {
         self.references.insert(node_id, locations);
     }
 
+    // ==========================================
+    // Code Lens Cache
+    // ==========================================
+
+    /// Get a cached code lens command.
+    pub fn get_code_lens(&self, node_id: NodeId, kind: &'static str) -> Option<Command> {
+        self.code_lenses.get(&(node_id, kind)).map(|v| v.clone())
+    }
+
+    /// Store a resolved code lens command in cache.
+    pub fn set_code_lens(&self, node_id: NodeId, kind: &'static str, command: Command) {
+        self.code_lenses.insert((node_id, kind), command);
+    }
+
     // ==========================================
     // Call Hierarchy Cache
     // ==========================================
@@ -227,6 +297,47 @@ impl QueryCache {
         }
     }
 
+    // ==========================================
+    // AI Query Primitive Cache
+    // ==========================================
+
+    /// Get a cached primitive result, recording a hit or miss.
+    /// `params` is a normalized string of every parameter that affects the result.
+    pub(crate) fn get_primitive(
+        &self,
+        kind: &'static str,
+        node_id: NodeId,
+        params: &str,
+    ) -> Option<PrimitiveResult> {
+        let cached = self
+            .primitives
+            .lock()
+            .ok()?
+            .get(&(kind, node_id, params.to_string()))
+            .map(|entry| entry.result.clone());
+
+        let counter = if cached.is_some() {
+            &self.primitive_hits
+        } else {
+            &self.primitive_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+        cached
+    }
+
+    /// Store a primitive result in cache.
+    pub(crate) fn set_primitive(
+        &self,
+        kind: &'static str,
+        node_id: NodeId,
+        params: String,
+        entry: PrimitiveCacheEntry,
+    ) {
+        if let Ok(mut guard) = self.primitives.lock() {
+            guard.put((kind, node_id, params), entry);
+        }
+    }
+
     // ==========================================
     // Invalidation
     // ==========================================
@@ -239,6 +350,9 @@ impl QueryCache {
         // Clear references cache (could be more selective)
         self.references.clear();
 
+        // Lens counts include references and tests from any file
+        self.code_lenses.clear();
+
         // Clear call hierarchies
         if let Ok(mut guard) = self.call_hierarchies.lock() {
             guard.clear();
@@ -250,12 +364,27 @@ impl QueryCache {
             // For now, just clear if the path matches the first in key
             guard.clear();
         }
+
+        // Remove primitive results derived from this file
+        if let Ok(mut guard) = self.primitives.lock() {
+            let stale: Vec<PrimitiveKey> = guard
+                .iter()
+                .filter(|(_, entry)| entry.depends_on(path))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in &stale {
+                guard.pop(key);
+            }
+            self.primitive_invalidations
+                .fetch_add(stale.len() as u64, Ordering::Relaxed);
+        }
     }
 
     /// Invalidate entire cache.
     pub fn invalidate_all(&self) {
         self.definitions.clear();
         self.references.clear();
+        self.code_lenses.clear();
 
         if let Ok(mut guard) = self.call_hierarchies.lock() {
             guard.clear();
@@ -276,6 +405,10 @@ impl QueryCache {
         if let Ok(mut guard) = self.traversals.lock() {
             guard.clear();
         }
+
+        if let Ok(mut guard) = self.primitives.lock() {
+            guard.clear();
+        }
     }
 
     /// Get cache statistics.
@@ -288,6 +421,10 @@ impl QueryCache {
             ai_contexts_count: self.ai_contexts.lock().map(|g| g.len()).unwrap_or(0),
             symbol_searches_count: self.symbol_searches.lock().map(|g| g.len()).unwrap_or(0),
             traversals_count: self.traversals.lock().map(|g| g.len()).unwrap_or(0),
+            primitives_count: self.primitives.lock().map(|g| g.len()).unwrap_or(0),
+            primitive_hits: self.primitive_hits.load(Ordering::Relaxed),
+            primitive_misses: self.primitive_misses.load(Ordering::Relaxed),
+            primitive_invalidations: self.primitive_invalidations.load(Ordering::Relaxed),
         }
     }
 }
@@ -301,6 +438,10 @@ pub struct CacheStats {
     pub ai_contexts_count: usize,
     pub symbol_searches_count: usize,
     pub traversals_count: usize,
+    pub primitives_count: usize,
+    pub primitive_hits: u64,
+    pub primitive_misses: u64,
+    pub primitive_invalidations: u64,
 }
 
 impl Default for QueryCache {
@@ -562,6 +703,24 @@ mod tests {
         assert!(cache.get_references(node_id).is_none());
     }
 
+    #[test]
+    fn test_invalidate_file_clears_code_lenses() {
+        let cache = QueryCache::new(100);
+        let command = Command {
+            title: "2 references".to_string(),
+            command: "codegraph.showReferences".to_string(),
+            arguments: None,
+        };
+
+        cache.set_code_lens(7, "references", command.clone());
+        assert_eq!(cache.get_code_lens(7, "references"), Some(command));
+        assert!(cache.get_code_lens(7, "tests").is_none());
+
+        // A change to any file can add a reference or a test
+        cache.invalidate_file(&PathBuf::from("/test/other.rs"));
+        assert!(cache.get_code_lens(7, "references").is_none());
+    }
+
     #[test]
     fn test_invalidate_all() {
         let cache = QueryCache::new(100);
@@ -645,6 +804,68 @@ mod tests {
         assert!(cache.get_call_hierarchy(2).is_some());
     }
 
+    fn traversal_entry(files: &[&str], any_file: bool) -> PrimitiveCacheEntry {
+        PrimitiveCacheEntry {
+            result: PrimitiveResult::Traversal(vec![]),
+            files: files.iter().map(PathBuf::from).collect(),
+            any_file,
+        }
+    }
+
+    #[test]
+    fn test_primitive_cache_hit_miss_stats() {
+        let cache = QueryCache::new(100);
+
+        assert!(cache.get_primitive("traverse", 1, "depth=3").is_none());
+        cache.set_primitive(
+            "traverse",
+            1,
+            "depth=3".to_string(),
+            traversal_entry(&["/test/a.rs"], false),
+        );
+        assert!(cache.get_primitive("traverse", 1, "depth=3").is_some());
+        // Different params are a different entry
+        assert!(cache.get_primitive("traverse", 1, "depth=2").is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.primitives_count, 1);
+        assert_eq!(stats.primitive_hits, 1);
+        assert_eq!(stats.primitive_misses, 2);
+    }
+
+    #[test]
+    fn test_invalidate_file_is_selective_for_primitives() {
+        let cache = QueryCache::new(100);
+        let a = PathBuf::from("/test/a.rs");
+
+        cache.set_primitive(
+            "callees",
+            1,
+            String::new(),
+            traversal_entry(&["/test/a.rs"], false),
+        );
+        cache.set_primitive(
+            "callees",
+            2,
+            String::new(),
+            traversal_entry(&["/test/b.rs"], false),
+        );
+        cache.set_primitive(
+            "callers",
+            2,
+            String::new(),
+            traversal_entry(&["/test/b.rs"], true),
+        );
+
+        cache.invalidate_file(&a);
+
+        // Entries touching a.rs and incoming queries are dropped
+        assert!(cache.get_primitive("callees", 1, "").is_none());
+        assert!(cache.get_primitive("callers", 2, "").is_none());
+        assert!(cache.get_primitive("callees", 2, "").is_some());
+        assert_eq!(cache.stats().primitive_invalidations, 2);
+    }
+
     #[test]
     fn test_concurrent_access_definitions() {
         use std::sync::Arc;
@@ -0,0 +1,175 @@
+//! Cancellation of long-running requests.
+//!
+//! tower-lsp answers `$/cancelRequest` by dropping the request's future. That
+//! stops nothing that runs without awaiting, like a graph traversal, and
+//! dropping a reindex halfway would leave the graph without resolved imports.
+//! Heavy handlers therefore run their work on a separate task through
+//! [`run_cancellable`], which cancels a [`CancellationToken`] when the request
+//! is dropped. The work checks the token, stops early and keeps what it has
+//! done so far.
+//!
+//! A token can also carry a deadline, so queries on a huge graph return
+//! what they found within their time budget instead of running on.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Shared flag telling a running operation to stop, with an optional
+/// deadline after which it counts as cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    /// Wakes the futures waiting in [`CancellationToken::cancelled`]
+    notify: Arc<tokio::sync::Notify>,
+    deadline: Option<Instant>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// This token, also cancelled once `budget` has passed. `None` leaves it
+    /// unchanged.
+    pub fn with_budget(mut self, budget: Option<Duration>) -> Self {
+        if let Some(budget) = budget {
+            self.deadline = Some(Instant::now() + budget);
+        }
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.notify.notify_waiters();
+    }
+
+    /// Wait until the token is cancelled or its deadline has passed, e.g. to
+    /// stop waiting for something else in a `select!`.
+    pub async fn cancelled(&self) {
+        let notified = self.notify.notified();
+        tokio::pin!(notified);
+        // Registered before checking, so a cancel in between is not missed
+        notified.as_mut().enable();
+        if self.is_cancelled() {
+            return;
+        }
+        match self.deadline {
+            Some(deadline) => {
+                tokio::select! {
+                    _ = notified => {}
+                    _ = tokio::time::sleep_until(deadline.into()) => {}
+                }
+            }
+            None => notified.await,
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed) || self.timed_out()
+    }
+
+    /// Whether the deadline has passed.
+    pub fn timed_out(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// A guard that cancels this token when dropped.
+    pub fn drop_guard(&self) -> CancelOnDrop {
+        CancelOnDrop(self.clone())
+    }
+}
+
+/// Cancels its token when dropped.
+#[derive(Debug)]
+pub struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Run `work` on its own task and wait for it. If the calling future is
+/// dropped first, as tower-lsp does on `$/cancelRequest`, the token given to
+/// `work` is cancelled.
+pub async fn run_cancellable<F, Fut, T>(work: F) -> Result<T, tokio::task::JoinError>
+where
+    F: FnOnce(CancellationToken) -> Fut,
+    Fut: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    let token = CancellationToken::new();
+    let _guard = token.drop_guard();
+    tokio::spawn(work(token.clone())).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_dropping_request_cancels_work() {
+        let (started_tx, started_rx) = tokio::sync::oneshot::channel();
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel();
+        let request = run_cancellable(|token| async move {
+            let _ = started_tx.send(());
+            while !token.is_cancelled() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+            let _ = done_tx.send("stopped");
+        });
+
+        // Drop the request once the work is running
+        tokio::select! {
+            _ = request => panic!("work should not finish by itself"),
+            _ = started_rx => {}
+        }
+        assert_eq!(
+            tokio::time::timeout(Duration::from_secs(5), done_rx)
+                .await
+                .unwrap()
+                .unwrap(),
+            "stopped"
+        );
+
+        // Work that finishes is returned as usual
+        assert_eq!(run_cancellable(|_| async { 42 }).await.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_budget_cancels_after_deadline() {
+        let token = CancellationToken::new().with_budget(Some(Duration::ZERO));
+        assert!(token.timed_out());
+        assert!(token.is_cancelled());
+
+        let unbounded = CancellationToken::new().with_budget(None);
+        assert!(!unbounded.is_cancelled());
+        unbounded.cancel();
+        assert!(unbounded.is_cancelled() && !unbounded.timed_out());
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_resolves_on_cancel_and_deadline() {
+        let token = CancellationToken::new();
+        let waiting = tokio::spawn({
+            let token = token.clone();
+            async move { token.cancelled().await }
+        });
+        tokio::task::yield_now().await;
+        assert!(!waiting.is_finished());
+        token.cancel();
+        tokio::time::timeout(Duration::from_secs(5), waiting)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let budgeted = CancellationToken::new().with_budget(Some(Duration::from_millis(10)));
+        tokio::time::timeout(Duration::from_secs(5), budgeted.cancelled())
+            .await
+            .unwrap();
+    }
+}
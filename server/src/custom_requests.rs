@@ -3,6 +3,7 @@
 //! Tower-LSP handles custom requests through the request method on LanguageServer trait.
 
 use crate::backend::CodeGraphBackend;
+use crate::cancellation::run_cancellable;
 use crate::handlers::*;
 use crate::watcher::GraphUpdater;
 use serde_json::Value;
@@ -11,6 +12,22 @@ use tower_lsp::jsonrpc::{Error, Result};
 /// Custom request handler dispatcher
 impl CodeGraphBackend {
     pub async fn handle_custom_request(&self, method: &str, params: Value) -> Result<Value> {
+        if method == "codegraph/batchQuery" {
+            let params: BatchQueryParams = serde_json::from_value(params)
+                .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+            let response = self.handle_batch_query(params).await?;
+            return serde_json::to_value(response).map_err(|_| Error::internal_error());
+        }
+        self.dispatch_custom_request(method, params).await
+    }
+
+    /// Dispatch a single custom request. Batch queries fan out through here,
+    /// which keeps them from nesting.
+    pub(crate) async fn dispatch_custom_request(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<Value> {
         match method {
             "codegraph/getDependencyGraph" => {
                 let params: DependencyGraphParams = serde_json::from_value(params)
@@ -33,6 +50,13 @@ impl CodeGraphBackend {
                 serde_json::to_value(response).map_err(|_| Error::internal_error())
             }
 
+            "codegraph/exportGraphVisualization" => {
+                let params: ExportGraphParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_export_graph_visualization(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
             "codegraph/getParserMetrics" => {
                 let params: ParserMetricsParams = serde_json::from_value(params)
                     .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
@@ -40,6 +64,16 @@ impl CodeGraphBackend {
                 serde_json::to_value(response).map_err(|_| Error::internal_error())
             }
 
+            "codegraph/getCacheStats" => {
+                let response = self.handle_get_cache_stats().await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getStatistics" => {
+                let response = self.handle_get_statistics().await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
             "codegraph/reindexWorkspace" => {
                 let total_indexed = self.handle_reindex_workspace().await?;
                 serde_json::to_value(serde_json::json!({
@@ -99,6 +133,13 @@ impl CodeGraphBackend {
                 serde_json::to_value(response).map_err(|_| Error::internal_error())
             }
 
+            "codegraph/checkArchitecture" => {
+                let params: CheckArchitectureParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_check_architecture(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
             // AI Agent Query Primitives
             "codegraph/symbolSearch" => {
                 let params: SymbolSearchParams = serde_json::from_value(params)
@@ -142,6 +183,13 @@ impl CodeGraphBackend {
                 serde_json::to_value(response).map_err(|_| Error::internal_error())
             }
 
+            "codegraph/expandCallHierarchy" => {
+                let params: ExpandCallHierarchyParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_expand_call_hierarchy(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
             "codegraph/getDetailedSymbolInfo" => {
                 let params: GetDetailedInfoParams = serde_json::from_value(params)
                     .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
@@ -156,6 +204,111 @@ impl CodeGraphBackend {
                 serde_json::to_value(response).map_err(|_| Error::internal_error())
             }
 
+            "codegraph/findCycles" => {
+                let params: FindCyclesParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_cycles(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getHotSymbols" => {
+                let params: GetHotSymbolsParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_get_hot_symbols(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getRiskHotspots" => {
+                let params: GetRiskHotspotsParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_get_risk_hotspots(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getCodeOwnership" => {
+                let params: GetCodeOwnershipParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_get_code_ownership(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findTypeUsages" => {
+                let params: FindTypeUsagesParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_type_usages(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findImplementations" => {
+                let params: FindImplementationsParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_implementations(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getTypeHierarchy" => {
+                let params: GetTypeHierarchyParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_get_type_hierarchy(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/reaches" => {
+                let params: ReachesParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_reaches(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findSimilarCode" => {
+                let params: FindSimilarCodeParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_similar_code(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findConfigUsages" => {
+                let params: FindConfigUsagesParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_config_usages(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findTodos" => {
+                let params: FindTodosParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_todos(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/findByAnnotation" => {
+                let params: FindByAnnotationParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_find_by_annotation(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/previewRename" => {
+                let params: PreviewRenameParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_preview_rename(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/query" => {
+                let params: QueryPipelineParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_query_pipeline(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
+            "codegraph/getFileOutline" => {
+                let params: GetFileOutlineParams = serde_json::from_value(params)
+                    .map_err(|e| Error::invalid_params(format!("Invalid params: {e}")))?;
+                let response = self.handle_get_file_outline(params).await?;
+                serde_json::to_value(response).map_err(|_| Error::internal_error())
+            }
+
             "codegraph/findDuplicates" => {
                 let threshold = params
                     .get("threshold")
@@ -315,57 +468,23 @@ impl CodeGraphBackend {
         }
     }
 
-    /// Handle reindex workspace request
-    async fn handle_reindex_workspace(&self) -> Result<usize> {
+    /// Handle reindex workspace request. Reindexing runs on its own task, so
+    /// cancelling the request stops indexing more files but still links the
+    /// files indexed so far.
+    pub(crate) async fn handle_reindex_workspace(&self) -> Result<usize> {
         tracing::info!("Reindexing workspace");
-
-        // Clear current graph and indexes
-        {
-            let mut graph = self.graph.write().await;
-            *graph = codegraph::CodeGraph::in_memory().expect("Failed to create in-memory graph");
-        }
-        self.symbol_index.clear();
-        self.file_cache.clear();
-
-        self.client
-            .log_message(
-                tower_lsp::lsp_types::MessageType::INFO,
-                "Clearing indexes...",
-            )
-            .await;
-
-        // Re-index all workspace folders
-        let folders = self.workspace_folders.read().await.clone();
-        let mut total_indexed = 0;
-
-        for folder in &folders {
-            let count = self.index_directory(folder).await;
-            total_indexed += count;
-            self.client
-                .log_message(
-                    tower_lsp::lsp_types::MessageType::INFO,
-                    format!("Reindexed {} files from {}", count, folder.display()),
-                )
+        let backend = self.clone();
+        run_cancellable(move |cancel| async move {
+            let progress = backend
+                .progress
+                .begin_cancellable(&backend.client, "CodeGraph: Reindexing workspace", cancel)
                 .await;
-        }
-
-        // Resolve cross-file imports after all files are indexed
-        {
-            let mut graph = self.graph.write().await;
-            GraphUpdater::resolve_cross_file_imports(&mut graph);
-        }
-
-        // Rebuild AI query engine indexes
-        self.query_engine.build_indexes().await;
-
-        self.client
-            .log_message(
-                tower_lsp::lsp_types::MessageType::INFO,
-                format!("Workspace reindexed: {total_indexed} files"),
-            )
-            .await;
-
-        Ok(total_indexed)
+            let total_indexed = backend.reindex_workspace(&progress).await;
+            progress.finish(format!("Reindexed {total_indexed} files"));
+            total_indexed
+        })
+        .await
+        .map_err(|_| Error::internal_error())
     }
 
     async fn handle_index_directory(&self, params: Value) -> Result<Value> {
@@ -430,12 +549,7 @@ impl CodeGraphBackend {
         let new_config: CodeGraphConfig = serde_json::from_value(params)
             .map_err(|e| Error::invalid_params(format!("Invalid configuration: {e}")))?;
 
-        tracing::info!("Updating configuration: {:?}", new_config);
-
-        {
-            let mut config = self.config.write().await;
-            *config = new_config;
-        }
+        self.apply_configuration(new_config).await;
 
         self.client
             .log_message(
@@ -0,0 +1,323 @@
+//! In-memory text of documents open in the editor.
+//!
+//! Documents are kept as ropes so incremental changes from the editor are
+//! applied in place instead of resending the whole file on every keystroke.
+//! Each document also keeps its tree-sitter syntax tree. Every change is
+//! applied to the tree as an [`InputEdit`], so the next parse reuses the
+//! unchanged parts of it, and recorded until the next reparse, so only the
+//! symbols in the edited lines need updating.
+
+use ropey::Rope;
+use tower_lsp::lsp_types::{Position, TextDocumentContentChangeEvent};
+use tree_sitter::{InputEdit, Language, Parser, Point, Tree};
+
+/// An open document: its text, syntax tree and the editor's version number.
+#[derive(Debug, Clone)]
+pub struct OpenDocument {
+    text: Rope,
+    pub version: i32,
+    /// Changes applied since the document was last parsed
+    edits: Vec<InputEdit>,
+    /// Syntax tree of the last parse, kept in step with the changes since
+    tree: Option<Tree>,
+}
+
+impl OpenDocument {
+    pub fn new(text: &str, version: i32) -> Self {
+        Self {
+            text: Rope::from_str(text),
+            version,
+            edits: Vec::new(),
+            tree: None,
+        }
+    }
+
+    /// Apply one change: a range replacement, or the full text if the change
+    /// has no range.
+    pub fn apply_change(&mut self, change: &TextDocumentContentChangeEvent) {
+        let (start, end) = match change.range {
+            Some(range) => {
+                let start = self.char_index(range.start);
+                (start, self.char_index(range.end).max(start))
+            }
+            None => (0, self.text.len_chars()),
+        };
+        let start_byte = self.text.char_to_byte(start);
+        let start_position = self.point(start);
+        let old_end_byte = self.text.char_to_byte(end);
+        let old_end_position = self.point(end);
+
+        match change.range {
+            Some(_) => {
+                self.text.remove(start..end);
+                self.text.insert(start, &change.text);
+            }
+            None => self.text = Rope::from_str(&change.text),
+        }
+
+        let new_end = start + change.text.chars().count();
+        let edit = InputEdit {
+            start_byte,
+            old_end_byte,
+            new_end_byte: self.text.char_to_byte(new_end),
+            start_position,
+            old_end_position,
+            new_end_position: self.point(new_end),
+        };
+        if let Some(tree) = self.tree.as_mut() {
+            tree.edit(&edit);
+        }
+        self.edits.push(edit);
+    }
+
+    /// Parse the text with `grammar`, reusing the previous tree, and keep the
+    /// new tree for the next parse.
+    ///
+    /// Returns the rows whose syntax changed since the previous tree, as
+    /// inclusive zero-based row ranges. They can reach past the edited rows,
+    /// e.g. when an edit opens a string or a comment. Returns None when there
+    /// was no previous tree or the text could not be parsed.
+    pub fn parse_tree(&mut self, grammar: &Language) -> Option<Vec<(usize, usize)>> {
+        let mut parser = Parser::new();
+        if let Err(e) = parser.set_language(grammar) {
+            tracing::debug!("Cannot parse document with grammar: {}", e);
+            self.tree = None;
+            return None;
+        }
+        let text = &self.text;
+        let tree = parser.parse_with(
+            &mut |byte: usize, _: Point| -> &[u8] {
+                if byte >= text.len_bytes() {
+                    return &[];
+                }
+                let (chunk, chunk_start, _, _) = text.chunk_at_byte(byte);
+                &chunk.as_bytes()[byte - chunk_start..]
+            },
+            self.tree.as_ref(),
+        );
+        let Some(tree) = tree else {
+            self.tree = None;
+            return None;
+        };
+        let changed = self.tree.as_ref().map(|old| {
+            old.changed_ranges(&tree)
+                .map(|range| (range.start_point.row, range.end_point.row))
+                .collect()
+        });
+        self.tree = Some(tree);
+        changed
+    }
+
+    /// The syntax tree of the last [`Self::parse_tree`], if any.
+    pub fn tree(&self) -> Option<&Tree> {
+        self.tree.as_ref()
+    }
+
+    /// Take the edits applied since the last call, oldest first.
+    pub fn take_edits(&mut self) -> Vec<InputEdit> {
+        std::mem::take(&mut self.edits)
+    }
+
+    /// Put back edits taken by [`Self::take_edits`] that could not be used,
+    /// ahead of any applied since.
+    pub fn restore_edits(&mut self, mut edits: Vec<InputEdit>) {
+        edits.append(&mut self.edits);
+        self.edits = edits;
+    }
+
+    /// The full text of the document.
+    pub fn text(&self) -> String {
+        self.text.to_string()
+    }
+
+    /// Tree-sitter point of a char index.
+    fn point(&self, char_idx: usize) -> Point {
+        let row = self.text.char_to_line(char_idx);
+        let column = self.text.char_to_byte(char_idx) - self.text.line_to_byte(row);
+        Point { row, column }
+    }
+
+    /// Char index of an LSP position (UTF-16 columns). Positions past the end
+    /// of a line or of the document are clamped, as the protocol requires.
+    fn char_index(&self, position: Position) -> usize {
+        let line = position.line as usize;
+        if line >= self.text.len_lines() {
+            return self.text.len_chars();
+        }
+        let line_start = self.text.line_to_char(line);
+        let content = self.text.line(line);
+        let mut line_len = content.len_chars();
+        while line_len > 0 && matches!(content.char(line_len - 1), '\n' | '\r') {
+            line_len -= 1;
+        }
+        let line_end = line_start + line_len;
+        let column = self.text.char_to_utf16_cu(line_start) + position.character as usize;
+        let column = column.min(self.text.char_to_utf16_cu(line_end));
+        self.text.utf16_cu_to_char(column)
+    }
+}
+
+/// Rows touched by `edits`, applied in order, as inclusive zero-based row
+/// ranges of the edited text. Rows below an edit shift with the rows it
+/// inserted or removed.
+pub fn changed_rows(edits: &[InputEdit]) -> Vec<(usize, usize)> {
+    let mut rows: Vec<(usize, usize)> = Vec::new();
+    for edit in edits {
+        let start = edit.start_position.row;
+        let old_end = edit.old_end_position.row;
+        let new_end = edit.new_end_position.row;
+        let shift = |row: usize| (row + new_end).saturating_sub(old_end);
+
+        let mut merged = (start, new_end);
+        rows.retain_mut(|range| {
+            if range.1 < start {
+                true
+            } else if range.0 > old_end {
+                *range = (shift(range.0), shift(range.1));
+                true
+            } else {
+                merged.0 = merged.0.min(range.0);
+                if range.1 > old_end {
+                    merged.1 = merged.1.max(shift(range.1));
+                }
+                false
+            }
+        });
+        rows.push(merged);
+    }
+    rows.sort_unstable();
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Range;
+
+    fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range {
+                start: Position::new(start.0, start.1),
+                end: Position::new(end.0, end.1),
+            }),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_apply_incremental_changes() {
+        let mut doc = OpenDocument::new("fn main() {\n    run();\n}\n", 1);
+
+        doc.apply_change(&edit((1, 4), (1, 7), "start"));
+        assert_eq!(doc.text(), "fn main() {\n    start();\n}\n");
+
+        // Multi-line replacement
+        doc.apply_change(&edit((0, 10), (2, 0), "{ stop(); "));
+        assert_eq!(doc.text(), "fn main() { stop(); }\n");
+
+        // Insertion at the end of the document
+        doc.apply_change(&edit((1, 0), (1, 0), "fn stop() {}\n"));
+        assert_eq!(doc.text(), "fn main() { stop(); }\nfn stop() {}\n");
+
+        // Full-text change
+        doc.apply_change(&TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "x".to_string(),
+        });
+        assert_eq!(doc.text(), "x");
+    }
+
+    #[test]
+    fn test_positions_are_utf16() {
+        // The emoji is two UTF-16 code units
+        let mut doc = OpenDocument::new("let s = \"😀\"; total;\n", 1);
+        doc.apply_change(&edit((0, 14), (0, 19), "sum"));
+        assert_eq!(doc.text(), "let s = \"😀\"; sum;\n");
+
+        // Columns past the end of a line are clamped to it, before the newline
+        doc.apply_change(&edit((0, 16), (0, 99), "!"));
+        assert_eq!(doc.text(), "let s = \"😀\"; su!\n");
+    }
+
+    #[test]
+    fn test_records_input_edits() {
+        let mut doc = OpenDocument::new("fn main() {\n    run();\n}\n", 1);
+        doc.apply_change(&edit((1, 4), (1, 7), "start"));
+        doc.apply_change(&edit((2, 1), (2, 1), "\nfn stop() {}"));
+
+        let edits = doc.take_edits();
+        assert_eq!(
+            edits[0],
+            InputEdit {
+                start_byte: 16,
+                old_end_byte: 19,
+                new_end_byte: 21,
+                start_position: Point { row: 1, column: 4 },
+                old_end_position: Point { row: 1, column: 7 },
+                new_end_position: Point { row: 1, column: 9 },
+            }
+        );
+        assert_eq!(edits[1].start_position, Point { row: 2, column: 1 });
+        assert_eq!(edits[1].new_end_position, Point { row: 3, column: 12 });
+        assert!(doc.take_edits().is_empty());
+
+        // Edits put back come before later ones
+        doc.apply_change(&edit((0, 0), (0, 0), "// x\n"));
+        doc.restore_edits(edits);
+        let edits = doc.take_edits();
+        assert_eq!(edits.len(), 3);
+        assert_eq!(edits[2].new_end_position, Point { row: 1, column: 0 });
+    }
+
+    #[test]
+    fn test_full_text_change_covers_document() {
+        let mut doc = OpenDocument::new("a\nb\n", 1);
+        doc.apply_change(&TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: "x\ny\nz".to_string(),
+        });
+        let edits = doc.take_edits();
+        assert_eq!(edits[0].start_byte, 0);
+        assert_eq!(edits[0].old_end_byte, 4);
+        assert_eq!(edits[0].new_end_position, Point { row: 2, column: 1 });
+        assert_eq!(changed_rows(&edits), vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_changed_rows_follow_later_edits() {
+        let mut doc = OpenDocument::new("a\nb\nc\nd\ne\n", 1);
+        // Edit row 3, then insert two rows above it
+        doc.apply_change(&edit((3, 0), (3, 1), "D"));
+        doc.apply_change(&edit((0, 1), (0, 1), "\n1\n2"));
+        assert_eq!(changed_rows(&doc.take_edits()), vec![(0, 2), (5, 5)]);
+
+        // Overlapping edits merge; removing rows pulls later ranges up
+        doc.apply_change(&edit((4, 0), (4, 1), "X"));
+        doc.apply_change(&edit((3, 0), (5, 0), ""));
+        doc.apply_change(&edit((5, 0), (5, 1), "Y"));
+        assert_eq!(changed_rows(&doc.take_edits()), vec![(3, 3), (5, 5)]);
+    }
+
+    #[test]
+    fn test_parse_tree_follows_edits() {
+        let grammar = tree_sitter_python::language();
+        let mut doc = OpenDocument::new("def a():\n    return 1\n\ndef b():\n    return 2\n", 1);
+        assert_eq!(doc.parse_tree(&grammar), None);
+
+        // Renaming keeps the structure of the tree
+        doc.apply_change(&edit((0, 4), (0, 5), "first"));
+        assert_eq!(doc.parse_tree(&grammar), Some(Vec::new()));
+        let root = doc.tree().unwrap().root_node();
+        let name = root.child(0).unwrap().child_by_field_name("name").unwrap();
+        assert_eq!(&doc.text()[name.byte_range()], "first");
+
+        // Opening a string on row 2 changes the syntax of the rows below it
+        doc.apply_change(&edit((2, 0), (2, 0), "x = \"\"\""));
+        let rows = doc.parse_tree(&grammar).unwrap();
+        assert_eq!(changed_rows(&doc.take_edits()), vec![(0, 0), (2, 2)]);
+        assert!(rows.iter().any(|&(start, end)| start <= 4 && end >= 4));
+    }
+}
@@ -895,34 +895,30 @@ fn get_edges(
     };
 
     let mut edges = Vec::new();
+    // Co-change edges record history, not a relationship in code
+    let mut push = |edge_id| {
+        if let Ok(edge) = graph.get_edge(edge_id) {
+            if !node_props::is_co_change(edge.edge_type, &edge.properties) {
+                edges.push((edge.source_id, edge.target_id, edge.edge_type));
+            }
+        }
+    };
     for neighbor_id in neighbors {
         let (source, target) = match direction {
             Direction::Outgoing => (node_id, neighbor_id),
             Direction::Incoming => (neighbor_id, node_id),
             Direction::Both => {
                 if let Ok(edge_ids) = graph.get_edges_between(node_id, neighbor_id) {
-                    for edge_id in edge_ids {
-                        if let Ok(edge) = graph.get_edge(edge_id) {
-                            edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                        }
-                    }
+                    edge_ids.into_iter().for_each(&mut push);
                 }
                 if let Ok(edge_ids) = graph.get_edges_between(neighbor_id, node_id) {
-                    for edge_id in edge_ids {
-                        if let Ok(edge) = graph.get_edge(edge_id) {
-                            edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                        }
-                    }
+                    edge_ids.into_iter().for_each(&mut push);
                 }
                 continue;
             }
         };
         if let Ok(edge_ids) = graph.get_edges_between(source, target) {
-            for edge_id in edge_ids {
-                if let Ok(edge) = graph.get_edge(edge_id) {
-                    edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                }
-            }
+            edge_ids.into_iter().for_each(&mut push);
         }
     }
     edges
@@ -1218,4 +1214,50 @@ mod tests {
         let desc = generate_usage_description("", "my_function", "my_function()");
         assert!(desc.contains("Usage of `my_function`"));
     }
+
+    #[test]
+    fn test_usage_examples_skip_co_change_edges() {
+        use codegraph::{PropertyMap, PropertyValue};
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let function = |name: &str, source: &str| {
+            PropertyMap::new()
+                .with("name", name)
+                .with("path", format!("/src/{name}.ts"))
+                .with("line_start", PropertyValue::Int(1))
+                .with("line_end", PropertyValue::Int(1))
+                .with("source", source)
+        };
+        let target = graph
+            .add_node(NodeType::Function, function("parse", "function parse() {}"))
+            .unwrap();
+        let caller = graph
+            .add_node(
+                NodeType::Function,
+                function("load", "function load() { parse() }"),
+            )
+            .unwrap();
+        let coupled = graph
+            .add_node(
+                NodeType::Function,
+                function("render", "function render() {}"),
+            )
+            .unwrap();
+        graph
+            .add_edge(caller, target, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+        graph
+            .add_edge(
+                coupled,
+                target,
+                EdgeType::References,
+                PropertyMap::new().with("kind", node_props::CO_CHANGE_KIND),
+            )
+            .unwrap();
+
+        let examples =
+            get_usage_examples(&graph, target, "parse", &mut TokenBudget::new(1000)).unwrap();
+        assert_eq!(examples.len(), 1);
+        assert!(examples[0].code.contains("load"));
+    }
 }
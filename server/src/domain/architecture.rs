@@ -0,0 +1,334 @@
+//! Architecture rule checking — transport-agnostic.
+//!
+//! Layers and the dependency directions allowed between them are declared in
+//! `<workspace>/.codegraph/architecture.json`:
+//!
+//! ```json
+//! {
+//!   "layers": [
+//!     { "name": "ui", "patterns": ["src/ui/**"] },
+//!     { "name": "services", "patterns": ["src/services/**"] },
+//!     { "name": "data", "patterns": ["src/data/**", "src/models/**"] }
+//!   ],
+//!   "rules": ["ui -> services -> data"]
+//! }
+//! ```
+//!
+//! A chain allows each layer to depend on the layers after it (`ui -> data` is
+//! fine, `data -> services` is not); with `"strict": true` only the next layer is
+//! allowed. Dependencies within a layer are always allowed, files outside every
+//! layer are ignored, and any other dependency between two layers is a violation.
+
+use crate::domain::node_props;
+use codegraph::{CodeGraph, EdgeType, NodeId, NodeType};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Edge types that count as a dependency of the source on the target.
+const DEPENDENCY_EDGES: &[EdgeType] = &[
+    EdgeType::Imports,
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::References,
+    EdgeType::Uses,
+    EdgeType::Instantiates,
+    EdgeType::Extends,
+    EdgeType::Implements,
+];
+
+// ============================================================
+// Configuration
+// ============================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LayerConfig {
+    pub name: String,
+    /// Globs matched against workspace-relative paths
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ArchitectureConfig {
+    pub layers: Vec<LayerConfig>,
+    /// Allowed dependency chains, e.g. "ui -> services -> data"
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// Only allow dependencies on the next layer of a chain
+    #[serde(default)]
+    pub strict: bool,
+}
+
+/// Location of the architecture config for a workspace.
+pub(crate) fn config_path(workspace: &Path) -> PathBuf {
+    workspace.join(".codegraph").join("architecture.json")
+}
+
+/// Load the workspace architecture config. Returns Ok(None) if there is none.
+pub(crate) fn load_config(workspace: &Path) -> Result<Option<ArchitectureConfig>, String> {
+    let path = config_path(workspace);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+/// Compiled layer patterns and allowed (from, to) layer pairs.
+pub(crate) struct ArchitectureRules {
+    layers: Vec<(String, GlobSet)>,
+    allowed: HashSet<(usize, usize)>,
+}
+
+impl ArchitectureRules {
+    pub(crate) fn from_config(config: &ArchitectureConfig) -> Result<Self, String> {
+        let mut layers = Vec::with_capacity(config.layers.len());
+        for layer in &config.layers {
+            if layers.iter().any(|(name, _)| name == &layer.name) {
+                return Err(format!("Duplicate layer '{}'", layer.name));
+            }
+            let mut builder = GlobSetBuilder::new();
+            for pattern in &layer.patterns {
+                let glob = Glob::new(pattern).map_err(|e| {
+                    format!("Invalid pattern '{pattern}' in layer '{}': {e}", layer.name)
+                })?;
+                builder.add(glob);
+            }
+            let set = builder
+                .build()
+                .map_err(|e| format!("Invalid patterns in layer '{}': {e}", layer.name))?;
+            layers.push((layer.name.clone(), set));
+        }
+
+        let mut allowed = HashSet::new();
+        for rule in &config.rules {
+            let chain = rule
+                .split("->")
+                .map(|name| {
+                    let name = name.trim();
+                    layers
+                        .iter()
+                        .position(|(layer, _)| layer == name)
+                        .ok_or_else(|| format!("Unknown layer '{name}' in rule '{rule}'"))
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
+            if chain.len() < 2 {
+                return Err(format!("Rule '{rule}' must name at least two layers"));
+            }
+            for (i, &from) in chain.iter().enumerate() {
+                let reachable = if config.strict {
+                    &chain[i + 1..(i + 2).min(chain.len())]
+                } else {
+                    &chain[i + 1..]
+                };
+                allowed.extend(reachable.iter().map(|&to| (from, to)));
+            }
+        }
+
+        Ok(Self { layers, allowed })
+    }
+
+    /// Index of the first layer whose patterns match a workspace-relative path.
+    pub(crate) fn layer_of(&self, relative_path: &str) -> Option<usize> {
+        self.layers
+            .iter()
+            .position(|(_, set)| set.is_match(relative_path))
+    }
+
+    pub(crate) fn is_allowed(&self, from: usize, to: usize) -> bool {
+        from == to || self.allowed.contains(&(from, to))
+    }
+
+    pub(crate) fn layer_name(&self, index: usize) -> &str {
+        &self.layers[index].0
+    }
+}
+
+// ============================================================
+// Check
+// ============================================================
+
+/// A dependency between two layers that no rule allows.
+#[derive(Debug, Clone)]
+pub(crate) struct LayerViolation {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub source_id: NodeId,
+    pub target_id: NodeId,
+    pub source_name: String,
+    pub target_name: String,
+    pub source_file: String,
+    pub target_file: String,
+    /// Line of the dependency in the source file (1-indexed)
+    pub line: u32,
+    pub edge_type: EdgeType,
+}
+
+pub(crate) struct ArchitectureCheckResult {
+    pub violations: Vec<LayerViolation>,
+    /// Dependencies between files that both belong to a layer
+    pub edges_checked: usize,
+    /// Layer name -> number of files assigned to it
+    pub layer_files: BTreeMap<String, usize>,
+}
+
+/// Check every dependency edge in the graph against the layering rules.
+pub(crate) fn check_architecture(
+    graph: &CodeGraph,
+    rules: &ArchitectureRules,
+    workspace: &Path,
+) -> ArchitectureCheckResult {
+    let layer_for = |node: &codegraph::Node| -> Option<usize> {
+        let path = node_props::path(node);
+        if path.is_empty() {
+            return None;
+        }
+        let relative = Path::new(path)
+            .strip_prefix(workspace)
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .unwrap_or_else(|_| path.to_string());
+        rules.layer_of(&relative)
+    };
+
+    let mut layer_files: BTreeMap<String, usize> = BTreeMap::new();
+    for (_, node) in graph.iter_nodes() {
+        if node.node_type == NodeType::CodeFile {
+            if let Some(layer) = layer_for(node) {
+                *layer_files
+                    .entry(rules.layer_name(layer).to_string())
+                    .or_default() += 1;
+            }
+        }
+    }
+
+    let mut violations = Vec::new();
+    let mut seen: HashSet<(NodeId, NodeId)> = HashSet::new();
+    let mut edges_checked = 0;
+
+    for (_, edge) in graph.iter_edges() {
+        if !DEPENDENCY_EDGES.contains(&edge.edge_type)
+            || node_props::is_co_change(edge.edge_type, &edge.properties)
+        {
+            continue;
+        }
+        let (Ok(source), Ok(target)) = (
+            graph.get_node(edge.source_id),
+            graph.get_node(edge.target_id),
+        ) else {
+            continue;
+        };
+        let (Some(from), Some(to)) = (layer_for(source), layer_for(target)) else {
+            continue;
+        };
+        if node_props::path(source) == node_props::path(target) {
+            continue;
+        }
+
+        edges_checked += 1;
+        if rules.is_allowed(from, to) || !seen.insert((edge.source_id, edge.target_id)) {
+            continue;
+        }
+
+        let line = edge
+            .properties
+            .get_int("line")
+            .map(|l| l as u32)
+            .unwrap_or_else(|| node_props::line_start(source))
+            .max(1);
+        violations.push(LayerViolation {
+            from_layer: rules.layer_name(from).to_string(),
+            to_layer: rules.layer_name(to).to_string(),
+            source_id: edge.source_id,
+            target_id: edge.target_id,
+            source_name: node_props::name(source).to_string(),
+            target_name: node_props::name(target).to_string(),
+            source_file: node_props::path(source).to_string(),
+            target_file: node_props::path(target).to_string(),
+            line,
+            edge_type: edge.edge_type,
+        });
+    }
+
+    violations.sort_by(|a, b| (&a.source_file, a.line).cmp(&(&b.source_file, b.line)));
+
+    ArchitectureCheckResult {
+        violations,
+        edges_checked,
+        layer_files,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::{PropertyMap, PropertyValue};
+
+    fn layered_config(strict: bool) -> ArchitectureConfig {
+        serde_json::from_value(serde_json::json!({
+            "layers": [
+                { "name": "ui", "patterns": ["src/ui/**"] },
+                { "name": "services", "patterns": ["src/services/**"] },
+                { "name": "data", "patterns": ["src/data/**"] }
+            ],
+            "rules": ["ui -> services -> data"],
+            "strict": strict
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rules_allow_downward_dependencies() {
+        let rules = ArchitectureRules::from_config(&layered_config(false)).unwrap();
+        assert_eq!(rules.layer_of("src/services/user.ts"), Some(1));
+        assert_eq!(rules.layer_of("scripts/build.ts"), None);
+        assert!(rules.is_allowed(0, 1));
+        assert!(rules.is_allowed(0, 2));
+        assert!(rules.is_allowed(2, 2));
+        assert!(!rules.is_allowed(2, 1));
+
+        let strict = ArchitectureRules::from_config(&layered_config(true)).unwrap();
+        assert!(strict.is_allowed(0, 1));
+        assert!(!strict.is_allowed(0, 2));
+
+        let mut config = layered_config(false);
+        config.rules = vec!["ui -> domain".to_string()];
+        assert!(ArchitectureRules::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_check_architecture_reports_upward_edges() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut add_function = |name: &str, path: &str| {
+            let mut props = PropertyMap::new();
+            props.insert("name".to_string(), PropertyValue::String(name.to_string()));
+            props.insert("path".to_string(), PropertyValue::String(path.to_string()));
+            props.insert("line_start".to_string(), PropertyValue::Int(3));
+            graph.add_node(NodeType::Function, props).unwrap()
+        };
+        let render = add_function("render", "/ws/src/ui/page.ts");
+        let load = add_function("loadUser", "/ws/src/services/user.ts");
+        let query = add_function("queryUser", "/ws/src/data/db.ts");
+        let format = add_function("formatUser", "/ws/src/ui/format.ts");
+        for (from, to) in [(render, load), (load, query), (query, format)] {
+            graph
+                .add_edge(from, to, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+        }
+
+        let rules = ArchitectureRules::from_config(&layered_config(false)).unwrap();
+        let result = check_architecture(&graph, &rules, Path::new("/ws"));
+        assert_eq!(result.edges_checked, 3);
+        assert_eq!(result.violations.len(), 1);
+        let violation = &result.violations[0];
+        assert_eq!(violation.from_layer, "data");
+        assert_eq!(violation.to_layer, "ui");
+        assert_eq!(violation.source_name, "queryUser");
+        assert_eq!(violation.line, 3);
+    }
+}
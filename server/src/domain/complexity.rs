@@ -1,11 +1,18 @@
 //! Shared complexity analysis — single source of truth for both LSP and MCP handlers.
 //!
-//! This module contains the domain logic for cyclomatic complexity analysis.
-//! It has no dependency on tower-lsp, MCP protocol types, or serde_json::Value.
+//! This module contains the domain logic for cyclomatic and cognitive complexity
+//! analysis. It has no dependency on tower-lsp, MCP protocol types, or
+//! serde_json::Value.
+//!
+//! Metrics are computed once at parse time from the file's tree-sitter syntax
+//! tree by [`record_complexity`] and stored as node properties (`complexity`, `complexity_cognitive`, `complexity_*`);
+//! the analysis functions only read them back.
 
 use super::node_props;
-use codegraph::{CodeGraph, NodeId, NodeType};
+use codegraph::{CodeGraph, NodeId, NodeType, PropertyMap, PropertyValue};
+use codegraph_parser_api::FileInfo;
 use serde::{Deserialize, Serialize};
+use tree_sitter::{Language, Node, Parser, Tree};
 
 // ==========================================
 // Shared Types
@@ -14,6 +21,8 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ComplexityDetails {
+    /// Cognitive complexity (control flow weighted by nesting)
+    pub complexity_cognitive: u32,
     /// Number of if/else/switch branches
     pub complexity_branches: u32,
     /// Number of for/while/loop constructs
@@ -91,6 +100,8 @@ pub(crate) fn get_complexity_from_node(node: &codegraph::Node) -> (u32, Complexi
             .and_then(|s| s.chars().next())
             .unwrap_or_else(|| complexity_grade(complexity));
         let details = ComplexityDetails {
+            complexity_cognitive: node.properties.get_int("complexity_cognitive").unwrap_or(0)
+                as u32,
             complexity_branches: node.properties.get_int("complexity_branches").unwrap_or(0) as u32,
             complexity_loops: node.properties.get_int("complexity_loops").unwrap_or(0) as u32,
             complexity_logical_ops: node
@@ -111,6 +122,7 @@ pub(crate) fn get_complexity_from_node(node: &codegraph::Node) -> (u32, Complexi
         (complexity, details, grade)
     } else {
         let details = ComplexityDetails {
+            complexity_cognitive: 0,
             complexity_branches: 0,
             complexity_loops: 0,
             complexity_logical_ops: 0,
@@ -218,3 +230,611 @@ pub(crate) fn analyze_file_complexity(
         recommendations,
     }
 }
+
+// ==========================================
+// Parse-Time Computation
+// ==========================================
+
+/// Compute complexity for the functions of a freshly parsed file and store it
+/// as node properties.
+///
+/// The source is parsed with the file's tree-sitter `grammar` once; each
+/// function is measured on its node of the syntax tree. Metrics a parser
+/// already recorded are kept; only missing ones are filled in. Returns the
+/// number of functions updated.
+pub(crate) fn record_complexity(
+    graph: &mut CodeGraph,
+    file_info: &FileInfo,
+    grammar: &Language,
+    source: &str,
+) -> usize {
+    let mut parser = Parser::new();
+    if parser.set_language(grammar).is_err() {
+        return 0;
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return 0;
+    };
+    let mut updates: Vec<(NodeId, PropertyMap)> = Vec::new();
+
+    for &node_id in &file_info.functions {
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        let has_cyclomatic = node.properties.get_int("complexity").is_some();
+        if has_cyclomatic && node.properties.get_int("complexity_cognitive").is_some() {
+            continue;
+        }
+        let start = node_props::line_start(node) as usize;
+        let end = node_props::line_end(node) as usize;
+        if start == 0 || start > end {
+            continue;
+        }
+        let Some(function) = function_node(&tree, start - 1, end - 1) else {
+            continue;
+        };
+
+        let metrics = tree_complexity(function, source.as_bytes());
+        let int = |value: u32| PropertyValue::Int(value as i64);
+        let mut props = PropertyMap::new().with("complexity_cognitive", int(metrics.cognitive));
+        if !has_cyclomatic {
+            props = props
+                .with("complexity", int(metrics.cyclomatic))
+                .with("complexity_branches", int(metrics.branches))
+                .with("complexity_loops", int(metrics.loops))
+                .with("complexity_logical_ops", int(metrics.logical_ops))
+                .with("complexity_nesting", int(metrics.nesting))
+                .with("complexity_exceptions", int(metrics.exceptions))
+                .with("complexity_early_returns", int(metrics.early_returns));
+        }
+        updates.push((node_id, props));
+    }
+
+    let count = updates.len();
+    for (node_id, props) in updates {
+        let _ = graph.update_node_properties(node_id, props);
+    }
+    count
+}
+
+/// Metrics computed from a function's syntax tree.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub(crate) struct SourceComplexity {
+    pub cyclomatic: u32,
+    pub cognitive: u32,
+    pub branches: u32,
+    pub loops: u32,
+    pub logical_ops: u32,
+    pub nesting: u32,
+    pub exceptions: u32,
+    pub early_returns: u32,
+}
+
+/// Node kinds of the grammars in use, by role. Names shared by several
+/// grammars are listed once.
+const FUNCTION_KINDS: &[&str] = &[
+    "function_item",
+    "function_definition",
+    "function_declaration",
+    "method_declaration",
+    "method_definition",
+    "method",
+    "singleton_method",
+    "constructor_declaration",
+    "secondary_constructor",
+    "init_declaration",
+    "local_function_statement",
+    "function_expression",
+    "arrow_function",
+    "func_literal",
+    "lambda_expression",
+    "closure_expression",
+];
+const IF_KINDS: &[&str] = &[
+    "if_statement",
+    "if_expression",
+    "if",
+    "unless",
+    "guard_statement",
+    "if_modifier",
+    "unless_modifier",
+];
+/// `elif` / `elsif` / `elseif` clauses, which are else-ifs of their own
+const ELSE_IF_KINDS: &[&str] = &["elif_clause", "elsif", "else_if_clause"];
+const ELSE_KINDS: &[&str] = &["else_clause", "else"];
+const LOOP_KINDS: &[&str] = &[
+    "for_statement",
+    "for_in_statement",
+    "for_expression",
+    "for_range_loop",
+    "enhanced_for_statement",
+    "foreach_statement",
+    "while_statement",
+    "while_expression",
+    "loop_expression",
+    "do_statement",
+    "do_while_statement",
+    "repeat_while_statement",
+    "for",
+    "while",
+    "until",
+    "while_modifier",
+    "until_modifier",
+];
+const SWITCH_KINDS: &[&str] = &[
+    "switch_statement",
+    "switch_expression",
+    "match_expression",
+    "match_statement",
+    "expression_switch_statement",
+    "type_switch_statement",
+    "select_statement",
+    "when_expression",
+    "case",
+];
+const ARM_KINDS: &[&str] = &[
+    "switch_case",
+    "case_statement",
+    "switch_block_statement_group",
+    "switch_rule",
+    "switch_section",
+    "switch_expression_arm",
+    "expression_case",
+    "type_case",
+    "communication_case",
+    "match_arm",
+    "case_clause",
+    "when",
+    "when_entry",
+    "switch_entry",
+    "match_conditional_expression",
+];
+const CATCH_KINDS: &[&str] = &["catch_clause", "except_clause", "rescue", "catch_block"];
+const TERNARY_KINDS: &[&str] = &[
+    "conditional_expression",
+    "ternary_expression",
+    "conditional",
+];
+const LAMBDA_KINDS: &[&str] = &[
+    "closure_expression",
+    "lambda_expression",
+    "lambda_literal",
+    "lambda",
+    "arrow_function",
+    "function_expression",
+    "func_literal",
+    "anonymous_function_creation_expression",
+    "do_block",
+];
+const LOGICAL_KINDS: &[&str] = &[
+    "binary_expression",
+    "boolean_operator",
+    "binary",
+    "conjunction_expression",
+    "disjunction_expression",
+];
+
+/// The syntax node of the function spanning rows `start_row..=end_row`: the
+/// outermost function-like node on exactly those rows, so a closure on the
+/// line of a one-line function is not taken for it, else the innermost
+/// function-like node covering them, else the innermost node.
+pub(crate) fn function_node(tree: &Tree, start_row: usize, end_row: usize) -> Option<Node<'_>> {
+    let covers =
+        |node: &Node| node.start_position().row <= start_row && node.end_position().row >= end_row;
+    let mut node = tree.root_node();
+    if !covers(&node) {
+        return None;
+    }
+    let mut exact = None;
+    let mut function = None;
+    loop {
+        if FUNCTION_KINDS.contains(&node.kind()) {
+            let on_rows =
+                node.start_position().row == start_row && node.end_position().row == end_row;
+            if on_rows && exact.is_none() {
+                exact = Some(node);
+            }
+            function = Some(node);
+        }
+        let mut cursor = node.walk();
+        let Some(child) = node.named_children(&mut cursor).find(|child| covers(child)) else {
+            break;
+        };
+        node = child;
+    }
+    exact.or(function).or(Some(node))
+}
+
+/// Compute cyclomatic and cognitive complexity of the function at `function`.
+///
+/// Cyclomatic complexity is 1 plus one per decision point: conditionals, case
+/// arms other than the default, loops, exception handlers, ternaries and
+/// short-circuit operators. Cognitive complexity follows the SonarSource
+/// definition: control structures cost 1 plus their nesting level, `else`
+/// branches cost 1, and each sequence of like logical operators costs 1.
+pub(crate) fn tree_complexity(function: Node, source: &[u8]) -> SourceComplexity {
+    let mut m = SourceComplexity::default();
+    let mut stack: Vec<(Node, u32)> = Vec::new();
+    let mut cursor = function.walk();
+    stack.extend(function.children(&mut cursor).map(|child| (child, 0)));
+
+    while let Some((node, nesting)) = stack.pop() {
+        let kind = node.kind();
+        let mut inner = nesting;
+        if !node.is_named() {
+            // Keywords and punctuation
+        } else if IF_KINDS.contains(&kind) {
+            m.branches += 1;
+            if is_else_if(node) {
+                m.cognitive += 1;
+            } else {
+                m.enter_structure(nesting);
+                inner += 1;
+            }
+        } else if ELSE_IF_KINDS.contains(&kind) {
+            m.branches += 1;
+            m.cognitive += 1;
+        } else if is_plain_else(node) {
+            m.cognitive += 1;
+        } else if LOOP_KINDS.contains(&kind) && !is_do_block(node) {
+            m.loops += 1;
+            m.enter_structure(nesting);
+            inner += 1;
+        } else if SWITCH_KINDS.contains(&kind) {
+            m.enter_structure(nesting);
+            inner += 1;
+        } else if ARM_KINDS.contains(&kind) {
+            if !is_default_arm(node, source) {
+                m.branches += 1;
+            }
+        } else if CATCH_KINDS.contains(&kind) {
+            m.exceptions += 1;
+            m.enter_structure(nesting);
+            inner += 1;
+        } else if TERNARY_KINDS.contains(&kind) {
+            m.branches += 1;
+            m.enter_structure(nesting);
+            inner += 1;
+        } else if LAMBDA_KINDS.contains(&kind) {
+            inner += 1;
+        } else if let Some(operator) = logical_operator(node) {
+            m.logical_ops += 1;
+            // Only the first of a run of like operators adds to cognitive
+            // complexity (`a && b && c` costs 1, `a && b || c` costs 2)
+            if node.parent().and_then(logical_operator) != Some(operator) {
+                m.cognitive += 1;
+            }
+        } else {
+            match jump(node) {
+                Some(Jump::Return) if !is_final_statement(node, function) => m.early_returns += 1,
+                Some(Jump::Loop) => m.early_returns += 1,
+                _ => {}
+            }
+        }
+
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor).map(|child| (child, inner)));
+    }
+
+    m.cyclomatic = 1 + m.branches + m.loops + m.exceptions + m.logical_ops;
+    m
+}
+
+impl SourceComplexity {
+    /// An `if`, loop, switch, handler or ternary: +1 plus its nesting level.
+    fn enter_structure(&mut self, nesting: u32) {
+        self.cognitive += 1 + nesting;
+        self.nesting = self.nesting.max(nesting + 1);
+    }
+}
+
+/// Whether an `if` node continues an `else`: `else if` in its many forms.
+fn is_else_if(node: Node) -> bool {
+    let after_else = |node: Node| node.prev_sibling().is_some_and(|s| s.kind() == "else");
+    if after_else(node) {
+        return true;
+    }
+    // `else_clause(if)`, or Kotlin's `else` body holding only the `if`
+    node.parent().is_some_and(|parent| {
+        parent.named_child_count() == 1
+            && (parent.kind() == "else_clause"
+                || (parent.kind() == "control_structure_body" && after_else(parent)))
+    })
+}
+
+/// Whether `node` is an `else` branch that is not an `else if`.
+fn is_plain_else(node: Node) -> bool {
+    let is_if = |node: Option<Node>| node.is_some_and(|n| IF_KINDS.contains(&n.kind()));
+    match node.kind() {
+        // Swift's `else` keyword is a named node; what follows is the branch
+        "else" if node.parent().is_some_and(|p| p.kind() == "guard_statement") => false,
+        "else" if node.child_count() == 0 => !is_if(node.next_named_sibling()),
+        // Not the `else` of a `case`, loop or `try`
+        kind if ELSE_KINDS.contains(&kind) => {
+            node.parent()
+                .is_some_and(|p| IF_KINDS.contains(&p.kind()) || ELSE_IF_KINDS.contains(&p.kind()))
+                && !(node.named_child_count() == 1 && is_if(node.named_child(0)))
+        }
+        // Java, Go, C#: the alternative of an `if` is the block itself
+        _ => {
+            let parent = node.parent();
+            let is_alternative = parent.is_some_and(|p| {
+                IF_KINDS.contains(&p.kind())
+                    && p.child_by_field_name("alternative")
+                        .is_some_and(|a| a.id() == node.id())
+            });
+            let kotlin_else = node.kind() == "control_structure_body"
+                && node.prev_sibling().is_some_and(|s| s.kind() == "else");
+            (is_alternative || kotlin_else)
+                && !is_if(Some(node))
+                && !ELSE_IF_KINDS.contains(&node.kind())
+                && !(node.named_child_count() == 1 && is_if(node.named_child(0)))
+        }
+    }
+}
+
+/// Swift's `do { } catch { }`, unlike C's `do { } while (x)`, is not a loop.
+fn is_do_block(node: Node) -> bool {
+    let mut cursor = node.walk();
+    let is_do = node.kind() == "do_statement"
+        && !node
+            .children(&mut cursor)
+            .any(|child| child.kind() == "while");
+    is_do
+}
+
+/// Whether a case arm is the default one, which adds no path.
+fn is_default_arm(node: Node, source: &[u8]) -> bool {
+    let text = |node: Option<Node>| node.and_then(|n| n.utf8_text(source).ok()).map(str::trim);
+    match node.kind() {
+        // `default:` has no value, `_ =>` / `case _:` match anything
+        "case_statement" => node.child_by_field_name("value").is_none(),
+        "switch_block_statement_group" | "switch_rule" => {
+            let label = node.named_child(0);
+            label.is_some_and(|l| l.named_child_count() == 0)
+        }
+        "switch_section" => node.named_child(0).is_some_and(|first| {
+            !first.kind().ends_with("pattern") && first.kind() != "case_switch_label"
+        }),
+        "switch_expression_arm" => node.named_child(0).is_some_and(|p| p.kind() == "discard"),
+        "match_arm" => text(node.child_by_field_name("pattern")) == Some("_"),
+        "case_clause" => text(node.named_child(0)) == Some("_"),
+        "when_entry" => node
+            .named_child(0)
+            .is_some_and(|c| c.kind() != "when_condition"),
+        "switch_entry" => node
+            .named_child(0)
+            .is_some_and(|c| c.kind() == "default_keyword"),
+        _ => false,
+    }
+}
+
+/// `&&` or `||` (`and`, `or`) if `node` is a short-circuit operation.
+fn logical_operator(node: Node) -> Option<&'static str> {
+    match node.kind() {
+        "conjunction_expression" => return Some("&&"),
+        "disjunction_expression" => return Some("||"),
+        kind if !LOGICAL_KINDS.contains(&kind) => return None,
+        _ => {}
+    }
+    let mut cursor = node.walk();
+    let operator = node
+        .children(&mut cursor)
+        .filter(|child| !child.is_named())
+        .find_map(|child| match child.kind() {
+            "&&" | "and" => Some("&&"),
+            "||" | "or" => Some("||"),
+            _ => None,
+        });
+    operator
+}
+
+enum Jump {
+    Return,
+    /// `break`, `continue`, `next`
+    Loop,
+}
+
+fn jump(node: Node) -> Option<Jump> {
+    let kind = match node.kind() {
+        // Kotlin and Swift: the keyword tells
+        "jump_expression" | "control_transfer_statement" => node.child(0)?.kind(),
+        kind => kind,
+    };
+    match kind {
+        "return_statement" | "return_expression" | "return" => Some(Jump::Return),
+        "break_statement"
+        | "break_expression"
+        | "break"
+        | "continue_statement"
+        | "continue_expression"
+        | "continue"
+        | "next" => Some(Jump::Loop),
+        _ => None,
+    }
+}
+
+/// Whether `node` is the last statement of `function`, reached without
+/// branching: a return there is the normal exit, not an early one.
+fn is_final_statement(node: Node, function: Node) -> bool {
+    let mut node = node;
+    while let Some(parent) = node.parent() {
+        let mut cursor = parent.walk();
+        let last = parent
+            .named_children(&mut cursor)
+            .filter(|child| !child.kind().contains("comment"))
+            .last();
+        if last.map(|last| last.id()) != Some(node.id()) {
+            return false;
+        }
+        if parent.id() == function.id() {
+            return true;
+        }
+        let kind = parent.kind();
+        if IF_KINDS.contains(&kind)
+            || LOOP_KINDS.contains(&kind)
+            || SWITCH_KINDS.contains(&kind)
+            || CATCH_KINDS.contains(&kind)
+            || ELSE_KINDS.contains(&kind)
+            || ARM_KINDS.contains(&kind)
+        {
+            return false;
+        }
+        node = parent;
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measure(grammar: Language, source: &str) -> SourceComplexity {
+        let mut parser = Parser::new();
+        parser.set_language(&grammar).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let last_row = source.lines().count() - 1;
+        let function = function_node(&tree, 0, last_row).unwrap();
+        tree_complexity(function, source.as_bytes())
+    }
+
+    #[test]
+    fn test_tree_complexity_rust() {
+        let source = r#"fn classify<'a>(items: &'a [i32], verbose: bool) -> u32 {
+    let mut total = 0;
+    // if this && that, else loop
+    let label = "while (x) { if y }";
+    for item in items {
+        if *item > 0 && verbose {
+            total += 1;
+        } else if *item < 0 {
+            return 0;
+        }
+    }
+    match total {
+        0 => 1,
+        1 => 2,
+        _ => 3,
+    }
+}"#;
+        assert_eq!(
+            measure(tree_sitter_rust::language(), source),
+            SourceComplexity {
+                cyclomatic: 7,
+                cognitive: 6,
+                branches: 4,
+                loops: 1,
+                logical_ops: 1,
+                nesting: 2,
+                exceptions: 0,
+                early_returns: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_tree_complexity_python() {
+        let source = r#"def load(path, retries):
+    """Load the file. if this or that"""
+    for attempt in range(retries):
+        try:
+            with open(path) as f:
+                return f.read()
+        except OSError:
+            if attempt == retries - 1 or not path:
+                raise
+    match = None
+    return match"#;
+        assert_eq!(
+            measure(tree_sitter_python::language(), source),
+            SourceComplexity {
+                cyclomatic: 5,
+                cognitive: 7,
+                branches: 1,
+                loops: 1,
+                logical_ops: 1,
+                nesting: 3,
+                exceptions: 1,
+                early_returns: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn test_tree_complexity_typescript() {
+        // `try` is not a control structure, so the loop inside it is not nested
+        let source = r#"function pick(a: number, b?: string): string {
+    switch (a) {
+        case 1:
+            return b ?? "one";
+        case 2:
+            return a > 1 ? "two" : "2";
+        default:
+            break;
+    }
+    try {
+        while (a-- > 0) { }
+    } catch (e) {
+        return "error";
+    }
+    return user?.name || "none";
+}"#;
+        assert_eq!(
+            measure(tree_sitter_typescript::language_typescript(), source),
+            SourceComplexity {
+                cyclomatic: 7,
+                cognitive: 6,
+                branches: 3,
+                loops: 1,
+                logical_ops: 1,
+                nesting: 2,
+                exceptions: 1,
+                early_returns: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_tree_complexity_languages() {
+        // if / else if / else with `&&`, a loop holding an `if`, a switch with
+        // two cases and a default, then a handler where the language has one
+        let cases = [
+            (
+                tree_sitter_java::language(),
+                "int f(int a) {\n if (a > 0 && b) { } else if (c) { } else { }\n for (int x : xs) { if (a) { break; } }\n switch (a) { case 1: g(); case 2: g(); default: g(); }\n try { g(); } catch (E e) { }\n return a;\n}",
+                (9, 9),
+            ),
+            (
+                tree_sitter_go::language(),
+                "func f(a int) int {\n if a > 0 && b { } else if c { } else { }\n for _, x := range xs { if a { break } }\n switch a { case 1: g()\n case 2: g()\n default: g() }\n return a\n}",
+                (8, 8),
+            ),
+            (
+                tree_sitter_kotlin::language(),
+                "fun f(a: Int): Int {\n if (a > 0 && b) { } else if (c) { } else { }\n for (x in xs) { if (a) { break } }\n when (a) { 1 -> g()\n 2 -> g()\n else -> g() }\n try { g() } catch (e: E) { }\n return a\n}",
+                (9, 9),
+            ),
+            (
+                tree_sitter_swift::language(),
+                "func f(a: Int) -> Int {\n if a > 0 && b { } else if c { } else { }\n for x in xs { if a { break } }\n switch a { case 1: g()\n case 2: g()\n default: g() }\n do { try g() } catch { }\n return a\n}",
+                (9, 9),
+            ),
+            (
+                tree_sitter_ruby::language(),
+                "def f(a)\n if a > 0 && b\n g\n elsif c\n g\n else\n g\n end\n while x\n break if a\n end\n case a\n when 1 then g\n when 2 then g\n else g\n end\n begin\n g\n rescue E\n g\n end\n return a\nend",
+                (9, 9),
+            ),
+        ];
+        let one_line =
+            "fn f(xs: &[i32]) -> bool { xs.iter().any(|x| *x > 0 && *x < 9) || xs.is_empty() }";
+        assert_eq!(
+            measure(tree_sitter_rust::language(), one_line).logical_ops,
+            2
+        );
+        for (grammar, source, expected) in cases {
+            let m = measure(grammar, source);
+            assert_eq!((m.cyclomatic, m.cognitive), expected, "{source}");
+            assert_eq!((m.loops, m.logical_ops, m.early_returns), (1, 1, 1));
+        }
+    }
+}
@@ -313,34 +313,30 @@ fn get_edges(
     };
 
     let mut edges = Vec::new();
+    // Co-change edges record history, not a relationship in code
+    let mut push = |edge_id| {
+        if let Ok(edge) = graph.get_edge(edge_id) {
+            if !node_props::is_co_change(edge.edge_type, &edge.properties) {
+                edges.push((edge.source_id, edge.target_id, edge.edge_type));
+            }
+        }
+    };
     for neighbor_id in neighbors {
         let (source, target) = match direction {
             Direction::Outgoing => (node_id, neighbor_id),
             Direction::Incoming => (neighbor_id, node_id),
             Direction::Both => {
                 if let Ok(edge_ids) = graph.get_edges_between(node_id, neighbor_id) {
-                    for edge_id in edge_ids {
-                        if let Ok(edge) = graph.get_edge(edge_id) {
-                            edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                        }
-                    }
+                    edge_ids.into_iter().for_each(&mut push);
                 }
                 if let Ok(edge_ids) = graph.get_edges_between(neighbor_id, node_id) {
-                    for edge_id in edge_ids {
-                        if let Ok(edge) = graph.get_edge(edge_id) {
-                            edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                        }
-                    }
+                    edge_ids.into_iter().for_each(&mut push);
                 }
                 continue;
             }
         };
         if let Ok(edge_ids) = graph.get_edges_between(source, target) {
-            for edge_id in edge_ids {
-                if let Ok(edge) = graph.get_edge(edge_id) {
-                    edges.push((edge.source_id, edge.target_id, edge.edge_type));
-                }
-            }
+            edge_ids.into_iter().for_each(&mut push);
         }
     }
     edges
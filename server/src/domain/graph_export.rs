@@ -0,0 +1,299 @@
+//! Subgraph export as Graphviz DOT or Mermaid text — transport-agnostic.
+//!
+//! Collects the neighborhood of one or more start nodes, optionally filtered
+//! by node and edge type, and renders it as text that can be pasted into
+//! docs or PR descriptions.
+
+use crate::domain::node_props;
+use codegraph::{CodeGraph, Direction, NodeId};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Write;
+
+/// Output format of an exported graph.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Dot,
+    Mermaid,
+}
+
+impl ExportFormat {
+    pub(crate) fn parse(format: &str) -> Option<Self> {
+        match format.to_lowercase().as_str() {
+            "dot" | "graphviz" => Some(Self::Dot),
+            "mermaid" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+/// What to collect around the start nodes.
+#[derive(Debug, Clone)]
+pub(crate) struct SubgraphOptions {
+    pub depth: usize,
+    pub direction: Direction,
+    /// Node types to keep (lowercase, e.g. "function"); empty keeps all.
+    /// Start nodes are always kept.
+    pub node_types: Vec<String>,
+    /// Edge types to follow (lowercase, e.g. "calls"); empty follows all.
+    pub edge_types: Vec<String>,
+    pub max_nodes: usize,
+}
+
+/// A node of an exported subgraph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExportNode {
+    pub id: NodeId,
+    pub label: String,
+    pub node_type: String,
+}
+
+/// An edge of an exported subgraph.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ExportEdge {
+    pub from: NodeId,
+    pub to: NodeId,
+    pub edge_type: String,
+}
+
+/// Result of `collect_subgraph`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Subgraph {
+    pub nodes: Vec<ExportNode>,
+    pub edges: Vec<ExportEdge>,
+    /// Whether `max_nodes` cut the traversal short.
+    pub truncated: bool,
+}
+
+/// Edges of the accepted types between `node_id` and its neighbors, as
+/// (neighbor, source, target, edge type).
+fn typed_edges(
+    graph: &CodeGraph,
+    node_id: NodeId,
+    direction: Direction,
+    edge_types: &[String],
+) -> Vec<(NodeId, NodeId, NodeId, String)> {
+    let mut neighbors = graph.get_neighbors(node_id, direction).unwrap_or_default();
+    neighbors.sort_unstable();
+    neighbors.dedup();
+
+    let mut edges = Vec::new();
+    for neighbor in neighbors {
+        let pairs = match direction {
+            Direction::Outgoing => vec![(node_id, neighbor)],
+            Direction::Incoming => vec![(neighbor, node_id)],
+            Direction::Both => vec![(node_id, neighbor), (neighbor, node_id)],
+        };
+        for (source, target) in pairs {
+            for edge_id in graph.get_edges_between(source, target).unwrap_or_default() {
+                let Ok(edge) = graph.get_edge(edge_id) else {
+                    continue;
+                };
+                let edge_type = format!("{:?}", edge.edge_type).to_lowercase();
+                if edge_types.is_empty() || edge_types.contains(&edge_type) {
+                    edges.push((neighbor, source, target, edge_type));
+                }
+            }
+        }
+    }
+    edges
+}
+
+/// Label and lowercase type of a node, if it exists.
+fn export_node(graph: &CodeGraph, node_id: NodeId) -> Option<ExportNode> {
+    let node = graph.get_node(node_id).ok()?;
+    Some(ExportNode {
+        id: node_id,
+        label: node_props::name(node).to_string(),
+        node_type: format!("{:?}", node.node_type).to_lowercase(),
+    })
+}
+
+/// Collect the subgraph within `options.depth` hops of `roots`, breadth-first.
+pub(crate) fn collect_subgraph(
+    graph: &CodeGraph,
+    roots: &[NodeId],
+    options: &SubgraphOptions,
+) -> Subgraph {
+    let node_types: Vec<String> = options
+        .node_types
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+    let edge_types: Vec<String> = options
+        .edge_types
+        .iter()
+        .map(|t| t.to_lowercase())
+        .collect();
+
+    let mut subgraph = Subgraph::default();
+    let mut included: HashSet<NodeId> = HashSet::new();
+    let mut queue: VecDeque<(NodeId, usize)> = VecDeque::new();
+    for &root in roots {
+        if included.contains(&root) {
+            continue;
+        }
+        if let Some(node) = export_node(graph, root) {
+            included.insert(root);
+            subgraph.nodes.push(node);
+            queue.push_back((root, 0));
+        }
+    }
+
+    let mut seen_edges: HashSet<(NodeId, NodeId, String)> = HashSet::new();
+    while let Some((node_id, depth)) = queue.pop_front() {
+        if depth >= options.depth {
+            continue;
+        }
+        for (neighbor, source, target, edge_type) in
+            typed_edges(graph, node_id, options.direction, &edge_types)
+        {
+            if !included.contains(&neighbor) {
+                let Some(node) = export_node(graph, neighbor) else {
+                    continue;
+                };
+                if !node_types.is_empty() && !node_types.contains(&node.node_type) {
+                    continue;
+                }
+                if subgraph.nodes.len() >= options.max_nodes {
+                    subgraph.truncated = true;
+                    continue;
+                }
+                included.insert(neighbor);
+                subgraph.nodes.push(node);
+                queue.push_back((neighbor, depth + 1));
+            }
+            if seen_edges.insert((source, target, edge_type.clone())) {
+                subgraph.edges.push(ExportEdge {
+                    from: source,
+                    to: target,
+                    edge_type,
+                });
+            }
+        }
+    }
+    subgraph
+}
+
+/// Escape a label for a double-quoted DOT string.
+fn dot_escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escape a label for a quoted Mermaid node or edge label.
+fn mermaid_escape(label: &str) -> String {
+    label
+        .replace('"', "#quot;")
+        .replace('<', "#lt;")
+        .replace('>', "#gt;")
+}
+
+/// Render a subgraph in `format`. Node ids are renumbered (`n0`, `n1`, …) in
+/// collection order so the output is stable across reindexes.
+pub(crate) fn render(subgraph: &Subgraph, format: ExportFormat, title: &str) -> String {
+    let ids: HashMap<NodeId, usize> = subgraph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(index, node)| (node.id, index))
+        .collect();
+
+    let mut out = String::new();
+    match format {
+        ExportFormat::Dot => {
+            let _ = writeln!(out, "digraph \"{}\" {{", dot_escape(title));
+            let _ = writeln!(out, "  rankdir=LR;");
+            let _ = writeln!(out, "  node [shape=box, fontname=\"Helvetica\"];");
+            for (index, node) in subgraph.nodes.iter().enumerate() {
+                let _ = writeln!(
+                    out,
+                    "  n{index} [label=\"{}\", tooltip=\"{}\"];",
+                    dot_escape(&node.label),
+                    node.node_type
+                );
+            }
+            for edge in &subgraph.edges {
+                let (Some(from), Some(to)) = (ids.get(&edge.from), ids.get(&edge.to)) else {
+                    continue;
+                };
+                let _ = writeln!(out, "  n{from} -> n{to} [label=\"{}\"];", edge.edge_type);
+            }
+            out.push_str("}\n");
+        }
+        ExportFormat::Mermaid => {
+            let _ = writeln!(out, "---\ntitle: {}\n---", mermaid_escape(title));
+            out.push_str("flowchart LR\n");
+            for (index, node) in subgraph.nodes.iter().enumerate() {
+                let _ = writeln!(out, "  n{index}[\"{}\"]", mermaid_escape(&node.label));
+            }
+            for edge in &subgraph.edges {
+                let (Some(from), Some(to)) = (ids.get(&edge.from), ids.get(&edge.to)) else {
+                    continue;
+                };
+                let _ = writeln!(out, "  n{from} -->|{}| n{to}", edge.edge_type);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::{EdgeType, NodeType, PropertyMap};
+
+    #[test]
+    fn test_collect_and_render_subgraph() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut add = |node_type: NodeType, name: &str| {
+            graph
+                .add_node(node_type, PropertyMap::new().with("name", name))
+                .unwrap()
+        };
+        let file = add(NodeType::CodeFile, "main.rs");
+        let main = add(NodeType::Function, "main");
+        let parse = add(NodeType::Function, "parse \"args\"");
+        let run = add(NodeType::Function, "run");
+        graph
+            .add_edge(file, main, EdgeType::Contains, PropertyMap::new())
+            .unwrap();
+        graph
+            .add_edge(main, parse, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+        graph
+            .add_edge(parse, run, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+
+        let options = SubgraphOptions {
+            depth: 1,
+            direction: Direction::Both,
+            node_types: vec![],
+            edge_types: vec!["Calls".to_string()],
+            max_nodes: 100,
+        };
+        let subgraph = collect_subgraph(&graph, &[main], &options);
+        assert_eq!(subgraph.nodes.len(), 2);
+        assert_eq!(subgraph.edges.len(), 1);
+        assert!(!subgraph.truncated);
+
+        let dot = render(&subgraph, ExportFormat::Dot, "main");
+        assert!(dot.starts_with("digraph \"main\" {"));
+        assert!(dot.contains("n1 [label=\"parse \\\"args\\\"\", tooltip=\"function\"];"));
+        assert!(dot.contains("n0 -> n1 [label=\"calls\"];"));
+
+        let mermaid = render(&subgraph, ExportFormat::Mermaid, "main");
+        assert!(mermaid.contains("flowchart LR\n"));
+        assert!(mermaid.contains("n1[\"parse #quot;args#quot;\"]"));
+        assert!(mermaid.contains("n0 -->|calls| n1"));
+
+        // Deeper, all edge types, capped
+        let options = SubgraphOptions {
+            depth: 3,
+            edge_types: vec![],
+            max_nodes: 3,
+            ..options
+        };
+        let subgraph = collect_subgraph(&graph, &[main], &options);
+        assert_eq!(subgraph.nodes.len(), 3);
+        assert!(subgraph.truncated);
+    }
+}
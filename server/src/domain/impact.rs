@@ -2,15 +2,27 @@
 //!
 //! Extracts analyze_impact from MCP server.
 
-use crate::ai_query::QueryEngine;
+use crate::ai_query::{EntryType, QueryEngine};
 use crate::domain::node_props;
 use codegraph::{
     CodeGraph, Direction, EdgeType, NamespacedBackend, NodeId, RocksDBBackend, StorageBackend,
 };
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{BTreeSet, HashSet, VecDeque};
 use tokio::sync::RwLock;
 
+/// How many caller levels to follow when looking for tests that exercise a change.
+const TEST_SELECTION_DEPTH: u32 = 10;
+
+/// Edge types through which a test can (transitively) exercise the changed symbol.
+const TEST_REACH_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::References,
+    EdgeType::Uses,
+    EdgeType::Instantiates,
+];
+
 // ============================================================
 // Response Types
 // ============================================================
@@ -47,6 +59,17 @@ pub(crate) struct IndirectImpactItem {
     pub severity: String,
 }
 
+/// A test that transitively depends on the changed symbol.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AffectedTestItem {
+    pub node_id: String,
+    pub name: String,
+    pub path: String,
+    pub line_start: u32,
+    /// Caller levels between the test and the changed symbol (1 = direct).
+    pub depth: u32,
+}
+
 /// A consumer of the changed symbol found in another indexed project.
 #[derive(Debug, Clone, Serialize)]
 pub(crate) struct CrossProjectImpact {
@@ -69,6 +92,10 @@ pub(crate) struct ImpactResult {
     /// Consumers in other indexed projects.
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub cross_project_impacts: Vec<CrossProjectImpact>,
+    /// Tests whose transitive callees include the changed symbol, nearest first.
+    pub affected_tests: Vec<AffectedTestItem>,
+    /// Distinct files of `affected_tests`, for change-based test selection.
+    pub test_files: Vec<String>,
     pub total_impacted: usize,
     pub direct_impacted: usize,
     pub risk_level: String,
@@ -91,7 +118,8 @@ pub(crate) struct ImpactResult {
 ///
 /// Computes direct impact from all incoming edge types (not just calls), then BFS
 /// 2 levels from each direct impact for indirect impact. Uses `query_engine.get_callers()`
-/// for risk-level calculation (broader caller count at depth 3).
+/// for risk-level calculation (broader caller count at depth 3). Affected tests are the
+/// TestEntry nodes among the transitive callers (up to `TEST_SELECTION_DEPTH` levels).
 pub(crate) async fn analyze_impact(
     graph: &RwLock<CodeGraph>,
     query_engine: &QueryEngine,
@@ -125,6 +153,14 @@ pub(crate) async fn analyze_impact(
                         if let Ok(edge) = g.get_edge(edge_id) {
                             let impact_type = match edge.edge_type {
                                 EdgeType::Calls => "caller",
+                                EdgeType::References
+                                    if node_props::is_co_change(
+                                        edge.edge_type,
+                                        &edge.properties,
+                                    ) =>
+                                {
+                                    "co_change"
+                                }
                                 EdgeType::References => "reference",
                                 EdgeType::Extends => "subclass",
                                 EdgeType::Implements => "implementation",
@@ -204,6 +240,18 @@ pub(crate) async fn analyze_impact(
         }
     }
 
+    let affected_tests = {
+        let g = graph.read().await;
+        find_affected_tests(&g, query_engine, start_node)
+    };
+    let test_files: Vec<String> = affected_tests
+        .iter()
+        .filter(|t| !t.path.is_empty())
+        .map(|t| t.path.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+
     let impacted: Vec<ImpactedSymbol> = direct_impacts.into_iter().map(|(_, sym)| sym).collect();
 
     let direct_impacted = impacted.len();
@@ -247,6 +295,8 @@ pub(crate) async fn analyze_impact(
         impacted,
         indirect_impacted,
         cross_project_impacts,
+        affected_tests,
+        test_files,
         total_impacted,
         direct_impacted,
         risk_level: risk_level.to_string(),
@@ -258,6 +308,67 @@ pub(crate) async fn analyze_impact(
     }
 }
 
+/// Walk transitive callers of `start_node` and collect the ones that are tests.
+///
+/// The walk continues through tests, so tests that reach the change through a
+/// helper in a test file are found as well.
+fn find_affected_tests(
+    graph: &CodeGraph,
+    query_engine: &QueryEngine,
+    start_node: NodeId,
+) -> Vec<AffectedTestItem> {
+    let mut tests = Vec::new();
+    let mut visited: HashSet<NodeId> = HashSet::from([start_node]);
+    let mut queue: VecDeque<(NodeId, u32)> = VecDeque::from([(start_node, 0)]);
+
+    while let Some((node_id, depth)) = queue.pop_front() {
+        if depth >= TEST_SELECTION_DEPTH {
+            continue;
+        }
+        let Ok(callers) = graph.get_neighbors(node_id, Direction::Incoming) else {
+            continue;
+        };
+        for caller_id in callers {
+            if visited.contains(&caller_id) {
+                continue;
+            }
+            let depends = graph
+                .get_edges_between(caller_id, node_id)
+                .unwrap_or_default()
+                .iter()
+                .any(|&eid| {
+                    graph.get_edge(eid).is_ok_and(|e| {
+                        TEST_REACH_EDGES.contains(&e.edge_type)
+                            && !node_props::is_co_change(e.edge_type, &e.properties)
+                    })
+                });
+            if !depends {
+                continue;
+            }
+            visited.insert(caller_id);
+            queue.push_back((caller_id, depth + 1));
+
+            let Ok(node) = graph.get_node(caller_id) else {
+                continue;
+            };
+            let is_test = node.node_type == codegraph::NodeType::Function
+                && query_engine.detect_entry_type(node, node_props::name(node))
+                    == Some(EntryType::TestEntry);
+            if is_test {
+                tests.push(AffectedTestItem {
+                    node_id: caller_id.to_string(),
+                    name: node_props::name(node).to_string(),
+                    path: node_props::path(node).to_string(),
+                    line_start: node_props::line_start(node),
+                    depth: depth + 1,
+                });
+            }
+        }
+    }
+
+    tests
+}
+
 /// Search other indexed projects for functions that call or reference the given symbol.
 ///
 /// Opens each project's graph from the shared RocksDB (excluding the current project),
@@ -5,6 +5,7 @@
 //! or MCP protocol types.
 
 pub(crate) mod ai_context;
+pub(crate) mod architecture;
 pub(crate) mod call_graph;
 pub(crate) mod callers;
 pub(crate) mod complexity;
@@ -12,10 +13,14 @@ pub(crate) mod coupling;
 pub(crate) mod curated_context;
 pub(crate) mod dependency_graph;
 pub(crate) mod edit_context;
+pub(crate) mod graph_export;
 pub(crate) mod impact;
+pub(crate) mod monikers;
 pub(crate) mod node_props;
 pub(crate) mod node_resolution;
 pub(crate) mod related_tests;
+pub(crate) mod semantic_tokens;
 pub(crate) mod source_code;
+pub(crate) mod source_text;
 pub(crate) mod symbol_info;
 pub(crate) mod unused_code;
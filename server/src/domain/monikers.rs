@@ -0,0 +1,204 @@
+//! Stable symbol monikers.
+//!
+//! NodeIds are assigned at parse time and change on every reindex. A moniker
+//! names a symbol by where it is and what it looks like instead:
+//!
+//! ```text
+//! src/models/user.ts#User.save~3f9a1c2e
+//! ```
+//!
+//! that is, the file path relative to the repository root, the name qualified
+//! by its class or trait, and a hash of the normalized signature to tell
+//! overloads apart. Monikers are recorded at parse time as the `moniker`
+//! property, so they can be looked up again after a reindex.
+
+use super::node_props;
+use codegraph::{CodeGraph, Direction, EdgeType, Node, NodeId, NodeType, PropertyMap};
+use codegraph_parser_api::FileInfo;
+use std::path::{Path, PathBuf};
+
+/// Moniker scheme reported to LSP clients.
+pub(crate) const MONIKER_SCHEME: &str = "codegraph";
+
+/// Build a moniker from its parts.
+pub(crate) fn moniker(
+    relative_path: &str,
+    qualified_name: &str,
+    signature: Option<&str>,
+) -> String {
+    let mut moniker = format!("{}#{qualified_name}", relative_path.replace('\\', "/"));
+    let signature = signature
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
+        .filter(|s| !s.is_empty());
+    if let Some(signature) = signature {
+        // Monikers are persisted in memory links, so the hash must be stable
+        let hash = crate::ai_query::content_hash(signature.as_bytes());
+        moniker.push_str(&format!("~{:08x}", hash & 0xFFFF_FFFF));
+    }
+    moniker
+}
+
+/// The repository (or, failing that, filesystem) root `path` is relative to:
+/// the nearest ancestor with a `.git` entry.
+fn repository_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Name of a node qualified by the class, interface or trait containing it.
+fn qualified_name(graph: &CodeGraph, node_id: NodeId, node: &Node) -> String {
+    let name = node_props::name(node);
+    let container = graph
+        .get_neighbors(node_id, Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|parent| {
+            let parent_node = graph.get_node(parent).ok()?;
+            let is_container = matches!(
+                parent_node.node_type,
+                NodeType::Class | NodeType::Interface | NodeType::Trait
+            ) && graph
+                .get_edges_between(parent, node_id)
+                .unwrap_or_default()
+                .into_iter()
+                .any(|eid| {
+                    graph
+                        .get_edge(eid)
+                        .is_ok_and(|e| e.edge_type == EdgeType::Contains)
+                });
+            is_container.then(|| node_props::name(parent_node).to_string())
+        });
+    match container {
+        Some(container) if !container.is_empty() => format!("{container}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+/// Record a `moniker` property on the functions, classes and traits of a
+/// freshly parsed file. Returns the number of nodes updated.
+pub(crate) fn record_monikers(graph: &mut CodeGraph, file_info: &FileInfo, path: &Path) -> usize {
+    let relative = repository_root(path)
+        .and_then(|root| path.strip_prefix(root).ok().map(Path::to_path_buf))
+        .unwrap_or_else(|| path.to_path_buf());
+    let relative = relative.to_string_lossy();
+
+    let mut updates: Vec<(NodeId, String)> = Vec::new();
+    for &node_id in file_info
+        .functions
+        .iter()
+        .chain(file_info.classes.iter())
+        .chain(file_info.traits.iter())
+    {
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        if node_props::name(node).is_empty() {
+            continue;
+        }
+        let name = qualified_name(graph, node_id, node);
+        let signature = node.properties.get_string("signature");
+        updates.push((node_id, moniker(&relative, &name, signature)));
+    }
+
+    let count = updates.len();
+    for (node_id, moniker) in updates {
+        let _ = graph.update_node_properties(node_id, PropertyMap::new().with("moniker", moniker));
+    }
+    count
+}
+
+/// Keys a memory code link may use for a node: its NodeId and, when recorded,
+/// its moniker. Links by moniker survive reindexing.
+pub(crate) fn link_keys(node_id: NodeId, node: &Node) -> Vec<String> {
+    std::iter::once(node_id.to_string())
+        .chain(node.properties.get_string("moniker").map(String::from))
+        .collect()
+}
+
+/// Resolve a symbol reference: a NodeId, or a moniker of a node in the graph.
+pub(crate) fn resolve_node_ref(graph: &CodeGraph, reference: &str) -> Option<NodeId> {
+    if let Ok(node_id) = reference.parse::<NodeId>() {
+        return graph.get_node(node_id).is_ok().then_some(node_id);
+    }
+    graph
+        .query()
+        .property("moniker", reference)
+        .execute()
+        .ok()?
+        .into_iter()
+        .next()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::PropertyValue;
+
+    #[test]
+    fn test_moniker_is_stable_and_ignores_whitespace() {
+        let a = moniker("src/user.ts", "User.save", Some("save(force: boolean)"));
+        let b = moniker("src/user.ts", "User.save", Some("save(force:  boolean)"));
+        assert_eq!(a, b);
+        // Persisted monikers must not change between builds
+        assert_eq!(a, "src/user.ts#User.save~d7ce3006");
+
+        // Overloads differ by signature
+        let c = moniker("src/user.ts", "User.save", Some("save()"));
+        assert_ne!(a, c);
+        assert_eq!(moniker("src\\lib.rs", "run", None), "src/lib.rs#run");
+    }
+
+    #[test]
+    fn test_record_and_resolve_monikers() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let props = |name: &str| {
+            PropertyMap::new()
+                .with("name", name)
+                .with("path", "/nowhere/src/shape.py")
+        };
+        let class = graph.add_node(NodeType::Class, props("Shape")).unwrap();
+        let method = graph
+            .add_node(
+                NodeType::Function,
+                props("area").with("signature", PropertyValue::String("def area(self)".into())),
+            )
+            .unwrap();
+        graph
+            .add_edge(class, method, EdgeType::Contains, PropertyMap::new())
+            .unwrap();
+
+        let file_info = FileInfo {
+            file_path: "/nowhere/src/shape.py".into(),
+            file_id: 1,
+            functions: vec![method],
+            classes: vec![class],
+            traits: vec![],
+            imports: vec![],
+            parse_time: std::time::Duration::from_millis(0),
+            line_count: 0,
+            byte_count: 0,
+        };
+        assert_eq!(
+            record_monikers(&mut graph, &file_info, Path::new("/nowhere/src/shape.py")),
+            2
+        );
+
+        let method_moniker = graph
+            .get_node(method)
+            .unwrap()
+            .properties
+            .get_string("moniker")
+            .unwrap()
+            .to_string();
+        assert!(method_moniker.starts_with("/nowhere/src/shape.py#Shape.area~"));
+        assert_eq!(resolve_node_ref(&graph, &method_moniker), Some(method));
+        assert_eq!(
+            resolve_node_ref(&graph, "/nowhere/src/shape.py#Shape"),
+            Some(class)
+        );
+        assert_eq!(resolve_node_ref(&graph, &class.to_string()), Some(class));
+        assert_eq!(resolve_node_ref(&graph, "missing#symbol"), None);
+    }
+}
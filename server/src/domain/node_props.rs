@@ -3,7 +3,29 @@
 //! These functions eliminate scattered get_int/get_string fallback chains
 //! by providing a single canonical accessor for each property.
 //!
-use codegraph::{Node, PropertyMap};
+use codegraph::{EdgeType, Node, PropertyMap};
+
+// Edge accessors
+
+/// `kind` of the `References` edges that link files changing together in git
+/// history, rather than a reference in code.
+pub(crate) const CO_CHANGE_KIND: &str = "co_change";
+
+/// Whether an edge links co-changing files (see [`CO_CHANGE_KIND`]).
+pub(crate) fn is_co_change(edge_type: EdgeType, props: &PropertyMap) -> bool {
+    edge_type == EdgeType::References && props.get_string("kind") == Some(CO_CHANGE_KIND)
+}
+
+// Node kinds
+
+/// `kind` of the nodes that record TODO/FIXME/HACK/SAFETY comments, which are
+/// not symbols.
+pub(crate) const TODO_COMMENT_KIND: &str = "todo_comment";
+
+/// Whether a node records a TODO comment (see [`TODO_COMMENT_KIND`]).
+pub(crate) fn is_todo_comment(node: &Node) -> bool {
+    node.properties.get_string("kind") == Some(TODO_COMMENT_KIND)
+}
 
 // Line accessors (from Node)
 
@@ -106,3 +128,19 @@ pub(crate) fn is_public(node: &Node) -> bool {
         .or_else(|| node.properties.get_bool("exported"))
         .unwrap_or_else(|| matches!(visibility(node), "public" | "pub"))
 }
+
+/// Whether the node is deprecated: a `deprecated` flag set by the parser, or a
+/// recorded `@Deprecated` / `#[deprecated]` / `[Obsolete]` annotation.
+pub(crate) fn is_deprecated(node: &Node) -> bool {
+    node.properties.get_bool("deprecated").unwrap_or(false)
+        || node
+            .properties
+            .get_string_list_compat("attributes")
+            .is_some_and(|attrs| {
+                attrs.iter().any(|attr| {
+                    ["deprecated", "Deprecated", "Obsolete"]
+                        .iter()
+                        .any(|name| crate::annotations::annotation_matches(attr, name))
+                })
+            })
+}
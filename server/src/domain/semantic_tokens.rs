@@ -0,0 +1,331 @@
+//! Graph-aware semantic highlighting.
+//!
+//! Classifies the identifiers of a file by the graph symbol they name. Token
+//! types come from node types (function, class, interface, ...); modifiers come
+//! from node properties and edges: `public`, `deprecated`, `unused` and
+//! `declaration` at the symbol's own definition. Identifiers are matched by
+//! name against the symbols defined in the file and the symbols those use
+//! elsewhere. Comments and string literals are skipped.
+
+use super::node_props;
+use super::source_text::{is_word_char, mask_comments_and_strings, Syntax};
+use super::unused_code::{is_test_node, is_usage_edge};
+use codegraph::{CodeGraph, Direction, Node, NodeId, NodeType};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Token types in legend order; a token's type is an index into this list.
+pub(crate) const TOKEN_TYPES: &[&str] = &[
+    "namespace",
+    "class",
+    "interface",
+    "type",
+    "function",
+    "variable",
+];
+
+/// Token modifiers in legend order; a token's modifiers are a bit set over it.
+pub(crate) const TOKEN_MODIFIERS: &[&str] = &["declaration", "public", "deprecated", "unused"];
+
+const MODIFIER_DECLARATION: u32 = 1 << 0;
+const MODIFIER_PUBLIC: u32 = 1 << 1;
+const MODIFIER_DEPRECATED: u32 = 1 << 2;
+const MODIFIER_UNUSED: u32 = 1 << 3;
+
+/// A classified identifier (0-indexed line, UTF-16 columns).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ClassifiedToken {
+    pub line: u32,
+    pub start: u32,
+    pub length: u32,
+    pub token_type: u32,
+    pub modifiers: u32,
+}
+
+struct Classification {
+    token_type: u32,
+    modifiers: u32,
+    /// Lines (1-indexed) where a symbol with this name is defined in the file
+    declared_at: Vec<u32>,
+}
+
+fn token_type(node_type: NodeType) -> Option<u32> {
+    let name = match node_type {
+        NodeType::Module => "namespace",
+        NodeType::Class => "class",
+        NodeType::Interface | NodeType::Trait => "interface",
+        NodeType::Type => "type",
+        NodeType::Function => "function",
+        NodeType::Variable => "variable",
+        _ => return None,
+    };
+    TOKEN_TYPES
+        .iter()
+        .position(|t| *t == name)
+        .map(|i| i as u32)
+}
+
+/// Whether `to` has an incoming usage edge (call, reference, import, ...) from `from`.
+fn is_used_by(graph: &CodeGraph, from: NodeId, to: NodeId) -> bool {
+    graph
+        .get_edges_between(from, to)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|eid| graph.get_edge(eid).ok())
+        .any(|e| is_usage_edge(e.edge_type, &e.properties))
+}
+
+/// Modifiers of a symbol defined in the file being highlighted.
+///
+/// Only private functions and classes are marked unused: variables rarely get
+/// usage edges, and tests and `main` are called by frameworks, not code.
+fn symbol_modifiers(graph: &CodeGraph, node_id: NodeId, node: &Node) -> u32 {
+    let mut modifiers = 0;
+    let is_public = node_props::is_public(node);
+    if is_public {
+        modifiers |= MODIFIER_PUBLIC;
+    }
+    if node_props::is_deprecated(node) {
+        modifiers |= MODIFIER_DEPRECATED;
+    }
+    let can_be_unused = matches!(node.node_type, NodeType::Function | NodeType::Class)
+        && !is_public
+        && node_props::name(node) != "main"
+        && !is_test_node(node);
+    if can_be_unused {
+        let used = graph
+            .get_neighbors(node_id, Direction::Incoming)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|from| is_used_by(graph, from, node_id));
+        if !used {
+            modifiers |= MODIFIER_UNUSED;
+        }
+    }
+    modifiers
+}
+
+/// Classify every identifier in `text` (the content of `path`) that names a
+/// symbol defined in the file or a symbol the file's symbols use.
+///
+/// Tokens are returned in document order. When several symbols share a name,
+/// symbols defined in the file take precedence.
+pub(crate) fn compute_semantic_tokens(
+    graph: &CodeGraph,
+    path: &Path,
+    text: &str,
+) -> Vec<ClassifiedToken> {
+    let path_str = path.to_string_lossy();
+    let file_nodes = graph
+        .query()
+        .property("path", path_str.as_ref())
+        .execute()
+        .unwrap_or_default();
+
+    let mut symbols: HashMap<String, Classification> = HashMap::new();
+    let mut used: Vec<NodeId> = Vec::new();
+    for &node_id in &file_nodes {
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        used.extend(
+            graph
+                .get_neighbors(node_id, Direction::Outgoing)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&target| is_used_by(graph, node_id, target)),
+        );
+
+        let (Some(token_type), name) = (token_type(node.node_type), node_props::name(node)) else {
+            continue;
+        };
+        if name.is_empty() {
+            continue;
+        }
+        let line = node_props::line_start(node);
+        symbols
+            .entry(name.to_string())
+            .and_modify(|c| c.declared_at.push(line))
+            .or_insert_with(|| Classification {
+                token_type,
+                modifiers: symbol_modifiers(graph, node_id, node),
+                declared_at: vec![line],
+            });
+    }
+
+    for target in used {
+        let Ok(node) = graph.get_node(target) else {
+            continue;
+        };
+        let (Some(token_type), name) = (token_type(node.node_type), node_props::name(node)) else {
+            continue;
+        };
+        if name.is_empty() || symbols.contains_key(name) {
+            continue;
+        }
+        let mut modifiers = 0;
+        if node_props::is_public(node) {
+            modifiers |= MODIFIER_PUBLIC;
+        }
+        if node_props::is_deprecated(node) {
+            modifiers |= MODIFIER_DEPRECATED;
+        }
+        symbols.insert(
+            name.to_string(),
+            Classification {
+                token_type,
+                modifiers,
+                declared_at: Vec::new(),
+            },
+        );
+    }
+    if symbols.is_empty() {
+        return Vec::new();
+    }
+
+    let masked = match Syntax::from_path(path) {
+        Some(syntax) => mask_comments_and_strings(text, syntax),
+        None => text.to_string(),
+    };
+
+    let mut tokens = Vec::new();
+    let mut declared: HashSet<(u32, &str)> = HashSet::new();
+    for (line_idx, (masked_line, line)) in masked.lines().zip(text.lines()).enumerate() {
+        let line_number = line_idx as u32 + 1;
+        // Masking replaces characters one for one, so original characters give
+        // the UTF-16 width of each position
+        let original: Vec<char> = line.chars().collect();
+        let width = |i: usize| original.get(i).map_or(1, |c| c.len_utf16() as u32);
+        let chars: Vec<char> = masked_line.chars().collect();
+
+        let mut i = 0;
+        let mut column = 0;
+        while i < chars.len() {
+            if !is_word_char(chars[i]) {
+                column += width(i);
+                i += 1;
+                continue;
+            }
+            let (word_start, start) = (i, column);
+            while i < chars.len() && is_word_char(chars[i]) {
+                column += width(i);
+                i += 1;
+            }
+            let word: String = chars[word_start..i].iter().collect();
+            let Some((name, class)) = symbols.get_key_value(&word) else {
+                continue;
+            };
+
+            let mut modifiers = class.modifiers;
+            if class.declared_at.contains(&line_number)
+                && declared.insert((line_number, name.as_str()))
+            {
+                modifiers |= MODIFIER_DECLARATION;
+            }
+            tokens.push(ClassifiedToken {
+                line: line_idx as u32,
+                start,
+                length: column - start,
+                token_type: class.token_type,
+                modifiers,
+            });
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::{EdgeType, PropertyMap, PropertyValue};
+
+    fn add_symbol(
+        g: &mut CodeGraph,
+        node_type: NodeType,
+        name: &str,
+        path: &str,
+        line: i64,
+        is_public: bool,
+    ) -> NodeId {
+        let props = PropertyMap::new()
+            .with("name", PropertyValue::String(name.to_string()))
+            .with("path", PropertyValue::String(path.to_string()))
+            .with("line_start", PropertyValue::Int(line))
+            .with("is_public", PropertyValue::Bool(is_public));
+        g.add_node(node_type, props).unwrap()
+    }
+
+    #[test]
+    fn test_compute_semantic_tokens() {
+        let mut g = CodeGraph::in_memory().unwrap();
+        let app = "/src/app.py";
+        add_symbol(&mut g, NodeType::Class, "Store", app, 1, true);
+        let save = add_symbol(&mut g, NodeType::Function, "save", app, 2, true);
+        let helper = add_symbol(&mut g, NodeType::Function, "helper", app, 5, false);
+        add_symbol(&mut g, NodeType::Function, "_orphan", app, 9, false);
+        let format_item = add_symbol(
+            &mut g,
+            NodeType::Function,
+            "format_item",
+            "/src/fmt.py",
+            1,
+            true,
+        );
+        g.update_node_properties(
+            format_item,
+            PropertyMap::new().with("deprecated", PropertyValue::Bool(true)),
+        )
+        .unwrap();
+        g.add_edge(save, helper, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+        g.add_edge(helper, format_item, EdgeType::Calls, PropertyMap::new())
+            .unwrap();
+
+        let text = "\
+class Store:
+    def save(self, item):
+        return helper(item)
+
+def helper(item):
+    # helper is called from save, \"helper\" too
+    return format_item(item)
+
+def _orphan():
+    pass
+";
+        let tokens = compute_semantic_tokens(&g, Path::new(app), text);
+        let function = TOKEN_TYPES.iter().position(|t| *t == "function").unwrap() as u32;
+        let class = TOKEN_TYPES.iter().position(|t| *t == "class").unwrap() as u32;
+        let token = |line, start, length, token_type, modifiers| ClassifiedToken {
+            line,
+            start,
+            length,
+            token_type,
+            modifiers,
+        };
+        assert_eq!(
+            tokens,
+            vec![
+                token(0, 6, 5, class, MODIFIER_DECLARATION | MODIFIER_PUBLIC),
+                token(1, 8, 4, function, MODIFIER_DECLARATION | MODIFIER_PUBLIC),
+                token(2, 15, 6, function, 0),
+                token(4, 4, 6, function, MODIFIER_DECLARATION),
+                token(6, 11, 11, function, MODIFIER_PUBLIC | MODIFIER_DEPRECATED),
+                token(8, 4, 7, function, MODIFIER_DECLARATION | MODIFIER_UNUSED),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_columns_are_utf16() {
+        let mut g = CodeGraph::in_memory().unwrap();
+        add_symbol(&mut g, NodeType::Variable, "total", "/src/a.ts", 1, false);
+
+        let tokens =
+            compute_semantic_tokens(&g, Path::new("/src/a.ts"), "const s = \"é😀\"; total;");
+        assert_eq!(tokens.len(), 1);
+        // "é" is one UTF-16 unit, the emoji two
+        assert_eq!(tokens[0].start, 17);
+        assert_eq!(tokens[0].length, 5);
+    }
+}
@@ -0,0 +1,156 @@
+//! Lightweight source text scanning for features that need more than the
+//! graph records but have no AST: semantic highlighting, comment fixes.
+//!
+//! Scanning is per language family, not per language. Comments and string
+//! literals are masked out first so keywords and identifiers inside them are
+//! never counted.
+
+use std::path::Path;
+
+/// Block and comment syntax of a source file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Syntax {
+    /// `{}` blocks, `//` and `/* */` comments (C, C++, Java, JS/TS, Go, C#, ...)
+    Brace,
+    /// Like `Brace`, plus `match` arms and lifetimes
+    Rust,
+    /// Indentation blocks, `#` comments
+    Python,
+    /// `end`-terminated blocks (nesting read from indentation), `#` comments
+    Ruby,
+}
+
+impl Syntax {
+    /// Syntax for a file, by extension. None for unsupported languages.
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "rs" => Some(Syntax::Rust),
+            "py" | "pyi" => Some(Syntax::Python),
+            "rb" => Some(Syntax::Ruby),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" | "kt" | "kts" | "js"
+            | "jsx" | "mjs" | "cjs" | "ts" | "tsx" | "go" | "cs" | "swift" | "php" => {
+                Some(Syntax::Brace)
+            }
+            _ => None,
+        }
+    }
+
+    pub(crate) fn hash_comments(self) -> bool {
+        matches!(self, Syntax::Python | Syntax::Ruby)
+    }
+}
+
+pub(crate) fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '$'
+}
+
+/// Blank out comments and string literals, keeping every other character in
+/// place.
+///
+/// Each character is replaced one for one, so lines and character columns of
+/// the remaining code are unchanged. A string literal becomes a `"` followed by
+/// spaces, leaving a single token where the literal was.
+pub(crate) fn mask_comments_and_strings(source: &str, syntax: Syntax) -> String {
+    let chars: Vec<char> = source.chars().collect();
+    let mut out = String::with_capacity(source.len());
+    let blank = |out: &mut String, c: char| out.push(if c == '\n' { '\n' } else { ' ' });
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        let line_comment = if syntax.hash_comments() {
+            c == '#'
+        } else {
+            c == '/' && next == Some('/')
+        };
+        if line_comment {
+            while i < chars.len() && chars[i] != '\n' {
+                blank(&mut out, chars[i]);
+                i += 1;
+            }
+            continue;
+        }
+        if !syntax.hash_comments() && c == '/' && next == Some('*') {
+            let end = (i + 2..chars.len().saturating_sub(1))
+                .find(|&j| chars[j] == '*' && chars[j + 1] == '/')
+                .map_or(chars.len(), |j| j + 2);
+            for &ch in &chars[i..end] {
+                blank(&mut out, ch);
+            }
+            i = end;
+            continue;
+        }
+
+        let quote = c == '"'
+            || c == '`'
+            || (c == '\'' && (syntax != Syntax::Rust || is_char_literal(&chars, i)));
+        if !quote {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+
+        let triple = syntax == Syntax::Python && next == Some(c) && chars.get(i + 2) == Some(&c);
+        let delimiter = if triple { 3 } else { 1 };
+        let mut end = i + delimiter;
+        while end < chars.len() {
+            if chars[end] == '\\' {
+                end += 2;
+                continue;
+            }
+            if chars[end] == c
+                && (!triple || (chars.get(end + 1) == Some(&c) && chars.get(end + 2) == Some(&c)))
+            {
+                end += delimiter;
+                break;
+            }
+            end += 1;
+        }
+        let end = end.min(chars.len());
+        out.push('"');
+        for &ch in &chars[i + 1..end] {
+            blank(&mut out, ch);
+        }
+        i = end;
+    }
+    out
+}
+
+/// Whether the `'` at `i` opens a Rust char literal rather than a lifetime.
+fn is_char_literal(chars: &[char], i: usize) -> bool {
+    chars.get(i + 1) == Some(&'\\') || chars.get(i + 2) == Some(&'\'')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntax_from_path() {
+        assert_eq!(
+            Syntax::from_path(Path::new("src/app.tsx")),
+            Some(Syntax::Brace)
+        );
+        assert_eq!(Syntax::from_path(Path::new("lib.rs")), Some(Syntax::Rust));
+        assert_eq!(Syntax::from_path(Path::new("README.md")), None);
+    }
+
+    #[test]
+    fn test_mask_keeps_columns() {
+        let source = "let s = \"if é\"; // while\nfn f<'a>(c: char) -> bool { c == '\"' }";
+        let masked = mask_comments_and_strings(source, Syntax::Rust);
+        assert_eq!(
+            masked,
+            "let s = \"     ;         \nfn f<'a>(c: char) -> bool { c == \"   }"
+        );
+        assert_eq!(masked.chars().count(), source.chars().count());
+
+        let source = "x = 1  # if\ny = \"\"\"doc\nor\"\"\" + z";
+        assert_eq!(
+            mask_comments_and_strings(source, Syntax::Python),
+            "x = 1      \ny = \"     \n      + z"
+        );
+    }
+}
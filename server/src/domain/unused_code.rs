@@ -5,7 +5,8 @@
 
 use crate::ai_query::QueryEngine;
 use crate::domain::node_props;
-use codegraph::{CodeGraph, NodeId, NodeType};
+use codegraph::{CodeGraph, EdgeType, NodeId, NodeType, PropertyMap};
+use std::collections::{HashSet, VecDeque};
 
 // ==========================================
 // Parameters & Results
@@ -26,6 +27,8 @@ pub(crate) struct UnusedCodeCandidate {
     pub node_type: NodeType,
     pub confidence: f64,
     pub is_public: bool,
+    /// Has callers, but all of them are themselves unreachable from entry points
+    pub has_callers: bool,
     pub line_start: u32,
     pub line_end: u32,
 }
@@ -35,6 +38,27 @@ pub(crate) struct FindUnusedCodeResult {
     pub total_checked: usize,
     pub scope: String,
     pub min_confidence: f64,
+    /// Number of entry points liveness was computed from. None when the graph has
+    /// no detectable entry points and the direct-caller check was used instead.
+    pub entry_points: Option<usize>,
+}
+
+/// Edge types through which a live symbol keeps its target alive.
+pub(crate) const USAGE_EDGES: &[EdgeType] = &[
+    EdgeType::Calls,
+    EdgeType::Invokes,
+    EdgeType::References,
+    EdgeType::Uses,
+    EdgeType::Instantiates,
+    EdgeType::Extends,
+    EdgeType::Implements,
+    EdgeType::Imports,
+];
+
+/// Whether an edge is one of [`USAGE_EDGES`], leaving out the `References`
+/// edges that only record files changing together.
+pub(crate) fn is_usage_edge(edge_type: EdgeType, props: &PropertyMap) -> bool {
+    USAGE_EDGES.contains(&edge_type) && !node_props::is_co_change(edge_type, props)
 }
 
 // ==========================================
@@ -43,8 +67,12 @@ pub(crate) struct FindUnusedCodeResult {
 
 /// Find unused code symbols in the graph.
 ///
-/// Uses the richer detection strategy: checks callers via QueryEngine (respects
-/// test filtering), structural usage (child methods, sibling functions), and
+/// A symbol is live when it is reachable from an entry point (main, HTTP handlers,
+/// tests, exported APIs, framework hooks, top-level file code) over usage edges, so
+/// helpers that are only called indirectly are not reported, while call chains that
+/// nothing live reaches are. If the graph has no detectable entry points, falls back
+/// to checking direct callers via QueryEngine (respects test filtering). Both modes
+/// apply the structural usage checks (child methods, sibling functions) and
 /// confidence scoring with framework-specific heuristics.
 pub(crate) async fn find_unused_code(
     graph: &CodeGraph,
@@ -96,6 +124,10 @@ pub(crate) async fn find_unused_code(
     let total_checked = nodes_to_check.len();
     let mut candidates = Vec::new();
 
+    let roots = entry_point_roots(graph, query_engine);
+    let entry_points = roots.len();
+    let reachable = (entry_points > 0).then(|| reachable_from(graph, roots));
+
     for node_id in nodes_to_check {
         if let Ok(node) = graph.get_node(node_id) {
             // Skip structural node types (files, modules)
@@ -124,6 +156,32 @@ pub(crate) async fn find_unused_code(
                 continue;
             }
 
+            if let Some(reachable) = &reachable {
+                let is_live = reachable.contains(&node_id)
+                    || (matches!(node.node_type, NodeType::Class | NodeType::Type)
+                        && (has_called_child_methods(graph, node_id, Some(reachable))
+                            || has_active_same_file_functions(graph, node_id, Some(reachable))));
+                if is_live {
+                    continue;
+                }
+
+                let is_exported = node_props::is_public(node);
+                let item_confidence = compute_unused_confidence(name, is_exported, node);
+                if item_confidence >= params.confidence {
+                    candidates.push(UnusedCodeCandidate {
+                        name: name.to_string(),
+                        node_id,
+                        node_type: node.node_type,
+                        confidence: item_confidence,
+                        is_public: is_exported,
+                        has_callers: has_incoming_usage(graph, node_id),
+                        line_start: node_props::line_start(node),
+                        line_end: node_props::line_end(node),
+                    });
+                }
+                continue;
+            }
+
             // Check for callers (via Calls edges)
             let callers = query_engine.get_callers(node_id, 1).await;
             let total_callers = callers.len();
@@ -153,8 +211,8 @@ pub(crate) async fn find_unused_code(
             // (via Contains edges) that have callers, OR if sibling functions
             // in the same file are called, the struct itself is in use
             if matches!(node.node_type, NodeType::Class | NodeType::Type)
-                && (has_called_child_methods(graph, node_id)
-                    || has_active_same_file_functions(graph, node_id))
+                && (has_called_child_methods(graph, node_id, None)
+                    || has_active_same_file_functions(graph, node_id, None))
             {
                 continue;
             }
@@ -188,7 +246,7 @@ pub(crate) async fn find_unused_code(
                                                 | codegraph::EdgeType::Extends
                                                 | codegraph::EdgeType::Implements
                                                 | codegraph::EdgeType::Imports
-                                        )
+                                        ) && !node_props::is_co_change(e.edge_type, &e.properties)
                                     })
                                     .unwrap_or(false)
                             })
@@ -207,6 +265,7 @@ pub(crate) async fn find_unused_code(
                         node_type: node.node_type,
                         confidence: item_confidence,
                         is_public: is_exported,
+                        has_callers: false,
                         line_start: node_props::line_start(node),
                         line_end: node_props::line_end(node),
                     });
@@ -220,6 +279,7 @@ pub(crate) async fn find_unused_code(
         total_checked,
         scope: params.scope,
         min_confidence: params.confidence,
+        entry_points: reachable.map(|_| entry_points),
     }
 }
 
@@ -298,8 +358,129 @@ fn is_build_output_path(path: &str) -> bool {
         .any(|component| EXCLUDED_DIRS.contains(&component))
 }
 
-/// Check if a struct/class has child methods (via Contains edges) that are called.
-fn has_called_child_methods(graph: &CodeGraph, node_id: NodeId) -> bool {
+/// Collect entry-point symbols: main, HTTP/CLI/event handlers, tests and exported
+/// functions (as classified by QueryEngine), framework hooks and trait impl methods
+/// dispatched by the runtime, and exported types.
+fn entry_point_roots(graph: &CodeGraph, query_engine: &QueryEngine) -> Vec<NodeId> {
+    graph
+        .iter_nodes()
+        .filter(|(_, node)| match node.node_type {
+            NodeType::Function => {
+                let name = node_props::name(node);
+                query_engine.detect_entry_type(node, name).is_some()
+                    || is_test_node(node)
+                    || is_framework_entry_point(name)
+                    || is_trait_impl_method(name)
+            }
+            NodeType::Class | NodeType::Interface | NodeType::Type | NodeType::Variable => {
+                node_props::is_public(node)
+            }
+            _ => false,
+        })
+        .map(|(node_id, _)| node_id)
+        .collect()
+}
+
+/// Compute every symbol reachable from `roots` over usage edges.
+///
+/// Files and modules are roots too, since top-level code runs on load. A live
+/// class or interface keeps its contained methods alive: they may be dispatched
+/// dynamically (e.g. through an interface) without a direct call edge.
+fn reachable_from(graph: &CodeGraph, roots: Vec<NodeId>) -> HashSet<NodeId> {
+    let mut queue: VecDeque<NodeId> = roots
+        .into_iter()
+        .chain(
+            graph
+                .iter_nodes()
+                .filter(|(_, node)| matches!(node.node_type, NodeType::CodeFile | NodeType::Module))
+                .map(|(node_id, _)| node_id),
+        )
+        .collect();
+    let mut reachable: HashSet<NodeId> = queue.iter().copied().collect();
+
+    while let Some(node_id) = queue.pop_front() {
+        let is_container = graph
+            .get_node(node_id)
+            .is_ok_and(|n| matches!(n.node_type, NodeType::Class | NodeType::Interface));
+        let Ok(neighbors) = graph.get_neighbors(node_id, codegraph::Direction::Outgoing) else {
+            continue;
+        };
+        for neighbor_id in neighbors {
+            if reachable.contains(&neighbor_id) {
+                continue;
+            }
+            let is_usage = graph
+                .get_edges_between(node_id, neighbor_id)
+                .unwrap_or_default()
+                .iter()
+                .any(|&eid| {
+                    graph.get_edge(eid).is_ok_and(|e| {
+                        is_usage_edge(e.edge_type, &e.properties)
+                            || (is_container && e.edge_type == EdgeType::Contains)
+                    })
+                });
+            if is_usage {
+                reachable.insert(neighbor_id);
+                queue.push_back(neighbor_id);
+            }
+        }
+    }
+
+    reachable
+}
+
+/// Check if anything references a node through a usage edge.
+fn has_incoming_usage(graph: &CodeGraph, node_id: NodeId) -> bool {
+    graph
+        .get_neighbors(node_id, codegraph::Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .any(|neighbor_id| {
+            graph
+                .get_edges_between(neighbor_id, node_id)
+                .unwrap_or_default()
+                .iter()
+                .any(|&eid| {
+                    graph
+                        .get_edge(eid)
+                        .is_ok_and(|e| is_usage_edge(e.edge_type, &e.properties))
+                })
+        })
+}
+
+/// Check if a function is in use: reachable from an entry point when `reachable`
+/// is given, otherwise called by anything.
+fn is_live_function(
+    graph: &CodeGraph,
+    func_id: NodeId,
+    reachable: Option<&HashSet<NodeId>>,
+) -> bool {
+    if let Some(reachable) = reachable {
+        return reachable.contains(&func_id);
+    }
+    let Ok(neighbors) = graph.get_neighbors(func_id, codegraph::Direction::Incoming) else {
+        return false;
+    };
+    neighbors.iter().any(|&neighbor_id| {
+        graph
+            .get_edges_between(neighbor_id, func_id)
+            .unwrap_or_default()
+            .iter()
+            .any(|&eid| {
+                graph
+                    .get_edge(eid)
+                    .map(|e| e.edge_type == codegraph::EdgeType::Calls)
+                    .unwrap_or(false)
+            })
+    })
+}
+
+/// Check if a struct/class has child methods (via Contains edges) that are in use.
+fn has_called_child_methods(
+    graph: &CodeGraph,
+    node_id: NodeId,
+    reachable: Option<&HashSet<NodeId>>,
+) -> bool {
     let children = match graph.get_neighbors(node_id, codegraph::Direction::Outgoing) {
         Ok(c) => c,
         Err(_) => return false,
@@ -323,29 +504,19 @@ fn has_called_child_methods(graph: &CodeGraph, node_id: NodeId) -> bool {
                 continue;
             }
         }
-        if let Ok(neighbors) = graph.get_neighbors(child_id, codegraph::Direction::Incoming) {
-            for &neighbor_id in &neighbors {
-                let has_call = graph
-                    .get_edges_between(neighbor_id, child_id)
-                    .unwrap_or_default()
-                    .iter()
-                    .any(|&eid| {
-                        graph
-                            .get_edge(eid)
-                            .map(|e| e.edge_type == codegraph::EdgeType::Calls)
-                            .unwrap_or(false)
-                    });
-                if has_call {
-                    return true;
-                }
-            }
+        if is_live_function(graph, child_id, reachable) {
+            return true;
         }
     }
     false
 }
 
-/// Check if a struct/class shares its file with functions that have callers.
-fn has_active_same_file_functions(graph: &CodeGraph, node_id: NodeId) -> bool {
+/// Check if a struct/class shares its file with functions that are in use.
+fn has_active_same_file_functions(
+    graph: &CodeGraph,
+    node_id: NodeId,
+    reachable: Option<&HashSet<NodeId>>,
+) -> bool {
     let path = match graph.get_node(node_id) {
         Ok(n) => {
             let p = node_props::path(n).to_string();
@@ -366,22 +537,8 @@ fn has_active_same_file_functions(graph: &CodeGraph, node_id: NodeId) -> bool {
         if func_id == node_id {
             continue;
         }
-        if let Ok(neighbors) = graph.get_neighbors(func_id, codegraph::Direction::Incoming) {
-            for &neighbor_id in &neighbors {
-                let has_call = graph
-                    .get_edges_between(neighbor_id, func_id)
-                    .unwrap_or_default()
-                    .iter()
-                    .any(|&eid| {
-                        graph
-                            .get_edge(eid)
-                            .map(|e| e.edge_type == codegraph::EdgeType::Calls)
-                            .unwrap_or(false)
-                    });
-                if has_call {
-                    return true;
-                }
-            }
+        if is_live_function(graph, func_id, reachable) {
+            return true;
         }
     }
     false
@@ -0,0 +1,464 @@
+//! Pull request enrichment for mined merge commits.
+//!
+//! Merge and squash commit messages rarely say more than a title, while the
+//! pull request behind them holds the description and the review discussion.
+//! When enabled, the pull request of each mined merge commit is fetched from
+//! GitHub or GitLab and its text added to the memory. The host is taken from
+//! the `origin` remote and the token from the environment (`GITHUB_TOKEN` or
+//! `GH_TOKEN`, `GITLAB_TOKEN`); requests go through `curl`, with the token
+//! passed on stdin so it never shows up in the process list.
+//!
+//! Tokens are only sent to github.com, gitlab.com and the self-hosted
+//! instances listed in `CODEGRAPH_PULL_REQUEST_HOSTS`
+//! (`github:github.acme.com,gitlab:git.acme.com`), never to a host just
+//! because its name looks like one of them.
+
+use serde_json::Value;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Environment variable listing self-hosted instances tokens may be sent to
+const HOSTS_VAR: &str = "CODEGRAPH_PULL_REQUEST_HOSTS";
+
+/// Seconds a single API request may take
+const REQUEST_TIMEOUT_SECS: &str = "15";
+
+/// Review comments kept per pull request
+const MAX_REVIEW_COMMENTS: usize = 10;
+
+/// Characters kept of a description / of a review comment
+const MAX_DESCRIPTION_CHARS: usize = 2000;
+const MAX_COMMENT_CHARS: usize = 300;
+
+/// Where a repository's pull requests live.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PullRequestHost {
+    /// GitHub or GitHub Enterprise; `api` is the REST API root
+    GitHub { api: String, repo: String },
+    /// GitLab, hosted or self-managed; `project` is the project path
+    GitLab { api: String, project: String },
+}
+
+/// A self-hosted GitHub Enterprise or GitLab instance, as configured by the
+/// user.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfiguredHost {
+    GitHub(String),
+    GitLab(String),
+}
+
+impl ConfiguredHost {
+    /// Hosts from a comma-separated `kind:host` list; entries of another kind
+    /// are skipped.
+    pub fn parse_list(list: &str) -> Vec<Self> {
+        list.split(',')
+            .filter_map(|entry| {
+                let (kind, host) = entry.trim().split_once(':')?;
+                let host = host.trim().to_ascii_lowercase();
+                if host.is_empty() {
+                    return None;
+                }
+                match kind.trim().to_ascii_lowercase().as_str() {
+                    "github" => Some(Self::GitHub(host)),
+                    "gitlab" => Some(Self::GitLab(host)),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// Hosts configured in the environment
+    pub fn from_env() -> Vec<Self> {
+        std::env::var(HOSTS_VAR)
+            .map(|list| Self::parse_list(&list))
+            .unwrap_or_default()
+    }
+}
+
+impl PullRequestHost {
+    /// The host of a remote URL (`https://github.com/o/r.git`,
+    /// `git@gitlab.com:group/project.git`, `ssh://git@host/o/r`), if it is
+    /// github.com, gitlab.com or one of the `configured` hosts.
+    pub fn from_remote_url(url: &str, configured: &[ConfiguredHost]) -> Option<Self> {
+        let url = url.trim();
+        let (authority, path) = match url.split_once("://") {
+            Some((_, rest)) => rest.split_once('/')?,
+            // scp-like syntax: user@host:path
+            None => url.split_once(':')?,
+        };
+        let host = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+        let host = host.split(':').next().unwrap_or(host).to_ascii_lowercase();
+        let path = path.trim_matches('/').trim_end_matches(".git").to_string();
+        if path.split('/').count() < 2 {
+            return None;
+        }
+
+        if host == "github.com" {
+            Some(Self::GitHub {
+                api: "https://api.github.com".to_string(),
+                repo: path,
+            })
+        } else if host == "gitlab.com" {
+            Some(Self::GitLab {
+                api: "https://gitlab.com/api/v4".to_string(),
+                project: path,
+            })
+        } else {
+            configured.iter().find_map(|configured| match configured {
+                ConfiguredHost::GitHub(name) if *name == host => Some(Self::GitHub {
+                    api: format!("https://{}/api/v3", host),
+                    repo: path.clone(),
+                }),
+                ConfiguredHost::GitLab(name) if *name == host => Some(Self::GitLab {
+                    api: format!("https://{}/api/v4", host),
+                    project: path.clone(),
+                }),
+                _ => None,
+            })
+        }
+    }
+
+    /// Environment variables holding a token for this host
+    fn token_vars(&self) -> &'static [&'static str] {
+        match self {
+            Self::GitHub { .. } => &["GITHUB_TOKEN", "GH_TOKEN"],
+            Self::GitLab { .. } => &["GITLAB_TOKEN"],
+        }
+    }
+}
+
+/// A pull request's text, as added to a memory.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PullRequestInfo {
+    pub number: u64,
+    pub title: String,
+    pub description: String,
+    /// `author: comment` for the first review comments
+    pub review_comments: Vec<String>,
+}
+
+impl PullRequestInfo {
+    /// Section appended to the memory content, with the description unless
+    /// the memory already holds it.
+    pub fn to_content(&self, with_description: bool) -> String {
+        let mut content = format!("Pull request #{}: {}", self.number, self.title);
+        let description = self.description.trim();
+        if with_description && !description.is_empty() {
+            content.push_str("\n\n");
+            content.push_str(&truncate(description, MAX_DESCRIPTION_CHARS));
+        }
+        if !self.review_comments.is_empty() {
+            content.push_str("\n\nReview comments:");
+            for comment in &self.review_comments {
+                content.push_str("\n- ");
+                content.push_str(comment);
+            }
+        }
+        content
+    }
+}
+
+/// Fetches the pull requests of mined merge commits.
+#[derive(Debug)]
+pub struct PullRequestEnricher {
+    host: PullRequestHost,
+    token: String,
+    /// Pull requests fetched / that could not be fetched
+    fetched: AtomicUsize,
+    failed: AtomicUsize,
+}
+
+impl PullRequestEnricher {
+    /// An enricher for the repository at `remote_url`, with the token from
+    /// the environment. The error says why enrichment is not possible.
+    pub fn from_remote(remote_url: &str) -> Result<Self, String> {
+        let host = PullRequestHost::from_remote_url(remote_url, &ConfiguredHost::from_env())
+            .ok_or_else(|| {
+                format!(
+                    "Pull request enrichment supports github.com, gitlab.com and the hosts in {}, not {}",
+                    HOSTS_VAR, remote_url
+                )
+            })?;
+        let vars = host.token_vars();
+        let token = vars
+            .iter()
+            .find_map(|var| std::env::var(var).ok().filter(|token| !token.is_empty()))
+            .ok_or_else(|| {
+                format!(
+                    "Pull request enrichment needs a token in {}",
+                    vars.join(" or ")
+                )
+            })?;
+        Ok(Self {
+            host,
+            token,
+            fetched: AtomicUsize::new(0),
+            failed: AtomicUsize::new(0),
+        })
+    }
+
+    /// Fetch pull request `number`, or `None` if it could not be fetched.
+    pub fn fetch(&self, number: u64) -> Option<PullRequestInfo> {
+        let fetched = match &self.host {
+            PullRequestHost::GitHub { api, repo } => self.fetch_github(api, repo, number),
+            PullRequestHost::GitLab { api, project } => self.fetch_gitlab(api, project, number),
+        };
+        match fetched {
+            Ok(info) => {
+                self.fetched.fetch_add(1, Ordering::Relaxed);
+                Some(info)
+            }
+            Err(e) => {
+                tracing::debug!("Failed to fetch pull request #{}: {}", number, e);
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    /// Pull requests fetched so far
+    pub fn fetched(&self) -> usize {
+        self.fetched.load(Ordering::Relaxed)
+    }
+
+    /// Pull requests that could not be fetched so far
+    pub fn failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    fn fetch_github(&self, api: &str, repo: &str, number: u64) -> Result<PullRequestInfo, String> {
+        let headers = format!(
+            "Authorization: Bearer {}\nAccept: application/vnd.github+json\nUser-Agent: codegraph",
+            self.token
+        );
+        let base = format!("{}/repos/{}/pulls/{}", api, repo, number);
+        let pull = get_json(&base, &headers)?;
+
+        // Review summaries first, then comments on the diff
+        let mut review_comments = Vec::new();
+        for url in [format!("{}/reviews", base), format!("{}/comments", base)] {
+            let Value::Array(items) = get_json(&url, &headers)? else {
+                continue;
+            };
+            review_comments.extend(items.iter().filter_map(|item| {
+                comment_line(
+                    item.pointer("/user/login").and_then(Value::as_str),
+                    item.get("body").and_then(Value::as_str),
+                )
+            }));
+        }
+        review_comments.truncate(MAX_REVIEW_COMMENTS);
+
+        Ok(PullRequestInfo {
+            number,
+            title: string_field(&pull, "title"),
+            description: string_field(&pull, "body"),
+            review_comments,
+        })
+    }
+
+    fn fetch_gitlab(
+        &self,
+        api: &str,
+        project: &str,
+        number: u64,
+    ) -> Result<PullRequestInfo, String> {
+        let headers = format!("PRIVATE-TOKEN: {}", self.token);
+        let base = format!(
+            "{}/projects/{}/merge_requests/{}",
+            api,
+            project.replace('/', "%2F"),
+            number
+        );
+        let request = get_json(&base, &headers)?;
+        let notes = get_json(&format!("{}/notes?sort=asc&per_page=50", base), &headers)?;
+
+        let review_comments = notes
+            .as_array()
+            .into_iter()
+            .flatten()
+            // System notes record pushes and approvals, not discussion
+            .filter(|note| !note.get("system").and_then(Value::as_bool).unwrap_or(false))
+            .filter_map(|note| {
+                comment_line(
+                    note.pointer("/author/username").and_then(Value::as_str),
+                    note.get("body").and_then(Value::as_str),
+                )
+            })
+            .take(MAX_REVIEW_COMMENTS)
+            .collect();
+
+        Ok(PullRequestInfo {
+            number,
+            title: string_field(&request, "title"),
+            description: string_field(&request, "description"),
+            review_comments,
+        })
+    }
+}
+
+/// GET `url` with `headers` (one per line) and parse the JSON response.
+fn get_json(url: &str, headers: &str) -> Result<Value, String> {
+    let mut child = Command::new("curl")
+        .args([
+            "--silent",
+            "--show-error",
+            "--fail",
+            "--max-time",
+            REQUEST_TIMEOUT_SECS,
+            "--header",
+            "@-",
+            url,
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run curl: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(headers.as_bytes())
+            .map_err(|e| format!("Failed to pass headers to curl: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("curl failed: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("Invalid response: {}", e))
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// `author: comment`, or `None` for an empty comment
+fn comment_line(author: Option<&str>, body: Option<&str>) -> Option<String> {
+    let body = body?.trim();
+    if body.is_empty() {
+        return None;
+    }
+    let body = truncate(
+        &body.split_whitespace().collect::<Vec<_>>().join(" "),
+        MAX_COMMENT_CHARS,
+    );
+    Some(format!("{}: {}", author.unwrap_or("unknown"), body))
+}
+
+/// `text` cut to at most `max_chars` characters, marked with `…` if cut
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_host_from_remote_url() {
+        let github = PullRequestHost::GitHub {
+            api: "https://api.github.com".to_string(),
+            repo: "acme/app".to_string(),
+        };
+        assert_eq!(
+            PullRequestHost::from_remote_url("https://github.com/acme/app.git", &[]),
+            Some(github.clone())
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("git@github.com:acme/app.git\n", &[]),
+            Some(github)
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("git@gitlab.com:group/app.git", &[]),
+            Some(PullRequestHost::GitLab {
+                api: "https://gitlab.com/api/v4".to_string(),
+                project: "group/app".to_string(),
+            })
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("https://bitbucket.org/acme/app.git", &[]),
+            None
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("/srv/git/app.git", &[]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_lookalike_hosts_need_configuring() {
+        for url in [
+            "https://github.evil.com/acme/app",
+            "https://github.acme.com/team/app",
+            "ssh://git@gitlab.example.com:2222/group/sub/app",
+        ] {
+            assert_eq!(PullRequestHost::from_remote_url(url, &[]), None, "{}", url);
+        }
+
+        let configured =
+            ConfiguredHost::parse_list("github:GitHub.acme.com, gitlab:gitlab.example.com,svn:x");
+        assert_eq!(
+            configured,
+            vec![
+                ConfiguredHost::GitHub("github.acme.com".to_string()),
+                ConfiguredHost::GitLab("gitlab.example.com".to_string()),
+            ]
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("https://github.acme.com/team/app", &configured),
+            Some(PullRequestHost::GitHub {
+                api: "https://github.acme.com/api/v3".to_string(),
+                repo: "team/app".to_string(),
+            })
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url(
+                "ssh://git@gitlab.example.com:2222/group/sub/app",
+                &configured
+            ),
+            Some(PullRequestHost::GitLab {
+                api: "https://gitlab.example.com/api/v4".to_string(),
+                project: "group/sub/app".to_string(),
+            })
+        );
+        assert_eq!(
+            PullRequestHost::from_remote_url("https://github.evil.com/acme/app", &configured),
+            None
+        );
+    }
+
+    #[test]
+    fn test_pull_request_content() {
+        let info = PullRequestInfo {
+            number: 42,
+            title: "Retry login on timeout".to_string(),
+            description: "The auth service drops idle connections.\n".to_string(),
+            review_comments: vec![comment_line(
+                Some("alice"),
+                Some("Should the retry\n back off?"),
+            )
+            .unwrap()],
+        };
+        assert_eq!(
+            info.to_content(true),
+            "Pull request #42: Retry login on timeout\n\n\
+             The auth service drops idle connections.\n\n\
+             Review comments:\n- alice: Should the retry back off?"
+        );
+        assert!(!info.to_content(false).contains("idle connections"));
+        assert_eq!(comment_line(Some("bob"), Some("  ")), None);
+        assert_eq!(truncate("abcdef", 3), "abc…");
+        assert_eq!(truncate("abc", 3), "abc");
+    }
+}
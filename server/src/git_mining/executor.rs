@@ -34,6 +34,11 @@ impl GitExecutor {
         })
     }
 
+    /// The repository this executor runs in.
+    pub fn repo_path(&self) -> &Path {
+        &self.repo_path
+    }
+
     /// Get commit log with custom format.
     ///
     /// Format placeholders:
@@ -73,6 +78,228 @@ impl GitExecutor {
         Ok(String::from_utf8(output.stdout)?)
     }
 
+    /// Get the log of the commits dated between `since` and `until` (any
+    /// date `git log --since` accepts, like `2024-01-01` or `6 months ago`),
+    /// newest first.
+    pub fn log_window(
+        &self,
+        format: &str,
+        limit: Option<usize>,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<String, GitMiningError> {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(&self.repo_path);
+        cmd.args(["log", &format!("--format={}", format)]);
+
+        if let Some(n) = limit {
+            cmd.arg(format!("-n{}", n));
+        }
+        if let Some(since) = since {
+            cmd.arg(format!("--since={}", since));
+        }
+        if let Some(until) = until {
+            cmd.arg(format!("--until={}", until));
+        }
+
+        cmd.arg("--");
+
+        let output = cmd.output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Get the log of the commits after `since` up to HEAD, newest first.
+    pub fn log_since(
+        &self,
+        format: &str,
+        since: &str,
+        limit: Option<usize>,
+    ) -> Result<String, GitMiningError> {
+        let mut cmd = Command::new("git");
+        cmd.current_dir(&self.repo_path);
+        cmd.args(["log", &format!("--format={}", format)]);
+
+        if let Some(n) = limit {
+            cmd.arg(format!("-n{}", n));
+        }
+
+        cmd.args([&format!("{}..HEAD", since), "--"]);
+
+        let output = cmd.output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Get the log of `revisions` (branches, or ranges like `A..B`), newest
+    /// first, with `--source` so that `%S` names the revision each commit was
+    /// reached from.
+    pub fn log_revisions(
+        &self,
+        format: &str,
+        revisions: &[String],
+        limit: Option<usize>,
+        path_filter: Option<&Path>,
+    ) -> Result<String, GitMiningError> {
+        // Revisions come from clients; one starting with a dash would be
+        // taken as an option, e.g. `--output=<file>`
+        if let Some(option) = revisions.iter().find(|rev| rev.starts_with('-')) {
+            return Err(GitMiningError::CommandFailed(format!(
+                "Invalid revision: {}",
+                option
+            )));
+        }
+
+        let mut cmd = Command::new("git");
+        cmd.current_dir(&self.repo_path);
+        cmd.args(["log", "--source", &format!("--format={}", format)]);
+
+        if let Some(n) = limit {
+            cmd.arg(format!("-n{}", n));
+        }
+
+        cmd.args(revisions);
+        cmd.arg("--");
+
+        if let Some(path) = path_filter {
+            cmd.arg(path);
+        }
+
+        let output = cmd.output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Local and remote-tracking branches matching the glob `pattern`
+    /// (e.g. `release/*`), by short name.
+    pub fn branches_matching(&self, pattern: &str) -> Result<Vec<String>, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args([
+                "for-each-ref",
+                "--format=%(refname:short)",
+                &format!("refs/heads/{}", pattern),
+                &format!("refs/remotes/*/{}", pattern),
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Get the most recent tags, newest first, in `format` (a
+    /// `git for-each-ref` format).
+    pub fn tags(&self, format: &str, limit: usize) -> Result<String, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args([
+                "for-each-ref",
+                "--sort=-creatordate",
+                &format!("--count={}", limit),
+                &format!("--format={}", format),
+                "refs/tags",
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?)
+    }
+
+    /// Get the committer date of a commit, in unix seconds.
+    pub fn commit_timestamp(&self, commit_hash: &str) -> Result<i64, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args(["show", "-s", "--format=%ct", commit_hash])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        let stdout = String::from_utf8(output.stdout)?;
+        stdout
+            .trim()
+            .parse()
+            .map_err(|_| GitMiningError::ParseError(format!("Invalid commit date: {}", stdout)))
+    }
+
+    /// Get a commit's zero-context diff of one file.
+    pub fn show_file_diff(&self, commit_hash: &str, path: &str) -> Result<String, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args([
+                "show",
+                "-U0",
+                "--no-color",
+                "--format=",
+                commit_hash,
+                "--",
+                path,
+            ])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Get the URL of remote `name`.
+    pub fn remote_url(&self, name: &str) -> Result<String, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args(["remote", "get-url", name])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8(output.stdout)?.trim().to_string())
+    }
+
+    /// Whether `commit` exists and is an ancestor of HEAD, i.e. history was
+    /// not rewritten past it.
+    pub fn is_ancestor_of_head(&self, commit: &str) -> bool {
+        Command::new("git")
+            .current_dir(&self.repo_path)
+            .args(["merge-base", "--is-ancestor", commit, "HEAD"])
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
     /// Get commits matching a grep pattern in commit messages.
     pub fn log_grep(
         &self,
@@ -124,6 +351,21 @@ impl GitExecutor {
             .collect())
     }
 
+    /// Get the zero-context diff of a commit, for its changed line ranges.
+    pub fn show_hunks(&self, commit_hash: &str) -> Result<String, GitMiningError> {
+        let output = Command::new("git")
+            .current_dir(&self.repo_path)
+            .args(["show", "-U0", "--no-color", "--format=", commit_hash])
+            .output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(GitMiningError::CommandFailed(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
     /// Get the diff statistics for a commit.
     pub fn show_stat(&self, commit_hash: &str) -> Result<String, GitMiningError> {
         let output = Command::new("git")
@@ -292,6 +534,25 @@ mod tests {
     use super::*;
     use std::env;
 
+    #[test]
+    fn test_log_revisions_rejects_options() {
+        let repo = tempfile::tempdir().unwrap();
+        let init = Command::new("git")
+            .current_dir(repo.path())
+            .args(["init", "-q"])
+            .status();
+        if !init.is_ok_and(|status| status.success()) {
+            return; // git is not available
+        }
+        let executor = GitExecutor::new(repo.path()).unwrap();
+
+        let target = repo.path().join("written");
+        let revisions = vec![format!("--output={}", target.display())];
+        let result = executor.log_revisions("%H", &revisions, None, None);
+        assert!(matches!(result, Err(GitMiningError::CommandFailed(_))));
+        assert!(!target.exists());
+    }
+
     #[test]
     fn test_git_executor_creation() {
         // This test only works if run from within a git repository
@@ -1,15 +1,23 @@
 //! Git history miner for extracting memories from commits.
 
 use super::{
+    enrichment::{PullRequestEnricher, PullRequestInfo},
     executor::GitExecutor,
-    parser::{self, CommitInfo, CommitPattern, ParsedCommit, LOG_FORMAT},
-    GitMiningError,
+    parser::{
+        self, CommitInfo, CommitPattern, MemoryTrailer, ParsedCommit, TrailerKind, LOG_FORMAT,
+        SOURCE_LOG_FORMAT, TAG_FORMAT, TIMED_LOG_FORMAT,
+    },
+    patterns::CommitPatterns,
+    symbols, GitMiningError,
 };
+use crate::domain::node_props;
 use crate::memory::MemoryManager;
-use codegraph::CodeGraph;
-use codegraph_memory::{LinkedNodeType, MemoryNode, MemorySource};
+use codegraph::{CodeGraph, EdgeType, PropertyMap, PropertyValue};
+use codegraph_memory::{CodeLink, LinkedNodeType, MemoryNode, MemorySource};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 
 /// Configuration for git mining operations.
@@ -31,6 +39,28 @@ pub struct MiningConfig {
     pub mine_features: bool,
     /// Whether to mine deprecations as known issues.
     pub mine_deprecations: bool,
+    /// Scan the last `max_commits` commits even if the repository was mined
+    /// before, instead of only the commits since.
+    pub full: bool,
+    /// Whether to add the pull request behind each mined merge commit
+    /// (description and review comments) to its memory. Needs a GitHub or
+    /// GitLab token in the environment; self-hosted instances must also be
+    /// listed in `CODEGRAPH_PULL_REQUEST_HOSTS`.
+    pub enrich_pull_requests: bool,
+    /// Branch to mine instead of the checked-out one: a branch or other ref
+    /// (`main`), a glob over branch names (`release/*`) or a range (`A..B`).
+    /// Mining a branch always scans its last `max_commits` commits.
+    pub branch: Option<String>,
+    /// Whether to mine annotated tags and release commits as project
+    /// context, with their release notes.
+    pub mine_releases: bool,
+    /// Whether to turn `Memory:` and `Decision:` commit trailers into
+    /// memories, whatever the commit is classified as.
+    pub mine_trailers: bool,
+    /// Collect the memories mining would create as candidates, without
+    /// storing them, invalidating reverted ones or moving the incremental
+    /// mark.
+    pub dry_run: bool,
 }
 
 impl Default for MiningConfig {
@@ -44,6 +74,53 @@ impl Default for MiningConfig {
             mine_reverts: true,
             mine_features: true,
             mine_deprecations: true,
+            full: false,
+            enrich_pull_requests: false,
+            branch: None,
+            mine_releases: true,
+            mine_trailers: true,
+            dry_run: false,
+        }
+    }
+}
+
+impl MiningConfig {
+    /// Config from command or tool params (`maxCommits`, `minConfidence`,
+    /// `mineBugFixes`, ...), taking what they leave out from `defaults`.
+    pub fn from_params(params: &serde_json::Value, defaults: MiningConfig) -> Self {
+        let flag =
+            |key: &str, default: bool| params.get(key).and_then(|v| v.as_bool()).unwrap_or(default);
+        Self {
+            max_commits: params
+                .get("maxCommits")
+                .or_else(|| params.get("max_commits"))
+                .and_then(|v| v.as_u64())
+                .map(|v| v as usize)
+                .unwrap_or(defaults.max_commits),
+            min_confidence: params
+                .get("minConfidence")
+                .or_else(|| params.get("min_confidence"))
+                .and_then(|v| v.as_f64())
+                .map(|v| v as f32)
+                .unwrap_or(defaults.min_confidence),
+            mine_bug_fixes: flag("mineBugFixes", defaults.mine_bug_fixes),
+            mine_arch_decisions: flag("mineArchDecisions", defaults.mine_arch_decisions),
+            mine_breaking_changes: flag("mineBreakingChanges", defaults.mine_breaking_changes),
+            mine_reverts: flag("mineReverts", defaults.mine_reverts),
+            mine_features: flag("mineFeatures", defaults.mine_features),
+            mine_deprecations: flag("mineDeprecations", defaults.mine_deprecations),
+            full: flag("full", defaults.full),
+            enrich_pull_requests: flag("enrichPullRequests", defaults.enrich_pull_requests),
+            branch: params
+                .get("branch")
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|branch| !branch.is_empty())
+                .map(str::to_string)
+                .or(defaults.branch),
+            mine_releases: flag("mineReleases", defaults.mine_releases),
+            mine_trailers: flag("mineTrailers", defaults.mine_trailers),
+            dry_run: flag("dryRun", defaults.dry_run),
         }
     }
 }
@@ -61,6 +138,373 @@ pub struct MiningResult {
     pub memory_ids: Vec<String>,
     /// Errors encountered (non-fatal).
     pub warnings: Vec<String>,
+    /// The previous run's HEAD, when only the commits since were mined.
+    pub since_commit: Option<String>,
+    /// Number of commits added to the memory of an earlier fix for the same
+    /// issue instead of creating their own.
+    pub commits_grouped: usize,
+    /// Number of pull requests whose text was added to memories.
+    pub pull_requests_enriched: usize,
+    /// Number of memories invalidated because their commit was reverted.
+    pub memories_reverted: usize,
+    /// Number of memories made current again because the revert of their
+    /// commit was itself reverted.
+    pub memories_restored: usize,
+    /// Number of releases mined as project context.
+    pub releases_mined: usize,
+    /// Number of squash merges whose squashed commits were mined one by one.
+    pub squash_merges_split: usize,
+    /// Number of memories created from `Memory:` and `Decision:` trailers.
+    pub trailers_mined: usize,
+    /// On a dry run, the memories that would have been created.
+    pub candidates: Option<Vec<MemoryCandidate>>,
+}
+
+/// A memory a dry run would have created.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MemoryCandidate {
+    pub kind: String,
+    pub title: String,
+    pub confidence: f32,
+    /// Commits the memory was mined from
+    pub commits: Vec<String>,
+}
+
+impl MemoryCandidate {
+    fn from_memory(memory: &MemoryNode) -> Self {
+        let mut commits: Vec<String> = match &memory.source {
+            MemorySource::GitHistory { commit_hash } => vec![commit_hash.clone()],
+            _ => Vec::new(),
+        };
+        for hash in grouped_commits(&memory.content) {
+            if !commits.contains(&hash) {
+                commits.push(hash);
+            }
+        }
+        Self {
+            kind: memory.kind.discriminant_name().to_string(),
+            title: memory.title.clone(),
+            confidence: memory.confidence,
+            commits,
+        }
+    }
+}
+
+impl MiningResult {
+    /// Count the outcomes of processing `commit`.
+    fn record(
+        &mut self,
+        commit: &CommitInfo,
+        processed: Result<Vec<CommitOutcome>, GitMiningError>,
+    ) {
+        match processed {
+            Ok(outcomes) if outcomes.is_empty() => {
+                self.commits_skipped += 1;
+            }
+            Ok(outcomes) => {
+                for outcome in outcomes {
+                    self.record_outcome(outcome);
+                }
+            }
+            Err(e) => {
+                self.warnings.push(format!(
+                    "Failed to process commit {}: {}",
+                    &commit.hash[..7],
+                    e
+                ));
+            }
+        }
+    }
+
+    fn record_outcome(&mut self, outcome: CommitOutcome) {
+        match outcome {
+            CommitOutcome::Created(memory_id) => {
+                self.memories_created += 1;
+                self.memory_ids.push(memory_id);
+            }
+            CommitOutcome::Grouped(memory_id) => {
+                self.commits_grouped += 1;
+                if !self.memory_ids.contains(&memory_id) {
+                    self.memory_ids.push(memory_id);
+                }
+            }
+            CommitOutcome::Candidate(candidate) => {
+                self.candidates.get_or_insert_with(Vec::new).push(candidate);
+            }
+            CommitOutcome::Trailer(memory_id) => {
+                self.memories_created += 1;
+                self.trailers_mined += 1;
+                self.memory_ids.push(memory_id);
+            }
+            CommitOutcome::Squashed(outcomes) => {
+                self.squash_merges_split += 1;
+                for outcome in outcomes {
+                    self.record_outcome(outcome);
+                }
+            }
+        }
+    }
+
+    /// Count the pull requests `enricher` fetched, if enrichment ran.
+    fn record_enrichment(&mut self, enricher: Option<&PullRequestEnricher>) {
+        let Some(enricher) = enricher else {
+            return;
+        };
+        self.pull_requests_enriched = enricher.fetched();
+        if enricher.failed() > 0 {
+            self.warnings.push(format!(
+                "Could not fetch {} pull requests; their commits were mined without them",
+                enricher.failed()
+            ));
+        }
+    }
+}
+
+/// What processing a commit did with it.
+#[derive(Debug)]
+enum CommitOutcome {
+    /// A memory was created from the commit.
+    Created(String),
+    /// The commit was added to the memory of an earlier fix for its issue.
+    Grouped(String),
+    /// A memory was created from a `Memory:` or `Decision:` trailer.
+    Trailer(String),
+    /// On a dry run, the memory that would have been created.
+    Candidate(MemoryCandidate),
+    /// The commit was a squash merge; these are the outcomes of the
+    /// squashed commits that were mined.
+    Squashed(Vec<CommitOutcome>),
+}
+
+/// Tag marking a memory as referring to an issue or pull request.
+fn issue_ref_tag(issue_ref: &str) -> String {
+    format!("ref:{}", issue_ref)
+}
+
+/// Revert chains followed from one revert commit, so that a malformed
+/// history cannot loop
+const MAX_REVERT_CHAIN: usize = 32;
+
+/// Abbreviated hash used in revert tags; revert messages may name the
+/// reverted commit by any prefix of its hash.
+fn short_hash(hash: &str) -> &str {
+    &hash[..hash.len().min(7)]
+}
+
+/// Tag marking a memory whose commit was reverted by `revert_hash`.
+fn reverted_by_tag(revert_hash: &str) -> String {
+    format!("reverted-by:{}", short_hash(revert_hash))
+}
+
+/// Tag marking the memory of a revert commit with the commit it reverted.
+fn reverts_tag(reverted_hash: &str) -> String {
+    format!("reverts:{}", short_hash(reverted_hash))
+}
+
+/// Tag marking a memory made current again by `restore_hash`, which reverted
+/// the revert that invalidated it.
+fn restored_by_tag(restore_hash: &str) -> String {
+    format!("restored-by:{}", short_hash(restore_hash))
+}
+
+/// Move `tag` to the end of `memory`'s tags, so the last `reverted-by:` tag
+/// names the latest revert.
+fn push_tag(memory: &mut MemoryNode, tag: String) {
+    memory.tags.retain(|t| *t != tag);
+    memory.tags.push(tag);
+}
+
+/// Invalidate `memory` because `revert_hash` reverted its commit, noting
+/// the revert in its content. Returns whether it was current.
+fn mark_reverted(memory: &mut MemoryNode, revert_hash: &str) -> bool {
+    if !memory.is_current() {
+        return false;
+    }
+    memory.temporal.invalidate();
+    push_tag(memory, reverted_by_tag(revert_hash));
+    memory.content.push_str(&format!(
+        "\n\n**Reverted** by commit {}",
+        short_hash(revert_hash)
+    ));
+    true
+}
+
+/// Whether the revert commit `revert_hash` was applied by an earlier run:
+/// some memory records it as the revert that invalidated or restored it.
+fn revert_applied(memories: &[MemoryNode], revert_hash: &str) -> bool {
+    let tags = [reverted_by_tag(revert_hash), restored_by_tag(revert_hash)];
+    memories
+        .iter()
+        .any(|memory| memory.tags.iter().any(|t| tags.contains(t)))
+}
+
+/// Half-life of changes in mined hotspots: a change a quarter old counts
+/// half as much as one made today
+const HOTSPOT_HALF_LIFE_DAYS: f32 = 90.0;
+
+/// Annotated tags looked at for release notes
+const MAX_RELEASE_TAGS: usize = 100;
+
+/// Notes kept per release
+const MAX_RELEASE_NOTES: usize = 40;
+
+/// A release found in history.
+#[derive(Debug)]
+struct Release {
+    version: String,
+    commit: String,
+    timestamp: i64,
+    notes: Vec<String>,
+}
+
+/// Tag marking the ownership memory of a module.
+fn ownership_tag(module: &str) -> String {
+    format!("ownership:{}", module)
+}
+
+/// Tag marking the memory of a release.
+fn release_tag(version: &str) -> String {
+    format!("release:{}", version)
+}
+
+/// Whether `path` is a changelog or release notes file.
+fn is_changelog(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    let stem = name.split('.').next().unwrap_or(&name);
+    matches!(
+        stem,
+        "changelog"
+            | "changes"
+            | "history"
+            | "news"
+            | "releases"
+            | "release-notes"
+            | "release_notes"
+    )
+}
+
+/// Release notes from a tag or commit message and the lines a release
+/// added to changelogs: non-blank lines, without the version heading.
+fn release_notes(version: &str, message: &str, changelog: &[String]) -> Vec<String> {
+    message
+        .lines()
+        .chain(changelog.iter().map(String::as_str))
+        .map(str::trim_end)
+        .filter(|line| !line.trim().is_empty())
+        .filter(|line| !(line.starts_with('#') && line.contains(version)))
+        .take(MAX_RELEASE_NOTES)
+        .map(str::to_string)
+        .collect()
+}
+
+/// One-line description of a release: its first note that is neither a
+/// heading nor names the version, without list markers.
+fn release_summary(release: &Release) -> String {
+    release
+        .notes
+        .iter()
+        .map(|line| line.trim())
+        .find(|line| !line.starts_with('#') && !line.contains(&release.version))
+        .map(|line| line.trim_start_matches(['-', '*', '+', ' ']).to_string())
+        .unwrap_or_else(|| format!("Released {}", release.version))
+}
+
+/// Make `memory`, invalidated by the revert `revert_hash`, current again
+/// because `restore_hash` reverted that revert. Returns false, leaving it
+/// alone, when it is current or was invalidated by a later revert.
+fn mark_restored(memory: &mut MemoryNode, revert_hash: &str, restore_hash: &str) -> bool {
+    let latest_revert = memory
+        .tags
+        .iter()
+        .rev()
+        .find(|t| t.starts_with("reverted-by:"));
+    if memory.is_current() || latest_revert != Some(&reverted_by_tag(revert_hash)) {
+        return false;
+    }
+    memory.temporal.invalid_at = None;
+    push_tag(memory, restored_by_tag(restore_hash));
+    memory.content.push_str(&format!(
+        "\n\n**Restored** by commit {}, which reverted the revert",
+        short_hash(restore_hash)
+    ));
+    true
+}
+
+/// The commit the commit of `memory` reverted, if it is a revert.
+fn reverted_commit(memory: &MemoryNode) -> Option<String> {
+    memory
+        .tags
+        .iter()
+        .find_map(|t| t.strip_prefix("reverts:"))
+        .map(str::to_string)
+}
+
+/// Apply the revert commits `reverts`, oldest first, to `memories`: each
+/// `(reverted, revert)` pair invalidates the memories of the reverted commit
+/// and, when that commit was a revert itself, restores what it undid.
+/// Reverts an earlier run applied are skipped, so applying the same reverts
+/// again changes nothing. Returns the indexes of the memories changed.
+fn apply_revert_commits(
+    memories: &mut [MemoryNode],
+    reverts: &[(String, String)],
+    result: &mut MiningResult,
+) -> BTreeSet<usize> {
+    let mut changed = BTreeSet::new();
+    for (reverted_hash, revert_hash) in reverts {
+        if revert_applied(memories, revert_hash) {
+            continue;
+        }
+        let mut pending = vec![(reverted_hash.clone(), revert_hash.clone())];
+        let mut steps = 0;
+        while let Some((reverted, revert)) = pending.pop() {
+            steps += 1;
+            if steps > MAX_REVERT_CHAIN {
+                break;
+            }
+
+            // Knowledge from the reverted commit no longer holds
+            for (index, memory) in memories.iter_mut().enumerate() {
+                let own = matches!(&memory.source, MemorySource::GitHistory { commit_hash }
+                    if commit_hash.starts_with(&reverted));
+                if own && mark_reverted(memory, &revert) {
+                    changed.insert(index);
+                    result.memories_reverted += 1;
+                }
+            }
+
+            // If the reverted commit was a revert, what it undid is back
+            let undone = reverted_by_tag(&reverted);
+            for (index, memory) in memories.iter_mut().enumerate() {
+                if !memory.tags.contains(&undone) || !mark_restored(memory, &reverted, &revert) {
+                    continue;
+                }
+                changed.insert(index);
+                result.memories_restored += 1;
+
+                // A restored revert memory means that revert applies again
+                if let (Some(target), MemorySource::GitHistory { commit_hash }) =
+                    (reverted_commit(memory), &memory.source)
+                {
+                    pending.push((target, commit_hash.clone()));
+                }
+            }
+        }
+        tracing::debug!(
+            "Applied revert {} of {}",
+            short_hash(revert_hash),
+            short_hash(reverted_hash)
+        );
+    }
+    changed
+}
+
+/// Receives progress from a mining run and can stop it early.
+pub trait MiningProgress: Send + Sync {
+    /// Called after each commit is processed.
+    fn commit_processed(&self, processed: usize, total: usize);
+
+    /// Whether mining should stop before the next commit.
+    fn is_cancelled(&self) -> bool;
 }
 
 /// Data about file churn for hotspot detection.
@@ -68,19 +512,89 @@ pub struct MiningResult {
 struct FileChurnData {
     path: String,
     change_count: usize,
+    weighted_changes: f32,
     unique_commits: std::collections::HashSet<String>,
     recent_changes: Vec<String>,
 }
 
-/// A code hotspot (high-churn file).
+/// A code hotspot (high-churn file, or directory when aggregated).
 #[derive(Debug, Clone)]
 pub struct ChurnHotspot {
     pub file_path: String,
     pub change_count: usize,
+    /// Changes weighted by their age; equal to `change_count` without decay
+    pub weighted_changes: f32,
     pub unique_commits: usize,
     pub recent_changes: Vec<String>,
 }
 
+/// Options for hotspot detection.
+#[derive(Debug, Clone)]
+pub struct HotspotOptions {
+    /// Minimum number of changes for a hotspot.
+    pub threshold: usize,
+    /// Most recent commits to scan (None = all).
+    pub max_commits: Option<usize>,
+    /// Only scan commits after this date (any date `git log --since`
+    /// accepts, like `2024-01-01` or `6 months ago`).
+    pub since: Option<String>,
+    /// Only scan commits before this date.
+    pub until: Option<String>,
+    /// Halve the weight of a change for every this many days it is older
+    /// than the newest scanned commit, so that files which were busy long
+    /// ago but are stable now rank low. None weighs all changes equally.
+    pub half_life_days: Option<f32>,
+    /// Aggregate churn by directory instead of by file.
+    pub by_directory: bool,
+}
+
+impl Default for HotspotOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 2,
+            max_commits: None,
+            since: None,
+            until: None,
+            half_life_days: None,
+            by_directory: false,
+        }
+    }
+}
+
+impl HotspotOptions {
+    /// Options for hotspots recorded as memories: only files changed at
+    /// least ten times, with recent changes weighing more.
+    pub fn for_memories() -> Self {
+        Self {
+            threshold: 10,
+            half_life_days: Some(HOTSPOT_HALF_LIFE_DAYS),
+            ..Self::default()
+        }
+    }
+
+    /// Read the hotspot window of a mining request (`hotspotsSince`,
+    /// `hotspotsUntil`, `hotspotsByDirectory`), falling back to `defaults`.
+    pub fn from_params(params: &serde_json::Value, defaults: HotspotOptions) -> Self {
+        let date = |key: &str| {
+            params
+                .get(key)
+                .and_then(|v| v.as_str())
+                .map(str::trim)
+                .filter(|date| !date.is_empty())
+                .map(str::to_string)
+        };
+        Self {
+            since: date("hotspotsSince").or(defaults.since),
+            until: date("hotspotsUntil").or(defaults.until),
+            by_directory: params
+                .get("hotspotsByDirectory")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(defaults.by_directory),
+            ..defaults
+        }
+    }
+}
+
 /// File coupling information (co-change pattern).
 #[derive(Debug, Clone)]
 pub struct FileCoupling {
@@ -91,16 +605,77 @@ pub struct FileCoupling {
     pub coupling_strength: f32,
 }
 
+/// One author's share of the changes to a file or module.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuthorShare {
+    pub author: String,
+    pub email: String,
+    /// Commits by this author that changed the file or module
+    pub commits: usize,
+    /// Fraction of the file's or module's commits by this author
+    pub share: f32,
+    /// Author date of the author's most recent change
+    pub last_change: String,
+}
+
+/// Who changed a file or module, most active author first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnershipEntry {
+    /// Repository-relative file path, or directory for modules (`.` for the
+    /// repository root)
+    pub path: String,
+    pub commits: usize,
+    pub owners: Vec<AuthorShare>,
+}
+
+/// Ownership map of a repository or part of it.
+#[derive(Debug, Clone, Default)]
+pub struct CodeOwnership {
+    /// Files or modules, most changed first
+    pub entries: Vec<OwnershipEntry>,
+    /// Authors of all the analyzed commits, most active first: the
+    /// reviewers to suggest for the whole scope
+    pub reviewers: Vec<AuthorShare>,
+    pub commits_analyzed: usize,
+}
+
+/// Files of a commit blamed to find the symbols it touched; the rest are
+/// linked by their diff hunks alone
+const MAX_BLAMED_FILES: usize = 20;
+
+/// Symbols linked to a memory per changed file
+const MAX_LINKS_PER_FILE: usize = 5;
+
 /// Git history miner that extracts memories from commit history.
 pub struct GitMiner {
     executor: GitExecutor,
+    /// Blame of the current version of each file, by repository-relative
+    /// path, shared by the commits of a run
+    blame_cache: Mutex<HashMap<String, Vec<parser::BlameLine>>>,
+    /// Commit classification, with the workspace's `.codegraph/mining.toml`
+    patterns: CommitPatterns,
+    /// Why the workspace patterns could not be loaded, reported as a
+    /// warning of each run
+    patterns_error: Option<String>,
 }
 
 impl GitMiner {
     /// Create a new git miner for the given repository.
     pub fn new(repo_path: &Path) -> Result<Self, GitMiningError> {
         let executor = GitExecutor::new(repo_path)?;
-        Ok(Self { executor })
+        let (patterns, patterns_error) = match CommitPatterns::load(repo_path) {
+            Ok(patterns) => (patterns, None),
+            Err(e) => {
+                tracing::warn!("{}; using the built-in commit patterns", e);
+                (CommitPatterns::default(), Some(e))
+            }
+        };
+        Ok(Self {
+            executor,
+            blame_cache: Mutex::new(HashMap::new()),
+            patterns,
+            patterns_error,
+        })
     }
 
     /// Mine repository history and create memories.
@@ -109,52 +684,113 @@ impl GitMiner {
         memory_manager: &MemoryManager,
         graph: &Arc<RwLock<CodeGraph>>,
         config: &MiningConfig,
+        progress: Option<&dyn MiningProgress>,
     ) -> Result<MiningResult, GitMiningError> {
-        let mut result = MiningResult::default();
+        let mut result = MiningResult {
+            candidates: config.dry_run.then(Vec::new),
+            ..MiningResult::default()
+        };
+        result.warnings.extend(self.patterns_error.clone());
 
         // Collect already-mined commit hashes to avoid duplicates
         let already_mined = Self::collect_mined_commits(memory_manager).await;
+        let enricher = self.pull_request_enricher(config, &mut result);
+
+        // Only the commits since the last run, unless a full scan or another
+        // branch is asked for or history was rewritten past the last mined
+        // commit
+        let repo_path = self.executor.repo_path();
+        let since = if config.full || config.branch.is_some() {
+            None
+        } else {
+            memory_manager
+                .last_mined_commit(repo_path)
+                .await
+                .filter(|commit| self.executor.is_ancestor_of_head(commit))
+        };
+        let head = self.executor.head_commit().ok();
 
         // Collect commits matching our patterns
-        let commits = self.collect_relevant_commits(config)?;
+        let commits = self.collect_relevant_commits(config, since.as_deref(), None)?;
         result.commits_processed = commits.len();
+        result.since_commit = since;
 
         tracing::info!(
-            "Found {} relevant commits to process ({} already mined)",
+            "Found {} relevant commits to process ({} already mined, since {})",
             commits.len(),
-            already_mined.len()
+            already_mined.len(),
+            result.since_commit.as_deref().unwrap_or("the start")
         );
+        // With more new commits than `max_commits`, the oldest ones are mined
+        // and the mark stops at the newest of them
+        let mut mark = head;
+        if result.since_commit.is_some() && commits.len() == config.max_commits {
+            mark = commits.first().map(|(commit, _)| commit.hash.clone());
+            result.warnings.push(format!(
+                "More than {} new commits since the last run; newer ones are mined by the next run",
+                config.max_commits
+            ));
+        }
 
         // Process each commit
-        for commit in commits {
-            match self
-                .process_commit(&commit, memory_manager, graph, config, &already_mined)
-                .await
-            {
-                Ok(Some(memory_id)) => {
-                    result.memories_created += 1;
-                    result.memory_ids.push(memory_id);
-                }
-                Ok(None) => {
-                    result.commits_skipped += 1;
-                }
-                Err(e) => {
-                    result.warnings.push(format!(
-                        "Failed to process commit {}: {}",
-                        &commit.hash[..7],
-                        e
-                    ));
-                }
+        let total = commits.len();
+        let mut processed_count = total;
+        for (index, (commit, branch)) in commits.iter().enumerate() {
+            if progress.is_some_and(|p| p.is_cancelled()) {
+                result
+                    .warnings
+                    .push(format!("Mining cancelled after {index} of {total} commits"));
+                processed_count = index;
+                break;
+            }
+            let processed = self
+                .process_commit(
+                    commit,
+                    memory_manager,
+                    graph,
+                    config,
+                    &already_mined,
+                    enricher.as_ref(),
+                    branch.as_deref(),
+                )
+                .await;
+            if let Some(progress) = progress {
+                progress.commit_processed(index + 1, total);
             }
+            result.record(commit, processed);
+        }
+
+        result.record_enrichment(enricher.as_deref());
+        if !config.dry_run {
+            self.apply_reverts(&commits[..processed_count], memory_manager, &mut result)
+                .await;
+        }
+        let cancelled = processed_count < total;
+        if config.mine_releases && !cancelled {
+            self.mine_releases(&commits, memory_manager, config.dry_run, &mut result)
+                .await;
         }
 
         tracing::info!(
-            "Mining complete: {} memories created from {} commits ({} skipped)",
+            "Mining complete: {} memories created from {} commits ({} grouped, {} skipped)",
             result.memories_created,
             result.commits_processed,
+            result.commits_grouped,
             result.commits_skipped
         );
 
+        // The next run starts here; a cancelled run is resumed from the
+        // previous mark, with the commits it mined skipped as already mined.
+        // The mark follows the checked-out branch, so other branches leave it
+        if let (Some(mark), false, None, false) = (mark, cancelled, &config.branch, config.dry_run)
+        {
+            if let Err(e) = memory_manager.set_last_mined_commit(repo_path, &mark).await {
+                result
+                    .warnings
+                    .push(format!("Failed to record the last mined commit: {}", e));
+            }
+        }
+
         Ok(result)
     }
 
@@ -165,17 +801,20 @@ impl GitMiner {
         memory_manager: &MemoryManager,
         graph: &Arc<RwLock<CodeGraph>>,
         config: &MiningConfig,
+        progress: Option<&dyn MiningProgress>,
     ) -> Result<MiningResult, GitMiningError> {
-        let mut result = MiningResult::default();
+        let mut result = MiningResult {
+            candidates: config.dry_run.then(Vec::new),
+            ..MiningResult::default()
+        };
+        result.warnings.extend(self.patterns_error.clone());
 
         // Collect already-mined commit hashes to avoid duplicates
         let already_mined = Self::collect_mined_commits(memory_manager).await;
+        let enricher = self.pull_request_enricher(config, &mut result);
 
         // Get commits that touched this file
-        let output = self
-            .executor
-            .log(LOG_FORMAT, Some(config.max_commits), Some(file_path))?;
-        let commits = parser::parse_log_output(&output)?;
+        let commits = self.collect_relevant_commits(config, None, Some(file_path))?;
         result.commits_processed = commits.len();
 
         tracing::info!(
@@ -185,32 +824,67 @@ impl GitMiner {
             already_mined.len()
         );
 
-        for commit in commits {
-            match self
-                .process_commit(&commit, memory_manager, graph, config, &already_mined)
-                .await
-            {
-                Ok(Some(memory_id)) => {
-                    result.memories_created += 1;
-                    result.memory_ids.push(memory_id);
-                }
-                Ok(None) => {
-                    result.commits_skipped += 1;
-                }
-                Err(e) => {
-                    result.warnings.push(format!(
-                        "Failed to process commit {}: {}",
-                        &commit.hash[..7],
-                        e
-                    ));
-                }
+        let total = commits.len();
+        let mut processed_count = total;
+        for (index, (commit, branch)) in commits.iter().enumerate() {
+            if progress.is_some_and(|p| p.is_cancelled()) {
+                result
+                    .warnings
+                    .push(format!("Mining cancelled after {index} of {total} commits"));
+                processed_count = index;
+                break;
+            }
+            let processed = self
+                .process_commit(
+                    commit,
+                    memory_manager,
+                    graph,
+                    config,
+                    &already_mined,
+                    enricher.as_ref(),
+                    branch.as_deref(),
+                )
+                .await;
+            if let Some(progress) = progress {
+                progress.commit_processed(index + 1, total);
             }
+            result.record(commit, processed);
+        }
+        result.record_enrichment(enricher.as_deref());
+        if !config.dry_run {
+            self.apply_reverts(&commits[..processed_count], memory_manager, &mut result)
+                .await;
         }
 
         Ok(result)
     }
 
-    /// Collect commit hashes that have already been mined into memories.
+    /// The pull request enricher for this repository, if `config` asks for
+    /// one. Why it is not available becomes a warning.
+    fn pull_request_enricher(
+        &self,
+        config: &MiningConfig,
+        result: &mut MiningResult,
+    ) -> Option<Arc<PullRequestEnricher>> {
+        if !config.enrich_pull_requests {
+            return None;
+        }
+        let enricher = self
+            .executor
+            .remote_url("origin")
+            .map_err(|e| format!("Pull request enrichment needs an origin remote: {}", e))
+            .and_then(|url| PullRequestEnricher::from_remote(&url));
+        match enricher {
+            Ok(enricher) => Some(Arc::new(enricher)),
+            Err(e) => {
+                result.warnings.push(e);
+                None
+            }
+        }
+    }
+
+    /// Collect commit hashes that have already been mined into memories,
+    /// including the commits grouped into another commit's memory.
     async fn collect_mined_commits(
         memory_manager: &MemoryManager,
     ) -> std::collections::HashSet<String> {
@@ -221,31 +895,87 @@ impl GitMiner {
 
         memories
             .into_iter()
-            .filter_map(|m| {
-                if let MemorySource::GitHistory { commit_hash } = m.source {
-                    Some(commit_hash)
-                } else {
-                    None
+            .flat_map(|m| match m.source {
+                MemorySource::GitHistory { commit_hash } => {
+                    let mut hashes: Vec<String> = grouped_commits(&m.content).collect();
+                    hashes.push(commit_hash);
+                    hashes
                 }
+                _ => Vec::new(),
             })
             .collect()
     }
 
-    /// Collect recent commits for mining.
+    /// Collect recent commits for mining, newest first, with the branch each
+    /// was mined from: the last `max_commits`, or the first `max_commits`
+    /// after `since` if given.
     ///
-    /// Fetches the last `max_commits` commits directly (no grep filter),
-    /// so commits with non-conventional messages are still processed.
+    /// Fetches the commits directly (no grep filter), so commits with
+    /// non-conventional messages are still processed.
     fn collect_relevant_commits(
         &self,
         config: &MiningConfig,
-    ) -> Result<Vec<CommitInfo>, GitMiningError> {
-        let output = self
+        since: Option<&str>,
+        path_filter: Option<&Path>,
+    ) -> Result<Vec<(CommitInfo, Option<String>)>, GitMiningError> {
+        if let Some(branch) = &config.branch {
+            let revisions = self.branch_revisions(branch)?;
+            let output = self.executor.log_revisions(
+                SOURCE_LOG_FORMAT,
+                &revisions,
+                Some(config.max_commits),
+                path_filter,
+            )?;
+            return parser::parse_source_log_output(&output);
+        }
+
+        let commits = match since {
+            // All of them, so that the oldest are mined first and the mark
+            // never moves past commits that were not mined
+            Some(since) => {
+                let output = self.executor.log_since(LOG_FORMAT, since, None)?;
+                let mut commits = parser::parse_log_output(&output)?;
+                let newer = commits.len().saturating_sub(config.max_commits);
+                commits.drain(..newer);
+                commits
+            }
+            None => {
+                let output =
+                    self.executor
+                        .log(LOG_FORMAT, Some(config.max_commits), path_filter)?;
+                parser::parse_log_output(&output)?
+            }
+        };
+        // Detached HEAD has no branch to record
+        let checked_out = self
             .executor
-            .log(LOG_FORMAT, Some(config.max_commits), None)?;
-        parser::parse_log_output(&output)
+            .current_branch()
+            .ok()
+            .filter(|branch| branch != "HEAD");
+        Ok(commits
+            .into_iter()
+            .map(|commit| (commit, checked_out.clone()))
+            .collect())
+    }
+
+    /// The revisions to log for a `branch` setting: the branches a glob
+    /// matches, or the branch or range as given.
+    fn branch_revisions(&self, branch: &str) -> Result<Vec<String>, GitMiningError> {
+        if branch.contains("..") || !branch.contains(['*', '?', '[']) {
+            return Ok(vec![branch.to_string()]);
+        }
+        let branches = self.executor.branches_matching(branch)?;
+        if branches.is_empty() {
+            return Err(GitMiningError::CommandFailed(format!(
+                "No branches match {}",
+                branch
+            )));
+        }
+        Ok(branches)
     }
 
     /// Process a single commit and optionally create a memory.
+    #[allow(clippy::too_many_arguments)]
     async fn process_commit(
         &self,
         commit: &CommitInfo,
@@ -253,14 +983,163 @@ impl GitMiner {
         graph: &Arc<RwLock<CodeGraph>>,
         config: &MiningConfig,
         already_mined: &std::collections::HashSet<String>,
-    ) -> Result<Option<String>, GitMiningError> {
+        enricher: Option<&Arc<PullRequestEnricher>>,
+        branch: Option<&str>,
+    ) -> Result<Vec<CommitOutcome>, GitMiningError> {
         // Skip commits that have already been mined
         if already_mined.contains(&commit.hash) {
-            return Ok(None);
+            return Ok(Vec::new());
+        }
+
+        // Knowledge the author wrote down as trailers is kept verbatim
+        let mut outcomes = Vec::new();
+        if config.mine_trailers {
+            for trailer in commit.memory_trailers() {
+                let outcome = self
+                    .store_trailer(commit, &trailer, memory_manager, config, branch)
+                    .await?;
+                outcomes.push(outcome);
+            }
+        }
+
+        // A squash merge lists the messages of the commits it squashed; each
+        // is classified and mined on its own, and the squash as a whole only
+        // if none of them is
+        let squashed = commit.squashed_commits();
+        if !squashed.is_empty() {
+            let mut squashed_outcomes = Vec::new();
+            for squashed_commit in &squashed {
+                let outcome = self
+                    .process_message(
+                        squashed_commit,
+                        memory_manager,
+                        graph,
+                        config,
+                        enricher,
+                        branch,
+                    )
+                    .await?;
+                squashed_outcomes.extend(outcome);
+            }
+            if !squashed_outcomes.is_empty() {
+                outcomes.push(CommitOutcome::Squashed(squashed_outcomes));
+                return Ok(outcomes);
+            }
+        }
+
+        let outcome = self
+            .process_message(commit, memory_manager, graph, config, enricher, branch)
+            .await?;
+        outcomes.extend(outcome);
+        Ok(outcomes)
+    }
+
+    /// Store `trailer` of `commit` as a memory, word for word.
+    async fn store_trailer(
+        &self,
+        commit: &CommitInfo,
+        trailer: &MemoryTrailer,
+        memory_manager: &MemoryManager,
+        config: &MiningConfig,
+        branch: Option<&str>,
+    ) -> Result<CommitOutcome, GitMiningError> {
+        let mut builder = match trailer.kind {
+            TrailerKind::Decision => MemoryNode::builder()
+                .architectural_decision(&trailer.text, &commit.subject)
+                .title(format!("[Decision] {}", trailer.text))
+                .tag("architecture"),
+            TrailerKind::Memory => MemoryNode::builder()
+                .project_context(&commit.subject, &trailer.text)
+                .title(format!("[Memory] {}", trailer.text)),
+        };
+        builder = builder
+            .content(format!(
+                "Commit: {}\nAuthor: {} <{}>\nDate: {}\n\n{}",
+                commit.hash,
+                commit.author_name,
+                commit.author_email,
+                commit.author_date,
+                trailer.text
+            ))
+            .from_git(&commit.hash)
+            .at_commit(&commit.hash)
+            .tag("git-mined")
+            .tag("trailer")
+            .confidence(0.95);
+        if let Some(branch) = branch {
+            builder = builder.tag(format!("branch:{}", branch));
+        }
+
+        let memory = builder
+            .build()
+            .map_err(|e| GitMiningError::MemoryError(format!("Failed to build memory: {}", e)))?;
+        let outcome = self.store(memory, memory_manager, config.dry_run).await?;
+        Ok(match outcome {
+            CommitOutcome::Created(id) => CommitOutcome::Trailer(id),
+            outcome => outcome,
+        })
+    }
+
+    /// Store `memory` in the mined workspace folder's store, or on a dry run
+    /// only describe it.
+    async fn store(
+        &self,
+        memory: MemoryNode,
+        memory_manager: &MemoryManager,
+        dry_run: bool,
+    ) -> Result<CommitOutcome, GitMiningError> {
+        if dry_run {
+            return Ok(CommitOutcome::Candidate(MemoryCandidate::from_memory(
+                &memory,
+            )));
         }
+        let id = memory_manager
+            .put_for_path(memory, self.executor.repo_path())
+            .await?;
+        Ok(CommitOutcome::Created(id))
+    }
 
+    /// Classify the message of `commit` and create a memory from it, or add
+    /// it to the memory of an earlier fix for the same issue.
+    async fn process_message(
+        &self,
+        commit: &CommitInfo,
+        memory_manager: &MemoryManager,
+        graph: &Arc<RwLock<CodeGraph>>,
+        config: &MiningConfig,
+        enricher: Option<&Arc<PullRequestEnricher>>,
+        branch: Option<&str>,
+    ) -> Result<Option<CommitOutcome>, GitMiningError> {
         // Detect pattern
-        let (pattern, confidence) = parser::detect_pattern(commit);
+        let (mut pattern, mut confidence, mut pattern_tags) = self.patterns.detect(commit);
+
+        // Fetch the pull request of a merge commit, or of a squash merge that
+        // will be mined anyway
+        let pull_request = match (enricher, commit.pull_request_number()) {
+            (Some(enricher), Some(number))
+                if commit.is_merge()
+                    || (self.should_process_pattern(&pattern, config)
+                        && confidence >= config.min_confidence) =>
+            {
+                // Up to three requests of several seconds each; keep them off
+                // the async workers
+                let enricher = Arc::clone(enricher);
+                tokio::task::spawn_blocking(move || enricher.fetch(number))
+                    .await
+                    .ok()
+                    .flatten()
+            }
+            _ => None,
+        };
+
+        // A merge commit's own message only names the branch; its pull
+        // request says what it does
+        let mut info = commit.clone();
+        if let Some(pull_request) = pull_request.as_ref().filter(|_| commit.is_merge()) {
+            info.subject = pull_request.title.clone();
+            info.body = pull_request.description.trim().to_string();
+            (pattern, confidence, pattern_tags) = self.patterns.detect(&info);
+        }
 
         // Check if we should process this pattern
         if !self.should_process_pattern(&pattern, config) {
@@ -276,11 +1155,21 @@ impl GitMiner {
         let files_changed = self.executor.show_files(&commit.hash)?;
 
         // Create parsed commit
+        let mut issue_refs = commit.issue_refs();
+        if let Some(pull_request) = &pull_request {
+            let text = format!("{}\n{}", pull_request.title, pull_request.description);
+            for issue_ref in parser::extract_issue_references(&text) {
+                if !issue_refs.contains(&issue_ref) {
+                    issue_refs.push(issue_ref);
+                }
+            }
+        }
         let parsed = ParsedCommit {
-            info: commit.clone(),
+            info,
             pattern: pattern.clone(),
             files_changed: files_changed.clone(),
             confidence,
+            issue_refs,
         };
 
         // Get memory kind
@@ -290,24 +1179,37 @@ impl GitMiner {
         };
 
         // Find code nodes to link to
-        let code_links = self.find_code_links(&files_changed, graph).await;
+        let code_links = self
+            .find_code_links(&commit.hash, &files_changed, graph)
+            .await;
+
+        // A later commit fixing the same issue extends the earlier fix's memory
+        if matches!(pattern, CommitPattern::BugFix { .. }) {
+            if let Some(id) = self
+                .group_with_issue_fix(
+                    &parsed,
+                    pull_request.as_ref(),
+                    &code_links,
+                    memory_manager,
+                    config.dry_run,
+                )
+                .await?
+            {
+                tracing::debug!(
+                    "Grouped commit {} into memory {} ({})",
+                    &commit.hash[..7],
+                    id,
+                    parsed.issue_refs.join(", ")
+                );
+                return Ok(Some(CommitOutcome::Grouped(id)));
+            }
+        }
 
         // Build the memory
         let mut builder = MemoryNode::builder()
             .kind(memory_kind)
-            .title(format!("[Git] {}", commit.subject))
-            .content(format!(
-                "Commit: {}\nAuthor: {} <{}>\nDate: {}\n\n{}",
-                commit.hash,
-                commit.author_name,
-                commit.author_email,
-                commit.author_date,
-                if commit.body.is_empty() {
-                    &commit.subject
-                } else {
-                    &commit.body
-                }
-            ))
+            .title(format!("[Git] {}", parsed.info.subject))
+            .content(commit_content(&parsed, pull_request.as_ref()))
             .from_git(&commit.hash)
             .at_commit(&commit.hash)
             .tag("git-mined")
@@ -315,92 +1217,349 @@ impl GitMiner {
             .confidence(confidence);
 
         // Add pattern-specific tag
-        builder = match pattern {
+        builder = match &pattern {
             CommitPattern::BugFix { .. } => builder.tag("bug-fix"),
             CommitPattern::ArchitecturalDecision => builder.tag("architecture"),
             CommitPattern::BreakingChange => builder.tag("breaking-change"),
+            CommitPattern::Revert {
+                reverted_hash: Some(reverted_hash),
+            } => builder.tag("revert").tag(reverts_tag(reverted_hash)),
             CommitPattern::Revert { .. } => builder.tag("revert"),
             _ => builder,
         };
+        for issue_ref in &parsed.issue_refs {
+            builder = builder.tag(issue_ref_tag(issue_ref));
+        }
+        for tag in pattern_tags {
+            builder = builder.tag(tag.clone());
+        }
+        if let Some(branch) = branch {
+            builder = builder.tag(format!("branch:{}", branch));
+        }
+        // Conventional commit scope, e.g. `fix(auth): ...`
+        if let Some(scope) = parsed.info.conventional().and_then(|c| c.scope) {
+            builder = builder.tag(format!("scope:{}", scope.to_lowercase()));
+        }
 
         // Add code links
-        for (node_id, node_type) in code_links {
-            builder = builder.link_to_code(&node_id, node_type);
-        }
+        builder = builder.code_links(code_links);
 
         let memory = builder
             .build()
             .map_err(|e| GitMiningError::MemoryError(format!("Failed to build memory: {}", e)))?;
 
-        // Store the memory
-        let id = memory_manager.put(memory).await?;
+        let outcome = self.store(memory, memory_manager, config.dry_run).await?;
+        if let CommitOutcome::Created(id) = &outcome {
+            tracing::debug!(
+                "Created memory {} from commit {} ({})",
+                id,
+                &commit.hash[..7],
+                commit.subject
+            );
+        }
 
-        tracing::debug!(
-            "Created memory {} from commit {} ({})",
-            id,
-            &commit.hash[..7],
-            commit.subject
-        );
+        Ok(Some(outcome))
+    }
+
+    /// Invalidate the memories of commits reverted by any of `commits`
+    /// (newest first), and restore those whose revert was itself reverted.
+    ///
+    /// Reverts are applied oldest first, whether or not revert commits are
+    /// mined themselves, so that a revert of a revert finds the memories the
+    /// first revert invalidated. Memories are loaded once for all of them.
+    async fn apply_reverts(
+        &self,
+        commits: &[(CommitInfo, Option<String>)],
+        memory_manager: &MemoryManager,
+        result: &mut MiningResult,
+    ) {
+        let reverts: Vec<(String, String)> = commits
+            .iter()
+            .rev()
+            .filter_map(|(commit, _)| match self.patterns.detect(commit).0 {
+                CommitPattern::Revert {
+                    reverted_hash: Some(hash),
+                } => Some((hash, commit.hash.clone())),
+                _ => None,
+            })
+            .collect();
+        if reverts.is_empty() {
+            return;
+        }
+        let mut memories = match memory_manager.get_all_memories(false).await {
+            Ok(memories) => memories,
+            Err(e) => {
+                result
+                    .warnings
+                    .push(format!("Failed to load memories to apply reverts: {}", e));
+                return;
+            }
+        };
+
+        let changed = apply_revert_commits(&mut memories, &reverts, result);
+        let repo_path = self.executor.repo_path();
+        for (_, memory) in memories
+            .into_iter()
+            .enumerate()
+            .filter(|(index, _)| changed.contains(index))
+        {
+            let id = memory.id.to_string();
+            if let Err(e) = memory_manager.put_for_path(memory, repo_path).await {
+                result
+                    .warnings
+                    .push(format!("Failed to update reverted memory {}: {}", id, e));
+            }
+        }
+    }
+
+    /// Store a project context memory for each release not mined before:
+    /// the annotated tags, then the release commits among `commits` that no
+    /// tag marks. Each is valid from its release date and carries the tag
+    /// or commit message and the lines the release added to changelogs.
+    async fn mine_releases(
+        &self,
+        commits: &[(CommitInfo, Option<String>)],
+        memory_manager: &MemoryManager,
+        dry_run: bool,
+        result: &mut MiningResult,
+    ) {
+        let tags = match self.executor.tags(TAG_FORMAT, MAX_RELEASE_TAGS) {
+            Ok(output) => parser::parse_tag_output(&output),
+            Err(e) => {
+                result
+                    .warnings
+                    .push(format!("Failed to list release tags: {}", e));
+                Vec::new()
+            }
+        };
+
+        // Releases with their messages; notes are read only for new ones
+        let mut releases: Vec<(Release, String)> = Vec::new();
+        for tag in &tags {
+            let version = match tag.name.strip_prefix('v') {
+                Some(rest) if rest.starts_with(|c: char| c.is_ascii_digit()) => rest,
+                _ => &tag.name,
+            };
+            let release = Release {
+                version: version.to_string(),
+                commit: tag.commit.clone(),
+                timestamp: tag.timestamp,
+                notes: Vec::new(),
+            };
+            releases.push((release, format!("{}\n{}", tag.subject, tag.body)));
+        }
+        for (commit, _) in commits {
+            let Some(version) = commit.release_version() else {
+                continue;
+            };
+            if releases
+                .iter()
+                .any(|(r, _)| r.version == version || r.commit == commit.hash)
+            {
+                continue;
+            }
+            let Ok(timestamp) = self.executor.commit_timestamp(&commit.hash) else {
+                continue;
+            };
+            let release = Release {
+                version,
+                commit: commit.hash.clone(),
+                timestamp,
+                notes: Vec::new(),
+            };
+            releases.push((release, commit.body.clone()));
+        }
+
+        for (mut release, message) in releases {
+            match memory_manager
+                .find_by_tag(&release_tag(&release.version))
+                .await
+            {
+                Ok(existing) if !existing.is_empty() => continue,
+                Ok(_) => {}
+                Err(e) => {
+                    result.warnings.push(format!(
+                        "Failed to store release {}: {}",
+                        release.version, e
+                    ));
+                    continue;
+                }
+            }
+            let changelog = self.changelog_lines(&release.commit);
+            release.notes = release_notes(&release.version, &message, &changelog);
+            match self.store_release(&release, memory_manager, dry_run).await {
+                Ok(outcome) => {
+                    result.record_outcome(outcome);
+                    result.releases_mined += 1;
+                }
+                Err(e) => result.warnings.push(format!(
+                    "Failed to store release {}: {}",
+                    release.version, e
+                )),
+            }
+        }
+    }
+
+    /// Store the memory of `release`.
+    async fn store_release(
+        &self,
+        release: &Release,
+        memory_manager: &MemoryManager,
+        dry_run: bool,
+    ) -> Result<CommitOutcome, GitMiningError> {
+        let tag = release_tag(&release.version);
+        let summary = release_summary(release);
+        let memory = MemoryNode::builder()
+            .project_context(format!("Release {}", release.version), summary)
+            .title(format!("[Release] {}", release.version))
+            .content(format!(
+                "Commit: {}\nVersion: {}\n\n{}",
+                release.commit,
+                release.version,
+                release.notes.join("\n")
+            ))
+            .at_commit(&release.commit)
+            .at_version(&release.version)
+            .valid_since(release.timestamp)
+            .tag("release")
+            .tag(tag)
+            .tag("git-mined")
+            .confidence(0.8)
+            .build()
+            .map_err(|e| GitMiningError::MemoryError(format!("Failed to build memory: {}", e)))?;
+        self.store(memory, memory_manager, dry_run).await
+    }
+
+    /// Lines `commit` added to changelogs; none if it touched none.
+    fn changelog_lines(&self, commit: &str) -> Vec<String> {
+        let files = self.executor.show_files(commit).unwrap_or_default();
+        files
+            .iter()
+            .filter(|file| is_changelog(file))
+            .filter_map(|file| self.executor.show_file_diff(commit, file).ok())
+            .flat_map(|diff| parser::parse_added_lines(&diff))
+            .collect()
+    }
 
-        Ok(Some(id))
+    /// Add `parsed` to the current bug-fix memory of an issue it refers to,
+    /// if there is one. Returns the ID of the memory it was added to.
+    async fn group_with_issue_fix(
+        &self,
+        parsed: &ParsedCommit,
+        pull_request: Option<&PullRequestInfo>,
+        code_links: &[CodeLink],
+        memory_manager: &MemoryManager,
+        dry_run: bool,
+    ) -> Result<Option<String>, GitMiningError> {
+        for issue_ref in &parsed.issue_refs {
+            let fixes = memory_manager
+                .find_by_tag(&issue_ref_tag(issue_ref))
+                .await?;
+            let Some(mut memory) = fixes
+                .into_iter()
+                .find(|m| m.is_current() && m.tags.iter().any(|tag| tag == "bug-fix"))
+            else {
+                continue;
+            };
+
+            memory.content.push_str(&format!(
+                "\n\n---\n\n{}",
+                commit_content(parsed, pull_request)
+            ));
+            for issue_ref in &parsed.issue_refs {
+                let tag = issue_ref_tag(issue_ref);
+                if !memory.tags.contains(&tag) {
+                    memory.tags.push(tag);
+                }
+            }
+            for link in code_links {
+                if !memory
+                    .code_links
+                    .iter()
+                    .any(|existing| existing.node_id == link.node_id)
+                {
+                    memory.code_links.push(link.clone());
+                }
+            }
+            memory.confidence = memory.confidence.max(parsed.confidence);
+            // Re-embedded for the longer content when stored
+            memory.embedding = None;
+
+            if dry_run {
+                return Ok(Some(memory.id.to_string()));
+            }
+            let id = memory_manager
+                .put_for_path(memory, self.executor.repo_path())
+                .await?;
+            return Ok(Some(id));
+        }
+        Ok(None)
     }
 
-    /// Detect code hotspots (high-churn files) in repository history.
+    /// Detect code hotspots (high-churn files or directories) in the
+    /// repository history `options` selects, busiest first.
     pub async fn detect_hotspots(
         &self,
-        threshold: usize,
+        options: &HotspotOptions,
     ) -> Result<Vec<ChurnHotspot>, GitMiningError> {
-        // Get all commits
-        let output = self.executor.log(
-            parser::LOG_FORMAT,
-            None, // No limit
-            None, // All files
+        let output = self.executor.log_window(
+            TIMED_LOG_FORMAT,
+            options.max_commits,
+            options.since.as_deref(),
+            options.until.as_deref(),
         )?;
-        let commits = parser::parse_log_output(&output)?;
+        let commits = parser::parse_timed_log_output(&output);
 
-        // Track file changes
-        let mut file_changes: std::collections::HashMap<String, FileChurnData> =
-            std::collections::HashMap::new();
+        let mut changes = Vec::with_capacity(commits.len());
+        for (commit, timestamp) in &commits {
+            let files = self.executor.show_files(&commit.hash)?;
+            changes.push((commit, *timestamp, files));
+        }
+
+        Ok(aggregate_churn(&changes, options))
+    }
 
+    /// Aggregate who changed each file (or, `by_module`, each directory) in
+    /// the most recent `max_commits` commits, restricted to `path_filter`
+    /// (repository-relative) if given.
+    pub async fn detect_ownership(
+        &self,
+        max_commits: Option<usize>,
+        path_filter: Option<&str>,
+        by_module: bool,
+    ) -> Result<CodeOwnership, GitMiningError> {
+        let output = self
+            .executor
+            .log(LOG_FORMAT, max_commits, path_filter.map(Path::new))?;
+        let commits = parser::parse_log_output(&output)?;
+
+        let mut changes = Vec::with_capacity(commits.len());
         for commit in &commits {
-            let files = self.executor.show_files(&commit.hash)?;
-            for file in files {
-                let data = file_changes.entry(file.clone()).or_insert(FileChurnData {
-                    path: file.clone(),
-                    change_count: 0,
-                    unique_commits: std::collections::HashSet::new(),
-                    recent_changes: Vec::new(),
-                });
-                data.change_count += 1;
-                data.unique_commits.insert(commit.hash.clone());
-                if data.recent_changes.len() < 5 {
-                    data.recent_changes.push(commit.subject.clone());
+            let mut files = self.executor.show_files(&commit.hash)?;
+            if let Some(filter) = path_filter.map(|f| f.trim_end_matches('/')) {
+                let prefix = format!("{}/", filter);
+                if !filter.is_empty() {
+                    files.retain(|file| file == filter || file.starts_with(&prefix));
                 }
             }
+            changes.push((commit, files));
         }
 
-        // Filter and convert to hotspots
-        let mut hotspots: Vec<ChurnHotspot> = file_changes
-            .into_iter()
-            .filter(|(_, data)| data.change_count >= threshold)
-            .map(|(_, data)| ChurnHotspot {
-                file_path: data.path,
-                change_count: data.change_count,
-                unique_commits: data.unique_commits.len(),
-                recent_changes: data.recent_changes,
-            })
-            .collect();
-
-        // Sort by change count descending
-        hotspots.sort_by(|a, b| b.change_count.cmp(&a.change_count));
+        Ok(aggregate_ownership(&changes, by_module))
+    }
 
-        Ok(hotspots)
+    /// Per-line authorship of a file from `git blame`.
+    pub fn blame_file(&self, file_path: &Path) -> Result<Vec<parser::BlameLine>, GitMiningError> {
+        let output = self.executor.blame(file_path, None)?;
+        Ok(parser::parse_blame_output(&output))
     }
 
-    /// Detect file coupling (files that frequently change together).
+    /// Detect file coupling (files that frequently change together). With a
+    /// `graph`, coupled files are also linked by co-change edges.
     pub async fn detect_coupling(
         &self,
         min_coupling: f32,
+        graph: Option<&Arc<RwLock<CodeGraph>>>,
     ) -> Result<Vec<FileCoupling>, GitMiningError> {
         // Get all commits
         let output = self.executor.log(
@@ -465,9 +1624,304 @@ impl GitMiner {
                 .unwrap_or(std::cmp::Ordering::Equal)
         });
 
+        if let Some(graph) = graph {
+            let linked = self.add_co_change_edges(&couplings, graph).await;
+            tracing::debug!("Linked {} co-changing file pairs in the graph", linked);
+        }
+
         Ok(couplings)
     }
 
+    /// Link the file nodes of each of `couplings` both ways with `References`
+    /// edges of kind [`node_props::CO_CHANGE_KIND`], weighted by their
+    /// co-change count and coupling strength, so
+    /// that traversals and impact analysis see historical coupling next to
+    /// static imports. Pairs already linked keep their edges until one of
+    /// the files is re-indexed. Returns how many pairs were linked.
+    async fn add_co_change_edges(
+        &self,
+        couplings: &[FileCoupling],
+        graph: &Arc<RwLock<CodeGraph>>,
+    ) -> usize {
+        let repo_path = self.executor.repo_path();
+        let mut graph = graph.write().await;
+        let mut linked = 0;
+        for coupling in couplings {
+            let (Some(file_a), Some(file_b)) = (
+                symbols::file_node(&graph, repo_path, &coupling.file_a),
+                symbols::file_node(&graph, repo_path, &coupling.file_b),
+            ) else {
+                continue;
+            };
+            let already_linked = graph
+                .get_edges_between(file_a, file_b)
+                .unwrap_or_default()
+                .into_iter()
+                .any(|edge_id| {
+                    graph.get_edge(edge_id).is_ok_and(|edge| {
+                        node_props::is_co_change(edge.edge_type, &edge.properties)
+                    })
+                });
+            if already_linked {
+                continue;
+            }
+
+            let weights = || {
+                PropertyMap::new()
+                    .with(
+                        "kind",
+                        PropertyValue::String(node_props::CO_CHANGE_KIND.to_string()),
+                    )
+                    .with(
+                        "co_changes",
+                        PropertyValue::Int(coupling.co_change_count as i64),
+                    )
+                    .with(
+                        "total_changes",
+                        PropertyValue::Int(coupling.total_changes as i64),
+                    )
+                    .with(
+                        "strength",
+                        PropertyValue::Float(coupling.coupling_strength as f64),
+                    )
+            };
+            if graph
+                .add_edge(file_a, file_b, EdgeType::References, weights())
+                .and_then(|_| graph.add_edge(file_b, file_a, EdgeType::References, weights()))
+                .is_ok()
+            {
+                linked += 1;
+            }
+        }
+        linked
+    }
+
+    /// Store a project context memory for each of the busiest hotspots,
+    /// adding their IDs to `result`. Returns how many were created; a failed
+    /// detection becomes a warning.
+    pub async fn mine_hotspots(
+        &self,
+        memory_manager: &MemoryManager,
+        options: &HotspotOptions,
+        result: &mut MiningResult,
+    ) -> usize {
+        let hotspots = match self.detect_hotspots(options).await {
+            Ok(hotspots) => hotspots,
+            Err(e) => {
+                result
+                    .warnings
+                    .push(format!("Failed to detect hotspots: {}", e));
+                return 0;
+            }
+        };
+
+        let kind = if options.by_directory {
+            "directory"
+        } else {
+            "file"
+        };
+        let mut created = 0;
+        for hotspot in hotspots.iter().take(20) {
+            let memory = MemoryNode::builder()
+                .project_context(
+                    format!("High-activity {}: {}", kind, hotspot.file_path),
+                    format!(
+                        "Modified {} times across {} commits. This {} shows high churn, \
+                         indicating active development or potential complexity.",
+                        hotspot.change_count, hotspot.unique_commits, kind
+                    ),
+                )
+                .title(format!("Hotspot: {}", hotspot.file_path))
+                .content(format!(
+                    "**Change Count:** {}\n**Unique Commits:** {}\n**Recent Changes:**\n{}",
+                    hotspot.change_count,
+                    hotspot.unique_commits,
+                    hotspot.recent_changes.join("\n- ")
+                ))
+                .tag("hotspot")
+                .tag("git-mined")
+                .confidence(0.7)
+                .build()
+                .ok();
+
+            // A dry run only describes the memory
+            if let (Some(memory), Some(candidates)) = (&memory, result.candidates.as_mut()) {
+                candidates.push(MemoryCandidate::from_memory(memory));
+                continue;
+            }
+            if let Some(memory) = memory {
+                let path = self.executor.repo_path().join(&hotspot.file_path);
+                if let Ok(id) = memory_manager.put_for_path(memory, &path).await {
+                    result.memory_ids.push(id);
+                    created += 1;
+                }
+            }
+        }
+        created
+    }
+
+    /// Store a convention memory for each of the most strongly coupled file
+    /// pairs, adding their IDs to `result`, and link every coupled pair in
+    /// `graph`. Returns how many memories were created; a failed detection
+    /// becomes a warning.
+    pub async fn mine_couplings(
+        &self,
+        memory_manager: &MemoryManager,
+        graph: &Arc<RwLock<CodeGraph>>,
+        result: &mut MiningResult,
+    ) -> usize {
+        // A dry run leaves the graph as it is too
+        let link_in = Some(graph).filter(|_| result.candidates.is_none());
+        let couplings = match self.detect_coupling(0.7, link_in).await {
+            Ok(couplings) => couplings,
+            Err(e) => {
+                result
+                    .warnings
+                    .push(format!("Failed to detect coupling: {}", e));
+                return 0;
+            }
+        };
+
+        let file_name = |path: &str| path.split('/').next_back().unwrap_or(path).to_string();
+        let mut created = 0;
+        for coupling in couplings.iter().take(15) {
+            let memory = MemoryNode::builder()
+                .convention(
+                    format!("Co-change: {} ↔ {}", coupling.file_a, coupling.file_b),
+                    format!(
+                        "These files change together {:.0}% of the time ({} of {} changes). \
+                         When modifying one, consider checking the other.",
+                        coupling.coupling_strength * 100.0,
+                        coupling.co_change_count,
+                        coupling.total_changes
+                    ),
+                )
+                .title(format!(
+                    "Coupling: {} ↔ {}",
+                    file_name(&coupling.file_a),
+                    file_name(&coupling.file_b)
+                ))
+                .content(format!(
+                    "**File A:** {}\n**File B:** {}\n**Coupling Strength:** {:.1}%\n\
+                     **Co-changes:** {} out of {} total changes",
+                    coupling.file_a,
+                    coupling.file_b,
+                    coupling.coupling_strength * 100.0,
+                    coupling.co_change_count,
+                    coupling.total_changes
+                ))
+                .tag("coupling")
+                .tag("git-mined")
+                .confidence(coupling.coupling_strength)
+                .build()
+                .ok();
+
+            // A dry run only describes the memory
+            if let (Some(memory), Some(candidates)) = (&memory, result.candidates.as_mut()) {
+                candidates.push(MemoryCandidate::from_memory(memory));
+                continue;
+            }
+            if let Some(memory) = memory {
+                let path = self.executor.repo_path().join(&coupling.file_a);
+                if let Ok(id) = memory_manager.put_for_path(memory, &path).await {
+                    result.memory_ids.push(id);
+                    created += 1;
+                }
+            }
+        }
+        created
+    }
+
+    /// Store a project context memory naming the main authors of each of the
+    /// most changed modules, adding their IDs to `result`. A module that
+    /// already has one gets it replaced rather than a second memory. Returns
+    /// how many were stored; a failed detection becomes a warning.
+    pub async fn mine_ownership(
+        &self,
+        memory_manager: &MemoryManager,
+        result: &mut MiningResult,
+    ) -> usize {
+        let ownership = match self.detect_ownership(Some(500), None, true).await {
+            Ok(ownership) => ownership,
+            Err(e) => {
+                result
+                    .warnings
+                    .push(format!("Failed to detect ownership: {}", e));
+                return 0;
+            }
+        };
+
+        let mut created = 0;
+        for entry in ownership.entries.iter().filter(|e| e.commits >= 5).take(15) {
+            let owners: Vec<String> = entry
+                .owners
+                .iter()
+                .take(3)
+                .map(|owner| {
+                    format!(
+                        "{} <{}>: {} commits ({:.0}%), last {}",
+                        owner.author,
+                        owner.email,
+                        owner.commits,
+                        owner.share * 100.0,
+                        owner.last_change
+                    )
+                })
+                .collect();
+            let Some(main) = entry.owners.first() else {
+                continue;
+            };
+            let memory = MemoryNode::builder()
+                .project_context(
+                    format!("Ownership of {}", entry.path),
+                    format!(
+                        "{} made {:.0}% of the {} recent commits to {}. \
+                         Suggest them as a reviewer for changes there.",
+                        main.author,
+                        main.share * 100.0,
+                        entry.commits,
+                        entry.path
+                    ),
+                )
+                .title(format!("Ownership: {}", entry.path))
+                .content(format!(
+                    "**Module:** {}\n**Commits:** {}\n**Main authors:**\n- {}",
+                    entry.path,
+                    entry.commits,
+                    owners.join("\n- ")
+                ))
+                .tag("ownership")
+                .tag(ownership_tag(&entry.path))
+                .tag("git-mined")
+                .confidence(main.share.clamp(0.5, 0.9))
+                .build()
+                .ok();
+
+            // A dry run only describes the memory
+            if let (Some(memory), Some(candidates)) = (&memory, result.candidates.as_mut()) {
+                candidates.push(MemoryCandidate::from_memory(memory));
+                continue;
+            }
+            if let Some(mut memory) = memory {
+                // Take over the id of the module's current ownership memory
+                if let Ok(existing) = memory_manager
+                    .find_by_tag(&ownership_tag(&entry.path))
+                    .await
+                {
+                    if let Some(current) = existing.iter().find(|m| m.is_current()) {
+                        memory.id = current.id;
+                    }
+                }
+                let path = self.executor.repo_path().join(&entry.path);
+                if let Ok(id) = memory_manager.put_for_path(memory, &path).await {
+                    result.memory_ids.push(id);
+                    created += 1;
+                }
+            }
+        }
+        created
+    }
+
     /// Check if we should process a given pattern based on config.
     fn should_process_pattern(&self, pattern: &CommitPattern, config: &MiningConfig) -> bool {
         match pattern {
@@ -481,41 +1935,300 @@ impl GitMiner {
         }
     }
 
-    /// Find code graph nodes to link memories to based on changed files.
+    /// Find the code graph nodes a commit touched: the innermost functions,
+    /// classes and interfaces enclosing its changed lines (with the range of
+    /// lines it changed in each), or the file itself when no symbol encloses
+    /// them.
+    ///
+    /// Changed lines are located in the current version of each file by
+    /// blame, so they match the graph's line numbers. Lines the commit
+    /// changed that were rewritten since fall back to the commit's diff
+    /// hunks, which may have drifted.
     async fn find_code_links(
         &self,
+        commit_hash: &str,
         files: &[String],
         graph: &Arc<RwLock<CodeGraph>>,
-    ) -> Vec<(String, LinkedNodeType)> {
-        let mut links = Vec::new();
+    ) -> Vec<CodeLink> {
+        let changed = self.changed_lines(commit_hash, files);
         let graph = graph.read().await;
+        let repo_path = self.executor.repo_path();
 
-        for file in files {
-            // Query for nodes in this file
-            let repo_path = self.executor.repo_path();
-            let full_path = repo_path.join(file);
-            let path_str = full_path.to_string_lossy().to_string();
-
-            if let Ok(nodes) = graph.query().property("path", path_str).execute() {
-                for node_id in nodes.iter().take(5) {
-                    // Limit links per file
-                    // Determine node type from the graph
-                    if let Ok(node) = graph.get_node(*node_id) {
-                        let node_type = match node.node_type {
-                            codegraph::NodeType::Function => LinkedNodeType::Function,
-                            codegraph::NodeType::Class => LinkedNodeType::Class,
-                            codegraph::NodeType::Module => LinkedNodeType::Module,
-                            codegraph::NodeType::Interface => LinkedNodeType::Interface,
-                            _ => LinkedNodeType::File,
-                        };
-                        links.push((node_id.to_string(), node_type));
-                    }
+        let mut links = Vec::new();
+        for (file, lines) in changed {
+            let change = symbols::map_changed_lines(&graph, repo_path, &file, &lines);
+            if change.symbols.is_empty() {
+                if let Some(file_node) = change.file_node {
+                    links.push(CodeLink::new(file_node.to_string(), LinkedNodeType::File));
                 }
             }
+            for symbol in change.symbols.into_iter().take(MAX_LINKS_PER_FILE) {
+                links.push(
+                    CodeLink::new(symbol.node_id.to_string(), symbol.node_type)
+                        .with_line_range(symbol.first_line, symbol.last_line),
+                );
+            }
         }
 
         links
     }
+
+    /// Lines each of `files` had changed by `commit_hash`: those of the
+    /// current version it last touched per `git blame`, or, for files past
+    /// [`MAX_BLAMED_FILES`] or with no blamed lines left, its diff hunks.
+    fn changed_lines(&self, commit_hash: &str, files: &[String]) -> Vec<(String, Vec<u32>)> {
+        let mut changed: Vec<(String, Vec<u32>)> = files
+            .iter()
+            .take(MAX_BLAMED_FILES)
+            .map(|file| (file.clone(), self.blamed_lines(commit_hash, file)))
+            .collect();
+        changed.extend(
+            files
+                .iter()
+                .skip(MAX_BLAMED_FILES)
+                .map(|file| (file.clone(), Vec::new())),
+        );
+        if changed.iter().any(|(_, lines)| lines.is_empty()) {
+            let hunks = self
+                .executor
+                .show_hunks(commit_hash)
+                .map(|output| parser::parse_diff_hunks(&output))
+                .unwrap_or_default();
+            for (file, lines) in changed.iter_mut().filter(|(_, lines)| lines.is_empty()) {
+                if let Some(ranges) = hunks.get(file) {
+                    *lines = symbols::hunk_lines(ranges);
+                }
+            }
+        }
+        changed
+    }
+
+    /// Lines of the current version of `file` last changed by `commit_hash`.
+    fn blamed_lines(&self, commit_hash: &str, file: &str) -> Vec<u32> {
+        let mut cache = self.blame_cache.lock().unwrap_or_else(|e| e.into_inner());
+        let blame = cache.entry(file.to_string()).or_insert_with(|| {
+            // Deleted and binary files have no blame
+            self.executor
+                .blame(Path::new(file), None)
+                .map(|output| parser::parse_blame_output(&output))
+                .unwrap_or_default()
+        });
+        blame
+            .iter()
+            .filter(|line| line.commit == commit_hash)
+            .map(|line| line.line)
+            .collect()
+    }
+}
+
+/// Author key for ownership: the email, since one author often commits
+/// under several spellings of their name.
+fn author_key(commit: &CommitInfo) -> String {
+    if commit.author_email.is_empty() {
+        commit.author_name.to_lowercase()
+    } else {
+        commit.author_email.to_lowercase()
+    }
+}
+
+/// Directory of a repository-relative `file`; `.` for the root.
+fn module_of(file: &str) -> &str {
+    file.rsplit_once('/').map_or(".", |(dir, _)| dir)
+}
+
+/// Weight of a change `age_secs` old when weights halve every
+/// `half_life_days`.
+fn decay_weight(age_secs: i64, half_life_days: f32) -> f32 {
+    let age_days = age_secs.max(0) as f32 / 86_400.0;
+    0.5f32.powf(age_days / half_life_days)
+}
+
+/// Aggregate the files changed by each commit (newest first, with its unix
+/// timestamp) into hotspots, busiest first.
+fn aggregate_churn(
+    changes: &[(&CommitInfo, i64, Vec<String>)],
+    options: &HotspotOptions,
+) -> Vec<ChurnHotspot> {
+    let newest = changes.iter().map(|(_, timestamp, _)| *timestamp).max();
+    let mut churn: HashMap<String, FileChurnData> = HashMap::new();
+    for (commit, timestamp, files) in changes {
+        let weight = match (options.half_life_days, newest) {
+            (Some(half_life), Some(newest)) if half_life > 0.0 => {
+                decay_weight(newest - timestamp, half_life)
+            }
+            _ => 1.0,
+        };
+        let mut keys: Vec<&str> = files
+            .iter()
+            .map(|file| {
+                if options.by_directory {
+                    module_of(file)
+                } else {
+                    file.as_str()
+                }
+            })
+            .collect();
+        keys.sort_unstable();
+        keys.dedup();
+
+        for key in keys {
+            let data = churn.entry(key.to_string()).or_insert(FileChurnData {
+                path: key.to_string(),
+                change_count: 0,
+                weighted_changes: 0.0,
+                unique_commits: std::collections::HashSet::new(),
+                recent_changes: Vec::new(),
+            });
+            data.change_count += 1;
+            data.weighted_changes += weight;
+            data.unique_commits.insert(commit.hash.clone());
+            if data.recent_changes.len() < 5 {
+                data.recent_changes.push(commit.subject.clone());
+            }
+        }
+    }
+
+    let mut hotspots: Vec<ChurnHotspot> = churn
+        .into_values()
+        .filter(|data| data.change_count >= options.threshold)
+        .map(|data| ChurnHotspot {
+            file_path: data.path,
+            change_count: data.change_count,
+            weighted_changes: data.weighted_changes,
+            unique_commits: data.unique_commits.len(),
+            recent_changes: data.recent_changes,
+        })
+        .collect();
+
+    // Busiest first, by weighted changes when recent ones count more
+    hotspots.sort_by(|a, b| {
+        b.weighted_changes
+            .partial_cmp(&a.weighted_changes)
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then_with(|| b.change_count.cmp(&a.change_count))
+            .then_with(|| a.file_path.cmp(&b.file_path))
+    });
+    hotspots
+}
+
+/// Build the ownership map from commits (newest first) and the files each
+/// changed.
+fn aggregate_ownership(changes: &[(&CommitInfo, Vec<String>)], by_module: bool) -> CodeOwnership {
+    // Path -> (commits, author key -> share)
+    let mut paths: HashMap<String, (usize, HashMap<String, AuthorShare>)> = HashMap::new();
+    let mut overall: HashMap<String, AuthorShare> = HashMap::new();
+
+    let count = |authors: &mut HashMap<String, AuthorShare>, commit: &CommitInfo| {
+        authors
+            .entry(author_key(commit))
+            .or_insert_with(|| AuthorShare {
+                author: commit.author_name.clone(),
+                email: commit.author_email.clone(),
+                commits: 0,
+                share: 0.0,
+                // Newest first, so the first change seen is the latest
+                last_change: commit.author_date.clone(),
+            })
+            .commits += 1;
+    };
+
+    let mut commits_analyzed = 0;
+    for (commit, files) in changes {
+        let mut keys: Vec<String> = files
+            .iter()
+            .map(|file| {
+                if by_module {
+                    module_of(file).to_string()
+                } else {
+                    file.clone()
+                }
+            })
+            .collect();
+        keys.sort();
+        keys.dedup();
+        if keys.is_empty() {
+            continue;
+        }
+
+        commits_analyzed += 1;
+        count(&mut overall, commit);
+        for key in keys {
+            let (commits, authors) = paths.entry(key).or_default();
+            *commits += 1;
+            count(authors, commit);
+        }
+    }
+
+    let rank = |authors: HashMap<String, AuthorShare>, total: usize| {
+        let mut owners: Vec<AuthorShare> = authors
+            .into_values()
+            .map(|mut owner| {
+                owner.share = owner.commits as f32 / total.max(1) as f32;
+                owner
+            })
+            .collect();
+        owners.sort_by(|a, b| {
+            b.commits
+                .cmp(&a.commits)
+                .then_with(|| a.author.cmp(&b.author))
+        });
+        owners
+    };
+
+    let mut entries: Vec<OwnershipEntry> = paths
+        .into_iter()
+        .map(|(path, (commits, authors))| OwnershipEntry {
+            path,
+            commits,
+            owners: rank(authors, commits),
+        })
+        .collect();
+    entries.sort_by(|a, b| b.commits.cmp(&a.commits).then_with(|| a.path.cmp(&b.path)));
+
+    CodeOwnership {
+        entries,
+        reviewers: rank(overall, commits_analyzed),
+        commits_analyzed,
+    }
+}
+
+/// Memory content describing a mined commit and the pull request it
+/// merged, if fetched.
+fn commit_content(parsed: &ParsedCommit, pull_request: Option<&PullRequestInfo>) -> String {
+    let commit = &parsed.info;
+    let refs = if parsed.issue_refs.is_empty() {
+        String::new()
+    } else {
+        format!("Refs: {}\n", parsed.issue_refs.join(", "))
+    };
+    let mut content = format!(
+        "Commit: {}\nAuthor: {} <{}>\nDate: {}\n{}\n{}",
+        commit.hash,
+        commit.author_name,
+        commit.author_email,
+        commit.author_date,
+        refs,
+        if commit.body.is_empty() {
+            &commit.subject
+        } else {
+            &commit.body
+        }
+    );
+    if let Some(pull_request) = pull_request {
+        // A merge commit's body already is the description
+        let with_description = commit.body != pull_request.description.trim();
+        content.push_str("\n\n");
+        content.push_str(&pull_request.to_content(with_description));
+    }
+    content
+}
+
+/// Hashes of the commits described in a mined memory's content.
+fn grouped_commits(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .lines()
+        .filter_map(|line| line.strip_prefix("Commit: "))
+        .map(|hash| hash.trim().to_string())
 }
 
 #[cfg(test)]
@@ -531,4 +2244,341 @@ mod tests {
         assert_eq!(config.max_commits, 500);
         assert!(config.min_confidence >= 0.0 && config.min_confidence <= 1.0);
     }
+
+    #[test]
+    fn test_mining_config_from_params() {
+        let defaults = MiningConfig {
+            max_commits: 100,
+            mine_features: false,
+            ..MiningConfig::default()
+        };
+        let params = serde_json::json!({
+            "max_commits": 50,
+            "minConfidence": 0.9,
+            "mineReverts": false,
+            "full": true,
+            "branch": "release/*",
+        });
+        let config = MiningConfig::from_params(&params, defaults.clone());
+        assert_eq!(config.max_commits, 50);
+        assert!((config.min_confidence - 0.9).abs() < 1e-6);
+        assert!(!config.mine_reverts);
+        assert!(!config.mine_features);
+        assert!(config.mine_bug_fixes);
+        assert!(config.full);
+        assert_eq!(config.branch.as_deref(), Some("release/*"));
+
+        let config = MiningConfig::from_params(&serde_json::json!({ "branch": " " }), defaults);
+        assert_eq!(config.max_commits, 100);
+        assert!(config.mine_reverts);
+        assert!(!config.full);
+        assert_eq!(config.branch, None);
+    }
+
+    #[test]
+    fn test_aggregate_churn() {
+        let commit = |hash: &str| CommitInfo {
+            hash: hash.repeat(40),
+            subject: format!("change {}", hash),
+            body: String::new(),
+            author_name: "Test".to_string(),
+            author_email: "test@example.com".to_string(),
+            author_date: "2024-01-01".to_string(),
+        };
+        let (a, b, c, d) = (commit("a"), commit("b"), commit("c"), commit("d"));
+        let day = 86_400;
+        let files = |names: &[&str]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+        // `old.rs` was busy a year ago; `new.rs` changed twice this week
+        let changes = vec![
+            (&a, 400 * day, files(&["src/new.rs"])),
+            (&b, 398 * day, files(&["src/new.rs", "docs/guide.md"])),
+            (&c, 40 * day, files(&["src/old.rs", "docs/guide.md"])),
+            (&d, 35 * day, files(&["src/old.rs"])),
+        ];
+
+        let hotspots = aggregate_churn(&changes, &HotspotOptions::default());
+        assert_eq!(hotspots.len(), 3);
+        assert_eq!(hotspots[0].change_count, 2);
+        assert!((hotspots[0].weighted_changes - 2.0).abs() < 1e-6);
+
+        let options = HotspotOptions {
+            half_life_days: Some(30.0),
+            ..HotspotOptions::default()
+        };
+        let hotspots = aggregate_churn(&changes, &options);
+        let order: Vec<&str> = hotspots.iter().map(|h| h.file_path.as_str()).collect();
+        assert_eq!(order, vec!["src/new.rs", "docs/guide.md", "src/old.rs"]);
+        assert!(hotspots[2].weighted_changes < 0.01);
+        assert_eq!(hotspots[0].recent_changes, vec!["change a", "change b"]);
+
+        let options = HotspotOptions {
+            by_directory: true,
+            ..options
+        };
+        let hotspots = aggregate_churn(&changes, &options);
+        assert_eq!(hotspots[0].file_path, "src");
+        assert_eq!(hotspots[0].change_count, 4);
+        assert_eq!(module_of("README.md"), ".");
+    }
+
+    #[test]
+    fn test_hotspot_options_from_params() {
+        let params = serde_json::json!({
+            "hotspotsSince": "6 months ago",
+            "hotspotsUntil": " ",
+            "hotspotsByDirectory": true,
+        });
+        let options = HotspotOptions::from_params(&params, HotspotOptions::for_memories());
+        assert_eq!(options.since.as_deref(), Some("6 months ago"));
+        assert_eq!(options.until, None);
+        assert!(options.by_directory);
+        assert_eq!(options.threshold, 10);
+        assert_eq!(options.half_life_days, Some(HOTSPOT_HALF_LIFE_DAYS));
+
+        let options =
+            HotspotOptions::from_params(&serde_json::json!({}), HotspotOptions::default());
+        assert!(!options.by_directory);
+        assert_eq!(options.threshold, 2);
+    }
+
+    #[test]
+    fn test_release_notes() {
+        assert!(is_changelog("CHANGELOG.md"));
+        assert!(is_changelog("docs/release-notes.rst"));
+        assert!(!is_changelog("src/changelog_parser.rs"));
+
+        let changelog = vec![
+            "## [2.3.0] - 2024-05-01".to_string(),
+            "".to_string(),
+            "- Removed the legacy auth flow".to_string(),
+        ];
+        let notes = release_notes("2.3.0", "Release 2.3.0\n", &changelog);
+        assert_eq!(
+            notes,
+            vec!["Release 2.3.0", "- Removed the legacy auth flow"]
+        );
+
+        let release = Release {
+            version: "2.3.0".to_string(),
+            commit: "a".repeat(40),
+            timestamp: 0,
+            notes,
+        };
+        assert_eq!(release_summary(&release), "Removed the legacy auth flow");
+        let release = Release {
+            notes: vec!["### Breaking".to_string()],
+            ..release
+        };
+        assert_eq!(release_summary(&release), "Released 2.3.0");
+    }
+
+    #[test]
+    fn test_dry_run_records_candidates() {
+        let (first, second) = ("a".repeat(40), "b".repeat(40));
+        let memory = MemoryNode::builder()
+            .debug_context("Timeouts on idle connections", "Retry once")
+            .title("[Git] fix: retry on timeout")
+            .content(format!(
+                "Commit: {first}\nAuthor: Test\n\n---\n\nCommit: {second}\nAuthor: Test"
+            ))
+            .from_git(&first)
+            .confidence(0.9)
+            .build()
+            .unwrap();
+
+        let mut result = MiningResult::default();
+        result.record(
+            &CommitInfo {
+                hash: second.clone(),
+                subject: "fix: retry on timeout".to_string(),
+                body: String::new(),
+                author_name: "Test".to_string(),
+                author_email: "test@example.com".to_string(),
+                author_date: "2024-01-01".to_string(),
+            },
+            Ok(vec![CommitOutcome::Candidate(
+                MemoryCandidate::from_memory(&memory),
+            )]),
+        );
+        assert_eq!(result.memories_created, 0);
+        assert_eq!(
+            result.candidates,
+            Some(vec![MemoryCandidate {
+                kind: "debug_context".to_string(),
+                title: "[Git] fix: retry on timeout".to_string(),
+                confidence: 0.9,
+                commits: vec![first, second],
+            }])
+        );
+    }
+
+    #[test]
+    fn test_revert_chain_marks() {
+        let original = "a".repeat(40);
+        let revert = "b".repeat(40);
+        let mut memory = MemoryNode::builder()
+            .known_issue(
+                "Reverted: feat: add caching",
+                codegraph_memory::IssueSeverity::Medium,
+            )
+            .title("[Git] Revert \"feat: add caching\"")
+            .content("Commit: bbbb")
+            .from_git(&revert)
+            .tag("revert")
+            .tag(reverts_tag(&original))
+            .build()
+            .unwrap();
+
+        assert!(mark_reverted(&mut memory, &"c".repeat(40)));
+        assert!(!memory.is_current());
+        assert!(memory.tags.contains(&"reverted-by:ccccccc".to_string()));
+        // Already invalidated memories are left alone
+        assert!(!mark_reverted(&mut memory, &"d".repeat(40)));
+
+        // Reverting the revert of a revert re-applies the first revert
+        assert!(mark_restored(&mut memory, &"c".repeat(40), &"d".repeat(40)));
+        assert_eq!(reverted_commit(&memory).as_deref(), Some("aaaaaaa"));
+        assert!(memory.is_current());
+        assert!(memory.tags.contains(&"restored-by:ddddddd".to_string()));
+        assert!(memory.content.contains("Restored** by commit ddddddd"));
+        // Restoring is done once
+        assert!(!mark_restored(
+            &mut memory,
+            &"c".repeat(40),
+            &"d".repeat(40)
+        ));
+        // The notes are not mistaken for grouped commits
+        assert_eq!(grouped_commits(&memory.content).count(), 1);
+    }
+
+    #[test]
+    fn test_apply_reverts_twice_is_idempotent() {
+        let (feature, revert, restore) = ("a".repeat(40), "b".repeat(40), "c".repeat(40));
+        let memory = |title: &str, hash: &str| {
+            MemoryNode::builder()
+                .known_issue(title, codegraph_memory::IssueSeverity::Medium)
+                .title(title)
+                .content(format!("Commit: {hash}"))
+                .from_git(hash)
+                .build()
+                .unwrap()
+        };
+        let mut revert_memory = memory("Revert \"feat: add caching\"", &revert);
+        revert_memory.tags.push(reverts_tag(&feature));
+        let mut memories = vec![memory("feat: add caching", &feature), revert_memory];
+        // The feature is reverted, then the revert is reverted
+        let reverts = [
+            (feature.clone(), revert.clone()),
+            (revert.clone(), restore.clone()),
+        ];
+
+        let mut result = MiningResult::default();
+        let changed = apply_revert_commits(&mut memories, &reverts, &mut result);
+        assert_eq!(changed.len(), 2);
+        assert_eq!((result.memories_reverted, result.memories_restored), (2, 1));
+        assert!(memories[0].is_current());
+        assert!(!memories[1].is_current());
+        let applied: Vec<_> = memories.iter().map(|m| m.content.clone()).collect();
+
+        let mut again = MiningResult::default();
+        let changed = apply_revert_commits(&mut memories, &reverts, &mut again);
+        assert!(changed.is_empty());
+        assert_eq!((again.memories_reverted, again.memories_restored), (0, 0));
+        assert!(memories[0].is_current());
+        let contents: Vec<_> = memories.iter().map(|m| m.content.clone()).collect();
+        assert_eq!(contents, applied);
+    }
+
+    #[test]
+    fn test_aggregate_ownership() {
+        let commit = |author: &str, date: &str| CommitInfo {
+            hash: "a".repeat(40),
+            subject: "change".to_string(),
+            body: String::new(),
+            author_name: author.to_string(),
+            author_email: format!("{}@example.com", author.to_lowercase()),
+            author_date: date.to_string(),
+        };
+        let (alice_new, bob, alice_old) = (
+            commit("Alice", "2024-03-01"),
+            commit("Bob", "2024-02-01"),
+            commit("Alice", "2024-01-01"),
+        );
+        let changes = vec![
+            (
+                &alice_new,
+                vec![
+                    "src/auth/login.rs".to_string(),
+                    "src/auth/token.rs".to_string(),
+                ],
+            ),
+            (
+                &bob,
+                vec!["src/auth/login.rs".to_string(), "README.md".to_string()],
+            ),
+            (&alice_old, vec!["src/auth/login.rs".to_string()]),
+        ];
+
+        let by_module = aggregate_ownership(&changes, true);
+        assert_eq!(by_module.commits_analyzed, 3);
+        let auth = &by_module.entries[0];
+        assert_eq!(auth.path, "src/auth");
+        // One commit touching two files of the module counts once
+        assert_eq!(auth.commits, 3);
+        assert_eq!(auth.owners[0].author, "Alice");
+        assert_eq!(auth.owners[0].commits, 2);
+        assert_eq!(auth.owners[0].last_change, "2024-03-01");
+        assert!((auth.owners[0].share - 2.0 / 3.0).abs() < 1e-6);
+        assert_eq!(by_module.entries[1].path, ".");
+        assert_eq!(by_module.reviewers[0].author, "Alice");
+
+        let by_file = aggregate_ownership(&changes, false);
+        assert_eq!(by_file.entries[0].path, "src/auth/login.rs");
+        assert_eq!(by_file.entries[0].commits, 3);
+        assert_eq!(by_file.entries.len(), 3);
+    }
+
+    #[test]
+    fn test_commit_content_lists_refs_and_grouped_commits() {
+        let info = CommitInfo {
+            hash: "a".repeat(40),
+            subject: "fix: retry on timeout (#12)".to_string(),
+            body: String::new(),
+            author_name: "Test".to_string(),
+            author_email: "test@example.com".to_string(),
+            author_date: "2024-01-01".to_string(),
+        };
+        let parsed = ParsedCommit {
+            issue_refs: info.issue_refs(),
+            info,
+            pattern: CommitPattern::BugFix {
+                issue_ref: Some("#12".to_string()),
+            },
+            files_changed: vec![],
+            confidence: 0.9,
+        };
+        let content = commit_content(&parsed, None);
+        assert!(content.contains("Refs: #12\n"));
+        assert!(content.ends_with("fix: retry on timeout (#12)"));
+
+        let grouped = format!(
+            "{}\n\n---\n\nCommit: {}\nAuthor: Test",
+            content,
+            "b".repeat(40)
+        );
+        let hashes: Vec<String> = grouped_commits(&grouped).collect();
+        assert_eq!(hashes, vec!["a".repeat(40), "b".repeat(40)]);
+
+        let pull_request = PullRequestInfo {
+            number: 12,
+            title: "Retry on timeout".to_string(),
+            description: "Idle connections are dropped by the proxy.".to_string(),
+            review_comments: vec![],
+        };
+        let content = commit_content(&parsed, Some(&pull_request));
+        assert!(content.ends_with(
+            "fix: retry on timeout (#12)\n\nPull request #12: Retry on timeout\n\n\
+             Idle connections are dropped by the proxy."
+        ));
+    }
 }
@@ -2,15 +2,28 @@
 //!
 //! This module provides functionality to:
 //! - Parse git commit history
-//! - Extract debug contexts, architectural decisions, and known issues
+//! - Extract debug contexts, architectural decisions, and known issues,
+//!   using the workspace's own commit patterns from `.codegraph/mining.toml`
 //! - Link mined memories to code graph nodes
+//! - Map the lines each commit changed to the symbols they fall in
+//! - Enrich merge commits with their pull request's description and reviews
 
+mod enrichment;
 mod error;
 mod executor;
 mod miner;
 mod parser;
+mod patterns;
+mod symbols;
 
+pub use enrichment::{ConfiguredHost, PullRequestEnricher, PullRequestHost, PullRequestInfo};
 pub use error::GitMiningError;
 pub use executor::GitExecutor;
-pub use miner::{GitMiner, MiningConfig, MiningResult};
-pub use parser::{CommitInfo, CommitPattern, ParsedCommit};
+pub use miner::{
+    AuthorShare, ChurnHotspot, CodeOwnership, GitMiner, HotspotOptions, MemoryCandidate,
+    MiningConfig, MiningProgress, MiningResult, OwnershipEntry,
+};
+pub use parser::{
+    BlameLine, CommitInfo, CommitPattern, ConventionalCommit, MemoryTrailer, ParsedCommit, TagInfo,
+    TrailerKind,
+};
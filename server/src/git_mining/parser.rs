@@ -2,6 +2,8 @@
 
 use super::GitMiningError;
 use codegraph_memory::MemoryKind;
+use regex::Regex;
+use std::sync::OnceLock;
 
 /// Separator used in git log format output.
 pub const FIELD_SEPARATOR: &str = "␞"; // ASCII Record Separator
@@ -18,6 +20,32 @@ pub const LOG_FORMAT: &str = concat!(
     "␝"    // commit separator
 );
 
+/// [`LOG_FORMAT`] plus the ref each commit was reached from, for
+/// `git log --source` over several branches.
+pub const SOURCE_LOG_FORMAT: &str = concat!(
+    "%H", "␞", // hash
+    "%s", "␞", // subject
+    "%b", "␞", // body
+    "%an", "␞", // author name
+    "%ae", "␞", // author email
+    "%ai", "␞",  // author date
+    "%S", // ref the commit was reached from
+    "␝"   // commit separator
+);
+
+/// [`LOG_FORMAT`] plus the author date in unix seconds, for weighting
+/// commits by age.
+pub const TIMED_LOG_FORMAT: &str = concat!(
+    "%H", "␞", // hash
+    "%s", "␞", // subject
+    "%b", "␞", // body
+    "%an", "␞", // author name
+    "%ae", "␞", // author email
+    "%ai", "␞",   // author date
+    "%at", // author date (unix seconds)
+    "␝"    // commit separator
+);
+
 /// Basic commit information extracted from git log.
 #[derive(Debug, Clone)]
 pub struct CommitInfo {
@@ -29,6 +57,249 @@ pub struct CommitInfo {
     pub author_date: String,
 }
 
+impl CommitInfo {
+    /// The message parsed as a conventional commit, if it is one.
+    pub fn conventional(&self) -> Option<ConventionalCommit> {
+        parse_conventional(&self.subject, &self.body)
+    }
+
+    /// Issues and pull requests the commit message refers to.
+    pub fn issue_refs(&self) -> Vec<String> {
+        extract_issue_references(&format!("{}\n{}", self.subject, self.body))
+    }
+
+    /// Version this commit releases, if it is a release commit
+    /// (`chore(release): 2.3.0`, `Release v2.3`, `Bump version to 2.3.0`).
+    pub fn release_version(&self) -> Option<String> {
+        static REGEX: OnceLock<Regex> = OnceLock::new();
+        let regex = REGEX
+            .get_or_init(|| Regex::new(RELEASE_PATTERN).expect("release pattern must compile"));
+        let caps = regex.captures(self.subject.trim())?;
+        Some(caps[1].to_string())
+    }
+
+    /// Number of the pull (or merge) request this commit merged, if any.
+    ///
+    /// Recognizes GitHub merge commits (`Merge pull request #12 from ...`),
+    /// squash merges (`Subject (#12)`) and GitLab merge commits
+    /// (`See merge request group/project!12`).
+    pub fn pull_request_number(&self) -> Option<u64> {
+        static REGEX: OnceLock<Regex> = OnceLock::new();
+        let regex = REGEX.get_or_init(|| {
+            Regex::new(PULL_REQUEST_PATTERN).expect("pull request pattern must compile")
+        });
+        let caps = regex
+            .captures(&self.subject)
+            .or_else(|| regex.captures(&self.body))?;
+        caps.iter()
+            .skip(1)
+            .flatten()
+            .next()
+            .and_then(|number| number.as_str().parse().ok())
+    }
+
+    /// Whether this is a merge commit, whose own message only names the
+    /// merged branch or request.
+    pub fn is_merge(&self) -> bool {
+        self.subject.starts_with("Merge pull request ")
+            || (self.subject.starts_with("Merge branch ")
+                && self.body.contains("See merge request "))
+    }
+
+    /// The `Memory:` and `Decision:` trailers of the message, in order and
+    /// without duplicates. Like `git interpret-trailers`, only the final
+    /// paragraph is read, and only when it is a trailer block. Indented
+    /// lines continue the trailer above them.
+    pub fn memory_trailers(&self) -> Vec<MemoryTrailer> {
+        let mut trailers: Vec<MemoryTrailer> = Vec::new();
+        let mut current: Option<MemoryTrailer> = None;
+        for line in trailer_block(&self.body) {
+            if let Some(trailer) = current.as_mut() {
+                if line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+                    trailer.text.push(' ');
+                    trailer.text.push_str(line.trim());
+                    continue;
+                }
+            }
+            trailers.extend(current.take());
+            let Some((key, text)) = line.split_once(':') else {
+                continue;
+            };
+            let kind = match key.trim_end().to_lowercase().as_str() {
+                "memory" => TrailerKind::Memory,
+                "decision" => TrailerKind::Decision,
+                _ => continue,
+            };
+            current = Some(MemoryTrailer {
+                kind,
+                text: text.trim().to_string(),
+            });
+        }
+        trailers.extend(current);
+
+        let mut unique: Vec<MemoryTrailer> = Vec::new();
+        for trailer in trailers {
+            if !trailer.text.is_empty() && !unique.contains(&trailer) {
+                unique.push(trailer);
+            }
+        }
+        unique
+    }
+
+    /// The commits a squash merge squashed, if its body lists two or more:
+    /// the log `git merge --squash` writes, or, in a pull request's squash
+    /// merge, GitHub's `* message` bullets. Bullets in other commits are an
+    /// ordinary list in the body. Each has the squash's hash and author and
+    /// notes the squash in its body, so that the squash's issue references
+    /// carry over.
+    pub fn squashed_commits(&self) -> Vec<CommitInfo> {
+        let messages = if self.body.starts_with("Squashed commit of the following:") {
+            squashed_log_messages(&self.body)
+        } else if self.pull_request_number().is_some() {
+            squashed_bullet_messages(&self.body)
+        } else {
+            return Vec::new();
+        };
+        let messages: Vec<(String, String)> =
+            messages.iter().filter_map(|m| split_message(m)).collect();
+        if messages.len() < 2 {
+            return Vec::new();
+        }
+        messages
+            .into_iter()
+            .map(|(subject, body)| CommitInfo {
+                hash: self.hash.clone(),
+                subject,
+                body: format!("{}\n\nSquashed into: {}", body, self.subject)
+                    .trim_start()
+                    .to_string(),
+                author_name: self.author_name.clone(),
+                author_email: self.author_email.clone(),
+                author_date: self.author_date.clone(),
+            })
+            .collect()
+    }
+}
+
+/// The lines of the trailer block ending `body`, by git's rules: the final
+/// paragraph, if all of its lines are `Key: value` trailers or their
+/// indented continuations, or if a quarter of them are and one was written
+/// by git (`Signed-off-by:`, `(cherry picked from commit ...)`). Empty when
+/// the final paragraph is prose.
+fn trailer_block(body: &str) -> Vec<&str> {
+    let lines: Vec<&str> = body.trim_end().lines().collect();
+    let start = lines
+        .iter()
+        .rposition(|line| line.trim().is_empty())
+        .map_or(0, |blank| blank + 1);
+    let block = &lines[start..];
+
+    let mut trailers = 0;
+    let mut others = 0;
+    let mut git_generated = false;
+    let mut in_trailer = false;
+    for line in block {
+        if in_trailer && line.starts_with([' ', '\t']) {
+            continue;
+        }
+        git_generated |=
+            line.starts_with("Signed-off-by: ") || line.starts_with("(cherry picked from commit ");
+        in_trailer = is_trailer_line(line);
+        if in_trailer {
+            trailers += 1;
+        } else {
+            others += 1;
+        }
+    }
+    let is_trailer_block =
+        trailers > 0 && (others == 0 || (git_generated && trailers * 3 >= others));
+    if is_trailer_block {
+        block.to_vec()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Whether `line` is a `Key: value` trailer: a key of letters, digits and
+/// dashes, optionally followed by spaces, then a colon.
+fn is_trailer_line(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        let key = key.trim_end();
+        !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Messages of a GitHub squash merge body: each `* ` bullet with the lines
+/// up to the next, without the co-author trailers GitHub appends.
+fn squashed_bullet_messages(body: &str) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    for line in body.lines() {
+        let trimmed = line.trim();
+        let is_trailer = (trimmed.len() >= 3 && trimmed.chars().all(|c| c == '-'))
+            || trimmed.starts_with("Co-authored-by:")
+            || trimmed.starts_with("Signed-off-by:");
+        if let Some(subject) = line.strip_prefix("* ") {
+            messages.push(subject.to_string());
+        } else if let Some(message) = messages.last_mut().filter(|_| !is_trailer) {
+            message.push('\n');
+            message.push_str(line);
+        }
+    }
+    messages
+}
+
+/// Messages of a `git merge --squash` body: the indented message of each
+/// `commit <hash>` entry.
+fn squashed_log_messages(body: &str) -> Vec<String> {
+    let mut messages: Vec<String> = Vec::new();
+    for line in body.lines() {
+        if line.starts_with("commit ") {
+            messages.push(String::new());
+        } else if let Some(message) = messages.last_mut() {
+            if let Some(text) = line.strip_prefix("    ") {
+                message.push_str(text);
+                message.push('\n');
+            } else if line.trim().is_empty() {
+                message.push('\n');
+            }
+        }
+    }
+    messages
+}
+
+/// Split a commit message into its subject and body; None if it is empty.
+fn split_message(message: &str) -> Option<(String, String)> {
+    let message = message.trim();
+    if message.is_empty() {
+        return None;
+    }
+    let (subject, body) = message.split_once('\n').unwrap_or((message, ""));
+    Some((subject.trim().to_string(), body.trim().to_string()))
+}
+
+/// Kind of knowledge a commit trailer records.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailerKind {
+    /// `Memory:` — anything worth knowing about the change
+    Memory,
+    /// `Decision:` — a design decision
+    Decision,
+}
+
+/// A memory the author wrote into a commit message as a trailer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryTrailer {
+    pub kind: TrailerKind,
+    pub text: String,
+}
+
+/// Pull request numbers in merge and squash commit messages
+const PULL_REQUEST_PATTERN: &str =
+    r"^Merge pull request #(\d+) |\(#(\d+)\)$|See merge request [\w./-]+!(\d+)";
+
+/// Subjects of release commits; the version is the first group.
+const RELEASE_PATTERN: &str = r"(?i)^(?:chore(?:\([^)]*\))?:\s*)?(?:release[ds]?|releasing|bump(?:ed)? version(?: to)?|version|prepare(?: for)? release)?\s*:?\s*v?(\d+\.\d+(?:\.\d+)?(?:-[0-9a-z.]+)?)(?:\s*\(#\d+\))?$";
+
 /// Pattern detected in a commit message.
 #[derive(Debug, Clone, PartialEq)]
 pub enum CommitPattern {
@@ -61,6 +332,8 @@ pub struct ParsedCommit {
     pub pattern: CommitPattern,
     pub files_changed: Vec<String>,
     pub confidence: f32,
+    /// Issues and pull requests the commit refers to (`#123`, `JIRA-456`)
+    pub issue_refs: Vec<String>,
 }
 
 impl ParsedCommit {
@@ -70,22 +343,23 @@ impl ParsedCommit {
             CommitPattern::BugFix { .. } => Some(MemoryKind::DebugContext {
                 problem_description: self.extract_problem(),
                 root_cause: self.extract_root_cause(),
-                solution: self.info.subject.clone(),
+                solution: self.summary(),
                 symptoms: vec![],
                 related_errors: vec![],
             }),
             CommitPattern::ArchitecturalDecision => Some(MemoryKind::ArchitecturalDecision {
-                decision: self.info.subject.clone(),
-                rationale: self.info.body.clone(),
-                alternatives_considered: None,
+                decision: self.summary(),
+                rationale: self.explanation(),
+                alternatives_considered: labeled_section(&self.info.body, &["alternatives"]),
                 stakeholders: vec![self.info.author_name.clone()],
             }),
             CommitPattern::Feature => {
                 // Only create memory for features with substantial body text
-                if !self.info.body.is_empty() && self.info.body.len() > 50 {
+                let explanation = self.explanation();
+                if explanation.len() > 50 {
                     Some(MemoryKind::ArchitecturalDecision {
-                        decision: self.info.subject.clone(),
-                        rationale: self.info.body.clone(),
+                        decision: self.summary(),
+                        rationale: explanation,
                         alternatives_considered: None,
                         stakeholders: vec![self.info.author_name.clone()],
                     })
@@ -94,113 +368,401 @@ impl ParsedCommit {
                 }
             }
             CommitPattern::BreakingChange => Some(MemoryKind::KnownIssue {
-                description: self.info.subject.clone(),
+                // The footer says what breaks; the subject what changed
+                description: self
+                    .info
+                    .conventional()
+                    .and_then(|c| c.breaking_note().map(String::from))
+                    .filter(|note| !note.is_empty())
+                    .unwrap_or_else(|| self.info.subject.clone()),
                 severity: codegraph_memory::IssueSeverity::High,
                 workaround: self.extract_workaround(),
-                tracking_id: None,
+                tracking_id: self.issue_refs.first().cloned(),
             }),
             CommitPattern::Deprecation => Some(MemoryKind::KnownIssue {
                 description: format!("Deprecated: {}", self.info.subject),
                 severity: codegraph_memory::IssueSeverity::Medium,
                 workaround: self.extract_workaround(),
-                tracking_id: None,
+                tracking_id: self.issue_refs.first().cloned(),
             }),
             CommitPattern::Revert { .. } => Some(MemoryKind::KnownIssue {
                 description: format!("Reverted: {}", self.info.subject),
                 severity: codegraph_memory::IssueSeverity::Medium,
                 workaround: None,
-                tracking_id: None,
+                tracking_id: self.issue_refs.first().cloned(),
             }),
             _ => None, // Refactor, Doc, Test, Other don't create memories
         }
     }
 
     fn extract_problem(&self) -> String {
-        // Try to extract problem description from body or use subject
-        if !self.info.body.is_empty() {
-            // Look for common patterns like "Problem:", "Issue:", "Bug:"
-            for line in self.info.body.lines() {
-                let lower = line.to_lowercase();
-                if lower.starts_with("problem:")
-                    || lower.starts_with("issue:")
-                    || lower.starts_with("bug:")
-                {
-                    return line
-                        .split_once(':')
-                        .map(|x| x.1)
-                        .unwrap_or("")
-                        .trim()
-                        .to_string();
-                }
-            }
-        }
-        // Fall back to subject
-        self.info.subject.clone()
+        // Look for common patterns like "Problem:", "Issue:", "Bug:"
+        labeled_section(&self.info.body, &["problem", "issue", "bug"])
+            // Fall back to the description
+            .unwrap_or_else(|| self.summary())
     }
 
     fn extract_root_cause(&self) -> Option<String> {
-        // Look for root cause in body
-        for line in self.info.body.lines() {
-            let lower = line.to_lowercase();
-            if lower.starts_with("cause:")
-                || lower.starts_with("root cause:")
-                || lower.starts_with("reason:")
-            {
-                return Some(
-                    line.split_once(':')
-                        .map(|x| x.1)
-                        .unwrap_or("")
-                        .trim()
-                        .to_string(),
-                );
+        labeled_section(&self.info.body, &["root cause", "cause", "reason"])
+    }
+
+    fn extract_workaround(&self) -> Option<String> {
+        labeled_section(&self.info.body, &["workaround", "migration"])
+    }
+
+    /// The subject without its conventional `type(scope):` prefix
+    fn summary(&self) -> String {
+        match self.info.conventional() {
+            Some(conventional) => conventional.description,
+            None => self.info.subject.clone(),
+        }
+    }
+
+    /// The body without its conventional footers
+    fn explanation(&self) -> String {
+        match self.info.conventional() {
+            Some(conventional) if !conventional.body.is_empty() => conventional.body,
+            _ => self.info.body.clone(),
+        }
+    }
+}
+
+/// The text after `label:` in `body` (matched case-insensitively at the
+/// start of a line), through the end of its paragraph.
+fn labeled_section(body: &str, labels: &[&str]) -> Option<String> {
+    for paragraph in body.split("\n\n") {
+        let mut lines = paragraph.lines().map(str::trim);
+        while let Some(line) = lines.next() {
+            let Some((label, rest)) = line.split_once(':') else {
+                continue;
+            };
+            if !labels.contains(&label.trim().to_lowercase().as_str()) {
+                continue;
             }
+            let text = std::iter::once(rest.trim())
+                .chain(lines)
+                .filter(|line| !line.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            return Some(text);
         }
-        None
     }
+    None
+}
 
-    fn extract_workaround(&self) -> Option<String> {
-        for line in self.info.body.lines() {
-            let lower = line.to_lowercase();
-            if lower.starts_with("workaround:") || lower.starts_with("migration:") {
-                return Some(
-                    line.split_once(':')
-                        .map(|x| x.1)
-                        .unwrap_or("")
-                        .trim()
-                        .to_string(),
-                );
+/// A commit message following the Conventional Commits format:
+/// `type(scope)!: description`, an optional body and trailing footers
+/// (`Token: value`, `Token #value`, `BREAKING CHANGE: note`).
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ConventionalCommit {
+    /// Lowercased type (`fix`, `feat`, ...)
+    pub commit_type: String,
+    pub scope: Option<String>,
+    pub description: String,
+    /// Body paragraphs, footers excluded
+    pub body: String,
+    /// Footers in order, e.g. `("Reviewed-by", "Alice")`
+    pub footers: Vec<(String, String)>,
+    /// Marked breaking by `!` or a `BREAKING CHANGE` footer
+    pub breaking: bool,
+}
+
+impl ConventionalCommit {
+    /// The note of the `BREAKING CHANGE` footer, if any
+    pub fn breaking_note(&self) -> Option<&str> {
+        self.footers
+            .iter()
+            .find(|(token, _)| is_breaking_token(token))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn is_breaking_token(token: &str) -> bool {
+    token == "BREAKING CHANGE" || token == "BREAKING-CHANGE"
+}
+
+const CONVENTIONAL_HEADER_PATTERN: &str = r"^([A-Za-z]+)(?:\(([^()\r\n]*)\))?(!)?:\s+(.+)$";
+const FOOTER_PATTERN: &str = r"^(BREAKING[ -]CHANGE|[A-Za-z][\w-]*)(?::\s+|\s+#)(.*)$";
+
+fn footer_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(FOOTER_PATTERN).expect("footer pattern must compile"))
+}
+
+/// Parse `subject` and `body` as a conventional commit, if the subject has
+/// a `type:` prefix.
+pub fn parse_conventional(subject: &str, body: &str) -> Option<ConventionalCommit> {
+    static HEADER: OnceLock<Regex> = OnceLock::new();
+    let header = HEADER.get_or_init(|| {
+        Regex::new(CONVENTIONAL_HEADER_PATTERN).expect("conventional header pattern must compile")
+    });
+    let caps = header.captures(subject.trim())?;
+
+    let mut paragraphs: Vec<&str> = body
+        .split("\n\n")
+        .map(str::trim)
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect();
+
+    // Footers are the last paragraph, if it starts with one; lines that are
+    // not a footer continue the one before
+    let mut footers: Vec<(String, String)> = Vec::new();
+    let footer_paragraph = paragraphs
+        .last()
+        .and_then(|last| last.lines().next())
+        .is_some_and(|line| footer_regex().is_match(line));
+    if footer_paragraph {
+        for line in paragraphs.pop().unwrap_or_default().lines() {
+            match footer_regex().captures(line) {
+                Some(footer) => footers.push((footer[1].to_string(), footer[2].trim().to_string())),
+                None => {
+                    if let Some((_, value)) = footers.last_mut() {
+                        value.push(' ');
+                        value.push_str(line.trim());
+                    }
+                }
             }
         }
-        None
     }
+
+    let breaking =
+        caps.get(3).is_some() || footers.iter().any(|(token, _)| is_breaking_token(token));
+    Some(ConventionalCommit {
+        commit_type: caps[1].to_lowercase(),
+        scope: caps
+            .get(2)
+            .map(|scope| scope.as_str().trim().to_string())
+            .filter(|scope| !scope.is_empty()),
+        description: caps[4].trim().to_string(),
+        body: paragraphs.join("\n\n"),
+        footers,
+        breaking,
+    })
 }
 
 /// Parse git log output into structured commit information.
 pub fn parse_log_output(output: &str) -> Result<Vec<CommitInfo>, GitMiningError> {
-    let mut commits = Vec::new();
+    Ok(parse_log_entries(output)
+        .map(|(commit, _)| commit)
+        .collect())
+}
+
+/// Parse output of [`SOURCE_LOG_FORMAT`] into commits and the ref each was
+/// reached from.
+pub fn parse_source_log_output(
+    output: &str,
+) -> Result<Vec<(CommitInfo, Option<String>)>, GitMiningError> {
+    Ok(parse_log_entries(output)
+        .map(|(commit, source)| {
+            let source = source
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(str::to_string);
+            (commit, source)
+        })
+        .collect())
+}
 
-    for commit_str in output.split(COMMIT_SEPARATOR) {
+/// Parse output of [`TIMED_LOG_FORMAT`] into commits and their author
+/// dates in unix seconds (0 if missing).
+pub fn parse_timed_log_output(output: &str) -> Vec<(CommitInfo, i64)> {
+    parse_log_entries(output)
+        .map(|(commit, timestamp)| {
+            let timestamp = timestamp.and_then(|t| t.trim().parse().ok());
+            (commit, timestamp.unwrap_or(0))
+        })
+        .collect()
+}
+
+/// Commits of git log output, with the field after the author date if any.
+fn parse_log_entries(output: &str) -> impl Iterator<Item = (CommitInfo, Option<&str>)> {
+    output.split(COMMIT_SEPARATOR).filter_map(|commit_str| {
         let commit_str = commit_str.trim();
         if commit_str.is_empty() {
-            continue;
+            return None;
         }
 
         let fields: Vec<&str> = commit_str.split(FIELD_SEPARATOR).collect();
         if fields.len() < 6 {
-            continue; // Skip malformed entries
+            return None; // Skip malformed entries
         }
 
-        commits.push(CommitInfo {
+        let commit = CommitInfo {
             hash: fields[0].to_string(),
             subject: fields[1].to_string(),
             body: fields[2].trim().to_string(),
             author_name: fields[3].to_string(),
             author_email: fields[4].to_string(),
             author_date: fields[5].trim().to_string(),
-        });
+        };
+        Some((commit, fields.get(6).copied()))
+    })
+}
+
+/// `git for-each-ref` format for tags: name, object type (`tag` for
+/// annotated tags), tagged commit, tag date (unix seconds) and message.
+pub const TAG_FORMAT: &str = concat!(
+    "%(refname:short)",
+    "␞",
+    "%(objecttype)",
+    "␞",
+    "%(*objectname)",
+    "␞",
+    "%(creatordate:unix)",
+    "␞",
+    "%(contents:subject)",
+    "␞",
+    "%(contents:body)",
+    "␝"
+);
+
+/// An annotated tag.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagInfo {
+    pub name: String,
+    /// Hash of the tagged commit
+    pub commit: String,
+    /// Tag date (unix seconds)
+    pub timestamp: i64,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Parse output of [`TAG_FORMAT`] into its annotated tags; lightweight tags
+/// carry no message and are skipped.
+pub fn parse_tag_output(output: &str) -> Vec<TagInfo> {
+    output
+        .split(COMMIT_SEPARATOR)
+        .filter_map(|entry| {
+            let fields: Vec<&str> = entry.trim().split(FIELD_SEPARATOR).collect();
+            if fields.len() < 6 || fields[1] != "tag" || fields[2].is_empty() {
+                return None;
+            }
+            Some(TagInfo {
+                name: fields[0].to_string(),
+                commit: fields[2].to_string(),
+                timestamp: fields[3].trim().parse().ok()?,
+                subject: fields[4].trim().to_string(),
+                body: strip_signature(fields[5]).trim().to_string(),
+            })
+        })
+        .collect()
+}
+
+/// A tag or commit message without its trailing PGP or SSH signature.
+fn strip_signature(message: &str) -> &str {
+    [
+        "-----BEGIN PGP SIGNATURE-----",
+        "-----BEGIN SSH SIGNATURE-----",
+    ]
+    .iter()
+    .filter_map(|marker| message.find(marker))
+    .min()
+    .map_or(message, |index| &message[..index])
+}
+
+/// Lines a zero-context diff adds, without their `+`.
+pub fn parse_added_lines(diff: &str) -> Vec<String> {
+    diff.lines()
+        .filter(|line| !line.starts_with("+++"))
+        .filter_map(|line| line.strip_prefix('+'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Authorship of one line from `git blame --porcelain`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameLine {
+    /// Line number in the current file (1-indexed)
+    pub line: u32,
+    pub commit: String,
+    pub author_name: String,
+    /// Author timestamp (unix seconds)
+    pub author_time: i64,
+}
+
+/// Parse `git blame --porcelain` output into per-line authorship.
+///
+/// Porcelain output only lists a commit's author headers the first time the
+/// commit appears, so they are remembered for later lines of the same commit.
+pub fn parse_blame_output(output: &str) -> Vec<BlameLine> {
+    let mut authors: std::collections::HashMap<String, (String, i64)> =
+        std::collections::HashMap::new();
+    let mut lines = Vec::new();
+    let mut current: Option<(String, u32)> = None;
+
+    for row in output.lines() {
+        if row.starts_with('\t') {
+            // Content line ends the entry
+            if let Some((commit, line)) = current.take() {
+                let (author_name, author_time) = authors.get(&commit).cloned().unwrap_or_default();
+                lines.push(BlameLine {
+                    line,
+                    commit,
+                    author_name,
+                    author_time,
+                });
+            }
+            continue;
+        }
+
+        let mut fields = row.split(' ');
+        let first = fields.next().unwrap_or("");
+        if first.len() == 40 && first.chars().all(|c| c.is_ascii_hexdigit()) {
+            let final_line = fields.nth(1).and_then(|l| l.parse().ok()).unwrap_or(0);
+            current = Some((first.to_string(), final_line));
+            continue;
+        }
+
+        let Some((commit, _)) = &current else {
+            continue;
+        };
+        if let Some(name) = row.strip_prefix("author ") {
+            authors.entry(commit.clone()).or_default().0 = name.to_string();
+        } else if let Some(time) = row.strip_prefix("author-time ") {
+            authors.entry(commit.clone()).or_default().1 = time.trim().parse().unwrap_or(0);
+        }
     }
 
-    Ok(commits)
+    lines
+}
+
+/// Parse a zero-context diff (`git show -U0`) into the lines each file's
+/// hunks cover in the commit's version of the file, as inclusive ranges.
+///
+/// A hunk that only deletes lines covers the line before the deletion.
+pub fn parse_diff_hunks(output: &str) -> std::collections::HashMap<String, Vec<(u32, u32)>> {
+    let mut hunks: std::collections::HashMap<String, Vec<(u32, u32)>> =
+        std::collections::HashMap::new();
+    let mut current: Option<String> = None;
+
+    for row in output.lines() {
+        if let Some(path) = row.strip_prefix("+++ ") {
+            // `/dev/null` for deleted files, which have no lines left
+            current = path.strip_prefix("b/").map(String::from);
+            continue;
+        }
+        let Some(range) = row
+            .strip_prefix("@@ ")
+            .and_then(|rest| rest.split(' ').find(|field| field.starts_with('+')))
+        else {
+            continue;
+        };
+        let Some(path) = &current else {
+            continue;
+        };
+        let mut parts = range[1..].splitn(2, ',');
+        let start: u32 = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+        let count: u32 = parts.next().and_then(|c| c.parse().ok()).unwrap_or(1);
+        let (start, end) = if count == 0 {
+            (start.max(1), start.max(1))
+        } else {
+            (start, start + count - 1)
+        };
+        hunks.entry(path.clone()).or_default().push((start, end));
+    }
+
+    hunks
 }
 
 /// Detect the pattern of a commit from its subject and body.
@@ -210,6 +772,34 @@ pub fn parse_log_output(output: &str) -> Result<Vec<CommitInfo>, GitMiningError>
 /// - **Tier 2 (moderate confidence)**: Keywords anywhere in the message,
 ///   catching lazy commit messages like "fixed the login bug" or "added auth"
 pub fn detect_pattern(commit: &CommitInfo) -> (CommitPattern, f32) {
+    // === Tier 0: Conventional commits (`type(scope)!: description`) ===
+    if let Some(conventional) = commit.conventional() {
+        if conventional.breaking {
+            return (CommitPattern::BreakingChange, 0.95);
+        }
+        let detected = match conventional.commit_type.as_str() {
+            "fix" | "bugfix" | "bug" | "hotfix" => {
+                let issue_ref = commit.issue_refs().into_iter().next();
+                Some((CommitPattern::BugFix { issue_ref }, 0.9))
+            }
+            "feat" | "feature" | "add" => Some((CommitPattern::Feature, 0.8)),
+            "refactor" | "cleanup" | "clean" | "perf" => Some((CommitPattern::Refactor, 0.8)),
+            "docs" | "doc" | "documentation" => Some((CommitPattern::Documentation, 0.9)),
+            "test" | "tests" | "testing" => Some((CommitPattern::Test, 0.9)),
+            "deprecate" | "deprecated" => Some((CommitPattern::Deprecation, 0.9)),
+            "arch" | "adr" | "decision" => Some((CommitPattern::ArchitecturalDecision, 0.85)),
+            "revert" => {
+                let reverted_hash = extract_reverted_commit(&commit.body);
+                Some((CommitPattern::Revert { reverted_hash }, 0.95))
+            }
+            // chore, ci, build, style, ...: judged by their wording below
+            _ => None,
+        };
+        if let Some(detected) = detected {
+            return detected;
+        }
+    }
+
     let subject_lower = commit.subject.to_lowercase();
     let body_lower = commit.body.to_lowercase();
 
@@ -240,7 +830,8 @@ pub fn detect_pattern(commit: &CommitInfo) -> (CommitPattern, f32) {
 
     // Reverts
     if subject_lower.starts_with("revert") {
-        let reverted_hash = extract_revert_hash(&commit.subject);
+        let reverted_hash =
+            extract_revert_hash(&commit.subject).or_else(|| extract_reverted_commit(&commit.body));
         return (CommitPattern::Revert { reverted_hash }, 0.95);
     }
 
@@ -368,26 +959,48 @@ pub fn detect_pattern(commit: &CommitInfo) -> (CommitPattern, f32) {
     (CommitPattern::Other, 0.5)
 }
 
-/// Extract issue reference (e.g., #123) from commit message.
-fn extract_issue_reference(text: &str) -> Option<String> {
-    // Look for patterns like #123, GH-123, JIRA-123
-    let re_patterns = [
-        r"#(\d+)",
-        r"(?i)gh-(\d+)",
-        r"(?i)closes?\s+#(\d+)",
-        r"(?i)fixes?\s+#(\d+)",
-    ];
-
-    for pattern in &re_patterns {
-        if let Ok(re) = regex::Regex::new(pattern) {
-            if let Some(caps) = re.captures(text) {
-                if let Some(m) = caps.get(1) {
-                    return Some(format!("#{}", m.as_str()));
-                }
+/// Issue and pull request references: `#123`, `GH-123`, `.../issues/123`
+/// and `.../pull/123` (all as `#123`), and tracker keys like `JIRA-456`.
+/// Closing keywords (`Fixes #123`, `Closes JIRA-456`) are matched by the
+/// reference they precede.
+const ISSUE_REF_PATTERN: &str =
+    r"/(?:issues|pull)/(\d+)|\b(?i:gh)-(\d+)\b|(?:^|[^\w&])#(\d+)\b|\b([A-Z][A-Z0-9]+-\d+)\b";
+
+/// Uppercase prefixes that look like tracker keys but name standards
+/// (`UTF-8`, `SHA-256`, `ISO-8859`)
+const NOT_TRACKER_KEYS: &[&str] = &["UTF", "UCS", "SHA", "MD", "ISO", "RFC", "CP"];
+
+fn issue_ref_regex() -> &'static Regex {
+    static REGEX: OnceLock<Regex> = OnceLock::new();
+    REGEX.get_or_init(|| Regex::new(ISSUE_REF_PATTERN).expect("issue ref pattern must compile"))
+}
+
+/// Extract the issue references (e.g., #123, JIRA-456) from a commit
+/// message, in order of appearance and without duplicates.
+pub fn extract_issue_references(text: &str) -> Vec<String> {
+    let mut refs: Vec<String> = Vec::new();
+    for caps in issue_ref_regex().captures_iter(text) {
+        let reference = if let Some(number) = caps.get(1).or(caps.get(2)).or(caps.get(3)) {
+            format!("#{}", number.as_str())
+        } else if let Some(key) = caps.get(4) {
+            let project = key.as_str().split('-').next().unwrap_or("");
+            if NOT_TRACKER_KEYS.contains(&project) {
+                continue;
             }
+            key.as_str().to_string()
+        } else {
+            continue;
+        };
+        if !refs.contains(&reference) {
+            refs.push(reference);
         }
     }
-    None
+    refs
+}
+
+/// Extract the first issue reference (e.g., #123) from commit message.
+fn extract_issue_reference(text: &str) -> Option<String> {
+    extract_issue_references(text).into_iter().next()
 }
 
 /// Extract reverted commit hash from revert commit message.
@@ -403,6 +1016,17 @@ fn extract_revert_hash(subject: &str) -> Option<String> {
     None
 }
 
+/// Extract the hash from git's `This reverts commit <hash>.` body line.
+pub(crate) fn extract_reverted_commit(body: &str) -> Option<String> {
+    body.lines().find_map(|line| {
+        let hash = line
+            .trim()
+            .strip_prefix("This reverts commit ")?
+            .trim_end_matches('.');
+        (hash.len() >= 7 && hash.chars().all(|c| c.is_ascii_hexdigit())).then(|| hash.to_string())
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -451,6 +1075,45 @@ mod tests {
         assert_eq!(extract_issue_reference("no issue"), None);
     }
 
+    #[test]
+    fn test_extract_issue_references() {
+        assert_eq!(
+            extract_issue_references(
+                "Fix login timeout (#12)\n\nFixes GH-34, closes PROJ-56.\n\
+                 See https://github.com/acme/app/pull/78 and #12 again."
+            ),
+            vec!["#12", "#34", "PROJ-56", "#78"]
+        );
+        // Standards and HTML entities are not issues
+        assert!(extract_issue_references("Decode UTF-8 and SHA-256 &#39;s").is_empty());
+        assert!(extract_issue_references("bump to v1.2-3 in abc#4x").is_empty());
+
+        let mut commit = make_commit("fix: handle empty config");
+        commit.body = "Resolves JIRA-456".to_string();
+        assert_eq!(commit.issue_refs(), vec!["JIRA-456"]);
+    }
+
+    #[test]
+    fn test_pull_request_number() {
+        let merge = make_commit("Merge pull request #42 from acme/fix-login");
+        assert_eq!(merge.pull_request_number(), Some(42));
+        assert!(merge.is_merge());
+
+        let squash = make_commit("Fix login timeout (#57)");
+        assert_eq!(squash.pull_request_number(), Some(57));
+        assert!(!squash.is_merge());
+
+        let mut gitlab = make_commit("Merge branch 'fix-login' into 'main'");
+        gitlab.body = "Fix login timeout\n\nSee merge request acme/app!9".to_string();
+        assert_eq!(gitlab.pull_request_number(), Some(9));
+        assert!(gitlab.is_merge());
+
+        assert_eq!(
+            make_commit("fix: see #12 for context").pull_request_number(),
+            None
+        );
+    }
+
     fn make_commit(subject: &str) -> CommitInfo {
         CommitInfo {
             hash: "abc123".to_string(),
@@ -462,6 +1125,105 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_memory_trailers() {
+        let mut commit = make_commit("Switch sessions to Redis");
+        commit.body = "Sessions outlived restarts only by accident.\n\n\
+                       Decision: keep sessions in Redis, not in process memory,\n\
+                       \x20 so that deploys do not log users out\n\
+                       memory: the session TTL is set in config/redis.toml\n\
+                       Signed-off-by: Test <test@example.com>\n\
+                       Memory: the session TTL is set in config/redis.toml\n\
+                       Memory:"
+            .to_string();
+        assert_eq!(
+            commit.memory_trailers(),
+            vec![
+                MemoryTrailer {
+                    kind: TrailerKind::Decision,
+                    text: "keep sessions in Redis, not in process memory, \
+                           so that deploys do not log users out"
+                        .to_string(),
+                },
+                MemoryTrailer {
+                    kind: TrailerKind::Memory,
+                    text: "the session TTL is set in config/redis.toml".to_string(),
+                },
+            ]
+        );
+        assert!(make_commit("fix: typo").memory_trailers().is_empty());
+
+        // Prose, in the body or in a final paragraph that is not all trailers
+        commit.body = "Cache the parsed files.\n\n\
+                       Memory: usage dropped 20% after the change.\n\n\
+                       Measured on the monorepo.\n\
+                       Memory: the cache lives in .codegraph/cache"
+            .to_string();
+        assert!(commit.memory_trailers().is_empty());
+
+        commit.body = "Cache the parsed files.\n\n\
+                       Memory: usage dropped 20% after the change.\n\n\
+                       Memory: the cache lives in .codegraph/cache\n"
+            .to_string();
+        assert_eq!(
+            commit.memory_trailers(),
+            vec![MemoryTrailer {
+                kind: TrailerKind::Memory,
+                text: "the cache lives in .codegraph/cache".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_squashed_commits() {
+        let mut squash = make_commit("Add the parser cache (#42)");
+        squash.body = "* feat: cache parsed files\n\nKeyed by content hash.\n\n\
+                       * fix: evict on rename\n\n---------\n\n\
+                       Co-authored-by: Other <other@example.com>"
+            .to_string();
+        let squashed = squash.squashed_commits();
+        assert_eq!(squashed.len(), 2);
+        assert_eq!(squashed[0].subject, "feat: cache parsed files");
+        assert_eq!(
+            squashed[0].body,
+            "Keyed by content hash.\n\nSquashed into: Add the parser cache (#42)"
+        );
+        assert_eq!(squashed[1].subject, "fix: evict on rename");
+        assert_eq!(squashed[1].issue_refs(), vec!["#42".to_string()]);
+        assert!(matches!(
+            detect_pattern(&squashed[1]).0,
+            CommitPattern::BugFix { .. }
+        ));
+
+        squash.body = "Squashed commit of the following:\n\n\
+                       commit 1111111111111111111111111111111111111111\n\
+                       Author: Test <test@example.com>\n\
+                       Date:   Mon Jan 1 00:00:00 2024 +0000\n\n\
+                       \x20   fix: retry on timeout\n\n\
+                       \x20   Idle connections are dropped.\n\n\
+                       commit 2222222222222222222222222222222222222222\n\
+                       Author: Test <test@example.com>\n\
+                       Date:   Mon Jan 1 00:00:00 2024 +0000\n\n\
+                       \x20   docs: note the retry\n"
+            .to_string();
+        let squashed = squash.squashed_commits();
+        assert_eq!(squashed.len(), 2);
+        assert_eq!(squashed[0].subject, "fix: retry on timeout");
+        assert!(squashed[0]
+            .body
+            .starts_with("Idle connections are dropped.\n\n"));
+        assert_eq!(squashed[1].subject, "docs: note the retry");
+
+        // A single bullet is an ordinary body
+        squash.body = "* just one change".to_string();
+        assert!(squash.squashed_commits().is_empty());
+
+        // So are bullets outside a pull request's squash merge
+        let mut listed = make_commit("refactor: split the parser");
+        listed.body = "* move the lexer out\n* rename the visitor".to_string();
+        assert!(listed.squashed_commits().is_empty());
+    }
+
     #[test]
     fn test_detect_lazy_bug_fix() {
         // "fixed" without conventional prefix
@@ -513,6 +1275,194 @@ mod tests {
         assert!(conv_confidence > kw_confidence);
     }
 
+    #[test]
+    fn test_parse_source_log_output() {
+        let output = format!(
+            "{}␞fix: crash on empty input␞␞Alice␞alice@example.com␞2024-01-02 10:00:00 +0000␞release/1.2\n␝\n\
+             {}␞docs: readme␞Longer body␞Bob␞bob@example.com␞2024-01-01 09:00:00 +0000␞␝",
+            "a".repeat(40),
+            "b".repeat(40)
+        );
+        let commits = parse_source_log_output(&output).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].0.subject, "fix: crash on empty input");
+        assert_eq!(commits[0].1.as_deref(), Some("release/1.2"));
+        assert_eq!(commits[1].0.body, "Longer body");
+        assert_eq!(commits[1].1, None);
+        // The plain format has no source field
+        assert_eq!(parse_log_output(&output).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_release_version() {
+        for (subject, version) in [
+            ("chore(release): 2.3.0", Some("2.3.0")),
+            ("chore: release v1.4.0-rc.1", Some("1.4.0-rc.1")),
+            ("Release v2.3", Some("2.3")),
+            ("Bump version to 0.11.2 (#140)", Some("0.11.2")),
+            ("v3.0.0", Some("3.0.0")),
+            ("fix: crash in release 2.3.0 builds", None),
+            ("Update dependencies", None),
+        ] {
+            assert_eq!(
+                make_commit(subject).release_version().as_deref(),
+                version,
+                "{subject}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_tag_output() {
+        let output = format!(
+            "v2.3.0␞tag␞{}␞1700000000␞Release 2.3.0␞Removed the legacy auth flow.\n\
+             -----BEGIN PGP SIGNATURE-----\nabc\n-----END PGP SIGNATURE-----\n␝\n\
+             v2.2.9␞commit␞␞1690000000␞fix: typo␞␝",
+            "a".repeat(40)
+        );
+        let tags = parse_tag_output(&output);
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0].name, "v2.3.0");
+        assert_eq!(tags[0].commit, "a".repeat(40));
+        assert_eq!(tags[0].timestamp, 1700000000);
+        assert_eq!(tags[0].body, "Removed the legacy auth flow.");
+
+        let diff = "diff --git a/CHANGELOG.md b/CHANGELOG.md\n+++ b/CHANGELOG.md\n@@ -1,0 +1,2 @@\n+## 2.3.0\n+- Removed the legacy auth flow\n";
+        assert_eq!(
+            parse_added_lines(diff),
+            vec!["## 2.3.0", "- Removed the legacy auth flow"]
+        );
+    }
+
+    #[test]
+    fn test_parse_blame_output_reuses_commit_headers() {
+        let sha_a = "a".repeat(40);
+        let sha_b = "b".repeat(40);
+        let output = format!(
+            "{sha_a} 1 1 1\nauthor Alice\nauthor-mail <alice@example.com>\nauthor-time 1700000000\nsummary init\nfilename src/lib.rs\n\tfn main() {{\n\
+             {sha_b} 2 2 1\nauthor Bob\nauthor-time 1710000000\nfilename src/lib.rs\n\t// TODO: tidy\n\
+             {sha_a} 2 3\n\t}}\n"
+        );
+        let lines = parse_blame_output(&output);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].author_name, "Bob");
+        assert_eq!(lines[1].line, 2);
+        assert_eq!(lines[2].author_name, "Alice");
+        assert_eq!(lines[2].author_time, 1700000000);
+        assert_eq!(lines[2].line, 3);
+    }
+
+    #[test]
+    fn test_parse_diff_hunks() {
+        let output = "diff --git a/src/auth.rs b/src/auth.rs\n\
+                      --- a/src/auth.rs\n\
+                      +++ b/src/auth.rs\n\
+                      @@ -10,2 +10,3 @@ fn login() {\n\
+                      -old\n+new\n\
+                      @@ -40 +41 @@ fn logout() {\n\
+                      @@ -60,4 +62,0 @@\n\
+                      diff --git a/src/old.rs b/src/old.rs\n\
+                      --- a/src/old.rs\n\
+                      +++ /dev/null\n\
+                      @@ -1,5 +0,0 @@\n";
+        let hunks = parse_diff_hunks(output);
+        assert_eq!(hunks["src/auth.rs"], vec![(10, 12), (41, 41), (62, 62)]);
+        assert!(!hunks.contains_key("src/old.rs"));
+    }
+
+    #[test]
+    fn test_parse_conventional_commit() {
+        let conventional = parse_conventional(
+            "feat(auth)!: drop session cookies",
+            "Sessions now use bearer tokens.\n\n\
+             Cookies could not be shared across subdomains.\n\n\
+             BREAKING CHANGE: clients must send the Authorization\n\
+             header instead of the session cookie.\n\
+             Refs #42\n\
+             Reviewed-by: Alice",
+        )
+        .unwrap();
+        assert_eq!(conventional.commit_type, "feat");
+        assert_eq!(conventional.scope.as_deref(), Some("auth"));
+        assert_eq!(conventional.description, "drop session cookies");
+        assert!(conventional.breaking);
+        assert_eq!(
+            conventional.body,
+            "Sessions now use bearer tokens.\n\nCookies could not be shared across subdomains."
+        );
+        assert_eq!(
+            conventional.breaking_note(),
+            Some("clients must send the Authorization header instead of the session cookie.")
+        );
+        assert_eq!(
+            conventional.footers[1],
+            ("Refs".to_string(), "42".to_string())
+        );
+        assert_eq!(conventional.footers.len(), 3);
+
+        let plain = parse_conventional("Fix(Parser): handle tabs", "").unwrap();
+        assert_eq!(plain.commit_type, "fix");
+        assert_eq!(plain.scope.as_deref(), Some("Parser"));
+        assert!(!plain.breaking);
+        assert!(plain.footers.is_empty());
+
+        assert!(parse_conventional("Fixed the login page crash", "").is_none());
+    }
+
+    #[test]
+    fn test_conventional_breaking_footer_becomes_known_issue() {
+        let mut commit = make_commit("fix(api): rename the timeout option");
+        commit.body = "Problem: the option name was misleading\n\
+                       and confused users.\n\n\
+                       BREAKING CHANGE: `timeout` is now `request_timeout`"
+            .to_string();
+        let (pattern, confidence) = detect_pattern(&commit);
+        assert_eq!(pattern, CommitPattern::BreakingChange);
+        assert!(confidence >= 0.9);
+
+        let parsed = ParsedCommit {
+            info: commit,
+            pattern,
+            files_changed: vec![],
+            confidence,
+            issue_refs: vec![],
+        };
+        match parsed.to_memory_kind() {
+            Some(MemoryKind::KnownIssue { description, .. }) => {
+                assert_eq!(description, "`timeout` is now `request_timeout`")
+            }
+            other => panic!("expected a known issue, got {:?}", other),
+        }
+        assert_eq!(
+            parsed.extract_problem(),
+            "the option name was misleading and confused users."
+        );
+    }
+
+    #[test]
+    fn test_conventional_types_and_reverts() {
+        let (pattern, _) = detect_pattern(&make_commit("perf(graph): cache lookups"));
+        assert_eq!(pattern, CommitPattern::Refactor);
+
+        let (pattern, confidence) = detect_pattern(&make_commit("fix(parser): handle tabs"));
+        assert!(matches!(pattern, CommitPattern::BugFix { .. }));
+        assert!(confidence >= 0.9);
+
+        // Not a known type: judged by its wording
+        let (pattern, _) = detect_pattern(&make_commit("chore: fixed the build script crash"));
+        assert!(matches!(pattern, CommitPattern::BugFix { .. }));
+
+        let mut revert = make_commit("Revert \"feat: add caching\"");
+        revert.body = format!("This reverts commit {}.", "c".repeat(40));
+        let (pattern, _) = detect_pattern(&revert);
+        assert_eq!(
+            pattern,
+            CommitPattern::Revert {
+                reverted_hash: Some("c".repeat(40))
+            }
+        );
+    }
+
     #[test]
     fn test_unknown_commit_stays_other() {
         let (pattern, confidence) = detect_pattern(&make_commit("wip"));
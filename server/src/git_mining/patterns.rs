@@ -0,0 +1,294 @@
+//! Workspace-defined commit patterns.
+//!
+//! Teams with their own commit conventions describe them in
+//! `<workspace>/.codegraph/mining.toml`. The first pattern matching a commit
+//! decides its kind, ahead of the built-in conventional commit and keyword
+//! detection:
+//!
+//! ```toml
+//! # Use only the patterns below, without the built-in detection
+//! replace_defaults = false
+//!
+//! [[patterns]]
+//! name = "jira-bug"
+//! regex = '^BUG-\d+'
+//! kind = "bug_fix"
+//! confidence = 0.9
+//! tags = ["jira"]
+//!
+//! [[patterns]]
+//! name = "design-note"
+//! regex = '(?i)^design:|\[design\]'
+//! match = "message"
+//! kind = "architectural_decision"
+//!
+//! [[patterns]]
+//! name = "release"
+//! regex = '^chore\(release\)'
+//! kind = "ignore"
+//! ```
+//!
+//! `regex` is matched against the subject, or the whole message with
+//! `match = "message"`. Kinds are `bug_fix`, `feature`, `refactor`,
+//! `architectural_decision`, `breaking_change`, `deprecation`, `revert`,
+//! `documentation`, `test` and `ignore`; refactors, documentation, tests and
+//! ignored commits are never mined. Tags are added to the memories the
+//! pattern creates.
+
+use super::parser::{self, CommitInfo, CommitPattern};
+use regex::Regex;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+// ============================================================
+// Configuration
+// ============================================================
+
+/// Kind of commit a pattern detects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum PatternKind {
+    BugFix,
+    Feature,
+    Refactor,
+    ArchitecturalDecision,
+    BreakingChange,
+    Deprecation,
+    Revert,
+    Documentation,
+    Test,
+    /// Matching commits are never mined
+    Ignore,
+}
+
+/// Part of the commit message a pattern is matched against.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum MatchField {
+    #[default]
+    Subject,
+    /// Subject and body
+    Message,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub(crate) struct PatternConfig {
+    pub name: String,
+    pub regex: String,
+    pub kind: PatternKind,
+    #[serde(default = "default_confidence")]
+    pub confidence: f32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default, rename = "match")]
+    pub field: MatchField,
+}
+
+fn default_confidence() -> f32 {
+    0.8
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct MiningPatternsConfig {
+    #[serde(default)]
+    pub patterns: Vec<PatternConfig>,
+    /// Use only the workspace patterns, without the built-in detection
+    #[serde(default)]
+    pub replace_defaults: bool,
+}
+
+/// Location of the mining patterns for a workspace.
+pub(crate) fn config_path(workspace: &Path) -> PathBuf {
+    workspace.join(".codegraph").join("mining.toml")
+}
+
+/// Load the workspace mining patterns. Returns Ok(None) if there are none.
+pub(crate) fn load_config(workspace: &Path) -> Result<Option<MiningPatternsConfig>, String> {
+    let path = config_path(workspace);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(format!("Failed to read {}: {e}", path.display())),
+    };
+    toml::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Invalid {}: {e}", path.display()))
+}
+
+// ============================================================
+// Detection
+// ============================================================
+
+struct CompiledPattern {
+    config: PatternConfig,
+    regex: Regex,
+}
+
+/// Commit classification: the workspace patterns, then the built-in
+/// detection unless they replace it.
+#[derive(Default)]
+pub(crate) struct CommitPatterns {
+    patterns: Vec<CompiledPattern>,
+    replace_defaults: bool,
+}
+
+impl CommitPatterns {
+    /// Compile `config`. The error names the first invalid pattern.
+    pub fn from_config(config: MiningPatternsConfig) -> Result<Self, String> {
+        let patterns = config
+            .patterns
+            .into_iter()
+            .map(|mut pattern| {
+                let regex = Regex::new(&pattern.regex).map_err(|e| {
+                    format!("Invalid regex in mining pattern {}: {e}", pattern.name)
+                })?;
+                pattern.confidence = pattern.confidence.clamp(0.0, 1.0);
+                Ok(CompiledPattern {
+                    config: pattern,
+                    regex,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            patterns,
+            replace_defaults: config.replace_defaults,
+        })
+    }
+
+    /// The patterns of the workspace at `workspace`, or only the built-in
+    /// detection if it defines none.
+    pub fn load(workspace: &Path) -> Result<Self, String> {
+        match load_config(workspace)? {
+            Some(config) => Self::from_config(config),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The pattern and confidence of `commit`, with the tags of the
+    /// workspace pattern that matched it, if one did.
+    pub fn detect(&self, commit: &CommitInfo) -> (CommitPattern, f32, &[String]) {
+        let message = format!("{}\n\n{}", commit.subject, commit.body);
+        let matched = self.patterns.iter().find(|pattern| {
+            let text = match pattern.config.field {
+                MatchField::Subject => &commit.subject,
+                MatchField::Message => &message,
+            };
+            pattern.regex.is_match(text)
+        });
+
+        if let Some(pattern) = matched {
+            let config = &pattern.config;
+            let detected = match config.kind {
+                PatternKind::BugFix => CommitPattern::BugFix {
+                    issue_ref: commit.issue_refs().into_iter().next(),
+                },
+                PatternKind::Feature => CommitPattern::Feature,
+                PatternKind::Refactor => CommitPattern::Refactor,
+                PatternKind::ArchitecturalDecision => CommitPattern::ArchitecturalDecision,
+                PatternKind::BreakingChange => CommitPattern::BreakingChange,
+                PatternKind::Deprecation => CommitPattern::Deprecation,
+                PatternKind::Revert => CommitPattern::Revert {
+                    reverted_hash: parser::extract_reverted_commit(&commit.body),
+                },
+                PatternKind::Documentation => CommitPattern::Documentation,
+                PatternKind::Test => CommitPattern::Test,
+                PatternKind::Ignore => return (CommitPattern::Other, 0.0, &[]),
+            };
+            return (detected, config.confidence, &config.tags);
+        }
+
+        if self.replace_defaults {
+            return (CommitPattern::Other, 0.0, &[]);
+        }
+        let (detected, confidence) = parser::detect_pattern(commit);
+        (detected, confidence, &[])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit(subject: &str, body: &str) -> CommitInfo {
+        CommitInfo {
+            hash: "a".repeat(40),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            author_name: "Test".to_string(),
+            author_email: "test@example.com".to_string(),
+            author_date: "2024-01-01".to_string(),
+        }
+    }
+
+    fn patterns(toml: &str) -> CommitPatterns {
+        CommitPatterns::from_config(toml::from_str(toml).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_workspace_patterns_come_first() {
+        let patterns = patterns(
+            r#"
+            [[patterns]]
+            name = "jira-bug"
+            regex = '^BUG-\d+'
+            kind = "bug_fix"
+            confidence = 0.9
+            tags = ["jira"]
+
+            [[patterns]]
+            name = "design-note"
+            regex = '(?i)\[design\]'
+            match = "message"
+            kind = "architectural_decision"
+
+            [[patterns]]
+            name = "release"
+            regex = '^chore\(release\)'
+            kind = "ignore"
+            "#,
+        );
+
+        let (pattern, confidence, tags) = patterns.detect(&commit("BUG-12 login loops", ""));
+        assert!(matches!(pattern, CommitPattern::BugFix { .. }));
+        assert!((confidence - 0.9).abs() < 1e-6);
+        assert_eq!(tags, ["jira".to_string()]);
+
+        let (pattern, confidence, _) =
+            patterns.detect(&commit("Split the queue", "[design] one per tenant"));
+        assert_eq!(pattern, CommitPattern::ArchitecturalDecision);
+        assert!((confidence - 0.8).abs() < 1e-6);
+
+        // Ignored even though the built-ins would mine it as a fix
+        let (pattern, _, _) = patterns.detect(&commit("chore(release): fix version", ""));
+        assert_eq!(pattern, CommitPattern::Other);
+
+        // Unmatched commits fall back to the built-in detection
+        let (pattern, _, tags) = patterns.detect(&commit("fix: crash on empty input", ""));
+        assert!(matches!(pattern, CommitPattern::BugFix { .. }));
+        assert!(tags.is_empty());
+    }
+
+    #[test]
+    fn test_replace_defaults_and_invalid_patterns() {
+        let patterns = patterns("replace_defaults = true");
+        let (pattern, _, _) = patterns.detect(&commit("fix: crash on empty input", ""));
+        assert_eq!(pattern, CommitPattern::Other);
+
+        let config = toml::from_str(
+            r#"
+            [[patterns]]
+            name = "broken"
+            regex = '('
+            kind = "feature"
+            "#,
+        )
+        .unwrap();
+        let error = CommitPatterns::from_config(config).err().unwrap();
+        assert!(error.contains("broken"));
+
+        assert!(toml::from_str::<MiningPatternsConfig>(
+            "[[patterns]]\nname = \"x\"\nregex = \"y\"\nkind = \"chore\""
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Mapping of the lines a commit changed to the graph symbols they fall in.
+//!
+//! A commit's changed lines come either from `git blame` (the lines of the
+//! current version it last touched) or from its diff hunks (the lines of the
+//! version it produced). Both are mapped onto the line spans stored on graph
+//! nodes to link mined memories to the symbols a commit changed. Hunk lines
+//! are only exact for commits whose files have not changed since; blame
+//! lines always are.
+
+use crate::domain::node_props;
+use codegraph::{CodeGraph, NodeId, NodeType};
+use codegraph_memory::LinkedNodeType;
+use std::path::Path;
+
+/// A symbol a commit changed, with the lines of it that changed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedSymbol {
+    pub node_id: NodeId,
+    pub node_type: LinkedNodeType,
+    pub name: String,
+    /// Repository-relative path of the symbol's file
+    pub file: String,
+    /// First and last changed line within the symbol
+    pub first_line: u32,
+    pub last_line: u32,
+    /// Number of changed lines within the symbol
+    pub lines_changed: usize,
+}
+
+/// What a commit changed in one file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileChange {
+    /// Repository-relative path
+    pub file: String,
+    /// The file's own node, if the file is indexed
+    pub file_node: Option<NodeId>,
+    /// Changed symbols, in order of their first changed line
+    pub symbols: Vec<ChangedSymbol>,
+}
+
+/// Map `lines` changed in `file` (repository-relative) to the innermost
+/// function, class or interface enclosing each.
+pub fn map_changed_lines(
+    graph: &CodeGraph,
+    repo_path: &Path,
+    file: &str,
+    lines: &[u32],
+) -> FileChange {
+    let mut change = FileChange {
+        file: file.to_string(),
+        file_node: None,
+        symbols: Vec::new(),
+    };
+
+    let path_str = repo_path.join(file).to_string_lossy().to_string();
+    let Ok(nodes) = graph.query().property("path", path_str).execute() else {
+        return change;
+    };
+
+    let mut symbols = Vec::new();
+    for node_id in nodes {
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        let node_type = match node.node_type {
+            NodeType::Function => LinkedNodeType::Function,
+            NodeType::Class => LinkedNodeType::Class,
+            NodeType::Interface => LinkedNodeType::Interface,
+            NodeType::CodeFile => {
+                change.file_node = Some(node_id);
+                continue;
+            }
+            _ => continue,
+        };
+        let span = (node_props::line_start(node), node_props::line_end(node));
+        symbols.push((node_id, node_type, node_props::name(node).to_string(), span));
+    }
+
+    let spans: Vec<(u32, u32)> = symbols.iter().map(|(_, _, _, span)| *span).collect();
+    change.symbols = assign_lines(&spans, lines)
+        .into_iter()
+        .map(|(index, first_line, last_line, lines_changed)| {
+            let (node_id, node_type, name, _) = &symbols[index];
+            ChangedSymbol {
+                node_id: *node_id,
+                node_type: *node_type,
+                name: name.clone(),
+                file: file.to_string(),
+                first_line,
+                last_line,
+                lines_changed,
+            }
+        })
+        .collect();
+    change
+}
+
+/// The node of `file` (repository-relative), if it is indexed.
+pub(crate) fn file_node(graph: &CodeGraph, repo_path: &Path, file: &str) -> Option<NodeId> {
+    let path_str = repo_path.join(file).to_string_lossy().to_string();
+    let nodes = graph.query().property("path", path_str).execute().ok()?;
+    nodes.into_iter().find(|&node_id| {
+        graph
+            .get_node(node_id)
+            .is_ok_and(|node| node.node_type == NodeType::CodeFile)
+    })
+}
+
+/// Every line covered by inclusive `ranges`.
+pub(crate) fn hunk_lines(ranges: &[(u32, u32)]) -> Vec<u32> {
+    ranges
+        .iter()
+        .flat_map(|&(start, end)| start..=end)
+        .collect()
+}
+
+/// Group `lines` by the innermost of `spans` enclosing them, as
+/// `(span index, first line, last line, line count)` in order of each
+/// span's first line in `lines`. Lines outside every span are dropped.
+fn assign_lines(spans: &[(u32, u32)], lines: &[u32]) -> Vec<(usize, u32, u32, usize)> {
+    let mut assigned: Vec<(usize, u32, u32, usize)> = Vec::new();
+    for &line in lines {
+        let Some(index) = innermost_enclosing(spans, line) else {
+            continue;
+        };
+        match assigned
+            .iter_mut()
+            .find(|(assigned, ..)| *assigned == index)
+        {
+            Some((_, first, last, count)) => {
+                *first = (*first).min(line);
+                *last = (*last).max(line);
+                *count += 1;
+            }
+            None => assigned.push((index, line, line, 1)),
+        }
+    }
+    assigned
+}
+
+/// Index of the narrowest span containing `line`, if any.
+fn innermost_enclosing(spans: &[(u32, u32)], line: u32) -> Option<usize> {
+    spans
+        .iter()
+        .enumerate()
+        .filter(|(_, span)| span.0 > 0 && span.0 <= line && line <= span.1)
+        .min_by_key(|(_, span)| span.1 - span.0)
+        .map(|(index, _)| index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_innermost_enclosing() {
+        // A class spanning a method, and a free function
+        let spans = [(10, 50), (20, 30), (60, 70), (0, 0)];
+        assert_eq!(innermost_enclosing(&spans, 25), Some(1));
+        assert_eq!(innermost_enclosing(&spans, 40), Some(0));
+        assert_eq!(innermost_enclosing(&spans, 60), Some(2));
+        assert_eq!(innermost_enclosing(&spans, 55), None);
+        // Nodes without a line range never enclose anything
+        assert_eq!(innermost_enclosing(&[(0, 0)], 0), None);
+    }
+
+    #[test]
+    fn test_assign_lines() {
+        let spans = [(10, 50), (20, 30), (60, 70)];
+        let lines = hunk_lines(&[(62, 63), (21, 22), (40, 40), (55, 55), (69, 69)]);
+        assert_eq!(lines, vec![62, 63, 21, 22, 40, 55, 69]);
+        assert_eq!(
+            assign_lines(&spans, &lines),
+            vec![(2, 62, 69, 3), (1, 21, 22, 2), (0, 40, 40, 1)]
+        );
+    }
+}
@@ -0,0 +1,364 @@
+//! Graph Persistence
+//!
+//! Saves the CodeGraph to `<workspace>/.codegraph/graph/` on shutdown so the
+//! next startup re-parses only the files that changed instead of the whole
+//! workspace.
+//!
+//! A file's nodes are restored only when the file still hashes to the content
+//! hash recorded when it was parsed. Nodes of files that changed or
+//! disappeared are dropped, and those files are parsed again along with any
+//! new ones. Restored nodes keep their ids, so the persisted query indexes,
+//! which refer to nodes by id, still match the graph.
+
+use crate::ai_query::content_hash;
+use codegraph::{CodeGraph, EdgeType, NodeId, NodeType, PropertyMap, PropertyValue};
+use codegraph_parser_api::FileInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Bump whenever the snapshot layout or the parsers' output changes.
+const GRAPH_SNAPSHOT_VERSION: u32 = 1;
+
+/// File name of the snapshot inside the graph directory.
+const GRAPH_SNAPSHOT_FILE: &str = "graph.json";
+
+/// File node property holding the hash of the text the file was parsed from.
+const CONTENT_HASH: &str = "content_hash";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotNode {
+    id: NodeId,
+    node_type: NodeType,
+    properties: PropertyMap,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotEdge {
+    source: NodeId,
+    target: NodeId,
+    edge_type: EdgeType,
+    properties: PropertyMap,
+}
+
+/// A source file as it was indexed.
+#[derive(Debug, Serialize, Deserialize)]
+struct SnapshotFile {
+    /// Content hash at save time
+    hash: u64,
+    /// Symbols the file contributed to the symbol index
+    symbols: Vec<NodeId>,
+}
+
+/// On-disk snapshot of the graph.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct GraphSnapshot {
+    version: u32,
+    nodes: Vec<SnapshotNode>,
+    edges: Vec<SnapshotEdge>,
+    /// Source file path -> hash and symbols
+    files: BTreeMap<String, SnapshotFile>,
+}
+
+/// A graph rebuilt from a snapshot.
+pub(crate) struct RestoredGraph {
+    pub graph: CodeGraph,
+    /// Files whose nodes were restored, to add to the symbol index
+    pub files: Vec<(PathBuf, FileInfo)>,
+    /// Number of files dropped because they changed or were deleted
+    pub stale: usize,
+}
+
+/// Directory holding the persisted graph for a workspace.
+pub fn graph_dir(workspace: &Path) -> PathBuf {
+    workspace.join(".codegraph").join("graph")
+}
+
+/// Record the hash of `source`, the text a file was just parsed from, on its
+/// file node. A snapshot keeps the file's nodes only while it still hashes
+/// to this, so the nodes always match the text they came from.
+pub(crate) fn record_content_hash(graph: &mut CodeGraph, file_info: &FileInfo, source: &str) {
+    let hash = content_hash(source.as_bytes());
+    let props = PropertyMap::new().with(CONTENT_HASH, PropertyValue::Int(hash as i64));
+    let _ = graph.update_node_properties(file_info.file_id, props);
+}
+
+/// Add `node` to `graph` under its snapshot id.
+///
+/// Ids are handed out in sequence and never reused, so the ids of nodes that
+/// are gone are used up by adding the node and deleting it again until it
+/// gets its own. Falls back to the next free id when the sequence is already
+/// past it.
+fn add_with_id(graph: &mut CodeGraph, node: &SnapshotNode) -> Option<NodeId> {
+    let mut previous = None;
+    loop {
+        let id = graph
+            .add_node(node.node_type, node.properties.clone())
+            .ok()?;
+        if id >= node.id || previous.is_some_and(|previous| id <= previous) {
+            return Some(id);
+        }
+        let _ = graph.delete_node(id);
+        previous = Some(id);
+    }
+}
+
+impl GraphSnapshot {
+    /// Capture `graph`. `symbols` lists the indexed symbols of a file. Files
+    /// in `skip`, e.g. open with unsaved changes, and files without a
+    /// recorded content hash are left out so they are parsed again.
+    pub(crate) fn capture(
+        graph: &CodeGraph,
+        symbols: impl Fn(&Path) -> Vec<NodeId>,
+        skip: &HashSet<String>,
+    ) -> Self {
+        let nodes = graph
+            .iter_nodes()
+            .map(|(id, node)| SnapshotNode {
+                id,
+                node_type: node.node_type,
+                properties: node.properties.clone(),
+            })
+            .collect();
+        let edges = graph
+            .iter_edges()
+            .map(|(_, edge)| SnapshotEdge {
+                source: edge.source_id,
+                target: edge.target_id,
+                edge_type: edge.edge_type,
+                properties: edge.properties.clone(),
+            })
+            .collect();
+
+        let files = graph
+            .iter_nodes()
+            .filter(|(_, node)| node.node_type == NodeType::CodeFile)
+            .filter_map(|(_, node)| {
+                let path = node.properties.get_string("path")?;
+                if path.is_empty() || skip.contains(path) {
+                    return None;
+                }
+                let file = SnapshotFile {
+                    hash: node.properties.get_int(CONTENT_HASH)? as u64,
+                    symbols: symbols(Path::new(path)),
+                };
+                Some((path.to_string(), file))
+            })
+            .collect();
+
+        Self {
+            version: GRAPH_SNAPSHOT_VERSION,
+            nodes,
+            edges,
+            files,
+        }
+    }
+
+    /// Rebuild the graph, keeping the nodes of files that are unchanged on
+    /// disk and nodes that belong to no file. Returns `None` if the snapshot
+    /// was written by a different format version.
+    pub(crate) fn restore(self) -> Option<RestoredGraph> {
+        if self.version != GRAPH_SNAPSHOT_VERSION {
+            return None;
+        }
+
+        // Unchanged files, with their line and byte counts
+        let mut fresh: HashMap<&str, (usize, usize)> = HashMap::new();
+        for (path, file) in &self.files {
+            match std::fs::read(path) {
+                Ok(bytes) if content_hash(&bytes) == file.hash => {
+                    let lines = String::from_utf8_lossy(&bytes).lines().count();
+                    fresh.insert(path.as_str(), (lines, bytes.len()));
+                }
+                _ => {}
+            }
+        }
+
+        let mut graph = CodeGraph::in_memory().ok()?;
+        let mut ids: HashMap<NodeId, NodeId> = HashMap::new();
+        let mut file_nodes: HashMap<&str, NodeId> = HashMap::new();
+        let mut nodes: Vec<&SnapshotNode> = self.nodes.iter().collect();
+        nodes.sort_by_key(|node| node.id);
+        for node in nodes {
+            let path = node.properties.get_string("path").unwrap_or_default();
+            if !path.is_empty() && !fresh.contains_key(path) {
+                continue;
+            }
+            let Some(id) = add_with_id(&mut graph, node) else {
+                continue;
+            };
+            ids.insert(node.id, id);
+            if node.node_type == NodeType::CodeFile {
+                file_nodes.insert(path, id);
+            }
+        }
+        for edge in self.edges {
+            if let (Some(&source), Some(&target)) = (ids.get(&edge.source), ids.get(&edge.target)) {
+                let _ = graph.add_edge(source, target, edge.edge_type, edge.properties);
+            }
+        }
+
+        let mut files = Vec::new();
+        for (path, (line_count, byte_count)) in &fresh {
+            let Some(&file_id) = file_nodes.get(path) else {
+                continue;
+            };
+            let mut info = FileInfo {
+                file_path: PathBuf::from(path),
+                file_id,
+                functions: vec![],
+                classes: vec![],
+                traits: vec![],
+                imports: vec![],
+                parse_time: std::time::Duration::ZERO,
+                line_count: *line_count,
+                byte_count: *byte_count,
+            };
+            for symbol in &self.files[*path].symbols {
+                let Some(&id) = ids.get(symbol) else {
+                    continue;
+                };
+                match graph.get_node(id).map(|node| node.node_type) {
+                    Ok(NodeType::Function) => info.functions.push(id),
+                    Ok(NodeType::Class) => info.classes.push(id),
+                    Ok(_) => info.traits.push(id),
+                    Err(_) => {}
+                }
+            }
+            files.push((PathBuf::from(path), info));
+        }
+
+        Some(RestoredGraph {
+            graph,
+            stale: self.files.len() - fresh.len(),
+            files,
+        })
+    }
+
+    /// Write the snapshot to `dir`, creating it if needed.
+    ///
+    /// Writes to a temporary file first so a crash mid-write never leaves a
+    /// truncated snapshot behind.
+    pub(crate) fn save(&self, dir: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {e}", dir.display()))?;
+
+        let json = serde_json::to_vec(self).map_err(|e| format!("Failed to serialize: {e}"))?;
+        let path = dir.join(GRAPH_SNAPSHOT_FILE);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .map_err(|e| format!("Failed to write {}: {e}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .map_err(|e| format!("Failed to write {}: {e}", path.display()))?;
+        Ok(())
+    }
+
+    /// Read a snapshot from `dir`. Returns `None` if absent or unreadable.
+    pub(crate) fn load(dir: &Path) -> Option<Self> {
+        let bytes = std::fs::read(dir.join(GRAPH_SNAPSHOT_FILE)).ok()?;
+        match serde_json::from_slice(&bytes) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                tracing::warn!("Ignoring corrupt graph snapshot: {}", e);
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_restore_keeps_unchanged_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let kept = dir.path().join("kept.py");
+        let changed = dir.path().join("changed.py");
+        std::fs::write(&kept, "def kept():\n    helper()\n").unwrap();
+        std::fs::write(&changed, "def helper():\n    pass\n").unwrap();
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut ids = HashMap::new();
+        for (path, node_type, name) in [
+            (&kept, NodeType::CodeFile, "kept.py"),
+            (&kept, NodeType::Function, "kept"),
+            (&changed, NodeType::CodeFile, "changed.py"),
+            (&changed, NodeType::Function, "helper"),
+        ] {
+            let mut props = PropertyMap::new()
+                .with("name", name)
+                .with("path", path.to_string_lossy().to_string());
+            if node_type == NodeType::CodeFile {
+                let hash = content_hash(&std::fs::read(path).unwrap());
+                props = props.with(CONTENT_HASH, PropertyValue::Int(hash as i64));
+            }
+            ids.insert(name, graph.add_node(node_type, props).unwrap());
+        }
+        // A node deleted since leaves a gap in the ids
+        let gone = graph
+            .add_node(NodeType::Function, PropertyMap::new())
+            .unwrap();
+        graph.delete_node(gone).unwrap();
+        let module = graph
+            .add_node(NodeType::Module, PropertyMap::new().with("name", "os"))
+            .unwrap();
+        for (from, to, edge_type) in [
+            (ids["kept.py"], ids["kept"], EdgeType::Contains),
+            (ids["kept"], ids["helper"], EdgeType::Calls),
+            (ids["kept.py"], module, EdgeType::Imports),
+        ] {
+            graph
+                .add_edge(from, to, edge_type, PropertyMap::new())
+                .unwrap();
+        }
+
+        let symbols = |path: &Path| {
+            if path == kept {
+                vec![ids["kept"]]
+            } else {
+                vec![ids["helper"]]
+            }
+        };
+        let snapshot = GraphSnapshot::capture(&graph, symbols, &HashSet::new());
+        snapshot.save(&graph_dir(dir.path())).unwrap();
+        std::fs::write(&changed, "def helper():\n    return 1\n").unwrap();
+
+        let restored = GraphSnapshot::load(&graph_dir(dir.path()))
+            .unwrap()
+            .restore()
+            .unwrap();
+        assert_eq!(restored.stale, 1);
+        // kept.py, kept() and the module it imports
+        assert_eq!(restored.graph.node_count(), 3);
+        // The call into the changed file is dropped with it
+        assert_eq!(restored.graph.edge_count(), 2);
+        assert_eq!(restored.files.len(), 1);
+        let (path, info) = &restored.files[0];
+        assert_eq!(path, &kept);
+        assert_eq!(info.functions, vec![ids["kept"]]);
+        assert_eq!(info.line_count, 2);
+        // Restored nodes keep their ids, past the gaps left by dropped nodes
+        for (id, name) in [
+            (ids["kept.py"], "kept.py"),
+            (ids["kept"], "kept"),
+            (module, "os"),
+        ] {
+            let node = restored.graph.get_node(id).unwrap();
+            assert_eq!(node.properties.get_string("name"), Some(name));
+        }
+    }
+
+    #[test]
+    fn test_capture_skips_files_without_content_hash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("new.py");
+        std::fs::write(&path, "def f():\n    pass\n").unwrap();
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let props = PropertyMap::new().with("path", path.to_string_lossy().to_string());
+        graph.add_node(NodeType::CodeFile, props).unwrap();
+
+        let snapshot = GraphSnapshot::capture(&graph, |_| Vec::new(), &HashSet::new());
+        assert!(snapshot.files.is_empty());
+    }
+}
@@ -5,12 +5,20 @@
 //! composable query primitives for AI agents to explore codebases.
 
 use crate::ai_query::{
-    EntryType, ImportMatchMode, ImportSearchOptions, SearchOptions, SignaturePattern, SymbolType,
-    TraversalDirection, TraversalFilter,
+    group_results, CentralityMetric, ConfigSource, CycleLevel, EntryType, GroupBy,
+    ImplementationDirection, ImportMatchMode, ImportSearchOptions, ReachabilityPredicate,
+    SearchMode, SearchOptions, SignaturePattern, SimilarityMode, SymbolType, TodoTag,
+    TraversalDirection, TraversalFilter, TypeHierarchyDirection, MAX_GROUPED_MATCHES,
 };
 use crate::backend::CodeGraphBackend;
+use crate::cache::{PrimitiveCacheEntry, PrimitiveResult};
+use crate::cancellation::run_cancellable;
+use crate::domain::node_props;
 use codegraph::NodeId;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::Url;
 
@@ -35,14 +43,35 @@ pub struct SymbolSearchParams {
     /// Include private symbols (default: false)
     #[serde(default)]
     pub include_private: Option<bool>,
+    /// Query mode: "text" (default) or "regex" (matched against names and signatures)
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Aggregate matches by "file" or "directory"; `limit` then caps the number of groups
+    #[serde(default)]
+    pub group_by: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SymbolSearchResult {
     pub results: Vec<SymbolMatchResponse>,
+    /// Matches aggregated by file/directory (only when `groupBy` is set; `results` is then empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<ResultGroupResponse>>,
     pub total_matches: usize,
     pub query_time_ms: u64,
+    /// The time budget ran out; results are what was found until then
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResultGroupResponse {
+    pub key: String,
+    pub count: usize,
+    pub score: f32,
+    pub top_symbols: Vec<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -162,16 +191,33 @@ pub struct TraverseGraphParams {
     /// Filter by symbol types
     #[serde(default)]
     pub filter_symbol_types: Option<Vec<String>>,
-    /// Maximum nodes to return
+    /// Maximum nodes to return (page size when paging with `cursor`)
     #[serde(default)]
     pub max_nodes: Option<usize>,
+    /// Aggregate reached nodes by "file" or "directory"
+    #[serde(default)]
+    pub group_by: Option<String>,
+    /// `nextCursor` of the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TraverseGraphResponse {
     pub nodes: Vec<TraversalNodeResponse>,
+    /// Nodes aggregated by file/directory (only when `groupBy` is set; `nodes` is then empty)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<ResultGroupResponse>>,
+    /// More nodes were reached than returned
+    pub has_more: bool,
+    /// Pass as `cursor` to fetch the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
     pub query_time_ms: u64,
+    /// The time budget ran out; nodes are what was reached until then
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
     /// Whether a fallback to nearest symbol was used
     #[serde(skip_serializing_if = "Option::is_none")]
     pub used_fallback: Option<bool>,
@@ -207,12 +253,23 @@ pub struct GetCallersParams {
     /// Depth of caller chain (default: 1)
     #[serde(default)]
     pub depth: Option<u32>,
+    /// Page size (default: all)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// `nextCursor` of the previous page
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetCallersResponse {
     pub callers: Vec<CallInfoResponse>,
+    /// Number of callers across all pages
+    pub total: usize,
+    /// Pass as `cursor` to fetch the next page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
     pub query_time_ms: u64,
     /// Whether a fallback to nearest symbol was used
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -351,166 +408,966 @@ pub struct FindBySignatureResponse {
 }
 
 // ==========================================
-// Handler Implementations
+// Find Cycles Request
 // ==========================================
 
-impl CodeGraphBackend {
-    /// Handle symbol search request
-    pub async fn handle_symbol_search(
-        &self,
-        params: SymbolSearchParams,
-    ) -> Result<SymbolSearchResult> {
-        // Build search options
-        let mut options = SearchOptions::new();
-
-        if let Some(limit) = params.limit {
-            options = options.with_limit(limit);
-        }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindCyclesParams {
+    /// Edge types to follow (default: ["imports", "importsfrom", "calls"])
+    #[serde(default)]
+    pub edge_types: Option<Vec<String>>,
+    /// Granularity: "file" (default) or "symbol"
+    #[serde(default)]
+    pub level: Option<String>,
+    /// Minimum cycle size to report (default: 2)
+    #[serde(default)]
+    pub min_size: Option<usize>,
+    /// Maximum number of cycles to return (default: 50)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
 
-        if params.include_private.unwrap_or(false) {
-            options = options.include_private();
-        }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindCyclesResponse {
+    pub cycles: Vec<DependencyCycleResponse>,
+    pub total_cycles: usize,
+    pub members_in_cycles: usize,
+    pub level: String,
+    pub query_time_ms: u64,
+}
 
-        if let Some(types) = params.symbol_types {
-            let symbol_types: Vec<SymbolType> = types
-                .iter()
-                .filter_map(|t| match t.as_str() {
-                    "function" => Some(SymbolType::Function),
-                    "class" => Some(SymbolType::Class),
-                    "variable" => Some(SymbolType::Variable),
-                    "module" => Some(SymbolType::Module),
-                    "interface" => Some(SymbolType::Interface),
-                    "type" => Some(SymbolType::Type),
-                    _ => None,
-                })
-                .collect();
-            options = options.with_symbol_types(symbol_types);
-        }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyCycleResponse {
+    pub size: usize,
+    pub members: Vec<CycleMemberResponse>,
+    pub files: Vec<String>,
+    pub edge_types: Vec<String>,
+}
 
-        let result = self
-            .query_engine
-            .symbol_search(&params.query, &options)
-            .await;
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CycleMemberResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    pub name: String,
+    pub file: String,
+    pub line: u32,
+}
 
-        let results = result
-            .results
-            .into_iter()
-            .map(|m| SymbolMatchResponse {
-                node_id: m.node_id.to_string(),
-                symbol: symbol_info_to_response(&m.symbol),
-                score: m.score,
-                match_reason: m.match_reason,
-            })
-            .collect();
+// ==========================================
+// Get Hot Symbols Request
+// ==========================================
 
-        Ok(SymbolSearchResult {
-            results,
-            total_matches: result.total_matches,
-            query_time_ms: result.query_time_ms,
-        })
-    }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHotSymbolsParams {
+    /// Ranking metric: "pagerank", "betweenness", or "combined" (default)
+    #[serde(default)]
+    pub metric: Option<String>,
+    /// Maximum number of symbols to return (default: 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Restrict results to symbols in files matching this URI or path fragment
+    #[serde(default)]
+    pub uri: Option<String>,
+}
 
-    /// Handle find by imports request
-    pub async fn handle_find_by_imports(
-        &self,
-        params: FindByImportsParams,
-    ) -> Result<FindByImportsResponse> {
-        let start = std::time::Instant::now();
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetHotSymbolsResponse {
+    pub symbols: Vec<HotSymbolResponse>,
+    pub metric: String,
+    pub total_ranked: usize,
+    pub query_time_ms: u64,
+}
 
-        let match_mode = match params.match_mode.as_deref() {
-            Some("prefix") => ImportMatchMode::Prefix,
-            Some("fuzzy") => ImportMatchMode::Fuzzy,
-            _ => ImportMatchMode::Exact,
-        };
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HotSymbolResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub score: f32,
+    pub pagerank: f32,
+    pub betweenness: f32,
+    pub caller_count: usize,
+    pub callee_count: usize,
+}
 
-        let options = ImportSearchOptions::new().with_match_mode(match_mode);
+// ==========================================
+// Get Risk Hotspots Request
+// ==========================================
 
-        // Search for each library
-        let mut all_results = Vec::new();
-        for library in &params.libraries {
-            let results = self.query_engine.find_by_imports(library, &options).await;
-            all_results.extend(results);
-        }
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRiskHotspotsParams {
+    /// Maximum number of symbols to return (default: 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// Restrict results to symbols in files matching this URI or path fragment
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// Number of recent commits to mine for churn (default: 500)
+    #[serde(default)]
+    pub max_commits: Option<usize>,
+    /// Ignore files changed in fewer commits than this (default: 2)
+    #[serde(default)]
+    pub min_changes: Option<usize>,
+    /// Only count commits after this date (e.g. "2024-01-01", "6 months ago")
+    #[serde(default)]
+    pub since: Option<String>,
+    /// Only count commits before this date
+    #[serde(default)]
+    pub until: Option<String>,
+    /// Halve the weight of a change every this many days, so that recent
+    /// churn counts more (default: all changes count the same)
+    #[serde(default)]
+    pub half_life_days: Option<f32>,
+    /// Rank symbols by the churn of their whole directory instead of their
+    /// own file (default: false)
+    #[serde(default)]
+    pub by_directory: Option<bool>,
+}
 
-        let results = all_results
-            .into_iter()
-            .map(|m| SymbolMatchResponse {
-                node_id: m.node_id.to_string(),
-                symbol: symbol_info_to_response(&m.symbol),
-                score: m.score,
-                match_reason: m.match_reason,
-            })
-            .collect();
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetRiskHotspotsResponse {
+    pub hotspots: Vec<RiskHotspotResponse>,
+    pub files_with_churn: usize,
+    pub total_ranked: usize,
+    pub query_time_ms: u64,
+}
 
-        Ok(FindByImportsResponse {
-            results,
-            query_time_ms: start.elapsed().as_millis() as u64,
-        })
-    }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RiskHotspotResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub risk: f32,
+    pub churn: usize,
+    pub churn_score: f32,
+    pub centrality: f32,
+    pub caller_count: usize,
+}
 
-    /// Handle find entry points request
-    pub async fn handle_find_entry_points(
-        &self,
-        params: FindEntryPointsParams,
-    ) -> Result<FindEntryPointsResponse> {
-        let entry_types: Vec<EntryType> = if let Some(et) = params.entry_type {
-            match et.as_str() {
-                "http_handler" => vec![EntryType::HttpHandler],
-                "cli_command" => vec![EntryType::CliCommand],
-                "public_api" => vec![EntryType::PublicApi],
-                "event_handler" => vec![EntryType::EventHandler],
-                "test_entry" => vec![EntryType::TestEntry],
-                "main" => vec![EntryType::Main],
-                _ => vec![],
-            }
-        } else {
-            vec![]
-        };
+// ==========================================
+// Get Code Ownership Request
+// ==========================================
 
-        let results = self.query_engine.find_entry_points(&entry_types).await;
-        let total_found = results.len();
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCodeOwnershipParams {
+    /// Restrict the map to this file or directory (URI or path)
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// "module" to group by directory (default) or "file"
+    #[serde(default)]
+    pub group_by: Option<String>,
+    /// Number of recent commits to analyze (default: 500)
+    #[serde(default)]
+    pub max_commits: Option<usize>,
+    /// Maximum number of files or modules to return (default: 20)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
 
-        let entry_points = results
-            .into_iter()
-            .map(|ep| EntryPointResponse {
-                node_id: ep.node_id.to_string(),
-                entry_type: format!("{:?}", ep.entry_type).to_lowercase(),
-                route: ep.route,
-                method: ep.method,
-                description: ep.description,
-                symbol: symbol_info_to_response(&ep.symbol),
-            })
-            .collect();
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetCodeOwnershipResponse {
+    pub entries: Vec<OwnershipEntryResponse>,
+    /// Most active authors across the whole scope, as suggested reviewers
+    pub reviewers: Vec<AuthorShareResponse>,
+    pub commits_analyzed: usize,
+}
 
-        Ok(FindEntryPointsResponse {
-            entry_points,
-            total_found,
-        })
-    }
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnershipEntryResponse {
+    pub path: String,
+    pub commits: usize,
+    pub owners: Vec<AuthorShareResponse>,
+}
 
-    /// Handle traverse graph request
-    pub async fn handle_traverse_graph(
-        &self,
-        params: TraverseGraphParams,
-    ) -> Result<TraverseGraphResponse> {
-        let start = std::time::Instant::now();
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuthorShareResponse {
+    pub author: String,
+    pub email: String,
+    pub commits: usize,
+    pub share: f32,
+    pub last_change: String,
+}
 
-        // Resolve start node with fallback support
-        let (start_node, used_fallback, fallback_message) = self
-            .resolve_node_id_with_fallback(&params.start_node_id, &params.uri, &params.line)
-            .await?;
+impl From<crate::git_mining::AuthorShare> for AuthorShareResponse {
+    fn from(share: crate::git_mining::AuthorShare) -> Self {
+        Self {
+            author: share.author,
+            email: share.email,
+            commits: share.commits,
+            share: share.share,
+            last_change: share.last_change,
+        }
+    }
+}
 
-        let direction = match params.direction.as_deref() {
-            Some("incoming") => TraversalDirection::Incoming,
-            Some("both") => TraversalDirection::Both,
-            _ => TraversalDirection::Outgoing,
-        };
+// ==========================================
+// Find Type Usages Request
+// ==========================================
 
-        let depth = params.depth.unwrap_or(3);
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindTypeUsagesParams {
+    /// Node ID of the type/class, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// Maximum number of usages to return (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
 
-        let mut filter = TraversalFilter::new();
-        if let Some(max) = params.max_nodes {
-            filter = filter.with_max_nodes(max);
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindTypeUsagesResponse {
+    pub type_name: String,
+    pub usages: Vec<TypeUsageResponse>,
+    pub total_usages: usize,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeUsageResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub usage_kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub via: Option<String>,
+}
+
+// ==========================================
+// Find Implementations Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindImplementationsParams {
+    /// Node ID of the trait/interface/class, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// "implementations" (default) or "supertypes"
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Maximum number of results to return (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindImplementationsResponse {
+    pub target_name: String,
+    pub direction: String,
+    pub implementations: Vec<ImplementationResponse>,
+    pub total_implementations: usize,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImplementationResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub relation: String,
+}
+
+// ==========================================
+// Get Type Hierarchy Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTypeHierarchyParams {
+    /// Node ID of the class/interface/trait, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// "subtypes" (default) or "supertypes"
+    #[serde(default)]
+    pub direction: Option<String>,
+    /// Maximum depth to walk (default: unlimited)
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetTypeHierarchyResponse {
+    pub root: SymbolInfoResponse,
+    pub direction: String,
+    pub nodes: Vec<TypeHierarchyNodeResponse>,
+    pub max_depth: u32,
+    pub has_cycle: bool,
+    pub truncated: bool,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypeHierarchyNodeResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub depth: u32,
+    pub parent_id: String,
+    pub relation: String,
+}
+
+// ==========================================
+// Reaches Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReachesParams {
+    /// Node ID of the starting symbol, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// Sink symbol name globs (e.g. "db.*")
+    #[serde(default)]
+    pub sinks: Option<Vec<String>>,
+    /// Built-in sink categories: filesystem, network, process, database
+    #[serde(default)]
+    pub categories: Option<Vec<String>>,
+    /// Edge types to follow (default: Calls)
+    #[serde(default)]
+    pub edge_types: Option<Vec<String>>,
+    /// Maximum depth (default: 10)
+    #[serde(default)]
+    pub max_depth: Option<u32>,
+    /// Maximum number of matches (default: 20)
+    #[serde(default)]
+    pub max_matches: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReachesResponse {
+    pub from: SymbolInfoResponse,
+    pub reachable: bool,
+    pub matches: Vec<SinkMatchResponse>,
+    pub nodes_visited: usize,
+    pub truncated: bool,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SinkMatchResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub sink: String,
+    pub pattern: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    pub external: bool,
+    pub depth: u32,
+    pub path: Vec<String>,
+}
+
+// ==========================================
+// Find Similar Code Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindSimilarCodeParams {
+    /// Node ID of the function, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    /// "auto" (default), "semantic" or "structural"
+    #[serde(default)]
+    pub mode: Option<String>,
+    /// Minimum similarity score (default: 0.1 semantic, 0.5 structural)
+    #[serde(default)]
+    pub min_similarity: Option<f32>,
+    /// Maximum number of results (default: 10)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindSimilarCodeResponse {
+    pub target: SymbolInfoResponse,
+    pub mode: String,
+    pub matches: Vec<SimilarCodeResponse>,
+    pub total_compared: usize,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimilarCodeResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub similarity: f32,
+}
+
+// ==========================================
+// Find Config Usages Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindConfigUsagesParams {
+    /// Key or glob, e.g. "DATABASE_URL" or "AWS_*"
+    pub key: String,
+    /// "env" or "config" (default: both)
+    #[serde(default)]
+    pub source: Option<String>,
+    /// Maximum number of usages (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindConfigUsagesResponse {
+    pub key: String,
+    pub usages: Vec<ConfigUsageResponse>,
+    pub keys: Vec<String>,
+    pub total_usages: usize,
+    pub files_scanned: usize,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigUsageResponse {
+    pub key: String,
+    pub source: String,
+    pub accessor: String,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<SymbolInfoResponse>,
+}
+
+// ==========================================
+// Find Todos Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindTodosParams {
+    /// Any of "todo", "fixme", "hack", "safety" (default: all)
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// File URI or path fragment to restrict the search to
+    #[serde(default)]
+    pub uri: Option<String>,
+    /// Blame author (or inline owner) containing this text, case-insensitive
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Only comments last changed at least this many days ago
+    #[serde(default)]
+    pub min_age_days: Option<u64>,
+    /// Only comments last changed at most this many days ago
+    #[serde(default)]
+    pub max_age_days: Option<u64>,
+    /// Attach blame author and age even without author/age filters
+    #[serde(default)]
+    pub include_blame: Option<bool>,
+    /// Maximum number of comments (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindTodosResponse {
+    pub todos: Vec<TodoCommentResponse>,
+    /// Number of matching comments before the limit was applied
+    pub total: usize,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoCommentResponse {
+    pub tag: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub owner: Option<String>,
+    pub file: String,
+    pub line: u32,
+    pub column: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol: Option<SymbolInfoResponse>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+    /// Unix timestamp of the commit that last changed the line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author_time: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub age_days: Option<u64>,
+}
+
+// ==========================================
+// Preview Rename Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewRenameParams {
+    /// Node ID of the symbol to rename, or uri+line
+    #[serde(default)]
+    pub node_id: Option<String>,
+    #[serde(default)]
+    pub uri: Option<String>,
+    #[serde(default)]
+    pub line: Option<u32>,
+    pub new_name: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewRenameResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub old_name: String,
+    pub new_name: String,
+    pub references: Vec<RenameReferenceResponse>,
+    pub collisions: Vec<RenameCollisionResponse>,
+    pub memories: Vec<RenameMemoryResponse>,
+    pub affected_files: Vec<String>,
+    pub query_time_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub used_fallback: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fallback_message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameReferenceResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub reference_kind: String,
+    pub file: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameCollisionResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub scope: String,
+    pub in_referencing_file: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenameMemoryResponse {
+    pub id: String,
+    pub title: String,
+    pub kind: String,
+    pub linked: bool,
+}
+
+// ==========================================
+// Find By Annotation Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindByAnnotationParams {
+    /// Decorator or attribute, e.g. "@app.route", "#[tokio::test]" or "Deprecated"
+    pub annotation: String,
+    /// Restrict to "function", "class" or "interface" (default: all)
+    #[serde(default)]
+    pub symbol_types: Option<Vec<String>>,
+    /// Maximum number of results (default: 100)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FindByAnnotationResponse {
+    pub annotation: String,
+    pub results: Vec<AnnotatedSymbolResponse>,
+    pub total: usize,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AnnotatedSymbolResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub annotations: Vec<String>,
+}
+
+// ==========================================
+// Query Pipeline Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPipelineParams {
+    /// Pipeline, e.g. `symbol_search("auth") | callers(depth=2) | limit(20)`
+    pub query: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryPipelineResponse {
+    pub query: String,
+    pub results: Vec<PipelineMatchResponse>,
+    pub stages: Vec<PipelineStageResponse>,
+    pub truncated: bool,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineMatchResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PipelineStageResponse {
+    pub stage: String,
+    pub count: usize,
+}
+
+// ==========================================
+// Get File Outline Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFileOutlineParams {
+    /// File URI or path
+    pub uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetFileOutlineResponse {
+    pub file: String,
+    pub symbols: Vec<OutlineNodeResponse>,
+    pub total_symbols: usize,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineNodeResponse {
+    pub node_id: String,
+    pub symbol: SymbolInfoResponse,
+    pub children: Vec<OutlineNodeResponse>,
+}
+
+// ==========================================
+// Batch Query Request
+// ==========================================
+
+/// Maximum number of queries accepted in one batch.
+pub const MAX_BATCH_QUERIES: usize = 32;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchQueryParams {
+    pub queries: Vec<BatchQueryItem>,
+}
+
+/// One query in a batch: a custom request method and its params.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchQueryItem {
+    /// Caller-chosen id used to key the result
+    pub id: String,
+    /// Custom request method, e.g. "codegraph/symbolSearch" (the "codegraph/" prefix is optional)
+    pub method: String,
+    #[serde(default)]
+    pub params: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchQueryResponse {
+    /// Query id -> outcome
+    pub results: BTreeMap<String, BatchQueryItemResult>,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub query_time_ms: u64,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchQueryItemResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub query_time_ms: u64,
+}
+
+// ==========================================
+// Handler Implementations
+// ==========================================
+
+impl CodeGraphBackend {
+    /// Handle symbol search request
+    pub async fn handle_symbol_search(
+        &self,
+        params: SymbolSearchParams,
+    ) -> Result<SymbolSearchResult> {
+        let group_by = parse_group_by(params.group_by.as_deref())?;
+
+        // Build search options
+        let mut options = SearchOptions::new();
+
+        if group_by.is_some() {
+            // Aggregate over a wide result set; the limit applies to groups
+            options = options.with_limit(MAX_GROUPED_MATCHES);
+        } else if let Some(limit) = params.limit {
+            options = options.with_limit(limit);
+        }
+
+        if params.include_private.unwrap_or(false) {
+            options = options.include_private();
+        }
+
+        if let Some(types) = params.symbol_types {
+            let symbol_types: Vec<SymbolType> = types
+                .iter()
+                .filter_map(|t| match t.as_str() {
+                    "function" => Some(SymbolType::Function),
+                    "class" => Some(SymbolType::Class),
+                    "variable" => Some(SymbolType::Variable),
+                    "module" => Some(SymbolType::Module),
+                    "interface" => Some(SymbolType::Interface),
+                    "type" => Some(SymbolType::Type),
+                    _ => None,
+                })
+                .collect();
+            options = options.with_symbol_types(symbol_types);
+        }
+
+        match params.mode.as_deref() {
+            None | Some("text") => {}
+            Some("regex") => {
+                crate::ai_query::QueryEngine::compile_search_regex(&params.query)
+                    .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+                options = options.with_mode(SearchMode::Regex);
+            }
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid mode: {other}. Expected \"text\" or \"regex\""
+                )))
+            }
+        }
+
+        options = options.with_time_budget(self.config.read().await.query_budget());
+        let result = self
+            .query_engine
+            .symbol_search(&params.query, &options)
+            .await;
+
+        if let Some(group_by) = group_by {
+            let mut groups = group_results(
+                result.results.iter().map(|m| (&m.symbol, m.score)),
+                group_by,
+            );
+            groups.truncate(params.limit.unwrap_or(20));
+            return Ok(SymbolSearchResult {
+                results: Vec::new(),
+                groups: Some(groups.into_iter().map(group_to_response).collect()),
+                total_matches: result.total_matches,
+                query_time_ms: result.query_time_ms,
+                truncated: result.truncated,
+            });
+        }
+
+        let results = result
+            .results
+            .into_iter()
+            .map(|m| SymbolMatchResponse {
+                node_id: m.node_id.to_string(),
+                symbol: symbol_info_to_response(&m.symbol),
+                score: m.score,
+                match_reason: m.match_reason,
+            })
+            .collect();
+
+        Ok(SymbolSearchResult {
+            results,
+            groups: None,
+            total_matches: result.total_matches,
+            query_time_ms: result.query_time_ms,
+            truncated: result.truncated,
+        })
+    }
+
+    /// Handle find by imports request
+    pub async fn handle_find_by_imports(
+        &self,
+        params: FindByImportsParams,
+    ) -> Result<FindByImportsResponse> {
+        let start = std::time::Instant::now();
+
+        let match_mode = match params.match_mode.as_deref() {
+            Some("prefix") => ImportMatchMode::Prefix,
+            Some("fuzzy") => ImportMatchMode::Fuzzy,
+            _ => ImportMatchMode::Exact,
+        };
+
+        let options = ImportSearchOptions::new().with_match_mode(match_mode);
+
+        // Search for each library
+        let mut all_results = Vec::new();
+        for library in &params.libraries {
+            let results = self.query_engine.find_by_imports(library, &options).await;
+            all_results.extend(results);
+        }
+
+        let results = all_results
+            .into_iter()
+            .map(|m| SymbolMatchResponse {
+                node_id: m.node_id.to_string(),
+                symbol: symbol_info_to_response(&m.symbol),
+                score: m.score,
+                match_reason: m.match_reason,
+            })
+            .collect();
+
+        Ok(FindByImportsResponse {
+            results,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Handle find entry points request
+    pub async fn handle_find_entry_points(
+        &self,
+        params: FindEntryPointsParams,
+    ) -> Result<FindEntryPointsResponse> {
+        let entry_types: Vec<EntryType> = if let Some(et) = params.entry_type {
+            match et.as_str() {
+                "http_handler" => vec![EntryType::HttpHandler],
+                "cli_command" => vec![EntryType::CliCommand],
+                "public_api" => vec![EntryType::PublicApi],
+                "event_handler" => vec![EntryType::EventHandler],
+                "test_entry" => vec![EntryType::TestEntry],
+                "main" => vec![EntryType::Main],
+                _ => vec![],
+            }
+        } else {
+            vec![]
+        };
+
+        let results = self.query_engine.find_entry_points(&entry_types).await;
+        let total_found = results.len();
+
+        let entry_points = results
+            .into_iter()
+            .map(|ep| EntryPointResponse {
+                node_id: ep.node_id.to_string(),
+                entry_type: format!("{:?}", ep.entry_type).to_lowercase(),
+                route: ep.route,
+                method: ep.method,
+                description: ep.description,
+                symbol: symbol_info_to_response(&ep.symbol),
+            })
+            .collect();
+
+        Ok(FindEntryPointsResponse {
+            entry_points,
+            total_found,
+        })
+    }
+
+    /// Handle traverse graph request
+    pub async fn handle_traverse_graph(
+        &self,
+        params: TraverseGraphParams,
+    ) -> Result<TraverseGraphResponse> {
+        let start = std::time::Instant::now();
+        let group_by = parse_group_by(params.group_by.as_deref())?;
+
+        // Resolve start node with fallback support
+        let (start_node, used_fallback, fallback_message) = self
+            .resolve_node_id_with_fallback(&params.start_node_id, &params.uri, &params.line)
+            .await?;
+
+        let direction = match params.direction.as_deref() {
+            Some("incoming") => TraversalDirection::Incoming,
+            Some("both") => TraversalDirection::Both,
+            _ => TraversalDirection::Outgoing,
+        };
+
+        let depth = params.depth.unwrap_or(3);
+        let offset = decode_cursor(params.cursor.as_deref())?;
+
+        let mut filter = TraversalFilter::new();
+        if let Some(max) = params.max_nodes {
+            filter = filter.with_max_nodes(max);
+        }
+        // Ask for the nodes of earlier pages plus one to tell whether more follow
+        let page_size = filter.max_nodes;
+        if group_by.is_none() {
+            filter = filter.with_max_nodes(offset.saturating_add(page_size).saturating_add(1));
         }
         if let Some(types) = params.filter_symbol_types {
             let symbol_types: Vec<SymbolType> = types
@@ -528,238 +1385,1317 @@ impl CodeGraphBackend {
             filter = filter.with_symbol_types(symbol_types);
         }
 
-        let results = self
+        let cache_params = format!(
+            "direction={direction:?} depth={depth} max_nodes={} types={:?}",
+            filter.max_nodes, filter.symbol_types
+        );
+        let budget = self.config.read().await.query_budget();
+        let mut truncated = false;
+        let results = match self
+            .query_cache
+            .get_primitive("traverse", start_node, &cache_params)
+        {
+            Some(PrimitiveResult::Traversal(cached)) => cached,
+            _ => {
+                let engine = Arc::clone(&self.query_engine);
+                let traversal_filter = filter.clone();
+                let (results, timed_out) = run_cancellable(move |cancel| async move {
+                    let cancel = cancel.with_budget(budget);
+                    let results = engine
+                        .traverse_graph(start_node, direction, depth, &traversal_filter, &cancel)
+                        .await;
+                    (results, cancel.timed_out())
+                })
+                .await
+                .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?;
+                truncated = timed_out;
+                // Only an unfiltered, untruncated outgoing traversal returns every node
+                // it visited, so only then do the result files cover all inputs.
+                let any_file = !matches!(direction, TraversalDirection::Outgoing)
+                    || !filter.symbol_types.is_empty()
+                    || results.len() >= filter.max_nodes;
+                let files = results
+                    .iter()
+                    .map(|n| PathBuf::from(&n.symbol.location.file))
+                    .collect();
+                // Partial results would be served in place of complete ones
+                if !truncated {
+                    self.cache_primitive(
+                        "traverse",
+                        start_node,
+                        cache_params,
+                        PrimitiveResult::Traversal(results.clone()),
+                        files,
+                        any_file,
+                    )
+                    .await;
+                }
+                results
+            }
+        };
+
+        if let Some(group_by) = group_by {
+            let groups = group_results(results.iter().map(|n| (&n.symbol, 1.0)), group_by);
+            return Ok(TraverseGraphResponse {
+                nodes: Vec::new(),
+                groups: Some(groups.into_iter().map(group_to_response).collect()),
+                has_more: results.len() >= filter.max_nodes,
+                next_cursor: None,
+                query_time_ms: start.elapsed().as_millis() as u64,
+                truncated,
+                used_fallback: if used_fallback { Some(true) } else { None },
+                fallback_message,
+            });
+        }
+
+        let (page, next_cursor) = paginate(results, offset, Some(page_size));
+        let nodes = page
+            .into_iter()
+            .map(|n| TraversalNodeResponse {
+                node_id: n.node_id.to_string(),
+                depth: n.depth,
+                path: n.path.iter().map(|id| id.to_string()).collect(),
+                edge_type: n.edge_type,
+                symbol: symbol_info_to_response(&n.symbol),
+            })
+            .collect();
+
+        Ok(TraverseGraphResponse {
+            nodes,
+            groups: None,
+            has_more: next_cursor.is_some(),
+            next_cursor,
+            query_time_ms: start.elapsed().as_millis() as u64,
+            truncated,
+            used_fallback: if used_fallback { Some(true) } else { None },
+            fallback_message,
+        })
+    }
+
+    /// Handle get callers request
+    pub async fn handle_get_callers(&self, params: GetCallersParams) -> Result<GetCallersResponse> {
+        let start = std::time::Instant::now();
+
+        let (node_id, used_fallback, _) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+        let depth = params.depth.unwrap_or(1);
+        let offset = decode_cursor(params.cursor.as_deref())?;
+
+        let cache_params = format!(
+            "depth={depth} fallback_line={:?}",
+            used_fallback.then_some(params.line)
+        );
+        let domain_result = match self
+            .query_cache
+            .get_primitive("callers", node_id, &cache_params)
+        {
+            Some(PrimitiveResult::Callers(cached)) => cached,
+            _ => {
+                let result = crate::domain::callers::get_callers(
+                    &self.graph,
+                    &self.query_engine,
+                    node_id,
+                    depth,
+                    used_fallback,
+                    params.line,
+                )
+                .await;
+                // A new caller can appear in any file
+                self.cache_primitive(
+                    "callers",
+                    node_id,
+                    cache_params,
+                    PrimitiveResult::Callers(result.clone()),
+                    HashSet::new(),
+                    true,
+                )
+                .await;
+                result
+            }
+        };
+
+        let total = domain_result.callers.len();
+        let (page, next_cursor) = paginate(domain_result.callers, offset, params.limit);
+        Ok(GetCallersResponse {
+            callers: page.into_iter().map(call_info_to_response).collect(),
+            total,
+            next_cursor,
+            query_time_ms: start.elapsed().as_millis() as u64,
+            used_fallback: domain_result.used_fallback,
+            fallback_message: domain_result.fallback_message,
+        })
+    }
+
+    /// Handle get callees request
+    pub async fn handle_get_callees(&self, params: GetCallersParams) -> Result<GetCallersResponse> {
+        let start = std::time::Instant::now();
+
+        let (node_id, used_fallback, _) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+        let depth = params.depth.unwrap_or(1);
+        let offset = decode_cursor(params.cursor.as_deref())?;
+
+        let cache_params = format!(
+            "depth={depth} fallback_line={:?}",
+            used_fallback.then_some(params.line)
+        );
+        let domain_result = match self
+            .query_cache
+            .get_primitive("callees", node_id, &cache_params)
+        {
+            Some(PrimitiveResult::Callees(cached)) => cached,
+            _ => {
+                let result = crate::domain::callers::get_callees(
+                    &self.graph,
+                    &self.query_engine,
+                    node_id,
+                    depth,
+                    used_fallback,
+                    params.line,
+                )
+                .await;
+                let files = result
+                    .callees
+                    .iter()
+                    .map(|c| PathBuf::from(&c.symbol.location.file))
+                    .collect();
+                // An empty result falls back to same-name variants in other files
+                let any_file = result.callees.is_empty();
+                self.cache_primitive(
+                    "callees",
+                    node_id,
+                    cache_params,
+                    PrimitiveResult::Callees(result.clone()),
+                    files,
+                    any_file,
+                )
+                .await;
+                result
+            }
+        };
+
+        let total = domain_result.callees.len();
+        let (page, next_cursor) = paginate(domain_result.callees, offset, params.limit);
+        Ok(GetCallersResponse {
+            callers: page.into_iter().map(call_info_to_response).collect(),
+            total,
+            next_cursor,
+            query_time_ms: start.elapsed().as_millis() as u64,
+            used_fallback: domain_result.used_fallback,
+            fallback_message: domain_result.fallback_message,
+        })
+    }
+
+    /// Handle get detailed info request
+    pub async fn handle_get_detailed_symbol_info(
+        &self,
+        params: GetDetailedInfoParams,
+    ) -> Result<DetailedSymbolResponse> {
+        let (node_id, used_fallback, _) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+
+        let include_callers = params.include_callers.unwrap_or(true);
+        let include_callees = params.include_callees.unwrap_or(true);
+
+        let domain_result = crate::domain::symbol_info::get_symbol_info(
+            &self.graph,
+            &self.query_engine,
+            node_id,
+            include_callers || include_callees,
+            used_fallback,
+            params.line,
+        )
+        .await
+        .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Symbol not found"))?;
+
+        let callers = if include_callers {
+            domain_result
+                .callers
+                .unwrap_or_default()
+                .into_iter()
+                .map(call_info_to_response)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let callees = if include_callees {
+            domain_result
+                .callees
+                .unwrap_or_default()
+                .into_iter()
+                .map(call_info_to_response)
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok(DetailedSymbolResponse {
+            symbol: symbol_info_to_response(&domain_result.symbol),
+            callers,
+            callees,
+            complexity: domain_result.complexity,
+            lines_of_code: domain_result.lines_of_code,
+            is_public: domain_result.is_public,
+            is_deprecated: domain_result.is_deprecated,
+            reference_count: domain_result.reference_count,
+            used_fallback: domain_result.used_fallback,
+            fallback_message: domain_result.fallback_message,
+        })
+    }
+
+    /// Handle get detailed symbol request (with optional source code)
+    pub async fn handle_get_detailed_symbol(
+        &self,
+        params: GetDetailedSymbolParams,
+    ) -> Result<GetDetailedSymbolResponse> {
+        let (node_id, used_fallback, _) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+
+        let include_source = params.include_source.unwrap_or(false);
+        let include_callers = params.include_callers.unwrap_or(true);
+        let include_callees = params.include_callees.unwrap_or(true);
+
+        let domain_result = crate::domain::symbol_info::get_detailed_symbol(
+            &self.graph,
+            &self.query_engine,
+            node_id,
+            include_source,
+            include_callers,
+            include_callees,
+            used_fallback,
+            params.line,
+        )
+        .await;
+
+        let info = domain_result
+            .symbol
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Symbol not found"))?;
+
+        Ok(GetDetailedSymbolResponse {
+            symbol: symbol_info_to_response(&info.symbol),
+            source: domain_result.source,
+            callers: domain_result
+                .callers
+                .unwrap_or_default()
+                .into_iter()
+                .map(call_info_to_response)
+                .collect(),
+            callees: domain_result
+                .callees
+                .unwrap_or_default()
+                .into_iter()
+                .map(call_info_to_response)
+                .collect(),
+            complexity: info.complexity,
+            lines_of_code: info.lines_of_code,
+            is_public: info.is_public,
+            is_deprecated: info.is_deprecated,
+            reference_count: info.reference_count,
+            used_fallback: domain_result.used_fallback,
+            fallback_message: domain_result.fallback_message,
+        })
+    }
+
+    /// Handle find by signature request
+    pub async fn handle_find_by_signature(
+        &self,
+        params: FindBySignatureParams,
+    ) -> Result<FindBySignatureResponse> {
+        let start = std::time::Instant::now();
+
+        // Build signature pattern from params
+        let pattern = SignaturePattern {
+            name_pattern: params.name_pattern,
+            return_type: params.return_type,
+            param_count: params.param_count.map(|r| (r.min, r.max)),
+            modifiers: params.modifiers.unwrap_or_default(),
+        };
+
+        let results = self.query_engine.find_by_signature(&pattern, None).await;
+
+        let response_results = results
+            .into_iter()
+            .map(|m| SymbolMatchResponse {
+                node_id: m.node_id.to_string(),
+                symbol: symbol_info_to_response(&m.symbol),
+                score: m.score,
+                match_reason: m.match_reason,
+            })
+            .collect();
+
+        Ok(FindBySignatureResponse {
+            results: response_results,
+            query_time_ms: start.elapsed().as_millis() as u64,
+        })
+    }
+
+    /// Handle find cycles request
+    pub async fn handle_find_cycles(&self, params: FindCyclesParams) -> Result<FindCyclesResponse> {
+        let level = match params.level.as_deref() {
+            None | Some("file") | Some("module") => CycleLevel::File,
+            Some("symbol") => CycleLevel::Symbol,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid level: {other}. Expected \"file\" or \"symbol\""
+                )))
+            }
+        };
+        let edge_types = params.edge_types.unwrap_or_else(|| {
+            vec![
+                "imports".to_string(),
+                "importsfrom".to_string(),
+                "calls".to_string(),
+            ]
+        });
+
+        let result = self
             .query_engine
-            .traverse_graph(start_node, direction, depth, &filter)
+            .find_cycles(
+                &edge_types,
+                level,
+                params.min_size.unwrap_or(2),
+                params.limit.unwrap_or(50),
+            )
             .await;
 
-        let nodes = results
+        let cycles = result
+            .cycles
             .into_iter()
-            .map(|n| TraversalNodeResponse {
-                node_id: n.node_id.to_string(),
-                depth: n.depth,
-                path: n.path.iter().map(|id| id.to_string()).collect(),
-                edge_type: n.edge_type,
-                symbol: symbol_info_to_response(&n.symbol),
+            .map(|c| DependencyCycleResponse {
+                size: c.size,
+                members: c
+                    .members
+                    .into_iter()
+                    .map(|m| CycleMemberResponse {
+                        node_id: m.node_id.map(|id| id.to_string()),
+                        name: m.name,
+                        file: m.file,
+                        line: m.line,
+                    })
+                    .collect(),
+                files: c.files,
+                edge_types: c.edge_types,
             })
             .collect();
 
-        Ok(TraverseGraphResponse {
-            nodes,
-            query_time_ms: start.elapsed().as_millis() as u64,
-            used_fallback: if used_fallback { Some(true) } else { None },
-            fallback_message,
+        Ok(FindCyclesResponse {
+            cycles,
+            total_cycles: result.total_cycles,
+            members_in_cycles: result.members_in_cycles,
+            level: match result.level {
+                CycleLevel::File => "file".to_string(),
+                CycleLevel::Symbol => "symbol".to_string(),
+            },
+            query_time_ms: result.query_time_ms,
         })
     }
 
-    /// Handle get callers request
-    pub async fn handle_get_callers(&self, params: GetCallersParams) -> Result<GetCallersResponse> {
-        let start = std::time::Instant::now();
+    /// Handle get hot symbols request
+    pub async fn handle_get_hot_symbols(
+        &self,
+        params: GetHotSymbolsParams,
+    ) -> Result<GetHotSymbolsResponse> {
+        let metric = match params.metric.as_deref() {
+            None | Some("combined") => CentralityMetric::Combined,
+            Some("pagerank") => CentralityMetric::PageRank,
+            Some("betweenness") => CentralityMetric::Betweenness,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "Invalid metric: {other}. Expected \"pagerank\", \"betweenness\", or \"combined\""
+            )))
+            }
+        };
 
-        let (node_id, used_fallback, _) = self
+        // Accept either a file URI or a plain path fragment
+        let path_filter = params.uri.map(|uri| {
+            Url::parse(&uri)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or(uri)
+        });
+
+        let result = self
+            .query_engine
+            .get_hot_symbols(metric, params.limit.unwrap_or(20), path_filter.as_deref())
+            .await;
+
+        let symbols = result
+            .symbols
+            .into_iter()
+            .map(|h| HotSymbolResponse {
+                node_id: h.node_id.to_string(),
+                symbol: symbol_info_to_response(&h.symbol),
+                score: h.score,
+                pagerank: h.pagerank,
+                betweenness: h.betweenness,
+                caller_count: h.caller_count,
+                callee_count: h.callee_count,
+            })
+            .collect();
+
+        Ok(GetHotSymbolsResponse {
+            symbols,
+            metric: match result.metric {
+                CentralityMetric::PageRank => "pagerank".to_string(),
+                CentralityMetric::Betweenness => "betweenness".to_string(),
+                CentralityMetric::Combined => "combined".to_string(),
+            },
+            total_ranked: result.total_ranked,
+            query_time_ms: result.query_time_ms,
+        })
+    }
+
+    /// Handle get risk hotspots request
+    ///
+    /// Mines file churn from the workspace git history, optionally within a
+    /// time window and weighted towards recent changes, then ranks functions
+    /// by churn combined with call graph centrality.
+    pub async fn handle_get_risk_hotspots(
+        &self,
+        params: GetRiskHotspotsParams,
+    ) -> Result<GetRiskHotspotsResponse> {
+        use crate::git_mining::{GitMiner, HotspotOptions};
+
+        let workspace = self
+            .workspace_folders
+            .read()
+            .await
+            .first()
+            .cloned()
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("No workspace folder"))?;
+
+        let miner = GitMiner::new(&workspace).map_err(|e| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("Git history unavailable: {e}"))
+        })?;
+        let options = HotspotOptions {
+            threshold: params.min_changes.unwrap_or(2),
+            max_commits: Some(params.max_commits.unwrap_or(500)),
+            since: params.since,
+            until: params.until,
+            half_life_days: params.half_life_days,
+            by_directory: params.by_directory.unwrap_or(false),
+        };
+        let churn = miner.detect_hotspots(&options).await.map_err(|e| {
+            tracing::error!("Churn mining failed: {}", e);
+            tower_lsp::jsonrpc::Error::internal_error()
+        })?;
+        let file_churn: HashMap<String, usize> = churn
+            .into_iter()
+            .map(|h| {
+                // Directory churn of the repository root is keyed by `.`
+                let path = match h.file_path.as_str() {
+                    "." => workspace.clone(),
+                    dir => workspace.join(dir),
+                };
+                let churn = match options.half_life_days {
+                    Some(_) => h.weighted_changes.round() as usize,
+                    None => h.change_count,
+                };
+                (path.to_string_lossy().to_string(), churn)
+            })
+            .collect();
+
+        // Accept either a file URI or a plain path fragment
+        let path_filter = params.uri.map(|uri| {
+            Url::parse(&uri)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or(uri)
+        });
+
+        let result = self
+            .query_engine
+            .get_risk_hotspots(
+                &file_churn,
+                params.limit.unwrap_or(20),
+                path_filter.as_deref(),
+            )
+            .await;
+
+        Ok(GetRiskHotspotsResponse {
+            hotspots: result
+                .hotspots
+                .into_iter()
+                .map(|h| RiskHotspotResponse {
+                    node_id: h.node_id.to_string(),
+                    symbol: symbol_info_to_response(&h.symbol),
+                    risk: h.risk,
+                    churn: h.churn,
+                    churn_score: h.churn_score,
+                    centrality: h.centrality,
+                    caller_count: h.caller_count,
+                })
+                .collect(),
+            files_with_churn: result.files_with_churn,
+            total_ranked: result.total_ranked,
+            query_time_ms: result.query_time_ms,
+        })
+    }
+
+    /// Handle get code ownership request
+    ///
+    /// Aggregates who changed each module (or file) in the recent git history
+    /// of the workspace folder containing `uri`, to suggest reviewers.
+    pub async fn handle_get_code_ownership(
+        &self,
+        params: GetCodeOwnershipParams,
+    ) -> Result<GetCodeOwnershipResponse> {
+        use crate::git_mining::GitMiner;
+
+        // Accept either a file URI or a plain path
+        let path = params.uri.map(|uri| {
+            Url::parse(&uri)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .unwrap_or_else(|| PathBuf::from(uri))
+        });
+        let workspace = self
+            .workspace_folder_for(path.as_deref())
+            .await
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("No workspace folder"))?;
+        let path_filter = path.as_ref().map(|path| {
+            path.strip_prefix(&workspace)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        });
+        let by_module = match params.group_by.as_deref() {
+            None | Some("module") => true,
+            Some("file") => false,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid groupBy: {other} (expected \"module\" or \"file\")"
+                )))
+            }
+        };
+
+        let miner = GitMiner::new(&workspace).map_err(|e| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("Git history unavailable: {e}"))
+        })?;
+        let ownership = miner
+            .detect_ownership(
+                Some(params.max_commits.unwrap_or(500)),
+                path_filter.as_deref(),
+                by_module,
+            )
+            .await
+            .map_err(|e| {
+                tracing::error!("Ownership mining failed: {}", e);
+                tower_lsp::jsonrpc::Error::internal_error()
+            })?;
+
+        Ok(GetCodeOwnershipResponse {
+            entries: ownership
+                .entries
+                .into_iter()
+                .take(params.limit.unwrap_or(20))
+                .map(|entry| OwnershipEntryResponse {
+                    path: entry.path,
+                    commits: entry.commits,
+                    owners: entry.owners.into_iter().map(Into::into).collect(),
+                })
+                .collect(),
+            reviewers: ownership.reviewers.into_iter().map(Into::into).collect(),
+            commits_analyzed: ownership.commits_analyzed,
+        })
+    }
+
+    /// Handle find type usages request
+    pub async fn handle_find_type_usages(
+        &self,
+        params: FindTypeUsagesParams,
+    ) -> Result<FindTypeUsagesResponse> {
+        let (node_id, used_fallback, fallback_message) = self
             .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
             .await?;
-        let depth = params.depth.unwrap_or(1);
 
-        let domain_result = crate::domain::callers::get_callers(
-            &self.graph,
-            &self.query_engine,
-            node_id,
-            depth,
-            used_fallback,
-            params.line,
-        )
-        .await;
+        let result = self
+            .query_engine
+            .find_type_usages(node_id, params.limit.unwrap_or(100))
+            .await
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Type not found"))?;
 
-        Ok(GetCallersResponse {
-            callers: domain_result
-                .callers
+        Ok(FindTypeUsagesResponse {
+            type_name: result.type_name,
+            usages: result
+                .usages
                 .into_iter()
-                .map(call_info_to_response)
+                .map(|u| TypeUsageResponse {
+                    node_id: u.node_id.to_string(),
+                    symbol: symbol_info_to_response(&u.symbol),
+                    usage_kind: u.usage_kind,
+                    via: u.via,
+                })
                 .collect(),
-            query_time_ms: start.elapsed().as_millis() as u64,
-            used_fallback: domain_result.used_fallback,
-            fallback_message: domain_result.fallback_message,
+            total_usages: result.total_usages,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
         })
     }
 
-    /// Handle get callees request
-    pub async fn handle_get_callees(&self, params: GetCallersParams) -> Result<GetCallersResponse> {
-        let start = std::time::Instant::now();
+    /// Handle find implementations request
+    pub async fn handle_find_implementations(
+        &self,
+        params: FindImplementationsParams,
+    ) -> Result<FindImplementationsResponse> {
+        let direction = match params.direction.as_deref() {
+            None | Some("implementations") => ImplementationDirection::Implementations,
+            Some("supertypes") => ImplementationDirection::Supertypes,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid direction: {other}. Expected \"implementations\" or \"supertypes\""
+                )))
+            }
+        };
 
-        let (node_id, used_fallback, _) = self
+        let (node_id, used_fallback, fallback_message) = self
             .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
             .await?;
-        let depth = params.depth.unwrap_or(1);
-
-        let domain_result = crate::domain::callers::get_callees(
-            &self.graph,
-            &self.query_engine,
-            node_id,
-            depth,
-            used_fallback,
-            params.line,
-        )
-        .await;
 
-        Ok(GetCallersResponse {
-            callers: domain_result
-                .callees
+        let result = self
+            .query_engine
+            .find_implementations(node_id, direction, params.limit.unwrap_or(100))
+            .await
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Type not found"))?;
+
+        Ok(FindImplementationsResponse {
+            target_name: result.target_name,
+            direction: match result.direction {
+                ImplementationDirection::Implementations => "implementations".to_string(),
+                ImplementationDirection::Supertypes => "supertypes".to_string(),
+            },
+            implementations: result
+                .implementations
                 .into_iter()
-                .map(call_info_to_response)
+                .map(|i| ImplementationResponse {
+                    node_id: i.node_id.to_string(),
+                    symbol: symbol_info_to_response(&i.symbol),
+                    relation: i.relation,
+                })
                 .collect(),
-            query_time_ms: start.elapsed().as_millis() as u64,
-            used_fallback: domain_result.used_fallback,
-            fallback_message: domain_result.fallback_message,
+            total_implementations: result.total_implementations,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
         })
     }
 
-    /// Handle get detailed info request
-    pub async fn handle_get_detailed_symbol_info(
+    /// Handle get type hierarchy request
+    pub async fn handle_get_type_hierarchy(
         &self,
-        params: GetDetailedInfoParams,
-    ) -> Result<DetailedSymbolResponse> {
-        let (node_id, used_fallback, _) = self
+        params: GetTypeHierarchyParams,
+    ) -> Result<GetTypeHierarchyResponse> {
+        let direction = match params.direction.as_deref() {
+            None | Some("subtypes") => TypeHierarchyDirection::Subtypes,
+            Some("supertypes") => TypeHierarchyDirection::Supertypes,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid direction: {other}. Expected \"subtypes\" or \"supertypes\""
+                )))
+            }
+        };
+
+        let (node_id, used_fallback, fallback_message) = self
             .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
             .await?;
 
-        let include_callers = params.include_callers.unwrap_or(true);
-        let include_callees = params.include_callees.unwrap_or(true);
+        let result = self
+            .query_engine
+            .get_type_hierarchy(node_id, direction, params.max_depth)
+            .await
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Type not found"))?;
+
+        Ok(GetTypeHierarchyResponse {
+            root: symbol_info_to_response(&result.root),
+            direction: match result.direction {
+                TypeHierarchyDirection::Subtypes => "subtypes".to_string(),
+                TypeHierarchyDirection::Supertypes => "supertypes".to_string(),
+            },
+            nodes: result
+                .nodes
+                .into_iter()
+                .map(|n| TypeHierarchyNodeResponse {
+                    node_id: n.node_id.to_string(),
+                    symbol: symbol_info_to_response(&n.symbol),
+                    depth: n.depth,
+                    parent_id: n.parent.to_string(),
+                    relation: n.relation,
+                })
+                .collect(),
+            max_depth: result.max_depth,
+            has_cycle: result.has_cycle,
+            truncated: result.truncated,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
+        })
+    }
 
-        let domain_result = crate::domain::symbol_info::get_symbol_info(
-            &self.graph,
-            &self.query_engine,
-            node_id,
-            include_callers || include_callees,
-            used_fallback,
-            params.line,
-        )
+    /// Handle reaches request
+    pub async fn handle_reaches(&self, params: ReachesParams) -> Result<ReachesResponse> {
+        let (node_id, used_fallback, fallback_message) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+
+        let mut predicate = ReachabilityPredicate::new()
+            .with_sinks(params.sinks.unwrap_or_default())
+            .with_categories(params.categories.unwrap_or_default())
+            .with_edge_types(params.edge_types.unwrap_or_default());
+        if let Some(depth) = params.max_depth {
+            predicate = predicate.with_max_depth(depth);
+        }
+        if let Some(max) = params.max_matches {
+            predicate = predicate.with_max_matches(max);
+        }
+
+        let engine = Arc::clone(&self.query_engine);
+        let budget = self.config.read().await.query_budget();
+        let result = run_cancellable(move |cancel| async move {
+            let cancel = cancel.with_budget(budget);
+            engine.reaches(node_id, &predicate, &cancel).await
+        })
         .await
-        .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Symbol not found"))?;
+        .map_err(|_| tower_lsp::jsonrpc::Error::internal_error())?
+        .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        Ok(ReachesResponse {
+            from: symbol_info_to_response(&result.from),
+            reachable: result.reachable,
+            matches: result
+                .matches
+                .into_iter()
+                .map(|m| SinkMatchResponse {
+                    node_id: m.node_id.to_string(),
+                    symbol: symbol_info_to_response(&m.symbol),
+                    sink: m.sink,
+                    pattern: m.pattern,
+                    category: m.category,
+                    external: m.external,
+                    depth: m.depth,
+                    path: m.path,
+                })
+                .collect(),
+            nodes_visited: result.nodes_visited,
+            truncated: result.truncated,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
+        })
+    }
+
+    /// Handle find similar code request
+    pub async fn handle_find_similar_code(
+        &self,
+        params: FindSimilarCodeParams,
+    ) -> Result<FindSimilarCodeResponse> {
+        let mode = match params.mode.as_deref() {
+            None | Some("auto") => SimilarityMode::Auto,
+            Some("semantic") => SimilarityMode::Semantic,
+            Some("structural") => SimilarityMode::Structural,
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid mode: {other}. Expected \"auto\", \"semantic\" or \"structural\""
+                )))
+            }
+        };
+
+        let (node_id, used_fallback, fallback_message) = self
+            .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
+            .await?;
+
+        let result = self
+            .query_engine
+            .find_similar_code(
+                node_id,
+                mode,
+                params.min_similarity,
+                params.limit.unwrap_or(10),
+            )
+            .await
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        Ok(FindSimilarCodeResponse {
+            target: symbol_info_to_response(&result.target),
+            mode: match result.mode {
+                SimilarityMode::Semantic => "semantic".to_string(),
+                _ => "structural".to_string(),
+            },
+            matches: result
+                .matches
+                .into_iter()
+                .map(|m| SimilarCodeResponse {
+                    node_id: m.node_id.to_string(),
+                    symbol: symbol_info_to_response(&m.symbol),
+                    similarity: m.similarity,
+                })
+                .collect(),
+            total_compared: result.total_compared,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
+        })
+    }
+
+    /// Handle find config usages request
+    pub async fn handle_find_config_usages(
+        &self,
+        params: FindConfigUsagesParams,
+    ) -> Result<FindConfigUsagesResponse> {
+        let source = match params.source.as_deref() {
+            None => None,
+            Some("env") => Some(ConfigSource::Env),
+            Some("config") => Some(ConfigSource::Config),
+            Some(other) => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid source: {other}. Expected \"env\" or \"config\""
+                )))
+            }
+        };
+
+        let result = self
+            .query_engine
+            .find_config_usages(&params.key, source, params.limit.unwrap_or(100))
+            .await
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        Ok(FindConfigUsagesResponse {
+            key: result.key,
+            usages: result
+                .usages
+                .into_iter()
+                .map(|u| ConfigUsageResponse {
+                    key: u.key,
+                    source: match u.source {
+                        ConfigSource::Env => "env".to_string(),
+                        ConfigSource::Config => "config".to_string(),
+                    },
+                    accessor: u.accessor,
+                    file: u.file,
+                    line: u.line,
+                    column: u.column,
+                    node_id: u.node_id.map(|id| id.to_string()),
+                    symbol: u.symbol.as_ref().map(symbol_info_to_response),
+                })
+                .collect(),
+            keys: result.keys,
+            total_usages: result.total_usages,
+            files_scanned: result.files_scanned,
+            query_time_ms: result.query_time_ms,
+        })
+    }
+
+    /// Handle find todos request
+    pub async fn handle_find_todos(&self, params: FindTodosParams) -> Result<FindTodosResponse> {
+        use crate::git_mining::GitMiner;
+
+        let tags = params
+            .tags
+            .unwrap_or_default()
+            .iter()
+            .map(|tag| match tag.to_lowercase().as_str() {
+                "todo" => Ok(TodoTag::Todo),
+                "fixme" => Ok(TodoTag::Fixme),
+                "hack" => Ok(TodoTag::Hack),
+                "safety" => Ok(TodoTag::Safety),
+                other => Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid tag: {other}. Expected \"todo\", \"fixme\", \"hack\" or \"safety\""
+                ))),
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        // Accept either a file URI or a plain path fragment
+        let path_filter = params.uri.map(|uri| {
+            Url::parse(&uri)
+                .ok()
+                .and_then(|url| url.to_file_path().ok())
+                .map(|path| path.to_string_lossy().to_string())
+                .unwrap_or(uri)
+        });
+
+        let result = self
+            .query_engine
+            .find_todos(&tags, path_filter.as_deref())
+            .await;
+
+        // Blame every file with a matching comment when authorship is needed
+        let needs_blame = params.author.is_some()
+            || params.min_age_days.is_some()
+            || params.max_age_days.is_some()
+            || params.include_blame.unwrap_or(false);
+        let mut blame: HashMap<(String, u32), (String, i64)> = HashMap::new();
+        if needs_blame {
+            let workspace = self
+                .workspace_folders
+                .read()
+                .await
+                .first()
+                .cloned()
+                .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("No workspace folder"))?;
+            let miner = GitMiner::new(&workspace).map_err(|e| {
+                tower_lsp::jsonrpc::Error::invalid_params(format!("Git history unavailable: {e}"))
+            })?;
+            let files: HashSet<&str> = result.todos.iter().map(|t| t.file.as_str()).collect();
+            for file in files {
+                match miner.blame_file(std::path::Path::new(file)) {
+                    Ok(lines) => blame.extend(
+                        lines
+                            .into_iter()
+                            .map(|l| ((file.to_string(), l.line), (l.author_name, l.author_time))),
+                    ),
+                    Err(e) => tracing::debug!("Blame failed for {}: {}", file, e),
+                }
+            }
+        }
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or(0);
+        let author = params.author.map(|a| a.to_lowercase());
+
+        let todos: Vec<TodoCommentResponse> = result
+            .todos
+            .into_iter()
+            .filter_map(|t| {
+                let blamed = blame.get(&(t.file.clone(), t.line)).cloned();
+                let age_days = blamed
+                    .as_ref()
+                    .map(|(_, time)| (now - time).max(0) as u64 / 86_400);
+
+                if let Some(author) = &author {
+                    let by_blame = blamed
+                        .as_ref()
+                        .is_some_and(|(name, _)| name.to_lowercase().contains(author));
+                    let by_owner = t
+                        .owner
+                        .as_ref()
+                        .is_some_and(|owner| owner.to_lowercase().contains(author));
+                    if !by_blame && !by_owner {
+                        return None;
+                    }
+                }
+                if let Some(min) = params.min_age_days {
+                    if age_days.map_or(true, |age| age < min) {
+                        return None;
+                    }
+                }
+                if let Some(max) = params.max_age_days {
+                    if age_days.map_or(true, |age| age > max) {
+                        return None;
+                    }
+                }
+
+                Some(TodoCommentResponse {
+                    tag: t.tag.as_str().to_string(),
+                    text: t.text,
+                    owner: t.owner,
+                    file: t.file,
+                    line: t.line,
+                    column: t.column,
+                    node_id: t.node_id.map(|id| id.to_string()),
+                    symbol: t.symbol.as_ref().map(symbol_info_to_response),
+                    author_time: blamed.as_ref().map(|(_, time)| *time),
+                    author: blamed.map(|(name, _)| name),
+                    age_days,
+                })
+            })
+            .collect();
+
+        let total = todos.len();
+        Ok(FindTodosResponse {
+            todos: todos
+                .into_iter()
+                .take(params.limit.unwrap_or(100))
+                .collect(),
+            total,
+            query_time_ms: result.query_time_ms,
+        })
+    }
+
+    /// Handle find by annotation request
+    pub async fn handle_find_by_annotation(
+        &self,
+        params: FindByAnnotationParams,
+    ) -> Result<FindByAnnotationResponse> {
+        let symbol_types: Vec<SymbolType> = params
+            .symbol_types
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|t| match t.as_str() {
+                "function" => Some(SymbolType::Function),
+                "class" => Some(SymbolType::Class),
+                "interface" => Some(SymbolType::Interface),
+                _ => None,
+            })
+            .collect();
+
+        let result = self
+            .query_engine
+            .find_by_annotation(
+                &params.annotation,
+                &symbol_types,
+                params.limit.unwrap_or(100),
+            )
+            .await
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
 
-        let callers = if include_callers {
-            domain_result
-                .callers
-                .unwrap_or_default()
+        Ok(FindByAnnotationResponse {
+            annotation: result.annotation,
+            results: result
+                .results
                 .into_iter()
-                .map(call_info_to_response)
-                .collect()
-        } else {
-            Vec::new()
-        };
+                .map(|r| AnnotatedSymbolResponse {
+                    node_id: r.node_id.to_string(),
+                    symbol: symbol_info_to_response(&r.symbol),
+                    annotations: r.annotations,
+                })
+                .collect(),
+            total: result.total,
+            query_time_ms: result.query_time_ms,
+        })
+    }
 
-        let callees = if include_callees {
-            domain_result
-                .callees
-                .unwrap_or_default()
+    /// Handle query pipeline request
+    pub async fn handle_query_pipeline(
+        &self,
+        params: QueryPipelineParams,
+    ) -> Result<QueryPipelineResponse> {
+        let result = self
+            .query_engine
+            .run_query(&params.query)
+            .await
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        Ok(QueryPipelineResponse {
+            query: result.query,
+            results: result
+                .results
                 .into_iter()
-                .map(call_info_to_response)
-                .collect()
-        } else {
-            Vec::new()
-        };
+                .map(|m| PipelineMatchResponse {
+                    node_id: m.node_id.to_string(),
+                    symbol: symbol_info_to_response(&m.symbol),
+                })
+                .collect(),
+            stages: result
+                .stages
+                .into_iter()
+                .map(|s| PipelineStageResponse {
+                    stage: s.stage,
+                    count: s.count,
+                })
+                .collect(),
+            truncated: result.truncated,
+            query_time_ms: result.query_time_ms,
+        })
+    }
 
-        Ok(DetailedSymbolResponse {
-            symbol: symbol_info_to_response(&domain_result.symbol),
-            callers,
-            callees,
-            complexity: domain_result.complexity,
-            lines_of_code: domain_result.lines_of_code,
-            is_public: domain_result.is_public,
-            is_deprecated: domain_result.is_deprecated,
-            reference_count: domain_result.reference_count,
-            used_fallback: domain_result.used_fallback,
-            fallback_message: domain_result.fallback_message,
+    /// Handle get file outline request
+    pub async fn handle_get_file_outline(
+        &self,
+        params: GetFileOutlineParams,
+    ) -> Result<GetFileOutlineResponse> {
+        let path = Url::parse(&params.uri)
+            .ok()
+            .and_then(|url| url.to_file_path().ok())
+            .map(|path| path.to_string_lossy().to_string())
+            .unwrap_or(params.uri);
+
+        let outline = self
+            .query_engine
+            .get_file_outline(&path)
+            .await
+            .ok_or_else(|| {
+                tower_lsp::jsonrpc::Error::invalid_params(
+                    "No symbols found in file. Try indexing the workspace first.",
+                )
+            })?;
+
+        Ok(GetFileOutlineResponse {
+            file: outline.file,
+            symbols: outline
+                .symbols
+                .iter()
+                .map(outline_node_to_response)
+                .collect(),
+            total_symbols: outline.total_symbols,
+            query_time_ms: outline.query_time_ms,
         })
     }
 
-    /// Handle get detailed symbol request (with optional source code)
-    pub async fn handle_get_detailed_symbol(
+    /// Handle preview rename request
+    pub async fn handle_preview_rename(
         &self,
-        params: GetDetailedSymbolParams,
-    ) -> Result<GetDetailedSymbolResponse> {
-        let (node_id, used_fallback, _) = self
+        params: PreviewRenameParams,
+    ) -> Result<PreviewRenameResponse> {
+        let (node_id, used_fallback, fallback_message) = self
             .resolve_node_id_with_fallback(&params.node_id, &params.uri, &params.line)
             .await?;
 
-        let include_source = params.include_source.unwrap_or(false);
-        let include_callers = params.include_callers.unwrap_or(true);
-        let include_callees = params.include_callees.unwrap_or(true);
-
-        let domain_result = crate::domain::symbol_info::get_detailed_symbol(
-            &self.graph,
-            &self.query_engine,
-            node_id,
-            include_source,
-            include_callers,
-            include_callees,
-            used_fallback,
-            params.line,
-        )
-        .await;
-
-        let info = domain_result
-            .symbol
-            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Symbol not found"))?;
+        // Memories are optional context: an uninitialized store just yields none
+        let memories = self
+            .memory_manager
+            .get_all_current()
+            .await
+            .unwrap_or_default();
 
-        Ok(GetDetailedSymbolResponse {
-            symbol: symbol_info_to_response(&info.symbol),
-            source: domain_result.source,
-            callers: domain_result
-                .callers
-                .unwrap_or_default()
+        let result = self
+            .query_engine
+            .preview_rename(node_id, &params.new_name, &memories)
+            .await
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        Ok(PreviewRenameResponse {
+            node_id: result.node_id.to_string(),
+            symbol: symbol_info_to_response(&result.symbol),
+            old_name: result.old_name,
+            new_name: result.new_name,
+            references: result
+                .references
                 .into_iter()
-                .map(call_info_to_response)
+                .map(|r| RenameReferenceResponse {
+                    node_id: r.node_id.to_string(),
+                    symbol: symbol_info_to_response(&r.symbol),
+                    reference_kind: r.reference_kind,
+                    file: r.file,
+                    line: r.line,
+                })
                 .collect(),
-            callees: domain_result
-                .callees
-                .unwrap_or_default()
+            collisions: result
+                .collisions
                 .into_iter()
-                .map(call_info_to_response)
+                .map(|c| RenameCollisionResponse {
+                    node_id: c.node_id.to_string(),
+                    symbol: symbol_info_to_response(&c.symbol),
+                    scope: c.scope,
+                    in_referencing_file: c.in_referencing_file,
+                })
                 .collect(),
-            complexity: info.complexity,
-            lines_of_code: info.lines_of_code,
-            is_public: info.is_public,
-            is_deprecated: info.is_deprecated,
-            reference_count: info.reference_count,
-            used_fallback: domain_result.used_fallback,
-            fallback_message: domain_result.fallback_message,
+            memories: result
+                .memories
+                .into_iter()
+                .map(|m| RenameMemoryResponse {
+                    id: m.id,
+                    title: m.title,
+                    kind: m.kind,
+                    linked: m.linked,
+                })
+                .collect(),
+            affected_files: result.affected_files,
+            query_time_ms: result.query_time_ms,
+            used_fallback: used_fallback.then_some(true),
+            fallback_message,
         })
     }
 
-    /// Handle find by signature request
-    pub async fn handle_find_by_signature(
-        &self,
-        params: FindBySignatureParams,
-    ) -> Result<FindBySignatureResponse> {
+    /// Handle batch query request.
+    ///
+    /// Runs every query concurrently through the custom request dispatcher. Each
+    /// query takes its own shared read lock on the graph; holding one guard across
+    /// all of them would deadlock against a queued writer, since tokio's RwLock
+    /// is write-preferring. A failing query is reported under its id and does not
+    /// fail the batch.
+    pub async fn handle_batch_query(&self, params: BatchQueryParams) -> Result<BatchQueryResponse> {
         let start = std::time::Instant::now();
 
-        // Build signature pattern from params
-        let pattern = SignaturePattern {
-            name_pattern: params.name_pattern,
-            return_type: params.return_type,
-            param_count: params.param_count.map(|r| (r.min, r.max)),
-            modifiers: params.modifiers.unwrap_or_default(),
-        };
+        if params.queries.len() > MAX_BATCH_QUERIES {
+            return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "Too many queries: {} (max {MAX_BATCH_QUERIES})",
+                params.queries.len()
+            )));
+        }
+        let mut ids = std::collections::HashSet::new();
+        for query in &params.queries {
+            if !ids.insert(query.id.as_str()) {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Duplicate query id: {}",
+                    query.id
+                )));
+            }
+        }
 
-        let results = self.query_engine.find_by_signature(&pattern, None).await;
+        let runs = params.queries.into_iter().map(|query| async move {
+            let query_start = std::time::Instant::now();
+            let method = if query.method.starts_with("codegraph/") {
+                query.method
+            } else {
+                format!("codegraph/{}", query.method)
+            };
 
-        let response_results = results
-            .into_iter()
-            .map(|m| SymbolMatchResponse {
-                node_id: m.node_id.to_string(),
-                symbol: symbol_info_to_response(&m.symbol),
-                score: m.score,
-                match_reason: m.match_reason,
-            })
-            .collect();
+            let outcome = if method == "codegraph/batchQuery" {
+                Err("Nested batch queries are not supported".to_string())
+            } else {
+                self.dispatch_custom_request(&method, query.params)
+                    .await
+                    .map_err(|e| e.message.to_string())
+            };
 
-        Ok(FindBySignatureResponse {
-            results: response_results,
+            let elapsed = query_start.elapsed().as_millis() as u64;
+            let item = match outcome {
+                Ok(value) => BatchQueryItemResult {
+                    result: Some(value),
+                    error: None,
+                    query_time_ms: elapsed,
+                },
+                Err(error) => BatchQueryItemResult {
+                    result: None,
+                    error: Some(error),
+                    query_time_ms: elapsed,
+                },
+            };
+            (query.id, item)
+        });
+
+        let results: BTreeMap<String, BatchQueryItemResult> =
+            futures::future::join_all(runs).await.into_iter().collect();
+        let failed = results.values().filter(|r| r.error.is_some()).count();
+
+        Ok(BatchQueryResponse {
+            succeeded: results.len() - failed,
+            failed,
+            results,
             query_time_ms: start.elapsed().as_millis() as u64,
         })
     }
 
+    /// Store a primitive result in the query cache, adding the queried node's file
+    /// to the files the result depends on.
+    async fn cache_primitive(
+        &self,
+        kind: &'static str,
+        node_id: NodeId,
+        params: String,
+        result: PrimitiveResult,
+        mut files: HashSet<PathBuf>,
+        any_file: bool,
+    ) {
+        {
+            let graph = self.graph.read().await;
+            if let Ok(node) = graph.get_node(node_id) {
+                files.insert(PathBuf::from(node_props::path(node)));
+            }
+        }
+        files.remove(&PathBuf::new());
+
+        self.query_cache.set_primitive(
+            kind,
+            node_id,
+            params,
+            PrimitiveCacheEntry {
+                result,
+                files,
+                any_file,
+            },
+        );
+    }
+
     /// Helper to resolve a node ID from either direct ID or uri+line
     /// Returns (NodeId, used_fallback, fallback_message)
     async fn resolve_node_id_with_fallback(
@@ -769,9 +2705,9 @@ impl CodeGraphBackend {
         line: &Option<u32>,
     ) -> Result<(NodeId, bool, Option<String>)> {
         if let Some(id_str) = node_id {
-            let node_id = id_str
-                .parse::<NodeId>()
-                .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid node ID"))?;
+            let graph = self.graph.read().await;
+            let node_id = crate::domain::monikers::resolve_node_ref(&graph, id_str)
+                .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Invalid node ID"))?;
             return Ok((node_id, false, None));
         }
 
@@ -840,6 +2776,68 @@ fn call_info_to_response(c: crate::ai_query::CallInfo) -> CallInfoResponse {
     }
 }
 
+/// Helper to convert an internal ResultGroup to response format
+fn group_to_response(g: crate::ai_query::ResultGroup) -> ResultGroupResponse {
+    ResultGroupResponse {
+        key: g.key,
+        count: g.count,
+        score: g.score,
+        top_symbols: g.top_symbols,
+    }
+}
+
+/// Parse a `groupBy` parameter ("file" or "directory").
+fn parse_group_by(value: Option<&str>) -> Result<Option<GroupBy>> {
+    match value {
+        None => Ok(None),
+        Some("file") => Ok(Some(GroupBy::File)),
+        Some("directory") => Ok(Some(GroupBy::Directory)),
+        Some(other) => Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+            "Invalid groupBy: {other}. Expected \"file\" or \"directory\""
+        ))),
+    }
+}
+
+/// Continuation cursors are offsets into a stably ordered result list, so a
+/// cursor stays valid as long as the graph does not change between pages.
+fn encode_cursor(offset: usize) -> String {
+    format!("offset:{offset}")
+}
+
+fn decode_cursor(cursor: Option<&str>) -> Result<usize> {
+    let Some(cursor) = cursor else {
+        return Ok(0);
+    };
+    cursor
+        .strip_prefix("offset:")
+        .and_then(|offset| offset.parse().ok())
+        .ok_or_else(|| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid cursor: {cursor}"))
+        })
+}
+
+/// Cut the page starting at `offset` out of `items`; returns it with the cursor
+/// of the next page, if there is one. Without a limit the rest is one page.
+fn paginate<T>(items: Vec<T>, offset: usize, limit: Option<usize>) -> (Vec<T>, Option<String>) {
+    let total = items.len();
+    let end = limit.map_or(total, |limit| offset.saturating_add(limit).min(total));
+    let next_cursor = (end < total).then(|| encode_cursor(end));
+    let page = items
+        .into_iter()
+        .skip(offset)
+        .take(end.saturating_sub(offset))
+        .collect();
+    (page, next_cursor)
+}
+
+fn outline_node_to_response(node: &crate::ai_query::OutlineNode) -> OutlineNodeResponse {
+    OutlineNodeResponse {
+        node_id: node.node_id.to_string(),
+        symbol: symbol_info_to_response(&node.symbol),
+        children: node.children.iter().map(outline_node_to_response).collect(),
+    }
+}
+
 /// Helper to convert internal SymbolInfo to response format
 fn symbol_info_to_response(info: &crate::ai_query::SymbolInfo) -> SymbolInfoResponse {
     SymbolInfoResponse {
@@ -912,6 +2910,8 @@ mod tests {
             symbol_types: None,
             limit: None,
             include_private: None,
+            mode: None,
+            group_by: None,
         };
 
         let result = backend.handle_symbol_search(params).await.unwrap();
@@ -964,6 +2964,8 @@ mod tests {
             symbol_types: Some(vec!["class".to_string()]),
             limit: None,
             include_private: None,
+            mode: None,
+            group_by: None,
         };
 
         let result = backend.handle_symbol_search(params).await.unwrap();
@@ -1003,6 +3005,8 @@ mod tests {
             symbol_types: None,
             limit: Some(3),
             include_private: None,
+            mode: None,
+            group_by: None,
         };
 
         let result = backend.handle_symbol_search(params).await.unwrap();
@@ -1010,6 +3014,51 @@ mod tests {
         assert_eq!(result.results.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_handle_symbol_search_group_by_file() {
+        let (backend, graph) = create_test_backend().await;
+
+        {
+            let mut g = graph.write().await;
+            for (i, path) in ["/src/a.rs", "/src/a.rs", "/src/a.rs", "/src/b.rs"]
+                .iter()
+                .enumerate()
+            {
+                let mut props = PropertyMap::new();
+                props.insert(
+                    "name".to_string(),
+                    PropertyValue::String(format!("authStep{i}")),
+                );
+                props.insert("path".to_string(), PropertyValue::String(path.to_string()));
+                props.insert("line_start".to_string(), PropertyValue::Int(i as i64 * 10));
+                g.add_node(NodeType::Function, props)
+                    .expect("Failed to add node");
+            }
+        }
+
+        backend.query_engine.build_indexes().await;
+
+        let params = SymbolSearchParams {
+            query: "auth".to_string(),
+            scope: None,
+            symbol_types: None,
+            limit: Some(1),
+            include_private: None,
+            mode: None,
+            group_by: Some("file".to_string()),
+        };
+
+        let result = backend.handle_symbol_search(params).await.unwrap();
+
+        assert!(result.results.is_empty());
+        let groups = result.groups.unwrap();
+        // limit caps groups, not matches
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].key, "/src/a.rs");
+        assert_eq!(groups[0].count, 3);
+        assert_eq!(result.total_matches, 4);
+    }
+
     // ==========================================
     // Find By Imports Handler Tests
     // ==========================================
@@ -1446,6 +3495,8 @@ mod tests {
             uri: None,
             line: None,
             depth: Some(1),
+            limit: None,
+            cursor: None,
         };
 
         let result = backend.handle_get_callers(params).await.unwrap();
@@ -1525,6 +3576,8 @@ mod tests {
             uri: None,
             line: None,
             depth: Some(1),
+            limit: None,
+            cursor: None,
         };
 
         let result = backend.handle_get_callees(params).await.unwrap();
@@ -1610,6 +3663,8 @@ mod tests {
             depth: Some(2),
             filter_symbol_types: None,
             max_nodes: None,
+            group_by: None,
+            cursor: None,
         };
 
         let result = backend.handle_traverse_graph(params).await.unwrap();
@@ -1694,6 +3749,8 @@ mod tests {
             depth: Some(1),
             filter_symbol_types: Some(vec!["function".to_string()]),
             max_nodes: None,
+            group_by: None,
+            cursor: None,
         };
 
         let result = backend.handle_traverse_graph(params).await.unwrap();
@@ -1703,6 +3760,103 @@ mod tests {
         assert_eq!(result.nodes[0].symbol.kind, "Function");
     }
 
+    #[tokio::test]
+    async fn test_traverse_graph_and_callers_pages_with_cursor() {
+        let (backend, graph) = create_test_backend().await;
+
+        let hub;
+        {
+            let mut g = graph.write().await;
+            let mut add = |name: &str| {
+                let mut props = PropertyMap::new();
+                props.insert("name".to_string(), PropertyValue::String(name.to_string()));
+                props.insert(
+                    "path".to_string(),
+                    PropertyValue::String("/src/lib.rs".to_string()),
+                );
+                props.insert("line_start".to_string(), PropertyValue::Int(1));
+                g.add_node(NodeType::Function, props).unwrap()
+            };
+            hub = add("hub");
+            let callers: Vec<_> = (0..5).map(|i| add(&format!("caller{i}"))).collect();
+            for caller in callers {
+                g.add_edge(caller, hub, EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
+            }
+        }
+        backend.query_engine.build_indexes().await;
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let result = backend
+                .handle_traverse_graph(TraverseGraphParams {
+                    start_node_id: Some(hub.to_string()),
+                    uri: None,
+                    line: None,
+                    direction: Some("incoming".to_string()),
+                    depth: Some(1),
+                    filter_symbol_types: None,
+                    max_nodes: Some(2),
+                    group_by: None,
+                    cursor: cursor.take(),
+                })
+                .await
+                .unwrap();
+            assert!(result.nodes.len() <= 2);
+            assert_eq!(result.has_more, result.next_cursor.is_some());
+            seen.extend(result.nodes.into_iter().map(|n| n.symbol.name));
+            match result.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        seen.sort();
+        assert_eq!(
+            seen,
+            vec!["caller0", "caller1", "caller2", "caller3", "caller4"]
+        );
+
+        let first = backend
+            .handle_get_callers(GetCallersParams {
+                node_id: Some(hub.to_string()),
+                uri: None,
+                line: None,
+                depth: None,
+                limit: Some(3),
+                cursor: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(first.total, 5);
+        assert_eq!(first.callers.len(), 3);
+        let second = backend
+            .handle_get_callers(GetCallersParams {
+                node_id: Some(hub.to_string()),
+                uri: None,
+                line: None,
+                depth: None,
+                limit: Some(3),
+                cursor: first.next_cursor,
+            })
+            .await
+            .unwrap();
+        assert_eq!(second.callers.len(), 2);
+        assert!(second.next_cursor.is_none());
+
+        let bad = backend
+            .handle_get_callers(GetCallersParams {
+                node_id: Some(hub.to_string()),
+                uri: None,
+                line: None,
+                depth: None,
+                limit: None,
+                cursor: Some("page-two".to_string()),
+            })
+            .await;
+        assert!(bad.is_err());
+    }
+
     // ==========================================
     // Get Detailed Symbol Info Handler Tests
     // ==========================================
@@ -1852,6 +4006,8 @@ mod tests {
             uri: None,
             line: None,
             depth: None,
+            limit: None,
+            cursor: None,
         };
 
         let result = backend.handle_get_callers(params).await;
@@ -1868,10 +4024,70 @@ mod tests {
             uri: None,
             line: None,
             depth: None,
+            limit: None,
+            cursor: None,
         };
 
         let result = backend.handle_get_callers(params).await;
 
         assert!(result.is_err());
     }
+
+    // ==========================================
+    // Batch Query Handler Tests
+    // ==========================================
+
+    #[tokio::test]
+    async fn test_handle_batch_query_keys_results_by_id() {
+        let (backend, graph) = create_test_backend().await;
+
+        {
+            let mut g = graph.write().await;
+            let mut props = PropertyMap::new();
+            props.insert(
+                "name".to_string(),
+                PropertyValue::String("findUser".to_string()),
+            );
+            props.insert(
+                "path".to_string(),
+                PropertyValue::String("/src/users.rs".to_string()),
+            );
+            g.add_node(NodeType::Function, props).unwrap();
+        }
+        backend.query_engine.build_indexes().await;
+
+        let params: BatchQueryParams = serde_json::from_value(serde_json::json!({
+            "queries": [
+                {"id": "search", "method": "codegraph/symbolSearch", "params": {"query": "findUser"}},
+                {"id": "short", "method": "symbolSearch", "params": {"query": "user"}},
+                {"id": "bad", "method": "codegraph/getCallers", "params": {"nodeId": "not-a-number"}},
+                {"id": "nested", "method": "batchQuery", "params": {"queries": []}},
+            ]
+        }))
+        .unwrap();
+        let response = backend.handle_batch_query(params).await.unwrap();
+
+        assert_eq!(response.results.len(), 4);
+        assert_eq!(response.succeeded, 2);
+        assert_eq!(response.failed, 2);
+        assert!(response.results["search"].result.is_some());
+        assert!(response.results["short"].result.is_some());
+        assert!(response.results["bad"].error.is_some());
+        assert!(response.results["nested"].error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_handle_batch_query_rejects_duplicate_ids() {
+        let (backend, _graph) = create_test_backend().await;
+
+        let params: BatchQueryParams = serde_json::from_value(serde_json::json!({
+            "queries": [
+                {"id": "a", "method": "symbolSearch", "params": {"query": "x"}},
+                {"id": "a", "method": "symbolSearch", "params": {"query": "y"}},
+            ]
+        }))
+        .unwrap();
+
+        assert!(backend.handle_batch_query(params).await.is_err());
+    }
 }
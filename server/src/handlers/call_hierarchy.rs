@@ -0,0 +1,290 @@
+//! Multi-level call hierarchy.
+//!
+//! `callHierarchy/incomingCalls` and `outgoingCalls` return one level at a
+//! time, which costs an agent a round trip per function. The
+//! `codegraph/expandCallHierarchy` request returns the whole tree down to a
+//! depth, stopping once a node cap is reached.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::node_props;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{CallHierarchyItem, Range, SymbolKind};
+
+/// Default and maximum depth of an expanded call tree.
+const DEFAULT_DEPTH: usize = 3;
+const MAX_DEPTH: usize = 10;
+
+/// Default and maximum number of nodes in an expanded call tree.
+const DEFAULT_MAX_NODES: usize = 200;
+const MAX_NODES: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandCallHierarchyParams {
+    /// Item returned by `textDocument/prepareCallHierarchy`
+    pub item: CallHierarchyItem,
+    /// "incoming" (callers, default) or "outgoing" (callees)
+    pub direction: Option<String>,
+    pub depth: Option<usize>,
+    pub max_nodes: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallHierarchyTreeNode {
+    pub item: CallHierarchyItem,
+    pub from_ranges: Vec<Range>,
+    pub children: Vec<CallHierarchyTreeNode>,
+    /// The function already appears on the path from the root, so it is not
+    /// expanded again
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recursive: bool,
+    /// The function was expanded elsewhere in the tree
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub repeated: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExpandCallHierarchyResponse {
+    pub root: CallHierarchyTreeNode,
+    pub node_count: usize,
+    /// Whether the node cap cut the tree short
+    pub truncated: bool,
+}
+
+/// A node of a call tree, linked to its parent by index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CallTreeEntry {
+    node_id: NodeId,
+    parent: Option<usize>,
+    recursive: bool,
+    repeated: bool,
+}
+
+/// Functions calling (`Incoming`) or called by (`Outgoing`) `node_id`.
+fn call_neighbors(graph: &CodeGraph, node_id: NodeId, direction: Direction) -> Vec<NodeId> {
+    let mut neighbors = graph.get_neighbors(node_id, direction).unwrap_or_default();
+    neighbors.sort_unstable();
+    neighbors.dedup();
+    neighbors.retain(|&neighbor| {
+        let (source, target) = match direction {
+            Direction::Incoming => (neighbor, node_id),
+            _ => (node_id, neighbor),
+        };
+        graph
+            .get_edges_between(source, target)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|eid| {
+                graph
+                    .get_edge(eid)
+                    .is_ok_and(|e| e.edge_type == EdgeType::Calls)
+            })
+    });
+    neighbors
+}
+
+/// Walk calls breadth-first from `root`, so a capped tree keeps its upper
+/// levels. Entry 0 is the root; every other entry comes after its parent.
+/// Returns the entries and whether `max_nodes` cut the walk short.
+fn call_tree(
+    graph: &CodeGraph,
+    root: NodeId,
+    direction: Direction,
+    depth: usize,
+    max_nodes: usize,
+) -> (Vec<CallTreeEntry>, bool) {
+    let mut entries = vec![CallTreeEntry {
+        node_id: root,
+        parent: None,
+        recursive: false,
+        repeated: false,
+    }];
+    let mut expanded: HashSet<NodeId> = HashSet::from([root]);
+    let mut level = vec![0];
+
+    for _ in 0..depth {
+        let mut next_level = Vec::new();
+        for &index in &level {
+            let node_id = entries[index].node_id;
+            for neighbor in call_neighbors(graph, node_id, direction) {
+                if entries.len() >= max_nodes {
+                    return (entries, true);
+                }
+                let mut ancestor = Some(index);
+                let mut recursive = false;
+                while let Some(i) = ancestor {
+                    if entries[i].node_id == neighbor {
+                        recursive = true;
+                        break;
+                    }
+                    ancestor = entries[i].parent;
+                }
+                let repeated = !recursive && !expanded.insert(neighbor);
+
+                entries.push(CallTreeEntry {
+                    node_id: neighbor,
+                    parent: Some(index),
+                    recursive,
+                    repeated,
+                });
+                if !recursive && !repeated {
+                    next_level.push(entries.len() - 1);
+                }
+            }
+        }
+        if next_level.is_empty() {
+            break;
+        }
+        level = next_level;
+    }
+    (entries, false)
+}
+
+impl CodeGraphBackend {
+    /// Call hierarchy item for a function node, or None if it has no location.
+    pub(crate) fn call_hierarchy_item(
+        &self,
+        graph: &CodeGraph,
+        node_id: NodeId,
+    ) -> Option<CallHierarchyItem> {
+        let node = graph.get_node(node_id).ok()?;
+        let location = self.node_to_location(graph, node_id).ok()?;
+        Some(CallHierarchyItem {
+            name: node_props::name(node).to_string(),
+            kind: SymbolKind::FUNCTION,
+            tags: None,
+            detail: node
+                .properties
+                .get_string("signature")
+                .map(|s| s.to_string()),
+            uri: location.uri,
+            range: location.range,
+            selection_range: location.range,
+            data: Some(serde_json::json!({ "nodeId": node_id.to_string() })),
+        })
+    }
+
+    pub async fn handle_expand_call_hierarchy(
+        &self,
+        params: ExpandCallHierarchyParams,
+    ) -> Result<ExpandCallHierarchyResponse> {
+        let root_id = self.extract_node_id_from_item(&params.item)?;
+        let direction = match params.direction.as_deref().unwrap_or("incoming") {
+            "incoming" => Direction::Incoming,
+            "outgoing" => Direction::Outgoing,
+            other => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(format!(
+                    "Invalid direction '{other}', expected 'incoming' or 'outgoing'"
+                )))
+            }
+        };
+        let depth = params.depth.unwrap_or(DEFAULT_DEPTH).min(MAX_DEPTH);
+        let max_nodes = params
+            .max_nodes
+            .unwrap_or(DEFAULT_MAX_NODES)
+            .clamp(1, MAX_NODES);
+
+        let graph = self.graph.read().await;
+        let (entries, truncated) = call_tree(&graph, root_id, direction, depth, max_nodes);
+
+        // Build the nested tree bottom-up: children always follow their parent
+        let mut nodes: Vec<Option<CallHierarchyTreeNode>> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let item = if index == 0 {
+                    params.item.clone()
+                } else {
+                    self.call_hierarchy_item(&graph, entry.node_id)?
+                };
+                Some(CallHierarchyTreeNode {
+                    from_ranges: vec![item.range],
+                    item,
+                    children: Vec::new(),
+                    recursive: entry.recursive,
+                    repeated: entry.repeated,
+                })
+            })
+            .collect();
+        for index in (1..entries.len()).rev() {
+            let (Some(node), Some(parent)) = (nodes[index].take(), entries[index].parent) else {
+                continue;
+            };
+            if let Some(parent_node) = nodes[parent].as_mut() {
+                parent_node.children.push(node);
+            }
+        }
+        let mut root = nodes[0]
+            .take()
+            .ok_or_else(tower_lsp::jsonrpc::Error::internal_error)?;
+        fn restore_order(node: &mut CallHierarchyTreeNode) {
+            node.children.reverse();
+            node.children.iter_mut().for_each(restore_order);
+        }
+        restore_order(&mut root);
+
+        Ok(ExpandCallHierarchyResponse {
+            root,
+            node_count: entries.len(),
+            truncated,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::{NodeType, PropertyMap};
+
+    #[test]
+    fn test_call_tree_marks_recursion_and_respects_cap() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut function = |name: &str| {
+            graph
+                .add_node(NodeType::Function, PropertyMap::new().with("name", name))
+                .unwrap()
+        };
+        let (main, parse, eval, helper) = (
+            function("main"),
+            function("parse"),
+            function("eval"),
+            function("helper"),
+        );
+        for (from, to) in [
+            (main, parse),
+            (main, eval),
+            (parse, helper),
+            (eval, helper),
+            (eval, eval),
+        ] {
+            graph
+                .add_edge(from, to, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+        }
+
+        let (entries, truncated) = call_tree(&graph, main, Direction::Outgoing, 5, 100);
+        assert!(!truncated);
+        // main, parse, eval, then helper (under parse), helper again and eval
+        // (under eval)
+        assert_eq!(entries.len(), 6);
+        let helpers: Vec<&CallTreeEntry> = entries.iter().filter(|e| e.node_id == helper).collect();
+        assert_eq!(helpers.len(), 2);
+        assert_eq!(helpers.iter().filter(|e| e.repeated).count(), 1);
+        assert!(entries
+            .iter()
+            .any(|e| e.node_id == eval && e.recursive && e.parent.is_some()));
+
+        // Callers of helper, one level deep
+        let (entries, _) = call_tree(&graph, helper, Direction::Incoming, 1, 100);
+        assert_eq!(entries.len(), 3);
+
+        let (entries, truncated) = call_tree(&graph, main, Direction::Outgoing, 5, 3);
+        assert!(truncated);
+        assert_eq!(entries.len(), 3);
+    }
+}
@@ -0,0 +1,270 @@
+//! Code actions backed by the memory layer.
+//!
+//! - "Store memory about `symbol`…" opens the store memory flow with the
+//!   symbol under the cursor pre-linked.
+//! - "Mine git history for this file" runs `codegraph.mineGitHistoryForFile`.
+//! - "Apply known fix from memory …" inserts the solution of a debug context
+//!   (or the workaround of a known issue) as a comment above the symbol. Fixes
+//!   come from memories linked to the symbol and from debug contexts whose
+//!   related errors match a diagnostic in the request.
+//! - "Review stale memories" opens the review queue from a stale memory hint.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::source_text::Syntax;
+use crate::domain::{monikers, node_props};
+use crate::memory::{MemoryKind, MemoryNode};
+use std::collections::HashMap;
+use std::path::Path;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, CodeActionResponse, Command,
+    Diagnostic, Position, Range, TextEdit, WorkspaceEdit,
+};
+
+/// The fix a memory records: a debug context's solution or a known issue's
+/// workaround.
+fn known_fix(memory: &MemoryNode) -> Option<&str> {
+    let fix = match &memory.kind {
+        MemoryKind::DebugContext { solution, .. } => solution.as_str(),
+        MemoryKind::KnownIssue {
+            workaround: Some(workaround),
+            ..
+        } => workaround.as_str(),
+        _ => return None,
+    };
+    let fix = fix.trim();
+    (!fix.is_empty()).then_some(fix)
+}
+
+/// Diagnostics whose message mentions one of a debug context's related errors.
+fn matching_diagnostics(memory: &MemoryNode, diagnostics: &[Diagnostic]) -> Vec<Diagnostic> {
+    let MemoryKind::DebugContext { related_errors, .. } = &memory.kind else {
+        return Vec::new();
+    };
+    diagnostics
+        .iter()
+        .filter(|d| {
+            related_errors
+                .iter()
+                .any(|e| !e.trim().is_empty() && d.message.contains(e.trim()))
+        })
+        .cloned()
+        .collect()
+}
+
+/// A comment recording `fix`, indented like `line` and ending in a newline.
+fn fix_comment(title: &str, fix: &str, line: &str, hash_comments: bool) -> String {
+    let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+    let marker = if hash_comments { "#" } else { "//" };
+    let mut comment = format!("{indent}{marker} Known fix ({title}):\n");
+    for fix_line in fix.lines() {
+        let fix_line = fix_line.trim_end();
+        if fix_line.is_empty() {
+            comment.push_str(&format!("{indent}{marker}\n"));
+        } else {
+            comment.push_str(&format!("{indent}{marker} {fix_line}\n"));
+        }
+    }
+    comment
+}
+
+fn command_action(
+    title: String,
+    command: &str,
+    argument: serde_json::Value,
+) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::EMPTY),
+        command: Some(Command {
+            title,
+            command: command.to_string(),
+            arguments: Some(vec![argument]),
+        }),
+        ..Default::default()
+    })
+}
+
+impl CodeGraphBackend {
+    /// Memory code actions for the symbol at the start of the requested range.
+    pub async fn handle_code_action(
+        &self,
+        params: CodeActionParams,
+    ) -> Result<Option<CodeActionResponse>> {
+        let uri = &params.text_document.uri;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        // Memories are optional: an uninitialized store just means no fixes
+        let memories = self
+            .memory_manager
+            .get_all_current()
+            .await
+            .unwrap_or_default();
+
+        let mut actions = Vec::new();
+        let symbol = {
+            let graph = self.graph.read().await;
+            self.find_node_at_position(&graph, &path, params.range.start)
+                .ok()
+                .flatten()
+                .and_then(|node_id| {
+                    let node = graph.get_node(node_id).ok()?;
+                    Some((
+                        monikers::link_keys(node_id, node),
+                        node_props::name(node).to_string(),
+                        format!("{:?}", node.node_type).to_lowercase(),
+                        Self::node_to_range(node)?.start.line,
+                    ))
+                })
+        };
+
+        if let Some((node_keys, name, node_type, _)) = &symbol {
+            // Link by moniker when there is one, so the link survives reindexing
+            let node_key = node_keys.last().cloned().unwrap_or_default();
+            actions.push(command_action(
+                format!("Store memory about `{name}`…"),
+                "codegraph.storeMemory",
+                serde_json::json!({
+                    "codeLinks": [{ "nodeId": node_key, "nodeType": node_type }],
+                }),
+            ));
+        }
+        actions.push(command_action(
+            "Mine git history for this file".to_string(),
+            "codegraph.mineGitHistoryForFile",
+            serde_json::json!({ "uri": uri.to_string() }),
+        ));
+
+        let node_keys = symbol.as_ref().map(|(node_keys, ..)| node_keys);
+        let fixes: Vec<(&MemoryNode, &str, Vec<Diagnostic>)> = memories
+            .iter()
+            .filter_map(|memory| {
+                let fix = known_fix(memory)?;
+                let diagnostics = matching_diagnostics(memory, &params.context.diagnostics);
+                let linked = node_keys.is_some_and(|keys| {
+                    memory.code_links.iter().any(|l| keys.contains(&l.node_id))
+                });
+                (linked || !diagnostics.is_empty()).then_some((memory, fix, diagnostics))
+            })
+            .collect();
+
+        if !fixes.is_empty() {
+            let line = symbol
+                .as_ref()
+                .map_or(params.range.start.line, |(.., line)| *line);
+            let line_text = self.document_line(uri, &path, line).unwrap_or_default();
+            let hash_comments = Syntax::from_path(&path).is_some_and(Syntax::hash_comments);
+            let insert_at = Position { line, character: 0 };
+
+            for (memory, fix, diagnostics) in fixes {
+                let edit = TextEdit {
+                    range: Range {
+                        start: insert_at,
+                        end: insert_at,
+                    },
+                    new_text: fix_comment(&memory.title, fix, &line_text, hash_comments),
+                };
+                actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: format!("Apply known fix from memory \"{}\"", memory.title),
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    diagnostics: (!diagnostics.is_empty()).then_some(diagnostics),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some(HashMap::from([(uri.clone(), vec![edit])])),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }));
+            }
+        }
+
+        actions.extend(super::stale_memory_actions(&params.context.diagnostics));
+
+        // Honor the client's kind filter (e.g. quick fixes only)
+        if let Some(only) = &params.context.only {
+            actions.retain(|action| match action {
+                CodeActionOrCommand::CodeAction(CodeAction {
+                    kind: Some(kind), ..
+                }) => only.iter().any(|o| kind.as_str().starts_with(o.as_str())),
+                _ => false,
+            });
+        }
+
+        Ok(Some(actions))
+    }
+
+    /// Line `line` (0-indexed) of a document, from the editor if open.
+    fn document_line(
+        &self,
+        uri: &tower_lsp::lsp_types::Url,
+        path: &Path,
+        line: u32,
+    ) -> Option<String> {
+        let text = match self.open_documents.get(uri) {
+            Some(doc) => doc.text(),
+            None => std::fs::read_to_string(path).ok()?,
+        };
+        text.lines().nth(line as usize).map(str::to_string)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryManager;
+
+    fn debug_context(solution: &str, related_errors: &[&str]) -> MemoryNode {
+        let mut memory = MemoryManager::builder()
+            .debug_context("Request hangs", solution)
+            .title("Retry on timeout")
+            .content("Requests to the billing API hang under load")
+            .build()
+            .unwrap();
+        memory.kind = MemoryKind::DebugContext {
+            problem_description: "Request hangs".to_string(),
+            root_cause: None,
+            solution: solution.to_string(),
+            symptoms: vec![],
+            related_errors: related_errors.iter().map(|e| e.to_string()).collect(),
+        };
+        memory
+    }
+
+    #[test]
+    fn test_known_fix_and_matching_diagnostics() {
+        let memory = debug_context("Wrap the call in retry()", &["E0502"]);
+        assert_eq!(known_fix(&memory), Some("Wrap the call in retry()"));
+        assert_eq!(known_fix(&debug_context("  ", &[])), None);
+
+        let diagnostic = |message: &str| Diagnostic {
+            message: message.to_string(),
+            ..Default::default()
+        };
+        let diagnostics = vec![
+            diagnostic("error[E0502]: cannot borrow `x` as mutable"),
+            diagnostic("unused variable: `y`"),
+        ];
+        let matched = matching_diagnostics(&memory, &diagnostics);
+        assert_eq!(matched.len(), 1);
+        assert!(matched[0].message.contains("E0502"));
+    }
+
+    #[test]
+    fn test_fix_comment_keeps_indentation() {
+        let comment = fix_comment(
+            "Retry on timeout",
+            "Wrap the call in retry()\n\nwith a 3s backoff",
+            "    def fetch(self):",
+            true,
+        );
+        assert_eq!(
+            comment,
+            "    # Known fix (Retry on timeout):\n    # Wrap the call in retry()\n    #\n    # with a 3s backoff\n"
+        );
+        assert_eq!(
+            fix_comment("t", "use &str", "fn a() {}", false),
+            "// Known fix (t):\n// use &str\n"
+        );
+    }
+}
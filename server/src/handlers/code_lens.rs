@@ -0,0 +1,332 @@
+//! Code lens handlers: reference, related test and linked memory counts above
+//! each function, class and interface.
+//!
+//! `textDocument/codeLens` only lists the lenses; counting references and
+//! tests is deferred to `codeLens/resolve` so it runs for visible symbols only.
+//! Resolved commands are cached in the query cache until any file changes.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::{monikers, node_props, related_tests, unused_code};
+use codegraph::{CodeGraph, Direction, NodeId, NodeType};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{CodeLens, Command, Location, Range, Url};
+
+/// Client command that shows locations in the references peek view.
+const SHOW_REFERENCES_COMMAND: &str = "codegraph.showReferences";
+
+/// Client command that opens one of a symbol's linked memories.
+const SHOW_MEMORIES_COMMAND: &str = "codegraph.showLinkedMemories";
+
+const REFERENCES_LENS: &str = "references";
+const TESTS_LENS: &str = "tests";
+
+/// Maximum number of related tests counted per symbol.
+const MAX_LENS_TESTS: usize = 20;
+
+/// Data carried by an unresolved code lens.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodeLensData {
+    pub uri: String,
+    pub node_id: NodeId,
+    /// Symbol name, used to find the symbol again if the file was re-parsed
+    pub name: String,
+    /// "references" or "tests"
+    pub kind: String,
+}
+
+fn lens_kind(kind: &str) -> Option<&'static str> {
+    match kind {
+        REFERENCES_LENS => Some(REFERENCES_LENS),
+        TESTS_LENS => Some(TESTS_LENS),
+        _ => None,
+    }
+}
+
+/// "1 reference", "3 references".
+fn count_title(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("1 {singular}")
+    } else {
+        format!("{count} {plural}")
+    }
+}
+
+impl CodeGraphBackend {
+    /// List the code lenses of a file. Reference and test lenses are left
+    /// unresolved; memory lenses are resolved immediately and only shown for
+    /// symbols that have linked memories.
+    pub async fn handle_code_lens(&self, uri: &Url) -> Result<Option<Vec<CodeLens>>> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        // Memories are optional: an uninitialized store just means no memory lenses
+        let memories = self
+            .memory_manager
+            .get_all_current()
+            .await
+            .unwrap_or_default();
+
+        let graph = self.graph.read().await;
+        let mut lenses = Vec::new();
+
+        for node_id in self.symbol_index.get_file_symbols(&path) {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            if !matches!(
+                node.node_type,
+                NodeType::Function | NodeType::Class | NodeType::Interface | NodeType::Trait
+            ) {
+                continue;
+            }
+            let Some(range) = Self::node_to_range(node) else {
+                continue;
+            };
+            // Lenses sit above the declaration line
+            let range = Range {
+                start: range.start,
+                end: range.start,
+            };
+
+            let kinds: &[&str] = if unused_code::is_test_node(node) {
+                &[REFERENCES_LENS]
+            } else {
+                &[REFERENCES_LENS, TESTS_LENS]
+            };
+            for kind in kinds {
+                let data = CodeLensData {
+                    uri: uri.to_string(),
+                    node_id,
+                    name: node_props::name(node).to_string(),
+                    kind: kind.to_string(),
+                };
+                lenses.push(CodeLens {
+                    range,
+                    command: None,
+                    data: serde_json::to_value(data).ok(),
+                });
+            }
+
+            let node_keys = monikers::link_keys(node_id, node);
+            let linked: Vec<serde_json::Value> = memories
+                .iter()
+                .filter(|m| m.code_links.iter().any(|l| node_keys.contains(&l.node_id)))
+                .map(|m| serde_json::json!({ "id": m.id.to_string(), "title": m.title }))
+                .collect();
+            if !linked.is_empty() {
+                lenses.push(CodeLens {
+                    range,
+                    command: Some(Command {
+                        title: count_title(linked.len(), "linked memory", "linked memories"),
+                        command: SHOW_MEMORIES_COMMAND.to_string(),
+                        arguments: Some(vec![serde_json::Value::Array(linked)]),
+                    }),
+                    data: None,
+                });
+            }
+        }
+
+        if lenses.is_empty() {
+            return Ok(None);
+        }
+        lenses.sort_by_key(|lens| (lens.range.start.line, lens.range.start.character));
+        Ok(Some(lenses))
+    }
+
+    /// Count references or related tests for a lens listed by [`Self::handle_code_lens`].
+    pub async fn handle_code_lens_resolve(&self, mut lens: CodeLens) -> Result<CodeLens> {
+        if lens.command.is_some() {
+            return Ok(lens);
+        }
+        let data: CodeLensData = lens
+            .data
+            .take()
+            .and_then(|data| serde_json::from_value(data).ok())
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Invalid code lens data"))?;
+        let kind = lens_kind(&data.kind)
+            .ok_or_else(|| tower_lsp::jsonrpc::Error::invalid_params("Unknown code lens kind"))?;
+        let uri = Url::parse(&data.uri)
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
+
+        let graph = self.graph.read().await;
+        let node_id = self.resolve_lens_node(&graph, &path, &data);
+
+        if let Some(command) = node_id.and_then(|id| self.query_cache.get_code_lens(id, kind)) {
+            lens.command = Some(command);
+            return Ok(lens);
+        }
+
+        let locations = match node_id {
+            Some(node_id) if kind == REFERENCES_LENS => {
+                self.lens_reference_locations(&graph, node_id)
+            }
+            Some(node_id) => self.lens_test_locations(&graph, &path, node_id).await,
+            // The symbol is gone; the editor asks for new lenses after the edit
+            None => Vec::new(),
+        };
+        let title = if kind == REFERENCES_LENS {
+            count_title(locations.len(), "reference", "references")
+        } else if locations.len() >= MAX_LENS_TESTS {
+            format!("{MAX_LENS_TESTS}+ related tests")
+        } else {
+            count_title(locations.len(), "related test", "related tests")
+        };
+
+        let command = Command {
+            title,
+            command: SHOW_REFERENCES_COMMAND.to_string(),
+            arguments: Some(vec![
+                serde_json::json!(data.uri),
+                serde_json::json!(lens.range.start),
+                serde_json::json!(locations),
+            ]),
+        };
+        if let Some(node_id) = node_id {
+            self.query_cache
+                .set_code_lens(node_id, kind, command.clone());
+        }
+        lens.command = Some(command);
+        Ok(lens)
+    }
+
+    /// Locations of the symbols that reference `node_id`, one per symbol.
+    fn lens_reference_locations(&self, graph: &CodeGraph, node_id: NodeId) -> Vec<Location> {
+        let mut sources: Vec<NodeId> = self
+            .get_connected_edges(graph, node_id, Direction::Incoming)
+            .into_iter()
+            .map(|(source, _, _)| source)
+            .collect();
+        sources.sort_unstable();
+        sources.dedup();
+        sources
+            .into_iter()
+            .filter_map(|source| self.node_to_location(graph, source).ok())
+            .collect()
+    }
+
+    /// Locations of the tests related to `node_id`, at most [`MAX_LENS_TESTS`].
+    async fn lens_test_locations(
+        &self,
+        graph: &CodeGraph,
+        path: &Path,
+        node_id: NodeId,
+    ) -> Vec<Location> {
+        let result = related_tests::find_related_tests(
+            graph,
+            &self.query_engine,
+            related_tests::FindRelatedTestsParams {
+                path: path.to_string_lossy().to_string(),
+                target_node_id: Some(node_id),
+                limit: MAX_LENS_TESTS,
+            },
+        )
+        .await;
+        result
+            .tests
+            .iter()
+            .filter_map(|test| self.node_to_location(graph, test.node_id).ok())
+            .collect()
+    }
+
+    /// The symbol a lens belongs to: its node if it still exists under the same
+    /// name, otherwise a symbol with that name in the same file.
+    fn resolve_lens_node(
+        &self,
+        graph: &CodeGraph,
+        path: &Path,
+        data: &CodeLensData,
+    ) -> Option<NodeId> {
+        let has_name = |node_id: NodeId| {
+            graph
+                .get_node(node_id)
+                .is_ok_and(|node| node_props::name(node) == data.name)
+        };
+        if has_name(data.node_id) {
+            return Some(data.node_id);
+        }
+        self.symbol_index
+            .get_file_symbols(path)
+            .into_iter()
+            .find(|&node_id| has_name(node_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai_query::QueryEngine;
+    use codegraph::{EdgeType, PropertyMap, PropertyValue};
+    use std::sync::Arc;
+    use tokio::sync::RwLock;
+
+    fn add_function(g: &mut CodeGraph, name: &str, path: &str, line: i64) -> NodeId {
+        let props = PropertyMap::new()
+            .with("name", PropertyValue::String(name.to_string()))
+            .with("path", PropertyValue::String(path.to_string()))
+            .with("line_start", PropertyValue::Int(line))
+            .with("line_end", PropertyValue::Int(line + 2));
+        g.add_node(NodeType::Function, props).unwrap()
+    }
+
+    #[test]
+    fn test_count_title() {
+        assert_eq!(count_title(1, "reference", "references"), "1 reference");
+        assert_eq!(count_title(0, "reference", "references"), "0 references");
+        assert_eq!(
+            count_title(3, "linked memory", "linked memories"),
+            "3 linked memories"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_code_lens_list_and_resolve() {
+        let graph = Arc::new(RwLock::new(CodeGraph::in_memory().unwrap()));
+        let (load, caller) = {
+            let mut g = graph.write().await;
+            let load = add_function(&mut g, "load", "/src/app.rs", 1);
+            let caller = add_function(&mut g, "run", "/src/main.rs", 1);
+            g.add_edge(caller, load, EdgeType::Calls, PropertyMap::new())
+                .unwrap();
+            (load, caller)
+        };
+        let query_engine = Arc::new(QueryEngine::new(Arc::clone(&graph)));
+        let backend = CodeGraphBackend::new_for_test(graph, query_engine);
+        let path = Path::new("/src/app.rs");
+        backend
+            .symbol_index
+            .add_node_for_test(path.to_path_buf(), load, "load", "Function", 1, 3);
+        backend.symbol_index.add_node_for_test(
+            Path::new("/src/main.rs").to_path_buf(),
+            caller,
+            "run",
+            "Function",
+            1,
+            3,
+        );
+
+        let uri = Url::from_file_path(path).unwrap();
+        let lenses = backend.handle_code_lens(&uri).await.unwrap().unwrap();
+        assert_eq!(lenses.len(), 2);
+        assert!(lenses.iter().all(|lens| lens.command.is_none()));
+
+        let references = lenses
+            .into_iter()
+            .find(|lens| lens.data.as_ref().unwrap()["kind"] == REFERENCES_LENS)
+            .unwrap();
+        let resolved = backend.handle_code_lens_resolve(references).await.unwrap();
+        let command = resolved.command.unwrap();
+        assert_eq!(command.title, "1 reference");
+        assert_eq!(command.command, SHOW_REFERENCES_COMMAND);
+        assert_eq!(
+            backend.query_cache.get_code_lens(load, REFERENCES_LENS),
+            Some(command)
+        );
+    }
+}
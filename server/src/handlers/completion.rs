@@ -0,0 +1,532 @@
+//! Completion of workspace symbols with auto-import edits.
+//!
+//! Offers the functions, classes, interfaces and constants of the whole
+//! workspace whose name starts with the identifier being typed, for languages
+//! whose own tooling has no workspace-wide completion: COBOL, Fortran, Tcl,
+//! Verilog and plugin languages. Languages with strong native tooling
+//! (TypeScript, Python, Rust, ...) are left to it. Picking a symbol from
+//! another file also inserts the import it needs, built from the graph's
+//! module structure:
+//!
+//! - Fortran: `use module_name, only: name` in the enclosing program unit,
+//!   for procedures of a module
+//! - Tcl: `source [file join [file dirname [info script]] relative/file.tcl]`
+//!
+//! Member access (`.`, `::`) is left alone.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::node_props;
+use codegraph::{CodeGraph, Direction, EdgeType, Node, NodeId, NodeType};
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemLabelDetails, CompletionItemTag,
+    CompletionList, CompletionParams, CompletionResponse, CompletionTextEdit, Documentation,
+    Position, Range, TextEdit,
+};
+
+/// Maximum number of completion items returned. Longer lists are marked
+/// incomplete so the editor asks again as the prefix grows.
+const MAX_COMPLETION_ITEMS: usize = 100;
+
+/// Node types offered as completions.
+const COMPLETION_NODE_TYPES: [NodeType; 6] = [
+    NodeType::Function,
+    NodeType::Class,
+    NodeType::Interface,
+    NodeType::Trait,
+    NodeType::Type,
+    NodeType::Variable,
+];
+
+/// Languages whose editors complete workspace symbols themselves.
+const NATIVE_COMPLETION_LANGUAGES: [&str; 13] = [
+    "c",
+    "cpp",
+    "csharp",
+    "go",
+    "java",
+    "javascript",
+    "kotlin",
+    "php",
+    "python",
+    "ruby",
+    "rust",
+    "swift",
+    "typescript",
+];
+
+/// How a language imports symbols from other files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModuleSystem {
+    Fortran,
+    Tcl,
+}
+
+impl ModuleSystem {
+    fn for_language(language: &str) -> Option<Self> {
+        match language {
+            "fortran" => Some(ModuleSystem::Fortran),
+            "tcl" => Some(ModuleSystem::Tcl),
+            _ => None,
+        }
+    }
+}
+
+/// Completion kind of a node, or None if it isn't offered. Variables are
+/// only offered when they are constants.
+fn completion_kind(node: &Node) -> Option<CompletionItemKind> {
+    match node.node_type {
+        NodeType::Function => Some(CompletionItemKind::FUNCTION),
+        NodeType::Class => Some(CompletionItemKind::CLASS),
+        NodeType::Interface | NodeType::Trait => Some(CompletionItemKind::INTERFACE),
+        NodeType::Type => Some(CompletionItemKind::STRUCT),
+        NodeType::Variable if node.properties.get_bool("is_const").unwrap_or(false) => {
+            Some(CompletionItemKind::CONSTANT)
+        }
+        _ => None,
+    }
+}
+
+/// The identifier typed before `character` (UTF-16) on `line`, and the UTF-16
+/// column it starts at. None when there is no identifier, or when it follows
+/// `.` or `::` (member access).
+fn word_before(line: &str, character: u32) -> Option<(String, u32)> {
+    let mut column = 0u32;
+    let mut before = String::new();
+    for c in line.chars() {
+        if column >= character {
+            break;
+        }
+        before.push(c);
+        column += c.len_utf16() as u32;
+    }
+
+    let word: String = before
+        .chars()
+        .rev()
+        .take_while(|c| crate::domain::source_text::is_word_char(*c))
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    let rest = &before[..before.len() - word.len()];
+    if word.is_empty()
+        || word.starts_with(|c: char| c.is_ascii_digit())
+        || rest.ends_with('.')
+        || rest.ends_with("::")
+    {
+        return None;
+    }
+    let start = column - word.encode_utf16().count() as u32;
+    Some((word, start))
+}
+
+fn component_str(component: Component) -> String {
+    component.as_os_str().to_string_lossy().into_owned()
+}
+
+/// Path of `target` relative to the directory `from_dir`, with `/`
+/// separators, e.g. `util.tcl` or `../lib/util.tcl`.
+fn relative_path(from_dir: &Path, target: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let to: Vec<Component> = target.components().collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+
+    let mut parts = vec!["..".to_string(); from.len() - common];
+    parts.extend(to[common..].iter().copied().map(component_str));
+    parts.join("/")
+}
+
+/// The statement importing `name`, defined in `target`, into `from`.
+/// `module` is the module containing the symbol, which Fortran imports name.
+/// None when the symbol cannot be imported.
+fn import_statement(
+    system: ModuleSystem,
+    from: &Path,
+    target: &Path,
+    module: Option<&str>,
+    name: &str,
+) -> Option<String> {
+    match system {
+        ModuleSystem::Fortran => Some(format!("use {}, only: {name}\n", module?)),
+        ModuleSystem::Tcl => {
+            let relative = relative_path(from.parent()?, target);
+            Some(format!(
+                "source [file join [file dirname [info script]] {relative}]\n"
+            ))
+        }
+    }
+}
+
+/// Line (0-indexed) after a leading shebang and comment block. New Tcl
+/// imports go here when a file has none yet.
+fn header_end(text: &str) -> u32 {
+    text.lines()
+        .take_while(|line| line.trim_start().starts_with('#'))
+        .count() as u32
+}
+
+/// Whether `file_node` already imports `symbol` (named `name`).
+fn already_imported(graph: &CodeGraph, file_node: NodeId, symbol: NodeId, name: &str) -> bool {
+    graph
+        .get_neighbors(file_node, Direction::Outgoing)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|target| {
+            graph
+                .get_edges_between(file_node, target)
+                .unwrap_or_default()
+                .into_iter()
+                .map(move |edge_id| (target, edge_id))
+        })
+        .filter_map(|(target, edge_id)| Some((target, graph.get_edge(edge_id).ok()?)))
+        .any(|(target, edge)| {
+            edge.edge_type == EdgeType::Imports
+                && (target == symbol
+                    || edge
+                        .properties
+                        .get_string_list_compat("symbols")
+                        .is_some_and(|symbols| symbols.iter().any(|s| s == name)))
+        })
+}
+
+/// Whether a function is a method: contained in a class, interface or trait.
+fn is_member(graph: &CodeGraph, node_id: NodeId) -> bool {
+    graph
+        .get_neighbors(node_id, Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .any(|parent| {
+            graph.get_node(parent).is_ok_and(|p| {
+                matches!(
+                    p.node_type,
+                    NodeType::Class | NodeType::Interface | NodeType::Trait
+                )
+            }) && graph
+                .get_edges_between(parent, node_id)
+                .unwrap_or_default()
+                .into_iter()
+                .any(|eid| {
+                    graph
+                        .get_edge(eid)
+                        .is_ok_and(|e| e.edge_type == EdgeType::Contains)
+                })
+        })
+}
+
+/// Name of the module containing a symbol, if any.
+fn containing_module(graph: &CodeGraph, node_id: NodeId) -> Option<&str> {
+    graph
+        .get_neighbors(node_id, Direction::Incoming)
+        .unwrap_or_default()
+        .into_iter()
+        .find_map(|parent| {
+            let module = graph.get_node(parent).ok()?;
+            let contains = module.node_type == NodeType::Module
+                && graph
+                    .get_edges_between(parent, node_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .any(|eid| {
+                        graph
+                            .get_edge(eid)
+                            .is_ok_and(|e| e.edge_type == EdgeType::Contains)
+                    });
+            contains.then(|| node_props::name(module))
+        })
+        .filter(|name| !name.is_empty())
+}
+
+/// Fortran `use` statements go right after the header of the program unit
+/// (program, module, subroutine or function) enclosing `line` (0-indexed):
+/// the position after its header line and the indentation of its body.
+fn fortran_use_position(
+    graph: &CodeGraph,
+    path: &str,
+    text: &str,
+    line: u32,
+) -> Option<(Position, String)> {
+    let header = graph
+        .query()
+        .property("path", path)
+        .execute()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| {
+            let node = graph.get_node(id).ok()?;
+            if !matches!(
+                node.node_type,
+                NodeType::Function | NodeType::Module | NodeType::Class
+            ) {
+                return None;
+            }
+            let start = node_props::line_start(node);
+            let end = node_props::line_end(node).max(start);
+            (start <= line + 1 && line + 1 <= end).then_some((start, end))
+        })
+        .min_by_key(|(start, end)| end - start)
+        .map(|(start, _)| start)?;
+    let indent: String = text
+        .lines()
+        .nth(header.saturating_sub(1) as usize)?
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    Some((Position::new(header, 0), format!("{indent}  ")))
+}
+
+impl CodeGraphBackend {
+    /// Workspace symbols starting with the identifier at the cursor.
+    pub async fn handle_completion(
+        &self,
+        params: CompletionParams,
+    ) -> Result<Option<CompletionResponse>> {
+        let uri = &params.text_document_position.text_document.uri;
+        let position = params.text_document_position.position;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+        let Some(language) = self.parsers.language_for_path(&path) else {
+            return Ok(None);
+        };
+        if NATIVE_COMPLETION_LANGUAGES.contains(&language) {
+            return Ok(None);
+        }
+        let module_system = ModuleSystem::for_language(language);
+
+        let text = match self.open_documents.get(uri) {
+            Some(doc) => doc.text(),
+            None => match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(_) => return Ok(None),
+            },
+        };
+        let line = text.lines().nth(position.line as usize).unwrap_or("");
+        let Some((prefix, start)) = word_before(line, position.character) else {
+            return Ok(None);
+        };
+        let prefix_lower = prefix.to_lowercase();
+        let replace_range = Range::new(Position::new(position.line, start), position);
+        let folders = self.workspace_folders.read().await.clone();
+
+        let graph = self.graph.read().await;
+        let path_str = path.to_string_lossy();
+        let file_node = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .property("path", path_str.as_ref())
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .next();
+
+        // New imports go after the file's last import, or into the enclosing
+        // program unit for Fortran, with the indentation to insert them at
+        let import_at = match module_system {
+            Some(ModuleSystem::Fortran) => {
+                fortran_use_position(&graph, &path_str, &text, position.line)
+            }
+            Some(ModuleSystem::Tcl) => {
+                let import_line = graph
+                    .query()
+                    .node_type(NodeType::Import)
+                    .property("path", path_str.as_ref())
+                    .execute()
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter_map(|id| node_props::line_end_opt(graph.get_node(id).ok()?))
+                    .max()
+                    .unwrap_or_else(|| header_end(&text));
+                Some((Position::new(import_line, 0), String::new()))
+            }
+            None => None,
+        };
+
+        let mut items = Vec::new();
+        let mut seen = HashSet::new();
+        let mut is_incomplete = false;
+        'collect: for node_type in COMPLETION_NODE_TYPES {
+            let node_ids = graph
+                .query()
+                .node_type(node_type)
+                .execute()
+                .unwrap_or_default();
+            for node_id in node_ids {
+                let Ok(node) = graph.get_node(node_id) else {
+                    continue;
+                };
+                let name = node_props::name(node);
+                if name.is_empty() || !name.to_lowercase().starts_with(&prefix_lower) {
+                    continue;
+                }
+                let Some(kind) = completion_kind(node) else {
+                    continue;
+                };
+                let symbol_path = Path::new(node_props::path(node));
+                let same_file = symbol_path == path;
+                if !same_file && !node_props::is_public(node) {
+                    continue;
+                }
+                if node.node_type == NodeType::Function && is_member(&graph, node_id) {
+                    continue;
+                }
+                if !seen.insert((name.to_string(), symbol_path.to_path_buf())) {
+                    continue;
+                }
+                if items.len() == MAX_COMPLETION_ITEMS {
+                    is_incomplete = true;
+                    break 'collect;
+                }
+
+                let root = folders
+                    .iter()
+                    .filter(|folder| symbol_path.starts_with(folder))
+                    .max_by_key(|folder| folder.components().count())
+                    .map(PathBuf::as_path)
+                    .or_else(|| symbol_path.parent())
+                    .unwrap_or(symbol_path);
+                let import = match (module_system, &import_at) {
+                    (Some(system), Some((position, indent)))
+                        if !same_file
+                            && self.parsers.language_for_path(symbol_path) == Some(language)
+                            && !file_node
+                                .is_some_and(|f| already_imported(&graph, f, node_id, name)) =>
+                    {
+                        import_statement(
+                            system,
+                            &path,
+                            symbol_path,
+                            containing_module(&graph, node_id),
+                            name,
+                        )
+                        .filter(|statement| !text.contains(statement.trim_end()))
+                        .map(|statement| (*position, format!("{indent}{statement}")))
+                    }
+                    _ => None,
+                };
+
+                let location = symbol_path
+                    .strip_prefix(root)
+                    .unwrap_or(symbol_path)
+                    .to_string_lossy()
+                    .into_owned();
+                items.push(CompletionItem {
+                    label: name.to_string(),
+                    label_details: Some(CompletionItemLabelDetails {
+                        detail: None,
+                        description: Some(location),
+                    }),
+                    kind: Some(kind),
+                    detail: node.properties.get_string("signature").map(String::from),
+                    documentation: node
+                        .properties
+                        .get_string("doc")
+                        .map(|doc| Documentation::String(doc.to_string())),
+                    tags: node_props::is_deprecated(node)
+                        .then(|| vec![CompletionItemTag::DEPRECATED]),
+                    // Symbols of the current file first
+                    sort_text: Some(format!("{}{}", u8::from(!same_file), name)),
+                    text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                        replace_range,
+                        name.to_string(),
+                    ))),
+                    additional_text_edits: import.map(|(position, statement)| {
+                        vec![TextEdit::new(Range::new(position, position), statement)]
+                    }),
+                    ..Default::default()
+                });
+            }
+        }
+
+        if items.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(CompletionResponse::List(CompletionList {
+            is_incomplete,
+            items,
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_word_before_cursor() {
+        assert_eq!(
+            word_before("let x = fetchUs", 15),
+            Some(("fetchUs".to_string(), 8))
+        );
+        // UTF-16 columns
+        assert_eq!(
+            word_before("s = \"😀\" + pa", 13),
+            Some(("pa".to_string(), 11))
+        );
+        // Member access and numbers are left alone
+        assert_eq!(word_before("user.na", 7), None);
+        assert_eq!(word_before("std::co", 7), None);
+        assert_eq!(word_before("x = 12", 6), None);
+        assert_eq!(word_before("x = ", 4), None);
+    }
+
+    #[test]
+    fn test_import_statements() {
+        assert_eq!(
+            import_statement(
+                ModuleSystem::Fortran,
+                Path::new("/ws/src/main.f90"),
+                Path::new("/ws/src/physics/solver.f90"),
+                Some("solver_mod"),
+                "solve"
+            )
+            .as_deref(),
+            Some("use solver_mod, only: solve\n")
+        );
+        // Only procedures of a module can be imported
+        assert_eq!(
+            import_statement(
+                ModuleSystem::Fortran,
+                Path::new("/ws/src/main.f90"),
+                Path::new("/ws/src/legacy.f"),
+                None,
+                "integrate"
+            ),
+            None
+        );
+        assert_eq!(
+            import_statement(
+                ModuleSystem::Tcl,
+                Path::new("/ws/app/main.tcl"),
+                Path::new("/ws/lib/util.tcl"),
+                None,
+                "log_info"
+            )
+            .as_deref(),
+            Some("source [file join [file dirname [info script]] ../lib/util.tcl]\n")
+        );
+    }
+
+    #[test]
+    fn test_only_weak_tooling_languages_have_module_systems() {
+        assert_eq!(
+            ModuleSystem::for_language("fortran"),
+            Some(ModuleSystem::Fortran)
+        );
+        assert_eq!(ModuleSystem::for_language("tcl"), Some(ModuleSystem::Tcl));
+        for language in NATIVE_COMPLETION_LANGUAGES {
+            assert_eq!(ModuleSystem::for_language(language), None);
+        }
+    }
+
+    #[test]
+    fn test_header_end_skips_comments() {
+        assert_eq!(
+            header_end("#!/usr/bin/env tclsh\n# Utilities\n\nproc a {} {}\n"),
+            2
+        );
+        assert_eq!(header_end("proc a {} {}\n"), 0);
+    }
+}
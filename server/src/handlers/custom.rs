@@ -2,8 +2,9 @@
 
 use crate::backend::CodeGraphBackend;
 use crate::domain::node_props;
-use codegraph::Node;
+use codegraph::{Node, NodeType};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use tower_lsp::jsonrpc::Result;
 use tower_lsp::lsp_types::{Position, Range, Url};
 
@@ -205,7 +206,7 @@ impl CodeGraphBackend {
         crate::domain::unused_code::is_test_node(node)
     }
 
-    fn node_to_range(node: &Node) -> Option<Range> {
+    pub(crate) fn node_to_range(node: &Node) -> Option<Range> {
         let start_line = node_props::line_start(node).saturating_sub(1);
         let end_line = node_props::line_end(node).saturating_sub(1);
 
@@ -409,6 +410,10 @@ pub struct IndirectImpact {
 pub struct AffectedTest {
     pub uri: String,
     pub test_name: String,
+    /// Line of the test (1-indexed)
+    pub line: u32,
+    /// Caller levels between the test and the changed symbol (1 = direct)
+    pub depth: u32,
 }
 
 #[derive(Debug, Serialize)]
@@ -425,6 +430,8 @@ pub struct ImpactAnalysisResponse {
     pub direct_impact: Vec<DirectImpact>,
     pub indirect_impact: Vec<IndirectImpact>,
     pub affected_tests: Vec<AffectedTest>,
+    /// Distinct files containing affected tests, for change-based test selection
+    pub test_files: Vec<String>,
     pub summary: ImpactSummary,
 }
 
@@ -449,6 +456,7 @@ impl CodeGraphBackend {
                         direct_impact: Vec::new(),
                         indirect_impact: Vec::new(),
                         affected_tests: Vec::new(),
+                        test_files: Vec::new(),
                         summary: ImpactSummary {
                             files_affected: 0,
                             breaking_changes: 0,
@@ -470,9 +478,8 @@ impl CodeGraphBackend {
         )
         .await;
 
-        // Convert domain impacted → LSP DirectImpact + AffectedTest
+        // Convert domain impacted → LSP DirectImpact
         let mut direct_impact: Vec<DirectImpact> = Vec::new();
-        let mut affected_tests: Vec<AffectedTest> = Vec::new();
 
         for sym in &result.impacted {
             let uri = if sym.path.is_empty() {
@@ -484,13 +491,6 @@ impl CodeGraphBackend {
                 sym.path.clone()
             };
 
-            if sym.is_test {
-                affected_tests.push(AffectedTest {
-                    uri: uri.clone(),
-                    test_name: sym.name.clone(),
-                });
-            }
-
             direct_impact.push(DirectImpact {
                 uri,
                 range: Range {
@@ -508,6 +508,18 @@ impl CodeGraphBackend {
             });
         }
 
+        // Transitive callers that are tests
+        let affected_tests: Vec<AffectedTest> = result
+            .affected_tests
+            .into_iter()
+            .map(|t| AffectedTest {
+                uri: t.path,
+                test_name: t.name,
+                line: t.line_start,
+                depth: t.depth,
+            })
+            .collect();
+
         // Convert domain indirect_impacted → LSP IndirectImpact
         let indirect_impact: Vec<IndirectImpact> = result
             .indirect_impacted
@@ -523,6 +535,7 @@ impl CodeGraphBackend {
             direct_impact,
             indirect_impact,
             affected_tests,
+            test_files: result.test_files,
             summary: ImpactSummary {
                 files_affected: result.files_affected,
                 breaking_changes: result.breaking_changes,
@@ -532,6 +545,99 @@ impl CodeGraphBackend {
     }
 }
 
+// ==========================================
+// Graph Export Request
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportGraphParams {
+    pub uri: String,
+    /// Symbol to center on; without it the whole file is the start node
+    pub position: Option<Position>,
+    /// "dot" (default) or "mermaid"
+    pub format: Option<String>,
+    pub depth: Option<usize>,
+    /// "outgoing", "incoming" or "both" (default)
+    pub direction: Option<String>,
+    /// Node types to keep, e.g. ["function", "class"]
+    pub node_types: Option<Vec<String>>,
+    /// Edge types to follow, e.g. ["calls", "imports"]
+    pub edge_types: Option<Vec<String>>,
+    pub max_nodes: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportGraphResponse {
+    pub format: String,
+    pub content: String,
+    pub node_count: usize,
+    pub edge_count: usize,
+    pub truncated: bool,
+}
+
+impl CodeGraphBackend {
+    /// Render the subgraph around a symbol or file as DOT or Mermaid text.
+    pub async fn handle_export_graph_visualization(
+        &self,
+        params: ExportGraphParams,
+    ) -> Result<ExportGraphResponse> {
+        use crate::domain::graph_export::{self, ExportFormat, SubgraphOptions};
+        use codegraph::Direction;
+
+        let uri = Url::parse(&params.uri)
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid file path"))?;
+
+        let format_name = params.format.as_deref().unwrap_or("dot");
+        let format = ExportFormat::parse(format_name).ok_or_else(|| {
+            tower_lsp::jsonrpc::Error::invalid_params(format!(
+                "Unknown format '{format_name}', expected 'dot' or 'mermaid'"
+            ))
+        })?;
+        let direction = match params.direction.as_deref().unwrap_or("both") {
+            "outgoing" => Direction::Outgoing,
+            "incoming" => Direction::Incoming,
+            _ => Direction::Both,
+        };
+        let options = SubgraphOptions {
+            depth: params.depth.unwrap_or(2).min(10),
+            direction,
+            node_types: params.node_types.unwrap_or_default(),
+            edge_types: params.edge_types.unwrap_or_default(),
+            max_nodes: params.max_nodes.unwrap_or(100).clamp(1, 500),
+        };
+
+        let graph = self.graph.read().await;
+        let root = match params.position {
+            Some(position) => self.find_node_at_position(&graph, &path, position)?,
+            None => codegraph::helpers::find_file_by_path(&graph, &path.to_string_lossy())
+                .ok()
+                .flatten(),
+        };
+        let root = root.ok_or_else(|| {
+            tower_lsp::jsonrpc::Error::invalid_params("No symbol or file found to export")
+        })?;
+
+        let title = graph
+            .get_node(root)
+            .map(|node| node_props::name(node).to_string())
+            .unwrap_or_default();
+        let subgraph = graph_export::collect_subgraph(&graph, &[root], &options);
+
+        Ok(ExportGraphResponse {
+            format: format!("{format:?}").to_lowercase(),
+            content: graph_export::render(&subgraph, format, &title),
+            node_count: subgraph.nodes.len(),
+            edge_count: subgraph.edges.len(),
+            truncated: subgraph.truncated,
+        })
+    }
+}
+
 // ==========================================
 // Parser Metrics Request
 // ==========================================
@@ -618,6 +724,175 @@ impl CodeGraphBackend {
     }
 }
 
+// ==========================================
+// Cache Stats Request
+// ==========================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStatsResponse {
+    /// Cached AI query primitive results (callers, callees, traversals)
+    pub primitive_entries: usize,
+    pub primitive_hits: u64,
+    pub primitive_misses: u64,
+    /// Fraction of primitive lookups served from cache
+    pub primitive_hit_rate: f64,
+    /// Primitive entries dropped because a file they depend on changed
+    pub primitive_invalidations: u64,
+    pub definitions: usize,
+    pub references: usize,
+    pub call_hierarchies: usize,
+    pub dependency_graphs: usize,
+    pub ai_contexts: usize,
+}
+
+impl CodeGraphBackend {
+    pub async fn handle_get_cache_stats(&self) -> Result<CacheStatsResponse> {
+        let stats = self.query_cache.stats();
+        let lookups = stats.primitive_hits + stats.primitive_misses;
+
+        Ok(CacheStatsResponse {
+            primitive_entries: stats.primitives_count,
+            primitive_hits: stats.primitive_hits,
+            primitive_misses: stats.primitive_misses,
+            primitive_hit_rate: if lookups > 0 {
+                stats.primitive_hits as f64 / lookups as f64
+            } else {
+                0.0
+            },
+            primitive_invalidations: stats.primitive_invalidations,
+            definitions: stats.definitions_count,
+            references: stats.references_count,
+            call_hierarchies: stats.call_hierarchies_count,
+            dependency_graphs: stats.dependency_graphs_count,
+            ai_contexts: stats.ai_contexts_count,
+        })
+    }
+}
+
+// ==========================================
+// Workspace Statistics Request
+// ==========================================
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SymbolIndexStatistics {
+    pub symbols: usize,
+    pub files: usize,
+    pub unique_names: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueryIndexStatistics {
+    pub text_documents: usize,
+    pub text_tokens: usize,
+    pub imported_modules: usize,
+    pub functions_with_callers: usize,
+    pub functions_with_callees: usize,
+    pub symbol_vectors: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceStatisticsResponse {
+    pub node_count: usize,
+    pub edge_count: usize,
+    /// Node counts by lowercase node type
+    pub nodes_by_type: BTreeMap<String, usize>,
+    /// Edge counts by lowercase edge type
+    pub edges_by_type: BTreeMap<String, usize>,
+    /// Indexed files by language ("other" when no parser claims the path)
+    pub files_by_language: BTreeMap<String, usize>,
+    pub symbol_index: SymbolIndexStatistics,
+    pub query_indexes: QueryIndexStatistics,
+    /// Files that failed to parse, by language (languages without failures are omitted)
+    pub parse_errors: BTreeMap<String, usize>,
+    pub total_parse_errors: usize,
+    /// Memory store statistics, or None when the store is not initialized
+    pub memory: Option<serde_json::Value>,
+    /// Unix time (seconds) the last full workspace index finished
+    pub last_full_index: Option<u64>,
+    /// Unix time (seconds) the query indexes were last rebuilt
+    pub last_index_update: Option<u64>,
+}
+
+/// Seconds since the Unix epoch.
+fn unix_seconds(time: std::time::SystemTime) -> Option<u64> {
+    time.duration_since(std::time::UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+impl CodeGraphBackend {
+    pub async fn handle_get_statistics(&self) -> Result<WorkspaceStatisticsResponse> {
+        let mut nodes_by_type: BTreeMap<String, usize> = BTreeMap::new();
+        let mut edges_by_type: BTreeMap<String, usize> = BTreeMap::new();
+        let mut files_by_language: BTreeMap<String, usize> = BTreeMap::new();
+        let (node_count, edge_count) = {
+            let graph = self.graph.read().await;
+            for (_, node) in graph.iter_nodes() {
+                let node_type = format!("{:?}", node.node_type).to_lowercase();
+                *nodes_by_type.entry(node_type).or_default() += 1;
+                if node.node_type == NodeType::CodeFile {
+                    let language = self
+                        .parsers
+                        .language_for_path(std::path::Path::new(node_props::path(node)))
+                        .unwrap_or("other");
+                    *files_by_language.entry(language.to_string()).or_default() += 1;
+                }
+            }
+            for (_, edge) in graph.iter_edges() {
+                let edge_type = format!("{:?}", edge.edge_type).to_lowercase();
+                *edges_by_type.entry(edge_type).or_default() += 1;
+            }
+            (graph.node_count(), graph.edge_count())
+        };
+
+        let index_stats = self.symbol_index.stats();
+        let sizes = self.query_engine.index_sizes().await;
+        let parse_errors: BTreeMap<String, usize> = self
+            .parsers
+            .all_metrics()
+            .into_iter()
+            .filter(|(_, m)| m.files_failed > 0)
+            .map(|(language, m)| (language.to_string(), m.files_failed))
+            .collect();
+
+        Ok(WorkspaceStatisticsResponse {
+            node_count,
+            edge_count,
+            nodes_by_type,
+            edges_by_type,
+            files_by_language,
+            symbol_index: SymbolIndexStatistics {
+                symbols: index_stats.total_symbols,
+                files: index_stats.total_files,
+                unique_names: index_stats.unique_names,
+            },
+            query_indexes: QueryIndexStatistics {
+                text_documents: sizes.text_documents,
+                text_tokens: sizes.text_tokens,
+                imported_modules: sizes.imported_modules,
+                functions_with_callers: sizes.functions_with_callers,
+                functions_with_callees: sizes.functions_with_callees,
+                symbol_vectors: sizes.symbol_vectors,
+            },
+            total_parse_errors: parse_errors.values().sum(),
+            parse_errors,
+            memory: self.memory_manager.stats().await.ok(),
+            last_full_index: self
+                .index_scheduler
+                .last_pass_finished()
+                .and_then(unix_seconds),
+            last_index_update: self
+                .query_engine
+                .indexes_updated_at()
+                .and_then(unix_seconds),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1065,6 +1340,57 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_handle_analyze_impact_selects_transitive_tests() {
+        let graph = Arc::new(RwLock::new(
+            CodeGraph::in_memory().expect("Failed to create graph"),
+        ));
+
+        // test_parse -> parse -> tokenize, plus a non-test caller of parse
+        let ids = {
+            let mut g = graph.write().await;
+            let mut ids = Vec::new();
+            for (name, path) in [
+                ("tokenize", "/src/lexer.rs"),
+                ("parse", "/src/parser.rs"),
+                ("test_parse", "/src/parser_test.rs"),
+                ("run", "/src/cli.rs"),
+            ] {
+                let mut props = PropertyMap::new();
+                props.insert("name".to_string(), PropertyValue::String(name.to_string()));
+                props.insert("path".to_string(), PropertyValue::String(path.to_string()));
+                props.insert("line_start".to_string(), PropertyValue::Int(1));
+                props.insert("line_end".to_string(), PropertyValue::Int(10));
+                ids.push(g.add_node(NodeType::Function, props).unwrap());
+            }
+            for (from, to) in [(1, 0), (2, 1), (3, 1)] {
+                g.add_edge(ids[from], ids[to], EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
+            }
+            ids
+        };
+
+        let query_engine = Arc::new(QueryEngine::new(Arc::clone(&graph)));
+        let backend = CodeGraphBackend::new_for_test(graph, query_engine);
+        let path = std::path::Path::new("/src/lexer.rs");
+        add_node_to_index(&backend, path, ids[0], "tokenize", "Function", 1, 10);
+
+        let params = ImpactAnalysisParams {
+            uri: "file:///src/lexer.rs".to_string(),
+            position: Position {
+                line: 0,
+                character: 0,
+            },
+            analysis_type: "modify".to_string(),
+        };
+        let response = backend.handle_analyze_impact(params).await.unwrap();
+
+        assert_eq!(response.affected_tests.len(), 1);
+        assert_eq!(response.affected_tests[0].test_name, "test_parse");
+        assert_eq!(response.affected_tests[0].depth, 2);
+        assert_eq!(response.test_files, vec!["/src/parser_test.rs"]);
+    }
+
     // ==========================================
     // Related Tests tests
     // ==========================================
@@ -1243,4 +1569,47 @@ mod tests {
         let response = result.unwrap();
         assert!(response.metrics.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_handle_get_cache_stats_counts_primitive_lookups() {
+        let backend = create_test_backend().await;
+
+        backend.query_cache.get_primitive("callers", 1, "depth=1");
+
+        let response = backend.handle_get_cache_stats().await.unwrap();
+        assert_eq!(response.primitive_entries, 0);
+        assert_eq!(response.primitive_misses, 1);
+        assert_eq!(response.primitive_hit_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_handle_get_statistics_counts_by_type() {
+        let backend = create_test_backend().await;
+        {
+            let mut graph = backend.graph.write().await;
+            let file = graph
+                .add_node(
+                    NodeType::CodeFile,
+                    PropertyMap::new()
+                        .with("name", "main.py")
+                        .with("path", "/src/main.py"),
+                )
+                .unwrap();
+            let main = graph
+                .add_node(NodeType::Function, PropertyMap::new().with("name", "main"))
+                .unwrap();
+            graph
+                .add_edge(file, main, EdgeType::Contains, PropertyMap::new())
+                .unwrap();
+        }
+
+        let response = backend.handle_get_statistics().await.unwrap();
+        assert_eq!(response.node_count, 2);
+        assert_eq!(response.edge_count, 1);
+        assert_eq!(response.nodes_by_type["function"], 1);
+        assert_eq!(response.edges_by_type["contains"], 1);
+        assert_eq!(response.files_by_language["python"], 1);
+        assert_eq!(response.total_parse_errors, 0);
+        assert!(response.last_full_index.is_none());
+    }
 }
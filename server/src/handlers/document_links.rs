@@ -0,0 +1,188 @@
+//! Document links from import statements to the files they import.
+//!
+//! Targets come from the Imports edges that cross-file resolution adds for
+//! each imported symbol, not from the module specifier, so path aliases,
+//! barrels and re-exports link to where the symbols are actually defined.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::node_props;
+use codegraph::{Direction, EdgeType, NodeId, NodeType};
+use std::collections::HashMap;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{DocumentLink, Position, Range, Url};
+
+/// Range of `specifier` on line `line` (0-indexed) of an import statement,
+/// falling back to the statement without its indentation.
+fn link_range(line_text: &str, line: u32, specifier: &str) -> Range {
+    let utf16_len = |s: &str| s.encode_utf16().count() as u32;
+    let (start, len) = match line_text.find(specifier).filter(|_| !specifier.is_empty()) {
+        Some(offset) => (utf16_len(&line_text[..offset]), utf16_len(specifier)),
+        None => {
+            let trimmed = line_text.trim();
+            let indent = line_text.len() - line_text.trim_start().len();
+            (utf16_len(&line_text[..indent]), utf16_len(trimmed))
+        }
+    };
+    Range::new(Position::new(line, start), Position::new(line, start + len))
+}
+
+/// The path most symbols of one import resolve to, and the line of the symbol
+/// if there is only one. Ties go to the first path seen.
+fn link_target(resolved: &[(String, u32)]) -> Option<(&str, Option<u32>)> {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+    for (path, _) in resolved {
+        match counts.iter_mut().find(|(p, _)| *p == path.as_str()) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((path.as_str(), 1)),
+        }
+    }
+    let (path, _) = counts
+        .iter()
+        .copied()
+        .reduce(|best, next| if next.1 > best.1 { next } else { best })?;
+    let line = match resolved {
+        [(_, line)] => Some(*line),
+        _ => None,
+    };
+    Some((path, line))
+}
+
+impl CodeGraphBackend {
+    /// Links for the import statements of a file whose symbols were resolved
+    /// to definitions in other files.
+    pub async fn handle_document_link(&self, uri: &Url) -> Result<Option<Vec<DocumentLink>>> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+        let path_str = path.to_string_lossy();
+
+        let text = match self.open_documents.get(uri) {
+            Some(doc) => doc.text(),
+            None => match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(_) => return Ok(None),
+            },
+        };
+        let lines: Vec<&str> = text.lines().collect();
+
+        let graph = self.graph.read().await;
+        let Some(file_node) = graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .property("path", path_str.as_ref())
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .next()
+        else {
+            return Ok(None);
+        };
+
+        // Import statements with the symbols they list, and the definitions
+        // those symbols resolved to
+        let mut statements: Vec<(NodeId, Vec<String>)> = Vec::new();
+        let mut resolved: HashMap<String, NodeId> = HashMap::new();
+        for target in graph
+            .get_neighbors(file_node, Direction::Outgoing)
+            .unwrap_or_default()
+        {
+            let Ok(target_node) = graph.get_node(target) else {
+                continue;
+            };
+            for edge_id in graph
+                .get_edges_between(file_node, target)
+                .unwrap_or_default()
+            {
+                let Ok(edge) = graph.get_edge(edge_id) else {
+                    continue;
+                };
+                if edge.edge_type != EdgeType::Imports {
+                    continue;
+                }
+                if let Some(symbol) = edge.properties.get_string("imported_symbol") {
+                    resolved.insert(symbol.to_string(), target);
+                } else if target_node.node_type == NodeType::Import {
+                    let symbols = edge
+                        .properties
+                        .get_string_list_compat("symbols")
+                        .unwrap_or_default();
+                    statements.push((target, symbols));
+                }
+            }
+        }
+
+        let mut links = Vec::new();
+        for (import_id, symbols) in statements {
+            let Ok(import) = graph.get_node(import_id) else {
+                continue;
+            };
+            let Some(line) = node_props::line_start_opt(import).map(|l| l.saturating_sub(1)) else {
+                continue;
+            };
+            let Some(line_text) = lines.get(line as usize) else {
+                continue;
+            };
+
+            let definitions: Vec<(String, u32)> = symbols
+                .iter()
+                .filter_map(|symbol| graph.get_node(*resolved.get(symbol)?).ok())
+                .filter(|node| node_props::path(node) != path_str)
+                .map(|node| {
+                    (
+                        node_props::path(node).to_string(),
+                        node_props::line_start(node),
+                    )
+                })
+                .collect();
+            let Some((target_path, target_line)) = link_target(&definitions) else {
+                continue;
+            };
+            let Ok(mut target) = Url::from_file_path(target_path) else {
+                continue;
+            };
+            if let Some(target_line) = target_line {
+                target.set_fragment(Some(&format!("L{target_line}")));
+            }
+
+            links.push(DocumentLink {
+                range: link_range(line_text, line, node_props::name(import)),
+                target: Some(target),
+                tooltip: Some(format!("Open {target_path}")),
+                data: None,
+            });
+        }
+
+        if links.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(links))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_link_range_and_target() {
+        let line = "  import { User } from '@app/models';";
+        assert_eq!(
+            link_range(line, 3, "@app/models"),
+            Range::new(Position::new(3, 24), Position::new(3, 35))
+        );
+        // Specifier not on the line: link the whole statement
+        assert_eq!(
+            link_range(line, 3, "./other"),
+            Range::new(Position::new(3, 2), Position::new(3, 37))
+        );
+
+        let resolved = vec![
+            ("/src/a.ts".to_string(), 4),
+            ("/src/b.ts".to_string(), 9),
+            ("/src/b.ts".to_string(), 12),
+        ];
+        assert_eq!(link_target(&resolved), Some(("/src/b.ts", None)));
+        assert_eq!(link_target(&resolved[..1]), Some(("/src/a.ts", Some(4))));
+        assert_eq!(link_target(&[]), None);
+    }
+}
@@ -0,0 +1,111 @@
+//! Folding ranges derived from node line spans.
+//!
+//! Functions, classes, interfaces, traits and modules fold over their span;
+//! adjacent imports are merged into one import block. Useful for languages
+//! where the editor has no other folding provider.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::node_props;
+use codegraph::NodeType;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{FoldingRange, FoldingRangeKind, Url};
+
+/// Merge import spans (0-indexed, inclusive lines) that touch or overlap into
+/// blocks. Single-line blocks are dropped since there is nothing to fold.
+fn import_blocks(mut spans: Vec<(u32, u32)>) -> Vec<(u32, u32)> {
+    spans.sort_unstable();
+    let mut blocks: Vec<(u32, u32)> = Vec::new();
+    for (start, end) in spans {
+        match blocks.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => blocks.push((start, end)),
+        }
+    }
+    blocks.retain(|(start, end)| end > start);
+    blocks
+}
+
+impl CodeGraphBackend {
+    /// Folding ranges for the symbols and import blocks of a file.
+    pub async fn handle_folding_range(&self, uri: &Url) -> Result<Option<Vec<FoldingRange>>> {
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+        let path_str = path.to_string_lossy();
+
+        let graph = self.graph.read().await;
+        let node_ids = graph
+            .query()
+            .property("path", path_str.as_ref())
+            .execute()
+            .unwrap_or_default();
+
+        let mut ranges = Vec::new();
+        let mut imports = Vec::new();
+        for node_id in node_ids {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            let (Some(start), Some(end)) = (
+                node_props::line_start_opt(node),
+                node_props::line_end_opt(node),
+            ) else {
+                continue;
+            };
+            let (start, end) = (start.saturating_sub(1), end.saturating_sub(1));
+
+            match node.node_type {
+                NodeType::Import => imports.push((start, end.max(start))),
+                NodeType::Function
+                | NodeType::Class
+                | NodeType::Interface
+                | NodeType::Trait
+                | NodeType::Module
+                    if end > start =>
+                {
+                    ranges.push(FoldingRange {
+                        start_line: start,
+                        start_character: None,
+                        end_line: end,
+                        end_character: None,
+                        kind: None,
+                        collapsed_text: None,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        ranges.extend(
+            import_blocks(imports)
+                .into_iter()
+                .map(|(start, end)| FoldingRange {
+                    start_line: start,
+                    start_character: None,
+                    end_line: end,
+                    end_character: None,
+                    kind: Some(FoldingRangeKind::Imports),
+                    collapsed_text: None,
+                }),
+        );
+
+        if ranges.is_empty() {
+            return Ok(None);
+        }
+        ranges.sort_by_key(|r| (r.start_line, std::cmp::Reverse(r.end_line)));
+        ranges.dedup_by_key(|r| (r.start_line, r.end_line));
+        Ok(Some(ranges))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_blocks_merge_adjacent_imports() {
+        let spans = vec![(4, 4), (0, 0), (1, 2), (3, 3), (8, 8), (10, 10), (11, 11)];
+        assert_eq!(import_blocks(spans), vec![(0, 4), (10, 11)]);
+        assert!(import_blocks(vec![(5, 5)]).is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+//! Inlay hints at function definitions: caller counts and complexity.
+//!
+//! Hints go at the end of a function's declaration line, e.g. `↖ 12 callers`
+//! and `complexity 17`. Each kind can be turned off, and complexity is only
+//! shown at or above a threshold so simple functions stay uncluttered.
+
+use crate::backend::{CodeGraphBackend, InlayHintConfig};
+use codegraph::{Direction, EdgeType, NodeId, NodeType};
+use std::collections::HashSet;
+use tower_lsp::jsonrpc::Result;
+use tower_lsp::lsp_types::{InlayHint, InlayHintLabel, InlayHintTooltip, Position, Range, Url};
+
+/// Hint labels with their tooltips for a function.
+fn hint_labels(
+    callers: usize,
+    complexity: Option<i64>,
+    config: &InlayHintConfig,
+) -> Vec<(String, &'static str)> {
+    let mut labels = Vec::new();
+    if config.callers && callers > 0 {
+        let noun = if callers == 1 { "caller" } else { "callers" };
+        labels.push((
+            format!("↖ {callers} {noun}"),
+            "Functions that call this function",
+        ));
+    }
+    if let Some(complexity) = complexity {
+        if config.complexity && complexity >= i64::from(config.complexity_threshold) {
+            labels.push((format!("complexity {complexity}"), "Cyclomatic complexity"));
+        }
+    }
+    labels
+}
+
+/// Length of line `line` (0-indexed) in UTF-16 code units.
+fn line_length(text: &str, line: u32) -> Option<u32> {
+    text.lines()
+        .nth(line as usize)
+        .map(|l| l.trim_end().encode_utf16().count() as u32)
+}
+
+impl CodeGraphBackend {
+    /// Inlay hints for the functions of a file whose declaration line is in `range`.
+    pub async fn handle_inlay_hint(
+        &self,
+        uri: &Url,
+        range: Range,
+    ) -> Result<Option<Vec<InlayHint>>> {
+        let config = self.config.read().await.inlay_hints.clone();
+        if !config.callers && !config.complexity {
+            return Ok(None);
+        }
+
+        let path = uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        // Prefer the editor's text so hints land at the end of unsaved lines
+        let text = match self.open_documents.get(uri) {
+            Some(doc) => doc.text(),
+            None => match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(_) => return Ok(None),
+            },
+        };
+
+        let graph = self.graph.read().await;
+        let mut hints = Vec::new();
+
+        for node_id in self.symbol_index.get_file_symbols(&path) {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            if node.node_type != NodeType::Function {
+                continue;
+            }
+            let Some(node_range) = Self::node_to_range(node) else {
+                continue;
+            };
+            let line = node_range.start.line;
+            if line < range.start.line || line > range.end.line {
+                continue;
+            }
+            let Some(character) = line_length(&text, line) else {
+                continue;
+            };
+
+            let callers: HashSet<NodeId> = self
+                .get_connected_edges(&graph, node_id, Direction::Incoming)
+                .into_iter()
+                .filter(|(_, _, edge_type)| *edge_type == EdgeType::Calls)
+                .map(|(source, _, _)| source)
+                .collect();
+            let complexity = node.properties.get_int("complexity");
+
+            for (label, tooltip) in hint_labels(callers.len(), complexity, &config) {
+                hints.push(InlayHint {
+                    position: Position { line, character },
+                    label: InlayHintLabel::String(label),
+                    kind: None,
+                    text_edits: None,
+                    tooltip: Some(InlayHintTooltip::String(tooltip.to_string())),
+                    padding_left: Some(true),
+                    padding_right: None,
+                    data: None,
+                });
+            }
+        }
+
+        if hints.is_empty() {
+            return Ok(None);
+        }
+        hints.sort_by_key(|hint| hint.position.line);
+        Ok(Some(hints))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint_labels() {
+        let config = InlayHintConfig::default();
+        let labels: Vec<String> = hint_labels(12, Some(17), &config)
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(labels, vec!["↖ 12 callers", "complexity 17"]);
+
+        // No callers and complexity below the threshold: nothing to show
+        assert!(hint_labels(0, Some(3), &config).is_empty());
+        assert_eq!(hint_labels(1, None, &config)[0].0, "↖ 1 caller");
+
+        let callers_off = InlayHintConfig {
+            callers: false,
+            ..InlayHintConfig::default()
+        };
+        assert_eq!(hint_labels(5, Some(10), &callers_off).len(), 1);
+    }
+
+    #[test]
+    fn test_line_length_is_utf16() {
+        let text = "fn a() {}\nfn é😀() {   \n";
+        assert_eq!(line_length(text, 0), Some(9));
+        assert_eq!(line_length(text, 1), Some(10));
+        assert_eq!(line_length(text, 2), None);
+    }
+}
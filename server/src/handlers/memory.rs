@@ -1,5 +1,6 @@
 //! Memory-related LSP command handlers for the CodeGraph memory system.
 
+use crate::memory::{CodeLink, LinkedNodeType, MemoryNode};
 use serde::{Deserialize, Serialize};
 
 // ==========================================
@@ -183,6 +184,24 @@ pub struct MemoryInvalidateResponse {
     pub success: bool,
 }
 
+/// Parameters for marking an invalidated memory as still valid.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRevalidateParams {
+    /// The memory ID to revalidate
+    pub id: String,
+}
+
+/// Response for memory revalidation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MemoryRevalidateResponse {
+    /// Whether the memory was found and revalidated
+    pub success: bool,
+    /// Number of code links moved to the symbols' current keys
+    pub relinked: usize,
+}
+
 // ==========================================
 // Memory List Request
 // ==========================================
@@ -254,6 +273,47 @@ pub struct MemoryUpdateParams {
     pub remove_code_links: Vec<String>,
 }
 
+impl MemoryUpdateParams {
+    /// Apply the edits to `memory`. Its embedding is cleared, so it is
+    /// regenerated for the new text when the memory is stored.
+    pub fn apply_to(self, memory: &mut MemoryNode) {
+        if let Some(title) = self.title {
+            memory.title = title;
+        }
+        if let Some(content) = self.content {
+            memory.content = content;
+        }
+        if let Some(tags) = self.tags {
+            memory.tags = tags;
+        }
+        if let Some(confidence) = self.confidence {
+            memory.confidence = confidence;
+        }
+
+        for link in self.add_code_links {
+            let node_type = match link.node_type.as_str() {
+                "function" => LinkedNodeType::Function,
+                "class" => LinkedNodeType::Class,
+                "module" => LinkedNodeType::Module,
+                "file" => LinkedNodeType::File,
+                "variable" => LinkedNodeType::Variable,
+                "import" => LinkedNodeType::Import,
+                "interface" => LinkedNodeType::Interface,
+                "trait" => LinkedNodeType::Trait,
+                _ => LinkedNodeType::Function, // Default fallback
+            };
+            memory
+                .code_links
+                .push(CodeLink::new(link.node_id, node_type));
+        }
+        for node_id in self.remove_code_links {
+            memory.code_links.retain(|link| link.node_id != node_id);
+        }
+
+        memory.embedding = None;
+    }
+}
+
 /// Response for memory update.
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -523,6 +583,12 @@ mod tests {
         assert_eq!(params.id, "mem_456");
     }
 
+    #[test]
+    fn test_memory_revalidate_params() {
+        let params: MemoryRevalidateParams = serde_json::from_str(r#"{"id": "mem_789"}"#).unwrap();
+        assert_eq!(params.id, "mem_789");
+    }
+
     #[test]
     fn test_memory_list_params_defaults() {
         let json = r#"{}"#;
@@ -551,6 +617,43 @@ mod tests {
         assert_eq!(params.remove_code_links.len(), 1);
     }
 
+    #[test]
+    fn test_memory_update_apply_to() {
+        let mut memory = crate::memory::MemoryNodeBuilder::new()
+            .title("Upload limit")
+            .content("Uploads over 1MB fail")
+            .tag("nginx")
+            .debug_context("413 on upload", "Raise client_max_body_size")
+            .build()
+            .unwrap();
+        memory.code_links.push(CodeLink::new(
+            "old_node".to_string(),
+            LinkedNodeType::Function,
+        ));
+
+        let params: MemoryUpdateParams = serde_json::from_value(serde_json::json!({
+            "id": memory.id.to_string(),
+            "content": "Uploads over 10MB fail",
+            "confidence": 0.5,
+            "addCodeLinks": [{"nodeId": "new_node", "nodeType": "class"}],
+            "removeCodeLinks": ["old_node"]
+        }))
+        .unwrap();
+        params.apply_to(&mut memory);
+
+        assert_eq!(memory.title, "Upload limit");
+        assert_eq!(memory.content, "Uploads over 10MB fail");
+        assert_eq!(memory.tags, vec!["nginx".to_string()]);
+        assert_eq!(memory.confidence, 0.5);
+        assert_eq!(memory.code_links.len(), 1);
+        assert_eq!(memory.code_links[0].node_id, "new_node");
+        assert!(matches!(
+            memory.code_links[0].node_type,
+            LinkedNodeType::Class
+        ));
+        assert!(memory.embedding.is_none());
+    }
+
     #[test]
     fn test_memory_context_params() {
         let json = r#"{
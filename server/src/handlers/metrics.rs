@@ -4,9 +4,12 @@ use crate::backend::CodeGraphBackend;
 use crate::handlers::ai_context::LocationInfo;
 use codegraph::{CodeGraph, NodeId, NodeType};
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use tower_lsp::jsonrpc::Result;
-use tower_lsp::lsp_types::Url;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Position, Range, Url};
+
+/// Diagnostic code of architecture layer violations.
+pub const LAYER_VIOLATION_CODE: &str = "layer-violation";
 
 // Re-export domain complexity types and functions so existing call sites are unaffected.
 pub(crate) use crate::domain::complexity::{analyze_file_complexity, ComplexityDetails};
@@ -104,6 +107,10 @@ pub struct UnusedSummary {
     pub by_type: UnusedByType,
     pub safe_deletions: u32,
     pub estimated_lines_removable: u32,
+    /// Entry points liveness was computed from (absent when none were detected
+    /// and only direct callers were checked)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_points: Option<u32>,
 }
 
 #[derive(Debug, Serialize)]
@@ -173,6 +180,51 @@ pub struct ArchViolation {
     pub suggestion: String,
 }
 
+// ==========================================
+// Architecture Rule Types
+// ==========================================
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckArchitectureParams {
+    /// Inline layer config; defaults to `<workspace>/.codegraph/architecture.json`
+    #[serde(default)]
+    pub config: Option<serde_json::Value>,
+    /// Publish violations as warnings on the offending files
+    #[serde(default)]
+    pub publish_diagnostics: Option<bool>,
+    /// Maximum number of violations returned (default: 200)
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckArchitectureResponse {
+    pub violations: Vec<LayerViolationInfo>,
+    pub total_violations: usize,
+    pub edges_checked: usize,
+    /// Layer name -> number of files assigned to it
+    pub layer_files: BTreeMap<String, usize>,
+    /// Config file the rules were read from (absent for inline config)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config_path: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerViolationInfo {
+    pub from_layer: String,
+    pub to_layer: String,
+    pub source_symbol: String,
+    pub target_symbol: String,
+    pub file: String,
+    pub line: u32,
+    pub target_file: String,
+    /// Dependency edge type, e.g. "Calls" or "Imports"
+    pub edge_type: String,
+}
+
 // ==========================================
 // LSP Handlers
 // ==========================================
@@ -277,7 +329,9 @@ impl CodeGraphBackend {
                 _ => "type",
             };
 
-            let reason = if candidate.is_public {
+            let reason = if candidate.has_callers {
+                "Only used by code that is unreachable from any entry point"
+            } else if candidate.is_public {
                 "Exported but no internal callers or importers found"
             } else {
                 "No callers or importers found in codebase"
@@ -329,6 +383,7 @@ impl CodeGraphBackend {
                 },
                 safe_deletions,
                 estimated_lines_removable: total_lines,
+                entry_points: result.entry_points.map(|n| n as u32),
             },
         })
     }
@@ -380,6 +435,130 @@ impl CodeGraphBackend {
             recommendations: result.recommendations,
         })
     }
+
+    /// Check graph dependencies against the workspace layering rules.
+    pub async fn handle_check_architecture(
+        &self,
+        params: CheckArchitectureParams,
+    ) -> Result<CheckArchitectureResponse> {
+        use crate::domain::architecture;
+
+        let workspace = self
+            .workspace_folders
+            .read()
+            .await
+            .first()
+            .cloned()
+            .unwrap_or_default();
+
+        let (config, config_path) = match params.config {
+            Some(value) => {
+                let config: architecture::ArchitectureConfig = serde_json::from_value(value)
+                    .map_err(|e| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!("Invalid config: {e}"))
+                    })?;
+                (config, None)
+            }
+            None if workspace.as_os_str().is_empty() => {
+                return Err(tower_lsp::jsonrpc::Error::invalid_params(
+                    "No workspace folder to read architecture rules from; pass a config",
+                ));
+            }
+            None => {
+                let path = architecture::config_path(&workspace);
+                let config = architecture::load_config(&workspace)
+                    .map_err(tower_lsp::jsonrpc::Error::invalid_params)?
+                    .ok_or_else(|| {
+                        tower_lsp::jsonrpc::Error::invalid_params(format!(
+                            "No architecture rules: create {} or pass a config",
+                            path.display()
+                        ))
+                    })?;
+                (config, Some(path.to_string_lossy().to_string()))
+            }
+        };
+        let rules = architecture::ArchitectureRules::from_config(&config)
+            .map_err(tower_lsp::jsonrpc::Error::invalid_params)?;
+
+        let result = {
+            let graph = self.graph.read().await;
+            architecture::check_architecture(&graph, &rules, &workspace)
+        };
+
+        if params.publish_diagnostics.unwrap_or(false) {
+            self.publish_architecture_diagnostics(&result.violations)
+                .await;
+        }
+
+        let total_violations = result.violations.len();
+        let violations = result
+            .violations
+            .into_iter()
+            .take(params.limit.unwrap_or(200))
+            .map(|v| LayerViolationInfo {
+                from_layer: v.from_layer,
+                to_layer: v.to_layer,
+                source_symbol: v.source_name,
+                target_symbol: v.target_name,
+                file: v.source_file,
+                line: v.line,
+                target_file: v.target_file,
+                edge_type: format!("{:?}", v.edge_type),
+            })
+            .collect();
+
+        Ok(CheckArchitectureResponse {
+            violations,
+            total_violations,
+            edges_checked: result.edges_checked,
+            layer_files: result.layer_files,
+            config_path,
+        })
+    }
+
+    /// Publish layer violations as warnings, clearing files that no longer have any.
+    async fn publish_architecture_diagnostics(
+        &self,
+        violations: &[crate::domain::architecture::LayerViolation],
+    ) {
+        let mut by_file: HashMap<Url, Vec<Diagnostic>> = HashMap::new();
+        for v in violations {
+            let Ok(uri) = Url::from_file_path(&v.source_file) else {
+                continue;
+            };
+            let line = v.line.saturating_sub(1);
+            by_file.entry(uri).or_default().push(Diagnostic {
+                range: Range::new(Position::new(line, 0), Position::new(line, u32::MAX)),
+                severity: Some(DiagnosticSeverity::WARNING),
+                code: Some(NumberOrString::String(LAYER_VIOLATION_CODE.to_string())),
+                source: Some("codegraph".to_string()),
+                message: format!(
+                    "Layer '{}' may not depend on '{}': {} -> {} ({:?})",
+                    v.from_layer, v.to_layer, v.source_name, v.target_name, v.edge_type
+                ),
+                ..Default::default()
+            });
+        }
+
+        let stale: Vec<Url> = self
+            .diagnostics
+            .lock()
+            .await
+            .iter()
+            .filter(|(uri, sets)| {
+                sets.contains_key(LAYER_VIOLATION_CODE) && !by_file.contains_key(*uri)
+            })
+            .map(|(uri, _)| uri.clone())
+            .collect();
+        for uri in stale {
+            self.publish_diagnostic_set(uri, LAYER_VIOLATION_CODE, Vec::new())
+                .await;
+        }
+        for (uri, diagnostics) in by_file {
+            self.publish_diagnostic_set(uri, LAYER_VIOLATION_CODE, diagnostics)
+                .await;
+        }
+    }
 }
 
 #[cfg(test)]
@@ -649,6 +828,80 @@ mod tests {
             );
         }
 
+        #[tokio::test]
+        async fn test_co_change_edge_does_not_count_as_usage() {
+            // A co-change edge records history, not a reference in code
+            let graph = Arc::new(RwLock::new(
+                CodeGraph::in_memory().expect("Failed to create graph"),
+            ));
+
+            let helper_id = {
+                let mut g = graph.write().await;
+
+                let mut helper_props = PropertyMap::new();
+                helper_props.insert(
+                    "name".to_string(),
+                    PropertyValue::String("staleHelper".to_string()),
+                );
+                helper_props.insert(
+                    "path".to_string(),
+                    PropertyValue::String("/src/helpers.ts".to_string()),
+                );
+                helper_props.insert("line_start".to_string(), PropertyValue::Int(5));
+                helper_props.insert("line_end".to_string(), PropertyValue::Int(15));
+                let helper_id = g.add_node(NodeType::Function, helper_props).unwrap();
+
+                let mut other_props = PropertyMap::new();
+                other_props.insert(
+                    "name".to_string(),
+                    PropertyValue::String("render".to_string()),
+                );
+                other_props.insert(
+                    "path".to_string(),
+                    PropertyValue::String("/src/view.ts".to_string()),
+                );
+                let other_id = g.add_node(NodeType::Function, other_props).unwrap();
+
+                let mut edge_props = PropertyMap::new();
+                edge_props.insert(
+                    "kind".to_string(),
+                    PropertyValue::String("co_change".to_string()),
+                );
+                g.add_edge(other_id, helper_id, EdgeType::References, edge_props)
+                    .unwrap();
+                helper_id
+            };
+
+            let backend = create_test_backend_with_graph(graph.clone());
+            backend.symbol_index.add_node_for_test(
+                std::path::PathBuf::from("/src/helpers.ts"),
+                helper_id,
+                "staleHelper",
+                "Function",
+                5,
+                15,
+            );
+
+            let params = UnusedCodeParams {
+                uri: Some("file:///src/helpers.ts".to_string()),
+                scope: "file".to_string(),
+                include_tests: Some(false),
+                confidence: Some(0.0),
+            };
+
+            let result = backend.handle_find_unused_code(params).await.unwrap();
+
+            let unused_names: Vec<&str> = result
+                .unused_items
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect();
+            assert!(
+                unused_names.contains(&"staleHelper"),
+                "Co-change edges should not keep a function in use. Found: {unused_names:?}"
+            );
+        }
+
         #[tokio::test]
         async fn test_function_with_both_call_and_import_is_not_unused() {
             // Create a graph where a function is both called and imported
@@ -728,5 +981,137 @@ mod tests {
                 "Function with both call and import edges should not be unused"
             );
         }
+
+        #[tokio::test]
+        async fn test_unused_code_is_reachability_from_entry_points() {
+            // main -> loadConfig -> parseLine is live; orphan -> orphanHelper is not
+            let graph = Arc::new(RwLock::new(
+                CodeGraph::in_memory().expect("Failed to create graph"),
+            ));
+
+            let backend = create_test_backend_with_graph(graph.clone());
+            {
+                let mut g = graph.write().await;
+                let mut ids = std::collections::HashMap::new();
+                for (i, name) in ["main", "loadConfig", "parseLine", "orphan", "orphanHelper"]
+                    .into_iter()
+                    .enumerate()
+                {
+                    let line = (i as i64) * 10 + 1;
+                    let mut props = PropertyMap::new();
+                    props.insert("name".to_string(), PropertyValue::String(name.to_string()));
+                    props.insert(
+                        "path".to_string(),
+                        PropertyValue::String("/src/app.ts".to_string()),
+                    );
+                    props.insert("line_start".to_string(), PropertyValue::Int(line));
+                    props.insert("line_end".to_string(), PropertyValue::Int(line + 5));
+                    let id = g.add_node(NodeType::Function, props).unwrap();
+                    backend.symbol_index.add_node_for_test(
+                        std::path::PathBuf::from("/src/app.ts"),
+                        id,
+                        name,
+                        "Function",
+                        line as u32,
+                        line as u32 + 5,
+                    );
+                    ids.insert(name, id);
+                }
+                for (from, to) in [
+                    ("main", "loadConfig"),
+                    ("loadConfig", "parseLine"),
+                    ("orphan", "orphanHelper"),
+                ] {
+                    g.add_edge(ids[from], ids[to], EdgeType::Calls, PropertyMap::new())
+                        .unwrap();
+                }
+            }
+
+            let params = UnusedCodeParams {
+                uri: Some("file:///src/app.ts".to_string()),
+                scope: "file".to_string(),
+                include_tests: Some(false),
+                confidence: Some(0.0),
+            };
+            let result = backend.handle_find_unused_code(params).await.unwrap();
+
+            let mut unused_names: Vec<&str> = result
+                .unused_items
+                .iter()
+                .map(|item| item.name.as_str())
+                .collect();
+            unused_names.sort();
+            assert_eq!(unused_names, vec!["orphan", "orphanHelper"]);
+            assert_eq!(result.summary.entry_points, Some(1));
+
+            let helper = result
+                .unused_items
+                .iter()
+                .find(|item| item.name == "orphanHelper")
+                .unwrap();
+            assert_eq!(
+                helper.reason,
+                "Only used by code that is unreachable from any entry point"
+            );
+        }
+    }
+
+    // ==========================================
+    // Architecture Rule Tests
+    // ==========================================
+
+    mod architecture_tests {
+        use super::*;
+        use crate::ai_query::QueryEngine;
+        use codegraph::{EdgeType, PropertyMap, PropertyValue};
+        use std::sync::Arc;
+        use tokio::sync::RwLock;
+
+        #[tokio::test]
+        async fn test_check_architecture_with_inline_config() {
+            let graph = Arc::new(RwLock::new(
+                CodeGraph::in_memory().expect("Failed to create graph"),
+            ));
+            {
+                let mut g = graph.write().await;
+                let mut ids = Vec::new();
+                for (name, path) in [("render", "/ws/ui/page.ts"), ("query", "/ws/data/db.ts")] {
+                    let mut props = PropertyMap::new();
+                    props.insert("name".to_string(), PropertyValue::String(name.to_string()));
+                    props.insert("path".to_string(), PropertyValue::String(path.to_string()));
+                    ids.push(g.add_node(NodeType::Function, props).unwrap());
+                }
+                g.add_edge(ids[1], ids[0], EdgeType::Calls, PropertyMap::new())
+                    .unwrap();
+            }
+            let query_engine = Arc::new(QueryEngine::new(Arc::clone(&graph)));
+            let backend = CodeGraphBackend::new_for_test(graph, query_engine);
+
+            // No workspace config and none passed
+            let params = CheckArchitectureParams {
+                config: None,
+                publish_diagnostics: None,
+                limit: None,
+            };
+            assert!(backend.handle_check_architecture(params).await.is_err());
+
+            let params = CheckArchitectureParams {
+                config: Some(serde_json::json!({
+                    "layers": [
+                        { "name": "ui", "patterns": ["**/ui/**"] },
+                        { "name": "data", "patterns": ["**/data/**"] }
+                    ],
+                    "rules": ["ui -> data"]
+                })),
+                publish_diagnostics: None,
+                limit: None,
+            };
+            let result = backend.handle_check_architecture(params).await.unwrap();
+            assert_eq!(result.total_violations, 1);
+            assert_eq!(result.violations[0].from_layer, "data");
+            assert_eq!(result.violations[0].to_layer, "ui");
+            assert_eq!(result.violations[0].edge_type, "Calls");
+            assert!(result.config_path.is_none());
+        }
     }
 }
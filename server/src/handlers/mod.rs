@@ -2,14 +2,30 @@
 
 pub mod ai_context;
 pub mod ai_query;
+pub mod call_hierarchy;
+pub mod code_actions;
+pub mod code_lens;
+pub mod completion;
 pub mod custom;
+pub mod document_links;
+pub mod folding_ranges;
+pub mod inlay_hints;
 pub mod memory;
 pub mod metrics;
 pub mod navigation;
+pub mod stale_memories;
 
 pub use ai_context::*;
 pub use ai_query::*;
+pub use call_hierarchy::*;
+pub use code_actions::*;
+pub use code_lens::*;
+pub use completion::*;
 pub use custom::*;
+pub use document_links::*;
+pub use folding_ranges::*;
+pub use inlay_hints::*;
 pub use memory::*;
 pub use metrics::*;
 pub use navigation::*;
+pub use stale_memories::*;
@@ -1,16 +1,18 @@
 //! Navigation-related helper functions.
 
 use crate::backend::CodeGraphBackend;
+use crate::domain::monikers::{self, MONIKER_SCHEME};
 use crate::domain::node_props;
 use codegraph::NodeId;
 use serde::{Deserialize, Serialize};
 use tower_lsp::jsonrpc::Result;
-use tower_lsp::lsp_types::{Range, Url};
+use tower_lsp::lsp_types::{Moniker, MonikerKind, MonikerParams, Range, UniquenessLevel, Url};
 
 /// Request to get a node's location by ID.
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GetNodeLocationParams {
+    /// A node ID or a symbol moniker
     pub node_id: String,
 }
 
@@ -23,18 +25,19 @@ pub struct NodeLocationResponse {
 }
 
 impl CodeGraphBackend {
-    /// Get the location of a node by its ID.
+    /// Get the location of a node by its ID or moniker.
     pub async fn handle_get_node_location(
         &self,
         params: GetNodeLocationParams,
     ) -> Result<Option<NodeLocationResponse>> {
-        let node_id: NodeId = params
-            .node_id
-            .parse()
-            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid node ID"))?;
-
         let graph = self.graph.read().await;
 
+        let node_id = match params.node_id.parse::<NodeId>() {
+            Ok(node_id) => node_id,
+            Err(_) => monikers::resolve_node_ref(&graph, &params.node_id).ok_or_else(|| {
+                tower_lsp::jsonrpc::Error::invalid_params("Invalid node ID or moniker")
+            })?,
+        };
         let node = match graph.get_node(node_id) {
             Ok(n) => n,
             Err(_) => return Ok(None),
@@ -70,6 +73,41 @@ impl CodeGraphBackend {
             },
         }))
     }
+
+    /// Moniker of the symbol at a position, stable across reindexes.
+    pub async fn handle_moniker(&self, params: MonikerParams) -> Result<Option<Vec<Moniker>>> {
+        let position = params.text_document_position_params;
+        let path = position
+            .text_document
+            .uri
+            .to_file_path()
+            .map_err(|_| tower_lsp::jsonrpc::Error::invalid_params("Invalid URI"))?;
+
+        let graph = self.graph.read().await;
+        let Some(node) = self
+            .find_node_at_position(&graph, &path, position.position)
+            .ok()
+            .flatten()
+            .and_then(|node_id| graph.get_node(node_id).ok())
+        else {
+            return Ok(None);
+        };
+        let Some(identifier) = node.properties.get_string("moniker") else {
+            return Ok(None);
+        };
+
+        let kind = if node_props::is_public(node) {
+            MonikerKind::Export
+        } else {
+            MonikerKind::Local
+        };
+        Ok(Some(vec![Moniker {
+            scheme: MONIKER_SCHEME.to_string(),
+            identifier: identifier.to_string(),
+            unique: UniquenessLevel::Project,
+            kind: Some(kind),
+        }]))
+    }
 }
 
 /// Request for workspace symbols.
@@ -0,0 +1,414 @@
+//! Hints on code whose linked memories were auto-invalidated.
+//!
+//! When a file is re-parsed, memories linked to its old nodes are invalidated
+//! automatically. Each one is remembered with the symbol it was linked to and
+//! published as a hint diagnostic on that symbol in the new parse, so the
+//! reader notices that what the memory says may no longer hold. The hint
+//! offers code actions opening the review queue and marking the memory as
+//! still valid, which relinks it to the symbol's current moniker.
+
+use crate::backend::CodeGraphBackend;
+use crate::domain::{monikers, node_props};
+use crate::handlers::{MemoryRevalidateParams, MemoryRevalidateResponse};
+use crate::memory::CodeLink;
+use codegraph::{CodeGraph, Node, NodeId};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tower_lsp::jsonrpc::{Error, Result};
+use tower_lsp::lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, Command, Diagnostic, DiagnosticSeverity,
+    NumberOrString, Range, Url,
+};
+
+/// Diagnostic code of stale memory hints.
+pub const STALE_MEMORY_CODE: &str = "stale-memory";
+
+/// A memory auto-invalidated by a change to the symbol it was linked to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleMemory {
+    pub memory_id: String,
+    pub title: String,
+    /// The linked symbol: its moniker without the signature hash, or its name.
+    pub symbol: String,
+    /// Where the symbol was before the change.
+    pub range: Range,
+}
+
+/// Name a symbol is found under again after a reparse. The signature hash is
+/// dropped from the moniker, as the change may well have touched the signature.
+fn anchor_name(node: &Node) -> String {
+    match node.properties.get_string("moniker") {
+        Some(moniker) => moniker.split('~').next().unwrap_or(moniker).to_string(),
+        None => node_props::name(node).to_string(),
+    }
+}
+
+/// Move `links` to the current moniker of their symbol: the node they still
+/// resolve to or, for a link whose node is gone, the flagged node found under
+/// the same name. Links matching neither are left as they are. Returns the
+/// number of links moved.
+fn relink(graph: &CodeGraph, links: &mut [CodeLink], flagged_nodes: &[NodeId]) -> usize {
+    let mut relinked = 0;
+    for link in links {
+        let anchor = link.node_id.split('~').next().unwrap_or(&link.node_id);
+        let current = monikers::resolve_node_ref(graph, &link.node_id).or_else(|| {
+            flagged_nodes
+                .iter()
+                .copied()
+                .find(|&id| graph.get_node(id).is_ok_and(|n| anchor_name(n) == anchor))
+        });
+        let Some(node_id) = current else {
+            continue;
+        };
+        let Ok(node) = graph.get_node(node_id) else {
+            continue;
+        };
+        if let Some(key) = monikers::link_keys(node_id, node).pop() {
+            if key != link.node_id {
+                link.node_id = key;
+                relinked += 1;
+            }
+        }
+    }
+    relinked
+}
+
+/// Hint diagnostic for a stale memory at `range`.
+pub fn stale_memory_diagnostic(memory: &StaleMemory, range: Range) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::HINT),
+        code: Some(NumberOrString::String(STALE_MEMORY_CODE.to_string())),
+        source: Some("codegraph".to_string()),
+        message: format!("Memory '{}' may be stale — code changed", memory.title),
+        data: Some(serde_json::json!({
+            "memoryId": memory.memory_id,
+            "title": memory.title,
+        })),
+        ..Default::default()
+    }
+}
+
+/// Quick fix running the client command `command` with a stale memory
+/// hint's data.
+fn hint_action(title: String, command: &str, diagnostic: &Diagnostic) -> CodeActionOrCommand {
+    CodeActionOrCommand::CodeAction(CodeAction {
+        title: title.clone(),
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        command: Some(Command {
+            title,
+            command: command.to_string(),
+            arguments: diagnostic.data.clone().map(|data| vec![data]),
+        }),
+        ..Default::default()
+    })
+}
+
+/// "Mark memory as still valid" and "Review stale memories" quick fixes for
+/// the stale memory hints among `diagnostics`.
+pub fn stale_memory_actions(diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    diagnostics
+        .iter()
+        .filter(|d| {
+            d.code == Some(NumberOrString::String(STALE_MEMORY_CODE.to_string()))
+                && d.source.as_deref() == Some("codegraph")
+        })
+        .flat_map(|diagnostic| {
+            let title = diagnostic
+                .data
+                .as_ref()
+                .and_then(|data| data["title"].as_str())
+                .unwrap_or_default();
+            [
+                hint_action(
+                    format!("Mark memory '{title}' as still valid"),
+                    "codegraph.revalidateMemory",
+                    diagnostic,
+                ),
+                hint_action(
+                    "Review stale memories".to_string(),
+                    "codegraph.reviewStaleMemories",
+                    diagnostic,
+                ),
+            ]
+        })
+        .collect()
+}
+
+impl CodeGraphBackend {
+    /// Symbols of an indexed file by memory link key (NodeId and moniker),
+    /// with the name and range they can be found under after a reparse.
+    pub(crate) async fn memory_link_anchors(
+        &self,
+        path: &Path,
+    ) -> HashMap<String, (String, Range)> {
+        let graph = self.graph.read().await;
+        let mut anchors = HashMap::new();
+        for node_id in self.symbol_index.get_file_symbols(path) {
+            let Ok(node) = graph.get_node(node_id) else {
+                continue;
+            };
+            let Some(range) = Self::node_to_range(node) else {
+                continue;
+            };
+            let name = anchor_name(node);
+            for key in monikers::link_keys(node_id, node) {
+                anchors.insert(key, (name.clone(), range));
+            }
+        }
+        anchors
+    }
+
+    /// Remember the memories just invalidated by a change to `path`, anchored
+    /// to the symbols they were linked to.
+    pub(crate) async fn record_stale_memories(
+        &self,
+        path: &Path,
+        anchors: &HashMap<String, (String, Range)>,
+        invalidated: Vec<(String, String)>,
+    ) {
+        let mut stale = Vec::new();
+        for (memory_id, title) in invalidated {
+            let Ok(Some(memory)) = self.memory_manager.get(&memory_id).await else {
+                continue;
+            };
+            let anchor = memory
+                .code_links
+                .iter()
+                .find_map(|link| anchors.get(&link.node_id));
+            if let Some((symbol, range)) = anchor {
+                stale.push(StaleMemory {
+                    memory_id,
+                    title,
+                    symbol: symbol.clone(),
+                    range: *range,
+                });
+            }
+        }
+        if !stale.is_empty() {
+            self.stale_memories
+                .lock()
+                .await
+                .entry(path.to_path_buf())
+                .or_default()
+                .extend(stale);
+        }
+    }
+
+    /// Publish the stale memory hints of `path` on the current parse. Memories
+    /// reviewed since (valid again or deleted) are dropped; a symbol no longer
+    /// found keeps the range it had.
+    pub(crate) async fn publish_stale_memory_diagnostics(&self, path: &Path) {
+        let Ok(uri) = Url::from_file_path(path) else {
+            return;
+        };
+        let recorded = self
+            .stale_memories
+            .lock()
+            .await
+            .get(path)
+            .cloned()
+            .unwrap_or_default();
+        if recorded.is_empty() {
+            return;
+        }
+
+        let mut stale = Vec::new();
+        for memory in recorded {
+            let still_stale = matches!(
+                self.memory_manager.get(&memory.memory_id).await,
+                Ok(Some(node)) if !node.temporal.is_current()
+            );
+            if still_stale {
+                stale.push(memory);
+            }
+        }
+
+        let ranges: HashMap<String, Range> = {
+            let graph = self.graph.read().await;
+            self.symbol_index
+                .get_file_symbols(path)
+                .into_iter()
+                .filter_map(|node_id| {
+                    let node = graph.get_node(node_id).ok()?;
+                    Some((anchor_name(node), Self::node_to_range(node)?))
+                })
+                .collect()
+        };
+        let diagnostics = stale
+            .iter()
+            .map(|memory| {
+                let range = ranges.get(&memory.symbol).copied().unwrap_or(memory.range);
+                stale_memory_diagnostic(memory, range)
+            })
+            .collect();
+
+        {
+            let mut recorded = self.stale_memories.lock().await;
+            if stale.is_empty() {
+                recorded.remove(path);
+            } else {
+                recorded.insert(path.to_path_buf(), stale);
+            }
+        }
+        self.publish_diagnostic_set(uri, STALE_MEMORY_CODE, diagnostics)
+            .await;
+    }
+
+    /// Mark a flagged memory as still valid against the current code: links
+    /// are moved to the current moniker (and so signature hash) of their
+    /// symbol, the memory is made current again and its hints are cleared.
+    pub async fn handle_memory_revalidate(
+        &self,
+        params: MemoryRevalidateParams,
+    ) -> Result<MemoryRevalidateResponse> {
+        let Some(mut memory) = self
+            .memory_manager
+            .get(&params.id)
+            .await
+            .map_err(|_| Error::internal_error())?
+        else {
+            return Ok(MemoryRevalidateResponse {
+                success: false,
+                relinked: 0,
+            });
+        };
+
+        // Symbols the memory was flagged on, for links whose node is gone
+        let flagged: Vec<(PathBuf, String)> = self
+            .stale_memories
+            .lock()
+            .await
+            .iter()
+            .flat_map(|(path, stale)| {
+                stale
+                    .iter()
+                    .filter(|m| m.memory_id == params.id)
+                    .map(|m| (path.clone(), m.symbol.clone()))
+            })
+            .collect();
+
+        let relinked = {
+            let graph = self.graph.read().await;
+            let flagged_nodes: Vec<NodeId> = flagged
+                .iter()
+                .filter_map(|(path, symbol)| {
+                    self.symbol_index
+                        .get_file_symbols(path)
+                        .into_iter()
+                        .find(|&id| graph.get_node(id).is_ok_and(|n| anchor_name(n) == *symbol))
+                })
+                .collect();
+            relink(&graph, &mut memory.code_links, &flagged_nodes)
+        };
+
+        memory.temporal.invalid_at = None;
+        self.memory_manager
+            .put(memory)
+            .await
+            .map_err(|_| Error::internal_error())?;
+
+        // Republishing drops the now current memory from the hints
+        let mut files: Vec<PathBuf> = flagged.into_iter().map(|(path, _)| path).collect();
+        files.sort();
+        files.dedup();
+        for path in files {
+            self.publish_stale_memory_diagnostics(&path).await;
+        }
+
+        Ok(MemoryRevalidateResponse {
+            success: true,
+            relinked,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tower_lsp::lsp_types::Position;
+
+    #[test]
+    fn test_stale_memory_diagnostic_and_actions() {
+        let memory = StaleMemory {
+            memory_id: "m1".to_string(),
+            title: "Nginx body size fix".to_string(),
+            symbol: "src/upload.ts#handleUpload".to_string(),
+            range: Range::new(Position::new(3, 0), Position::new(9, 1)),
+        };
+        let moved = Range::new(Position::new(5, 0), Position::new(11, 1));
+        let diagnostic = stale_memory_diagnostic(&memory, moved);
+        assert_eq!(diagnostic.range, moved);
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(
+            diagnostic.message,
+            "Memory 'Nginx body size fix' may be stale — code changed"
+        );
+
+        let unrelated = Diagnostic {
+            code: Some(NumberOrString::String("layer-violation".to_string())),
+            source: Some("codegraph".to_string()),
+            ..Default::default()
+        };
+        let actions = stale_memory_actions(&[unrelated, diagnostic.clone()]);
+        let commands: Vec<(&str, &str)> = actions
+            .iter()
+            .map(|action| {
+                let CodeActionOrCommand::CodeAction(action) = action else {
+                    panic!("expected a code action");
+                };
+                assert_eq!(action.diagnostics, Some(vec![diagnostic.clone()]));
+                let command = action.command.as_ref().unwrap();
+                assert_eq!(command.arguments.as_ref().unwrap()[0]["memoryId"], "m1");
+                (action.title.as_str(), command.command.as_str())
+            })
+            .collect();
+        assert_eq!(
+            commands,
+            vec![
+                (
+                    "Mark memory 'Nginx body size fix' as still valid",
+                    "codegraph.revalidateMemory"
+                ),
+                ("Review stale memories", "codegraph.reviewStaleMemories"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_relink_matches_links_to_their_own_symbol() {
+        use crate::memory::LinkedNodeType;
+        use codegraph::{NodeType, PropertyMap};
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let upload = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new().with("moniker", "src/upload.ts#handleUpload~5e1d"),
+            )
+            .unwrap();
+        let render = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new().with("moniker", "src/upload.ts#render~77aa"),
+            )
+            .unwrap();
+
+        let link = |node_id: &str| CodeLink::new(node_id.to_string(), LinkedNodeType::Function);
+        let mut links = vec![
+            // Gone: its signature changed
+            link("src/upload.ts#handleUpload~0b3c"),
+            // Gone, and not among the flagged symbols
+            link("src/upload.ts#removed~1f00"),
+            // Still there, by NodeId
+            link(&render.to_string()),
+        ];
+
+        let relinked = relink(&graph, &mut links, &[render, upload]);
+        assert_eq!(relinked, 2);
+        assert_eq!(links[0].node_id, "src/upload.ts#handleUpload~5e1d");
+        assert_eq!(links[1].node_id, "src/upload.ts#removed~1f00");
+        assert_eq!(links[2].node_id, "src/upload.ts#render~77aa");
+    }
+}
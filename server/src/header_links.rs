@@ -0,0 +1,371 @@
+//! Header/implementation linking for C and C++.
+//!
+//! Parsers see one file at a time, so an `#include` is only an Import node
+//! named after the included path, and a prototype in a header is unrelated to
+//! the function defined in a `.c`/`.cpp` file. After cross-file resolution:
+//! 1. Each `#include` that names an indexed header gets an `Imports` edge from
+//!    the including file to the header's file node, so dependency graphs and
+//!    file coupling follow includes.
+//! 2. Each function defined in an implementation file gets an `Implements`
+//!    edge to its prototype in a header, so impact analysis of a declaration
+//!    reaches the definition.
+//!
+//! Both passes are idempotent.
+
+use crate::domain::node_props;
+use codegraph::{CodeGraph, Direction, EdgeType, NodeId, NodeType, PropertyMap};
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// Extensions of C and C++ source and header files.
+const C_FAMILY_EXTENSIONS: &[&str] = &["c", "h", "cc", "cpp", "cxx", "hh", "hpp", "hxx"];
+
+/// Number of edges added by [`link_headers`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct HeaderLinkStats {
+    /// `Imports` edges from including files to header files
+    pub includes: usize,
+    /// `Implements` edges from definitions to header prototypes
+    pub definitions: usize,
+}
+
+/// Whether `path` is a C/C++ header, where prototypes are declared.
+pub(crate) fn is_header_path(path: &str) -> bool {
+    matches!(
+        Path::new(path).extension().and_then(|e| e.to_str()),
+        Some("h" | "hh" | "hpp" | "hxx")
+    )
+}
+
+/// Whether `path` is a C/C++ source or header file.
+pub(crate) fn is_c_family_path(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|ext| C_FAMILY_EXTENSIONS.contains(&ext))
+}
+
+/// Resolve `#include`s to header files and link definitions to their header
+/// prototypes.
+pub fn link_headers(graph: &mut CodeGraph) -> HeaderLinkStats {
+    let files: HashMap<String, NodeId> = graph
+        .query()
+        .node_type(NodeType::CodeFile)
+        .execute()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| {
+            let path = node_props::path(graph.get_node(id).ok()?);
+            is_c_family_path(path).then(|| (path.to_string(), id))
+        })
+        .collect();
+    if files.is_empty() {
+        return HeaderLinkStats::default();
+    }
+
+    let includes = link_includes(graph, &files);
+    let definitions = link_definitions(graph, &files);
+    if includes > 0 || definitions > 0 {
+        tracing::info!(
+            "Linked {} #include(s) and {} header declaration(s)",
+            includes,
+            definitions
+        );
+    }
+    HeaderLinkStats {
+        includes,
+        definitions,
+    }
+}
+
+/// Add `Imports` edges from each C/C++ file to the headers it includes.
+fn link_includes(graph: &mut CodeGraph, files: &HashMap<String, NodeId>) -> usize {
+    let headers: Vec<&str> = files
+        .keys()
+        .map(String::as_str)
+        .filter(|path| is_header_path(path))
+        .collect();
+
+    let mut edges_to_add: Vec<(NodeId, NodeId, String)> = Vec::new();
+    for (path, &file_id) in files {
+        for target in graph
+            .get_neighbors(file_id, Direction::Outgoing)
+            .unwrap_or_default()
+        {
+            let Ok(import) = graph.get_node(target) else {
+                continue;
+            };
+            if import.node_type != NodeType::Import
+                || !has_edge(graph, file_id, target, EdgeType::Imports)
+            {
+                continue;
+            }
+            let spec = node_props::name(import).trim_matches(|c| matches!(c, '"' | '<' | '>'));
+            let Some(header) = resolve_include(path, spec, &headers) else {
+                continue;
+            };
+            let header_id = files[header];
+            if header_id != file_id && !has_edge(graph, file_id, header_id, EdgeType::Imports) {
+                edges_to_add.push((file_id, header_id, spec.to_string()));
+            }
+        }
+    }
+
+    let count = edges_to_add.len();
+    for (from, to, spec) in edges_to_add {
+        let props = PropertyMap::new()
+            .with("include", spec)
+            .with("resolved_by", "include_resolution");
+        let _ = graph.add_edge(from, to, EdgeType::Imports, props);
+    }
+    count
+}
+
+/// The indexed header `spec` refers to when included from `from`: relative
+/// to the including file first, as the preprocessor does for quoted includes,
+/// then any header whose path ends with `spec`, nearest to `from` first.
+fn resolve_include<'a>(from: &str, spec: &str, headers: &[&'a str]) -> Option<&'a str> {
+    if spec.is_empty() {
+        return None;
+    }
+    let relative = Path::new(from)
+        .parent()
+        .map(|dir| normalize(&dir.join(spec)));
+    if let Some(relative) = relative {
+        if let Some(header) = headers.iter().copied().find(|h| Path::new(h) == relative) {
+            return Some(header);
+        }
+    }
+
+    let suffix = Path::new(spec);
+    headers
+        .iter()
+        .copied()
+        .filter(|h| Path::new(h).ends_with(suffix))
+        .max_by_key(|h| (common_prefix_len(from, h), std::cmp::Reverse(*h)))
+}
+
+/// `path` with `.` and `..` components resolved lexically.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Number of leading path components `a` and `b` share.
+fn common_prefix_len(a: &str, b: &str) -> usize {
+    Path::new(a)
+        .components()
+        .zip(Path::new(b).components())
+        .take_while(|(x, y)| x == y)
+        .count()
+}
+
+/// Add `Implements` edges from C/C++ function definitions to the header
+/// prototypes of the same name. When several definitions share a name, the
+/// one in a file including the header, or else with the header's file stem
+/// (`parser.h` and `parser.c`), is linked.
+fn link_definitions(graph: &mut CodeGraph, files: &HashMap<String, NodeId>) -> usize {
+    let mut prototypes: HashMap<String, Vec<NodeId>> = HashMap::new();
+    let mut definitions: HashMap<String, Vec<NodeId>> = HashMap::new();
+    for id in graph
+        .query()
+        .node_type(NodeType::Function)
+        .execute()
+        .unwrap_or_default()
+    {
+        let Ok(node) = graph.get_node(id) else {
+            continue;
+        };
+        let path = node_props::path(node);
+        if !is_c_family_path(path) {
+            continue;
+        }
+        let by_name = if is_header_path(path) {
+            &mut prototypes
+        } else {
+            &mut definitions
+        };
+        by_name
+            .entry(node_props::name(node).to_string())
+            .or_default()
+            .push(id);
+    }
+
+    // Headers each file includes, by path
+    let includes: HashMap<&str, HashSet<NodeId>> = files
+        .iter()
+        .map(|(path, &file_id)| {
+            let headers = graph
+                .get_neighbors(file_id, Direction::Outgoing)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&h| has_edge(graph, file_id, h, EdgeType::Imports))
+                .collect();
+            (path.as_str(), headers)
+        })
+        .collect();
+
+    let mut edges_to_add: Vec<(NodeId, NodeId)> = Vec::new();
+    for (name, prototype_ids) in &prototypes {
+        let Some(candidates) = definitions.get(name) else {
+            continue;
+        };
+        for &prototype_id in prototype_ids {
+            let Ok(prototype) = graph.get_node(prototype_id) else {
+                continue;
+            };
+            let header_path = node_props::path(prototype);
+            let header_file = files.get(header_path).copied();
+            let header_stem = Path::new(header_path).file_stem();
+            let score = |&&definition: &&NodeId| {
+                let path = graph.get_node(definition).map_or("", node_props::path);
+                let includes_header = header_file
+                    .is_some_and(|h| includes.get(path).is_some_and(|set| set.contains(&h)));
+                let same_stem = Path::new(path).file_stem() == header_stem;
+                (includes_header, same_stem)
+            };
+            let definition = match candidates.as_slice() {
+                [only] => Some(*only),
+                _ => candidates
+                    .iter()
+                    .filter(|d| score(d) != (false, false))
+                    .max_by_key(|d| (score(d), std::cmp::Reverse(**d)))
+                    .copied(),
+            };
+            if let Some(definition) = definition {
+                if !has_edge(graph, definition, prototype_id, EdgeType::Implements) {
+                    edges_to_add.push((definition, prototype_id));
+                }
+            }
+        }
+    }
+
+    let count = edges_to_add.len();
+    for (definition, prototype) in edges_to_add {
+        let props = PropertyMap::new().with("relation", "definition");
+        let _ = graph.add_edge(definition, prototype, EdgeType::Implements, props);
+    }
+    count
+}
+
+fn has_edge(graph: &CodeGraph, from: NodeId, to: NodeId, edge_type: EdgeType) -> bool {
+    graph
+        .get_edges_between(from, to)
+        .unwrap_or_default()
+        .into_iter()
+        .any(|e| graph.get_edge(e).is_ok_and(|e| e.edge_type == edge_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codegraph::PropertyValue;
+
+    fn add_file(graph: &mut CodeGraph, path: &str) -> NodeId {
+        graph
+            .add_node(
+                NodeType::CodeFile,
+                PropertyMap::new().with("name", path).with("path", path),
+            )
+            .unwrap()
+    }
+
+    fn add_include(graph: &mut CodeGraph, file: NodeId, path: &str, spec: &str) {
+        let import = graph
+            .add_node(
+                NodeType::Import,
+                PropertyMap::new().with("name", spec).with("path", path),
+            )
+            .unwrap();
+        graph
+            .add_edge(file, import, EdgeType::Imports, PropertyMap::new())
+            .unwrap();
+    }
+
+    fn add_function(graph: &mut CodeGraph, name: &str, path: &str, line: i64) -> NodeId {
+        graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", name)
+                    .with("path", path)
+                    .with("line_start", PropertyValue::Int(line)),
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_include() {
+        let headers = [
+            "/ws/src/parser.h",
+            "/ws/include/util/strings.h",
+            "/ws/lib/a/config.h",
+            "/ws/src/config.h",
+        ];
+        assert_eq!(
+            resolve_include("/ws/src/main.c", "parser.h", &headers),
+            Some("/ws/src/parser.h")
+        );
+        assert_eq!(
+            resolve_include("/ws/src/net/http.c", "../parser.h", &headers),
+            Some("/ws/src/parser.h")
+        );
+        assert_eq!(
+            resolve_include("/ws/src/main.c", "util/strings.h", &headers),
+            Some("/ws/include/util/strings.h")
+        );
+        // Nearest of several matches
+        assert_eq!(
+            resolve_include("/ws/src/net/http.c", "config.h", &headers),
+            Some("/ws/src/config.h")
+        );
+        assert_eq!(resolve_include("/ws/src/main.c", "stdio.h", &headers), None);
+    }
+
+    #[test]
+    fn test_link_headers() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let main_c = add_file(&mut graph, "/ws/src/main.c");
+        let parser_c = add_file(&mut graph, "/ws/src/parser.c");
+        let parser_h = add_file(&mut graph, "/ws/include/parser.h");
+        add_include(&mut graph, main_c, "/ws/src/main.c", "<stdio.h>");
+        add_include(&mut graph, main_c, "/ws/src/main.c", "\"parser.h\"");
+        add_include(&mut graph, parser_c, "/ws/src/parser.c", "\"parser.h\"");
+
+        let prototype = add_function(&mut graph, "parse", "/ws/include/parser.h", 3);
+        let definition = add_function(&mut graph, "parse", "/ws/src/parser.c", 10);
+        // A static helper of the same name elsewhere
+        add_function(&mut graph, "parse", "/ws/tools/dump.c", 4);
+        let main = add_function(&mut graph, "main", "/ws/src/main.c", 5);
+
+        let stats = link_headers(&mut graph);
+        assert_eq!(
+            stats,
+            HeaderLinkStats {
+                includes: 2,
+                definitions: 1,
+            }
+        );
+        assert!(has_edge(&graph, main_c, parser_h, EdgeType::Imports));
+        assert!(has_edge(&graph, parser_c, parser_h, EdgeType::Imports));
+        assert!(has_edge(
+            &graph,
+            definition,
+            prototype,
+            EdgeType::Implements
+        ));
+        assert!(!has_edge(&graph, main, prototype, EdgeType::Implements));
+
+        // Re-running adds nothing
+        assert_eq!(link_headers(&mut graph), HeaderLinkStats::default());
+    }
+}
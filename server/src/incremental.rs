@@ -0,0 +1,384 @@
+//! Incremental graph updates for edited documents.
+//!
+//! Reparsing an open document used to delete all of its nodes and insert the
+//! new parse, so every symbol got a new id, lost its cross-file edges and had
+//! its linked memories invalidated on each pause in typing. Instead, the new
+//! parse is matched against the file's current nodes by kind, enclosing
+//! symbol, name and signature. Symbols outside the edited rows keep their ids
+//! and edges, and only have their positions refreshed. Symbols in the edited
+//! rows are replaced, and symbols that are gone are removed. Classes and
+//! other containers are replaced only when their header is edited, not when
+//! one of their members is.
+
+use crate::domain::node_props;
+use crate::index_pipeline::FileFragment;
+use codegraph::{CodeGraph, NodeId, NodeType, PropertyMap};
+use codegraph_parser_api::FileInfo;
+use std::collections::{HashMap, VecDeque};
+
+/// Outcome of applying a reparsed file to the graph.
+pub struct IncrementalUpdate {
+    /// File info with node ids mapped into the graph
+    pub file_info: FileInfo,
+    /// Nodes removed or replaced; memories linked to them are stale
+    pub removed: Vec<NodeId>,
+    /// Nodes kept in place
+    pub kept: usize,
+}
+
+/// Apply `fragment`, the new parse of the file at `path`, to the file's nodes
+/// in `graph`. `changed_rows` are the zero-based row ranges edited since the
+/// last parse, or whose syntax changed, in the new text.
+pub fn apply_fragment(
+    graph: &mut CodeGraph,
+    path: &str,
+    fragment: FileFragment,
+    changed_rows: &[(usize, usize)],
+) -> IncrementalUpdate {
+    // Current nodes of the file by key, in source order
+    let mut current: Vec<(NodeId, NodeType, &PropertyMap)> = graph
+        .query()
+        .property("path", path.to_string())
+        .execute()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| {
+            let node = graph.get_node(id).ok()?;
+            Some((id, node.node_type, &node.properties))
+        })
+        .collect();
+    current.sort_by_key(|&(id, _, props)| (node_props::line_start_from_props(props), id));
+    let keys = node_keys(&current.iter().map(|&(_, t, p)| (t, p)).collect::<Vec<_>>());
+    let mut by_key: HashMap<NodeKey, VecDeque<NodeId>> = HashMap::new();
+    for (&(id, _, _), key) in current.iter().zip(keys) {
+        by_key.entry(key).or_default().push_back(id);
+    }
+
+    let (mut nodes, edges, file_info) = fragment.into_parts();
+    nodes.sort_by_key(|(id, _, props)| (node_props::line_start_from_props(props), *id));
+    let kinds: Vec<(NodeType, &PropertyMap)> = nodes.iter().map(|(_, t, p)| (*t, p)).collect();
+    let keys = node_keys(&kinds);
+    let edited = edited_nodes(&kinds, changed_rows);
+
+    let mut ids: HashMap<NodeId, NodeId> = HashMap::new();
+    let mut removed = Vec::new();
+    let mut kept = 0;
+    for (((id, node_type, properties), key), edited) in nodes.into_iter().zip(keys).zip(edited) {
+        let existing = by_key.get_mut(&key).and_then(VecDeque::pop_front);
+        match existing {
+            Some(existing) if !edited => {
+                let _ = graph.update_node_properties(existing, properties);
+                ids.insert(id, existing);
+                kept += 1;
+                continue;
+            }
+            Some(existing) => {
+                let _ = graph.delete_node(existing);
+                removed.push(existing);
+            }
+            None => {}
+        }
+        if let Ok(new_id) = graph.add_node(node_type, properties) {
+            ids.insert(id, new_id);
+        }
+    }
+    for stale in by_key.into_values().flatten() {
+        let _ = graph.delete_node(stale);
+        removed.push(stale);
+    }
+
+    // Edges between kept nodes are still in place
+    for (source, target, edge_type, properties) in edges {
+        let (Some(&source), Some(&target)) = (ids.get(&source), ids.get(&target)) else {
+            continue;
+        };
+        let exists = graph
+            .get_edges_between(source, target)
+            .unwrap_or_default()
+            .into_iter()
+            .any(|eid| graph.get_edge(eid).is_ok_and(|e| e.edge_type == edge_type));
+        if !exists {
+            let _ = graph.add_edge(source, target, edge_type, properties);
+        }
+    }
+
+    IncrementalUpdate {
+        file_info: FileFragment::remap_file_info(file_info, &ids),
+        removed,
+        kept,
+    }
+}
+
+/// Identity of a node across parses of the same file: its kind, the name of
+/// the symbol it is nested in, its name and its signature, which tells
+/// overloads apart.
+type NodeKey = (String, String, String, String);
+
+/// Keys of `nodes`. A node is nested in the smallest class, interface, trait,
+/// module or function whose lines enclose its own.
+fn node_keys(nodes: &[(NodeType, &PropertyMap)]) -> Vec<NodeKey> {
+    let spans: Vec<Option<(u32, u32)>> = nodes.iter().map(|(_, props)| span(props)).collect();
+    nodes
+        .iter()
+        .zip(&spans)
+        .map(|(&(node_type, props), &span)| {
+            let container = span
+                .and_then(|span| enclosing(nodes, &spans, span))
+                .and_then(|outer| outer.get_string("name"))
+                .unwrap_or_default();
+            let property = |key| props.get_string(key).unwrap_or_default().to_string();
+            (
+                node_type.to_string(),
+                container.to_string(),
+                property("name"),
+                property("signature"),
+            )
+        })
+        .collect()
+}
+
+/// The smallest container among `nodes` whose lines enclose `span`.
+fn enclosing<'a>(
+    nodes: &[(NodeType, &'a PropertyMap)],
+    spans: &[Option<(u32, u32)>],
+    (start, end): (u32, u32),
+) -> Option<&'a PropertyMap> {
+    nodes
+        .iter()
+        .zip(spans)
+        .filter(|((kind, _), _)| {
+            matches!(
+                kind,
+                NodeType::Class
+                    | NodeType::Interface
+                    | NodeType::Trait
+                    | NodeType::Module
+                    | NodeType::Function
+            )
+        })
+        .filter_map(|(&(_, outer), &outer_span)| {
+            let (outer_start, outer_end) = outer_span?;
+            let encloses = outer_start <= start
+                && end <= outer_end
+                && (outer_start, outer_end) != (start, end);
+            encloses.then_some((outer_end - outer_start, outer))
+        })
+        .min_by_key(|&(len, _)| len)
+        .map(|(_, outer)| outer)
+}
+
+/// A node's first and last line (one-based).
+fn span(props: &PropertyMap) -> Option<(u32, u32)> {
+    let start = node_props::line_start_opt_from_props(props)?;
+    let end = node_props::line_end_opt_from_props(props).map_or(start, |end| end.max(start));
+    Some((start, end))
+}
+
+/// Which of `nodes` were edited, their lines overlapping the zero-based
+/// `rows`. A class, interface, trait or module counts as edited only when its
+/// header changed: its lines before its first member, or all of them if it
+/// has none. Editing a method body leaves the class in place.
+fn edited_nodes(nodes: &[(NodeType, &PropertyMap)], rows: &[(usize, usize)]) -> Vec<bool> {
+    let spans: Vec<Option<(u32, u32)>> = nodes.iter().map(|(_, props)| span(props)).collect();
+    nodes
+        .iter()
+        .zip(&spans)
+        .map(|(&(node_type, props), &own)| match node_type {
+            NodeType::CodeFile => false,
+            NodeType::Class | NodeType::Interface | NodeType::Trait | NodeType::Module => {
+                let Some((start, end)) = own else {
+                    return false;
+                };
+                let first_member = spans
+                    .iter()
+                    .flatten()
+                    .filter(|&&member| {
+                        start <= member.0 && member.1 <= end && member != (start, end)
+                    })
+                    .map(|&(member_start, _)| member_start)
+                    .min();
+                let header_end =
+                    first_member.map_or(end, |first| first.saturating_sub(1).max(start));
+                lines_in_rows((start, header_end), rows)
+            }
+            _ => in_rows(props, rows),
+        })
+        .collect()
+}
+
+/// Whether a node's lines (one-based) overlap any of the zero-based rows.
+fn in_rows(props: &PropertyMap, rows: &[(usize, usize)]) -> bool {
+    span(props).is_some_and(|lines| lines_in_rows(lines, rows))
+}
+
+/// Whether one-based lines `start..=end` overlap any of the zero-based rows.
+fn lines_in_rows((start, end): (u32, u32), rows: &[(usize, usize)]) -> bool {
+    let start = start.saturating_sub(1) as usize;
+    let end = (end.saturating_sub(1) as usize).max(start);
+    rows.iter()
+        .any(|&(first, last)| start <= last && first <= end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser_registry::ParserRegistry;
+    use codegraph::PropertyValue;
+    use std::path::Path;
+
+    const SOURCE: &str = "def first():\n    return 1\n\ndef second():\n    return 2\n";
+
+    fn parse(source: &str) -> FileFragment {
+        FileFragment::parse_source(&ParserRegistry::new(), Path::new("/ws/app.py"), source).unwrap()
+    }
+
+    fn function(graph: &CodeGraph, info: &FileInfo, name: &str) -> Option<NodeId> {
+        info.functions.iter().copied().find(|&id| {
+            graph
+                .get_node(id)
+                .is_ok_and(|n| node_props::name(n) == name)
+        })
+    }
+
+    fn methods(graph: &CodeGraph, name: &str) -> Vec<NodeId> {
+        let mut ids = graph
+            .query()
+            .property("name", name.to_string())
+            .execute()
+            .unwrap();
+        ids.sort_by_key(|&id| node_props::line_start(graph.get_node(id).unwrap()));
+        ids
+    }
+
+    #[test]
+    fn test_unedited_symbols_keep_their_ids() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let info = parse(SOURCE).insert(&mut graph);
+        let first = function(&graph, &info, "first").unwrap();
+        let second = function(&graph, &info, "second").unwrap();
+
+        // A line inserted above `second` edits row 3 and shifts it down
+        let edited = "def first():\n    return 1\n\n# note\ndef second():\n    return 2\n";
+        let update = apply_fragment(&mut graph, "/ws/app.py", parse(edited), &[(3, 3)]);
+
+        assert_eq!(function(&graph, &update.file_info, "first"), Some(first));
+        assert_eq!(function(&graph, &update.file_info, "second"), Some(second));
+        assert!(update.removed.is_empty());
+        let node = graph.get_node(second).unwrap();
+        assert_eq!(node_props::line_start(node), 5);
+    }
+
+    #[test]
+    fn test_edited_and_deleted_symbols_are_replaced() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let info = parse(SOURCE).insert(&mut graph);
+        let first = function(&graph, &info, "first").unwrap();
+        let second = function(&graph, &info, "second").unwrap();
+
+        // `first` changes and `second` is deleted
+        let edited = "def first():\n    return 10\n";
+        let update = apply_fragment(&mut graph, "/ws/app.py", parse(edited), &[(1, 1)]);
+
+        let new_first = function(&graph, &update.file_info, "first").unwrap();
+        assert_ne!(new_first, first);
+        assert!(function(&graph, &update.file_info, "second").is_none());
+        assert!(update.removed.contains(&first));
+        assert!(update.removed.contains(&second));
+        assert!(graph.get_node(second).is_err());
+    }
+
+    #[test]
+    fn test_same_named_methods_are_told_apart() {
+        let source =
+            "class A:\n    def run(self):\n        return 1\n\nclass B:\n    def run(self):\n        return 2\n";
+        let mut graph = CodeGraph::in_memory().unwrap();
+        parse(source).insert(&mut graph);
+        let [a_run, b_run] = methods(&graph, "run")[..] else {
+            panic!("expected two methods named run");
+        };
+
+        // Class A is deleted, leaving its first row blank
+        let edited = "\nclass B:\n    def run(self):\n        return 2\n";
+        let update = apply_fragment(&mut graph, "/ws/app.py", parse(edited), &[(0, 0)]);
+
+        assert_eq!(methods(&graph, "run"), vec![b_run]);
+        assert!(update.removed.contains(&a_run));
+        assert!(!update.removed.contains(&b_run));
+    }
+
+    #[test]
+    fn test_editing_a_method_keeps_its_class() {
+        let source = "class A:\n    def run(self):\n        return 1\n\n    def stop(self):\n        return 2\n";
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let info = parse(source).insert(&mut graph);
+        let class = info.classes[0];
+        let [run] = methods(&graph, "run")[..] else {
+            panic!("expected one method named run");
+        };
+
+        // The body of `run` changes on row 2
+        let edited = "class A:\n    def run(self):\n        return 10\n\n    def stop(self):\n        return 2\n";
+        let update = apply_fragment(&mut graph, "/ws/app.py", parse(edited), &[(2, 2)]);
+
+        assert_eq!(update.file_info.classes, vec![class]);
+        assert!(!update.removed.contains(&class));
+        assert!(update.removed.contains(&run));
+        assert!(graph.get_node(class).is_ok());
+
+        // Renaming the class edits its header
+        let renamed = edited.replacen("class A", "class B", 1);
+        let update = apply_fragment(&mut graph, "/ws/app.py", parse(&renamed), &[(0, 0)]);
+        assert!(update.removed.contains(&class));
+    }
+
+    #[test]
+    fn test_node_keys() {
+        let node = |name: &str, signature: &str, lines: (i64, i64)| {
+            PropertyMap::new()
+                .with("name", name)
+                .with("signature", signature)
+                .with("line_start", PropertyValue::Int(lines.0))
+                .with("line_end", PropertyValue::Int(lines.1))
+        };
+        let class = node("Parser", "", (1, 10));
+        let by_int = node("parse", "void parse(int)", (2, 4));
+        let by_string = node("parse", "void parse(String)", (6, 8));
+        let keys = node_keys(&[
+            (NodeType::Class, &class),
+            (NodeType::Function, &by_int),
+            (NodeType::Function, &by_string),
+        ]);
+
+        assert_eq!(keys[0].1, "");
+        assert_eq!(keys[1].1, "Parser");
+        assert_eq!(keys[2].1, "Parser");
+        assert_ne!(keys[1], keys[2]);
+    }
+
+    #[test]
+    fn test_in_rows() {
+        let props = PropertyMap::new()
+            .with("line_start", PropertyValue::Int(3))
+            .with("line_end", PropertyValue::Int(5));
+        assert!(in_rows(&props, &[(4, 4)]));
+        assert!(in_rows(&props, &[(0, 2)]));
+        assert!(!in_rows(&props, &[(0, 1), (5, 9)]));
+        assert!(!in_rows(&PropertyMap::new(), &[(0, 100)]));
+    }
+
+    #[test]
+    fn test_edited_nodes_only_count_class_headers() {
+        let lines = |start: i64, end: i64| {
+            PropertyMap::new()
+                .with("line_start", PropertyValue::Int(start))
+                .with("line_end", PropertyValue::Int(end))
+        };
+        let (class, method) = (lines(1, 6), lines(3, 6));
+        let nodes = [(NodeType::Class, &class), (NodeType::Function, &method)];
+        // Rows 0-1 are the class header, rows 2-5 the method
+        assert_eq!(edited_nodes(&nodes, &[(4, 4)]), vec![false, true]);
+        assert_eq!(edited_nodes(&nodes, &[(1, 1)]), vec![true, false]);
+        let empty = [(NodeType::Class, &class)];
+        assert_eq!(edited_nodes(&empty, &[(4, 4)]), vec![true]);
+    }
+}
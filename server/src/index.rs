@@ -208,6 +208,15 @@ impl SymbolIndex {
             .unwrap_or_default()
     }
 
+    /// Indexed files at or below `dir`, e.g. the files of a deleted directory.
+    pub fn files_under(&self, dir: &Path) -> Vec<PathBuf> {
+        self.by_file
+            .iter()
+            .map(|entry| entry.key().clone())
+            .filter(|path| path.starts_with(dir))
+            .collect()
+    }
+
     /// Get all symbols of a specific type.
     pub fn get_by_type(&self, node_type: &str) -> Vec<NodeId> {
         self.by_type
@@ -785,4 +794,32 @@ mod tests {
         // Non-existent node
         assert!(index.find_file_for_node(99).is_none());
     }
+
+    #[test]
+    fn test_files_under() {
+        let index = SymbolIndex::new();
+        index
+            .by_file
+            .insert(PathBuf::from("/src/api/a.rs"), vec![1]);
+        index
+            .by_file
+            .insert(PathBuf::from("/src/api/v2/b.rs"), vec![2]);
+        index
+            .by_file
+            .insert(PathBuf::from("/src/apiary.rs"), vec![3]);
+
+        let mut files = index.files_under(Path::new("/src/api"));
+        files.sort();
+        assert_eq!(
+            files,
+            vec![
+                PathBuf::from("/src/api/a.rs"),
+                PathBuf::from("/src/api/v2/b.rs")
+            ]
+        );
+        assert_eq!(
+            index.files_under(Path::new("/src/apiary.rs")),
+            vec![PathBuf::from("/src/apiary.rs")]
+        );
+    }
 }
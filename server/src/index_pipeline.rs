@@ -0,0 +1,256 @@
+//! Parallel parsing for bulk indexing.
+//!
+//! Parsing dominates indexing time, and parsers write straight into a
+//! `CodeGraph`. To keep the shared graph's write lock out of it, each file is
+//! read and parsed on a blocking thread into a scratch graph of its own. The
+//! resulting [`FileFragment`]s stream back as they complete, at most a bounded
+//! number in flight, and the caller inserts them into the shared graph one at
+//! a time.
+
+use crate::parser_registry::ParserRegistry;
+use codegraph::{CodeGraph, EdgeType, NodeId, NodeType, PropertyMap};
+use codegraph_parser_api::FileInfo;
+use futures::stream::{self, Stream, StreamExt};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Files parsed at once when the available parallelism is unknown.
+const DEFAULT_PARSE_CONCURRENCY: usize = 4;
+
+/// Number of files to parse at once: one per available core.
+pub fn parse_concurrency() -> usize {
+    std::thread::available_parallelism()
+        .map(std::num::NonZeroUsize::get)
+        .unwrap_or(DEFAULT_PARSE_CONCURRENCY)
+}
+
+/// A node of a fragment: scratch graph id, type and properties.
+pub(crate) type FragmentNode = (NodeId, NodeType, PropertyMap);
+
+/// An edge of a fragment: source, target, type and properties.
+pub(crate) type FragmentEdge = (NodeId, NodeId, EdgeType, PropertyMap);
+
+/// A parsed file's nodes and edges, ready to insert into the shared graph.
+pub struct FileFragment {
+    nodes: Vec<FragmentNode>,
+    edges: Vec<FragmentEdge>,
+    /// File info with node ids of the scratch graph
+    file_info: FileInfo,
+}
+
+impl FileFragment {
+    /// Parse and enrich the file at `path` in a scratch graph. The parser
+    /// reads the file itself, so it counts in the parser's metrics.
+    pub fn parse(parsers: &ParserRegistry, path: &Path) -> Result<Self, String> {
+        let mut graph = CodeGraph::in_memory().map_err(|e| e.to_string())?;
+        let file_info = parsers
+            .parse_file(path, &mut graph)
+            .map_err(|e| e.to_string())?;
+        // Enrichment works on the text, which the parser does not hand back
+        let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        parsers.enrich_parsed_file(&mut graph, &file_info, path, &source);
+        Ok(Self::from_graph(&graph, file_info))
+    }
+
+    /// Parse and enrich `source`, the text of `path`, in a scratch graph. For
+    /// text that is not on disk, such as an open document.
+    pub fn parse_source(
+        parsers: &ParserRegistry,
+        path: &Path,
+        source: &str,
+    ) -> Result<Self, String> {
+        let mut graph = CodeGraph::in_memory().map_err(|e| e.to_string())?;
+        let file_info = parsers
+            .parse_source(source, path, &mut graph)
+            .map_err(|e| e.to_string())?;
+        parsers.enrich_parsed_file(&mut graph, &file_info, path, source);
+        Ok(Self::from_graph(&graph, file_info))
+    }
+
+    /// The nodes and edges of a scratch graph holding one parsed file.
+    fn from_graph(graph: &CodeGraph, file_info: FileInfo) -> Self {
+        let nodes = graph
+            .iter_nodes()
+            .map(|(id, node)| (id, node.node_type, node.properties.clone()))
+            .collect();
+        let edges = graph
+            .iter_edges()
+            .map(|(_, edge)| {
+                (
+                    edge.source_id,
+                    edge.target_id,
+                    edge.edge_type,
+                    edge.properties.clone(),
+                )
+            })
+            .collect();
+        Self {
+            nodes,
+            edges,
+            file_info,
+        }
+    }
+
+    /// Add the fragment's nodes and edges to `graph`. Returns the file info
+    /// with its node ids mapped into `graph`.
+    pub fn insert(self, graph: &mut CodeGraph) -> FileInfo {
+        let mut ids: HashMap<NodeId, NodeId> = HashMap::new();
+        for (id, node_type, properties) in self.nodes {
+            if let Ok(new_id) = graph.add_node(node_type, properties) {
+                ids.insert(id, new_id);
+            }
+        }
+        for (source, target, edge_type, properties) in self.edges {
+            if let (Some(&source), Some(&target)) = (ids.get(&source), ids.get(&target)) {
+                let _ = graph.add_edge(source, target, edge_type, properties);
+            }
+        }
+
+        Self::remap_file_info(self.file_info, &ids)
+    }
+
+    /// The fragment's nodes, edges and file info, with scratch graph ids.
+    pub(crate) fn into_parts(self) -> (Vec<FragmentNode>, Vec<FragmentEdge>, FileInfo) {
+        (self.nodes, self.edges, self.file_info)
+    }
+
+    /// Map the scratch graph ids in `info` through `ids`.
+    pub(crate) fn remap_file_info(info: FileInfo, ids: &HashMap<NodeId, NodeId>) -> FileInfo {
+        let remap = |list: Vec<NodeId>| -> Vec<NodeId> {
+            list.into_iter()
+                .filter_map(|id| ids.get(&id).copied())
+                .collect()
+        };
+        FileInfo {
+            file_id: ids.get(&info.file_id).copied().unwrap_or(info.file_id),
+            functions: remap(info.functions),
+            classes: remap(info.classes),
+            traits: remap(info.traits),
+            imports: remap(info.imports),
+            ..info
+        }
+    }
+}
+
+/// Parse `paths` on blocking threads with at most `concurrency` files in
+/// flight, yielding each path with its fragment as parsing completes.
+/// Dropping the stream stops parsing further files.
+pub fn parse_files<'a>(
+    parsers: Arc<ParserRegistry>,
+    paths: impl Iterator<Item = PathBuf> + Send + 'a,
+    concurrency: usize,
+) -> impl Stream<Item = (PathBuf, Result<FileFragment, String>)> + Send + 'a {
+    stream::iter(paths)
+        .map(move |path| {
+            let parsers = Arc::clone(&parsers);
+            async move {
+                let parse_path = path.clone();
+                let fragment =
+                    tokio::task::spawn_blocking(move || FileFragment::parse(&parsers, &parse_path))
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()));
+                (path, fragment)
+            }
+        })
+        .buffer_unordered(concurrency.max(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn source_file(suffix: &str, source: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(source.as_bytes()).unwrap();
+        file
+    }
+
+    fn path_nodes(graph: &CodeGraph, path: &Path) -> usize {
+        graph
+            .query()
+            .property("path", path.to_string_lossy().to_string())
+            .execute()
+            .map_or(0, |nodes| nodes.len())
+    }
+
+    #[test]
+    fn test_fragment_matches_direct_parse() {
+        let file = source_file(
+            ".py",
+            "def first():\n    pass\n\ndef second():\n    first()\n",
+        );
+        let parsers = ParserRegistry::new();
+
+        let mut direct = CodeGraph::in_memory().unwrap();
+        let direct_info = parsers.parse_file(file.path(), &mut direct).unwrap();
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let fragment = FileFragment::parse(&parsers, file.path()).unwrap();
+        let info = fragment.insert(&mut graph);
+
+        assert_eq!(info.functions.len(), direct_info.functions.len());
+        assert_eq!(graph.node_count(), direct.node_count());
+        assert_eq!(graph.edge_count(), direct.edge_count());
+        for id in info.functions.iter().chain([&info.file_id]) {
+            assert!(graph.get_node(*id).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_insert_keeps_existing_nodes() {
+        let a = source_file(".py", "def a():\n    pass\n");
+        let b = source_file(".py", "def b():\n    pass\n");
+        let parsers = ParserRegistry::new();
+
+        let mut graph = CodeGraph::in_memory().unwrap();
+        for file in [&a, &b] {
+            FileFragment::parse(&parsers, file.path())
+                .unwrap()
+                .insert(&mut graph);
+        }
+        assert!(path_nodes(&graph, a.path()) > 0);
+        assert!(path_nodes(&graph, b.path()) > 0);
+    }
+
+    #[test]
+    fn test_parse_counts_in_parser_metrics() {
+        let file = source_file(".py", "def a():\n    pass\n");
+        let parsers = ParserRegistry::new();
+        let attempted = |parsers: &ParserRegistry| {
+            parsers
+                .all_metrics()
+                .into_iter()
+                .find(|(language, _)| *language == "python")
+                .map_or(0, |(_, metrics)| metrics.files_attempted)
+        };
+
+        FileFragment::parse(&parsers, file.path()).unwrap();
+        assert_eq!(attempted(&parsers), 1);
+    }
+
+    #[test]
+    fn test_parse_unsupported_file_fails() {
+        let file = source_file(".txt", "plain text");
+        assert!(FileFragment::parse(&ParserRegistry::new(), file.path()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_parse_files_yields_every_path() {
+        let files: Vec<_> = (0..6)
+            .map(|i| source_file(".py", &format!("def f{i}():\n    pass\n")))
+            .collect();
+        let paths: Vec<PathBuf> = files.iter().map(|f| f.path().to_path_buf()).collect();
+
+        let parsed: Vec<_> = parse_files(
+            Arc::new(ParserRegistry::new()),
+            paths.clone().into_iter(),
+            2,
+        )
+        .collect()
+        .await;
+        assert_eq!(parsed.len(), paths.len());
+        assert!(parsed.iter().all(|(_, fragment)| fragment.is_ok()));
+    }
+}
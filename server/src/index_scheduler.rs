@@ -0,0 +1,228 @@
+//! Order in which files are indexed.
+//!
+//! Indexing a large workspace walks its directories for minutes. While that
+//! walk runs, the scheduler puts the files open in the editor and the files
+//! they import ahead of it, so navigation works on the file being looked at
+//! within seconds of startup. Files indexed out of order are skipped when the
+//! walk reaches them.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Queue of files to index before the rest of a bulk indexing pass.
+#[derive(Debug, Default)]
+pub struct IndexScheduler {
+    state: Mutex<SchedulerState>,
+}
+
+#[derive(Debug, Default)]
+struct SchedulerState {
+    /// Whether a bulk indexing pass is running.
+    active: bool,
+    /// Files to index before the walk continues.
+    queue: VecDeque<PathBuf>,
+    /// Files indexed so far in the current pass.
+    indexed: HashSet<PathBuf>,
+    /// When the last pass finished.
+    last_pass: Option<SystemTime>,
+}
+
+impl IndexScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a bulk pass, indexing `open` first and skipping `indexed`, e.g.
+    /// files restored from a snapshot. Open files are indexed even if listed
+    /// in `indexed`, as the editor may hold unsaved changes.
+    pub fn begin_pass(
+        &self,
+        open: impl IntoIterator<Item = PathBuf>,
+        indexed: impl IntoIterator<Item = PathBuf>,
+    ) {
+        let mut state = self.state.lock().unwrap();
+        state.active = true;
+        state.queue = open.into_iter().collect();
+        let indexed: HashSet<PathBuf> = indexed
+            .into_iter()
+            .filter(|path| !state.queue.contains(path))
+            .collect();
+        state.indexed = indexed;
+    }
+
+    pub fn end_pass(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.active = false;
+        state.queue.clear();
+        state.indexed.clear();
+        state.last_pass = Some(SystemTime::now());
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.state.lock().unwrap().active
+    }
+
+    pub fn last_pass_finished(&self) -> Option<SystemTime> {
+        self.state.lock().unwrap().last_pass
+    }
+
+    /// Queue `path` ahead of the walk. Does nothing outside a pass or if the
+    /// file was already indexed or queued in this one.
+    pub fn prioritize(&self, path: PathBuf) {
+        let mut state = self.state.lock().unwrap();
+        if state.active && !state.indexed.contains(&path) && !state.queue.contains(&path) {
+            state.queue.push_back(path);
+        }
+    }
+
+    pub fn has_priority(&self) -> bool {
+        !self.state.lock().unwrap().queue.is_empty()
+    }
+
+    /// Next queued file, if any.
+    pub fn next_priority(&self) -> Option<PathBuf> {
+        self.state.lock().unwrap().queue.pop_front()
+    }
+
+    /// Record that `path` is being indexed. Returns false if it already was
+    /// in this pass, in which case the caller skips it. Always true outside a
+    /// pass.
+    pub fn claim(&self, path: &Path) -> bool {
+        let mut state = self.state.lock().unwrap();
+        !state.active || state.indexed.insert(path.to_path_buf())
+    }
+}
+
+/// `path` with `.` and `..` components resolved lexically.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Files that the import `specifier` in `importer` may refer to, most likely
+/// first. Only relative specifiers are resolved: path-style (`./util`,
+/// `../lib/db.js`) with each of `extensions` (without dots) or an index file,
+/// and Python-style (`.models`, `..core.db`). Package imports are left to
+/// the walk.
+pub fn import_candidates(importer: &Path, specifier: &str, extensions: &[&str]) -> Vec<PathBuf> {
+    let Some(dir) = importer.parent() else {
+        return Vec::new();
+    };
+    let specifier = specifier.trim_matches(|c| c == '"' || c == '\'');
+
+    if specifier.starts_with("./") || specifier.starts_with("../") {
+        let base = normalize(&dir.join(specifier));
+        let mut candidates = vec![base.clone()];
+        for ext in extensions {
+            let mut with_ext = base.clone().into_os_string();
+            with_ext.push(format!(".{ext}"));
+            candidates.push(PathBuf::from(with_ext));
+        }
+        for ext in extensions {
+            candidates.push(base.join(format!("index.{ext}")));
+        }
+        return candidates;
+    }
+
+    if let Some(module) = specifier.strip_prefix('.') {
+        // One dot is the importer's package, each further dot its parent
+        let parents = module.chars().take_while(|&c| c == '.').count();
+        let module = &module[parents..];
+        if module.is_empty() {
+            return Vec::new();
+        }
+        let mut package = dir.to_path_buf();
+        for _ in 0..parents {
+            package.pop();
+        }
+        let base = module
+            .split('.')
+            .fold(package, |path, part| path.join(part));
+        return vec![base.with_extension("py"), base.join("__init__.py")];
+    }
+
+    Vec::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pass_indexes_priority_files_once() {
+        let scheduler = IndexScheduler::new();
+        let (open, import, other) = (
+            PathBuf::from("/ws/src/main.ts"),
+            PathBuf::from("/ws/src/util.ts"),
+            PathBuf::from("/ws/src/other.ts"),
+        );
+
+        // Outside a pass nothing is queued and everything is indexed
+        scheduler.prioritize(import.clone());
+        assert_eq!(scheduler.next_priority(), None);
+        assert!(scheduler.claim(&other) && scheduler.claim(&other));
+
+        let restored = PathBuf::from("/ws/src/restored.ts");
+        scheduler.begin_pass([open.clone()], [restored.clone(), open.clone()]);
+        assert!(!scheduler.claim(&restored));
+        assert_eq!(scheduler.next_priority(), Some(open.clone()));
+        assert!(scheduler.claim(&open));
+        scheduler.prioritize(import.clone());
+        scheduler.prioritize(import.clone());
+        scheduler.prioritize(open.clone());
+        assert_eq!(scheduler.next_priority(), Some(import.clone()));
+        assert!(!scheduler.has_priority());
+        assert!(scheduler.claim(&import));
+
+        // The walk skips files indexed ahead of it
+        assert!(!scheduler.claim(&import));
+        assert!(scheduler.claim(&other));
+
+        scheduler.end_pass();
+        assert!(!scheduler.is_active());
+        assert!(scheduler.last_pass_finished().is_some());
+        assert!(scheduler.claim(&import));
+    }
+
+    #[test]
+    fn test_import_candidates() {
+        let importer = Path::new("/ws/src/app/main.ts");
+        let candidates = import_candidates(importer, "../lib/db", &["ts", "js"]);
+        assert_eq!(
+            candidates,
+            vec![
+                PathBuf::from("/ws/src/lib/db"),
+                PathBuf::from("/ws/src/lib/db.ts"),
+                PathBuf::from("/ws/src/lib/db.js"),
+                PathBuf::from("/ws/src/lib/db/index.ts"),
+                PathBuf::from("/ws/src/lib/db/index.js"),
+            ]
+        );
+        assert_eq!(
+            import_candidates(importer, "./user.service", &["ts"])[1],
+            PathBuf::from("/ws/src/app/user.service.ts")
+        );
+
+        let importer = Path::new("/ws/pkg/api/views.py");
+        assert_eq!(
+            import_candidates(importer, "..core.db", &["py"]),
+            vec![
+                PathBuf::from("/ws/pkg/core/db.py"),
+                PathBuf::from("/ws/pkg/core/db/__init__.py"),
+            ]
+        );
+        assert!(import_candidates(importer, ".", &["py"]).is_empty());
+        assert!(import_candidates(importer, "react", &["ts"]).is_empty());
+    }
+}
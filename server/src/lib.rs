@@ -10,20 +10,31 @@
 //! - **MCP** (`--mcp` flag): Model Context Protocol for AI client integration
 
 pub mod ai_query;
+pub mod annotations;
 pub mod backend;
 pub mod branch_watcher;
 pub mod cache;
+pub mod cancellation;
 pub mod custom_requests;
+pub mod document;
 pub mod domain;
 pub mod error;
 pub mod git_mining;
+pub mod graph_snapshot;
 pub mod handlers;
+pub mod header_links;
+pub mod incremental;
 pub mod index;
+pub mod index_pipeline;
+pub mod index_scheduler;
 pub mod mcp;
 pub mod memory;
+pub mod parser_plugins;
 pub mod parser_registry;
+pub mod progress;
 pub mod runtime_deps;
 pub mod watcher;
+pub mod workspace_walk;
 
 pub use backend::CodeGraphBackend;
 pub use error::LspError;
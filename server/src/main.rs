@@ -4,9 +4,13 @@
 //! It supports two modes:
 //! - LSP mode (default): Serves Language Server Protocol over stdio for editors
 //! - MCP mode (--mcp): Serves Model Context Protocol over stdio for AI clients
+//!
+//! With --mcp-listen, LSP mode also serves MCP on a socket from the same graph
+//! and memory store, and --mcp --connect relays stdio to that socket.
 
 use clap::Parser;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 #[derive(Parser)]
@@ -41,6 +45,82 @@ struct Args {
     /// Embed full function body instead of just name+signature (~3x slower, better quality)
     #[arg(long)]
     full_body_embedding: bool,
+
+    /// LSP mode: also serve MCP clients on this address (e.g. 127.0.0.1:7424),
+    /// sharing the editor's index and memory store. Clients authenticate with
+    /// the token written to ~/.codegraph/mcp-daemon-<port>.token
+    #[arg(long)]
+    mcp_listen: Option<String>,
+
+    /// MCP mode: relay stdio to the LSP server listening on this address
+    /// instead of indexing the workspace again
+    #[arg(long, requires = "mcp")]
+    connect: Option<String>,
+
+    /// Maximum number of MCP tool calls running at once (batched queries, or
+    /// clients of --mcp-listen); further calls wait their turn
+    #[arg(long, default_value = "4")]
+    max_concurrent_tools: usize,
+
+    /// Maximum number of calls of one MCP tool running at once, as tool=N
+    /// (repeatable). Graph-wide analyses default to 2
+    #[arg(long)]
+    tool_concurrency: Vec<String>,
+
+    /// Run destructive MCP operations (e.g. invalidating a memory) without
+    /// confirmation, for clients that cannot ask the user
+    #[arg(long)]
+    yes: bool,
+
+    /// MCP tools to expose, as allow=a,b or deny=c (repeatable). Patterns may
+    /// omit the codegraph_ prefix, end in *, or be @read / @write
+    #[arg(long)]
+    tools: Vec<String>,
+
+    /// JSON file with "allow" and "deny" lists of MCP tool patterns
+    #[arg(long)]
+    tools_file: Option<PathBuf>,
+}
+
+/// The tool call limits given by `--max-concurrent-tools` and
+/// `--tool-concurrency`, or exit
+fn parse_tool_limits(args: &Args) -> codegraph_lsp::mcp::limits::ToolLimits {
+    use codegraph_lsp::mcp::limits::ToolLimits;
+
+    let mut limits = ToolLimits::new(args.max_concurrent_tools);
+    for spec in &args.tool_concurrency {
+        match ToolLimits::parse_tool_limit(spec) {
+            Ok((tool, max)) => limits = limits.with_tool_limit(&tool, max),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(2);
+            }
+        }
+    }
+    limits
+}
+
+/// The tool filter given by `--tools-file` and `--tools`, or exit
+fn parse_tool_filter(args: &Args) -> codegraph_lsp::mcp::tool_filter::ToolFilter {
+    use codegraph_lsp::mcp::tool_filter::ToolFilter;
+
+    let mut filter = match &args.tools_file {
+        Some(path) => ToolFilter::from_file(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }),
+        None => ToolFilter::new(),
+    };
+    for spec in &args.tools {
+        if let Err(e) = filter.add_spec(spec) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+    if !filter.is_unrestricted() {
+        tracing::info!("Tool filter: {:?}", filter);
+    }
+    filter
 }
 
 #[tokio::main]
@@ -55,15 +135,31 @@ async fn main() {
         "codegraph_lsp=info"
     };
 
+    // MCP mode also forwards log events to the client
+    let mcp_logger = Arc::new(codegraph_lsp::mcp::logging::McpLogger::new());
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| log_filter.into()),
         )
         .with(tracing_subscriber::fmt::layer().with_writer(std::io::stderr))
+        .with(
+            args.mcp
+                .then(|| codegraph_lsp::mcp::logging::McpLogLayer::new(Arc::clone(&mcp_logger))),
+        )
         .init();
 
-    if args.mcp {
+    let tool_filter = parse_tool_filter(&args);
+    let tool_limits = parse_tool_limits(&args);
+
+    if let Some(addr) = args.connect.as_deref() {
+        // MCP relay to a shared LSP server
+        tracing::info!("Relaying MCP to {}", addr);
+        if let Err(e) = codegraph_lsp::mcp::daemon::relay_stdio(addr).await {
+            tracing::error!("MCP relay error: {}", e);
+            std::process::exit(1);
+        }
+    } else if args.mcp {
         // MCP mode
         let workspaces = if args.workspace.is_empty() {
             vec![std::env::current_dir().expect("Failed to get current directory")]
@@ -84,7 +180,17 @@ async fn main() {
             tracing::info!("Excluding: {:?}", args.exclude);
         }
 
-        let mut server = codegraph_lsp::mcp::McpServer::new(workspaces, args.exclude, args.max_files, embedding_model, args.full_body_embedding);
+        let mut server = codegraph_lsp::mcp::McpServer::new(
+            workspaces,
+            args.exclude,
+            args.max_files,
+            embedding_model,
+            args.full_body_embedding,
+        )
+        .with_logger(mcp_logger)
+        .with_limits(Arc::new(tool_limits))
+        .with_assume_yes(args.yes)
+        .with_tool_filter(Arc::new(tool_filter));
         if let Err(e) = server.run().await {
             tracing::error!("MCP server error: {}", e);
             std::process::exit(1);
@@ -101,6 +207,20 @@ async fn main() {
 
         let (service, socket) = LspService::new(CodeGraphBackend::new);
 
+        if let Some(addr) = args.mcp_listen {
+            let shared = codegraph_lsp::mcp::daemon::SharedComponents::from_lsp(service.inner())
+                .with_limits(tool_limits)
+                .with_assume_yes(args.yes)
+                .with_tool_filter(tool_filter)
+                .with_exclude_dirs(args.exclude)
+                .with_max_files(args.max_files);
+            tokio::spawn(async move {
+                if let Err(e) = codegraph_lsp::mcp::daemon::serve(&addr, shared).await {
+                    tracing::error!("MCP daemon error: {}", e);
+                }
+            });
+        }
+
         Server::new(stdin, stdout, socket).serve(service).await;
     }
 }
@@ -0,0 +1,106 @@
+//! Requests from the server to the client (`sampling/createMessage`,
+//! `roots/list`).
+//!
+//! A request is written with an id of its own and awaited; the reader task
+//! hands the client's response back through [`ClientRequests::resolve`], so a
+//! request may be sent while the event loop is busy with a tool call.
+
+use super::protocol::{JsonRpcClientResponse, JsonRpcOutgoingRequest};
+use super::transport::MessageWriter;
+use dashmap::DashMap;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Sends requests to the client and matches its responses to them.
+#[derive(Default)]
+pub struct ClientRequests {
+    writer: OnceLock<MessageWriter>,
+    /// Senders of the requests awaiting a response, by request id
+    pending: DashMap<String, oneshot::Sender<Result<Value, String>>>,
+    next_id: AtomicU64,
+}
+
+impl ClientRequests {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Attach the writer requests are sent with, once the transport is up
+    pub fn attach(&self, writer: MessageWriter) {
+        let _ = self.writer.set(writer);
+    }
+
+    pub fn is_attached(&self) -> bool {
+        self.writer.get().is_some()
+    }
+
+    /// Send `method` to the client and wait up to `timeout` for its result
+    pub async fn send(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<Value, String> {
+        let writer = self
+            .writer
+            .get()
+            .ok_or_else(|| "Not connected to a client".to_string())?;
+
+        let id = format!("codegraph-{}", self.next_id.fetch_add(1, Ordering::Relaxed));
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(id.clone(), tx);
+        let request = JsonRpcOutgoingRequest::new(Value::String(id.clone()), method, params);
+        if let Err(e) = writer.write_request(&request).await {
+            self.pending.remove(&id);
+            return Err(format!("Failed to send {}: {}", method, e));
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("{} request dropped", method)),
+            Err(_) => {
+                self.pending.remove(&id);
+                Err(format!("{} request timed out", method))
+            }
+        }
+    }
+
+    /// Hand a client response to the request awaiting it. Returns whether the
+    /// response answered one of our requests.
+    pub fn resolve(&self, response: JsonRpcClientResponse) -> bool {
+        let Some((_, tx)) = response.id.as_str().and_then(|id| self.pending.remove(id)) else {
+            return false;
+        };
+        let result = match (response.result, response.error) {
+            (_, Some(error)) => Err(error
+                .get("message")
+                .and_then(|m| m.as_str())
+                .unwrap_or("Request failed")
+                .to_string()),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err("Empty response".to_string()),
+        };
+        let _ = tx.send(result);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_unknown_response() {
+        let requests = ClientRequests::new();
+        assert!(!requests.is_attached());
+        let response = JsonRpcClientResponse {
+            id: Value::String("codegraph-7".to_string()),
+            result: Some(Value::Null),
+            error: None,
+        };
+        assert!(!requests.resolve(response));
+    }
+}
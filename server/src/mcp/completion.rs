@@ -0,0 +1,337 @@
+//! MCP Argument Completion
+//!
+//! Answers `completion/complete` while a user fills in a resource template or
+//! tool argument. Symbol names come from the text index, symbol references
+//! complete to monikers, file paths and URIs to the indexed files, and memory
+//! ids to the current memories. Tool arguments are completed for clients that
+//! send a `ref/tool` reference.
+
+use super::protocol::{CompleteParams, Completion, CompletionReference};
+use super::resources::{MEMORY_URI_PREFIX, OUTLINE_URI_PREFIX, SYMBOL_URI_PREFIX};
+use crate::ai_query::QueryEngine;
+use crate::domain::node_props;
+use crate::memory::MemoryManager;
+use codegraph::{CodeGraph, NodeId, NodeType};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+
+/// Values returned per completion request, as the spec allows
+pub const MAX_COMPLETIONS: usize = 100;
+
+/// Tools whose `query` argument is a symbol name rather than free text
+const SYMBOL_QUERY_TOOLS: &[&str] = &["codegraph_symbol_search", "codegraph_get_curated_context"];
+
+/// What kind of value an argument takes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgumentKind {
+    /// A symbol name
+    SymbolName,
+    /// A NodeId or moniker
+    NodeRef,
+    /// A file path, relative to a workspace folder
+    FilePath,
+    /// A `file://` URI
+    FileUri,
+    /// A memory id
+    MemoryId,
+}
+
+/// The kind of `argument` of `reference`, if it can be completed
+pub fn argument_kind(reference: &CompletionReference, argument: &str) -> Option<ArgumentKind> {
+    match reference {
+        CompletionReference::Resource { uri } => {
+            let kind = if uri.starts_with(OUTLINE_URI_PREFIX) {
+                ArgumentKind::FilePath
+            } else if uri.starts_with(SYMBOL_URI_PREFIX) {
+                ArgumentKind::NodeRef
+            } else if uri.starts_with(MEMORY_URI_PREFIX) {
+                ArgumentKind::MemoryId
+            } else {
+                return None;
+            };
+            uri.contains(&format!("{{{}}}", argument)).then_some(kind)
+        }
+        CompletionReference::Tool { name } => match argument {
+            "query" if SYMBOL_QUERY_TOOLS.contains(&name.as_str()) => {
+                Some(ArgumentKind::SymbolName)
+            }
+            "id" if name.starts_with("codegraph_memory_") => Some(ArgumentKind::MemoryId),
+            _ if argument.starts_with("nodeId") || argument.ends_with("NodeId") => {
+                Some(ArgumentKind::NodeRef)
+            }
+            _ if argument.starts_with("uri") || argument.ends_with("Uri") => {
+                Some(ArgumentKind::FileUri)
+            }
+            _ => None,
+        },
+        // The server has no prompts
+        CompletionReference::Prompt { .. } => None,
+    }
+}
+
+/// Suggestions for the argument in `params`
+pub async fn complete(
+    params: &CompleteParams,
+    graph: &RwLock<CodeGraph>,
+    query_engine: &QueryEngine,
+    memory_manager: &MemoryManager,
+    workspace_folders: &[PathBuf],
+) -> Completion {
+    let Some(kind) = argument_kind(&params.reference, &params.argument.name) else {
+        return Completion::default();
+    };
+    let value = params.argument.value.trim();
+
+    let values = match kind {
+        ArgumentKind::SymbolName => symbol_names(value, graph, query_engine).await,
+        ArgumentKind::NodeRef => node_refs(value, graph, query_engine).await,
+        ArgumentKind::FilePath | ArgumentKind::FileUri => {
+            file_values(kind, value, graph, workspace_folders).await
+        }
+        ArgumentKind::MemoryId => memory_ids(value, memory_manager).await,
+    };
+    completion(values)
+}
+
+/// Deduplicate `values` and cap them at [`MAX_COMPLETIONS`]
+fn completion(values: Vec<String>) -> Completion {
+    let mut seen = HashSet::new();
+    let mut values: Vec<String> = values
+        .into_iter()
+        .filter(|value| seen.insert(value.clone()))
+        .collect();
+    let total = values.len();
+    values.truncate(MAX_COMPLETIONS);
+    Completion {
+        values,
+        total: Some(total),
+        has_more: Some(total > MAX_COMPLETIONS),
+    }
+}
+
+async fn symbol_names(
+    prefix: &str,
+    graph: &RwLock<CodeGraph>,
+    query_engine: &QueryEngine,
+) -> Vec<String> {
+    let node_ids = query_engine.complete_symbols(prefix, usize::MAX).await;
+    let graph = graph.read().await;
+    node_ids
+        .into_iter()
+        .filter_map(|node_id| Some(node_props::name(graph.get_node(node_id).ok()?).to_string()))
+        .collect()
+}
+
+/// Monikers (or NodeIds, for nodes without one) of the symbols `value` names.
+/// A partial moniker completes on the name after its last `#` or `.`.
+async fn node_refs(
+    value: &str,
+    graph: &RwLock<CodeGraph>,
+    query_engine: &QueryEngine,
+) -> Vec<String> {
+    if value.parse::<NodeId>().is_ok() {
+        let graph = graph.read().await;
+        return crate::domain::monikers::resolve_node_ref(&graph, value)
+            .map(|node_id| vec![node_id.to_string()])
+            .unwrap_or_default();
+    }
+
+    let name_prefix = value.rsplit(['#', '.']).next().unwrap_or(value);
+    let node_ids = query_engine.complete_symbols(name_prefix, usize::MAX).await;
+    let graph = graph.read().await;
+    node_ids
+        .into_iter()
+        .filter_map(|node_id| {
+            let node = graph.get_node(node_id).ok()?;
+            Some(match node.properties.get_string("moniker") {
+                Some(moniker) => moniker.to_string(),
+                None => node_id.to_string(),
+            })
+        })
+        .filter(|reference| name_prefix == value || reference.starts_with(value))
+        .collect()
+}
+
+/// Indexed files whose relative path contains `value` (or whose URI starts
+/// with it), paths starting with it first
+async fn file_values(
+    kind: ArgumentKind,
+    value: &str,
+    graph: &RwLock<CodeGraph>,
+    workspace_folders: &[PathBuf],
+) -> Vec<String> {
+    let paths: Vec<String> = {
+        let graph = graph.read().await;
+        graph
+            .query()
+            .node_type(NodeType::CodeFile)
+            .execute()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|id| Some(node_props::path(graph.get_node(id).ok()?).to_string()))
+            .collect()
+    };
+
+    let needle = value.to_lowercase();
+    let mut matches: Vec<(bool, String)> = paths
+        .iter()
+        .filter_map(|path| {
+            let relative = relative_path(Path::new(path), workspace_folders);
+            let completed = match kind {
+                ArgumentKind::FileUri => tower_lsp::lsp_types::Url::from_file_path(path)
+                    .ok()?
+                    .to_string(),
+                _ => relative.clone(),
+            };
+            let relative = relative.to_lowercase();
+            if completed.starts_with(value) || relative.starts_with(&needle) {
+                Some((true, completed))
+            } else if relative.contains(&needle) {
+                Some((false, completed))
+            } else {
+                None
+            }
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then_with(|| a.1.len().cmp(&b.1.len()))
+            .then_with(|| a.1.cmp(&b.1))
+    });
+    matches
+        .into_iter()
+        .map(|(_, completed)| completed)
+        .collect()
+}
+
+/// `path` relative to the first workspace folder containing it
+fn relative_path(path: &Path, workspace_folders: &[PathBuf]) -> String {
+    workspace_folders
+        .iter()
+        .find_map(|folder| path.strip_prefix(folder).ok())
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// Ids of current memories whose id starts with `value` or whose title
+/// contains it
+async fn memory_ids(value: &str, memory_manager: &MemoryManager) -> Vec<String> {
+    let memories = match memory_manager.get_all_current().await {
+        Ok(memories) => memories,
+        Err(e) => {
+            tracing::debug!("Memory completion unavailable: {}", e);
+            return Vec::new();
+        }
+    };
+    let needle = value.to_lowercase();
+    memories
+        .iter()
+        .filter(|memory| {
+            memory.id.to_string().starts_with(value)
+                || memory.title.to_lowercase().contains(&needle)
+        })
+        .map(|memory| memory.id.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tool(name: &str) -> CompletionReference {
+        CompletionReference::Tool {
+            name: name.to_string(),
+        }
+    }
+
+    fn resource(uri: &str) -> CompletionReference {
+        CompletionReference::Resource {
+            uri: uri.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_argument_kind() {
+        assert_eq!(
+            argument_kind(&resource("codegraph://outline/{path}"), "path"),
+            Some(ArgumentKind::FilePath)
+        );
+        assert_eq!(
+            argument_kind(&resource("codegraph://symbol/{nodeId}"), "nodeId"),
+            Some(ArgumentKind::NodeRef)
+        );
+        assert_eq!(
+            argument_kind(&resource("codegraph://memory/{id}"), "id"),
+            Some(ArgumentKind::MemoryId)
+        );
+        assert_eq!(
+            argument_kind(&resource("codegraph://memory/{id}"), "path"),
+            None
+        );
+        assert_eq!(argument_kind(&resource("codegraph://metrics"), "id"), None);
+
+        assert_eq!(
+            argument_kind(&tool("codegraph_symbol_search"), "query"),
+            Some(ArgumentKind::SymbolName)
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_memory_search"), "query"),
+            None
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_memory_get"), "id"),
+            Some(ArgumentKind::MemoryId)
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_find_path"), "fromNodeId"),
+            Some(ArgumentKind::NodeRef)
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_compare_symbols"), "nodeIdA"),
+            Some(ArgumentKind::NodeRef)
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_get_callers"), "uri"),
+            Some(ArgumentKind::FileUri)
+        );
+        assert_eq!(
+            argument_kind(&tool("codegraph_find_path"), "toUri"),
+            Some(ArgumentKind::FileUri)
+        );
+        assert_eq!(argument_kind(&tool("codegraph_get_callers"), "depth"), None);
+
+        let prompt = CompletionReference::Prompt {
+            name: "review".to_string(),
+        };
+        assert_eq!(argument_kind(&prompt, "uri"), None);
+    }
+
+    #[test]
+    fn test_completion_dedupes_and_caps() {
+        let result = completion(vec!["a".to_string(), "b".to_string(), "a".to_string()]);
+        assert_eq!(result.values, vec!["a", "b"]);
+        assert_eq!(result.total, Some(2));
+        assert_eq!(result.has_more, Some(false));
+
+        let many = (0..MAX_COMPLETIONS + 5).map(|i| i.to_string()).collect();
+        let result = completion(many);
+        assert_eq!(result.values.len(), MAX_COMPLETIONS);
+        assert_eq!(result.total, Some(MAX_COMPLETIONS + 5));
+        assert_eq!(result.has_more, Some(true));
+    }
+
+    #[test]
+    fn test_relative_path() {
+        let folders = vec![PathBuf::from("/work/app"), PathBuf::from("/work/lib")];
+        assert_eq!(
+            relative_path(Path::new("/work/lib/src/parse.rs"), &folders),
+            "src/parse.rs"
+        );
+        assert_eq!(
+            relative_path(Path::new("/elsewhere/main.rs"), &folders),
+            "/elsewhere/main.rs"
+        );
+    }
+}
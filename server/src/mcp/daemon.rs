@@ -0,0 +1,410 @@
+//! Shared daemon: MCP over TCP from the LSP server's process.
+//!
+//! With `--mcp-listen <addr>`, the LSP server also accepts MCP clients on a
+//! TCP socket and serves them from its own graph, query engine and memory
+//! store. The workspace is indexed once, and the memory store, which only one
+//! process can open, is no longer contended by a second server. MCP clients
+//! that only speak stdio connect through `codegraph-lsp --mcp --connect
+//! <addr>`, which relays stdio to the socket.
+//!
+//! Anyone who can reach the port could otherwise read the graph and the
+//! memories, so clients must first send the token the daemon writes to
+//! `~/.codegraph/mcp-daemon-<port>.token`, which only the user can read.
+
+use super::limits::ToolLimits;
+use super::server::{McpBackend, McpServer};
+use super::tool_filter::ToolFilter;
+use super::transport::AsyncTransport;
+use crate::ai_query::QueryEngine;
+use crate::backend::{CodeGraphBackend, CodeGraphConfig};
+use crate::memory::MemoryManager;
+use crate::parser_registry::ParserRegistry;
+use crate::watcher::{FileWatcher, WatchedChange};
+use codegraph::CodeGraph;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+/// How long a client may take to send its token
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest token line read from a client
+const MAX_TOKEN_LINE: u64 = 256;
+
+/// The components of the LSP server that MCP clients are served from
+#[derive(Clone)]
+pub struct SharedComponents {
+    graph: Arc<RwLock<CodeGraph>>,
+    parsers: Arc<ParserRegistry>,
+    query_engine: Arc<QueryEngine>,
+    memory_manager: Arc<MemoryManager>,
+    workspace_folders: Arc<RwLock<Vec<PathBuf>>>,
+    /// The LSP server's settings, for its exclusions and limits
+    config: Arc<RwLock<CodeGraphConfig>>,
+    /// The LSP server's file watcher, once it watches the workspace
+    lsp_watcher: Arc<Mutex<Option<FileWatcher>>>,
+    /// Watches the workspace for the clients while the LSP server does not
+    watcher: Arc<Mutex<Option<FileWatcher>>>,
+    /// Directories to exclude from indexing (`--exclude`)
+    exclude_dirs: Vec<String>,
+    /// Maximum number of files a client's reindex indexes (`--max-files`)
+    max_files: usize,
+    /// Shared by all clients, so together they stay within the limits
+    limits: Arc<ToolLimits>,
+    /// Run destructive operations without asking the user (`--yes`)
+    assume_yes: bool,
+    tool_filter: Arc<ToolFilter>,
+}
+
+impl SharedComponents {
+    pub fn from_lsp(backend: &CodeGraphBackend) -> Self {
+        Self {
+            graph: Arc::clone(&backend.graph),
+            parsers: Arc::clone(&backend.parsers),
+            query_engine: Arc::clone(&backend.query_engine),
+            memory_manager: Arc::clone(&backend.memory_manager),
+            workspace_folders: Arc::clone(&backend.workspace_folders),
+            config: Arc::clone(&backend.config),
+            lsp_watcher: Arc::clone(&backend.file_watcher),
+            watcher: Arc::new(Mutex::new(None)),
+            exclude_dirs: Vec::new(),
+            max_files: 5000,
+            limits: Arc::new(ToolLimits::default()),
+            assume_yes: false,
+            tool_filter: Arc::new(ToolFilter::new()),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ToolLimits) -> Self {
+        self.limits = Arc::new(limits);
+        self
+    }
+
+    pub fn with_assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    pub fn with_tool_filter(mut self, tool_filter: ToolFilter) -> Self {
+        self.tool_filter = Arc::new(tool_filter);
+        self
+    }
+
+    pub fn with_exclude_dirs(mut self, exclude_dirs: Vec<String>) -> Self {
+        self.exclude_dirs = exclude_dirs;
+        self
+    }
+
+    pub fn with_max_files(mut self, max_files: usize) -> Self {
+        self.max_files = max_files;
+        self
+    }
+
+    /// An MCP backend over these components, with the workspace folders the
+    /// editor has open now
+    async fn mcp_backend(&self) -> McpBackend {
+        let walk = CodeGraphBackend::configured_walk(&self.parsers, &*self.config.read().await);
+        McpBackend::from_shared(
+            Arc::clone(&self.graph),
+            Arc::clone(&self.parsers),
+            Arc::clone(&self.query_engine),
+            Arc::clone(&self.memory_manager),
+            self.workspace_folders.read().await.clone(),
+            walk,
+            self.exclude_dirs.clone(),
+            self.max_files,
+        )
+    }
+
+    /// The changes applied to the graph from now on, as the LSP server's
+    /// watcher reports them. While the LSP server does not watch the
+    /// workspace, the daemon watches it for all its clients.
+    async fn watch(&self, backend: &McpBackend) -> mpsc::UnboundedReceiver<WatchedChange> {
+        let (changes_tx, changes_rx) = mpsc::unbounded_channel();
+        if let Some(watcher) = self.lsp_watcher.lock().await.as_ref() {
+            watcher.add_change_listener(changes_tx);
+            return changes_rx;
+        }
+
+        let mut watcher = self.watcher.lock().await;
+        if watcher.is_none() {
+            match FileWatcher::headless(
+                Arc::clone(&self.graph),
+                Arc::clone(&self.parsers),
+                Arc::clone(&self.memory_manager),
+            ) {
+                Ok(mut started) => {
+                    started.set_workspace_walk(backend.workspace_walk());
+                    for folder in &backend.workspace_folders {
+                        if let Err(e) = started.watch(folder) {
+                            tracing::warn!("Failed to watch {:?}: {}", folder, e);
+                        }
+                    }
+                    *watcher = Some(started);
+                }
+                Err(e) => tracing::warn!("Failed to start file watcher: {}", e),
+            }
+        }
+        if let Some(watcher) = watcher.as_ref() {
+            watcher.add_change_listener(changes_tx);
+        }
+        changes_rx
+    }
+}
+
+/// Accept MCP clients on `addr`, each served by its own `McpServer` over the
+/// shared components, until the listener fails. Only clients that send the
+/// token written to [`token_path`] are served.
+pub async fn serve(addr: &str, shared: SharedComponents) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    let token = new_token();
+    write_token(&token_path(local_addr.port())?, &token)?;
+    let token = Arc::new(token);
+    tracing::info!("MCP daemon listening on {}", local_addr);
+
+    loop {
+        let (stream, peer) = listener.accept().await?;
+        let shared = shared.clone();
+        let token = Arc::clone(&token);
+        tokio::spawn(async move {
+            let (reader, writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            if !authenticate(&mut reader, &token).await {
+                tracing::warn!("MCP client {} sent no valid token, disconnecting", peer);
+                return;
+            }
+            tracing::info!("MCP client connected from {}", peer);
+
+            let backend = shared.mcp_backend().await;
+            let changes = shared.watch(&backend).await;
+            let query_budget = shared.config.read().await.query_budget();
+            let mut server = McpServer::shared(backend, changes)
+                .with_limits(Arc::clone(&shared.limits))
+                .with_assume_yes(shared.assume_yes)
+                .with_tool_filter(Arc::clone(&shared.tool_filter))
+                .with_query_budget(query_budget);
+            if let Err(e) = server.run_with(AsyncTransport::new(reader, writer)).await {
+                tracing::warn!("MCP client {} failed: {}", peer, e);
+            }
+            tracing::info!("MCP client {} disconnected", peer);
+        });
+    }
+}
+
+/// Relay stdio to the daemon listening on `addr`, until stdin closes and the
+/// daemon has answered, or the daemon disconnects.
+pub async fn relay_stdio(addr: &str) -> io::Result<()> {
+    let stream = TcpStream::connect(addr).await?;
+    let port = stream.peer_addr()?.port();
+    let token = std::fs::read_to_string(token_path(port)?).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("No token of an MCP daemon on port {}: {}", port, e),
+        )
+    })?;
+    relay(
+        stream,
+        token.trim(),
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+    )
+    .await
+}
+
+/// Send `token`, then relay `input` to `stream` and `stream` to `output`.
+/// Once `input` ends, the write half is shut down so the daemon sees the
+/// client disconnect, and the responses still in flight are drained.
+async fn relay(
+    stream: TcpStream,
+    token: &str,
+    mut input: impl AsyncRead + Unpin,
+    mut output: impl AsyncWrite + Unpin,
+) -> io::Result<()> {
+    let (mut reader, mut writer) = stream.into_split();
+    writer.write_all(format!("{}\n", token).as_bytes()).await?;
+
+    let upstream = async {
+        tokio::io::copy(&mut input, &mut writer).await?;
+        writer.shutdown().await
+    };
+    let downstream = async {
+        tokio::io::copy(&mut reader, &mut output).await?;
+        output.flush().await
+    };
+    tokio::pin!(upstream, downstream);
+    tokio::select! {
+        result = &mut upstream => {
+            result?;
+            downstream.await
+        }
+        // The daemon closed the connection; nothing more can be answered
+        result = &mut downstream => result,
+    }
+}
+
+/// Whether the first line read from a client is `token`. The rest of the
+/// input stays in `reader`.
+async fn authenticate<R: AsyncRead + Unpin>(reader: &mut BufReader<R>, token: &str) -> bool {
+    let mut line = String::new();
+    let read = async {
+        let mut limited = (&mut *reader).take(MAX_TOKEN_LINE);
+        limited.read_line(&mut line).await
+    };
+    match tokio::time::timeout(HANDSHAKE_TIMEOUT, read).await {
+        Ok(Ok(_)) => constant_time_eq(line.trim_end().as_bytes(), token.as_bytes()),
+        _ => false,
+    }
+}
+
+/// Compare without returning early, so the time taken does not tell how
+/// much of a guess was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+/// A random token: 256 bits from hashers seeded by the OS's random source
+fn new_token() -> String {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    (0..4)
+        .map(|i| {
+            let mut hasher = RandomState::new().build_hasher();
+            hasher.write_usize(i);
+            hasher.write_u128(nanos);
+            hasher.write_u32(std::process::id());
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+/// Where the daemon listening on `port` keeps its token
+pub fn token_path(port: u16) -> io::Result<PathBuf> {
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Cannot determine home directory")
+        })?;
+    Ok(PathBuf::from(home)
+        .join(".codegraph")
+        .join(format!("mcp-daemon-{}.token", port)))
+}
+
+/// Write `token` to `path`, readable by the user only
+fn write_token(path: &Path, token: &str) -> io::Result<()> {
+    use std::io::Write;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Created afresh, so the permissions of an older file do not carry over
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(token.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A daemon stand-in that checks the token, then answers the request it
+    /// reads after a delay
+    async fn slow_echo(listener: TcpListener, token: &'static str) -> bool {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (reader, mut writer) = stream.into_split();
+        let mut reader = BufReader::new(reader);
+        if !authenticate(&mut reader, token).await {
+            return false;
+        }
+        let mut request = String::new();
+        reader.read_line(&mut request).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        writer
+            .write_all(format!("answer to {}", request).as_bytes())
+            .await
+            .unwrap();
+        true
+    }
+
+    #[tokio::test]
+    async fn test_relay_drains_responses_after_input_ends() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let daemon = tokio::spawn(slow_echo(listener, "secret"));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut output = Vec::new();
+        relay(stream, "secret", &b"ping\n"[..], &mut output)
+            .await
+            .unwrap();
+
+        assert!(daemon.await.unwrap());
+        assert_eq!(String::from_utf8(output).unwrap(), "answer to ping\n");
+    }
+
+    #[tokio::test]
+    async fn test_daemon_refuses_wrong_token() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let daemon = tokio::spawn(slow_echo(listener, "secret"));
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut output = Vec::new();
+        // Writing to the closed connection may fail as well
+        let _ = relay(stream, "guess", &b"ping\n"[..], &mut output).await;
+
+        assert!(!daemon.await.unwrap());
+        assert!(output.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_authenticate_keeps_the_rest_of_the_input() {
+        let mut reader = BufReader::new(&b"secret\n{\"jsonrpc\":\"2.0\"}\n"[..]);
+        assert!(authenticate(&mut reader, "secret").await);
+        let mut rest = String::new();
+        reader.read_line(&mut rest).await.unwrap();
+        assert_eq!(rest, "{\"jsonrpc\":\"2.0\"}\n");
+
+        let mut reader = BufReader::new(&b"secre\n"[..]);
+        assert!(!authenticate(&mut reader, "secret").await);
+        let mut reader = BufReader::new(&b""[..]);
+        assert!(!authenticate(&mut reader, "secret").await);
+    }
+
+    #[test]
+    fn test_token_file_is_private() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.token");
+        std::fs::write(&path, "stale").unwrap();
+
+        let token = new_token();
+        assert_eq!(token.len(), 64);
+        assert_ne!(token, new_token());
+        write_token(&path, &token).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), token);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+}
@@ -0,0 +1,150 @@
+//! Confirmation of destructive operations (`elicitation/create`).
+//!
+//! Before a tool that destroys knowledge runs, the user is asked to confirm
+//! it through the client, if the client advertises the `elicitation`
+//! capability. Clients that cannot ask the user get a refusal the agent can
+//! relay, unless the server runs with `--yes`, which confirms every operation
+//! without asking.
+
+use super::client_requests::ClientRequests;
+use super::protocol::{ElicitAction, ElicitParams, ElicitResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How long to wait for the user to answer
+const ELICITATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Tools the user must confirm before they run
+const CONFIRMED_TOOLS: &[&str] = &["codegraph_memory_invalidate"];
+
+/// Whether `tool` asks the user before running
+pub fn needs_confirmation(tool: &str) -> bool {
+    CONFIRMED_TOOLS.contains(&tool)
+}
+
+/// Asks the user to confirm operations, through the client if it can.
+pub struct Confirmer {
+    /// Whether the client advertised the `elicitation` capability
+    supported: AtomicBool,
+    /// Confirm without asking (`--yes`)
+    assume_yes: bool,
+    requests: Arc<ClientRequests>,
+}
+
+impl Confirmer {
+    pub fn new(requests: Arc<ClientRequests>) -> Self {
+        Self {
+            supported: AtomicBool::new(false),
+            assume_yes: false,
+            requests,
+        }
+    }
+
+    pub fn with_assume_yes(mut self, assume_yes: bool) -> Self {
+        self.assume_yes = assume_yes;
+        self
+    }
+
+    pub fn set_supported(&self, supported: bool) {
+        self.supported.store(supported, Ordering::Relaxed);
+    }
+
+    /// Ask the user to confirm `message`. `Ok` means go ahead; the error
+    /// explains why the operation must not run.
+    pub async fn confirm(&self, message: &str) -> Result<(), String> {
+        if self.assume_yes {
+            return Ok(());
+        }
+        if !self.supported.load(Ordering::Relaxed) || !self.requests.is_attached() {
+            return Err(
+                "Not confirmed: this operation needs the user's confirmation, which the client \
+                 cannot ask for. The server allows it without asking when started with --yes."
+                    .to_string(),
+            );
+        }
+
+        let params = ElicitParams {
+            message: message.to_string(),
+            requested_schema: serde_json::json!({
+                "type": "object",
+                "properties": {
+                    "confirm": {
+                        "type": "boolean",
+                        "title": "Confirm",
+                        "description": "Go ahead with this operation",
+                        "default": true
+                    }
+                },
+                "required": ["confirm"]
+            }),
+        };
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        let result = self
+            .requests
+            .send("elicitation/create", Some(params), ELICITATION_TIMEOUT)
+            .await
+            .map_err(|e| format!("Not confirmed: {}", e))?;
+        let result: ElicitResult = serde_json::from_value(result)
+            .map_err(|e| format!("Invalid elicitation result: {}", e))?;
+        if is_confirmed(&result) {
+            Ok(())
+        } else {
+            Err("Not confirmed: the user declined the operation".to_string())
+        }
+    }
+}
+
+/// Whether the user accepted, without unticking the confirmation
+fn is_confirmed(result: &ElicitResult) -> bool {
+    let unticked = result
+        .content
+        .as_ref()
+        .and_then(|content| content.get("confirm"))
+        .and_then(|confirm| confirm.as_bool())
+        == Some(false);
+    result.action == ElicitAction::Accept && !unticked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(json: &str) -> ElicitResult {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_is_confirmed() {
+        assert!(is_confirmed(&result(
+            r#"{"action":"accept","content":{"confirm":true}}"#
+        )));
+        assert!(is_confirmed(&result(r#"{"action":"accept"}"#)));
+        assert!(!is_confirmed(&result(
+            r#"{"action":"accept","content":{"confirm":false}}"#
+        )));
+        assert!(!is_confirmed(&result(r#"{"action":"decline"}"#)));
+        assert!(!is_confirmed(&result(r#"{"action":"cancel"}"#)));
+    }
+
+    #[tokio::test]
+    async fn test_confirm_without_elicitation() {
+        // The client did not advertise elicitation
+        let confirmer = Confirmer::new(Arc::new(ClientRequests::new()));
+        let refused = confirmer.confirm("Invalidate memory?").await;
+        assert!(refused.unwrap_err().contains("--yes"));
+
+        let confirmer = Confirmer::new(Arc::new(ClientRequests::new()));
+        confirmer.set_supported(true);
+        // Not attached to a client: nobody to ask
+        let refused = confirmer.confirm("Invalidate memory?").await;
+        assert!(refused.unwrap_err().contains("--yes"));
+
+        // With --yes, such clients are not asked
+        let confirmer = Confirmer::new(Arc::new(ClientRequests::new())).with_assume_yes(true);
+        assert!(confirmer.confirm("Invalidate memory?").await.is_ok());
+
+        assert!(needs_confirmation("codegraph_memory_invalidate"));
+        assert!(!needs_confirmation("codegraph_memory_get"));
+    }
+}
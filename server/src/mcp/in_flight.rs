@@ -0,0 +1,114 @@
+//! Cancellation of in-flight tool calls (`notifications/cancelled`).
+//!
+//! Requests are read on their own task while the event loop handles them one
+//! at a time, so a cancellation can arrive while its call runs or before it
+//! has started. Each tool call is given a [`CancellationToken`] when it is
+//! read; the notification cancels it and the tool stops at its next check.
+
+use super::protocol::{CancelledParams, JsonRpcRequest};
+use crate::cancellation::CancellationToken;
+use dashmap::DashMap;
+use serde_json::Value;
+
+/// Cancellation tokens of the tool calls read and not yet answered, by
+/// request id.
+#[derive(Debug, Default)]
+pub struct InFlightCalls {
+    tokens: DashMap<String, CancellationToken>,
+}
+
+impl InFlightCalls {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track `request` if it is a cancellable tool call, or cancel the call a
+    /// `notifications/cancelled` refers to. Returns whether the message was
+    /// such a notification, which needs no further handling.
+    pub fn observe(&self, request: &JsonRpcRequest) -> bool {
+        match request.method.as_str() {
+            "tools/call" => {
+                if let Some(id) = &request.id {
+                    self.tokens.insert(key(id), CancellationToken::new());
+                }
+                false
+            }
+            "notifications/cancelled" => {
+                let params = request
+                    .params
+                    .clone()
+                    .and_then(|p| serde_json::from_value::<CancelledParams>(p).ok());
+                if let Some(params) = params {
+                    tracing::info!(
+                        "Cancelling request {}: {}",
+                        params.request_id,
+                        params.reason.as_deref().unwrap_or("no reason given")
+                    );
+                    // Unknown ids are calls already answered; nothing to do
+                    if let Some(token) = self.tokens.get(&key(&params.request_id)) {
+                        token.cancel();
+                    }
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Token of the call with `id`; a fresh one if it is not tracked.
+    pub fn token(&self, id: Option<&Value>) -> CancellationToken {
+        id.and_then(|id| self.tokens.get(&key(id)).map(|t| t.value().clone()))
+            .unwrap_or_default()
+    }
+
+    /// Stop tracking the call with `id`, returning whether it was cancelled.
+    pub fn finish(&self, id: Option<&Value>) -> bool {
+        id.and_then(|id| self.tokens.remove(&key(id)))
+            .is_some_and(|(_, token)| token.is_cancelled())
+    }
+}
+
+/// Ids are matched by their JSON form, so `1` and `"1"` stay distinct.
+fn key(id: &Value) -> String {
+    id.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(json: Value) -> JsonRpcRequest {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_cancel_in_flight_call() {
+        let calls = InFlightCalls::new();
+        let call = request(serde_json::json!({
+            "jsonrpc": "2.0", "id": 3, "method": "tools/call",
+            "params": {"name": "codegraph_symbol_search"}
+        }));
+        assert!(!calls.observe(&call));
+        let token = calls.token(call.id.as_ref());
+        assert!(!token.is_cancelled());
+
+        // Another id leaves the call running
+        let other = request(serde_json::json!({
+            "jsonrpc": "2.0", "method": "notifications/cancelled",
+            "params": {"requestId": "3"}
+        }));
+        assert!(calls.observe(&other));
+        assert!(!token.is_cancelled());
+
+        let cancel = request(serde_json::json!({
+            "jsonrpc": "2.0", "method": "notifications/cancelled",
+            "params": {"requestId": 3, "reason": "User requested cancellation"}
+        }));
+        assert!(calls.observe(&cancel));
+        assert!(token.is_cancelled());
+
+        assert!(calls.finish(call.id.as_ref()));
+        assert!(!calls.finish(call.id.as_ref()));
+        assert!(!calls.token(call.id.as_ref()).is_cancelled());
+    }
+}
@@ -0,0 +1,278 @@
+//! Concurrency limits on tool calls.
+//!
+//! Tool calls run concurrently inside `batch_query`, and for every client of
+//! a shared daemon. Each call takes a permit from a server-wide semaphore, and
+//! graph-wide analyses one from a small per-tool semaphore too, so an agent
+//! firing dozens of traversals cannot starve the graph lock. Calls over the
+//! limits queue in order and fail with a retryable error if they wait too
+//! long, or stop waiting when cancelled. The per-tool caps default to
+//! `HEAVY_TOOL_CONCURRENCY` for `HEAVY_TOOLS` and can be set for any tool.
+
+use crate::cancellation::CancellationToken;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Tool calls running at once, unless configured otherwise
+pub const DEFAULT_MAX_CONCURRENT_TOOLS: usize = 4;
+
+/// Calls of one graph-wide analysis running at once, unless configured
+/// otherwise
+pub const HEAVY_TOOL_CONCURRENCY: usize = 2;
+
+/// Tools that walk or score the whole graph while holding its lock
+pub const HEAVY_TOOLS: &[&str] = &[
+    "codegraph_traverse_graph",
+    "codegraph_reaches",
+    "codegraph_find_path",
+    "codegraph_find_cycles",
+    "codegraph_analyze_coupling",
+    "codegraph_analyze_impact",
+    "codegraph_get_call_graph",
+    "codegraph_get_dependency_graph",
+    "codegraph_find_duplicates",
+    "codegraph_cluster_symbols",
+    "codegraph_find_unused_code",
+    "codegraph_get_hot_symbols",
+];
+
+/// How long a call waits for a permit before failing
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Semaphores bounding the tool calls that run at once
+#[derive(Debug)]
+pub struct ToolLimits {
+    max_concurrent: usize,
+    calls: Arc<Semaphore>,
+    per_tool: HashMap<String, Arc<Semaphore>>,
+    queue_timeout: Duration,
+}
+
+/// Held while a tool call runs; dropping it lets the next queued call start
+#[derive(Debug)]
+pub struct ToolPermit {
+    _tool: Option<OwnedSemaphorePermit>,
+    _call: OwnedSemaphorePermit,
+}
+
+impl Default for ToolLimits {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_TOOLS)
+    }
+}
+
+impl ToolLimits {
+    /// Limits running at most `max_concurrent` tool calls at once (at least
+    /// one)
+    pub fn new(max_concurrent: usize) -> Self {
+        let max_concurrent = max_concurrent.max(1);
+        let limits = Self {
+            max_concurrent,
+            calls: Arc::new(Semaphore::new(max_concurrent)),
+            per_tool: HashMap::new(),
+            queue_timeout: QUEUE_TIMEOUT,
+        };
+        HEAVY_TOOLS.iter().fold(limits, |limits, tool| {
+            limits.with_tool_limit(tool, HEAVY_TOOL_CONCURRENCY)
+        })
+    }
+
+    /// Run at most `max_concurrent` calls of `tool` at once (at least one,
+    /// and no more than the server-wide limit)
+    pub fn with_tool_limit(mut self, tool: &str, max_concurrent: usize) -> Self {
+        let permits = max_concurrent.clamp(1, self.max_concurrent);
+        self.per_tool
+            .insert(tool.to_string(), Arc::new(Semaphore::new(permits)));
+        self
+    }
+
+    /// Parse a `tool=N` per-tool limit, as given on the command line. The
+    /// `codegraph_` prefix of the tool name may be omitted.
+    pub fn parse_tool_limit(spec: &str) -> Result<(String, usize), String> {
+        let (tool, max) = spec
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid tool limit '{}': expected tool=N", spec))?;
+        let max = max
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid tool limit '{}': N must be a number", spec))?;
+        let tool = tool.trim();
+        let tool = if tool.starts_with("codegraph_") {
+            tool.to_string()
+        } else {
+            format!("codegraph_{}", tool)
+        };
+        Ok((tool, max))
+    }
+
+    pub fn with_queue_timeout(mut self, queue_timeout: Duration) -> Self {
+        self.queue_timeout = queue_timeout;
+        self
+    }
+
+    /// Wait for the permits to run `tool`, in the order calls arrived, unless
+    /// `cancel` is cancelled first
+    pub async fn acquire(
+        &self,
+        tool: &str,
+        cancel: &CancellationToken,
+    ) -> Result<ToolPermit, String> {
+        let wait = async {
+            // The tool's own permit first, so calls queued behind a busy
+            // analysis do not hold server-wide permits other tools could use
+            let tool_permit = match self.per_tool.get(tool) {
+                Some(semaphore) => Some(Arc::clone(semaphore).acquire_owned().await.ok()?),
+                None => None,
+            };
+            let call_permit = Arc::clone(&self.calls).acquire_owned().await.ok()?;
+            Some(ToolPermit {
+                _tool: tool_permit,
+                _call: call_permit,
+            })
+        };
+
+        let waited = tokio::select! {
+            waited = tokio::time::timeout(self.queue_timeout, wait) => waited,
+            _ = cancel.cancelled() => return Err("Cancelled".to_string()),
+        };
+        match waited {
+            Ok(Some(permit)) => Ok(permit),
+            Ok(None) => Err("Tool limits are closed".to_string()),
+            Err(_) => {
+                tracing::warn!(
+                    "{} waited over {:?} for a free slot",
+                    tool,
+                    self.queue_timeout
+                );
+                Err(format!(
+                    "Server busy: too many tool calls running, {} was not started. Retry shortly.",
+                    tool
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_heavy_tools_are_capped() {
+        let limits = ToolLimits::new(4).with_queue_timeout(Duration::from_millis(50));
+        let cancel = CancellationToken::new();
+
+        let first = limits
+            .acquire("codegraph_traverse_graph", &cancel)
+            .await
+            .unwrap();
+        let _second = limits
+            .acquire("codegraph_traverse_graph", &cancel)
+            .await
+            .unwrap();
+        let third = limits.acquire("codegraph_traverse_graph", &cancel).await;
+        assert!(third.unwrap_err().starts_with("Server busy"));
+
+        // Other tools still run, up to the server-wide limit
+        let _search = limits
+            .acquire("codegraph_symbol_search", &cancel)
+            .await
+            .unwrap();
+        let _context = limits
+            .acquire("codegraph_get_ai_context", &cancel)
+            .await
+            .unwrap();
+        assert!(limits
+            .acquire("codegraph_memory_get", &cancel)
+            .await
+            .is_err());
+
+        drop(first);
+        assert!(limits
+            .acquire("codegraph_traverse_graph", &cancel)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_queued_call_starts_when_permit_is_released() {
+        let limits = Arc::new(ToolLimits::new(1));
+        let cancel = CancellationToken::new();
+        let running = limits
+            .acquire("codegraph_symbol_search", &cancel)
+            .await
+            .unwrap();
+
+        let queued = tokio::spawn({
+            let limits = Arc::clone(&limits);
+            async move {
+                limits
+                    .acquire("codegraph_get_callers", &CancellationToken::new())
+                    .await
+                    .is_ok()
+            }
+        });
+        tokio::task::yield_now().await;
+        assert!(!queued.is_finished());
+
+        drop(running);
+        assert!(queued.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tool_limits_are_configurable() {
+        let (tool, max) = ToolLimits::parse_tool_limit("symbol_search=1").unwrap();
+        assert_eq!((tool.as_str(), max), ("codegraph_symbol_search", 1));
+        assert!(ToolLimits::parse_tool_limit("symbol_search").is_err());
+        assert!(ToolLimits::parse_tool_limit("symbol_search=many").is_err());
+
+        let limits = ToolLimits::new(4)
+            .with_queue_timeout(Duration::from_millis(50))
+            .with_tool_limit(&tool, max)
+            .with_tool_limit("codegraph_traverse_graph", 3);
+        let cancel = CancellationToken::new();
+
+        let _search = limits
+            .acquire("codegraph_symbol_search", &cancel)
+            .await
+            .unwrap();
+        assert!(limits
+            .acquire("codegraph_symbol_search", &cancel)
+            .await
+            .is_err());
+        let _traversals = [
+            limits
+                .acquire("codegraph_traverse_graph", &cancel)
+                .await
+                .unwrap(),
+            limits
+                .acquire("codegraph_traverse_graph", &cancel)
+                .await
+                .unwrap(),
+            limits
+                .acquire("codegraph_traverse_graph", &cancel)
+                .await
+                .unwrap(),
+        ];
+    }
+
+    #[tokio::test]
+    async fn test_cancel_stops_waiting_for_a_permit() {
+        let limits = ToolLimits::new(1);
+        let _running = limits
+            .acquire("codegraph_symbol_search", &CancellationToken::new())
+            .await
+            .unwrap();
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let queued = tokio::time::timeout(
+            Duration::from_secs(5),
+            limits.acquire("codegraph_get_callers", &cancel),
+        )
+        .await
+        .expect("a cancelled call stops waiting before the queue timeout");
+        assert_eq!(queued.unwrap_err(), "Cancelled");
+    }
+}
@@ -0,0 +1,162 @@
+//! Logging to the client (`notifications/message`).
+//!
+//! A tracing layer forwards the server's log events to the client once it is
+//! initialized, from the level set with `logging/setLevel` up (warning until
+//! the client sets one), so indexing errors reach the client instead of only
+//! stderr.
+
+use super::protocol::{JsonRpcNotification, LoggingLevel, LoggingMessageParams};
+use super::transport::Outgoing;
+use serde_json::Value;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::{Arc, OnceLock};
+use tokio::sync::mpsc;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Sends log messages to the client at or above its chosen level.
+#[derive(Debug)]
+pub struct McpLogger {
+    /// Least severe level sent, as `LoggingLevel as u8`
+    level: AtomicU8,
+    notifications: OnceLock<mpsc::UnboundedSender<Outgoing>>,
+}
+
+impl Default for McpLogger {
+    fn default() -> Self {
+        Self {
+            level: AtomicU8::new(LoggingLevel::Warning as u8),
+            notifications: OnceLock::new(),
+        }
+    }
+}
+
+impl McpLogger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_level(&self, level: LoggingLevel) {
+        self.level.store(level as u8, Ordering::Relaxed);
+    }
+
+    /// Start sending messages through `notifications`, once the client is
+    /// initialized. Nothing is sent before.
+    pub fn attach(&self, notifications: mpsc::UnboundedSender<Outgoing>) {
+        let _ = self.notifications.set(notifications);
+    }
+
+    pub fn enabled(&self, level: LoggingLevel) -> bool {
+        self.notifications.get().is_some() && level as u8 >= self.level.load(Ordering::Relaxed)
+    }
+
+    pub fn log(&self, level: LoggingLevel, logger: Option<String>, data: Value) {
+        if !self.enabled(level) {
+            return;
+        }
+        let Some(notifications) = self.notifications.get() else {
+            return;
+        };
+        let params = LoggingMessageParams {
+            level,
+            logger,
+            data,
+        };
+        let notification =
+            JsonRpcNotification::new("notifications/message", serde_json::to_value(params).ok());
+        let _ = notifications.send(notification.into());
+    }
+}
+
+/// Tracing layer feeding events to an [`McpLogger`]
+pub struct McpLogLayer {
+    logger: Arc<McpLogger>,
+}
+
+impl McpLogLayer {
+    pub fn new(logger: Arc<McpLogger>) -> Self {
+        Self { logger }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for McpLogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let level = match *event.metadata().level() {
+            Level::ERROR => LoggingLevel::Error,
+            Level::WARN => LoggingLevel::Warning,
+            Level::INFO => LoggingLevel::Info,
+            _ => LoggingLevel::Debug,
+        };
+        if !self.logger.enabled(level) {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        self.logger.log(
+            level,
+            Some(event.metadata().target().to_string()),
+            Value::String(visitor.message + &visitor.fields),
+        );
+    }
+}
+
+/// Renders an event as its message followed by its other fields
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_log_layer_forwards_events() {
+        let logger = Arc::new(McpLogger::new());
+        let subscriber = tracing_subscriber::registry().with(McpLogLayer::new(logger.clone()));
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        tracing::subscriber::with_default(subscriber, || {
+            // Nothing is sent before the client is initialized
+            tracing::error!("too early");
+            logger.attach(tx);
+
+            tracing::info!("below the default level");
+            tracing::warn!(files = 3, "Failed to parse");
+            logger.set_level(LoggingLevel::Error);
+            tracing::warn!("below the new level");
+        });
+
+        let Outgoing::Notification(notification) = rx.try_recv().unwrap() else {
+            panic!("expected a notification");
+        };
+        assert_eq!(notification.method, "notifications/message");
+        let params = notification.params.unwrap();
+        assert_eq!(params["level"], "warning");
+        assert_eq!(params["data"], "Failed to parse files=3");
+        assert_eq!(params["logger"], "codegraph_lsp::mcp::logging::tests");
+        assert!(rx.try_recv().is_err());
+    }
+}
@@ -0,0 +1,153 @@
+//! Per-tool usage metrics (`codegraph://metrics`, `codegraph_server_stats`).
+//!
+//! Every tool call, batched queries included, is counted with its latency and
+//! whether it failed, so a slow agent session can be traced to the tools it
+//! spent its time in. Latency percentiles cover each tool's recent calls.
+
+use dashmap::DashMap;
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Latencies kept per tool for percentiles
+const LATENCY_SAMPLES: usize = 256;
+
+#[derive(Debug, Default)]
+struct ToolStats {
+    calls: u64,
+    errors: u64,
+    total_ms: u64,
+    max_ms: u64,
+    /// Latencies of the most recent calls, oldest first
+    recent_ms: VecDeque<u64>,
+}
+
+impl ToolStats {
+    fn to_json(&self, tool: &str) -> Value {
+        let mut sorted: Vec<u64> = self.recent_ms.iter().copied().collect();
+        sorted.sort_unstable();
+        serde_json::json!({
+            "tool": tool,
+            "calls": self.calls,
+            "errors": self.errors,
+            "error_rate": self.errors as f64 / self.calls.max(1) as f64,
+            "total_ms": self.total_ms,
+            "avg_ms": self.total_ms / self.calls.max(1),
+            "p50_ms": percentile(&sorted, 50),
+            "p95_ms": percentile(&sorted, 95),
+            "max_ms": self.max_ms,
+        })
+    }
+}
+
+/// The value below which `percentile`% of the `sorted` latencies fall
+fn percentile(sorted: &[u64], percentile: usize) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    sorted[(sorted.len() - 1) * percentile / 100]
+}
+
+/// Call counts, latencies and errors of each tool since the server started
+#[derive(Debug)]
+pub struct ToolMetrics {
+    started: Instant,
+    tools: DashMap<String, ToolStats>,
+}
+
+impl Default for ToolMetrics {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            tools: DashMap::new(),
+        }
+    }
+}
+
+impl ToolMetrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a call of `tool` that took `elapsed`. A result reporting an
+    /// `error` counts as failed, like an `Err`.
+    pub fn record(&self, tool: &str, elapsed: Duration, outcome: &Result<Value, String>) {
+        let failed = match outcome {
+            Ok(result) => result.get("error").is_some(),
+            Err(_) => true,
+        };
+        let ms = elapsed.as_millis() as u64;
+
+        let mut stats = self.tools.entry(tool.to_string()).or_default();
+        stats.calls += 1;
+        stats.errors += u64::from(failed);
+        stats.total_ms += ms;
+        stats.max_ms = stats.max_ms.max(ms);
+        if stats.recent_ms.len() == LATENCY_SAMPLES {
+            stats.recent_ms.pop_front();
+        }
+        stats.recent_ms.push_back(ms);
+    }
+
+    /// Metrics of every tool called so far, the most time-consuming first
+    pub fn snapshot(&self) -> Value {
+        let mut tools: Vec<Value> = self
+            .tools
+            .iter()
+            .map(|entry| entry.value().to_json(entry.key()))
+            .collect();
+        tools.sort_by_key(|t| std::cmp::Reverse(t["total_ms"].as_u64().unwrap_or(0)));
+
+        let calls: u64 = self.tools.iter().map(|entry| entry.calls).sum();
+        let errors: u64 = self.tools.iter().map(|entry| entry.errors).sum();
+        serde_json::json!({
+            "uptime_secs": self.started.elapsed().as_secs(),
+            "total_calls": calls,
+            "total_errors": errors,
+            "tools": tools,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_snapshot() {
+        let metrics = ToolMetrics::new();
+        for ms in [10, 20, 30, 40] {
+            metrics.record(
+                "codegraph_get_callers",
+                Duration::from_millis(ms),
+                &Ok(serde_json::json!({ "callers": [] })),
+            );
+        }
+        metrics.record(
+            "codegraph_symbol_search",
+            Duration::from_millis(5),
+            &Err("Missing 'query' parameter".to_string()),
+        );
+        metrics.record(
+            "codegraph_symbol_search",
+            Duration::from_millis(15),
+            &Ok(serde_json::json!({ "error": "Symbol not found" })),
+        );
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot["total_calls"], 6);
+        assert_eq!(snapshot["total_errors"], 2);
+
+        // Sorted by total time
+        let callers = &snapshot["tools"][0];
+        assert_eq!(callers["tool"], "codegraph_get_callers");
+        assert_eq!(callers["avg_ms"], 25);
+        assert_eq!(callers["p50_ms"], 20);
+        assert_eq!(callers["max_ms"], 40);
+        assert_eq!(callers["error_rate"], 0.0);
+
+        let search = &snapshot["tools"][1];
+        assert_eq!(search["errors"], 2);
+        assert_eq!(search["error_rate"], 1.0);
+    }
+}
@@ -10,10 +10,30 @@
 //! ```
 //!
 //! The MCP server communicates via stdio using JSON-RPC 2.0.
+//!
+//! To share one index with the editor, the LSP server can serve MCP on a
+//! socket as well (see [`daemon`]):
+//!
+//! ```bash
+//! codegraph-lsp --mcp-listen 127.0.0.1:7424
+//! codegraph-lsp --mcp --connect 127.0.0.1:7424
+//! ```
 
+pub mod client_requests;
+pub mod completion;
+pub mod daemon;
+pub mod elicitation;
+pub mod in_flight;
+pub mod limits;
+pub mod logging;
+pub mod metrics;
+pub mod pagination;
+pub mod progress;
 pub mod protocol;
 pub mod resources;
+pub mod sampling;
 pub mod server;
+pub mod tool_filter;
 pub mod tools;
 pub mod transport;
 
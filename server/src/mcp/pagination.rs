@@ -0,0 +1,148 @@
+//! Cursor pagination (`cursor` / `nextCursor`).
+//!
+//! `tools/list` and `resources/list` return pages of `LIST_PAGE_SIZE` items.
+//! Results of read-only tools too large for one response are split on their
+//! largest array: the result carries `next_cursor`, and calling the tool again
+//! with the same arguments plus `cursor` returns the following items. Tools
+//! that write are never split, since calling one again would write again.
+//! Cursors are offsets, opaque to clients.
+
+use serde_json::Value;
+
+/// Items per page of `tools/list` and `resources/list`
+pub const LIST_PAGE_SIZE: usize = 50;
+
+/// Serialized size above which a tool result is split into pages
+pub const MAX_RESULT_BYTES: usize = 64 * 1024;
+
+const CURSOR_PREFIX: &str = "offset-";
+
+pub fn encode_cursor(offset: usize) -> String {
+    format!("{}{}", CURSOR_PREFIX, offset)
+}
+
+pub fn decode_cursor(cursor: &str) -> Result<usize, String> {
+    cursor
+        .strip_prefix(CURSOR_PREFIX)
+        .and_then(|offset| offset.parse().ok())
+        .ok_or_else(|| format!("Invalid cursor: {}", cursor))
+}
+
+/// The page of `items` starting at `cursor`, and the cursor of the next page
+/// if there is one.
+pub fn paginate<T>(
+    items: Vec<T>,
+    cursor: Option<&str>,
+    page_size: usize,
+) -> Result<(Vec<T>, Option<String>), String> {
+    let offset = cursor.map(decode_cursor).transpose()?.unwrap_or(0);
+    let total = items.len();
+    let page: Vec<T> = items.into_iter().skip(offset).take(page_size).collect();
+    let end = offset + page.len();
+    Ok((page, (end < total).then(|| encode_cursor(end))))
+}
+
+/// Split `result` on its largest array when it serializes to more than
+/// `max_bytes`, or when a later page (`offset` > 0) is asked for. The page
+/// keeps the other fields and reports what it holds under `page`.
+pub fn paginate_result(result: Value, offset: usize, max_bytes: usize) -> Value {
+    let Value::Object(mut map) = result else {
+        return result;
+    };
+    if offset == 0 && serialized_len(&map) <= max_bytes {
+        return Value::Object(map);
+    }
+    let Some(field) = map
+        .iter()
+        .filter(|(_, value)| value.is_array())
+        .max_by_key(|(_, value)| serialized_len(value))
+        .map(|(key, _)| key.clone())
+    else {
+        return Value::Object(map);
+    };
+    let Some(Value::Array(items)) = map.remove(&field) else {
+        return Value::Object(map);
+    };
+
+    // Fill the page with whole items, taking at least one
+    let total = items.len();
+    let mut budget = max_bytes.saturating_sub(serialized_len(&map));
+    let mut page = Vec::new();
+    for item in items.into_iter().skip(offset) {
+        let len = serialized_len(&item) + 1;
+        if !page.is_empty() && len > budget {
+            break;
+        }
+        budget = budget.saturating_sub(len);
+        page.push(item);
+    }
+
+    let end = offset.min(total) + page.len();
+    map.insert(
+        "page".to_string(),
+        serde_json::json!({
+            "field": field,
+            "offset": offset,
+            "returned": page.len(),
+            "total": total,
+        }),
+    );
+    map.insert(field, Value::Array(page));
+    if end < total {
+        map.insert("next_cursor".to_string(), Value::String(encode_cursor(end)));
+        map.insert(
+            "note".to_string(),
+            Value::String(
+                "Result split into pages: call the tool again with the same arguments and \
+                 cursor=next_cursor for more."
+                    .to_string(),
+            ),
+        );
+    }
+    Value::Object(map)
+}
+
+fn serialized_len<T: serde::Serialize>(value: &T) -> usize {
+    serde_json::to_string(value).map(|s| s.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paginate_list() {
+        let (page, next) = paginate((0..5).collect(), None, 2).unwrap();
+        assert_eq!(page, vec![0, 1]);
+        assert_eq!(next.as_deref(), Some("offset-2"));
+
+        let (page, next) = paginate((0..5).collect(), Some("offset-4"), 2).unwrap();
+        assert_eq!(page, vec![4]);
+        assert!(next.is_none());
+
+        assert!(paginate((0..5).collect::<Vec<i32>>(), Some("4"), 2).is_err());
+    }
+
+    #[test]
+    fn test_paginate_result() {
+        let small = serde_json::json!({ "results": [1, 2, 3], "total": 3 });
+        assert_eq!(paginate_result(small.clone(), 0, 1024), small);
+
+        let items: Vec<String> = (0..20).map(|i| format!("item-{:04}", i)).collect();
+        let large = serde_json::json!({ "results": items, "tags": ["a"], "total": 20 });
+
+        // Each item takes 12 bytes plus a comma
+        let first = paginate_result(large.clone(), 0, 100);
+        let returned = first["results"].as_array().unwrap().len();
+        assert!(returned > 0 && returned < 20);
+        assert_eq!(first["total"], 20);
+        assert_eq!(first["page"]["field"], "results");
+        let offset = decode_cursor(first["next_cursor"].as_str().unwrap()).unwrap();
+        assert_eq!(offset, returned);
+
+        let last = paginate_result(large, 15, 1024);
+        assert_eq!(last["results"][0], "item-0015");
+        assert_eq!(last["page"]["returned"], 5);
+        assert!(last.get("next_cursor").is_none());
+    }
+}
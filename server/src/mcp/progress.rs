@@ -0,0 +1,156 @@
+//! Progress notifications (`notifications/progress`) for long-running tools.
+//!
+//! A client asks for progress by sending `_meta.progressToken` with a tool
+//! call. Indexing, reindexing and git mining then report how far they got
+//! while the call runs. Reports are queued with the server's responses and
+//! written in order by a background task, so callers can report from
+//! synchronous code and a call's progress precedes its response. Without a token, handles are
+//! no-ops. A handle also carries its call's cancellation, which git mining
+//! checks between commits and indexing between files.
+
+use super::protocol::JsonRpcNotification;
+use super::transport::Outgoing;
+use crate::cancellation::CancellationToken;
+use crate::git_mining::MiningProgress;
+use serde_json::Value;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc;
+
+/// Progress of one request, identified by the client's progress token.
+#[derive(Debug)]
+pub struct McpProgress {
+    sender: Option<mpsc::UnboundedSender<Outgoing>>,
+    token: Value,
+    /// Units of work done so far, across everything reported under the
+    /// token; reports must increase.
+    done: AtomicU64,
+    cancel: CancellationToken,
+}
+
+impl McpProgress {
+    /// Report to `sender` under `token`, or nothing without a token.
+    pub fn new(sender: mpsc::UnboundedSender<Outgoing>, token: Option<Value>) -> Self {
+        match token {
+            Some(token) => Self {
+                sender: Some(sender),
+                token,
+                done: AtomicU64::new(0),
+                cancel: CancellationToken::new(),
+            },
+            None => Self::disabled(),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            sender: None,
+            token: Value::Null,
+            done: AtomicU64::new(0),
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// This handle, reporting the work as cancelled once `cancel` is.
+    pub fn with_cancellation(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+
+    fn send(&self, progress: u64, total: Option<u64>, message: String) {
+        let Some(sender) = &self.sender else {
+            return;
+        };
+        let mut params = serde_json::json!({
+            "progressToken": self.token,
+            "progress": progress,
+            "message": message,
+        });
+        if let Some(total) = total {
+            params["total"] = total.into();
+        }
+        let _ =
+            sender.send(JsonRpcNotification::new("notifications/progress", Some(params)).into());
+    }
+
+    /// Count one unit of work and report it with `message`.
+    pub fn step(&self, message: impl Into<String>) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        self.send(done, None, message.into());
+    }
+
+    /// Count one unit of work, reporting every `every` units with the
+    /// message `message` builds from the count.
+    pub fn tick(&self, every: u64, message: impl FnOnce(u64) -> String) {
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        if every > 0 && done % every == 0 {
+            self.send(done, None, message(done));
+        }
+    }
+}
+
+impl MiningProgress for McpProgress {
+    fn commit_processed(&self, processed: usize, total: usize) {
+        // Each commit is a unit counted on from earlier work under the same
+        // token, such as indexing, so that progress never goes backwards
+        let done = self.done.fetch_add(1, Ordering::Relaxed) + 1;
+        let before = done.saturating_sub(processed as u64);
+        self.send(
+            done,
+            Some(before + total as u64),
+            format!("Processed {processed}/{total} commits"),
+        );
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_reports_under_token() {
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let progress = McpProgress::new(sender.clone(), Some(Value::from("tok-1")));
+        progress.tick(2, |n| format!("Indexed {n} files"));
+        progress.tick(2, |n| format!("Indexed {n} files"));
+        progress.step("Building indexes");
+        progress.commit_processed(1, 10);
+
+        let params: Vec<Value> = std::iter::from_fn(|| receiver.try_recv().ok())
+            .map(|message| {
+                let Outgoing::Notification(n) = message else {
+                    panic!("expected a notification, got {message:?}");
+                };
+                assert_eq!(n.method, "notifications/progress");
+                n.params.unwrap()
+            })
+            .collect();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params[0]["progressToken"], "tok-1");
+        assert_eq!(params[0]["progress"], 2);
+        assert_eq!(params[0]["message"], "Indexed 2 files");
+        assert_eq!(params[1]["progress"], 3);
+        assert!(params[1].get("total").is_none());
+        // Mining counts on from indexing: 3 units, then commit 1 of 10
+        assert_eq!(params[2]["progress"], 4);
+        assert_eq!(params[2]["total"], 13);
+        assert_eq!(params[2]["message"], "Processed 1/10 commits");
+
+        // Without a token nothing is sent
+        McpProgress::new(sender, None).step("Building indexes");
+        assert!(receiver.try_recv().is_err());
+
+        let cancel = CancellationToken::new();
+        let progress = McpProgress::disabled().with_cancellation(cancel.clone());
+        assert!(!progress.is_cancelled());
+        cancel.cancel();
+        assert!(progress.is_cancelled());
+    }
+}
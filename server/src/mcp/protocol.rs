@@ -48,6 +48,92 @@ impl JsonRpcResponse {
     }
 }
 
+/// JSON-RPC 2.0 notification (a message without an id, expecting no reply)
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// JSON-RPC 2.0 request sent by the server to the client
+#[derive(Debug, Clone, Serialize)]
+pub struct JsonRpcOutgoingRequest {
+    pub jsonrpc: String,
+    pub id: Value,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcOutgoingRequest {
+    pub fn new(id: Value, method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+/// JSON-RPC 2.0 response from the client to a server request
+#[derive(Debug, Clone, Deserialize)]
+pub struct JsonRpcClientResponse {
+    pub id: Value,
+    #[serde(default)]
+    pub result: Option<Value>,
+    #[serde(default)]
+    pub error: Option<Value>,
+}
+
+/// A message read from the client: a request or notification, or the
+/// response to a request the server sent
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum IncomingMessage {
+    Request(JsonRpcRequest),
+    Response(JsonRpcClientResponse),
+}
+
+/// A line of input: one message, or a batch of them sent in one write.
+/// Batch members that are not valid messages are kept as the error to
+/// answer them with, so the rest of the batch is still handled.
+#[derive(Debug, Clone)]
+pub enum Incoming {
+    Message(IncomingMessage),
+    Batch(Vec<Result<IncomingMessage, JsonRpcError>>),
+}
+
+impl Incoming {
+    pub fn parse(line: &str) -> serde_json::Result<Self> {
+        match serde_json::from_str(line)? {
+            Value::Array(members) => Ok(Incoming::Batch(
+                members
+                    .into_iter()
+                    .map(|member| {
+                        serde_json::from_value(member).map_err(|e| {
+                            JsonRpcError::invalid_request(format!("Invalid request: {}", e))
+                        })
+                    })
+                    .collect(),
+            )),
+            message => Ok(Incoming::Message(serde_json::from_value(message)?)),
+        }
+    }
+}
+
 /// JSON-RPC 2.0 error
 #[derive(Debug, Clone, Serialize)]
 pub struct JsonRpcError {
@@ -99,6 +185,51 @@ impl JsonRpcError {
     }
 }
 
+/// MCP revisions the server speaks, newest first
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// Revision that introduced structured tool output (`outputSchema`,
+/// `structuredContent`)
+const STRUCTURED_OUTPUT_VERSION: &str = "2025-06-18";
+
+/// Revision that introduced tool annotations
+const TOOL_ANNOTATIONS_VERSION: &str = "2025-03-26";
+
+/// Revision that introduced the `completions` server capability
+const COMPLETIONS_VERSION: &str = "2025-03-26";
+
+/// The version to answer `initialize` with: the client's if supported, else
+/// the newest supported revision older than it, else the newest supported.
+/// Revisions are dates, so they order as strings.
+pub fn negotiate_protocol_version(requested: Option<&str>) -> &'static str {
+    let newest = SUPPORTED_PROTOCOL_VERSIONS[0];
+    let Some(requested) = requested else {
+        return newest;
+    };
+    SUPPORTED_PROTOCOL_VERSIONS
+        .iter()
+        .find(|&&version| version <= requested)
+        .copied()
+        .unwrap_or(newest)
+}
+
+/// Whether tools may declare output schemas and return structured content
+/// under the negotiated `version`
+pub fn supports_structured_output(version: &str) -> bool {
+    version >= STRUCTURED_OUTPUT_VERSION
+}
+
+/// Whether tools may carry annotations under the negotiated `version`
+pub fn supports_tool_annotations(version: &str) -> bool {
+    version >= TOOL_ANNOTATIONS_VERSION
+}
+
+/// Whether the server declares the `completions` capability under the
+/// negotiated `version`. Older clients may still send `completion/complete`.
+pub fn supports_completions_capability(version: &str) -> bool {
+    version >= COMPLETIONS_VERSION
+}
+
 /// MCP Initialize Request params
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
@@ -134,11 +265,16 @@ pub struct ClientCapabilities {
     pub sampling: Option<SamplingCapability>,
     #[serde(default)]
     pub roots: Option<RootsCapability>,
+    #[serde(default)]
+    pub elicitation: Option<ElicitationCapability>,
 }
 
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct SamplingCapability {}
 
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct ElicitationCapability {}
+
 #[derive(Debug, Clone, Deserialize, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct RootsCapability {
@@ -146,6 +282,12 @@ pub struct RootsCapability {
     pub list_changed: Option<bool>,
 }
 
+/// `roots/list` result
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListRootsResult {
+    pub roots: Vec<Root>,
+}
+
 /// Client info
 #[derive(Debug, Clone, Deserialize)]
 pub struct ClientInfo {
@@ -167,6 +309,8 @@ pub struct InitializeResult {
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completions: Option<CompletionsCapability>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<HashMap<String, Value>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -179,6 +323,9 @@ pub struct ServerCapabilities {
     pub tools: Option<ToolsCapability>,
 }
 
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CompletionsCapability {}
+
 #[derive(Debug, Clone, Serialize, Default)]
 pub struct LoggingCapability {}
 
@@ -221,6 +368,26 @@ pub struct Tool {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     pub input_schema: ToolInputSchema,
+    /// JSON Schema of the tool's `structuredContent`, for tools that return it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<ToolAnnotations>,
+}
+
+/// Hints about what a tool does, for hosts deciding which calls need
+/// confirmation
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolAnnotations {
+    /// The tool does not change the graph or memories
+    pub read_only_hint: bool,
+    /// The tool may overwrite or drop existing data
+    pub destructive_hint: bool,
+    /// Repeating a call with the same arguments has no further effect
+    pub idempotent_hint: bool,
+    /// The tool reaches outside the workspace and its stores
+    pub open_world_hint: bool,
 }
 
 /// Tool input schema (JSON Schema subset)
@@ -253,10 +420,20 @@ pub struct PropertySchema {
     pub maximum: Option<f64>,
 }
 
+/// Params of a paginated list request (`tools/list`, `resources/list`)
+#[derive(Debug, Clone, Deserialize, Default)]
+pub struct PaginatedParams {
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
 /// Tools list response
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ToolsListResult {
     pub tools: Vec<Tool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Tool call request params
@@ -265,6 +442,137 @@ pub struct ToolCallParams {
     pub name: String,
     #[serde(default)]
     pub arguments: Option<Value>,
+    #[serde(default, rename = "_meta")]
+    pub meta: Option<RequestMeta>,
+}
+
+/// Request metadata (`_meta`)
+#[derive(Debug, Clone, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestMeta {
+    /// Token to report `notifications/progress` under
+    #[serde(default)]
+    pub progress_token: Option<Value>,
+}
+
+/// `notifications/cancelled` params
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledParams {
+    /// Id of the request to cancel
+    pub request_id: Value,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Severity of a log message (RFC 5424 levels), least severe first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+/// `logging/setLevel` request params
+#[derive(Debug, Clone, Deserialize)]
+pub struct SetLevelParams {
+    pub level: LoggingLevel,
+}
+
+/// `notifications/message` params
+#[derive(Debug, Clone, Serialize)]
+pub struct LoggingMessageParams {
+    pub level: LoggingLevel,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    pub data: Value,
+}
+
+/// `sampling/createMessage` request params
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    pub max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model_preferences: Option<ModelPreferences>,
+}
+
+/// A message of a sampling conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    /// "user" or "assistant"
+    pub role: String,
+    pub content: SamplingContent,
+}
+
+/// Content of a sampling message; only text is used
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum SamplingContent {
+    Text {
+        text: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Hints for the client choosing a model (priorities 0-1)
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_priority: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub speed_priority: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub intelligence_priority: Option<f32>,
+}
+
+/// `sampling/createMessage` result
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    pub content: SamplingContent,
+    #[serde(default)]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub stop_reason: Option<String>,
+}
+
+/// `elicitation/create` request params
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElicitParams {
+    pub message: String,
+    /// Flat object schema of the values asked for
+    pub requested_schema: Value,
+}
+
+/// What the user did with an elicitation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ElicitAction {
+    Accept,
+    Decline,
+    Cancel,
+}
+
+/// `elicitation/create` result
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElicitResult {
+    pub action: ElicitAction,
+    #[serde(default)]
+    pub content: Option<Value>,
 }
 
 /// Tool call response
@@ -272,6 +580,9 @@ pub struct ToolCallParams {
 #[serde(rename_all = "camelCase")]
 pub struct ToolCallResult {
     pub content: Vec<ToolResultContent>,
+    /// The result as JSON, for tools declaring an output schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub is_error: Option<bool>,
 }
@@ -309,8 +620,32 @@ pub struct Resource {
 
 /// Resources list response
 #[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct ResourcesListResult {
     pub resources: Vec<Resource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+}
+
+/// MCP resource template: a family of resources addressed by a URI template
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
+/// Resource templates list response
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplatesListResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 /// Resource read request params
@@ -319,6 +654,18 @@ pub struct ResourceReadParams {
     pub uri: String,
 }
 
+/// Resource subscribe and unsubscribe request params
+#[derive(Debug, Clone, Deserialize)]
+pub struct ResourceSubscribeParams {
+    pub uri: String,
+}
+
+/// Resource updated notification params
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceUpdatedParams {
+    pub uri: String,
+}
+
 /// Resource read response
 #[derive(Debug, Clone, Serialize)]
 pub struct ResourceReadResult {
@@ -338,6 +685,52 @@ pub struct ResourceContent {
     pub blob: Option<String>,
 }
 
+/// What an argument being completed belongs to
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type")]
+pub enum CompletionReference {
+    #[serde(rename = "ref/prompt")]
+    Prompt { name: String },
+    #[serde(rename = "ref/resource")]
+    Resource { uri: String },
+    /// Not part of the spec: tool arguments, for clients that complete them
+    #[serde(rename = "ref/tool")]
+    Tool { name: String },
+}
+
+/// The argument being completed and its partial value
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompletionArgument {
+    pub name: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+/// Completion request params
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompleteParams {
+    #[serde(rename = "ref")]
+    pub reference: CompletionReference,
+    pub argument: CompletionArgument,
+}
+
+/// Completion response
+#[derive(Debug, Clone, Serialize)]
+pub struct CompleteResult {
+    pub completion: Completion,
+}
+
+/// Suggested values for a completed argument
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Completion {
+    pub values: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_more: Option<bool>,
+}
+
 /// Ping response
 #[derive(Debug, Clone, Serialize)]
 pub struct PingResult {}
@@ -346,6 +739,104 @@ pub struct PingResult {}
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_logging_levels() {
+        let params: SetLevelParams = serde_json::from_str(r#"{"level":"warning"}"#).unwrap();
+        assert_eq!(params.level, LoggingLevel::Warning);
+        assert!(LoggingLevel::Error > LoggingLevel::Warning);
+        assert!(LoggingLevel::Debug < LoggingLevel::Info);
+        assert!(serde_json::from_str::<SetLevelParams>(r#"{"level":"verbose"}"#).is_err());
+    }
+
+    #[test]
+    fn test_parse_incoming_message() {
+        let json = r#"{"jsonrpc":"2.0","id":4,"method":"tools/call","params":{}}"#;
+        let message: IncomingMessage = serde_json::from_str(json).unwrap();
+        assert!(matches!(message, IncomingMessage::Request(r) if r.method == "tools/call"));
+
+        let json = r#"{"jsonrpc":"2.0","id":"s-1","result":{"role":"assistant","content":{"type":"text","text":"Short."},"model":"m"}}"#;
+        let IncomingMessage::Response(response) = serde_json::from_str(json).unwrap() else {
+            panic!("expected a response");
+        };
+        assert_eq!(response.id, "s-1");
+        let result: CreateMessageResult = serde_json::from_value(response.result.unwrap()).unwrap();
+        assert!(matches!(result.content, SamplingContent::Text { text } if text == "Short."));
+
+        let json = r#"{"jsonrpc":"2.0","id":"s-2","error":{"code":-1,"message":"User rejected sampling request"}}"#;
+        let message: IncomingMessage = serde_json::from_str(json).unwrap();
+        assert!(matches!(message, IncomingMessage::Response(r) if r.error.is_some()));
+    }
+
+    #[test]
+    fn test_negotiate_protocol_version() {
+        assert_eq!(negotiate_protocol_version(Some("2024-11-05")), "2024-11-05");
+        assert_eq!(negotiate_protocol_version(Some("2025-03-26")), "2025-03-26");
+        // A newer revision than ours falls back to our newest
+        assert_eq!(negotiate_protocol_version(Some("2099-01-01")), "2025-06-18");
+        // An unknown revision between two of ours gets the older one
+        assert_eq!(negotiate_protocol_version(Some("2025-05-01")), "2025-03-26");
+        // Older than all of ours: offer the newest, the client may disconnect
+        assert_eq!(negotiate_protocol_version(Some("2024-01-01")), "2025-06-18");
+        assert_eq!(negotiate_protocol_version(None), "2025-06-18");
+
+        assert!(supports_structured_output("2025-06-18"));
+        assert!(!supports_structured_output("2025-03-26"));
+        assert!(supports_tool_annotations("2025-03-26"));
+        assert!(!supports_tool_annotations("2024-11-05"));
+        assert!(supports_completions_capability("2025-03-26"));
+        assert!(!supports_completions_capability("2024-11-05"));
+    }
+
+    #[test]
+    fn test_parse_complete_params() {
+        let json = r#"{"ref":{"type":"ref/resource","uri":"codegraph://symbol/{nodeId}"},"argument":{"name":"nodeId","value":"pars"}}"#;
+        let params: CompleteParams = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            params.reference,
+            CompletionReference::Resource {
+                uri: "codegraph://symbol/{nodeId}".to_string()
+            }
+        );
+        assert_eq!(params.argument.name, "nodeId");
+        assert_eq!(params.argument.value, "pars");
+
+        let json = r#"{"ref":{"type":"ref/tool","name":"codegraph_get_callers"},"argument":{"name":"uri"}}"#;
+        let params: CompleteParams = serde_json::from_str(json).unwrap();
+        assert!(matches!(params.reference, CompletionReference::Tool { .. }));
+        assert_eq!(params.argument.value, "");
+
+        let result = CompleteResult {
+            completion: Completion {
+                values: vec!["parseConfig".to_string()],
+                total: Some(1),
+                has_more: Some(false),
+            },
+        };
+        assert_eq!(
+            serde_json::to_value(result).unwrap(),
+            serde_json::json!({
+                "completion": { "values": ["parseConfig"], "total": 1, "hasMore": false }
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_batch() {
+        let line = r#"[{"jsonrpc":"2.0","id":1,"method":"tools/list"},{"jsonrpc":"2.0","method":"notifications/cancelled","params":{"requestId":0}},42]"#;
+        let Incoming::Batch(members) = Incoming::parse(line).unwrap() else {
+            panic!("expected a batch");
+        };
+        assert_eq!(members.len(), 3);
+        assert!(matches!(&members[0], Ok(IncomingMessage::Request(r)) if r.method == "tools/list"));
+        assert!(matches!(&members[1], Ok(IncomingMessage::Request(r)) if r.id.is_none()));
+        assert!(matches!(&members[2], Err(e) if e.code == -32600));
+
+        let line = r#"{"jsonrpc":"2.0","id":1,"method":"ping"}"#;
+        assert!(matches!(Incoming::parse(line), Ok(Incoming::Message(_))));
+        assert!(Incoming::parse("42").is_err());
+        assert!(Incoming::parse("[").is_err());
+    }
+
     #[test]
     fn test_parse_request() {
         let json = r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#;
@@ -365,6 +856,38 @@ mod tests {
         assert!(json.contains("\"result\""));
     }
 
+    #[test]
+    fn test_parse_tool_call_progress_token() {
+        let json = r#"{"name":"codegraph_reindex_workspace","_meta":{"progressToken":7}}"#;
+        let params: ToolCallParams = serde_json::from_str(json).unwrap();
+        assert_eq!(params.meta.unwrap().progress_token, Some(Value::from(7)));
+
+        let params: ToolCallParams = serde_json::from_str(r#"{"name":"x"}"#).unwrap();
+        assert!(params.meta.is_none());
+    }
+
+    #[test]
+    fn test_serialize_notification() {
+        let notification = JsonRpcNotification::new(
+            "notifications/resources/updated",
+            serde_json::to_value(ResourceUpdatedParams {
+                uri: "codegraph://graph/stats".to_string(),
+            })
+            .ok(),
+        );
+        let json = serde_json::to_string(&notification).unwrap();
+        assert_eq!(
+            json,
+            r#"{"jsonrpc":"2.0","method":"notifications/resources/updated","params":{"uri":"codegraph://graph/stats"}}"#
+        );
+        let json = serde_json::to_string(&JsonRpcNotification::new(
+            "notifications/resources/list_changed",
+            None,
+        ))
+        .unwrap();
+        assert!(!json.contains("params") && !json.contains("\"id\""));
+    }
+
     #[test]
     fn test_error_response() {
         let error = JsonRpcError::method_not_found("unknown");
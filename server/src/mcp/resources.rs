@@ -2,11 +2,30 @@
 //!
 //! Defines resources available through the MCP protocol.
 
-use super::protocol::{Resource, ResourceContent, ResourceReadResult};
+use super::metrics::ToolMetrics;
+use super::protocol::{Resource, ResourceContent, ResourceReadResult, ResourceTemplate};
+use super::sampling::truncate_text;
+use crate::ai_query::{OutlineNode, QueryEngine};
+use crate::memory::MemoryNode;
+use crate::watcher::WatchedChange;
 use codegraph::CodeGraph;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+/// Prefix of the per-file outline resources, followed by the file's path
+pub const OUTLINE_URI_PREFIX: &str = "codegraph://outline/";
+
+/// Prefix of the symbol resources, followed by a NodeId or moniker
+pub const SYMBOL_URI_PREFIX: &str = "codegraph://symbol/";
+
+/// Prefix of the memory resources, followed by a memory id
+pub const MEMORY_URI_PREFIX: &str = "codegraph://memory/";
+
+/// Length (in chars) of the memory summaries in outlines
+const OUTLINE_MEMORY_CHARS: usize = 200;
+
 /// Get all available resources
 pub fn get_all_resources() -> Vec<Resource> {
     vec![
@@ -33,54 +52,297 @@ pub fn get_all_resources() -> Vec<Resource> {
             description: Some("Current indexing status and workspace configuration".to_string()),
             mime_type: Some("application/json".to_string()),
         },
+        Resource {
+            uri: "codegraph://metrics".to_string(),
+            name: "Tool Metrics".to_string(),
+            description: Some(
+                "Call counts, latencies and error rates of each tool since the server started"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+        },
+    ]
+}
+
+/// Get the templates of resources that are read by URI without being listed
+pub fn get_resource_templates() -> Vec<ResourceTemplate> {
+    vec![
+        ResourceTemplate {
+            uri_template: format!("{}{{path}}", OUTLINE_URI_PREFIX),
+            name: "File Outline".to_string(),
+            description: Some(
+                "Hierarchical symbol outline of a file, with summaries of the memories linked \
+                 to its symbols. The path is absolute or relative to a workspace folder."
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+        },
+        ResourceTemplate {
+            uri_template: format!("{}{{nodeId}}", SYMBOL_URI_PREFIX),
+            name: "Symbol".to_string(),
+            description: Some(
+                "Signature, location, documentation and relationships of a symbol, by the \
+                 nodeId or moniker from search results"
+                    .to_string(),
+            ),
+            mime_type: Some("application/json".to_string()),
+        },
+        ResourceTemplate {
+            uri_template: format!("{}{{id}}", MEMORY_URI_PREFIX),
+            name: "Memory".to_string(),
+            description: Some("A stored memory with its content, tags and code links".to_string()),
+            mime_type: Some("application/json".to_string()),
+        },
     ]
 }
 
+/// A change to what resources read, reported to subscribed clients.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceChange {
+    /// Files were re-indexed; `files_added_or_removed` when the set of
+    /// indexed files changed too, which is announced as a list change.
+    Graph { files_added_or_removed: bool },
+    /// Memories were stored, invalidated or mined.
+    Memories,
+}
+
+impl ResourceChange {
+    /// URIs of the resources whose contents the change affects.
+    pub fn affected_uris(&self) -> &'static [&'static str] {
+        match self {
+            Self::Graph { .. } => &["codegraph://graph/stats", "codegraph://index/status"],
+            Self::Memories => &["codegraph://memory/stats"],
+        }
+    }
+
+    /// Whether clients should fetch the resource list again.
+    pub fn list_changed(&self) -> bool {
+        matches!(
+            self,
+            Self::Graph {
+                files_added_or_removed: true
+            }
+        )
+    }
+
+    /// Resource changes caused by the file watcher updating the graph.
+    pub fn from_watched(change: &WatchedChange) -> Vec<Self> {
+        let mut changes = vec![Self::Graph {
+            files_added_or_removed: change.added_or_removed,
+        }];
+        if change.invalidated_memories > 0 {
+            changes.push(Self::Memories);
+        }
+        changes
+    }
+
+    /// Resource changes caused by a successful call to the tool `name`.
+    pub fn from_tool(name: &str) -> Vec<Self> {
+        match name {
+            "codegraph_memory_store"
+            | "codegraph_memory_update"
+            | "codegraph_memory_invalidate"
+            | "codegraph_mine_git_history"
+            | "codegraph_mine_git_history_for_file" => vec![Self::Memories],
+            "codegraph_reindex_workspace" => vec![Self::Graph {
+                files_added_or_removed: true,
+            }],
+            _ => Vec::new(),
+        }
+    }
+}
+
 /// Read a resource by URI
 pub async fn read_resource(
     uri: &str,
     graph: Arc<RwLock<CodeGraph>>,
+    query_engine: &QueryEngine,
     memory_manager: &crate::memory::MemoryManager,
     workspace_folders: &[std::path::PathBuf],
+    metrics: &ToolMetrics,
 ) -> Option<ResourceReadResult> {
     match uri {
         "codegraph://graph/stats" => {
             let stats = get_graph_stats(graph).await;
-            Some(ResourceReadResult {
-                contents: vec![ResourceContent {
-                    uri: uri.to_string(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(serde_json::to_string_pretty(&stats).unwrap_or_default()),
-                    blob: None,
-                }],
-            })
+            Some(json_resource(uri, &stats))
         }
         "codegraph://memory/stats" => {
             let stats = get_memory_stats(memory_manager).await;
-            Some(ResourceReadResult {
-                contents: vec![ResourceContent {
-                    uri: uri.to_string(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(serde_json::to_string_pretty(&stats).unwrap_or_default()),
-                    blob: None,
-                }],
-            })
+            Some(json_resource(uri, &stats))
         }
         "codegraph://index/status" => {
             let status = get_index_status(graph, workspace_folders).await;
-            Some(ResourceReadResult {
-                contents: vec![ResourceContent {
-                    uri: uri.to_string(),
-                    mime_type: Some("application/json".to_string()),
-                    text: Some(serde_json::to_string_pretty(&status).unwrap_or_default()),
-                    blob: None,
-                }],
-            })
+            Some(json_resource(uri, &status))
+        }
+        "codegraph://metrics" => Some(json_resource(uri, &metrics.snapshot())),
+        _ if uri.starts_with(OUTLINE_URI_PREFIX) => {
+            let path = outline_path(uri, workspace_folders)?;
+            let outline = get_file_outline(&path, query_engine, memory_manager).await?;
+            Some(json_resource(uri, &outline))
+        }
+        _ if uri.starts_with(SYMBOL_URI_PREFIX) => {
+            let reference = uri_param(uri, SYMBOL_URI_PREFIX)?;
+            let symbol = get_symbol(&reference, &graph, query_engine).await?;
+            Some(json_resource(uri, &symbol))
+        }
+        _ if uri.starts_with(MEMORY_URI_PREFIX) => {
+            let id = uri_param(uri, MEMORY_URI_PREFIX)?;
+            let memory = get_memory(&id, memory_manager).await?;
+            Some(json_resource(uri, &memory))
         }
         _ => None,
     }
 }
 
+fn json_resource(uri: &str, value: &Value) -> ResourceReadResult {
+    ResourceReadResult {
+        contents: vec![ResourceContent {
+            uri: uri.to_string(),
+            mime_type: Some("application/json".to_string()),
+            text: Some(serde_json::to_string_pretty(value).unwrap_or_default()),
+            blob: None,
+        }],
+    }
+}
+
+/// The file an outline URI refers to. A relative path is looked up in each
+/// workspace folder in turn.
+fn outline_path(uri: &str, workspace_folders: &[PathBuf]) -> Option<PathBuf> {
+    let path = PathBuf::from(uri_param(uri, OUTLINE_URI_PREFIX)?);
+    if path.is_absolute() {
+        return Some(path);
+    }
+    workspace_folders
+        .iter()
+        .map(|folder| folder.join(&path))
+        .find(|candidate| candidate.exists())
+}
+
+/// The parameter following `prefix` in a templated URI, decoded
+fn uri_param(uri: &str, prefix: &str) -> Option<String> {
+    percent_decode(uri.strip_prefix(prefix)?).filter(|param| !param.is_empty())
+}
+
+/// Decode `%XX` escapes, which clients expanding a URI template use for the
+/// slashes of a path and the reserved characters of a moniker
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+async fn get_symbol(
+    reference: &str,
+    graph: &RwLock<CodeGraph>,
+    query_engine: &QueryEngine,
+) -> Option<Value> {
+    let node_id = {
+        let graph = graph.read().await;
+        crate::domain::monikers::resolve_node_ref(&graph, reference)?
+    };
+    let info = crate::domain::symbol_info::get_symbol_info(
+        graph,
+        query_engine,
+        node_id,
+        false,
+        false,
+        None,
+    )
+    .await?;
+    serde_json::to_value(info).ok()
+}
+
+async fn get_memory(id: &str, memory_manager: &crate::memory::MemoryManager) -> Option<Value> {
+    let memory = memory_manager.get(id).await.ok()??;
+    let code_links: Vec<&str> = memory
+        .code_links
+        .iter()
+        .map(|link| link.node_id.as_str())
+        .collect();
+    Some(serde_json::json!({
+        "id": memory.id.to_string(),
+        "title": memory.title,
+        "content": memory.content,
+        "kind": memory.kind.discriminant_name(),
+        "tags": memory.tags,
+        "codeLinks": code_links,
+        "createdAt": memory.temporal.created_at.to_rfc3339(),
+        "invalidated": memory.temporal.invalid_at.is_some(),
+    }))
+}
+
+async fn get_file_outline(
+    path: &Path,
+    query_engine: &QueryEngine,
+    memory_manager: &crate::memory::MemoryManager,
+) -> Option<Value> {
+    let outline = query_engine
+        .get_file_outline(&path.to_string_lossy())
+        .await?;
+    // Memories are optional: an uninitialized store just means none are linked
+    let memories = memory_manager.get_all_current().await.unwrap_or_default();
+
+    Some(serde_json::json!({
+        "file": outline.file,
+        "totalSymbols": outline.total_symbols,
+        "symbols": outline_nodes_json(&outline.symbols, &memories),
+    }))
+}
+
+/// Outline nodes with their children and the memories linked to them
+fn outline_nodes_json(nodes: &[OutlineNode], memories: &[MemoryNode]) -> Vec<Value> {
+    nodes
+        .iter()
+        .map(|node| {
+            let symbol = &node.symbol;
+            let keys: Vec<String> = std::iter::once(node.node_id.to_string())
+                .chain(symbol.moniker.clone())
+                .collect();
+            let linked: Vec<Value> = memories
+                .iter()
+                .filter(|m| m.code_links.iter().any(|l| keys.contains(&l.node_id)))
+                .map(|m| {
+                    serde_json::json!({
+                        "id": m.id.to_string(),
+                        "title": m.title,
+                        "kind": m.kind.discriminant_name(),
+                        "summary": truncate_text(&m.content, OUTLINE_MEMORY_CHARS),
+                    })
+                })
+                .collect();
+
+            let mut entry = serde_json::json!({
+                "name": symbol.name,
+                "kind": symbol.kind,
+                "line": symbol.location.line,
+                "endLine": symbol.location.end_line,
+                "nodeId": node.node_id.to_string(),
+            });
+            if let Some(signature) = &symbol.signature {
+                entry["signature"] = Value::String(signature.clone());
+            }
+            if !linked.is_empty() {
+                entry["memories"] = Value::Array(linked);
+            }
+            if !node.children.is_empty() {
+                entry["children"] = Value::Array(outline_nodes_json(&node.children, memories));
+            }
+            entry
+        })
+        .collect()
+}
+
 async fn get_graph_stats(graph: Arc<RwLock<CodeGraph>>) -> serde_json::Value {
     let graph = graph.read().await;
 
@@ -132,10 +394,92 @@ async fn get_index_status(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resource_changes() {
+        let modified = WatchedChange {
+            path: std::path::PathBuf::from("/ws/src/lib.rs"),
+            added_or_removed: false,
+            invalidated_memories: 0,
+        };
+        let changes = ResourceChange::from_watched(&modified);
+        assert_eq!(
+            changes,
+            vec![ResourceChange::Graph {
+                files_added_or_removed: false
+            }]
+        );
+        assert!(!changes[0].list_changed());
+        assert!(changes[0]
+            .affected_uris()
+            .contains(&"codegraph://graph/stats"));
+
+        let deleted = WatchedChange {
+            added_or_removed: true,
+            invalidated_memories: 2,
+            ..modified
+        };
+        let changes = ResourceChange::from_watched(&deleted);
+        assert!(changes[0].list_changed());
+        assert_eq!(changes[1], ResourceChange::Memories);
+
+        assert_eq!(
+            ResourceChange::from_tool("codegraph_memory_store"),
+            vec![ResourceChange::Memories]
+        );
+        assert!(ResourceChange::from_tool("codegraph_symbol_search").is_empty());
+    }
+
+    #[test]
+    fn test_outline_path() {
+        let workspace = std::env::temp_dir().join("codegraph_outline_path_test");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        let lib = workspace.join("src/lib.rs");
+        std::fs::write(&lib, "").unwrap();
+        let folders = vec![PathBuf::from("/nonexistent"), workspace.clone()];
+
+        let uri = format!("{}src/lib.rs", OUTLINE_URI_PREFIX);
+        assert_eq!(outline_path(&uri, &folders), Some(lib.clone()));
+        let uri = format!("{}src%2Flib.rs", OUTLINE_URI_PREFIX);
+        assert_eq!(outline_path(&uri, &folders), Some(lib));
+        let uri = format!("{}src/missing.rs", OUTLINE_URI_PREFIX);
+        assert_eq!(outline_path(&uri, &folders), None);
+        let uri = format!("{}src%2", OUTLINE_URI_PREFIX);
+        assert_eq!(outline_path(&uri, &folders), None);
+
+        let _ = std::fs::remove_dir_all(&workspace);
+    }
+
+    #[test]
+    fn test_get_resource_templates() {
+        let templates: Vec<String> = get_resource_templates()
+            .into_iter()
+            .map(|t| t.uri_template)
+            .collect();
+        assert_eq!(
+            templates,
+            vec![
+                "codegraph://outline/{path}",
+                "codegraph://symbol/{nodeId}",
+                "codegraph://memory/{id}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_uri_param() {
+        let uri = "codegraph://symbol/src%2Flib.rs%23Parser.parse";
+        assert_eq!(
+            uri_param(uri, SYMBOL_URI_PREFIX).as_deref(),
+            Some("src/lib.rs#Parser.parse")
+        );
+        assert_eq!(uri_param("codegraph://memory/", MEMORY_URI_PREFIX), None);
+        assert_eq!(uri_param("codegraph://memory/%zz", MEMORY_URI_PREFIX), None);
+    }
+
     #[test]
     fn test_get_all_resources() {
         let resources = get_all_resources();
-        assert_eq!(resources.len(), 3);
+        assert_eq!(resources.len(), 4);
         assert!(resources.iter().any(|r| r.uri == "codegraph://graph/stats"));
         assert!(resources
             .iter()
@@ -143,5 +487,6 @@ mod tests {
         assert!(resources
             .iter()
             .any(|r| r.uri == "codegraph://index/status"));
+        assert!(resources.iter().any(|r| r.uri == "codegraph://metrics"));
     }
 }
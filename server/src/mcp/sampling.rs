@@ -0,0 +1,191 @@
+//! Summarization by the client's model (`sampling/createMessage`).
+//!
+//! When the client advertises the `sampling` capability, long memory content
+//! and module outlines are summarized by the model it is connected to.
+//! Without sampling, or if it fails, text is cut at a paragraph or sentence
+//! boundary instead: a tool never fails for want of a summary.
+
+use super::client_requests::ClientRequests;
+use super::protocol::{
+    CreateMessageParams, CreateMessageResult, ModelPreferences, SamplingContent, SamplingMessage,
+};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Text longer than this (in chars) is summarized
+pub const SUMMARY_THRESHOLD: usize = 1500;
+
+/// How long to wait for the client to answer a sampling request
+const SAMPLING_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Token limit of a sampled summary
+const SUMMARY_MAX_TOKENS: u32 = 300;
+
+/// Where a summary's text came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SummarySource {
+    /// Short enough to be returned as is
+    Original,
+    /// Summarized by the client's model
+    Sampled,
+    /// Cut to `SUMMARY_THRESHOLD` chars, sampling being unavailable
+    Truncated,
+    /// Listing of a module's symbols, sampling being unavailable
+    Outline,
+}
+
+#[derive(Debug, Clone)]
+pub struct Summary {
+    pub text: String,
+    pub source: SummarySource,
+}
+
+/// Sends `sampling/createMessage` requests, if the client supports them.
+pub struct Sampler {
+    /// Whether the client advertised the `sampling` capability
+    supported: AtomicBool,
+    requests: Arc<ClientRequests>,
+}
+
+impl Sampler {
+    pub fn new(requests: Arc<ClientRequests>) -> Self {
+        Self {
+            supported: AtomicBool::new(false),
+            requests,
+        }
+    }
+
+    pub fn set_supported(&self, supported: bool) {
+        self.supported.store(supported, Ordering::Relaxed);
+    }
+
+    pub fn is_available(&self) -> bool {
+        self.supported.load(Ordering::Relaxed) && self.requests.is_attached()
+    }
+
+    /// Ask the client's model for a message
+    pub async fn create_message(
+        &self,
+        params: CreateMessageParams,
+    ) -> Result<CreateMessageResult, String> {
+        if !self.supported.load(Ordering::Relaxed) {
+            return Err("Client does not support sampling".to_string());
+        }
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        let result = self
+            .requests
+            .send("sampling/createMessage", Some(params), SAMPLING_TIMEOUT)
+            .await?;
+        serde_json::from_value(result).map_err(|e| format!("Invalid sampling result: {}", e))
+    }
+
+    /// Have the client's model summarize `text` following `instruction`
+    pub async fn sample_summary(&self, instruction: &str, text: &str) -> Result<String, String> {
+        let params = CreateMessageParams {
+            messages: vec![SamplingMessage {
+                role: "user".to_string(),
+                content: SamplingContent::Text {
+                    text: format!("{}\n\n{}", instruction, text),
+                },
+            }],
+            system_prompt: Some(
+                "You summarize source code and engineering notes for a code intelligence \
+                 tool. Reply with the summary only."
+                    .to_string(),
+            ),
+            max_tokens: SUMMARY_MAX_TOKENS,
+            model_preferences: Some(ModelPreferences {
+                cost_priority: Some(0.8),
+                speed_priority: Some(0.8),
+                intelligence_priority: Some(0.3),
+            }),
+        };
+        match self.create_message(params).await? {
+            CreateMessageResult {
+                content: SamplingContent::Text { text },
+                ..
+            } if !text.trim().is_empty() => Ok(text.trim().to_string()),
+            _ => Err("Sampling returned no text".to_string()),
+        }
+    }
+
+    /// Summarize `text` following `instruction`, if it is longer than
+    /// `SUMMARY_THRESHOLD`. Falls back to truncation when sampling is
+    /// unavailable or fails.
+    pub async fn summarize(&self, text: &str, instruction: &str) -> Summary {
+        if text.chars().count() <= SUMMARY_THRESHOLD {
+            return Summary {
+                text: text.to_string(),
+                source: SummarySource::Original,
+            };
+        }
+
+        if self.is_available() {
+            match self.sample_summary(instruction, text).await {
+                Ok(text) => {
+                    return Summary {
+                        text,
+                        source: SummarySource::Sampled,
+                    };
+                }
+                Err(e) => tracing::warn!("Sampling failed, truncating instead: {}", e),
+            }
+        }
+
+        Summary {
+            text: truncate_text(text, SUMMARY_THRESHOLD),
+            source: SummarySource::Truncated,
+        }
+    }
+}
+
+/// Cut `text` to at most `max_chars` chars, at the last paragraph or sentence
+/// end in its second half if there is one, and mark the cut.
+pub fn truncate_text(text: &str, max_chars: usize) -> String {
+    let Some((end, _)) = text.char_indices().nth(max_chars) else {
+        return text.to_string();
+    };
+    let head = &text[..end];
+    let boundary = head
+        .rfind("\n\n")
+        .or_else(|| head.rfind(". ").map(|i| i + 1))
+        .filter(|&i| i >= head.len() / 2)
+        .unwrap_or(end);
+    format!("{} …", head[..boundary].trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_text() {
+        assert_eq!(truncate_text("short", 10), "short");
+
+        let text = "First sentence here. Second sentence is longer than the limit";
+        assert_eq!(truncate_text(text, 30), "First sentence here. …");
+
+        // No boundary in the second half: cut at the limit, on a char boundary
+        let text = "ééééééééééééééééééééé";
+        assert_eq!(truncate_text(text, 5), "ééééé …");
+    }
+
+    #[tokio::test]
+    async fn test_summarize_without_sampling() {
+        let sampler = Sampler::new(Arc::new(ClientRequests::new()));
+        assert!(!sampler.is_available());
+
+        let summary = sampler.summarize("A short note.", "Summarize").await;
+        assert_eq!(summary.source, SummarySource::Original);
+        assert_eq!(summary.text, "A short note.");
+
+        let long = "Lorem ipsum dolor sit amet. ".repeat(100);
+        let summary = sampler.summarize(&long, "Summarize").await;
+        assert_eq!(summary.source, SummarySource::Truncated);
+        assert!(summary.text.chars().count() <= SUMMARY_THRESHOLD + 2);
+        assert!(summary.text.ends_with(". …"));
+    }
+}
@@ -2,25 +2,46 @@
 //!
 //! Handles MCP protocol requests and routes them to CodeGraph functionality.
 
+use super::client_requests::ClientRequests;
+use super::elicitation::{needs_confirmation, Confirmer};
+use super::in_flight::InFlightCalls;
+use super::limits::ToolLimits;
+use super::logging::McpLogger;
+use super::metrics::ToolMetrics;
+use super::pagination::{self, paginate, paginate_result, LIST_PAGE_SIZE};
+use super::progress::McpProgress;
 use super::protocol::*;
-use super::resources::get_all_resources;
-use super::tools::get_all_tools;
-use super::transport::AsyncStdioTransport;
+use super::resources::{get_all_resources, get_resource_templates, ResourceChange};
+use super::sampling::{Sampler, SummarySource};
+use super::tool_filter::ToolFilter;
+use super::tools::{get_all_tools, has_output_schema, is_read_only};
+use super::transport::{AsyncTransport, Outgoing};
 use crate::ai_query::QueryEngine;
+use crate::cancellation::CancellationToken;
 use crate::domain::node_props;
-use crate::git_mining::{GitExecutor, GitMiner, MiningConfig};
+use crate::git_mining::{GitExecutor, GitMiner, HotspotOptions, MiningConfig};
+use crate::index_pipeline::{parse_concurrency, parse_files};
 use crate::memory::{self, MemoryManager};
 use crate::parser_registry::ParserRegistry;
+use crate::watcher::{FileWatcher, WatchedChange};
+use crate::workspace_walk::WorkspaceWalk;
 use codegraph::{CodeGraph, NamespacedBackend, RocksDBBackend, StorageBackend};
+use futures::StreamExt;
 use serde_json::Value;
+use std::collections::HashSet;
 use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{mpsc, RwLock};
 
-const PROTOCOL_VERSION: &str = "2024-11-05";
 const SERVER_NAME: &str = "codegraph";
 const SERVER_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// How long to wait for the client to list its roots
+const ROOTS_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+/// Instruction for summarizing long memory content
+const MEMORY_SUMMARY_PROMPT: &str = "Summarize this engineering note, keeping its key facts:";
+
 /// MCP Backend - wraps CodeGraph components for MCP access
 #[derive(Clone)]
 pub struct McpBackend {
@@ -35,6 +56,9 @@ pub struct McpBackend {
     pub exclude_dirs: Vec<String>,
     /// Maximum number of files to index
     pub max_files: usize,
+    /// The walk of the server whose components these are, with its own
+    /// exclusions, if shared
+    walk: Option<WorkspaceWalk>,
 }
 
 impl McpBackend {
@@ -76,19 +100,65 @@ impl McpBackend {
 
         let mut query_engine = QueryEngine::new(Arc::clone(&graph));
         query_engine.set_full_body_embedding(full_body_embedding);
+        if let Err(e) = query_engine.load_entry_rules(primary) {
+            tracing::warn!("{e}");
+        }
 
         Self {
             query_engine: Arc::new(query_engine),
             graph,
-            parsers: Arc::new(ParserRegistry::new()),
+            parsers: Arc::new(ParserRegistry::with_installed_plugins()),
             memory_manager: Arc::new(MemoryManager::with_model(extension_path, embedding_model)),
             workspace_folders: workspaces,
             project_slug: slug,
             exclude_dirs,
             max_files,
+            walk: None,
+        }
+    }
+
+    /// Create a backend over the components of a running server, which
+    /// indexes the workspace and keeps the graph up to date. `walk` is that
+    /// server's walk over the workspace, which `exclude_dirs` narrows.
+    #[allow(clippy::too_many_arguments)]
+    pub fn from_shared(
+        graph: Arc<RwLock<CodeGraph>>,
+        parsers: Arc<ParserRegistry>,
+        query_engine: Arc<QueryEngine>,
+        memory_manager: Arc<MemoryManager>,
+        workspace_folders: Vec<PathBuf>,
+        walk: WorkspaceWalk,
+        exclude_dirs: Vec<String>,
+        max_files: usize,
+    ) -> Self {
+        let project_slug = workspace_folders
+            .first()
+            .map(|folder| memory::project_slug(folder))
+            .unwrap_or_default();
+        Self {
+            graph,
+            parsers,
+            query_engine,
+            memory_manager,
+            workspace_folders,
+            project_slug,
+            exclude_dirs,
+            max_files,
+            walk: Some(walk),
         }
     }
 
+    /// The innermost workspace folder containing `path`, or the first folder.
+    fn workspace_folder_for(&self, path: Option<&std::path::Path>) -> Option<&PathBuf> {
+        path.and_then(|path| {
+            self.workspace_folders
+                .iter()
+                .filter(|folder| path.starts_with(folder))
+                .max_by_key(|folder| folder.components().count())
+        })
+        .or_else(|| self.workspace_folders.first())
+    }
+
     /// Open the shared graph database with project-scoped namespacing.
     ///
     /// Opens RocksDB at `~/.codegraph/graph.db`, wraps with NamespacedBackend,
@@ -265,8 +335,10 @@ impl McpBackend {
                     }
                 }
 
-                // Skip CodeFile nodes
-                if node.node_type == codegraph::NodeType::CodeFile {
+                // Skip CodeFile and TODO comment nodes
+                if node.node_type == codegraph::NodeType::CodeFile
+                    || node_props::is_todo_comment(node)
+                {
                     continue;
                 }
 
@@ -313,11 +385,17 @@ impl McpBackend {
         }))
     }
 
-    /// Index the workspace
-    pub async fn index_workspace(&self) -> usize {
+    /// Index the workspace. If `progress` is cancelled, indexing stops and
+    /// the files indexed so far are still linked, but not persisted: the
+    /// partial graph must not replace the complete one in `graph.db`.
+    pub async fn index_workspace(&self, progress: &McpProgress) -> usize {
+        self.query_engine.set_workspace_roots(&self.workspace_folders);
         let mut total = 0;
         for folder in &self.workspace_folders {
-            total += self.index_directory(folder).await;
+            if progress.is_cancelled() {
+                break;
+            }
+            total += self.index_directory(folder, progress).await;
 
             // Initialize memory manager with workspace path
             if let Err(e) = self.memory_manager.initialize(folder).await {
@@ -326,6 +404,7 @@ impl McpBackend {
         }
 
         // Resolve cross-file imports and calls before building indexes
+        progress.step(format!("Indexed {total} files, resolving imports"));
         {
             let mut graph = self.graph.write().await;
             crate::watcher::GraphUpdater::resolve_cross_file_imports(&mut graph);
@@ -334,21 +413,24 @@ impl McpBackend {
         // Detect runtime dependencies: HTTP routes and client calls
         {
             let mut graph = self.graph.write().await;
-            let routes = crate::runtime_deps::detect_route_handlers(&mut graph);
-            let clients = crate::runtime_deps::detect_http_client_calls(&mut graph);
-            if routes > 0 || clients > 0 {
-                let edges = crate::runtime_deps::create_runtime_call_edges(&mut graph);
+            let stats = crate::runtime_deps::link_runtime_calls(&mut graph);
+            if stats.routes > 0 || stats.clients > 0 {
                 tracing::info!(
                     "Runtime deps: {} routes, {} clients, {} edges",
-                    routes,
-                    clients,
-                    edges
+                    stats.routes,
+                    stats.clients,
+                    stats.edges
                 );
             }
         }
 
         // Persist graph to shared database
-        {
+        if progress.is_cancelled() {
+            tracing::info!(
+                "Indexing cancelled after {} files, not persisting the graph",
+                total
+            );
+        } else {
             let graph = self.graph.read().await;
             if let Err(e) = self.persist_graph(&graph) {
                 tracing::warn!("Failed to persist graph: {}", e);
@@ -356,11 +438,13 @@ impl McpBackend {
         }
 
         // Build query engine indexes
+        progress.step("Building query indexes");
         self.query_engine.build_indexes().await;
 
         // Share vector engine with query engine for semantic symbol search
         if let Some(engine) = self.memory_manager.get_vector_engine().await {
             self.query_engine.set_vector_engine(engine).await;
+            progress.step("Building semantic search index");
             tracing::info!("Building semantic search index... This may take a moment.");
             self.query_engine.build_symbol_vectors().await;
             tracing::info!("Semantic search index ready");
@@ -369,74 +453,53 @@ impl McpBackend {
         total
     }
 
-    /// Index a directory recursively
-    async fn index_directory(&self, dir: &std::path::Path) -> usize {
-        use std::fs;
+    /// Report indexing progress every this many files.
+    const INDEX_PROGRESS_INTERVAL: u64 = 50;
 
-        let mut indexed_count = 0;
-        let supported_extensions = self.parsers.supported_extensions();
+    /// Index the files of a directory that the workspace walk visits, parsing
+    /// them in parallel
+    async fn index_directory(&self, dir: &std::path::Path, progress: &McpProgress) -> usize {
+        let walk = self.workspace_walk();
 
         tracing::info!("Indexing directory: {:?}", dir);
-        tracing::debug!("Supported extensions: {:?}", supported_extensions);
-
-        if let Ok(entries) = fs::read_dir(dir) {
-            for entry in entries.flatten() {
-                let path = entry.path();
-
-                // Skip hidden files/directories and common exclusions
-                if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                    if name.starts_with('.')
-                        || matches!(
-                            name,
-                            "node_modules"
-                                | "target"
-                                | "__pycache__"
-                                | ".git"
-                                | "dist"
-                                | "build"
-                                | "out"
-                                | "vendor"
-                                | "DerivedData"
-                                | "tmp"
-                                | "coverage"
-                                | "logs"
-                        )
-                        || self.exclude_dirs.iter().any(|e| name == e.as_str())
-                    {
-                        continue;
-                    }
-                }
 
-                if path.is_dir() {
-                    // Check file limit before recursing
-                    let current = self.graph.read().await.node_count();
-                    if current >= self.max_files * 10 {
-                        // Rough heuristic: ~10 nodes per file
-                        tracing::warn!(
-                            "Approaching max file limit ({} nodes, max_files={}), stopping",
-                            current,
-                            self.max_files
-                        );
-                        break;
-                    }
-                    indexed_count += Box::pin(self.index_directory(&path)).await;
-                } else if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    // supported_extensions includes the dot (e.g., ".rs"), but path.extension() doesn't
-                    let ext_with_dot = format!(".{}", ext);
-                    let is_supported = supported_extensions
-                        .iter()
-                        .any(|e| *e == ext || *e == ext_with_dot);
-                    if is_supported {
-                        match self.index_file(&path).await {
-                            Ok(()) => {
-                                tracing::debug!("Indexed file: {:?}", path);
-                                indexed_count += 1;
-                            }
-                            Err(e) => {
-                                tracing::warn!("Failed to index {:?}: {}", path, e);
-                            }
-                        }
-                    }
+        // Parsing runs ahead in parallel; fragments enter the graph one by one.
+        // The walk advances as files are parsed, so it stops with the limit.
+        let mut parsed = std::pin::pin!(parse_files(
+            Arc::clone(&self.parsers),
+            walk.files(dir),
+            parse_concurrency()
+        ));
+        let mut indexed_count = 0;
+        while let Some((path, fragment)) = parsed.next().await {
+            if progress.is_cancelled() {
+                break;
+            }
+
+            let mut graph = self.graph.write().await;
+            // Check file limit before indexing more
+            let current = graph.node_count();
+            if current >= self.max_files * 10 {
+                // Rough heuristic: ~10 nodes per file
+                tracing::warn!(
+                    "Approaching max file limit ({} nodes, max_files={}), stopping",
+                    current,
+                    self.max_files
+                );
+                break;
+            }
+
+            match fragment {
+                Ok(fragment) => {
+                    fragment.insert(&mut graph);
+                    tracing::debug!("Indexed file: {:?}", path);
+                    indexed_count += 1;
+                    progress.tick(Self::INDEX_PROGRESS_INTERVAL, |n| {
+                        format!("Indexed {n} files")
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to index {:?}: {}", path, e);
                 }
             }
         }
@@ -444,23 +507,65 @@ impl McpBackend {
         indexed_count
     }
 
-    /// Index a single file
-    async fn index_file(&self, path: &std::path::Path) -> Result<(), String> {
-        let mut graph = self.graph.write().await;
-
-        // The parser directly modifies the graph
-        match self.parsers.parse_file(path, &mut graph) {
-            Ok(_file_info) => Ok(()),
-            Err(e) => Err(format!("{:?}", e)),
-        }
+    /// The walk over workspace files to index, skipping the configured
+    /// directories. The watcher skips what it skips.
+    pub(crate) fn workspace_walk(&self) -> WorkspaceWalk {
+        self.walk
+            .clone()
+            .unwrap_or_else(|| WorkspaceWalk::new(&self.parsers.supported_extensions()))
+            .exclude_names(&self.exclude_dirs)
     }
 }
 
+/// Requests read from one line of input, for the event loop to handle
+enum ReadRequests {
+    Single(JsonRpcRequest),
+    /// A batch's requests, answered together in one array. Invalid members
+    /// carry the error to answer them with.
+    Batch(Vec<Result<JsonRpcRequest, JsonRpcError>>),
+}
+
 /// MCP Server - handles protocol messages
 pub struct McpServer {
     backend: McpBackend,
+    /// Serving another server's components: the workspace is indexed and
+    /// watched there, and its folders are kept over the client's roots
+    shared: bool,
     initialized: bool,
+    /// Protocol version agreed on at initialize
+    protocol_version: &'static str,
     indexed: bool,
+    /// Resource URIs the client subscribed to
+    subscriptions: HashSet<String>,
+    /// Messages to the client, written in order by one task: notifications
+    /// sent while a request runs, e.g. progress, then its response
+    outgoing_tx: mpsc::UnboundedSender<Outgoing>,
+    outgoing_rx: Option<mpsc::UnboundedReceiver<Outgoing>>,
+    /// Tool calls that `notifications/cancelled` can abort
+    in_flight: Arc<InFlightCalls>,
+    /// Bounds the tool calls running at once, across a daemon's clients
+    limits: Arc<ToolLimits>,
+    /// Tools listed and run; the others are hidden and refused
+    tool_filter: Arc<ToolFilter>,
+    /// Call counts and latencies of each tool
+    metrics: ToolMetrics,
+    /// Requests to the client awaiting its response
+    client_requests: Arc<ClientRequests>,
+    /// Summarizes through the client's model, if it supports sampling
+    sampler: Arc<Sampler>,
+    /// Asks the user to confirm destructive operations
+    confirmer: Confirmer,
+    /// Time budget of a graph traversal or search, after which it returns
+    /// the results found so far as `truncated`
+    query_budget: Option<std::time::Duration>,
+    /// Whether the client advertised the `roots` capability
+    client_roots: bool,
+    /// Forwards log events to the client once it is initialized
+    logger: Arc<McpLogger>,
+    changes_tx: mpsc::UnboundedSender<ResourceChange>,
+    changes_rx: mpsc::UnboundedReceiver<ResourceChange>,
+    /// Re-indexes changed files once the workspace is indexed
+    watcher: Option<FileWatcher>,
 }
 
 impl McpServer {
@@ -471,55 +576,370 @@ impl McpServer {
         embedding_model: codegraph_memory::CodeGraphEmbeddingModel,
         full_body_embedding: bool,
     ) -> Self {
+        Self::from_backend(McpBackend::new(
+            workspaces,
+            exclude_dirs,
+            max_files,
+            embedding_model,
+            full_body_embedding,
+        ))
+    }
+
+    /// Serve the graph, query engine and memory store of a server that
+    /// indexes the workspace itself (see `daemon`), following the changes
+    /// its watcher reports on `changes`.
+    pub fn shared(backend: McpBackend, changes: mpsc::UnboundedReceiver<WatchedChange>) -> Self {
+        let mut server = Self::from_backend(backend);
+        server.indexed = true;
+        server.shared = true;
+        server.forward_changes(changes);
+        server
+    }
+
+    fn from_backend(backend: McpBackend) -> Self {
+        let (changes_tx, changes_rx) = mpsc::unbounded_channel();
+        let (outgoing_tx, outgoing_rx) = mpsc::unbounded_channel();
+        let client_requests = Arc::new(ClientRequests::new());
         Self {
-            backend: McpBackend::new(workspaces, exclude_dirs, max_files, embedding_model, full_body_embedding),
+            backend,
+            shared: false,
             initialized: false,
+            protocol_version: negotiate_protocol_version(None),
             indexed: false,
+            subscriptions: HashSet::new(),
+            outgoing_tx,
+            outgoing_rx: Some(outgoing_rx),
+            in_flight: Arc::new(InFlightCalls::new()),
+            limits: Arc::new(ToolLimits::default()),
+            tool_filter: Arc::new(ToolFilter::new()),
+            metrics: ToolMetrics::new(),
+            sampler: Arc::new(Sampler::new(Arc::clone(&client_requests))),
+            confirmer: Confirmer::new(Arc::clone(&client_requests)),
+            query_budget: crate::backend::CodeGraphConfig::default().query_budget(),
+            client_requests,
+            client_roots: false,
+            logger: Arc::new(McpLogger::new()),
+            changes_tx,
+            changes_rx,
+            watcher: None,
         }
     }
 
-    /// Ensure workspace is indexed (lazy — runs on first tool call)
-    async fn ensure_indexed(&mut self) {
+    /// Send log events to the client through `logger`, which the tracing
+    /// layer installed by `main` feeds.
+    pub fn with_logger(mut self, logger: Arc<McpLogger>) -> Self {
+        self.logger = logger;
+        self
+    }
+
+    /// Run tool calls within `limits`, which may be shared with other servers
+    pub fn with_limits(mut self, limits: Arc<ToolLimits>) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Expose only the tools `tool_filter` allows
+    pub fn with_tool_filter(mut self, tool_filter: Arc<ToolFilter>) -> Self {
+        self.tool_filter = tool_filter;
+        self
+    }
+
+    /// Run destructive operations without asking the user, for clients that
+    /// cannot ask
+    pub fn with_assume_yes(mut self, assume_yes: bool) -> Self {
+        self.confirmer = self.confirmer.with_assume_yes(assume_yes);
+        self
+    }
+
+    /// Bound traversals and searches by `budget`; `None` leaves them unbounded
+    pub fn with_query_budget(mut self, budget: Option<std::time::Duration>) -> Self {
+        self.query_budget = budget;
+        self
+    }
+
+    /// Ensure workspace is indexed (lazy — runs on first tool call, reporting
+    /// to that call's progress token)
+    async fn ensure_indexed(&mut self, progress: &McpProgress) {
         if self.indexed {
             return;
         }
         self.indexed = true;
         tracing::info!("Indexing workspace: {:?}", self.backend.workspace_folders);
-        let indexed = self.backend.index_workspace().await;
+        let indexed = self.backend.index_workspace(progress).await;
+        if progress.is_cancelled() {
+            // The next tool call indexes the workspace from scratch
+            self.discard_index().await;
+            return;
+        }
         tracing::info!("Indexed {} files", indexed);
+        self.start_watcher();
+    }
+
+    /// Drop the graph and the watcher, so that the next tool call indexes the
+    /// workspace again.
+    async fn discard_index(&mut self) {
+        match codegraph::CodeGraph::in_memory() {
+            Ok(graph) => {
+                *self.backend.graph.write().await = graph;
+                self.backend.query_engine.build_indexes().await;
+                self.watcher = None;
+                self.indexed = false;
+            }
+            Err(e) => tracing::warn!("Failed to create new graph: {}", e),
+        }
+    }
+
+    /// Watch the workspace folders, re-indexing changed files and reporting
+    /// the resources they affect.
+    fn start_watcher(&mut self) {
+        let mut watcher = match FileWatcher::headless(
+            Arc::clone(&self.backend.graph),
+            Arc::clone(&self.backend.parsers),
+            Arc::clone(&self.backend.memory_manager),
+        ) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                tracing::warn!("Failed to start file watcher: {}", e);
+                return;
+            }
+        };
+        watcher.set_workspace_walk(self.backend.workspace_walk());
+        for folder in &self.backend.workspace_folders {
+            if let Err(e) = watcher.watch(folder) {
+                tracing::warn!("Failed to watch {:?}: {}", folder, e);
+            }
+        }
+
+        let (watch_tx, watch_rx) = mpsc::unbounded_channel::<WatchedChange>();
+        watcher.add_change_listener(watch_tx);
+        self.forward_changes(watch_rx);
+        self.watcher = Some(watcher);
+    }
+
+    /// Keep the query indexes up to date with the changes a watcher reports,
+    /// and report the resources they affect.
+    fn forward_changes(&self, mut watch_rx: mpsc::UnboundedReceiver<WatchedChange>) {
+        let query_engine = Arc::clone(&self.backend.query_engine);
+        let changes_tx = self.changes_tx.clone();
+        tokio::spawn(async move {
+            while let Some(change) = watch_rx.recv().await {
+                // The server is gone; dropping `watch_rx` unsubscribes
+                if changes_tx.is_closed() {
+                    break;
+                }
+                // Rebuild the query indexes once per burst of changes
+                let mut batch = vec![change];
+                while let Ok(change) = watch_rx.try_recv() {
+                    batch.push(change);
+                }
+                query_engine.build_indexes().await;
+                for change in &batch {
+                    query_engine
+                        .update_file_vectors(&change.path.to_string_lossy())
+                        .await;
+                }
+                for change in batch.iter().flat_map(ResourceChange::from_watched) {
+                    let _ = changes_tx.send(change);
+                }
+            }
+        });
     }
 
-    /// Run the MCP server event loop
+    /// Send `notifications/resources/updated` for the subscribed resources
+    /// affected by `changes`, and `notifications/resources/list_changed` if
+    /// any change calls for it.
+    fn notify_changes(&self, changes: &[ResourceChange]) {
+        let mut updated: Vec<&str> = changes
+            .iter()
+            .flat_map(|change| change.affected_uris().iter().copied())
+            .filter(|uri| self.subscriptions.contains(*uri))
+            .collect();
+        updated.sort_unstable();
+        updated.dedup();
+        for uri in updated {
+            let params = ResourceUpdatedParams {
+                uri: uri.to_string(),
+            };
+            let notification = JsonRpcNotification::new(
+                "notifications/resources/updated",
+                serde_json::to_value(params).ok(),
+            );
+            let _ = self.outgoing_tx.send(notification.into());
+        }
+        if changes.iter().any(ResourceChange::list_changed) {
+            let notification =
+                JsonRpcNotification::new("notifications/resources/list_changed", None);
+            let _ = self.outgoing_tx.send(notification.into());
+        }
+    }
+
+    /// Run the MCP server event loop over stdio
     pub async fn run(&mut self) -> std::io::Result<()> {
-        let mut transport = AsyncStdioTransport::new();
+        self.run_with(AsyncTransport::stdio()).await
+    }
+
+    /// Run the MCP server event loop over `transport`, until the client
+    /// disconnects
+    pub async fn run_with(&mut self, transport: AsyncTransport) -> std::io::Result<()> {
+        let writer = transport.writer();
+        self.client_requests.attach(writer.clone());
+
+        // Notifications and responses are written in the order they are
+        // queued, so the progress of a call precedes its response
+        let writing = self.outgoing_rx.take().map(|mut outgoing| {
+            tokio::spawn(async move {
+                while let Some(message) = outgoing.recv().await {
+                    writer.write(&message).await?;
+                }
+                Ok::<_, std::io::Error>(())
+            })
+        });
+        let outgoing = self.outgoing_tx.clone();
+
+        // Requests are read on their own task, so that a cancellation or the
+        // response to a request of ours reaches a tool call while it runs
+        let (requests_tx, mut requests) = mpsc::unbounded_channel();
+        tokio::spawn(Self::read_requests(
+            transport,
+            Arc::clone(&self.in_flight),
+            Arc::clone(&self.client_requests),
+            requests_tx,
+        ));
 
         tracing::info!("MCP server starting...");
 
         loop {
-            match transport.read_request().await {
-                Ok(Some(request)) => {
-                    let response = self.handle_request(request).await;
-                    transport.write_response(&response).await?;
-                }
-                Ok(None) => {
-                    // Empty line, keep reading
-                    continue;
-                }
-                Err(e) => {
-                    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            tokio::select! {
+                request = requests.recv() => match request {
+                    Some(Ok(ReadRequests::Single(request))) => {
+                        let initialize = request.method == "initialize";
+                        if let Some(response) = self.answer(request).await {
+                            if outgoing.send(Outgoing::Response(response)).is_err() {
+                                break;
+                            }
+                        }
+                        // Log messages may only follow the initialize response
+                        if initialize {
+                            self.logger.attach(self.outgoing_tx.clone());
+                        }
+                    }
+                    Some(Ok(ReadRequests::Batch(members))) => {
+                        if members.is_empty() {
+                            let response = JsonRpcResponse::error(
+                                None,
+                                JsonRpcError::invalid_request("Empty batch"),
+                            );
+                            if outgoing.send(Outgoing::Response(response)).is_err() {
+                                break;
+                            }
+                            continue;
+                        }
+                        let mut initialize = false;
+                        let mut responses = Vec::new();
+                        for member in members {
+                            match member {
+                                Ok(request) => {
+                                    initialize |= request.method == "initialize";
+                                    responses.extend(self.answer(request).await);
+                                }
+                                Err(error) => responses.push(JsonRpcResponse::error(None, error)),
+                            }
+                        }
+                        // A batch of notifications only is not answered
+                        if !responses.is_empty() && outgoing.send(Outgoing::Batch(responses)).is_err() {
+                            break;
+                        }
+                        if initialize {
+                            self.logger.attach(self.outgoing_tx.clone());
+                        }
+                    }
+                    Some(Err(e)) => {
+                        let response = JsonRpcResponse::error(
+                            None,
+                            JsonRpcError::parse_error(format!("Parse error: {}", e)),
+                        );
+                        if outgoing.send(Outgoing::Response(response)).is_err() {
+                            break;
+                        }
+                    }
+                    None => {
                         tracing::info!("Client disconnected");
                         break;
                     }
-                    let response = JsonRpcResponse::error(
-                        None,
-                        JsonRpcError::parse_error(format!("Parse error: {}", e)),
-                    );
-                    transport.write_response(&response).await?;
+                },
+                Some(change) = self.changes_rx.recv() => {
+                    let mut changes = vec![change];
+                    while let Ok(change) = self.changes_rx.try_recv() {
+                        changes.push(change);
+                    }
+                    self.notify_changes(&changes);
                 }
             }
         }
 
-        Ok(())
+        // The writer's error, if it stopped the loop
+        match writing {
+            Some(writing) if writing.is_finished() => writing.await.unwrap_or(Ok(())),
+            _ => Ok(()),
+        }
+    }
+
+    /// Handle `request`, returning the response to write: none for a
+    /// notification or a cancelled call
+    async fn answer(&mut self, request: JsonRpcRequest) -> Option<JsonRpcResponse> {
+        let id = request.id.clone();
+        let response = self.handle_request(request).await;
+        (!self.in_flight.finish(id.as_ref()) && id.is_some()).then_some(response)
+    }
+
+    /// Read requests from `transport` into `requests` until the client
+    /// disconnects. Cancellations and responses to our own requests are
+    /// handled here, as they are read, rather than queued behind the call
+    /// waiting on them.
+    async fn read_requests(
+        mut transport: AsyncTransport,
+        in_flight: Arc<InFlightCalls>,
+        client_requests: Arc<ClientRequests>,
+        requests: mpsc::UnboundedSender<std::io::Result<ReadRequests>>,
+    ) {
+        // The request to queue, if the message is not handled here
+        let observe = |message: IncomingMessage| match message {
+            IncomingMessage::Request(request) => (!in_flight.observe(&request)).then_some(request),
+            IncomingMessage::Response(response) => {
+                if !client_requests.resolve(response) {
+                    tracing::warn!("Ignoring response to an unknown request");
+                }
+                None
+            }
+        };
+
+        loop {
+            let read = match transport.read_message().await {
+                Ok(Some(Incoming::Message(message))) => match observe(message) {
+                    Some(request) => Ok(ReadRequests::Single(request)),
+                    None => continue,
+                },
+                Ok(Some(Incoming::Batch(members))) => {
+                    let empty = members.is_empty();
+                    let members: Vec<_> = members
+                        .into_iter()
+                        .filter_map(|member| member.map(&observe).transpose())
+                        .collect();
+                    // Only cancellations and responses: nothing to answer
+                    if members.is_empty() && !empty {
+                        continue;
+                    }
+                    Ok(ReadRequests::Batch(members))
+                }
+                // Empty line, keep reading
+                Ok(None) => continue,
+                Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+                Err(e) => Err(e),
+            };
+            if requests.send(read).is_err() {
+                break;
+            }
+        }
     }
 
     /// Handle a JSON-RPC request
@@ -528,21 +948,34 @@ impl McpServer {
 
         match request.method.as_str() {
             "initialize" => self.handle_initialize(request.id, request.params).await,
-            "initialized" => {
-                // Respond immediately, index in background to avoid client timeout
+            "initialized" | "notifications/initialized" => {
+                // Indexing waits for the first tool call, so that it covers
+                // the roots the client lists now
+                self.refresh_roots().await;
+                JsonRpcResponse::success(request.id, Value::Null)
+            }
+            "notifications/roots/list_changed" => {
+                self.refresh_roots().await;
                 JsonRpcResponse::success(request.id, Value::Null)
             }
             "ping" => {
                 JsonRpcResponse::success(request.id, serde_json::to_value(PingResult {}).unwrap())
             }
-            "tools/list" => self.handle_tools_list(request.id).await,
+            "tools/list" => self.handle_tools_list(request.id, request.params).await,
             "tools/call" => self.handle_tools_call(request.id, request.params).await,
-            "resources/list" => self.handle_resources_list(request.id).await,
+            "resources/list" => self.handle_resources_list(request.id, request.params).await,
+            "resources/templates/list" => {
+                self.handle_resource_templates_list(request.id, request.params)
+            }
             "resources/read" => self.handle_resources_read(request.id, request.params).await,
-            "notifications/cancelled" => {
-                // Notification, no response needed
-                JsonRpcResponse::success(request.id, Value::Null)
+            "resources/subscribe" => {
+                self.handle_resources_subscribe(request.id, request.params, true)
+            }
+            "resources/unsubscribe" => {
+                self.handle_resources_subscribe(request.id, request.params, false)
             }
+            "logging/setLevel" => self.handle_set_level(request.id, request.params),
+            "completion/complete" => self.handle_complete(request.id, request.params).await,
             _ => {
                 JsonRpcResponse::error(request.id, JsonRpcError::method_not_found(&request.method))
             }
@@ -562,37 +995,16 @@ impl McpServer {
         // This allows a globally-configured MCP server to index the
         // correct project without per-project .mcp.json or --workspace.
         if let Some(roots) = &init_params.roots {
-            let root_paths: Vec<PathBuf> = roots
-                .iter()
-                .filter_map(|r| {
-                    r.uri
-                        .strip_prefix("file://")
-                        .map(PathBuf::from)
-                        .or_else(|| {
-                            // Accept bare paths too
-                            let p = PathBuf::from(&r.uri);
-                            if p.is_absolute() {
-                                Some(p)
-                            } else {
-                                None
-                            }
-                        })
-                })
-                .filter(|p| p.is_dir())
-                .collect();
-
-            if !root_paths.is_empty() {
-                tracing::info!(
-                    "Using {} workspace root(s) from client: {:?}",
-                    root_paths.len(),
-                    root_paths
-                );
-                self.backend.workspace_folders = root_paths;
-                // Recompute project slug from first root
-                self.backend.project_slug =
-                    crate::memory::project_slug(&self.backend.workspace_folders[0]);
-            }
+            self.apply_roots(roots).await;
         }
+        // Clients with the roots capability are asked for them once
+        // initialized, and again when they change
+        self.client_roots = init_params.capabilities.roots.is_some();
+
+        self.sampler
+            .set_supported(init_params.capabilities.sampling.is_some());
+        self.confirmer
+            .set_supported(init_params.capabilities.elicitation.is_some());
 
         if let Some(ref client_info) = init_params.client_info {
             tracing::info!(
@@ -602,17 +1014,25 @@ impl McpServer {
             );
         }
 
+        self.protocol_version = negotiate_protocol_version(init_params.protocol_version.as_deref());
+        tracing::info!(
+            "Protocol version {} (client asked for {})",
+            self.protocol_version,
+            init_params.protocol_version.as_deref().unwrap_or("none")
+        );
         self.initialized = true;
 
         let result = InitializeResult {
-            protocol_version: PROTOCOL_VERSION.to_string(),
+            protocol_version: self.protocol_version.to_string(),
             capabilities: ServerCapabilities {
+                completions: supports_completions_capability(self.protocol_version)
+                    .then_some(CompletionsCapability {}),
                 experimental: None,
                 logging: Some(LoggingCapability {}),
                 prompts: None,
                 resources: Some(ResourcesCapability {
-                    subscribe: Some(false),
-                    list_changed: Some(false),
+                    subscribe: Some(true),
+                    list_changed: Some(true),
                 }),
                 tools: Some(ToolsCapability {
                     list_changed: Some(false),
@@ -627,16 +1047,117 @@ impl McpServer {
         JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
     }
 
-    async fn handle_tools_list(&self, id: Option<Value>) -> JsonRpcResponse {
-        let result = ToolsListResult {
-            tools: get_all_tools(),
+    fn handle_set_level(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let params: SetLevelParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(
+                    id,
+                    JsonRpcError::invalid_params(format!("Invalid params: {}", e)),
+                );
+            }
+            None => {
+                return JsonRpcResponse::error(id, JsonRpcError::invalid_params("Missing params"));
+            }
+        };
+        self.logger.set_level(params.level);
+        JsonRpcResponse::success(id, serde_json::json!({}))
+    }
+
+    /// Ask the client for its roots, if it has them, and switch to them.
+    async fn refresh_roots(&mut self) {
+        if !self.client_roots {
+            return;
+        }
+        let result = self
+            .client_requests
+            .send("roots/list", None, ROOTS_TIMEOUT)
+            .await
+            .and_then(|r| serde_json::from_value::<ListRootsResult>(r).map_err(|e| e.to_string()));
+        match result {
+            Ok(result) => self.apply_roots(&result.roots).await,
+            Err(e) => tracing::warn!("Failed to list client roots: {}", e),
+        }
+    }
+
+    /// Use the client's roots as workspace folders. If the workspace is
+    /// already indexed, a change of folders drops the graph and the watcher,
+    /// and the next tool call indexes the new folders.
+    async fn apply_roots(&mut self, roots: &[Root]) {
+        if self.shared {
+            return;
+        }
+        let root_paths: Vec<PathBuf> = roots
+            .iter()
+            .filter_map(|r| {
+                r.uri
+                    .strip_prefix("file://")
+                    .map(PathBuf::from)
+                    .or_else(|| {
+                        // Accept bare paths too
+                        let p = PathBuf::from(&r.uri);
+                        if p.is_absolute() {
+                            Some(p)
+                        } else {
+                            None
+                        }
+                    })
+            })
+            .filter(|p| p.is_dir())
+            .collect();
+        if root_paths.is_empty() || root_paths == self.backend.workspace_folders {
+            return;
+        }
+
+        tracing::info!(
+            "Using {} workspace root(s) from client: {:?}",
+            root_paths.len(),
+            root_paths
+        );
+        self.backend.workspace_folders = root_paths;
+        // Recompute project slug from first root
+        self.backend.project_slug = crate::memory::project_slug(&self.backend.workspace_folders[0]);
+
+        if self.indexed {
+            self.discard_index().await;
+        }
+    }
+
+    async fn handle_tools_list(&self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
+        let cursor = Self::list_cursor(params);
+        let tools: Vec<Tool> = get_all_tools()
+            .into_iter()
+            .filter(|tool| self.tool_filter.allows(&tool.name))
+            .collect();
+        let page = paginate(tools, cursor.as_deref(), LIST_PAGE_SIZE);
+        let (tools, next_cursor) = match page {
+            Ok(page) => page,
+            Err(e) => return JsonRpcResponse::error(id, JsonRpcError::invalid_params(e)),
         };
+        // Output schemas and annotations are unknown to clients of older
+        // revisions
+        let structured = supports_structured_output(self.protocol_version);
+        let annotated = supports_tool_annotations(self.protocol_version);
+        let tools = tools
+            .into_iter()
+            .map(|tool| Tool {
+                output_schema: tool.output_schema.filter(|_| structured),
+                annotations: tool.annotations.filter(|_| annotated),
+                ..tool
+            })
+            .collect();
+        let result = ToolsListResult { tools, next_cursor };
         JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
     }
 
-    async fn handle_tools_call(&mut self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
-        self.ensure_indexed().await;
+    /// The `cursor` of a list request; list params are all optional
+    fn list_cursor(params: Option<Value>) -> Option<String> {
+        params
+            .and_then(|p| serde_json::from_value::<PaginatedParams>(p).ok())
+            .and_then(|p| p.cursor)
+    }
 
+    async fn handle_tools_call(&mut self, id: Option<Value>, params: Option<Value>) -> JsonRpcResponse {
         let params: ToolCallParams = match params {
             Some(p) => match serde_json::from_value(p) {
                 Ok(p) => p,
@@ -652,13 +1173,61 @@ impl McpServer {
             }
         };
 
-        match self.execute_tool(&params.name, params.arguments).await {
+        let progress_token = params.meta.as_ref().and_then(|m| m.progress_token.clone());
+        let cancel = self.in_flight.token(id.as_ref());
+        let progress = McpProgress::new(self.outgoing_tx.clone(), progress_token)
+            .with_cancellation(cancel.clone());
+        self.ensure_indexed(&progress).await;
+
+        // A large result of a read-only tool is paged; repeating the call
+        // with `cursor` resumes it. Repeating a write would redo it.
+        let paged = is_read_only(&params.name);
+        let cursor = match params
+            .arguments
+            .as_ref()
+            .and_then(|args| args.get("cursor"))
+            .and_then(|c| c.as_str())
+        {
+            Some(_) if !paged => Err(format!("{} does not take a cursor", params.name)),
+            cursor => cursor.map(pagination::decode_cursor).transpose(),
+        };
+
+        let start = std::time::Instant::now();
+        let outcome = match cursor {
+            // Cancelled while waiting, e.g. for indexing
+            _ if cancel.is_cancelled() => Err("Cancelled".to_string()),
+            Err(e) => Err(e),
+            Ok(offset) => self
+                .execute_tool(&params.name, params.arguments, &progress, &cancel)
+                .await
+                .map(|result| {
+                    if paged {
+                        paginate_result(result, offset.unwrap_or(0), pagination::MAX_RESULT_BYTES)
+                    } else {
+                        result
+                    }
+                }),
+        };
+        self.metrics.record(&params.name, start.elapsed(), &outcome);
+        // A cancelled reindex leaves only part of the workspace in the graph
+        if params.name == "codegraph_reindex_workspace" && cancel.is_cancelled() && !self.shared {
+            self.discard_index().await;
+        }
+        match outcome {
             Ok(result) => {
+                // Announced after the response, from the event loop
+                for change in ResourceChange::from_tool(&params.name) {
+                    let _ = self.changes_tx.send(change);
+                }
+                // The text block stays for clients that ignore structured content
                 let tool_result = ToolCallResult {
                     content: vec![ToolResultContent::Text {
                         text: serde_json::to_string_pretty(&result)
                             .unwrap_or_else(|_| result.to_string()),
                     }],
+                    structured_content: (has_output_schema(&params.name)
+                        && supports_structured_output(self.protocol_version))
+                    .then_some(result),
                     is_error: None,
                 };
                 JsonRpcResponse::success(id, serde_json::to_value(tool_result).unwrap())
@@ -668,6 +1237,7 @@ impl McpServer {
                     content: vec![ToolResultContent::Text {
                         text: format!("Error: {}", e),
                     }],
+                    structured_content: None,
                     is_error: Some(true),
                 };
                 JsonRpcResponse::success(id, serde_json::to_value(tool_result).unwrap())
@@ -675,13 +1245,78 @@ impl McpServer {
         }
     }
 
-    async fn handle_resources_list(&self, id: Option<Value>) -> JsonRpcResponse {
+    async fn handle_resources_list(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+    ) -> JsonRpcResponse {
+        let cursor = Self::list_cursor(params);
+        let page = paginate(get_all_resources(), cursor.as_deref(), LIST_PAGE_SIZE);
+        let (resources, next_cursor) = match page {
+            Ok(page) => page,
+            Err(e) => return JsonRpcResponse::error(id, JsonRpcError::invalid_params(e)),
+        };
         let result = ResourcesListResult {
-            resources: get_all_resources(),
+            resources,
+            next_cursor,
         };
         JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
     }
 
+    fn handle_resource_templates_list(
+        &self,
+        id: Option<Value>,
+        params: Option<Value>,
+    ) -> JsonRpcResponse {
+        let cursor = Self::list_cursor(params);
+        let page = paginate(get_resource_templates(), cursor.as_deref(), LIST_PAGE_SIZE);
+        let (resource_templates, next_cursor) = match page {
+            Ok(page) => page,
+            Err(e) => return JsonRpcResponse::error(id, JsonRpcError::invalid_params(e)),
+        };
+        let result = ResourceTemplatesListResult {
+            resource_templates,
+            next_cursor,
+        };
+        JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+    }
+
+    async fn handle_complete(
+        &mut self,
+        id: Option<Value>,
+        params: Option<Value>,
+    ) -> JsonRpcResponse {
+        let params: CompleteParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(
+                    id,
+                    JsonRpcError::invalid_params(format!("Invalid params: {}", e)),
+                );
+            }
+            None => {
+                return JsonRpcResponse::error(id, JsonRpcError::invalid_params("Missing params"));
+            }
+        };
+
+        // Suggestions come from the index, which a client may ask for before
+        // calling any tool
+        let cancel = self.in_flight.token(id.as_ref());
+        self.ensure_indexed(&McpProgress::disabled().with_cancellation(cancel))
+            .await;
+
+        let completion = super::completion::complete(
+            &params,
+            &self.backend.graph,
+            &self.backend.query_engine,
+            &self.backend.memory_manager,
+            &self.backend.workspace_folders,
+        )
+        .await;
+        let result = CompleteResult { completion };
+        JsonRpcResponse::success(id, serde_json::to_value(result).unwrap())
+    }
+
     async fn handle_resources_read(
         &self,
         id: Option<Value>,
@@ -705,8 +1340,10 @@ impl McpServer {
         match super::resources::read_resource(
             &params.uri,
             Arc::clone(&self.backend.graph),
+            &self.backend.query_engine,
             &self.backend.memory_manager,
             &self.backend.workspace_folders,
+            &self.metrics,
         )
         .await
         {
@@ -718,10 +1355,91 @@ impl McpServer {
         }
     }
 
+    /// Subscribe to (or unsubscribe from) updates of a resource.
+    fn handle_resources_subscribe(
+        &mut self,
+        id: Option<Value>,
+        params: Option<Value>,
+        subscribe: bool,
+    ) -> JsonRpcResponse {
+        let params: ResourceSubscribeParams = match params.map(serde_json::from_value) {
+            Some(Ok(p)) => p,
+            Some(Err(e)) => {
+                return JsonRpcResponse::error(
+                    id,
+                    JsonRpcError::invalid_params(format!("Invalid params: {}", e)),
+                );
+            }
+            None => {
+                return JsonRpcResponse::error(id, JsonRpcError::invalid_params("Missing params"));
+            }
+        };
+
+        if !get_all_resources().iter().any(|r| r.uri == params.uri) {
+            return JsonRpcResponse::error(
+                id,
+                JsonRpcError::invalid_params(format!("Resource not found: {}", params.uri)),
+            );
+        }
+        if subscribe {
+            self.subscriptions.insert(params.uri);
+        } else {
+            self.subscriptions.remove(&params.uri);
+        }
+        JsonRpcResponse::success(id, serde_json::json!({}))
+    }
+
+    /// What the user is asked before `name` runs, or `None` if the arguments
+    /// are invalid and the call will fail anyway
+    async fn confirmation_message(&self, name: &str, args: &Value) -> Option<String> {
+        match name {
+            "codegraph_memory_invalidate" => {
+                let id = args.get("id").and_then(|v| v.as_str())?;
+                // A memory that cannot be looked up may still be invalidated
+                let title = match self.backend.memory_manager.get(id).await {
+                    Ok(Some(memory)) => memory.title,
+                    Ok(None) => return None,
+                    Err(_) => id.to_string(),
+                };
+                Some(format!(
+                    "Invalidate the memory \"{}\"? It will no longer be returned by searches \
+                     or shown on the code it is linked to.",
+                    title
+                ))
+            }
+            _ => Some(format!("Allow {} to run?", name)),
+        }
+    }
+
     /// Execute a tool by name - delegates to query engine and other components
-    async fn execute_tool(&self, name: &str, args: Option<Value>) -> Result<Value, String> {
+    async fn execute_tool(
+        &self,
+        name: &str,
+        args: Option<Value>,
+        progress: &McpProgress,
+        cancel: &CancellationToken,
+    ) -> Result<Value, String> {
+        if !self.tool_filter.allows(name) {
+            return Err(format!("Tool {} is disabled on this server", name));
+        }
         let args = args.unwrap_or(Value::Object(serde_json::Map::new()));
 
+        // Asked before taking a permit, as the user may take a while
+        if needs_confirmation(name) {
+            if let Some(message) = self.confirmation_message(name, &args).await {
+                self.confirmer.confirm(&message).await?;
+            }
+        }
+
+        // A batch only dispatches: its queries take permits of their own
+        let _permit = match name {
+            "codegraph_batch_query" => None,
+            _ => Some(self.limits.acquire(name, cancel).await?),
+        };
+        if cancel.is_cancelled() {
+            return Err("Cancelled".to_string());
+        }
+
         match name {
             // ==================== Search Tools ====================
             "codegraph_symbol_search" => {
@@ -761,11 +1479,28 @@ impl McpServer {
                     .and_then(|v| v.as_bool())
                     .unwrap_or(true);
 
+                let mode = match args.get("mode").and_then(|v| v.as_str()) {
+                    None | Some("text") => crate::ai_query::SearchMode::Text,
+                    Some("regex") => {
+                        crate::ai_query::QueryEngine::compile_search_regex(query)?;
+                        crate::ai_query::SearchMode::Regex
+                    }
+                    Some(other) => return Err(format!("Invalid mode: {other}")),
+                };
+                let group_by = Self::parse_group_by(&args)?;
+
                 let options = crate::ai_query::SearchOptions::new()
-                    .with_limit(limit)
+                    .with_limit(if group_by.is_some() {
+                        crate::ai_query::MAX_GROUPED_MATCHES
+                    } else {
+                        limit
+                    })
                     .with_compact(compact)
                     .with_symbol_types(symbol_types)
-                    .with_include_private(include_private);
+                    .with_include_private(include_private)
+                    .with_mode(mode)
+                    .with_time_budget(self.query_budget)
+                    .with_cancellation(cancel.clone());
                 let mut result = self
                     .backend
                     .query_engine
@@ -776,6 +1511,20 @@ impl McpServer {
                 let mut seen = std::collections::HashSet::new();
                 result.results.retain(|m| seen.insert(m.node_id));
 
+                if let Some(group_by) = group_by {
+                    let mut groups = crate::ai_query::group_results(
+                        result.results.iter().map(|m| (&m.symbol, m.score)),
+                        group_by,
+                    );
+                    groups.truncate(limit);
+                    return Ok(serde_json::json!({
+                        "groups": groups,
+                        "total_matches": result.total_matches,
+                        "query_time_ms": result.query_time_ms,
+                        "truncated": result.truncated,
+                    }));
+                }
+
                 Ok(serde_json::to_value(result).map_err(|e| e.to_string())?)
             }
 
@@ -950,41 +1699,244 @@ impl McpServer {
                 let limit = args
                     .get("limit")
                     .and_then(|v| v.as_u64())
-                    .map(|v| v as usize);
-
-                // param_count is Option<(min, max)>
-                let param_count = if let Some(exact) = exact_param_count {
-                    Some((exact, exact))
-                } else if min_params.is_some() || max_params.is_some() {
-                    Some((min_params.unwrap_or(0), max_params.unwrap_or(usize::MAX)))
-                } else {
-                    None
-                };
-
-                let pattern = crate::ai_query::SignaturePattern {
-                    name_pattern,
-                    return_type,
-                    param_count,
-                    modifiers,
-                };
+                    .map(|v| v as usize);
+
+                // param_count is Option<(min, max)>
+                let param_count = if let Some(exact) = exact_param_count {
+                    Some((exact, exact))
+                } else if min_params.is_some() || max_params.is_some() {
+                    Some((min_params.unwrap_or(0), max_params.unwrap_or(usize::MAX)))
+                } else {
+                    None
+                };
+
+                let pattern = crate::ai_query::SignaturePattern {
+                    name_pattern,
+                    return_type,
+                    param_count,
+                    modifiers,
+                };
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .find_by_signature(&pattern, limit)
+                    .await;
+
+                // Deduplicate by node_id
+                let mut seen = std::collections::HashSet::new();
+                let deduped: Vec<_> = result
+                    .into_iter()
+                    .filter(|m| seen.insert(m.node_id))
+                    .collect();
+
+                Ok(serde_json::to_value(deduped).map_err(|e| e.to_string())?)
+            }
+
+            // ==================== Graph Traversal Tools ====================
+            "codegraph_get_hot_symbols" => {
+                let metric = match args.get("metric").and_then(|v| v.as_str()) {
+                    None | Some("combined") => crate::ai_query::CentralityMetric::Combined,
+                    Some("pagerank") => crate::ai_query::CentralityMetric::PageRank,
+                    Some("betweenness") => crate::ai_query::CentralityMetric::Betweenness,
+                    Some(other) => return Err(format!("Invalid metric: {other}")),
+                };
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(20);
+                let uri_filter = args.get("uri").and_then(|v| v.as_str()).map(|uri| {
+                    tower_lsp::lsp_types::Url::parse(uri)
+                        .ok()
+                        .and_then(|url| url.to_file_path().ok())
+                        .map(|path| path.to_string_lossy().to_string())
+                        .unwrap_or_else(|| uri.to_string())
+                });
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .get_hot_symbols(metric, limit, uri_filter.as_deref())
+                    .await;
+
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
+            }
+
+            "codegraph_find_type_usages" => {
+                let uri = args.get("uri").and_then(|v| v.as_str());
+                let line = args.get("line").and_then(|v| v.as_u64()).map(|v| v as u32);
+                let node_id = args
+                    .get("nodeId")
+                    .or_else(|| args.get("node_id"))
+                    .and_then(|v| v.as_str());
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(100);
+
+                let type_node = if let Some(id_str) = node_id {
+                    self.resolve_node_id(id_str).await
+                } else if let (Some(u), Some(l)) = (uri, line) {
+                    self.find_nearest_node_with_fallback(u, l)
+                        .await
+                        .map(|(id, _)| id)
+                } else {
+                    None
+                };
+                let type_node =
+                    type_node.ok_or("Could not find type. Provide either nodeId or uri+line.")?;
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .find_type_usages(type_node, limit)
+                    .await
+                    .ok_or("Type not found")?;
+
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
+            }
+
+            "codegraph_find_implementations" => {
+                let uri = args.get("uri").and_then(|v| v.as_str());
+                let line = args.get("line").and_then(|v| v.as_u64()).map(|v| v as u32);
+                let node_id = args
+                    .get("nodeId")
+                    .or_else(|| args.get("node_id"))
+                    .and_then(|v| v.as_str());
+                let direction = match args.get("direction").and_then(|v| v.as_str()) {
+                    None | Some("implementations") => {
+                        crate::ai_query::ImplementationDirection::Implementations
+                    }
+                    Some("supertypes") => crate::ai_query::ImplementationDirection::Supertypes,
+                    Some(other) => {
+                        return Err(format!(
+                            "Invalid direction: {other}. Expected 'implementations' or 'supertypes'"
+                        ))
+                    }
+                };
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(100);
+
+                let type_node = if let Some(id_str) = node_id {
+                    self.resolve_node_id(id_str).await
+                } else if let (Some(u), Some(l)) = (uri, line) {
+                    self.find_nearest_node_with_fallback(u, l)
+                        .await
+                        .map(|(id, _)| id)
+                } else {
+                    None
+                };
+                let type_node =
+                    type_node.ok_or("Could not find type. Provide either nodeId or uri+line.")?;
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .find_implementations(type_node, direction, limit)
+                    .await
+                    .ok_or("Type not found")?;
+
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
+            }
+
+            "codegraph_reaches" => {
+                let uri = args.get("uri").and_then(|v| v.as_str());
+                let line = args.get("line").and_then(|v| v.as_u64()).map(|v| v as u32);
+                let node_id = args
+                    .get("nodeId")
+                    .or_else(|| args.get("node_id"))
+                    .and_then(|v| v.as_str());
+                let string_list = |keys: &[&str]| -> Vec<String> {
+                    keys.iter()
+                        .find_map(|k| args.get(*k))
+                        .and_then(|v| v.as_array())
+                        .map(|arr| {
+                            arr.iter()
+                                .filter_map(|v| v.as_str().map(String::from))
+                                .collect()
+                        })
+                        .unwrap_or_default()
+                };
+
+                let mut predicate = crate::ai_query::ReachabilityPredicate::new()
+                    .with_sinks(string_list(&["sinks"]))
+                    .with_categories(string_list(&["categories"]))
+                    .with_edge_types(string_list(&["edgeTypes", "edge_types"]));
+                if let Some(depth) = args
+                    .get("maxDepth")
+                    .or_else(|| args.get("max_depth"))
+                    .and_then(|v| v.as_u64())
+                {
+                    predicate = predicate.with_max_depth(depth as u32);
+                }
+                if let Some(max) = args
+                    .get("maxMatches")
+                    .or_else(|| args.get("max_matches"))
+                    .and_then(|v| v.as_u64())
+                {
+                    predicate = predicate.with_max_matches(max as usize);
+                }
+
+                let start_node = if let Some(id_str) = node_id {
+                    self.resolve_node_id(id_str).await
+                } else if let (Some(u), Some(l)) = (uri, line) {
+                    self.find_nearest_node_with_fallback(u, l)
+                        .await
+                        .map(|(id, _)| id)
+                } else {
+                    None
+                };
+                let start_node = start_node
+                    .ok_or("Could not find function. Provide either nodeId or uri+line.")?;
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .reaches(start_node, &predicate, cancel)
+                    .await?;
+
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
+            }
+
+            "codegraph_find_path" => {
+                let from = self.resolve_symbol_arg(&args, "from").await.ok_or(
+                    "Could not find start symbol. Provide fromNodeId or fromUri+fromLine.",
+                )?;
+                let to = self
+                    .resolve_symbol_arg(&args, "to")
+                    .await
+                    .ok_or("Could not find target symbol. Provide toNodeId or toUri+toLine.")?;
+                let edge_types: Vec<String> = args
+                    .get("edgeTypes")
+                    .or_else(|| args.get("edge_types"))
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let max_depth = args
+                    .get("maxDepth")
+                    .or_else(|| args.get("max_depth"))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as u32)
+                    .unwrap_or(10);
 
                 let result = self
                     .backend
                     .query_engine
-                    .find_by_signature(&pattern, limit)
-                    .await;
+                    .find_path(from, to, &edge_types, max_depth, cancel)
+                    .await?;
 
-                // Deduplicate by node_id
-                let mut seen = std::collections::HashSet::new();
-                let deduped: Vec<_> = result
-                    .into_iter()
-                    .filter(|m| seen.insert(m.node_id))
-                    .collect();
-
-                Ok(serde_json::to_value(deduped).map_err(|e| e.to_string())?)
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
             }
 
-            // ==================== Graph Traversal Tools ====================
             "codegraph_get_callers" => {
                 let uri = args.get("uri").and_then(|v| v.as_str());
                 let line = args.get("line").and_then(|v| v.as_u64()).map(|v| v as u32);
@@ -1000,7 +1952,7 @@ impl McpServer {
 
                 // Use fallback for uri+line, exact match for node_id
                 let (start_node, used_fallback) = if let Some(id_str) = node_id {
-                    (parse_node_id(id_str), false)
+                    (self.resolve_node_id(id_str).await, false)
                 } else if let (Some(u), Some(l)) = (uri, line) {
                     match self.find_nearest_node_with_fallback(u, l).await {
                         Some((id, fallback)) => (Some(id), fallback),
@@ -1044,7 +1996,7 @@ impl McpServer {
 
                 // Use fallback for uri+line, exact match for node_id
                 let (start_node, used_fallback) = if let Some(id_str) = node_id {
-                    (parse_node_id(id_str), false)
+                    (self.resolve_node_id(id_str).await, false)
                 } else if let (Some(u), Some(l)) = (uri, line) {
                     match self.find_nearest_node_with_fallback(u, l).await {
                         Some((id, fallback)) => (Some(id), fallback),
@@ -1099,7 +2051,7 @@ impl McpServer {
 
                 // Use fallback for uri+line, exact match for node_id
                 let (start_node, used_fallback) = if let Some(id_str) = node_id {
-                    (parse_node_id(id_str), false)
+                    (self.resolve_node_id(id_str).await, false)
                 } else if let (Some(u), Some(l)) = (uri, line) {
                     match self.find_nearest_node_with_fallback(u, l).await {
                         Some((id, fallback)) => (Some(id), fallback),
@@ -1138,6 +2090,7 @@ impl McpServer {
                     .get("summary")
                     .and_then(|v| v.as_bool())
                     .unwrap_or(false);
+                let group_by = Self::parse_group_by(&args)?;
 
                 if let Some(start) = start_node {
                     let direction = match direction_str {
@@ -1152,13 +2105,27 @@ impl McpServer {
                         max_nodes: limit,
                     };
 
+                    let budgeted = cancel.clone().with_budget(self.query_budget);
                     let result = self
                         .backend
                         .query_engine
-                        .traverse_graph(start, direction, max_depth, &filter)
+                        .traverse_graph(start, direction, max_depth, &filter, &budgeted)
                         .await;
+                    // Cut short by the budget rather than by the client
+                    let truncated = budgeted.timed_out() && !cancel.is_cancelled();
 
-                    if summary {
+                    if let Some(group_by) = group_by {
+                        let groups = crate::ai_query::group_results(
+                            result.iter().map(|n| (&n.symbol, 1.0)),
+                            group_by,
+                        );
+                        Ok(serde_json::json!({
+                            "groups": groups,
+                            "node_count": result.len(),
+                            "direction": direction_str,
+                            "truncated": truncated,
+                        }))
+                    } else if summary {
                         let node_count = result.len();
                         let edge_types_seen: Vec<String> = result
                             .iter()
@@ -1173,12 +2140,15 @@ impl McpServer {
                                 "max_depth": max_depth,
                                 "direction": direction_str,
                                 "edge_types_seen": edge_types_seen,
-                            }
+                            },
+                            "truncated": truncated,
                         }))
                     } else {
+                        let mut response = serde_json::json!({
+                            "nodes": result,
+                            "truncated": truncated,
+                        });
                         // Add fallback metadata if used
-                        let mut response =
-                            serde_json::to_value(result).map_err(|e| e.to_string())?;
                         if used_fallback {
                             if let Some(obj) = response.as_object_mut() {
                                 let symbol_name = {
@@ -1228,7 +2198,7 @@ impl McpServer {
 
                 // Use fallback for uri+line, exact match for node_id
                 let (target_node, used_fallback) = if let Some(id_str) = node_id {
-                    (parse_node_id(id_str), false)
+                    (self.resolve_node_id(id_str).await, false)
                 } else if let (Some(u), Some(l)) = (uri, line) {
                     match self.find_nearest_node_with_fallback(u, l).await {
                         Some((id, fallback)) => (Some(id), fallback),
@@ -1286,7 +2256,7 @@ impl McpServer {
 
                 // Use fallback for uri+line, exact match for node_id
                 let (target_node, used_fallback) = if let Some(id_str) = node_id {
-                    (parse_node_id(id_str), false)
+                    (self.resolve_node_id(id_str).await, false)
                 } else if let (Some(u), Some(l)) = (uri, line) {
                     match self.find_nearest_node_with_fallback(u, l).await {
                         Some((id, fallback)) => (Some(id), fallback),
@@ -1519,9 +2489,14 @@ impl McpServer {
                         .get("symbol_id")
                         .and_then(|v| v.as_str())
                         .unwrap_or("");
+                    let test_files = result
+                        .get("test_files")
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!([]));
                     Ok(serde_json::json!({
                         "symbol": symbol_name,
                         "symbol_id": symbol_id,
+                        "test_files": test_files,
                         "summary": {
                             "total_impacted": total_impacted,
                             "direct_impacted": direct_impacted,
@@ -1577,6 +2552,49 @@ impl McpServer {
                 }
             }
 
+            "codegraph_find_cycles" => {
+                let level = match args.get("level").and_then(|v| v.as_str()) {
+                    None | Some("file") | Some("module") => crate::ai_query::CycleLevel::File,
+                    Some("symbol") => crate::ai_query::CycleLevel::Symbol,
+                    Some(other) => return Err(format!("Invalid level: {other}")),
+                };
+                let edge_types: Vec<String> = args
+                    .get("edgeTypes")
+                    .or_else(|| args.get("edge_types"))
+                    .and_then(|v| v.as_array())
+                    .map(|arr| {
+                        arr.iter()
+                            .filter_map(|v| v.as_str().map(String::from))
+                            .collect()
+                    })
+                    .unwrap_or_else(|| {
+                        vec![
+                            "imports".to_string(),
+                            "importsfrom".to_string(),
+                            "calls".to_string(),
+                        ]
+                    });
+                let min_size = args
+                    .get("minSize")
+                    .or_else(|| args.get("min_size"))
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(2);
+                let limit = args
+                    .get("limit")
+                    .and_then(|v| v.as_u64())
+                    .map(|v| v as usize)
+                    .unwrap_or(50);
+
+                let result = self
+                    .backend
+                    .query_engine
+                    .find_cycles(&edge_types, level, min_size, limit)
+                    .await;
+
+                Ok(serde_json::to_value(&result).map_err(|e| e.to_string())?)
+            }
+
             // ==================== Analysis Tools ====================
             "codegraph_get_ai_context" => {
                 let uri = args
@@ -1856,6 +2874,7 @@ impl McpServer {
                             "line_start": f.line_start,
                             "line_end": f.line_end,
                             "details": {
+                                "complexity_cognitive": f.details.complexity_cognitive,
                                 "complexity_branches": f.details.complexity_branches,
                                 "complexity_loops": f.details.complexity_loops,
                                 "complexity_logical_ops": f.details.complexity_logical_ops,
@@ -1950,6 +2969,7 @@ impl McpServer {
                             "type": format!("{:?}", c.node_type),
                             "confidence": c.confidence,
                             "is_public": c.is_public,
+                            "has_callers": c.has_callers,
                         })
                     })
                     .collect();
@@ -1961,7 +2981,54 @@ impl McpServer {
                         "unused_count": result.candidates.len(),
                         "scope": result.scope,
                         "min_confidence": result.min_confidence,
+                        "entry_points": result.entry_points,
+                    }
+                }))
+            }
+
+            "codegraph_summarize_module" => {
+                let uri = args
+                    .get("uri")
+                    .and_then(|v| v.as_str())
+                    .ok_or("Missing 'uri' parameter")?;
+                let url =
+                    tower_lsp::lsp_types::Url::parse(uri).map_err(|_| "Invalid URI".to_string())?;
+                let path = url
+                    .to_file_path()
+                    .map_err(|_| "Invalid file path".to_string())?;
+                let path_str = path.to_string_lossy().to_string();
+
+                let outline = self
+                    .backend
+                    .query_engine
+                    .get_file_outline(&path_str)
+                    .await
+                    .ok_or_else(|| format!("No symbols indexed for {}", path_str))?;
+
+                let mut summary = None;
+                if self.sampler.is_available() {
+                    let mut text = String::new();
+                    for node in &outline.symbols {
+                        Self::write_outline(&mut text, node, 0);
+                    }
+                    let instruction = format!(
+                        "Summarize the purpose and main responsibilities of {} in a short \
+                         paragraph, based on its outline:",
+                        path_str
+                    );
+                    match self.sampler.sample_summary(&instruction, &text).await {
+                        Ok(text) => summary = Some((text, SummarySource::Sampled)),
+                        Err(e) => tracing::warn!("Sampling failed, listing symbols instead: {}", e),
                     }
+                }
+                let (summary, source) = summary
+                    .unwrap_or_else(|| (Self::outline_summary(&outline), SummarySource::Outline));
+
+                Ok(serde_json::json!({
+                    "file": outline.file,
+                    "summary": summary,
+                    "summary_source": source,
+                    "total_symbols": outline.total_symbols,
                 }))
             }
 
@@ -1983,6 +3050,10 @@ impl McpServer {
                     .unwrap_or(true);
                 let kinds = Self::parse_kinds_filter(&args);
                 let tags = Self::parse_tags_filter(&args);
+                let summarize = args
+                    .get("summarize")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
                 let config = crate::memory::SearchConfig {
                     limit,
@@ -2002,7 +3073,7 @@ impl McpServer {
                 // Deduplicate by title and commit hash (git-mined commits create duplicates)
                 let mut seen_titles = std::collections::HashSet::new();
                 let mut seen_commits = std::collections::HashSet::new();
-                let results_json: Vec<serde_json::Value> = results
+                let mut results_json: Vec<serde_json::Value> = results
                     .iter()
                     .filter(|r| {
                         // Skip if commit hash already seen
@@ -2027,6 +3098,9 @@ impl McpServer {
                         })
                     })
                     .collect();
+                if summarize {
+                    self.summarize_memories(&mut results_json).await;
+                }
 
                 Ok(serde_json::json!({
                     "results": results_json,
@@ -2088,6 +3162,10 @@ impl McpServer {
                     .get("id")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'id' parameter")?;
+                let summarize = args
+                    .get("summarize")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
                 let result = self
                     .backend
@@ -2097,15 +3175,22 @@ impl McpServer {
                     .map_err(|e| format!("Failed to get memory: {:?}", e))?;
 
                 match result {
-                    Some(memory) => Ok(serde_json::json!({
-                        "id": memory.id,
-                        "title": memory.title,
-                        "content": memory.content,
-                        "kind": memory.kind.discriminant_name(),
-                        "tags": memory.tags,
-                        "created_at": memory.temporal.created_at.to_rfc3339(),
-                        "invalidated": memory.temporal.invalid_at.is_some(),
-                    })),
+                    Some(memory) => {
+                        let mut entry = serde_json::json!({
+                            "id": memory.id,
+                            "title": memory.title,
+                            "content": memory.content,
+                            "kind": memory.kind.discriminant_name(),
+                            "tags": memory.tags,
+                            "created_at": memory.temporal.created_at.to_rfc3339(),
+                            "invalidated": memory.temporal.invalid_at.is_some(),
+                        });
+                        if summarize {
+                            self.summarize_memories(std::slice::from_mut(&mut entry))
+                                .await;
+                        }
+                        Ok(entry)
+                    }
                     None => Ok(serde_json::json!({
                         "error": "Memory not found"
                     })),
@@ -2139,6 +3224,10 @@ impl McpServer {
                     .unwrap_or(true);
                 let kinds = Self::parse_kinds_filter(&args);
                 let tags = Self::parse_tags_filter(&args);
+                let summarize = args
+                    .get("summarize")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
                 let config = crate::memory::SearchConfig {
                     limit,
                     current_only,
@@ -2157,7 +3246,7 @@ impl McpServer {
                 // Deduplicate by title and commit hash (git-mined commits create duplicates)
                 let mut seen_titles = std::collections::HashSet::new();
                 let mut seen_commits = std::collections::HashSet::new();
-                let results_json: Vec<serde_json::Value> = results
+                let mut results_json: Vec<serde_json::Value> = results
                     .iter()
                     .filter(|r| {
                         if let crate::memory::MemorySource::GitHistory { ref commit_hash } =
@@ -2180,6 +3269,9 @@ impl McpServer {
                         })
                     })
                     .collect();
+                if summarize {
+                    self.summarize_memories(&mut results_json).await;
+                }
 
                 Ok(serde_json::json!({
                     "uri": uri,
@@ -2188,6 +3280,44 @@ impl McpServer {
                 }))
             }
 
+            "codegraph_memory_update" => {
+                let params: crate::handlers::MemoryUpdateParams =
+                    serde_json::from_value(args.clone())
+                        .map_err(|e| format!("Invalid parameters: {}", e))?;
+                let id = params.id.clone();
+
+                let mut memory = self
+                    .backend
+                    .memory_manager
+                    .get(&id)
+                    .await
+                    .map_err(|e| format!("Failed to get memory: {:?}", e))?
+                    .ok_or_else(|| format!("Memory not found: {}", id))?;
+                params.apply_to(&mut memory);
+
+                let updated = serde_json::json!({
+                    "id": id,
+                    "status": "updated",
+                    "title": memory.title,
+                    "content": memory.content,
+                    "kind": memory.kind.discriminant_name(),
+                    "tags": memory.tags,
+                    "confidence": memory.confidence,
+                    "code_links": memory
+                        .code_links
+                        .iter()
+                        .map(|link| link.node_id.as_str())
+                        .collect::<Vec<_>>(),
+                });
+                self.backend
+                    .memory_manager
+                    .put(memory)
+                    .await
+                    .map_err(|e| format!("Failed to update memory: {:?}", e))?;
+
+                Ok(updated)
+            }
+
             "codegraph_memory_invalidate" => {
                 let id = args
                     .get("id")
@@ -2314,49 +3444,105 @@ impl McpServer {
 
             // ==================== Git Mining Tools ====================
             "codegraph_mine_git_history" => {
-                let max_commits = args
-                    .get("maxCommits")
-                    .or_else(|| args.get("max_commits"))
-                    .and_then(|v| v.as_u64())
-                    .map(|v| v as usize)
-                    .unwrap_or(500);
-                let min_confidence = args
-                    .get("minConfidence")
-                    .or_else(|| args.get("min_confidence"))
-                    .and_then(|v| v.as_f64())
-                    .map(|v| v as f32)
-                    .unwrap_or(0.7);
+                // Defaults of the editor command: features are opt-in
+                let config = MiningConfig::from_params(
+                    &args,
+                    MiningConfig {
+                        mine_features: false,
+                        ..MiningConfig::default()
+                    },
+                );
+                let include_hotspots = args
+                    .get("includeHotspots")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let include_coupling = args
+                    .get("includeCoupling")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let include_ownership = args
+                    .get("includeOwnership")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
 
+                // Mine the workspace folder given in args, or the first one
+                let folder = args
+                    .get("folder")
+                    .and_then(|v| v.as_str())
+                    .map(PathBuf::from);
                 let repo_path = self
                     .backend
-                    .workspace_folders
-                    .first()
+                    .workspace_folder_for(folder.as_deref())
                     .ok_or("No workspace folder available for git mining")?;
 
                 let miner = GitMiner::new(repo_path).map_err(|e| e.to_string())?;
-                let config = MiningConfig {
-                    max_commits,
-                    min_confidence,
-                    ..MiningConfig::default()
-                };
-
-                match miner
-                    .mine_repository(&self.backend.memory_manager, &self.backend.graph, &config)
+                let mut result = match miner
+                    .mine_repository(
+                        &self.backend.memory_manager,
+                        &self.backend.graph,
+                        &config,
+                        Some(progress),
+                    )
                     .await
                 {
-                    Ok(result) => Ok(serde_json::json!({
-                        "status": "success",
-                        "commits_processed": result.commits_processed,
-                        "memories_created": result.memories_created,
-                        "commits_skipped": result.commits_skipped,
-                        "memory_ids": result.memory_ids,
-                        "warnings": result.warnings
-                    })),
-                    Err(e) => Ok(serde_json::json!({
-                        "status": "error",
-                        "message": e.to_string()
-                    })),
+                    Ok(result) => result,
+                    Err(e) => {
+                        return Ok(serde_json::json!({
+                            "status": "error",
+                            "message": e.to_string()
+                        }))
+                    }
+                };
+
+                let mut hotspots_created = 0;
+                if include_hotspots && !cancel.is_cancelled() {
+                    let options =
+                        HotspotOptions::from_params(&args, HotspotOptions::for_memories());
+                    hotspots_created = miner
+                        .mine_hotspots(&self.backend.memory_manager, &options, &mut result)
+                        .await;
+                }
+                let mut couplings_created = 0;
+                if include_coupling && !cancel.is_cancelled() {
+                    couplings_created = miner
+                        .mine_couplings(
+                            &self.backend.memory_manager,
+                            &self.backend.graph,
+                            &mut result,
+                        )
+                        .await;
+                }
+                let mut ownership_created = 0;
+                if include_ownership && !cancel.is_cancelled() {
+                    ownership_created = miner
+                        .mine_ownership(&self.backend.memory_manager, &mut result)
+                        .await;
                 }
+
+                Ok(serde_json::json!({
+                    "status": "success",
+                    "commits_processed": result.commits_processed,
+                    "memories_created": result.memories_created
+                        + hotspots_created
+                        + couplings_created
+                        + ownership_created,
+                    "commits_skipped": result.commits_skipped,
+                    "commits_grouped": result.commits_grouped,
+                    "pull_requests_enriched": result.pull_requests_enriched,
+                    "memories_reverted": result.memories_reverted,
+                    "memories_restored": result.memories_restored,
+                    "squash_merges_split": result.squash_merges_split,
+                    "trailers_mined": result.trailers_mined,
+                    "dry_run": config.dry_run,
+                    "candidates": result.candidates,
+                    "releases_mined": result.releases_mined,
+                    "since_commit": result.since_commit,
+                    "memory_ids": result.memory_ids,
+                    "warnings": result.warnings,
+                    "hotspots_detected": hotspots_created,
+                    "couplings_detected": couplings_created,
+                    "ownership_detected": ownership_created
+                }))
             }
 
             "codegraph_mine_git_history_for_file" => {
@@ -2364,18 +3550,13 @@ impl McpServer {
                     .get("uri")
                     .and_then(|v| v.as_str())
                     .ok_or("Missing 'uri' parameter")?;
-                let max_commits = args
-                    .get("maxCommits")
-                    .or_else(|| args.get("max_commits"))
-                    .and_then(|v| v.as_u64())
-                    .map(|v| v as usize)
-                    .unwrap_or(100);
-                let min_confidence = args
-                    .get("minConfidence")
-                    .or_else(|| args.get("min_confidence"))
-                    .and_then(|v| v.as_f64())
-                    .map(|v| v as f32)
-                    .unwrap_or(0.7);
+                let config = MiningConfig::from_params(
+                    &args,
+                    MiningConfig {
+                        max_commits: 100,
+                        ..MiningConfig::default()
+                    },
+                );
 
                 let file_path = match tower_lsp::lsp_types::Url::parse(uri) {
                     Ok(url) => match url.to_file_path() {
@@ -2390,25 +3571,20 @@ impl McpServer {
                     Err(_) => std::path::PathBuf::from(uri),
                 };
 
+                // Mine the repository of the workspace folder containing the file
                 let repo_path = self
                     .backend
-                    .workspace_folders
-                    .first()
+                    .workspace_folder_for(Some(&file_path))
                     .ok_or("No workspace folder available for git mining")?;
 
                 let miner = GitMiner::new(repo_path).map_err(|e| e.to_string())?;
-                let config = MiningConfig {
-                    max_commits,
-                    min_confidence,
-                    ..MiningConfig::default()
-                };
-
                 match miner
                     .mine_file(
                         &file_path,
                         &self.backend.memory_manager,
                         &self.backend.graph,
                         &config,
+                        Some(progress),
                     )
                     .await
                 {
@@ -2418,6 +3594,14 @@ impl McpServer {
                         "commits_processed": result.commits_processed,
                         "memories_created": result.memories_created,
                         "commits_skipped": result.commits_skipped,
+                        "commits_grouped": result.commits_grouped,
+                        "pull_requests_enriched": result.pull_requests_enriched,
+                        "memories_reverted": result.memories_reverted,
+                        "memories_restored": result.memories_restored,
+                        "squash_merges_split": result.squash_merges_split,
+                        "trailers_mined": result.trailers_mined,
+                        "dry_run": config.dry_run,
+                        "candidates": result.candidates,
                         "memory_ids": result.memory_ids,
                         "warnings": result.warnings
                     })),
@@ -2597,8 +3781,97 @@ impl McpServer {
             }
 
             // ==================== Admin Tools ====================
+            "codegraph_batch_query" => {
+                let queries = args
+                    .get("queries")
+                    .and_then(|v| v.as_array())
+                    .ok_or("Missing 'queries' array")?;
+                if queries.len() > crate::handlers::MAX_BATCH_QUERIES {
+                    return Err(format!(
+                        "Too many queries: {} (max {})",
+                        queries.len(),
+                        crate::handlers::MAX_BATCH_QUERIES
+                    ));
+                }
+
+                let mut seen = std::collections::HashSet::new();
+                let mut calls = Vec::new();
+                for (i, query) in queries.iter().enumerate() {
+                    let id = query
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(String::from)
+                        .unwrap_or_else(|| i.to_string());
+                    if !seen.insert(id.clone()) {
+                        return Err(format!("Duplicate query id: {id}"));
+                    }
+                    let tool = query
+                        .get("tool")
+                        .and_then(|v| v.as_str())
+                        .ok_or_else(|| format!("Query {id}: missing 'tool'"))?;
+                    let tool = if tool.starts_with("codegraph_") {
+                        tool.to_string()
+                    } else {
+                        format!("codegraph_{tool}")
+                    };
+                    calls.push((id, tool, query.get("arguments").cloned()));
+                }
+
+                let start = std::time::Instant::now();
+                let runs = calls.into_iter().map(|(id, tool, tool_args)| async move {
+                    let query_start = std::time::Instant::now();
+                    // A batch runs without confirmation, so only tools that
+                    // change nothing may run in it
+                    let outcome = match tool.as_str() {
+                        "codegraph_batch_query" => Err(format!("{tool} cannot run inside a batch")),
+                        _ if !is_read_only(&tool) => Err(format!(
+                            "{tool} changes state and cannot run inside a batch"
+                        )),
+                        _ => {
+                            // Queries in a batch report no progress of their own
+                            let progress = McpProgress::disabled();
+                            // Boxed because execute_tool is recursive through this arm
+                            let call: std::pin::Pin<
+                                Box<dyn std::future::Future<Output = Result<Value, String>> + '_>,
+                            > = Box::pin(self.execute_tool(&tool, tool_args, &progress, cancel));
+                            call.await
+                        }
+                    };
+                    self.metrics.record(&tool, query_start.elapsed(), &outcome);
+
+                    let mut entry = serde_json::json!({
+                        "query_time_ms": query_start.elapsed().as_millis() as u64,
+                    });
+                    match outcome {
+                        Ok(value) => entry["result"] = value,
+                        Err(e) => entry["error"] = Value::String(e),
+                    }
+                    (id, entry)
+                });
+
+                let results: serde_json::Map<String, Value> =
+                    futures::future::join_all(runs).await.into_iter().collect();
+                let failed = results
+                    .values()
+                    .filter(|r| r.get("error").is_some())
+                    .count();
+
+                Ok(serde_json::json!({
+                    "succeeded": results.len() - failed,
+                    "failed": failed,
+                    "results": results,
+                    "query_time_ms": start.elapsed().as_millis() as u64,
+                }))
+            }
+
             "codegraph_reindex_workspace" => {
+                if self.shared {
+                    return Err("The graph is shared with the editor's language server: \
+                                reindex from the editor with \"CodeGraph: Reindex Workspace\""
+                        .to_string());
+                }
                 tracing::info!("Reindexing workspace...");
+                let start = std::time::Instant::now();
 
                 // Clear the graph
                 {
@@ -2607,25 +3880,37 @@ impl McpServer {
                         .map_err(|e| format!("Failed to create new graph: {}", e))?;
                 }
 
-                // Reindex the workspace
-                let indexed = self.backend.index_workspace().await;
+                // Reindex the workspace, rebuilding the query engine indexes
+                let indexed = self.backend.index_workspace(progress).await;
                 tracing::info!("Reindexed {} files", indexed);
 
-                // Rebuild AI query engine indexes
-                self.backend.query_engine.build_indexes().await;
-
                 Ok(serde_json::json!({
                     "status": "success",
                     "message": format!("Reindexed {} files", indexed),
-                    "files_indexed": indexed
+                    "files_indexed": indexed,
+                    "duration_ms": start.elapsed().as_millis() as u64,
                 }))
             }
 
+            "codegraph_server_stats" => {
+                let mut stats = self.metrics.snapshot();
+                stats["protocol_version"] = Value::String(self.protocol_version.to_string());
+                stats["indexed"] = Value::Bool(self.indexed);
+                stats["shared"] = Value::Bool(self.shared);
+                Ok(stats)
+            }
+
             // ==================== Unknown Tool ====================
             _ => Err(format!("Unknown tool: {}", name)),
         }
     }
 
+    /// Resolve a `nodeId` argument: a NodeId or a symbol moniker.
+    async fn resolve_node_id(&self, reference: &str) -> Option<codegraph::NodeId> {
+        let graph = self.backend.graph.read().await;
+        crate::domain::monikers::resolve_node_ref(&graph, reference)
+    }
+
     /// Find a node at location with broader fallback, returning whether fallback was used.
     ///
     /// Strategy:
@@ -2645,6 +3930,82 @@ impl McpServer {
         crate::domain::node_resolution::find_nearest_node(&graph, &path_str, line)
     }
 
+    /// Resolve the symbol given by `{prefix}NodeId`, or by `{prefix}Uri` and
+    /// `{prefix}Line`.
+    async fn resolve_symbol_arg(&self, args: &Value, prefix: &str) -> Option<codegraph::NodeId> {
+        let arg = |name: &str| args.get(format!("{prefix}{name}"));
+        if let Some(id_str) = arg("NodeId").and_then(|v| v.as_str()) {
+            return self.resolve_node_id(id_str).await;
+        }
+        let uri = arg("Uri").and_then(|v| v.as_str())?;
+        let line = arg("Line").and_then(|v| v.as_u64())? as u32;
+        self.find_nearest_node_with_fallback(uri, line)
+            .await
+            .map(|(id, _)| id)
+    }
+
+    /// Replace the `content` of long memory entries with a summary, noting
+    /// where it came from in `content_source`.
+    async fn summarize_memories(&self, entries: &mut [Value]) {
+        for entry in entries {
+            let Some(content) = entry.get("content").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let summary = self.sampler.summarize(content, MEMORY_SUMMARY_PROMPT).await;
+            entry["content"] = Value::String(summary.text);
+            entry["content_source"] = serde_json::json!(summary.source);
+        }
+    }
+
+    /// Append `node` and its children to `text`, one indented line each.
+    fn write_outline(text: &mut String, node: &crate::ai_query::OutlineNode, depth: usize) {
+        let symbol = &node.symbol;
+        let signature = symbol.signature.as_deref().unwrap_or(&symbol.name);
+        let indent = "  ".repeat(depth);
+        text.push_str(&format!("{}{} {}", indent, symbol.kind, signature));
+        if let Some(doc) = symbol.docstring.as_deref().and_then(|d| d.lines().next()) {
+            text.push_str(&format!(" // {}", doc.trim()));
+        }
+        text.push('\n');
+        for child in &node.children {
+            Self::write_outline(text, child, depth + 1);
+        }
+    }
+
+    /// Describe a module by its symbols grouped by kind, for clients that
+    /// cannot sample.
+    fn outline_summary(outline: &crate::ai_query::FileOutline) -> String {
+        const MAX_NAMES: usize = 10;
+
+        let mut by_kind: std::collections::BTreeMap<&str, Vec<&str>> =
+            std::collections::BTreeMap::new();
+        let mut stack: Vec<&crate::ai_query::OutlineNode> = outline.symbols.iter().rev().collect();
+        while let Some(node) = stack.pop() {
+            by_kind
+                .entry(node.symbol.kind.as_str())
+                .or_default()
+                .push(node.symbol.name.as_str());
+            stack.extend(node.children.iter().rev());
+        }
+
+        let groups: Vec<String> = by_kind
+            .iter()
+            .map(|(kind, names)| {
+                let mut listed = names[..names.len().min(MAX_NAMES)].join(", ");
+                if names.len() > MAX_NAMES {
+                    listed.push_str(&format!(", and {} more", names.len() - MAX_NAMES));
+                }
+                format!("{} {} ({})", names.len(), kind, listed)
+            })
+            .collect();
+        format!(
+            "{} defines {} symbols: {}.",
+            outline.file,
+            outline.total_symbols,
+            groups.join("; ")
+        )
+    }
+
     /// Search git history using semantic (memory embeddings) + keyword (git log --grep) matching.
     async fn search_git_history(
         &self,
@@ -2954,6 +4315,22 @@ impl McpServer {
         }
     }
 
+    /// Parse the `groupBy` aggregation option ("file" or "directory")
+    fn parse_group_by(args: &Value) -> Result<Option<crate::ai_query::GroupBy>, String> {
+        match args
+            .get("groupBy")
+            .or_else(|| args.get("group_by"))
+            .and_then(|v| v.as_str())
+        {
+            None => Ok(None),
+            Some("file") => Ok(Some(crate::ai_query::GroupBy::File)),
+            Some("directory") => Ok(Some(crate::ai_query::GroupBy::Directory)),
+            Some(other) => Err(format!(
+                "Invalid groupBy: {other}. Expected 'file' or 'directory'"
+            )),
+        }
+    }
+
     /// Parse `kinds` filter from MCP args into MemoryKindFilter vec
     fn parse_kinds_filter(args: &serde_json::Value) -> Vec<crate::memory::MemoryKindFilter> {
         args.get("kinds")
@@ -3022,8 +4399,144 @@ impl McpServer {
     }
 }
 
-/// Parse a string into a NodeId
-fn parse_node_id(s: &str) -> Option<codegraph::NodeId> {
-    // NodeId is u64 in codegraph
-    s.parse::<codegraph::NodeId>().ok()
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use std::time::Duration;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    fn test_server(workspace: &Path) -> McpServer {
+        McpServer::new(
+            vec![workspace.to_path_buf()],
+            Vec::new(),
+            100,
+            codegraph_memory::CodeGraphEmbeddingModel::default(),
+            false,
+        )
+    }
+
+    async fn has_symbol(server: &McpServer, name: &str) -> bool {
+        let graph = server.backend.graph.read().await;
+        graph
+            .query()
+            .property("name", name.to_string())
+            .execute()
+            .is_ok_and(|nodes| !nodes.is_empty())
+    }
+
+    #[tokio::test]
+    async fn test_watcher_reindexes_edited_file() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().canonicalize().unwrap();
+        let file = root.join("app.py");
+        std::fs::write(&file, "def first():\n    pass\n").unwrap();
+
+        let mut server = test_server(&root);
+        let progress = McpProgress::disabled();
+        assert_eq!(server.backend.index_directory(&root, &progress).await, 1);
+        server.start_watcher();
+        assert!(has_symbol(&server, "first").await);
+
+        // Edited outside the session, e.g. by the agent's own file tools
+        std::fs::write(&file, "def second():\n    pass\n").unwrap();
+        let change = tokio::time::timeout(Duration::from_secs(10), server.changes_rx.recv())
+            .await
+            .expect("no change reported for the edited file");
+        assert!(change.is_some());
+
+        assert!(has_symbol(&server, "second").await);
+        assert!(!has_symbol(&server, "first").await);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_indexing_is_discarded() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().canonicalize().unwrap();
+        std::fs::write(root.join("app.py"), "def first():\n    pass\n").unwrap();
+        let mut server = test_server(&root);
+        server
+            .backend
+            .graph
+            .write()
+            .await
+            .add_node(
+                codegraph::NodeType::Function,
+                codegraph::PropertyMap::new().with("name", "partial"),
+            )
+            .unwrap();
+
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+        let progress = McpProgress::disabled().with_cancellation(cancel);
+        server.ensure_indexed(&progress).await;
+
+        // Neither kept nor persisted; the next call indexes again
+        assert!(!server.indexed);
+        assert!(server.watcher.is_none());
+        assert!(!has_symbol(&server, "partial").await);
+    }
+
+    #[tokio::test]
+    async fn test_complete_indexes_workspace() {
+        let workspace = tempfile::tempdir().unwrap();
+        let root = workspace.path().canonicalize().unwrap();
+        std::fs::write(root.join("app.py"), "def first():\n    pass\n").unwrap();
+        let mut server = test_server(&root);
+
+        // Completion before any tool call
+        let params = serde_json::json!({
+            "ref": {"type": "ref/tool", "name": "codegraph_symbol_search"},
+            "argument": {"name": "query", "value": "fir"},
+        });
+        let response = server
+            .handle_complete(Some(Value::from(1)), Some(params))
+            .await;
+
+        assert!(response.error.is_none());
+        assert!(server.indexed);
+        assert!(has_symbol(&server, "first").await);
+    }
+
+    #[tokio::test]
+    async fn test_declined_confirmation_refuses_tool() {
+        let workspace = tempfile::tempdir().unwrap();
+        let server = test_server(workspace.path());
+
+        // A client that can ask the user, who declines
+        let (client, server_io) = tokio::io::duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server_io);
+        server
+            .client_requests
+            .attach(AsyncTransport::new(server_read, server_write).writer());
+        server.confirmer.set_supported(true);
+        let requests = Arc::clone(&server.client_requests);
+        let user = tokio::spawn(async move {
+            let mut lines = BufReader::new(client).lines();
+            let line = lines.next_line().await.unwrap().unwrap();
+            let request: Value = serde_json::from_str(&line).unwrap();
+            assert_eq!(request["method"], "elicitation/create");
+            requests.resolve(JsonRpcClientResponse {
+                id: request["id"].clone(),
+                result: Some(serde_json::json!({"action": "decline"})),
+                error: None,
+            })
+        });
+
+        let args = serde_json::json!({"id": "memory-1", "reason": "superseded"});
+        let result = server
+            .execute_tool(
+                "codegraph_memory_invalidate",
+                Some(args),
+                &McpProgress::disabled(),
+                &CancellationToken::new(),
+            )
+            .await;
+
+        assert!(user.await.unwrap());
+        assert_eq!(
+            result.unwrap_err(),
+            "Not confirmed: the user declined the operation"
+        );
+    }
 }
@@ -0,0 +1,170 @@
+//! Which tools a server exposes (`--tools`, `--tools-file`).
+//!
+//! Deployments can narrow the tool set, e.g. to read-only analysis without
+//! the memory write tools. Filtered tools are left out of `tools/list` and
+//! refused if called anyway. Patterns name a tool with or without its
+//! `codegraph_` prefix, may end in `*` to match a family of tools
+//! (`memory_*`), and `@read` / `@write` match the read-only and the
+//! state-changing tools. A denied pattern wins over an allowed one. A pattern
+//! that matches no tool is rejected, so a typo cannot leave a tool exposed.
+//!
+//! The file form is JSON:
+//!
+//! ```json
+//! { "allow": ["@read"], "deny": ["cross_project_search"] }
+//! ```
+
+use super::tools::{get_all_tools, is_read_only};
+use serde::Deserialize;
+use std::path::Path;
+
+const TOOL_PREFIX: &str = "codegraph_";
+
+/// Tools allowed and denied on a server; allows every tool by default
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ToolFilter {
+    /// Only tools matching one of these run, if set
+    #[serde(default)]
+    allow: Option<Vec<String>>,
+    /// Tools matching one of these never run
+    #[serde(default)]
+    deny: Vec<String>,
+}
+
+impl ToolFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Read a filter from a JSON file with `allow` and `deny` lists
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let filter: Self = serde_json::from_str(&text)
+            .map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        let patterns = filter.allow.iter().flatten().chain(filter.deny.iter());
+        for pattern in patterns {
+            check_pattern(pattern).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        }
+        Ok(filter)
+    }
+
+    /// Add the patterns of an `allow=a,b` or `deny=c` spec
+    pub fn add_spec(&mut self, spec: &str) -> Result<(), String> {
+        let (list, patterns) = spec.split_once('=').ok_or_else(|| {
+            format!(
+                "Invalid tool filter '{}': expected allow=... or deny=...",
+                spec
+            )
+        })?;
+        let patterns = patterns
+            .split(',')
+            .map(str::trim)
+            .filter(|pattern| !pattern.is_empty())
+            .map(|pattern| check_pattern(pattern).map(|()| pattern.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("Invalid tool filter '{}': {}", spec, e))?;
+        match list.trim() {
+            "allow" => self.allow.get_or_insert_with(Vec::new).extend(patterns),
+            "deny" => self.deny.extend(patterns),
+            other => {
+                return Err(format!(
+                    "Invalid tool filter '{}': unknown list '{}', expected allow or deny",
+                    spec, other
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `tool` may be listed and called
+    pub fn allows(&self, tool: &str) -> bool {
+        if self.deny.iter().any(|pattern| matches(pattern, tool)) {
+            return false;
+        }
+        match &self.allow {
+            Some(allow) => allow.iter().any(|pattern| matches(pattern, tool)),
+            None => true,
+        }
+    }
+
+    /// Whether every tool is allowed
+    pub fn is_unrestricted(&self) -> bool {
+        self.allow.is_none() && self.deny.is_empty()
+    }
+}
+
+/// Fail if `pattern` matches none of the server's tools
+fn check_pattern(pattern: &str) -> Result<(), String> {
+    if get_all_tools()
+        .iter()
+        .any(|tool| matches(pattern, &tool.name))
+    {
+        Ok(())
+    } else {
+        Err(format!("'{}' matches no tool", pattern))
+    }
+}
+
+fn matches(pattern: &str, tool: &str) -> bool {
+    match pattern {
+        "@read" => return is_read_only(tool),
+        "@write" => return !is_read_only(tool),
+        _ => {}
+    }
+    let pattern = pattern.strip_prefix(TOOL_PREFIX).unwrap_or(pattern);
+    let tool = tool.strip_prefix(TOOL_PREFIX).unwrap_or(tool);
+    match pattern.strip_suffix('*') {
+        Some(prefix) => tool.starts_with(prefix),
+        None => tool == pattern,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allow_and_deny() {
+        assert!(ToolFilter::new().allows("codegraph_memory_store"));
+
+        let mut filter = ToolFilter::new();
+        filter.add_spec("allow=@read").unwrap();
+        filter
+            .add_spec("deny=codegraph_cross_project_search")
+            .unwrap();
+        assert!(filter.allows("codegraph_symbol_search"));
+        assert!(filter.allows("codegraph_memory_get"));
+        assert!(!filter.allows("codegraph_memory_store"));
+        assert!(!filter.allows("codegraph_reindex_workspace"));
+        assert!(!filter.allows("codegraph_cross_project_search"));
+
+        let mut filter = ToolFilter::new();
+        filter.add_spec("deny=memory_*, mine_git_history").unwrap();
+        assert!(!filter.allows("codegraph_memory_search"));
+        assert!(!filter.allows("codegraph_mine_git_history"));
+        assert!(filter.allows("codegraph_mine_git_history_for_file"));
+        assert!(filter.allows("codegraph_get_callers"));
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        let mut filter = ToolFilter::new();
+        assert!(filter.add_spec("memory_store").is_err());
+        assert!(filter.add_spec("block=memory_store").is_err());
+        assert!(filter.add_spec("deny=memory_stroe").is_err());
+        assert!(filter.add_spec("deny=memroy_*").is_err());
+        assert!(filter.add_spec("allow=@read,symbol_serach").is_err());
+        assert!(filter.is_unrestricted());
+    }
+
+    #[test]
+    fn test_from_json() {
+        let filter: ToolFilter =
+            serde_json::from_str(r#"{"allow":["@read"],"deny":["get_hot_symbols"]}"#).unwrap();
+        assert!(filter.allows("codegraph_get_callers"));
+        assert!(!filter.allows("codegraph_get_hot_symbols"));
+        assert!(!filter.allows("codegraph_memory_update"));
+    }
+}
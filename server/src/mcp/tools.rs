@@ -1,14 +1,16 @@
 //! MCP Tool Definitions
 //!
-//! Defines all 35 CodeGraph tools for the MCP protocol.
+//! Defines all 45 CodeGraph tools for the MCP protocol.
 
-use super::protocol::{PropertySchema, Tool, ToolInputSchema};
-use std::collections::HashMap;
+use super::protocol::{PropertySchema, Tool, ToolAnnotations, ToolInputSchema};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
 
 /// Get all available CodeGraph tools
 pub fn get_all_tools() -> Vec<Tool> {
-    vec![
-        // Analysis Tools (11)
+    let tools = vec![
+        // Analysis Tools (15)
         get_dependency_graph_tool(),
         get_call_graph_tool(),
         analyze_impact_tool(),
@@ -20,12 +22,19 @@ pub fn get_all_tools() -> Vec<Tool> {
         analyze_complexity_tool(),
         find_unused_code_tool(),
         analyze_coupling_tool(),
-        // Search Tools (5)
+        find_cycles_tool(),
+        reaches_tool(),
+        find_path_tool(),
+        summarize_module_tool(),
+        // Search Tools (8)
         symbol_search_tool(),
         find_by_imports_tool(),
         find_entry_points_tool(),
         traverse_graph_tool(),
         find_by_signature_tool(),
+        get_hot_symbols_tool(),
+        find_type_usages_tool(),
+        find_implementations_tool(),
         // Navigation Tools (3)
         get_callers_tool(),
         get_callees_tool(),
@@ -35,6 +44,7 @@ pub fn get_all_tools() -> Vec<Tool> {
         memory_search_tool(),
         memory_get_tool(),
         memory_context_tool(),
+        memory_update_tool(),
         memory_invalidate_tool(),
         memory_list_tool(),
         memory_stats_tool(),
@@ -48,9 +58,110 @@ pub fn get_all_tools() -> Vec<Tool> {
         find_similar_tool(),
         cluster_symbols_tool(),
         compare_symbols_tool(),
-        // Admin Tools (1)
+        // Batch Tools (1)
+        batch_query_tool(),
+        // Admin Tools (2)
         reindex_workspace_tool(),
-    ]
+        server_stats_tool(),
+    ];
+    tools.into_iter().map(with_cursor).collect()
+}
+
+/// Add the `cursor` argument to read-only tools, whose large results are
+/// paged. Tools that write are never paged, as repeating the call to resume
+/// would repeat the write.
+fn with_cursor(mut tool: Tool) -> Tool {
+    if tool.annotations.as_ref().is_some_and(|a| a.read_only_hint) {
+        tool.input_schema
+            .properties
+            .get_or_insert_with(HashMap::new)
+            .insert(
+                "cursor".to_string(),
+                string_prop(
+                    "next_cursor of a previous call with the same arguments, to get the next page of a large result",
+                ),
+            );
+    }
+    tool
+}
+
+/// Whether the tool declares an output schema, and so returns its result as
+/// `structuredContent` too
+pub fn has_output_schema(name: &str) -> bool {
+    static STRUCTURED: OnceLock<HashSet<String>> = OnceLock::new();
+    STRUCTURED
+        .get_or_init(|| {
+            get_all_tools()
+                .into_iter()
+                .filter(|tool| tool.output_schema.is_some())
+                .map(|tool| tool.name)
+                .collect()
+        })
+        .contains(name)
+}
+
+/// Whether the tool only reads the graph and memories, as its annotations
+/// declare. Only such tools may run inside a batch.
+pub fn is_read_only(name: &str) -> bool {
+    static READ_ONLY: OnceLock<HashSet<String>> = OnceLock::new();
+    READ_ONLY
+        .get_or_init(|| {
+            get_all_tools()
+                .into_iter()
+                .filter(|tool| tool.annotations.as_ref().is_some_and(|a| a.read_only_hint))
+                .map(|tool| tool.name)
+                .collect()
+        })
+        .contains(name)
+}
+
+// Annotations of a tool that only reads the graph and memories
+fn read_only() -> Option<ToolAnnotations> {
+    Some(ToolAnnotations {
+        read_only_hint: true,
+        destructive_hint: false,
+        idempotent_hint: true,
+        open_world_hint: false,
+    })
+}
+
+// Annotations of a tool that stores memories or rebuilds the graph:
+// `destructive` when it overwrites or drops existing data
+fn writes(destructive: bool, idempotent: bool) -> Option<ToolAnnotations> {
+    Some(ToolAnnotations {
+        read_only_hint: false,
+        destructive_hint: destructive,
+        idempotent_hint: idempotent,
+        open_world_hint: false,
+    })
+}
+
+// Output schema of a symbol, as in search results and call graphs
+fn symbol_output_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "name": { "type": "string" },
+            "kind": { "type": "string" },
+            "location": {
+                "type": "object",
+                "properties": {
+                    "file": { "type": "string" },
+                    "line": { "type": "integer" },
+                    "column": { "type": "integer" },
+                    "end_line": { "type": "integer" },
+                    "end_column": { "type": "integer" }
+                },
+                "required": ["file", "line"]
+            },
+            "signature": { "type": ["string", "null"] },
+            "docstring": { "type": ["string", "null"] },
+            "is_public": { "type": "boolean" },
+            "visibility": { "type": "string" },
+            "moniker": { "type": ["string", "null"] }
+        },
+        "required": ["name", "kind", "location"]
+    })
 }
 
 // Helper to create property schema
@@ -162,6 +273,8 @@ fn get_dependency_graph_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -200,6 +313,8 @@ fn get_call_graph_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string(), "line".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -231,12 +346,14 @@ fn analyze_impact_tool() -> Tool {
 
     Tool {
         name: "codegraph_analyze_impact".to_string(),
-        description: Some("Predicts blast radius of code changes before making them. USE WHEN: planning refactoring, renaming symbols, deleting code, or assessing risk. Returns: list of affected symbols (direct and transitive), risk assessment, and change type analysis. changeType affects the analysis: 'modify' shows callers/dependents, 'delete' shows all references that would break, 'rename' shows all sites needing updates. Requires uri and line parameters.".to_string()),
+        description: Some("Predicts blast radius of code changes before making them. USE WHEN: planning refactoring, renaming symbols, deleting code, or assessing risk. Returns: list of affected symbols (direct and transitive), risk assessment, change type analysis, and affected_tests/test_files (tests that transitively call the symbol) for change-based test selection. changeType affects the analysis: 'modify' shows callers/dependents, 'delete' shows all references that would break, 'rename' shows all sites needing updates. Requires uri and line parameters.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["uri".to_string(), "line".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -271,6 +388,8 @@ fn get_ai_context_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string(), "line".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -300,6 +419,8 @@ fn get_edit_context_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string(), "line".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -338,6 +459,8 @@ fn get_curated_context_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["query".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -364,6 +487,8 @@ fn find_related_tests_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -393,6 +518,8 @@ fn get_symbol_info_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string(), "line".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -420,12 +547,14 @@ fn analyze_complexity_tool() -> Tool {
 
     Tool {
         name: "codegraph_analyze_complexity".to_string(),
-        description: Some("Measures code complexity metrics for refactoring decisions. USE WHEN: identifying functions that need simplification, reviewing code quality, or prioritizing technical debt. Returns cyclomatic complexity score per function, with name, line range, and file path. Scores >10 typically indicate refactoring candidates, >20 is high complexity. Use threshold to filter — only functions at or above the threshold are returned. Omit line to analyze all functions in a file. Returns: {functions:[{name, complexity, grade, node_id, line_start, line_end, details:{complexity_branches, complexity_loops, complexity_logical_ops, complexity_nesting, complexity_exceptions, complexity_early_returns, lines_of_code}}], summary:{total_functions, average_complexity, max_complexity, above_threshold, threshold, overall_grade}, recommendations:[]} Requires uri parameter. Optionally line for a specific function.".to_string()),
+        description: Some("Measures code complexity metrics for refactoring decisions. USE WHEN: identifying functions that need simplification, reviewing code quality, or prioritizing technical debt. Returns cyclomatic complexity score per function (computed at index time) plus cognitive complexity, with name, line range, and file path. Scores >10 typically indicate refactoring candidates, >20 is high complexity. Use threshold to filter — only functions at or above the threshold are returned. Omit line to analyze all functions in a file. Returns: {functions:[{name, complexity, grade, node_id, line_start, line_end, details:{complexity_cognitive, complexity_branches, complexity_loops, complexity_logical_ops, complexity_nesting, complexity_exceptions, complexity_early_returns, lines_of_code}}], summary:{total_functions, average_complexity, max_complexity, above_threshold, threshold, overall_grade}, recommendations:[]} Requires uri parameter. Optionally line for a specific function.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -461,12 +590,14 @@ fn find_unused_code_tool() -> Tool {
 
     Tool {
         name: "codegraph_find_unused_code".to_string(),
-        description: Some("Detects dead code that can be safely removed. USE WHEN: cleaning up codebase, reducing bundle size, or finding abandoned features. Returns unused_items array (name, type, confidence 0-1, is_public) plus summary (total_checked, unused_count). confidence filters results — higher values mean more certain the code is unused. LIMITATIONS: May flag entry points, event handlers, or dynamically-called code.".to_string()),
+        description: Some("Detects dead code that can be safely removed. USE WHEN: cleaning up codebase, reducing bundle size, or finding abandoned features. Liveness is reachability from entry points (main, HTTP handlers, tests, exported APIs) over the call graph, so indirectly used helpers are not flagged but call chains nothing live reaches are (has_callers=true). Returns unused_items array (name, type, confidence 0-1, is_public, has_callers) plus summary (total_checked, unused_count, entry_points). confidence filters results — higher values mean more certain the code is unused. LIMITATIONS: May flag entry points, event handlers, or dynamically-called code.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -497,11 +628,207 @@ fn analyze_coupling_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn find_cycles_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "level".to_string(),
+        enum_prop(
+            "Granularity: 'file' collapses symbols into their files (module cycles), 'symbol' reports cycles between individual functions",
+            vec!["file", "symbol"],
+            Some("file"),
+        ),
+    );
+    properties.insert(
+        "edgeTypes".to_string(),
+        array_prop(
+            "Edge types to follow (default: ['imports', 'importsfrom', 'calls'])",
+            "string",
+        ),
+    );
+    properties.insert(
+        "minSize".to_string(),
+        number_prop(
+            "Minimum number of members for a cycle to be reported",
+            Some(2.0),
+        ),
+    );
+    properties.insert(
+        "limit".to_string(),
+        number_prop("Maximum number of cycles to return", Some(50.0)),
+    );
+
+    Tool {
+        name: "codegraph_find_cycles".to_string(),
+        description: Some("Detects circular dependencies using strongly connected components over import and call edges. USE WHEN: untangling circular imports, diagnosing initialization-order bugs, or reviewing module boundaries in large projects. Returns cycles sorted largest first, each with size, members, the files involved, and the edge types that close the loop. Use level='symbol' to see the exact functions forming a cycle.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn reaches_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "uri".to_string(),
+        string_prop("The file URI containing the starting function"),
+    );
+    properties.insert(
+        "line".to_string(),
+        number_prop("Line number of the starting function (1-indexed)", None),
+    );
+    properties.insert(
+        "nodeId".to_string(),
+        string_prop("Node ID of the starting function (alternative to uri+line)"),
+    );
+    properties.insert(
+        "categories".to_string(),
+        array_prop(
+            "Built-in sink sets: 'filesystem', 'network', 'process', 'database'",
+            "string",
+        ),
+    );
+    properties.insert(
+        "sinks".to_string(),
+        array_prop(
+            "Custom sink symbol name globs, e.g. ['db.*', 'sendEmail']",
+            "string",
+        ),
+    );
+    properties.insert(
+        "edgeTypes".to_string(),
+        array_prop("Edge types to follow (default: ['calls'])", "string"),
+    );
+    properties.insert(
+        "maxDepth".to_string(),
+        number_prop("Maximum call depth to explore", Some(10.0)),
+    );
+    properties.insert(
+        "maxMatches".to_string(),
+        number_prop("Stop after this many sink matches", Some(20.0)),
+    );
+
+    Tool {
+        name: "codegraph_reaches".to_string(),
+        description: Some("Answers 'does this function transitively reach X?' — e.g. does this HTTP handler end up touching the filesystem, network, or database. USE WHEN: security review, checking side effects before reusing a function, or verifying a pure/IO-free code path. Matches sinks against both graph symbols and external library calls (fs.readFile, requests.get, ...). Returns each sink hit with the call path from the start function. Provide categories and/or sinks, plus uri+line or nodeId.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn summarize_module_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert("uri".to_string(), string_prop("The file URI to summarize"));
+
+    Tool {
+        name: "codegraph_summarize_module".to_string(),
+        description: Some("Summarizes what a file/module does. USE WHEN: getting oriented in an unfamiliar file before reading it. When the client supports sampling, the summary is written by the client's model from the file's symbol outline; otherwise it lists the file's symbols grouped by kind. Returns: {file, summary, summary_source (sampled|outline), total_symbols}. Requires uri parameter.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: Some(vec!["uri".to_string()]),
+        },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn find_path_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "fromUri".to_string(),
+        string_prop("The file URI containing the start symbol"),
+    );
+    properties.insert(
+        "fromLine".to_string(),
+        number_prop("Line number of the start symbol", None),
+    );
+    properties.insert(
+        "fromNodeId".to_string(),
+        string_prop("Node ID of the start symbol (alternative to fromUri+fromLine)"),
+    );
+    properties.insert(
+        "toUri".to_string(),
+        string_prop("The file URI containing the target symbol"),
+    );
+    properties.insert(
+        "toLine".to_string(),
+        number_prop("Line number of the target symbol", None),
+    );
+    properties.insert(
+        "toNodeId".to_string(),
+        string_prop("Node ID of the target symbol (alternative to toUri+toLine)"),
+    );
+    properties.insert(
+        "edgeTypes".to_string(),
+        array_prop(
+            "Edge types to follow, e.g. ['calls', 'references'] (default: all)",
+            "string",
+        ),
+    );
+    properties.insert(
+        "maxDepth".to_string(),
+        number_prop("Maximum path length in edges", Some(10.0)),
+    );
+
+    Tool {
+        name: "codegraph_find_path".to_string(),
+        description: Some("Finds the shortest path from one symbol to another along graph edges. USE WHEN: answering 'how does X reach Y?' — e.g. how a request handler ends up calling a given function, or why a module depends on another. Returns each symbol on the path with the edge type leading to it, or found=false if Y is not reachable within maxDepth. Identify the symbols via fromNodeId/toNodeId OR fromUri+fromLine / toUri+toLine.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
 // === Search Tools ===
 
+fn symbol_search_output_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "results": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "node_id": { "description": "Node ID, accepted as nodeId by other tools" },
+                        "symbol": symbol_output_schema(),
+                        "score": { "type": "number" },
+                        "match_reason": { "type": "string" }
+                    },
+                    "required": ["node_id", "symbol", "score"]
+                }
+            },
+            "groups": {
+                "type": "array",
+                "description": "Matches grouped by file or directory, when groupBy is set",
+                "items": { "type": "object" }
+            },
+            "total_matches": { "type": "integer" },
+            "query_time_ms": { "type": "integer" },
+            "truncated": { "type": "boolean" }
+        },
+        "required": ["total_matches", "query_time_ms"]
+    })
+}
+
 fn symbol_search_tool() -> Tool {
     let mut properties = HashMap::new();
     properties.insert(
@@ -541,14 +868,33 @@ fn symbol_search_tool() -> Tool {
         ),
     );
 
+    properties.insert(
+        "mode".to_string(),
+        enum_prop(
+            "Query mode: 'text' for keyword/natural language search, 'regex' to match a regular expression against symbol names and signatures (e.g., '^use.*Controller$')",
+            vec!["text", "regex"],
+            Some("text"),
+        ),
+    );
+    properties.insert(
+        "groupBy".to_string(),
+        enum_prop(
+            "Aggregate matches by file or directory with counts instead of listing symbols. limit then caps the number of groups",
+            vec!["file", "directory"],
+            None,
+        ),
+    );
+
     Tool {
         name: "codegraph_symbol_search".to_string(),
-        description: Some("Searches codebase for symbols by name or pattern. USE WHEN: finding function/class implementations, exploring unfamiliar code, or locating specific functionality. THIS IS YOUR STARTING POINT when you don't know where code is located. Supports both exact name matching and natural language queries (e.g., 'function that validates email addresses'). Returns array of matches, each with: name, kind (function/class/method/variable/interface/type/module), file path, line range, signature, and docstring. Use compact=true for minimal output (name, kind, location only). symbolType filters by kind — use 'any' to search all types.".to_string()),
+        description: Some("Searches codebase for symbols by name or pattern. USE WHEN: finding function/class implementations, exploring unfamiliar code, or locating specific functionality. THIS IS YOUR STARTING POINT when you don't know where code is located. Supports both exact name matching and natural language queries (e.g., 'function that validates email addresses'). Returns array of matches, each with: name, kind (function/class/method/variable/interface/type/module), file path, line range, signature, and docstring. Use compact=true for minimal output (name, kind, location only). symbolType filters by kind — use 'any' to search all types. Use mode='regex' for patterns BM25 can't express, like '^handle.*Event$'. Use groupBy='directory' for a compact module-level answer to broad questions like 'where is auth handled'.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["query".to_string()]),
         },
+        output_schema: Some(symbol_search_output_schema()),
+        annotations: read_only(),
     }
 }
 
@@ -581,6 +927,8 @@ fn find_by_imports_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["moduleName".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -626,6 +974,8 @@ fn find_entry_points_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -677,6 +1027,14 @@ fn traverse_graph_tool() -> Tool {
         "summary".to_string(),
         boolean_prop("Return a condensed summary for large graphs", false),
     );
+    properties.insert(
+        "groupBy".to_string(),
+        enum_prop(
+            "Aggregate reached nodes by file or directory with counts",
+            vec!["file", "directory"],
+            None,
+        ),
+    );
 
     Tool {
         name: "codegraph_traverse_graph".to_string(),
@@ -686,6 +1044,8 @@ fn traverse_graph_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -731,11 +1091,145 @@ fn find_by_signature_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn get_hot_symbols_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "metric".to_string(),
+        enum_prop(
+            "Ranking metric: 'pagerank' favors widely depended-on functions, 'betweenness' favors chokepoints on call paths, 'combined' averages both",
+            vec!["pagerank", "betweenness", "combined"],
+            Some("combined"),
+        ),
+    );
+    properties.insert(
+        "limit".to_string(),
+        number_prop("Maximum number of symbols to return", Some(20.0)),
+    );
+    properties.insert(
+        "uri".to_string(),
+        string_prop("Optional file URI or path fragment to limit results to matching files"),
+    );
+
+    Tool {
+        name: "codegraph_get_hot_symbols".to_string(),
+        description: Some("Ranks functions by structural importance in the call graph using PageRank and betweenness centrality. USE WHEN: starting to explore an unfamiliar codebase, deciding where to begin reading, or identifying chokepoints whose changes ripple widely. Returns symbols with normalized scores (0.0-1.0) plus caller and callee counts. Scores are precomputed at index time, so this is fast on any codebase size.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn find_type_usages_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "uri".to_string(),
+        string_prop("The file URI containing the type/class definition"),
+    );
+    properties.insert(
+        "line".to_string(),
+        number_prop("Line number of the type/class definition (1-indexed)", None),
+    );
+    properties.insert(
+        "nodeId".to_string(),
+        string_prop("Node ID of the type/class (alternative to uri+line)"),
+    );
+    properties.insert(
+        "limit".to_string(),
+        number_prop("Maximum number of usages to return", Some(100.0)),
+    );
+
+    Tool {
+        name: "codegraph_find_type_usages".to_string(),
+        description: Some("Finds every function, class, field, or variable that references a given type or class through typed graph edges (references, uses, instantiates), not text search. USE WHEN: changing a type's shape, renaming a class, or assessing how widely a data model is used. Field usages also report the containing class with 'via' naming the field. Provide uri+line of the type definition or its nodeId.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn find_implementations_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "uri".to_string(),
+        string_prop("The file URI containing the trait/interface/class definition"),
+    );
+    properties.insert(
+        "line".to_string(),
+        number_prop("Line number of the type definition (1-indexed)", None),
+    );
+    properties.insert(
+        "nodeId".to_string(),
+        string_prop("Node ID of the type (alternative to uri+line)"),
+    );
+    properties.insert(
+        "direction".to_string(),
+        enum_prop(
+            "'implementations' returns types that implement/extend the target; 'supertypes' returns the interfaces and base classes the target implements/extends",
+            vec!["implementations", "supertypes"],
+            Some("implementations"),
+        ),
+    );
+    properties.insert(
+        "limit".to_string(),
+        number_prop("Maximum number of results to return", Some(100.0)),
+    );
+
+    Tool {
+        name: "codegraph_find_implementations".to_string(),
+        description: Some("Finds all implementations of a trait or interface (and subclasses of a base class) via Implements/Extends edges, or with direction='supertypes' the interfaces and base classes a type implements. USE WHEN: changing a trait/interface method signature, finding concrete types behind an abstraction, or checking what contracts a class fulfils. Returns direct relationships only, each with relation (Implements/Extends). Provide uri+line of the type definition or its nodeId.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
 // === Navigation Tools ===
 
+/// Output schema of get_callers and get_callees, listing calls under `key`
+fn call_graph_output_schema(key: &str) -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            key: {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "node_id": { "description": "Node ID, accepted as nodeId by other tools" },
+                        "symbol": symbol_output_schema(),
+                        "call_site": { "type": "object" },
+                        "depth": { "type": "integer", "description": "1 for direct calls" }
+                    },
+                    "required": ["node_id", "symbol", "depth"]
+                }
+            },
+            "symbol_name": { "type": "string" },
+            "diagnostic": { "type": "object" },
+            "used_fallback": { "type": "boolean" },
+            "fallback_message": { "type": "string" },
+            "message": { "type": "string" }
+        },
+        "required": [key]
+    })
+}
+
 fn get_callers_tool() -> Tool {
     let mut properties = HashMap::new();
     properties.insert(
@@ -766,6 +1260,8 @@ fn get_callers_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: Some(call_graph_output_schema("callers")),
+        annotations: read_only(),
     }
 }
 
@@ -799,6 +1295,8 @@ fn get_callees_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: Some(call_graph_output_schema("callees")),
+        annotations: read_only(),
     }
 }
 
@@ -840,6 +1338,8 @@ fn get_detailed_symbol_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -914,9 +1414,41 @@ fn memory_store_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["kind".to_string(), "title".to_string(), "content".to_string()]),
         },
+        output_schema: None,
+        annotations: writes(false, false),
     }
 }
 
+fn memory_search_output_schema() -> Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "results": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "string" },
+                        "title": { "type": "string" },
+                        "content": { "type": "string" },
+                        "content_source": {
+                            "type": "string",
+                            "enum": ["original", "sampled", "truncated"]
+                        },
+                        "kind": { "type": "string" },
+                        "score": { "type": "number" },
+                        "created_at": { "type": "string" },
+                        "tags": { "type": "array", "items": { "type": "string" } }
+                    },
+                    "required": ["id", "title", "content", "kind", "score"]
+                }
+            },
+            "total": { "type": "integer" }
+        },
+        "required": ["results", "total"]
+    })
+}
+
 fn memory_search_tool() -> Tool {
     let mut properties = HashMap::new();
     properties.insert(
@@ -940,30 +1472,48 @@ fn memory_search_tool() -> Tool {
         "codeContext".to_string(),
         array_prop("Code node IDs for proximity boosting", "string"),
     );
+    properties.insert(
+        "summarize".to_string(),
+        boolean_prop(
+            "Summarize long content with the client's model (sampling), or truncate it if sampling is unavailable",
+            false,
+        ),
+    );
 
     Tool {
         name: "codegraph_memory_search".to_string(),
-        description: Some("Searches memories with hybrid BM25 + semantic + graph proximity. USE WHEN: recalling past knowledge — previous debugging sessions, architectural decisions, known issues. ALWAYS SEARCH before starting complex tasks. Returns results array (id, title, content, kind, score, tags, created_at) sorted by relevance. Filter with kinds (debug_context, architectural_decision, known_issue, convention, project_context), tags, or codeContext (node IDs for proximity boosting). Set currentOnly=false to include invalidated memories.".to_string()),
+        description: Some("Searches memories with hybrid BM25 + semantic + graph proximity. USE WHEN: recalling past knowledge — previous debugging sessions, architectural decisions, known issues. ALWAYS SEARCH before starting complex tasks. Returns results array (id, title, content, kind, score, tags, created_at) sorted by relevance. Filter with kinds (debug_context, architectural_decision, known_issue, convention, project_context), tags, or codeContext (node IDs for proximity boosting). Set currentOnly=false to include invalidated memories. Set summarize=true to shorten long content (content_source tells whether it was sampled, truncated or is the original).".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["query".to_string()]),
         },
+        output_schema: Some(memory_search_output_schema()),
+        annotations: read_only(),
     }
 }
 
 fn memory_get_tool() -> Tool {
     let mut properties = HashMap::new();
     properties.insert("id".to_string(), string_prop("Memory ID to retrieve"));
+    properties.insert(
+        "summarize".to_string(),
+        boolean_prop(
+            "Summarize long content with the client's model (sampling), or truncate it if sampling is unavailable",
+            false,
+        ),
+    );
 
     Tool {
         name: "codegraph_memory_get".to_string(),
-        description: Some("Retrieves full memory details by ID. USE WHEN: you have a memory ID from search results and need complete content.".to_string()),
+        description: Some("Retrieves full memory details by ID. USE WHEN: you have a memory ID from search results and need complete content. Set summarize=true to get a summary of long content instead.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["id".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -989,15 +1539,68 @@ fn memory_context_tool() -> Tool {
         "kinds".to_string(),
         array_prop("Filter by memory kinds", "string"),
     );
+    properties.insert(
+        "summarize".to_string(),
+        boolean_prop(
+            "Summarize long content with the client's model (sampling), or truncate it if sampling is unavailable",
+            false,
+        ),
+    );
 
     Tool {
         name: "codegraph_memory_context".to_string(),
-        description: Some("Finds memories relevant to current code location. USE WHEN: starting work on a file/function to see past context. THIS SHOULD BE YOUR FIRST CALL when starting work on unfamiliar code. Returns memories array (id, title, content, kind, score, tags) ranked by relevance to the file/line. Optionally filter by kinds. Provide line for function-level precision.".to_string()),
+        description: Some("Finds memories relevant to current code location. USE WHEN: starting work on a file/function to see past context. THIS SHOULD BE YOUR FIRST CALL when starting work on unfamiliar code. Returns memories array (id, title, content, kind, score, tags) ranked by relevance to the file/line. Optionally filter by kinds. Provide line for function-level precision. Set summarize=true to shorten long content.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn memory_update_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert("id".to_string(), string_prop("Memory ID to update"));
+    properties.insert(
+        "title".to_string(),
+        string_prop("New title (keeps the current one if omitted)"),
+    );
+    properties.insert(
+        "content".to_string(),
+        string_prop("New content (keeps the current one if omitted)"),
+    );
+    properties.insert(
+        "tags".to_string(),
+        array_prop("New tags, replacing the current ones", "string"),
+    );
+    properties.insert(
+        "confidence".to_string(),
+        number_prop("New confidence score (0-1)", None),
+    );
+    properties.insert(
+        "addCodeLinks".to_string(),
+        array_prop(
+            "Code links to add, as {nodeId, nodeType} objects (nodeType: function, class, module, file, variable, import, interface, trait)",
+            "object",
+        ),
+    );
+    properties.insert(
+        "removeCodeLinks".to_string(),
+        array_prop("Node IDs of code links to remove", "string"),
+    );
+
+    Tool {
+        name: "codegraph_memory_update".to_string(),
+        description: Some("Corrects an existing memory in place: title, content, tags, confidence, and linked code. USE WHEN: a stored memory is partly wrong or incomplete but still relevant, or should be linked to other symbols. Omitted fields are kept. Use codegraph_memory_invalidate instead when the knowledge no longer holds at all.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: Some(vec!["id".to_string()]),
+        },
+        output_schema: None,
+        annotations: writes(true, true),
     }
 }
 
@@ -1007,12 +1610,14 @@ fn memory_invalidate_tool() -> Tool {
 
     Tool {
         name: "codegraph_memory_invalidate".to_string(),
-        description: Some("Marks memory as outdated without deleting. USE WHEN: knowledge is superseded, bugs are fixed, decisions are reversed. Maintains history while preventing outdated info from surfacing.".to_string()),
+        description: Some("Marks memory as outdated without deleting. USE WHEN: knowledge is superseded, bugs are fixed, decisions are reversed. Maintains history while preventing outdated info from surfacing. The user is asked to confirm: when the call fails as not confirmed, do not retry it without their go-ahead.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: Some(vec!["id".to_string()]),
         },
+        output_schema: None,
+        annotations: writes(true, true),
     }
 }
 
@@ -1044,6 +1649,8 @@ fn memory_list_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1059,6 +1666,8 @@ fn memory_stats_tool() -> Tool {
             properties: None,
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1075,15 +1684,119 @@ fn mine_git_history_tool() -> Tool {
             Some(0.7),
         ),
     );
+    properties.insert(
+        "folder".to_string(),
+        string_prop("Workspace folder to mine (default: the first one)"),
+    );
+    properties.insert(
+        "mineBugFixes".to_string(),
+        boolean_prop("Create memories from bug fix commits", true),
+    );
+    properties.insert(
+        "mineArchDecisions".to_string(),
+        boolean_prop("Create memories from architectural decisions", true),
+    );
+    properties.insert(
+        "mineBreakingChanges".to_string(),
+        boolean_prop("Create memories from breaking changes", true),
+    );
+    properties.insert(
+        "mineReverts".to_string(),
+        boolean_prop("Create memories from reverts", true),
+    );
+    properties.insert(
+        "mineFeatures".to_string(),
+        boolean_prop("Create memories from feature commits", false),
+    );
+    properties.insert(
+        "mineDeprecations".to_string(),
+        boolean_prop("Create memories from deprecations", true),
+    );
+    properties.insert(
+        "mineTrailers".to_string(),
+        boolean_prop(
+            "Store Memory: and Decision: commit trailers verbatim as high-confidence memories",
+            true,
+        ),
+    );
+    properties.insert(
+        "mineReleases".to_string(),
+        boolean_prop(
+            "Create project context memories from annotated tags and release commits, with their release notes",
+            true,
+        ),
+    );
+    properties.insert(
+        "includeHotspots".to_string(),
+        boolean_prop("Also record high-churn files as memories", false),
+    );
+    properties.insert(
+        "hotspotsSince".to_string(),
+        string_prop(
+            "Only count hotspot changes after this date (e.g. \"2024-01-01\", \"6 months ago\")",
+        ),
+    );
+    properties.insert(
+        "hotspotsUntil".to_string(),
+        string_prop("Only count hotspot changes before this date"),
+    );
+    properties.insert(
+        "hotspotsByDirectory".to_string(),
+        boolean_prop(
+            "Record high-churn directories instead of single files",
+            false,
+        ),
+    );
+    properties.insert(
+        "includeCoupling".to_string(),
+        boolean_prop(
+            "Also record files that change together as memories, and link them with co-change edges in the graph",
+            false,
+        ),
+    );
+    properties.insert(
+        "includeOwnership".to_string(),
+        boolean_prop(
+            "Also record the main authors of each module as memories, to suggest reviewers",
+            false,
+        ),
+    );
+    properties.insert(
+        "full".to_string(),
+        boolean_prop(
+            "Scan the last maxCommits commits again instead of only those since the previous run",
+            false,
+        ),
+    );
+    properties.insert(
+        "enrichPullRequests".to_string(),
+        boolean_prop(
+            "Add the pull request of each mined merge commit (description, review comments) to its memory; needs GITHUB_TOKEN or GITLAB_TOKEN, and self-hosted instances listed in CODEGRAPH_PULL_REQUEST_HOSTS",
+            false,
+        ),
+    );
+    properties.insert(
+        "dryRun".to_string(),
+        boolean_prop(
+            "Return the candidate memories (kind, title, confidence, commits) without storing anything, to tune patterns and minConfidence",
+            false,
+        ),
+    );
+    properties.insert(
+        "branch".to_string(),
+        string_prop("Branch to mine instead of the checked-out one: a branch (main), a glob (release/*) or a range (A..B); memories are tagged branch:<name>"),
+    );
 
     Tool {
         name: "codegraph_mine_git_history".to_string(),
-        description: Some("Mines git history to create memories from commit messages and patterns. USE WHEN: setting up a new project to bootstrap knowledge from past commits.".to_string()),
+        description: Some("Mines git history to create memories from commit messages and patterns. USE WHEN: setting up a new project to bootstrap knowledge from past commits, or catching up after new commits. Later runs only mine the commits made since the previous one (since_commit); full=true rescans. Commits already mined never create duplicate memories. Issue references (#123, JIRA-456) are tagged ref:<issue>, and later fixes for the same issue are added to the first fix's memory (commits_grouped). enrichPullRequests=true adds the pull request behind merge commits (description, review comments), fetched with GITHUB_TOKEN / GITLAB_TOKEN. branch mines another branch, a glob of branches or a range instead of the checked-out one. Annotated tags and release commits become project context memories tagged release:<version>, valid from the release date (releases_mined). Memory: and Decision: trailers in commit messages are stored word for word (trailers_mined).".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: writes(false, false),
     }
 }
 
@@ -1104,6 +1817,24 @@ fn mine_git_file_tool() -> Tool {
             Some(0.7),
         ),
     );
+    properties.insert(
+        "enrichPullRequests".to_string(),
+        boolean_prop(
+            "Add the pull request of each mined merge commit (description, review comments) to its memory; needs GITHUB_TOKEN or GITLAB_TOKEN, and self-hosted instances listed in CODEGRAPH_PULL_REQUEST_HOSTS",
+            false,
+        ),
+    );
+    properties.insert(
+        "dryRun".to_string(),
+        boolean_prop(
+            "Return the candidate memories (kind, title, confidence, commits) without storing anything, to tune patterns and minConfidence",
+            false,
+        ),
+    );
+    properties.insert(
+        "branch".to_string(),
+        string_prop("Branch to mine instead of the checked-out one: a branch (main), a glob (release/*) or a range (A..B); memories are tagged branch:<name>"),
+    );
 
     Tool {
         name: "codegraph_mine_git_history_for_file".to_string(),
@@ -1113,6 +1844,8 @@ fn mine_git_file_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["uri".to_string()]),
         },
+        output_schema: None,
+        annotations: writes(false, false),
     }
 }
 
@@ -1139,6 +1872,8 @@ fn search_git_history_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["query".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1180,18 +1915,59 @@ fn cross_project_search_tool() -> Tool {
             properties: Some(properties),
             required: Some(vec!["query".to_string()]),
         },
+        output_schema: None,
+        annotations: read_only(),
+    }
+}
+
+fn batch_query_tool() -> Tool {
+    let mut properties = HashMap::new();
+    properties.insert(
+        "queries".to_string(),
+        array_prop(
+            "Queries to run, each {id, tool, arguments}: id keys the result, tool is any CodeGraph tool name (the 'codegraph_' prefix is optional), arguments are that tool's arguments. Max 32.",
+            "object",
+        ),
+    );
+
+    Tool {
+        name: "codegraph_batch_query".to_string(),
+        description: Some("Runs several CodeGraph tool calls concurrently in one round trip and returns results keyed by query id. USE WHEN: you already know the next few lookups you need (e.g. symbol_search + get_callers + find_related_tests for one function) — batching them is much faster than calling tools one by one. A failing query reports its own 'error' without failing the batch. Only read-only tools can run in a batch: not memory writes, git mining, reindex_workspace or another batch_query.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: Some(properties),
+            required: Some(vec!["queries".to_string()]),
+        },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
 fn reindex_workspace_tool() -> Tool {
     Tool {
         name: "codegraph_reindex_workspace".to_string(),
-        description: Some("Reindex the workspace to refresh the code graph. USE WHEN: the graph data is stale or parsers have been updated. This clears the existing graph and re-parses all files.".to_string()),
+        description: Some("Reindex the workspace to refresh the code graph. USE WHEN: the graph data is stale or parsers have been updated, e.g. after long sessions or branch switches. This clears the existing graph, re-parses all files and rebuilds the search indexes, reporting progress; cancelling stops it with the files parsed so far.".to_string()),
         input_schema: ToolInputSchema {
             schema_type: "object".to_string(),
             properties: None,
             required: None,
         },
+        output_schema: None,
+        annotations: writes(true, true),
+    }
+}
+
+fn server_stats_tool() -> Tool {
+    Tool {
+        name: "codegraph_server_stats".to_string(),
+        description: Some("Per-tool call counts, latencies (avg, p50, p95, max) and error rates since the server started, the most time-consuming tools first. USE WHEN: a session feels slow or tools keep failing, to see where the time goes.".to_string()),
+        input_schema: ToolInputSchema {
+            schema_type: "object".to_string(),
+            properties: None,
+            required: None,
+        },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1221,6 +1997,8 @@ fn find_duplicates_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1251,6 +2029,8 @@ fn find_similar_tool() -> Tool {
             properties: Some(properties),
             required: None, // either nodeId or uri+line
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1280,6 +2060,8 @@ fn cluster_symbols_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1318,6 +2100,8 @@ fn compare_symbols_tool() -> Tool {
             properties: Some(properties),
             required: None,
         },
+        output_schema: None,
+        annotations: read_only(),
     }
 }
 
@@ -1328,8 +2112,8 @@ mod tests {
     #[test]
     fn test_get_all_tools_count() {
         let tools = get_all_tools();
-        // Analysis: 11, Search: 5, Navigation: 3, Memory: 10, Cross-Project: 1, Admin: 1 = 31 tools
-        assert_eq!(tools.len(), 31, "Expected 31 tools, got {}", tools.len());
+        // Analysis: 15, Search: 8, Navigation: 3, Memory: 11, Cross-Project: 1, Similarity: 4, Batch: 1, Admin: 2 = 45 tools
+        assert_eq!(tools.len(), 45, "Expected 45 tools, got {}", tools.len());
     }
 
     #[test]
@@ -1344,6 +2128,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_output_schemas() {
+        let structured: Vec<_> = get_all_tools()
+            .into_iter()
+            .filter_map(|tool| Some((tool.name, tool.output_schema?)))
+            .collect();
+        assert_eq!(structured.len(), 4);
+        for (name, schema) in &structured {
+            assert!(has_output_schema(name));
+            assert_eq!(schema["type"], "object", "{} output schema", name);
+        }
+        assert_eq!(
+            call_graph_output_schema("callers")["required"],
+            serde_json::json!(["callers"])
+        );
+        assert!(!has_output_schema("codegraph_get_ai_context"));
+    }
+
+    #[test]
+    fn test_tool_annotations() {
+        let tools = get_all_tools();
+        assert!(tools.iter().all(|tool| tool.annotations.is_some()));
+        let writing: HashSet<_> = tools
+            .iter()
+            .filter(|tool| !is_read_only(&tool.name))
+            .map(|tool| tool.name.as_str())
+            .collect();
+        assert_eq!(
+            writing,
+            HashSet::from([
+                "codegraph_memory_store",
+                "codegraph_memory_update",
+                "codegraph_memory_invalidate",
+                "codegraph_mine_git_history",
+                "codegraph_mine_git_history_for_file",
+                "codegraph_reindex_workspace",
+            ])
+        );
+        assert!(is_read_only("codegraph_symbol_search"));
+        assert!(!is_read_only("codegraph_unknown"));
+    }
+
+    #[test]
+    fn test_only_read_only_tools_take_a_cursor() {
+        for tool in get_all_tools() {
+            let has_cursor = tool
+                .input_schema
+                .properties
+                .as_ref()
+                .is_some_and(|p| p.contains_key("cursor"));
+            assert_eq!(has_cursor, is_read_only(&tool.name), "{}", tool.name);
+        }
+    }
+
     #[test]
     fn test_tool_names_are_unique() {
         let tools = get_all_tools();
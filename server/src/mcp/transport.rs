@@ -1,10 +1,16 @@
 //! MCP Transport Layer
 //!
-//! Stdio transport for JSON-RPC 2.0 communication.
+//! Line-delimited JSON-RPC 2.0 over stdio, or over any byte stream such as
+//! the daemon's sockets.
 
-use super::protocol::{JsonRpcRequest, JsonRpcResponse};
+use super::protocol::{
+    Incoming, JsonRpcNotification, JsonRpcOutgoingRequest, JsonRpcRequest, JsonRpcResponse,
+};
+use serde::Serialize;
 use std::io::{self, BufRead, Write};
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, Lines};
+use tokio::sync::Mutex;
 
 /// Synchronous stdio transport for MCP
 pub struct StdioTransport {
@@ -62,59 +68,148 @@ impl Default for StdioTransport {
     }
 }
 
-/// Async stdio transport for MCP
-pub struct AsyncStdioTransport {
-    stdin: BufReader<tokio::io::Stdin>,
-    stdout: tokio::io::Stdout,
+type BoxedReader = Box<dyn AsyncRead + Send + Unpin>;
+type BoxedWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+/// Async transport for MCP, over stdio or a socket
+pub struct AsyncTransport {
+    input: Lines<BufReader<BoxedReader>>,
+    output: MessageWriter,
 }
 
-impl AsyncStdioTransport {
-    pub fn new() -> Self {
+/// Writes messages to the output, one line at a time. Cloned to write from
+/// the event loop and from tasks sending notifications while a request runs.
+#[derive(Clone)]
+pub struct MessageWriter {
+    output: Arc<Mutex<BoxedWriter>>,
+}
+
+/// A message to the client, queued to be written in order: the
+/// notifications sent while a request runs, e.g. its progress, come before
+/// its response.
+#[derive(Debug)]
+pub enum Outgoing {
+    Response(JsonRpcResponse),
+    /// The responses to a batch, written as one array
+    Batch(Vec<JsonRpcResponse>),
+    Notification(JsonRpcNotification),
+}
+
+impl From<JsonRpcNotification> for Outgoing {
+    fn from(notification: JsonRpcNotification) -> Self {
+        Self::Notification(notification)
+    }
+}
+
+impl MessageWriter {
+    /// Write a queued message
+    pub async fn write(&self, message: &Outgoing) -> io::Result<()> {
+        match message {
+            Outgoing::Response(response) => self.write_response(response).await,
+            Outgoing::Batch(responses) => self.write_batch(responses).await,
+            Outgoing::Notification(notification) => self.write_notification(notification).await,
+        }
+    }
+
+    /// Write a JSON-RPC response asynchronously
+    pub async fn write_response(&self, response: &JsonRpcResponse) -> io::Result<()> {
+        self.write_message(response).await
+    }
+
+    /// Write the responses to a batch as one array
+    pub async fn write_batch(&self, responses: &[JsonRpcResponse]) -> io::Result<()> {
+        self.write_message(&responses).await
+    }
+
+    /// Write a JSON-RPC notification asynchronously
+    pub async fn write_notification(&self, notification: &JsonRpcNotification) -> io::Result<()> {
+        self.write_message(notification).await
+    }
+
+    /// Write a JSON-RPC request to the client asynchronously
+    pub async fn write_request(&self, request: &JsonRpcOutgoingRequest) -> io::Result<()> {
+        self.write_message(request).await
+    }
+
+    async fn write_message(&self, message: &impl Serialize) -> io::Result<()> {
+        let json = serde_json::to_string(message)?;
+        let mut output = self.output.lock().await;
+        output.write_all(json.as_bytes()).await?;
+        output.write_all(b"\n").await?;
+        output.flush().await
+    }
+}
+
+impl AsyncTransport {
+    /// Transport reading `input` and writing `output`, e.g. the halves of a
+    /// socket
+    pub fn new(
+        input: impl AsyncRead + Send + Unpin + 'static,
+        output: impl AsyncWrite + Send + Unpin + 'static,
+    ) -> Self {
+        let input: BoxedReader = Box::new(input);
+        let output: BoxedWriter = Box::new(output);
         Self {
-            stdin: BufReader::new(tokio::io::stdin()),
-            stdout: tokio::io::stdout(),
+            input: BufReader::new(input).lines(),
+            output: MessageWriter {
+                output: Arc::new(Mutex::new(output)),
+            },
         }
     }
 
-    /// Read a JSON-RPC request from stdin asynchronously
+    pub fn stdio() -> Self {
+        Self::new(tokio::io::stdin(), tokio::io::stdout())
+    }
+
+    /// Writer for messages sent while requests are read elsewhere
+    pub fn writer(&self) -> MessageWriter {
+        self.output.clone()
+    }
+
+    /// Read a JSON-RPC message asynchronously: a request, or a response to a
+    /// request the server sent, or a batch of them
     ///
-    /// Returns `Err(UnexpectedEof)` when stdin is closed (client disconnected).
+    /// Returns `Err(UnexpectedEof)` when the input is closed (client disconnected).
     /// Returns `Ok(None)` for empty/whitespace-only lines (keep reading).
-    pub async fn read_request(&mut self) -> io::Result<Option<JsonRpcRequest>> {
-        let mut line = String::new();
-        let bytes_read = self.stdin.read_line(&mut line).await?;
-
-        if bytes_read == 0 {
-            // EOF — stdin closed, client disconnected
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "stdin closed"));
-        }
+    /// Cancel safe: it may race outgoing notifications in `select!` without
+    /// losing a partly read line.
+    pub async fn read_message(&mut self) -> io::Result<Option<Incoming>> {
+        let Some(line) = self.input.next_line().await? else {
+            // EOF — input closed, client disconnected
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "input closed"));
+        };
 
         let line = line.trim();
         if line.is_empty() {
             return Ok(None);
         }
 
-        match serde_json::from_str(line) {
-            Ok(request) => Ok(Some(request)),
+        match Incoming::parse(line) {
+            Ok(message) => Ok(Some(message)),
             Err(e) => {
-                tracing::error!("Failed to parse JSON-RPC request: {}", e);
+                tracing::error!("Failed to parse JSON-RPC message: {}", e);
                 Err(io::Error::new(io::ErrorKind::InvalidData, e))
             }
         }
     }
 
-    /// Write a JSON-RPC response to stdout asynchronously
+    /// Write a JSON-RPC response asynchronously
     pub async fn write_response(&mut self, response: &JsonRpcResponse) -> io::Result<()> {
-        let json = serde_json::to_string(response)?;
-        self.stdout.write_all(json.as_bytes()).await?;
-        self.stdout.write_all(b"\n").await?;
-        self.stdout.flush().await
+        self.output.write_response(response).await
+    }
+
+    /// Write a JSON-RPC notification asynchronously
+    pub async fn write_notification(
+        &mut self,
+        notification: &JsonRpcNotification,
+    ) -> io::Result<()> {
+        self.output.write_notification(notification).await
     }
 }
 
-impl Default for AsyncStdioTransport {
+impl Default for AsyncTransport {
     fn default() -> Self {
-        Self::new()
+        Self::stdio()
     }
 }
 
@@ -122,6 +217,7 @@ impl Default for AsyncStdioTransport {
 mod tests {
     use super::*;
     use crate::mcp::protocol::JsonRpcError;
+    use serde_json::Value;
 
     #[test]
     fn test_response_serialization() {
@@ -141,4 +237,34 @@ mod tests {
         let json = serde_json::to_string(&response).unwrap();
         assert!(json.contains("-32601"));
     }
+
+    #[tokio::test]
+    async fn test_batch_round_trip() {
+        let (client, server) = tokio::io::duplex(4096);
+        let (server_read, server_write) = tokio::io::split(server);
+        let mut transport = AsyncTransport::new(server_read, server_write);
+        let (client_read, mut client_write) = tokio::io::split(client);
+
+        client_write
+            .write_all(b"[{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"ping\"}]\n")
+            .await
+            .unwrap();
+        let message = transport.read_message().await.unwrap();
+        assert!(matches!(message, Some(Incoming::Batch(members)) if members.len() == 1));
+
+        let responses = [
+            JsonRpcResponse::success(Some(serde_json::json!(1)), serde_json::json!({})),
+            JsonRpcResponse::success(Some(serde_json::json!(2)), serde_json::json!({})),
+        ];
+        transport.writer().write_batch(&responses).await.unwrap();
+        let line = BufReader::new(client_read)
+            .lines()
+            .next_line()
+            .await
+            .unwrap()
+            .unwrap();
+        let batch: Vec<Value> = serde_json::from_str(&line).unwrap();
+        assert_eq!(batch.len(), 2);
+        assert_eq!(batch[1]["id"], 2);
+    }
 }
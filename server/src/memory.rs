@@ -6,9 +6,14 @@
 //! Data is stored globally at `~/.codegraph/projects/<slug>/memory/` where
 //! `<slug>` is derived from the workspace directory name + a short hash of
 //! the full path for uniqueness (e.g. `codegraph-vscode-a3f2`).
+//!
+//! In a multi-root workspace each folder keeps its own store. Reads merge
+//! across all of them; writes go to the store of the folder a memory belongs
+//! to, or to the first folder's store.
 
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -82,6 +87,35 @@ fn project_data_dir(workspace_path: &Path) -> Result<PathBuf, MemoryError> {
         .join(slug))
 }
 
+/// File beside a folder's memory database recording how far git mining got
+const MINING_STATE_FILE: &str = "git_mining.json";
+
+#[derive(Debug, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MiningState {
+    /// HEAD when the repository was last mined
+    last_mined_commit: Option<String>,
+}
+
+/// A workspace folder and the memory database that belongs to it.
+#[derive(Debug, Clone)]
+struct FolderStore {
+    workspace: PathBuf,
+    data_dir: PathBuf,
+}
+
+/// Index of the folder store that owns `path`: the one with the deepest
+/// workspace root containing it, falling back to the first (primary) folder.
+fn owning_folder(folders: &[FolderStore], path: &Path) -> Option<usize> {
+    folders
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| path.starts_with(&f.workspace))
+        .max_by_key(|(_, f)| f.workspace.components().count())
+        .map(|(i, _)| i)
+        .or_else(|| (!folders.is_empty()).then_some(0))
+}
+
 /// Memory manager for the LSP server
 ///
 /// Opens the database on-demand for each operation and closes it immediately after.
@@ -91,14 +125,17 @@ fn project_data_dir(workspace_path: &Path) -> Result<PathBuf, MemoryError> {
 /// Data is stored at `~/.codegraph/projects/<slug>/memory/` rather than in the
 /// workspace directory, keeping workspaces clean.
 pub struct MemoryManager {
-    /// Resolved path to memory database (e.g. ~/.codegraph/projects/<slug>/memory)
-    data_dir: Arc<RwLock<Option<PathBuf>>>,
+    /// Per-folder memory databases (e.g. ~/.codegraph/projects/<slug>/memory);
+    /// the first entry is the primary store
+    folders: Arc<RwLock<Vec<FolderStore>>>,
     /// Path to extension root (unused by fastembed, kept for API compatibility)
     extension_path: Option<PathBuf>,
     /// Cached vector engine (holds model, not DB - safe to keep)
     engine: Arc<RwLock<Option<Arc<VectorEngine>>>>,
     /// Embedding model selection
     embedding_model: codegraph_memory::CodeGraphEmbeddingModel,
+    /// Invalidate memories when their linked code changes
+    auto_invalidate: AtomicBool,
 }
 
 impl MemoryManager {
@@ -113,19 +150,28 @@ impl MemoryManager {
         embedding_model: codegraph_memory::CodeGraphEmbeddingModel,
     ) -> Self {
         Self {
-            data_dir: Arc::new(RwLock::new(None)),
+            folders: Arc::new(RwLock::new(Vec::new())),
             extension_path,
             engine: Arc::new(RwLock::new(None)),
             embedding_model,
+            auto_invalidate: AtomicBool::new(true),
         }
     }
 
+    /// Enable or disable invalidating memories when their linked code changes.
+    pub fn set_auto_invalidate(&self, enabled: bool) {
+        self.auto_invalidate.store(enabled, Ordering::Relaxed);
+    }
+
     /// Initialize the memory manager with workspace path
     ///
     /// Resolves the global data directory at `~/.codegraph/projects/<slug>/memory/`,
     /// migrating from the old `workspace/.codegraph/memory/` location if needed.
     /// Does NOT hold the database open - that happens on-demand per operation.
     ///
+    /// Call once per workspace folder; the first folder initialized becomes the
+    /// primary store. Initializing an already known folder is a no-op.
+    ///
     /// # Arguments
     /// * `workspace_path` - Path to the workspace root
     pub async fn initialize(&self, workspace_path: &Path) -> Result<(), MemoryError> {
@@ -135,6 +181,16 @@ impl MemoryManager {
             workspace_path
         );
 
+        if self
+            .folders
+            .read()
+            .await
+            .iter()
+            .any(|f| f.workspace == workspace_path)
+        {
+            return Ok(());
+        }
+
         // Resolve global data directory
         let project_dir = project_data_dir(workspace_path)?;
         let data_dir = project_dir.join("memory");
@@ -165,7 +221,40 @@ impl MemoryManager {
             e
         })?;
 
-        // Initialize vector engine with selected model (cached, doesn't hold DB lock)
+        // Initialize vector engine with selected model (cached, doesn't hold DB lock).
+        // The engine is shared by all folder stores.
+        if self.engine.read().await.is_none() {
+            self.init_engine().await?;
+        }
+
+        // Store resolved path for on-demand use
+        let mut folders = self.folders.write().await;
+        if !folders.iter().any(|f| f.workspace == workspace_path) {
+            folders.push(FolderStore {
+                workspace: workspace_path.to_path_buf(),
+                data_dir: data_dir.clone(),
+            });
+        }
+
+        tracing::info!(
+            "[MemoryManager::initialize] Memory initialized at {:?}",
+            data_dir
+        );
+        Ok(())
+    }
+
+    /// Stop using the memory store of a workspace folder that was removed.
+    ///
+    /// The data stays on disk and is picked up again if the folder is re-added.
+    pub async fn remove_workspace_folder(&self, workspace_path: &Path) {
+        self.folders
+            .write()
+            .await
+            .retain(|f| f.workspace != workspace_path);
+    }
+
+    /// Create the shared vector engine
+    async fn init_engine(&self) -> Result<(), MemoryError> {
         let cache_dir = std::env::var("HOME")
             .or_else(|_| std::env::var("USERPROFILE"))
             .map(PathBuf::from)
@@ -180,14 +269,7 @@ impl MemoryManager {
             e
         })?;
 
-        // Store resolved path and engine for on-demand use
-        *self.data_dir.write().await = Some(data_dir.clone());
         *self.engine.write().await = Some(Arc::new(engine));
-
-        tracing::info!(
-            "[MemoryManager::initialize] Memory initialized at {:?}",
-            data_dir
-        );
         Ok(())
     }
 
@@ -230,7 +312,7 @@ impl MemoryManager {
 
     /// Check if memory manager is initialized
     pub async fn is_initialized(&self) -> bool {
-        self.data_dir.read().await.is_some() && self.engine.read().await.is_some()
+        !self.folders.read().await.is_empty() && self.engine.read().await.is_some()
     }
 
     /// Get the shared VectorEngine for embedding operations.
@@ -241,54 +323,141 @@ impl MemoryManager {
 
     /// Open a fresh MemoryStore for an operation
     ///
-    /// The store is dropped when it goes out of scope, releasing the DB lock.
-    async fn open_store(&self) -> Result<MemoryStore, MemoryError> {
-        let data_dir = self
-            .data_dir
+    /// Opens the store of the folder owning `path`, or the primary store when
+    /// `path` is `None`. The store is dropped when it goes out of scope,
+    /// releasing the DB lock.
+    async fn open_store_for(&self, path: Option<&Path>) -> Result<MemoryStore, MemoryError> {
+        let data_dir = self.data_dir_for(path).await?;
+        MemoryStore::new(&data_dir, self.require_engine().await?)
+    }
+
+    /// Data directory of the folder owning `path`, or of the primary folder
+    async fn data_dir_for(&self, path: Option<&Path>) -> Result<PathBuf, MemoryError> {
+        let folders = self.folders.read().await;
+        let index = match path {
+            Some(path) => owning_folder(&folders, path),
+            None => (!folders.is_empty()).then_some(0),
+        };
+        index
+            .map(|i| folders[i].data_dir.clone())
+            .ok_or_else(|| MemoryError::Other("Memory manager not initialized".to_string()))
+    }
+
+    /// HEAD of the repository at `repo_path` when it was last mined, if ever
+    pub async fn last_mined_commit(&self, repo_path: &Path) -> Option<String> {
+        let data_dir = self.data_dir_for(Some(repo_path)).await.ok()?;
+        let text = std::fs::read_to_string(data_dir.join(MINING_STATE_FILE)).ok()?;
+        serde_json::from_str::<MiningState>(&text)
+            .ok()?
+            .last_mined_commit
+    }
+
+    /// Record that the repository at `repo_path` was mined up to `commit`
+    pub async fn set_last_mined_commit(
+        &self,
+        repo_path: &Path,
+        commit: &str,
+    ) -> Result<(), MemoryError> {
+        let data_dir = self.data_dir_for(Some(repo_path)).await?;
+        let state = MiningState {
+            last_mined_commit: Some(commit.to_string()),
+        };
+        let json = serde_json::to_string(&state).map_err(|e| MemoryError::Other(e.to_string()))?;
+        std::fs::write(data_dir.join(MINING_STATE_FILE), json)?;
+        Ok(())
+    }
+
+    /// Open the store of every workspace folder, primary first
+    async fn open_stores(&self) -> Result<Vec<MemoryStore>, MemoryError> {
+        let data_dirs: Vec<PathBuf> = self
+            .folders
             .read()
             .await
-            .clone()
-            .ok_or_else(|| MemoryError::Other("Memory manager not initialized".to_string()))?;
+            .iter()
+            .map(|f| f.data_dir.clone())
+            .collect();
+        if data_dirs.is_empty() {
+            return Err(MemoryError::Other(
+                "Memory manager not initialized".to_string(),
+            ));
+        }
+
+        let engine = self.require_engine().await?;
+        data_dirs
+            .iter()
+            .map(|dir| MemoryStore::new(dir, Arc::clone(&engine)))
+            .collect()
+    }
 
-        let engine = self
-            .engine
+    async fn require_engine(&self) -> Result<Arc<VectorEngine>, MemoryError> {
+        self.engine
             .read()
             .await
             .clone()
-            .ok_or_else(|| MemoryError::Other("Vector engine not initialized".to_string()))?;
-
-        MemoryStore::new(&data_dir, engine)
+            .ok_or_else(|| MemoryError::Other("Vector engine not initialized".to_string()))
     }
 
-    /// Store a memory node
+    /// Store a memory node back in the store that holds it, or a new one in
+    /// the primary store
     ///
     /// Opens DB, stores memory, closes DB.
     pub async fn put(&self, node: MemoryNode) -> Result<String, MemoryError> {
-        let store = self.open_store().await?;
-        store.put(node).await
+        self.put_in(node, None).await
+    }
+
+    /// Store a memory node back in the store that holds it, or a new one in
+    /// the store of the workspace folder containing `path`
+    pub async fn put_for_path(&self, node: MemoryNode, path: &Path) -> Result<String, MemoryError> {
+        self.put_in(node, Some(path)).await
+    }
+
+    /// Store `node` in the store already holding its id, as `invalidate`
+    /// does, so that updating a memory never copies it into another store
+    async fn put_in(&self, node: MemoryNode, path: Option<&Path>) -> Result<String, MemoryError> {
+        let id = node.id.to_string();
+        let stores = self.open_stores().await?;
+        let index = match stores.iter().position(|store| store.get(&id).is_some()) {
+            Some(index) => index,
+            None => {
+                let folders = self.folders.read().await;
+                path.and_then(|path| owning_folder(&folders, path))
+                    .unwrap_or(0)
+            }
+        };
+        match stores.into_iter().nth(index) {
+            Some(store) => store.put(node).await,
+            None => Err(MemoryError::Other(
+                "Memory manager not initialized".to_string(),
+            )),
+        }
     }
 
     /// Get a memory by ID
     ///
     /// Opens DB, retrieves memory, closes DB.
     pub async fn get(&self, id: &str) -> Result<Option<MemoryNode>, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.get(id))
+        let stores = self.open_stores().await?;
+        Ok(stores.iter().find_map(|store| store.get(id)))
     }
 
     /// Search memories with hybrid search
     ///
-    /// Opens DB, performs search, closes DB.
+    /// Opens DB, performs search, closes DB. Results from all folder stores
+    /// are merged by score.
     pub async fn search(
         &self,
         query: &str,
         config: &SearchConfig,
         code_context: &[String],
     ) -> Result<Vec<SearchResult>, MemoryError> {
-        let store = self.open_store().await?;
-        let store = Arc::new(store);
-        let search = MemorySearch::new(store)?;
-        search.search(query, code_context, config)
+        let mut results = Vec::new();
+        for store in self.open_stores().await? {
+            let search = MemorySearch::new(Arc::new(store))?;
+            results.extend(search.search(query, code_context, config)?);
+        }
+        results.sort_by(|a, b| b.score.total_cmp(&a.score));
+        results.truncate(config.limit);
+        Ok(results)
     }
 
     /// Find memories linked to a code node
@@ -296,32 +465,49 @@ impl MemoryManager {
         &self,
         code_node_id: &str,
     ) -> Result<Vec<MemoryNode>, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.find_by_code_node(code_node_id))
+        let stores = self.open_stores().await?;
+        Ok(stores
+            .iter()
+            .flat_map(|store| store.find_by_code_node(code_node_id))
+            .collect())
     }
 
     /// Find memories with a specific tag
     pub async fn find_by_tag(&self, tag: &str) -> Result<Vec<MemoryNode>, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.find_by_tag(tag))
+        let stores = self.open_stores().await?;
+        Ok(stores
+            .iter()
+            .flat_map(|store| store.find_by_tag(tag))
+            .collect())
     }
 
     /// Invalidate a memory (mark as no longer current)
     pub async fn invalidate(&self, id: &str, reason: &str) -> Result<(), MemoryError> {
-        let store = self.open_store().await?;
-        store.invalidate(id, reason)
+        let stores = self.open_stores().await?;
+        match stores.iter().find(|store| store.get(id).is_some()) {
+            Some(store) => store.invalidate(id, reason),
+            // Let the primary store report the missing memory
+            None => stores[0].invalidate(id, reason),
+        }
     }
 
     /// Delete a memory permanently
     pub async fn delete(&self, id: &str) -> Result<bool, MemoryError> {
-        let store = self.open_store().await?;
-        store.delete(id)
+        for store in self.open_stores().await? {
+            if store.delete(id)? {
+                return Ok(true);
+            }
+        }
+        Ok(false)
     }
 
     /// Get all current (non-invalidated) memories
     pub async fn get_all_current(&self) -> Result<Vec<MemoryNode>, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.get_all_current())
+        let stores = self.open_stores().await?;
+        Ok(stores
+            .iter()
+            .flat_map(|store| store.get_all_current())
+            .collect())
     }
 
     /// Get all memories, optionally including invalidated ones
@@ -329,42 +515,63 @@ impl MemoryManager {
         &self,
         current_only: bool,
     ) -> Result<Vec<MemoryNode>, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.get_all_memories(current_only))
+        let stores = self.open_stores().await?;
+        Ok(stores
+            .iter()
+            .flat_map(|store| store.get_all_memories(current_only))
+            .collect())
     }
 
     /// Get store statistics
+    ///
+    /// With several workspace folders, statistics are listed per folder.
     pub async fn stats(&self) -> Result<serde_json::Value, MemoryError> {
-        let store = self.open_store().await?;
-        Ok(store.stats())
+        let folders = self.folders.read().await.clone();
+        let stores = self.open_stores().await?;
+        if stores.len() == 1 {
+            return Ok(stores[0].stats());
+        }
+        Ok(serde_json::json!({
+            "folders": folders
+                .iter()
+                .zip(&stores)
+                .map(|(folder, store)| serde_json::json!({
+                    "workspace": folder.workspace.to_string_lossy(),
+                    "stats": store.stats(),
+                }))
+                .collect::<Vec<_>>(),
+        }))
     }
 
     /// Invalidate all memories linked to any of the given code node IDs
     ///
-    /// Used for auto-invalidation when code changes.
+    /// Used for auto-invalidation when code changes; a no-op when
+    /// auto-invalidation is turned off.
     pub async fn invalidate_for_code_nodes(
         &self,
         node_ids: &[String],
         reason: &str,
     ) -> Result<Vec<(String, String)>, MemoryError> {
-        if !self.is_initialized().await {
+        if !self.auto_invalidate.load(Ordering::Relaxed) || !self.is_initialized().await {
             return Ok(vec![]);
         }
 
-        let store = self.open_store().await?;
+        let stores = self.open_stores().await?;
         let mut invalidated = Vec::new();
         let mut seen_ids = std::collections::HashSet::new();
 
-        for node_id in node_ids {
-            let memories = store.find_by_code_node(node_id);
-            for memory in memories {
-                let id_str = memory.id.to_string();
-                // Avoid invalidating the same memory twice
-                if seen_ids.insert(id_str.clone())
-                    && memory.temporal.is_current()
-                    && store.invalidate(&id_str, reason).is_ok()
-                {
-                    invalidated.push((id_str, memory.title.clone()));
+        for store in &stores {
+            for node_id in node_ids {
+                let memories = store.find_by_code_node(node_id);
+                for memory in memories {
+                    let id_str = memory.id.to_string();
+                    // Avoid invalidating the same memory twice
+                    if seen_ids.insert(id_str.clone())
+                        && memory.temporal.is_current()
+                        && store.invalidate(&id_str, reason).is_ok()
+                    {
+                        invalidated.push((id_str, memory.title.clone()));
+                    }
                 }
             }
         }
@@ -441,6 +648,34 @@ mod tests {
         assert_ne!(slug1, slug2);
     }
 
+    #[test]
+    fn test_owning_folder_prefers_deepest_root() {
+        let folder = |workspace: &str| FolderStore {
+            workspace: PathBuf::from(workspace),
+            data_dir: PathBuf::from("/data").join(workspace.trim_start_matches('/')),
+        };
+        let folders = vec![
+            folder("/ws/app"),
+            folder("/ws/lib"),
+            folder("/ws/lib/vendor"),
+        ];
+
+        assert_eq!(
+            owning_folder(&folders, Path::new("/ws/lib/src/a.rs")),
+            Some(1)
+        );
+        assert_eq!(
+            owning_folder(&folders, Path::new("/ws/lib/vendor/b.rs")),
+            Some(2)
+        );
+        // Paths outside every folder go to the primary store
+        assert_eq!(
+            owning_folder(&folders, Path::new("/elsewhere/c.rs")),
+            Some(0)
+        );
+        assert_eq!(owning_folder(&[], Path::new("/ws/app/d.rs")), None);
+    }
+
     #[tokio::test]
     async fn test_memory_manager_uninitialized() {
         let manager = MemoryManager::new(None);
@@ -486,4 +721,46 @@ mod tests {
         // Invalidate it
         manager.invalidate(&id, "testing").await.unwrap();
     }
+
+    #[tokio::test]
+    #[ignore = "requires model files"]
+    async fn test_put_writes_back_to_owning_store() {
+        use tempfile::TempDir;
+        let primary = TempDir::new().unwrap();
+        let secondary = TempDir::new().unwrap();
+        let manager = MemoryManager::new(None);
+        manager.initialize(primary.path()).await.unwrap();
+        manager.initialize(secondary.path()).await.unwrap();
+
+        let memory = MemoryManager::builder()
+            .debug_context("Flaky login", "Retry on timeout")
+            .title("Login retries")
+            .content("Original content")
+            .build()
+            .unwrap();
+        let id = manager
+            .put_for_path(memory, &secondary.path().join("src/login.rs"))
+            .await
+            .unwrap();
+
+        // An update through either method stays in the secondary store
+        let mut updated = manager.get(&id).await.unwrap().unwrap();
+        updated.content = "Updated content".to_string();
+        manager.put(updated.clone()).await.unwrap();
+        manager
+            .put_for_path(updated, &primary.path().join("src/main.rs"))
+            .await
+            .unwrap();
+
+        let primary_store = manager.open_store_for(None).await.unwrap();
+        assert!(primary_store.get(&id).is_none());
+        let secondary_store = manager
+            .open_store_for(Some(secondary.path()))
+            .await
+            .unwrap();
+        assert_eq!(
+            secondary_store.get(&id).map(|memory| memory.content),
+            Some("Updated content".to_string())
+        );
+    }
 }
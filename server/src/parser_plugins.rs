@@ -0,0 +1,586 @@
+//! Parsers loaded at runtime from tree-sitter grammars.
+//!
+//! Languages without a built-in parser can be added without rebuilding the
+//! server. Each subdirectory of the plugins directory (`~/.codegraph/parsers/`,
+//! or `$CODEGRAPH_PARSERS_DIR`) holds a compiled tree-sitter grammar and a
+//! `parser.toml` manifest mapping its syntax node kinds to graph nodes:
+//!
+//! ```toml
+//! language = "zig"
+//! extensions = ["zig"]
+//! library = "libtree-sitter-zig.so"
+//! # symbol = "tree_sitter_zig"  (the default: tree_sitter_<language>)
+//!
+//! [[nodes]]
+//! kind = "function_declaration"
+//! node_type = "function"
+//! # name_field = "name"  (the default)
+//! ```
+//!
+//! Mapped nodes are added with their name, path and position, contained in the
+//! file or in the nearest enclosing mapped node.
+
+use codegraph::{CodeGraph, EdgeType, NodeId, NodeType, PropertyMap, PropertyValue};
+use codegraph_parser_api::{CodeParser, FileInfo, ParserConfig, ParserError, ParserMetrics};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Manifest file name inside a plugin directory.
+pub const PLUGIN_MANIFEST: &str = "parser.toml";
+
+/// Environment variable overriding the plugins directory.
+pub const PLUGINS_DIR_ENV: &str = "CODEGRAPH_PARSERS_DIR";
+
+/// Kind of graph node a syntax node maps to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PluginNodeType {
+    Function,
+    Class,
+    Interface,
+    Trait,
+    Type,
+    Variable,
+    Module,
+    Import,
+}
+
+impl PluginNodeType {
+    fn node_type(self) -> NodeType {
+        match self {
+            Self::Function => NodeType::Function,
+            Self::Class => NodeType::Class,
+            Self::Interface => NodeType::Interface,
+            Self::Trait => NodeType::Trait,
+            Self::Type => NodeType::Type,
+            Self::Variable => NodeType::Variable,
+            Self::Module => NodeType::Module,
+            Self::Import => NodeType::Import,
+        }
+    }
+}
+
+/// A syntax node kind and the graph node it becomes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeMapping {
+    /// Tree-sitter node kind, e.g. `function_declaration`
+    pub kind: String,
+    pub node_type: PluginNodeType,
+    /// Field holding the node's name; imports without one use their text
+    #[serde(default = "default_name_field")]
+    pub name_field: String,
+}
+
+fn default_name_field() -> String {
+    "name".to_string()
+}
+
+/// Contents of a plugin's `parser.toml`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginManifest {
+    pub language: String,
+    /// File extensions, with or without the leading dot
+    pub extensions: Vec<String>,
+    /// Compiled grammar, relative to the plugin directory
+    pub library: PathBuf,
+    /// Exported function returning the grammar; `tree_sitter_<language>` if unset
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub nodes: Vec<NodeMapping>,
+}
+
+impl PluginManifest {
+    /// Read and validate the manifest in `dir`.
+    pub fn load(dir: &Path) -> Result<Self, String> {
+        let path = dir.join(PLUGIN_MANIFEST);
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        let manifest: Self =
+            toml::from_str(&content).map_err(|e| format!("Invalid {}: {e}", path.display()))?;
+        if manifest.extensions.is_empty() {
+            return Err(format!("{} lists no extensions", path.display()));
+        }
+        if manifest.nodes.is_empty() {
+            return Err(format!("{} maps no node kinds", path.display()));
+        }
+        Ok(manifest)
+    }
+
+    fn symbol(&self) -> String {
+        self.symbol
+            .clone()
+            .unwrap_or_else(|| format!("tree_sitter_{}", self.language.replace('-', "_")))
+    }
+}
+
+/// The plugins directory: `$CODEGRAPH_PARSERS_DIR`, or else
+/// `~/.codegraph/parsers`.
+pub fn plugins_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os(PLUGINS_DIR_ENV) {
+        return Some(PathBuf::from(dir));
+    }
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+    Some(PathBuf::from(home).join(".codegraph").join("parsers"))
+}
+
+/// Load every plugin under `dir`, in name order. Plugins that fail to load
+/// are logged and skipped.
+pub fn load_plugins(dir: &Path, config: &ParserConfig) -> Vec<PluginParser> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.join(PLUGIN_MANIFEST).is_file())
+        .collect();
+    dirs.sort();
+
+    let mut plugins = Vec::new();
+    for dir in dirs {
+        match PluginParser::load(&dir, config.clone()) {
+            Ok(plugin) => {
+                tracing::info!(
+                    "Loaded parser plugin '{}' for {:?}",
+                    plugin.language,
+                    plugin.extensions
+                );
+                plugins.push(plugin);
+            }
+            Err(e) => tracing::warn!("Skipping parser plugin {}: {}", dir.display(), e),
+        }
+    }
+    plugins
+}
+
+/// A parser for a language whose tree-sitter grammar is loaded at runtime.
+pub struct PluginParser {
+    /// Leaked once per loaded plugin, as the parser API hands out `&str`s
+    language: &'static str,
+    extensions: Vec<&'static str>,
+    grammar: tree_sitter::Language,
+    nodes: Vec<NodeMapping>,
+    config: ParserConfig,
+    metrics: Mutex<ParserMetrics>,
+    /// Keeps the grammar's code loaded for as long as `grammar` is used;
+    /// None for a grammar linked into the server
+    _library: Option<libloading::Library>,
+}
+
+impl PluginParser {
+    /// Load the plugin in `dir`.
+    pub fn load(dir: &Path, config: ParserConfig) -> Result<Self, String> {
+        let manifest = PluginManifest::load(dir)?;
+        let library_path = dir.join(&manifest.library);
+        let symbol = manifest.symbol();
+
+        // SAFETY: the manifest names a tree-sitter grammar, whose exported
+        // language function takes no arguments and returns a TSLanguage pointer
+        let (library, grammar) = unsafe {
+            let library = libloading::Library::new(&library_path)
+                .map_err(|e| format!("Failed to load {}: {e}", library_path.display()))?;
+            let language_fn: libloading::Symbol<unsafe extern "C" fn() -> tree_sitter::Language> =
+                library.get(symbol.as_bytes()).map_err(|e| {
+                    format!("No symbol {symbol} in {}: {e}", library_path.display())
+                })?;
+            let grammar = language_fn();
+            (library, grammar)
+        };
+
+        // Rejects grammars built for an incompatible tree-sitter version
+        tree_sitter::Parser::new()
+            .set_language(&grammar)
+            .map_err(|e| format!("Incompatible grammar {}: {e}", library_path.display()))?;
+
+        Ok(Self::with_grammar(manifest, grammar, config, Some(library)))
+    }
+
+    /// A parser mapping the syntax of `grammar` as `manifest` says.
+    /// `library` is the loaded code of the grammar, if it came from one.
+    fn with_grammar(
+        manifest: PluginManifest,
+        grammar: tree_sitter::Language,
+        config: ParserConfig,
+        library: Option<libloading::Library>,
+    ) -> Self {
+        Self {
+            language: Box::leak(manifest.language.into_boxed_str()),
+            extensions: manifest
+                .extensions
+                .into_iter()
+                .map(|ext| {
+                    &*Box::leak(format!(".{}", ext.trim_start_matches('.')).into_boxed_str())
+                })
+                .collect(),
+            grammar,
+            nodes: manifest.nodes,
+            config,
+            metrics: Mutex::new(ParserMetrics::default()),
+            _library: library,
+        }
+    }
+
+    /// Language name from the manifest.
+    pub fn language_name(&self) -> &'static str {
+        self.language
+    }
+
+    /// The plugin's tree-sitter grammar.
+    pub fn grammar(&self) -> &tree_sitter::Language {
+        &self.grammar
+    }
+
+    fn record(&self, succeeded: bool, started: Instant, entities: usize) {
+        if let Ok(mut metrics) = self.metrics.lock() {
+            metrics.files_attempted += 1;
+            if succeeded {
+                metrics.files_succeeded += 1;
+            } else {
+                metrics.files_failed += 1;
+            }
+            metrics.total_parse_time += started.elapsed();
+            metrics.total_entities += entities;
+        }
+    }
+
+    fn parse_tree(
+        &self,
+        source: &str,
+        path: &Path,
+        graph: &mut CodeGraph,
+    ) -> Result<FileInfo, ParserError> {
+        let started = Instant::now();
+        let mut parser = tree_sitter::Parser::new();
+        parser
+            .set_language(&self.grammar)
+            .map_err(|e| ParserError::ParseError(path.to_path_buf(), e.to_string()))?;
+        let Some(tree) = parser.parse(source, None) else {
+            return Err(ParserError::ParseError(
+                path.to_path_buf(),
+                "tree-sitter returned no tree".to_string(),
+            ));
+        };
+
+        let path_str = path.to_string_lossy().to_string();
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let line_count = source.lines().count();
+        let file_id = graph
+            .add_node(
+                NodeType::CodeFile,
+                PropertyMap::new()
+                    .with("name", file_name)
+                    .with("path", path_str.clone())
+                    .with("language", self.language)
+                    .with("line_count", PropertyValue::Int(line_count as i64)),
+            )
+            .map_err(|e| ParserError::ParseError(path.to_path_buf(), e.to_string()))?;
+
+        let mut info = FileInfo {
+            file_path: path.to_path_buf(),
+            file_id,
+            functions: vec![],
+            classes: vec![],
+            traits: vec![],
+            imports: vec![],
+            parse_time: std::time::Duration::ZERO,
+            line_count,
+            byte_count: source.len(),
+        };
+
+        // Depth-first walk, tracking the nearest enclosing mapped node
+        let mut cursor = tree.walk();
+        let mut parents: Vec<(usize, NodeId)> = vec![(usize::MAX, file_id)];
+        loop {
+            let node = cursor.node();
+            if let Some(mapping) = self.nodes.iter().find(|m| m.kind == node.kind()) {
+                if let Some(id) =
+                    self.add_mapped_node(graph, &mut info, mapping, node, source, &path_str)
+                {
+                    let parent = parents.last().map_or(file_id, |&(_, id)| id);
+                    let edge_type = if mapping.node_type == PluginNodeType::Import {
+                        EdgeType::Imports
+                    } else {
+                        EdgeType::Contains
+                    };
+                    let _ = graph.add_edge(parent, id, edge_type, PropertyMap::new());
+                    parents.push((node.id(), id));
+                }
+            }
+
+            if cursor.goto_first_child() {
+                continue;
+            }
+            loop {
+                if parents
+                    .last()
+                    .is_some_and(|&(node_id, _)| node_id == cursor.node().id())
+                {
+                    parents.pop();
+                }
+                if cursor.goto_next_sibling() {
+                    break;
+                }
+                if !cursor.goto_parent() {
+                    info.parse_time = started.elapsed();
+                    let entities = info.functions.len() + info.classes.len() + info.traits.len();
+                    self.record(true, started, entities);
+                    return Ok(info);
+                }
+            }
+        }
+    }
+
+    fn add_mapped_node(
+        &self,
+        graph: &mut CodeGraph,
+        info: &mut FileInfo,
+        mapping: &NodeMapping,
+        node: tree_sitter::Node,
+        source: &str,
+        path: &str,
+    ) -> Option<NodeId> {
+        let text = |n: tree_sitter::Node| source.get(n.byte_range()).unwrap_or_default();
+        let name = match node.child_by_field_name(&mapping.name_field) {
+            Some(name_node) => text(name_node).to_string(),
+            None if mapping.node_type == PluginNodeType::Import => text(node)
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_string(),
+            None => return None,
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let start = node.start_position();
+        let end = node.end_position();
+        let props = PropertyMap::new()
+            .with("name", name)
+            .with("path", path.to_string())
+            .with("language", self.language)
+            .with("line_start", PropertyValue::Int(start.row as i64 + 1))
+            .with("line_end", PropertyValue::Int(end.row as i64 + 1))
+            .with("col_start", PropertyValue::Int(start.column as i64))
+            .with("col_end", PropertyValue::Int(end.column as i64));
+        let id = graph.add_node(mapping.node_type.node_type(), props).ok()?;
+        match mapping.node_type {
+            PluginNodeType::Function => info.functions.push(id),
+            PluginNodeType::Class => info.classes.push(id),
+            PluginNodeType::Interface | PluginNodeType::Trait => info.traits.push(id),
+            PluginNodeType::Import => info.imports.push(id),
+            _ => {}
+        }
+        Some(id)
+    }
+}
+
+impl CodeParser for PluginParser {
+    fn language(&self) -> &str {
+        self.language
+    }
+
+    fn file_extensions(&self) -> &[&str] {
+        &self.extensions
+    }
+
+    fn can_parse(&self, path: &Path) -> bool {
+        path.extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .is_some_and(|ext| self.extensions.contains(&ext.as_str()))
+    }
+
+    fn parse_file(&self, path: &Path, graph: &mut CodeGraph) -> Result<FileInfo, ParserError> {
+        let source = std::fs::read_to_string(path)
+            .map_err(|e| ParserError::IoError(path.to_path_buf(), e))?;
+        self.parse_source(&source, path, graph)
+    }
+
+    fn parse_source(
+        &self,
+        source: &str,
+        file_path: &Path,
+        graph: &mut CodeGraph,
+    ) -> Result<FileInfo, ParserError> {
+        let started = Instant::now();
+        self.parse_tree(source, file_path, graph).inspect_err(|_| {
+            self.record(false, started, 0);
+        })
+    }
+
+    fn config(&self) -> &ParserConfig {
+        &self.config
+    }
+
+    fn metrics(&self) -> ParserMetrics {
+        self.metrics
+            .lock()
+            .map(|metrics| metrics.clone())
+            .unwrap_or_default()
+    }
+
+    fn reset_metrics(&mut self) {
+        if let Ok(metrics) = self.metrics.get_mut() {
+            *metrics = ParserMetrics::default();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plugin_dir(root: &Path, name: &str, manifest: &str) -> PathBuf {
+        let dir = root.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(PLUGIN_MANIFEST), manifest).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_manifest_defaults() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = plugin_dir(
+            root.path(),
+            "zig",
+            r#"
+language = "zig"
+extensions = [".zig"]
+library = "libtree-sitter-zig.so"
+
+[[nodes]]
+kind = "function_declaration"
+node_type = "function"
+
+[[nodes]]
+kind = "import_declaration"
+node_type = "import"
+name_field = "path"
+"#,
+        );
+        let manifest = PluginManifest::load(&dir).unwrap();
+        assert_eq!(manifest.symbol(), "tree_sitter_zig");
+        assert_eq!(manifest.nodes[0].name_field, "name");
+        assert_eq!(manifest.nodes[0].node_type, PluginNodeType::Function);
+        assert_eq!(manifest.nodes[1].node_type.node_type(), NodeType::Import);
+    }
+
+    #[test]
+    fn test_manifest_rejects_incomplete_mappings() {
+        let root = tempfile::tempdir().unwrap();
+        let no_nodes = plugin_dir(
+            root.path(),
+            "a",
+            "language = \"a\"\nextensions = [\"a\"]\nlibrary = \"liba.so\"\n",
+        );
+        assert!(PluginManifest::load(&no_nodes).is_err());
+
+        let unknown_type = plugin_dir(
+            root.path(),
+            "b",
+            "language = \"b\"\nextensions = [\"b\"]\nlibrary = \"libb.so\"\n\
+             [[nodes]]\nkind = \"x\"\nnode_type = \"widget\"\n",
+        );
+        assert!(PluginManifest::load(&unknown_type).is_err());
+    }
+
+    #[test]
+    fn test_parse_with_grammar() {
+        let manifest: PluginManifest = toml::from_str(
+            r#"
+language = "c"
+extensions = ["c"]
+library = "libtree-sitter-c.so"
+
+[[nodes]]
+kind = "preproc_include"
+node_type = "import"
+
+[[nodes]]
+kind = "struct_specifier"
+node_type = "class"
+
+[[nodes]]
+kind = "function_declarator"
+node_type = "function"
+name_field = "declarator"
+"#,
+        )
+        .unwrap();
+        let plugin = PluginParser::with_grammar(
+            manifest,
+            tree_sitter_c::language(),
+            ParserConfig::default(),
+            None,
+        );
+        let source = "#include <stdio.h>\n\nstruct outer {\n    struct inner {\n        int x;\n    } in;\n};\n\nint main(void) {\n    return 0;\n}\n";
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let info = plugin
+            .parse_source(source, Path::new("/ws/main.c"), &mut graph)
+            .unwrap();
+
+        let node = |name: &str| {
+            graph
+                .query()
+                .property("name", name.to_string())
+                .execute()
+                .unwrap()
+                .into_iter()
+                .next()
+                .unwrap_or_else(|| panic!("no node named {name}"))
+        };
+        let edge = |source: NodeId, target: NodeId| {
+            graph
+                .get_edges_between(source, target)
+                .unwrap()
+                .into_iter()
+                .map(|id| graph.get_edge(id).unwrap().edge_type)
+                .collect::<Vec<_>>()
+        };
+
+        // Imports without a name field are named by their first line
+        let include = node("#include <stdio.h>");
+        assert_eq!(info.imports, vec![include]);
+        assert_eq!(edge(info.file_id, include), vec![EdgeType::Imports]);
+
+        // Nested mapped nodes are contained in the nearest enclosing one
+        let (outer, inner, main) = (node("outer"), node("inner"), node("main"));
+        assert_eq!(info.classes, vec![outer, inner]);
+        assert_eq!(edge(info.file_id, outer), vec![EdgeType::Contains]);
+        assert_eq!(edge(outer, inner), vec![EdgeType::Contains]);
+        assert!(edge(info.file_id, inner).is_empty());
+
+        // Once `outer` ends, the file contains nodes again
+        assert_eq!(info.functions, vec![main]);
+        assert_eq!(edge(info.file_id, main), vec![EdgeType::Contains]);
+        assert!(edge(outer, main).is_empty());
+        let main_node = graph.get_node(main).unwrap();
+        assert_eq!(main_node.properties.get_int("line_start"), Some(9));
+        assert_eq!(main_node.properties.get_int("col_start"), Some(4));
+        assert_eq!(main_node.node_type, NodeType::Function);
+
+        assert_eq!(plugin.metrics().files_succeeded, 1);
+    }
+
+    #[test]
+    fn test_load_plugins_skips_broken_plugins() {
+        let root = tempfile::tempdir().unwrap();
+        plugin_dir(
+            root.path(),
+            "missing-library",
+            "language = \"m\"\nextensions = [\"m\"]\nlibrary = \"libnone.so\"\n\
+             [[nodes]]\nkind = \"f\"\nnode_type = \"function\"\n",
+        );
+        std::fs::create_dir_all(root.path().join("no-manifest")).unwrap();
+
+        assert!(load_plugins(root.path(), &ParserConfig::default()).is_empty());
+        assert!(load_plugins(&root.path().join("absent"), &ParserConfig::default()).is_empty());
+    }
+}
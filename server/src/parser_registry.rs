@@ -1,5 +1,10 @@
 //! Parser Registry - Manages all language parsers implementing the CodeParser trait.
+//!
+//! Besides the built-in parsers, the registry holds any parser plugins loaded
+//! from the plugins directory (see [`crate::parser_plugins`]). Built-in parsers
+//! take precedence for extensions both claim.
 
+use crate::parser_plugins::{load_plugins, plugins_dir, PluginParser};
 use codegraph::CodeGraph;
 use codegraph_c::CParser;
 use codegraph_cobol::CobolParser;
@@ -39,6 +44,9 @@ pub struct ParserRegistry {
     tcl: Arc<TclParser>,
     typescript: Arc<TypeScriptParser>,
     verilog: Arc<VerilogParser>,
+    /// Parsers loaded at runtime, in load order
+    plugins: Vec<Arc<PluginParser>>,
+    config: ParserConfig,
 }
 
 impl ParserRegistry {
@@ -65,10 +73,34 @@ impl ParserRegistry {
             swift: Arc::new(SwiftParser::with_config(config.clone())),
             tcl: Arc::new(TclParser::with_config(config.clone())),
             typescript: Arc::new(TypeScriptParser::with_config(config.clone())),
-            verilog: Arc::new(VerilogParser::with_config(config)),
+            verilog: Arc::new(VerilogParser::with_config(config.clone())),
+            plugins: Vec::new(),
+            config,
         }
     }
 
+    /// Create a registry with the built-in parsers and the plugins installed
+    /// in the plugins directory.
+    pub fn with_installed_plugins() -> Self {
+        let registry = Self::new();
+        match plugins_dir() {
+            Some(dir) => registry.with_plugins(&dir),
+            None => registry,
+        }
+    }
+
+    /// Add the parser plugins found in `dir`.
+    pub fn with_plugins(mut self, dir: &Path) -> Self {
+        self.plugins
+            .extend(load_plugins(dir, &self.config).into_iter().map(Arc::new));
+        self
+    }
+
+    /// Languages provided by plugins.
+    pub fn plugin_languages(&self) -> Vec<&'static str> {
+        self.plugins.iter().map(|p| p.language_name()).collect()
+    }
+
     /// Get parser by language identifier.
     pub fn get_parser(&self, language: &str) -> Option<Arc<dyn CodeParser>> {
         match language.to_lowercase().as_str() {
@@ -90,7 +122,11 @@ impl ParserRegistry {
                 Some(self.typescript.clone())
             }
             "verilog" | "systemverilog" => Some(self.verilog.clone()),
-            _ => None,
+            other => self
+                .plugins
+                .iter()
+                .find(|p| p.language_name().eq_ignore_ascii_case(other))
+                .map(|p| p.clone() as Arc<dyn CodeParser>),
         }
     }
 
@@ -119,7 +155,12 @@ impl ParserRegistry {
             self.verilog.clone(),
         ];
 
-        parsers.into_iter().find(|p| p.can_parse(path))
+        parsers.into_iter().find(|p| p.can_parse(path)).or_else(|| {
+            self.plugins
+                .iter()
+                .find(|p| p.can_parse(path))
+                .map(|p| p.clone() as Arc<dyn CodeParser>)
+        })
     }
 
     /// Get all supported file extensions.
@@ -141,12 +182,15 @@ impl ParserRegistry {
         extensions.extend(self.tcl.file_extensions().iter().copied());
         extensions.extend(self.typescript.file_extensions().iter().copied());
         extensions.extend(self.verilog.file_extensions().iter().copied());
+        for plugin in &self.plugins {
+            extensions.extend(plugin.file_extensions().iter().copied());
+        }
         extensions
     }
 
     /// Get metrics from all parsers.
     pub fn all_metrics(&self) -> Vec<(&str, ParserMetrics)> {
-        vec![
+        let mut metrics = vec![
             ("c", self.c.metrics()),
             ("cobol", self.cobol.metrics()),
             ("cpp", self.cpp.metrics()),
@@ -163,7 +207,11 @@ impl ParserRegistry {
             ("tcl", self.tcl.metrics()),
             ("typescript", self.typescript.metrics()),
             ("verilog", self.verilog.metrics()),
-        ]
+        ];
+        for plugin in &self.plugins {
+            metrics.push((plugin.language_name(), plugin.metrics()));
+        }
+        metrics
     }
 
     /// Check if a file path is supported by any parser.
@@ -249,8 +297,64 @@ impl ParserRegistry {
         } else if self.verilog.can_parse(path) {
             Some("verilog")
         } else {
-            None
+            self.plugins
+                .iter()
+                .find(|p| p.can_parse(path))
+                .map(|p| p.language_name())
+        }
+    }
+
+    /// Tree-sitter grammar for a file path, used to keep syntax trees of open
+    /// documents. None for languages whose grammar is not built in.
+    pub fn grammar_for_path(&self, path: &Path) -> Option<tree_sitter::Language> {
+        let grammar = match self.language_for_path(path)? {
+            "c" => tree_sitter_c::language(),
+            "cpp" => tree_sitter_cpp::language(),
+            "csharp" => tree_sitter_c_sharp::language(),
+            "go" => tree_sitter_go::language(),
+            "java" => tree_sitter_java::language(),
+            "kotlin" => tree_sitter_kotlin::language(),
+            "php" => tree_sitter_php::language_php(),
+            "python" => tree_sitter_python::language(),
+            "ruby" => tree_sitter_ruby::language(),
+            "rust" => tree_sitter_rust::language(),
+            "swift" => tree_sitter_swift::language(),
+            "typescript" | "javascript" => match path.extension().and_then(|e| e.to_str()) {
+                Some("tsx" | "jsx") => tree_sitter_typescript::language_tsx(),
+                _ => tree_sitter_typescript::language_typescript(),
+            },
+            "cobol" | "fortran" | "tcl" | "verilog" => return None,
+            _ => {
+                return self
+                    .plugins
+                    .iter()
+                    .find(|p| p.can_parse(path))
+                    .map(|p| p.grammar().clone())
+            }
+        };
+        Some(grammar)
+    }
+
+    /// Fill in node metadata the parsers do not record themselves.
+    ///
+    /// Called after every successful parse with the file's source: records
+    /// the source's content hash and decorators/attributes, computes
+    /// complexity metrics from the syntax tree, assigns monikers to the
+    /// new nodes and adds nodes for its TODO comments.
+    pub fn enrich_parsed_file(
+        &self,
+        graph: &mut CodeGraph,
+        file_info: &FileInfo,
+        path: &Path,
+        source: &str,
+    ) {
+        crate::graph_snapshot::record_content_hash(graph, file_info, source);
+        crate::annotations::record_annotations(graph, file_info, source);
+        if let Some(grammar) = self.grammar_for_path(path) {
+            crate::domain::complexity::record_complexity(graph, file_info, &grammar, source);
         }
+        crate::domain::monikers::record_monikers(graph, file_info, path);
+        crate::ai_query::record_todos(graph, file_info, path, source);
     }
 }
 
@@ -294,6 +398,19 @@ mod tests {
         assert!(registry.get_parser("python").is_some());
     }
 
+    #[test]
+    fn test_registry_without_plugins_keeps_builtins() {
+        let dir = tempfile::tempdir().unwrap();
+        let registry = ParserRegistry::new().with_plugins(dir.path());
+        assert!(registry.plugin_languages().is_empty());
+        assert_eq!(
+            registry.supported_extensions().len(),
+            ParserRegistry::new().supported_extensions().len()
+        );
+        assert_eq!(registry.all_metrics().len(), 16);
+        assert!(registry.get_parser("zig").is_none());
+    }
+
     #[test]
     fn test_parser_registry_with_config() {
         let config = ParserConfig::default();
@@ -569,6 +686,25 @@ mod tests {
         assert_eq!(registry.language_for_path(&PathBuf::from("test.txt")), None);
     }
 
+    #[test]
+    fn test_grammar_for_path() {
+        let registry = ParserRegistry::new();
+        assert_eq!(
+            registry.grammar_for_path(&PathBuf::from("test.py")),
+            Some(tree_sitter_python::language())
+        );
+        assert_eq!(
+            registry.grammar_for_path(&PathBuf::from("test.hpp")),
+            Some(tree_sitter_cpp::language())
+        );
+        assert_eq!(
+            registry.grammar_for_path(&PathBuf::from("test.jsx")),
+            Some(tree_sitter_typescript::language_tsx())
+        );
+        assert_eq!(registry.grammar_for_path(&PathBuf::from("test.f90")), None);
+        assert_eq!(registry.grammar_for_path(&PathBuf::from("test.txt")), None);
+    }
+
     #[test]
     fn test_parse_source_unsupported() {
         let registry = ParserRegistry::new();
@@ -0,0 +1,224 @@
+//! Work-done progress (`$/progress`) for long-running operations.
+//!
+//! Indexing, reindexing and git mining report progress to the editor instead
+//! of only logging it. Reports are queued and sent in order by a background
+//! task, so callers can report from synchronous code without awaiting the
+//! client. When the client cannot show progress, handles are no-ops.
+//!
+//! An operation is cancelled both from the progress notification and, when
+//! it was started with the request's token, by `$/cancelRequest`.
+
+use crate::cancellation::CancellationToken;
+use crate::git_mining::MiningProgress;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tower_lsp::lsp_types::notification::Progress;
+use tower_lsp::lsp_types::request::WorkDoneProgressCreate;
+use tower_lsp::lsp_types::{
+    NumberOrString, ProgressParams, ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin,
+    WorkDoneProgressCreateParams, WorkDoneProgressEnd, WorkDoneProgressReport,
+};
+use tower_lsp::Client;
+
+/// Creates progress handles and routes cancellation requests to them.
+#[derive(Debug, Default)]
+pub struct ProgressTracker {
+    /// Whether the client advertised `window.workDoneProgress`.
+    supported: AtomicBool,
+    next_token: AtomicU64,
+    /// Cancellation tokens of running operations, by progress token.
+    cancellations: Arc<DashMap<String, CancellationToken>>,
+}
+
+impl ProgressTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_supported(&self, supported: bool) {
+        self.supported.store(supported, Ordering::Relaxed);
+    }
+
+    /// Start reporting progress for an operation titled `title`.
+    pub async fn begin(&self, client: &Client, title: &str, cancellable: bool) -> WorkProgress {
+        self.start(client, title, cancellable, CancellationToken::new())
+            .await
+    }
+
+    /// Start reporting progress for a cancellable operation that also stops
+    /// when `cancelled` is cancelled, e.g. by the request that started it.
+    pub async fn begin_cancellable(
+        &self,
+        client: &Client,
+        title: &str,
+        cancelled: CancellationToken,
+    ) -> WorkProgress {
+        self.start(client, title, true, cancelled).await
+    }
+
+    async fn start(
+        &self,
+        client: &Client,
+        title: &str,
+        cancellable: bool,
+        cancelled: CancellationToken,
+    ) -> WorkProgress {
+        if !self.supported.load(Ordering::Relaxed) {
+            return WorkProgress::disabled(cancelled);
+        }
+
+        let id = self.next_token.fetch_add(1, Ordering::Relaxed);
+        let key = format!("codegraph/{id}");
+        let token = NumberOrString::String(key.clone());
+        if let Err(e) = client
+            .send_request::<WorkDoneProgressCreate>(WorkDoneProgressCreateParams {
+                token: token.clone(),
+            })
+            .await
+        {
+            tracing::debug!("Client refused work done progress: {}", e);
+            return WorkProgress::disabled(cancelled);
+        }
+
+        let (sender, mut receiver) = mpsc::unbounded_channel::<WorkDoneProgress>();
+        let client = client.clone();
+        tokio::spawn(async move {
+            while let Some(value) = receiver.recv().await {
+                client
+                    .send_notification::<Progress>(ProgressParams {
+                        token: token.clone(),
+                        value: ProgressParamsValue::WorkDone(value),
+                    })
+                    .await;
+            }
+        });
+
+        let _ = sender.send(WorkDoneProgress::Begin(WorkDoneProgressBegin {
+            title: title.to_string(),
+            cancellable: Some(cancellable),
+            message: None,
+            percentage: Some(0),
+        }));
+        self.cancellations.insert(key.clone(), cancelled.clone());
+
+        WorkProgress {
+            sender: Some(sender),
+            cancelled,
+            registration: Some((key, Arc::clone(&self.cancellations))),
+            end_message: None,
+        }
+    }
+
+    /// Handle `window/workDoneProgress/cancel`.
+    pub fn cancel(&self, token: &NumberOrString) {
+        let key = match token {
+            NumberOrString::String(s) => s.clone(),
+            NumberOrString::Number(n) => n.to_string(),
+        };
+        if let Some(cancelled) = self.cancellations.get(&key) {
+            cancelled.cancel();
+        }
+    }
+}
+
+/// Progress of one running operation. Ends when dropped.
+pub struct WorkProgress {
+    sender: Option<mpsc::UnboundedSender<WorkDoneProgress>>,
+    cancelled: CancellationToken,
+    registration: Option<(String, Arc<DashMap<String, CancellationToken>>)>,
+    end_message: Option<String>,
+}
+
+impl WorkProgress {
+    /// A handle that reports nothing and is never cancelled.
+    fn disabled(cancelled: CancellationToken) -> Self {
+        Self {
+            sender: None,
+            cancelled,
+            registration: None,
+            end_message: None,
+        }
+    }
+
+    /// Report a status message and, optionally, a percentage (0-100).
+    pub fn report(&self, message: impl Into<String>, percentage: Option<u32>) {
+        if let Some(sender) = &self.sender {
+            let _ = sender.send(WorkDoneProgress::Report(WorkDoneProgressReport {
+                cancellable: None,
+                message: Some(message.into()),
+                percentage: percentage.map(|p| p.min(100)),
+            }));
+        }
+    }
+
+    /// Whether the operation was cancelled from the editor.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.is_cancelled()
+    }
+
+    /// End the progress with a final message.
+    pub fn finish(mut self, message: impl Into<String>) {
+        self.end_message = Some(message.into());
+    }
+}
+
+impl Drop for WorkProgress {
+    fn drop(&mut self) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send(WorkDoneProgress::End(WorkDoneProgressEnd {
+                message: self.end_message.take(),
+            }));
+        }
+        if let Some((key, cancellations)) = self.registration.take() {
+            cancellations.remove(&key);
+        }
+    }
+}
+
+impl MiningProgress for WorkProgress {
+    fn commit_processed(&self, processed: usize, total: usize) {
+        let percentage = (processed * 100).checked_div(total).unwrap_or(100);
+        self.report(
+            format!("{processed}/{total} commits"),
+            Some(percentage as u32),
+        );
+    }
+
+    fn is_cancelled(&self) -> bool {
+        WorkProgress::is_cancelled(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_reaches_registered_operation() {
+        let tracker = ProgressTracker::new();
+        let cancelled = CancellationToken::new();
+        tracker
+            .cancellations
+            .insert("codegraph/7".to_string(), cancelled.clone());
+        let progress = WorkProgress {
+            sender: None,
+            cancelled,
+            registration: Some((
+                "codegraph/7".to_string(),
+                Arc::clone(&tracker.cancellations),
+            )),
+            end_message: None,
+        };
+
+        tracker.cancel(&NumberOrString::String("codegraph/8".to_string()));
+        assert!(!progress.is_cancelled());
+        tracker.cancel(&NumberOrString::String("codegraph/7".to_string()));
+        assert!(progress.is_cancelled());
+
+        // Ending the operation unregisters it
+        progress.finish("done");
+        assert!(tracker.cancellations.is_empty());
+    }
+}
@@ -1,17 +1,31 @@
 //! Runtime dependency detection for HTTP routes and client calls.
 //!
 //! Post-processes the code graph after indexing to:
-//! 1. Detect HTTP route handlers from function decorators/attributes
+//! 1. Detect HTTP route handlers from function decorators/attributes, and Go
+//!    handlers from their `net/http` registrations
 //! 2. Detect HTTP client calls from function call patterns
 //! 3. Match client calls to route handlers via `RuntimeCalls` edges
 //!
+//! [`link_runtime_calls`] runs all three steps. The synthetic `RuntimeCalls`
+//! edges are indexed like `Calls` edges, so callers, callees and traversals
+//! cross the client/server boundary in full-stack workspaces.
+//!
 //! ## Supported Frameworks
 //!
 //! **Route handlers (decorator-based):**
 //! - Python Flask/FastAPI: `@app.route("/path")`, `@app.get("/path")`, `@router.post("/path")`
 //! - Python Django REST: `@api_view(["GET"])` (with urlpatterns)
 //! - NestJS: `@Get("/path")`, `@Post("/path")`, `@Controller("/api")`
-//! - Spring Boot: `@GetMapping("/path")`, `@RequestMapping("/path")`
+//! - Spring Boot: `@GetMapping("/path")`, `@RequestMapping("/path")`,
+//!   `@RequestMapping(value = "/path", method = RequestMethod.POST)`
+//!
+//! Method routes are prefixed with the route of their controller class:
+//! Spring's class-level `@RequestMapping("/api")` or NestJS's
+//! `@Controller("/api")`.
+//!
+//! **Route handlers (registration-based):**
+//! - Go net/http: `http.HandleFunc("/path", handler)`,
+//!   `mux.Handle("GET /path/{id}", http.HandlerFunc(handler))`
 //!
 //! **HTTP client calls (name-based):**
 //! - JS/TS: `fetch()`, `axios.get()`, `http.get()`
@@ -19,8 +33,12 @@
 //! - Go: `http.Get()`, `http.NewRequest()`
 //! - Rust: `reqwest::get()`, `Client::get()`
 
+use crate::domain::node_props;
 use codegraph::{CodeGraph, EdgeType, NodeId, NodeType, PropertyMap};
-use std::collections::HashMap;
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::OnceLock;
 
 /// HTTP methods recognized by route detection.
 #[allow(dead_code)] // Used as reference; matching done inline
@@ -78,6 +96,11 @@ const HTTP_CLIENT_FUNCTIONS: &[&str] = &[
     "http.NewRequest",
     // Rust
     "reqwest::get",
+    "Client::get",
+    "Client::post",
+    "Client::put",
+    "Client::patch",
+    "Client::delete",
     // Axios
     "axios.get",
     "axios.post",
@@ -92,12 +115,39 @@ const HTTP_CLIENT_FUNCTIONS: &[&str] = &[
     "http.delete",
 ];
 
-/// Scan all Function nodes for route handler decorators and set
-/// `route` and `http_method` properties where detected.
+/// Counts reported by [`link_runtime_calls`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuntimeLinkStats {
+    pub routes: usize,
+    pub clients: usize,
+    pub edges: usize,
+}
+
+/// Detect route handlers and HTTP client calls, then link them with
+/// `RuntimeCalls` edges. Safe to re-run after incremental updates: edges that
+/// already exist are not duplicated.
+pub fn link_runtime_calls(graph: &mut CodeGraph) -> RuntimeLinkStats {
+    let routes = detect_route_handlers(graph);
+    let clients = detect_http_client_calls(graph);
+    let edges = if routes > 0 || clients > 0 {
+        create_runtime_call_edges(graph)
+    } else {
+        0
+    };
+    RuntimeLinkStats {
+        routes,
+        clients,
+        edges,
+    }
+}
+
+/// Scan all Function nodes for route handler decorators, and Go functions
+/// for `net/http` handler registrations, and set `route` and `http_method`
+/// properties on the handlers detected.
 ///
 /// Returns the number of routes detected.
 pub fn detect_route_handlers(graph: &mut CodeGraph) -> usize {
-    let mut updates: Vec<(NodeId, String, String)> = Vec::new();
+    let mut updates = detect_go_route_registrations(graph);
 
     for (node_id, node) in graph.iter_nodes() {
         if node.node_type != NodeType::Function {
@@ -118,6 +168,16 @@ pub fn detect_route_handlers(graph: &mut CodeGraph) -> usize {
         }
     }
 
+    // Method routes are relative to their controller's route
+    let prefixes = controller_prefixes(graph);
+    if !prefixes.is_empty() {
+        for (node_id, route, _) in &mut updates {
+            if let Some(class) = enclosing_class(graph, *node_id, &prefixes) {
+                *route = join_route(&prefixes[&class], route);
+            }
+        }
+    }
+
     let count = updates.len();
 
     for (node_id, route, method) in updates {
@@ -178,7 +238,11 @@ fn parse_route_decorator(decorator: &str) -> Option<(String, String)> {
                 _ => "ANY",
             }
         } else {
-            "ANY"
+            // Spring: @RequestMapping(value = "/path", method = RequestMethod.POST)
+            ["GET", "POST", "PUT", "PATCH", "DELETE", "HEAD", "OPTIONS"]
+                .into_iter()
+                .find(|m| after_pattern.contains(&format!("RequestMethod.{m}")))
+                .unwrap_or("ANY")
         };
 
         return Some((route, method.to_string()));
@@ -187,6 +251,193 @@ fn parse_route_decorator(decorator: &str) -> Option<(String, String)> {
     None
 }
 
+/// Route prefixes of controller classes, from Spring's class-level
+/// `@RequestMapping("/api")` or, in TypeScript, NestJS's `@Controller("/api")`.
+/// Spring's own `@Controller("name")` names the bean, not a route.
+fn controller_prefixes(graph: &CodeGraph) -> HashMap<NodeId, String> {
+    let mut prefixes = HashMap::new();
+    for (node_id, node) in graph.iter_nodes() {
+        if node.node_type != NodeType::Class {
+            continue;
+        }
+        let Some(attrs) = node.properties.get_string_list_compat("attributes") else {
+            continue;
+        };
+        let is_typescript = Path::new(node_props::path(node))
+            .extension()
+            .is_some_and(|ext| ext == "ts" || ext == "tsx");
+        let prefix_decorator = if is_typescript {
+            "Controller"
+        } else {
+            "RequestMapping"
+        };
+        let prefix = attrs.iter().find_map(|attr| {
+            let dec = attr.trim_start_matches('@');
+            let args = dec.strip_prefix(prefix_decorator)?;
+            // The whole name: not @ControllerAdvice or @RequestMappings
+            if !args.trim_start().starts_with('(') {
+                return None;
+            }
+            extract_first_string_arg(args)
+        });
+        if let Some(prefix) = prefix {
+            prefixes.insert(node_id, prefix);
+        }
+    }
+    prefixes
+}
+
+/// The innermost class of `classes` declaring `function`: linked to it by a
+/// `Contains` edge, or enclosing its first line in the same file.
+fn enclosing_class(
+    graph: &CodeGraph,
+    function: NodeId,
+    classes: &HashMap<NodeId, String>,
+) -> Option<NodeId> {
+    let node = graph.get_node(function).ok()?;
+    let path = node_props::path(node);
+    let line = node_props::line_start(node);
+    classes
+        .keys()
+        .copied()
+        .filter_map(|class_id| {
+            let class = graph.get_node(class_id).ok()?;
+            let contains = graph
+                .get_edges_between(class_id, function)
+                .unwrap_or_default()
+                .into_iter()
+                .any(|e| {
+                    graph
+                        .get_edge(e)
+                        .is_ok_and(|e| e.edge_type == EdgeType::Contains)
+                });
+            let encloses = !path.is_empty()
+                && node_props::path(class) == path
+                && node_props::line_start(class) <= line
+                && line <= node_props::line_end(class);
+            (contains || encloses).then(|| (node_props::line_start(class), class_id))
+        })
+        .max()
+        .map(|(_, class_id)| class_id)
+}
+
+/// Join a controller route and a method route with a single `/`.
+fn join_route(prefix: &str, route: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    let route = route.trim_start_matches('/');
+    match (prefix.is_empty(), route.is_empty()) {
+        (true, _) => format!("/{route}"),
+        (false, true) => format!("/{prefix}"),
+        (false, false) => format!("/{prefix}/{route}"),
+    }
+}
+
+/// Find Go handlers registered with `net/http`: `HandleFunc` and `Handle`
+/// calls on `http` or a `ServeMux`. Only functions calling one of them are
+/// read. Handlers are looked up by name, in the registering function's
+/// package (directory) first.
+fn detect_go_route_registrations(graph: &CodeGraph) -> Vec<(NodeId, String, String)> {
+    let is_go = |node: &codegraph::Node| node_props::path(node).ends_with(".go");
+    let registers = |callee: &str| callee.ends_with("HandleFunc") || callee.ends_with("Handle");
+
+    let mut registering: HashSet<NodeId> = HashSet::new();
+    for (_edge_id, edge) in graph.iter_edges() {
+        if edge.edge_type == EdgeType::Calls
+            && graph.get_node(edge.target_id).is_ok_and(|target| {
+                registers(target.properties.get_string("name").unwrap_or_default())
+            })
+        {
+            registering.insert(edge.source_id);
+        }
+    }
+    let mut go_functions: HashMap<&str, Vec<(NodeId, &str)>> = HashMap::new();
+    for (node_id, node) in graph.iter_nodes() {
+        if node.node_type != NodeType::Function || !is_go(node) {
+            continue;
+        }
+        let name = node_props::name(node);
+        let dir = Path::new(node_props::path(node))
+            .parent()
+            .and_then(|dir| dir.to_str())
+            .unwrap_or_default();
+        go_functions.entry(name).or_default().push((node_id, dir));
+        let calls = node
+            .properties
+            .get_string_list_compat("unresolved_calls")
+            .unwrap_or_default();
+        if calls.iter().any(|callee| registers(callee)) {
+            registering.insert(node_id);
+        }
+    }
+
+    let mut updates = Vec::new();
+    let mut seen: HashSet<NodeId> = HashSet::new();
+    for caller_id in registering {
+        let Ok(caller) = graph.get_node(caller_id) else {
+            continue;
+        };
+        if !is_go(caller) {
+            continue;
+        }
+        let Some(source) = crate::domain::source_code::get_symbol_source(graph, caller_id) else {
+            continue;
+        };
+        let caller_dir = Path::new(node_props::path(caller))
+            .parent()
+            .and_then(|dir| dir.to_str())
+            .unwrap_or_default();
+        for (route, method, handler) in parse_go_route_registrations(&source) {
+            let Some(candidates) = go_functions.get(handler.as_str()) else {
+                continue;
+            };
+            let handler_id = match candidates.iter().find(|(_, dir)| *dir == caller_dir) {
+                Some(&(id, _)) => id,
+                None if candidates.len() == 1 => candidates[0].0,
+                None => continue,
+            };
+            if seen.insert(handler_id) {
+                updates.push((handler_id, route, method));
+            }
+        }
+    }
+    updates
+}
+
+/// Parse Go `net/http` registrations in a function body into
+/// `(route, method, handler name)`. Go 1.22 patterns may start with a method
+/// (`"GET /users/{id}"`) and a host (`"api.example.com/users"`); without a
+/// method the route matches any. Method values (`h.listUsers`) and
+/// package-qualified handlers (`api.ListUsers`) resolve to the last name.
+fn parse_go_route_registrations(source: &str) -> Vec<(String, String, String)> {
+    static REGISTRATION: OnceLock<Regex> = OnceLock::new();
+    let registration = REGISTRATION.get_or_init(|| {
+        Regex::new(
+            r#"\.Handle(?:Func)?\(\s*"([^"]+)"\s*,\s*(?:http\.HandlerFunc\(\s*)?([A-Za-z_][\w.]*)"#,
+        )
+        .expect("valid Go route registration regex")
+    });
+
+    let mut routes = Vec::new();
+    for cap in registration.captures_iter(source) {
+        let pattern = cap[1].trim();
+        let (method, path) = match pattern.split_once(char::is_whitespace) {
+            Some((method, path)) if method.chars().all(|c| c.is_ascii_uppercase()) => {
+                (method.to_string(), path.trim())
+            }
+            _ => ("ANY".to_string(), pattern),
+        };
+        let Some(slash) = path.find('/') else {
+            continue;
+        };
+        let handler = cap[2].rsplit('.').next().unwrap_or_default();
+        if handler.is_empty() {
+            continue;
+        }
+        routes.push((path[slash..].to_string(), method, handler.to_string()));
+    }
+    routes
+}
+
 /// Extract the first string argument from a decorator's argument list.
 ///
 /// Input: `("/users/{id}", response_model=User)` → `"/users/{id}"`
@@ -274,9 +525,22 @@ pub fn detect_http_client_calls(graph: &mut CodeGraph) -> usize {
         }
     }
 
-    let count = callers.len();
-
+    // A function that uses several methods may reach handlers of any of them
+    let mut methods: HashMap<NodeId, String> = HashMap::new();
     for (node_id, method) in callers {
+        methods
+            .entry(node_id)
+            .and_modify(|m| {
+                if *m != method {
+                    *m = "ANY".to_string();
+                }
+            })
+            .or_insert(method);
+    }
+
+    let count = methods.len();
+
+    for (node_id, method) in methods {
         let mut props = PropertyMap::new();
         props.insert("http_client_call", "true");
         props.insert("http_client_method", method.as_str());
@@ -313,15 +577,22 @@ fn extract_http_method_from_name(name: &str) -> String {
 /// project. Cross-project matching (T1-4) will scan other namespaces in the
 /// shared database.
 ///
+/// A client call only links to handlers whose HTTP method is compatible
+/// (either side may be `ANY`), and each caller/handler pair gets at most one
+/// edge, including across repeated runs.
+///
 /// Returns the number of RuntimeCalls edges created.
 pub fn create_runtime_call_edges(graph: &mut CodeGraph) -> usize {
-    // Collect all route handlers: route pattern → node_id
-    let mut route_handlers: HashMap<String, Vec<NodeId>> = HashMap::new();
+    // Collect all route handlers: normalized route pattern → (node_id, method)
+    let mut route_handlers: HashMap<String, Vec<(NodeId, String)>> = HashMap::new();
 
     for (node_id, node) in graph.iter_nodes() {
         if let Some(route) = node.properties.get_string("route") {
-            let route = route.to_string();
-            route_handlers.entry(route).or_default().push(node_id);
+            let method = node.properties.get_string("http_method").unwrap_or("ANY");
+            route_handlers
+                .entry(normalize_route(route))
+                .or_default()
+                .push((node_id, method.to_string()));
         }
     }
 
@@ -329,11 +600,15 @@ pub fn create_runtime_call_edges(graph: &mut CodeGraph) -> usize {
         return 0;
     }
 
-    // Collect all HTTP client callers
-    let mut client_callers: Vec<NodeId> = Vec::new();
+    // Collect all HTTP client callers with the method they use
+    let mut client_callers: Vec<(NodeId, String)> = Vec::new();
     for (node_id, node) in graph.iter_nodes() {
         if node.properties.get_string("http_client_call").is_some() {
-            client_callers.push(node_id);
+            let method = node
+                .properties
+                .get_string("http_client_method")
+                .unwrap_or("ANY");
+            client_callers.push((node_id, method.to_string()));
         }
     }
 
@@ -344,8 +619,10 @@ pub fn create_runtime_call_edges(graph: &mut CodeGraph) -> usize {
     // Extract URL paths from client callers by reading their source code
     // and scanning for string literals that look like URL paths
     let mut edges_to_add: Vec<(NodeId, NodeId, PropertyMap)> = Vec::new();
+    let mut linked: HashSet<(NodeId, NodeId)> = HashSet::new();
 
-    for &caller_id in &client_callers {
+    for (caller_id, client_method) in &client_callers {
+        let caller_id = *caller_id;
         let source = crate::domain::source_code::get_symbol_source(graph, caller_id);
         if let Some(source) = source {
             let urls = extract_url_paths_from_source(&source);
@@ -353,25 +630,31 @@ pub fn create_runtime_call_edges(graph: &mut CodeGraph) -> usize {
                 // Normalize: strip query params, trailing slash
                 let normalized = normalize_route(&url);
                 // Try exact match first, then pattern match
-                if let Some(handlers) = route_handlers.get(&normalized) {
-                    for &handler_id in handlers {
+                let matches: Vec<(&String, &Vec<(NodeId, String)>, &str)> =
+                    match route_handlers.get_key_value(&normalized) {
+                        Some((pattern, handlers)) => vec![(pattern, handlers, "exact")],
+                        // Pattern matching (e.g. "/users/123" matches "/users/{id}")
+                        None => route_handlers
+                            .iter()
+                            .filter(|(pattern, _)| route_pattern_matches(pattern, &normalized))
+                            .map(|(pattern, handlers)| (pattern, handlers, "pattern"))
+                            .collect(),
+                    };
+                for (pattern, handlers, match_type) in matches {
+                    for (handler_id, handler_method) in handlers {
+                        let handler_id = *handler_id;
+                        if handler_id == caller_id
+                            || !methods_compatible(client_method, handler_method)
+                            || has_runtime_call_edge(graph, caller_id, handler_id)
+                            || !linked.insert((caller_id, handler_id))
+                        {
+                            continue;
+                        }
                         let props = PropertyMap::new()
-                            .with("matched_route", normalized.as_str())
-                            .with("match_type", "exact");
+                            .with("matched_route", pattern.as_str())
+                            .with("match_type", match_type);
                         edges_to_add.push((caller_id, handler_id, props));
                     }
-                } else {
-                    // Try pattern matching (e.g. "/users/123" matches "/users/{id}")
-                    for (pattern, handler_ids) in &route_handlers {
-                        if route_pattern_matches(pattern, &normalized) {
-                            for &handler_id in handler_ids {
-                                let props = PropertyMap::new()
-                                    .with("matched_route", pattern.as_str())
-                                    .with("match_type", "pattern");
-                                edges_to_add.push((caller_id, handler_id, props));
-                            }
-                        }
-                    }
                 }
             }
         }
@@ -394,17 +677,34 @@ pub fn create_runtime_call_edges(graph: &mut CodeGraph) -> usize {
     count
 }
 
+fn methods_compatible(client: &str, handler: &str) -> bool {
+    client == "ANY" || handler == "ANY" || client.eq_ignore_ascii_case(handler)
+}
+
+fn has_runtime_call_edge(graph: &CodeGraph, from: NodeId, to: NodeId) -> bool {
+    graph
+        .get_edges_between(from, to)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|eid| graph.get_edge(eid).ok())
+        .any(|e| e.edge_type == EdgeType::RuntimeCalls)
+}
+
 /// Extract URL path strings from function source code.
 ///
 /// Scans for string literals that look like URL paths (start with "/").
 /// Handles: `fetch("/api/users")`, `requests.get("http://host/api/users")`,
 /// `axios.post('/items')`, template literals with paths.
+///
+/// Template literals may start with a base URL interpolation
+/// (`` `${API_URL}/users` ``), and interpolated segments become path
+/// parameters: `` `/users/${id}` `` → `/users/{id}`.
 fn extract_url_paths_from_source(source: &str) -> Vec<String> {
     let mut paths = Vec::new();
 
     // Match string literals that contain URL paths
     // Pattern: quoted string starting with "/" or containing "://"
-    for cap in regex::Regex::new(r#"["'`](/[a-zA-Z0-9_/{}\-\.]+)"#)
+    for cap in regex::Regex::new(r#"["'](/[a-zA-Z0-9_/{}\-\.]+)"#)
         .unwrap()
         .captures_iter(source)
     {
@@ -413,6 +713,19 @@ fn extract_url_paths_from_source(source: &str) -> Vec<String> {
         }
     }
 
+    // Template literals, optionally prefixed by a base URL interpolation
+    for cap in regex::Regex::new(r"`(?:\$\{[^}]*\})?(/(?:[a-zA-Z0-9_/{}\-\.]|\$\{[^}]*\})+)")
+        .unwrap()
+        .captures_iter(source)
+    {
+        if let Some(path) = cap.get(1) {
+            let p = interpolations_to_params(path.as_str());
+            if !paths.contains(&p) {
+                paths.push(p);
+            }
+        }
+    }
+
     // Also extract paths from full URLs: "http://host:port/path"
     // Use a non-greedy match for the host part so we capture the path
     for cap in regex::Regex::new(r#"https?://[^/\s"'`]+(/[a-zA-Z0-9_/{}\-\.]+)"#)
@@ -430,23 +743,33 @@ fn extract_url_paths_from_source(source: &str) -> Vec<String> {
     paths
 }
 
+/// Rewrite template literal interpolations as path parameters:
+/// `/users/${user.id}/posts` → `/users/{user.id}/posts`.
+fn interpolations_to_params(path: &str) -> String {
+    path.replace("${", "{")
+}
+
 /// Normalize a route path for matching.
 ///
-/// Strips trailing slash, query params, fragments.
+/// Strips trailing slash, query params, fragments, and adds a missing leading
+/// slash (NestJS `@Get("users")`).
 fn normalize_route(route: &str) -> String {
     let route = route.split('?').next().unwrap_or(route);
     let route = route.split('#').next().unwrap_or(route);
     let route = route.trim_end_matches('/');
     if route.is_empty() {
         "/".to_string()
-    } else {
+    } else if route.starts_with('/') {
         route.to_string()
+    } else {
+        format!("/{route}")
     }
 }
 
 /// Check if a route pattern matches a concrete URL path.
 ///
-/// Handles path parameters: "/users/{id}" matches "/users/123"
+/// Handles path parameters: "/users/{id}" matches "/users/123". A parameter in
+/// the client path (from a template literal) matches any segment.
 fn route_pattern_matches(pattern: &str, path: &str) -> bool {
     let pattern_parts: Vec<&str> = pattern.split('/').collect();
     let path_parts: Vec<&str> = path.split('/').collect();
@@ -463,12 +786,14 @@ fn route_pattern_matches(pattern: &str, path: &str) -> bool {
                 || pat.starts_with('{') && pat.ends_with('}') // {id}, {user_id}, etc.
                 || pat.starts_with(':') // :id (Express-style)
                 || pat.starts_with('<') && pat.ends_with('>') // <id> (Flask-style)
+                || actual.starts_with('{') && actual.ends_with('}') // `${id}` in the client
         })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use codegraph::PropertyValue;
 
     #[test]
     fn test_parse_route_decorator_flask_get() {
@@ -515,6 +840,109 @@ mod tests {
         assert_eq!(result, Some(("/api".to_string(), "ANY".to_string())));
     }
 
+    #[test]
+    fn test_parse_route_decorator_spring_request_method() {
+        let result = parse_route_decorator(
+            "@RequestMapping(value = \"/users\", method = RequestMethod.POST)",
+        );
+        assert_eq!(result, Some(("/users".to_string(), "POST".to_string())));
+    }
+
+    #[test]
+    fn test_join_route() {
+        assert_eq!(join_route("/api", "/users"), "/api/users");
+        assert_eq!(join_route("api/", "users/{id}"), "/api/users/{id}");
+        assert_eq!(join_route("/api", ""), "/api");
+        assert_eq!(join_route("", "/users"), "/users");
+    }
+
+    #[test]
+    fn test_detect_route_handlers_controller_prefix() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let attributes = |attrs: &[&str]| {
+            PropertyValue::StringList(attrs.iter().map(|a| a.to_string()).collect())
+        };
+        let path = "/src/main/java/UserController.java";
+        graph
+            .add_node(
+                NodeType::Class,
+                PropertyMap::new()
+                    .with("name", "UserController")
+                    .with("path", path)
+                    .with("line_start", PropertyValue::Int(3))
+                    .with("line_end", PropertyValue::Int(20))
+                    .with(
+                        "attributes",
+                        attributes(&["@RestController", "@RequestMapping(\"/api/users\")"]),
+                    ),
+            )
+            .unwrap();
+        let get_user = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "getUser")
+                    .with("path", path)
+                    .with("line_start", PropertyValue::Int(6))
+                    .with("attributes", attributes(&["@GetMapping(\"/{id}\")"])),
+            )
+            .unwrap();
+        let create_user = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "createUser")
+                    .with("path", path)
+                    .with("line_start", PropertyValue::Int(12))
+                    .with(
+                        "attributes",
+                        attributes(&["@RequestMapping(method = RequestMethod.POST)"]),
+                    ),
+            )
+            .unwrap();
+
+        assert_eq!(detect_route_handlers(&mut graph), 2);
+        let node = graph.get_node(get_user).unwrap();
+        assert_eq!(node.properties.get_string("route"), Some("/api/users/{id}"));
+        assert_eq!(node.properties.get_string("http_method"), Some("GET"));
+        let node = graph.get_node(create_user).unwrap();
+        assert_eq!(node.properties.get_string("route"), Some("/api/users"));
+        assert_eq!(node.properties.get_string("http_method"), Some("POST"));
+    }
+
+    #[test]
+    fn test_controller_prefixes_by_framework() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let mut add_class = |path: &str, attrs: &[&str]| {
+            let attrs = attrs.iter().map(|a| a.to_string()).collect();
+            graph
+                .add_node(
+                    NodeType::Class,
+                    PropertyMap::new()
+                        .with("path", path)
+                        .with("attributes", PropertyValue::StringList(attrs)),
+                )
+                .unwrap()
+        };
+        // Spring: @Controller names the bean
+        let spring = add_class(
+            "/src/UserController.java",
+            &[
+                "@Controller(\"userController\")",
+                "@RequestMapping(\"/users\")",
+            ],
+        );
+        let bean_only = add_class("/src/PageController.java", &["@Controller(\"pages\")"]);
+        let advice = add_class("/src/errors.advice.ts", &["@ControllerAdvice(\"/errors\")"]);
+        let nest = add_class("/src/cats.controller.ts", &["@Controller('cats')"]);
+
+        let prefixes = controller_prefixes(&graph);
+        assert_eq!(prefixes.get(&spring).map(String::as_str), Some("/users"));
+        assert_eq!(prefixes.get(&nest).map(String::as_str), Some("cats"));
+        assert!(!prefixes.contains_key(&bean_only));
+        assert!(!prefixes.contains_key(&advice));
+    }
+
     #[test]
     fn test_parse_route_decorator_single_quotes() {
         let result = parse_route_decorator("@app.get('/users')");
@@ -609,6 +1037,119 @@ mod tests {
         assert_eq!(node.properties.get_string("route"), Some("/items"));
         assert_eq!(node.properties.get_string("http_method"), Some("POST"));
     }
+
+    #[test]
+    fn test_parse_go_route_registrations() {
+        let source = r#"func routes(mux *http.ServeMux, h *Handlers) {
+    http.HandleFunc("/health", health)
+    mux.HandleFunc("GET /users/{id}", h.getUser)
+    mux.Handle("POST api.example.com/users", http.HandlerFunc(api.CreateUser))
+    mux.Handle("/static/", fileServer)
+}"#;
+        assert_eq!(
+            parse_go_route_registrations(source),
+            vec![
+                ("/health".into(), "ANY".into(), "health".into()),
+                ("/users/{id}".into(), "GET".into(), "getUser".into()),
+                ("/users".into(), "POST".into(), "CreateUser".into()),
+                ("/static/".into(), "ANY".into(), "fileServer".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_detect_go_route_handlers() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+        let go_function =
+            |name: &str, path: &str| PropertyMap::new().with("name", name).with("path", path);
+        graph
+            .add_node(
+                NodeType::Function,
+                go_function("main", "/srv/cmd/api/main.go")
+                    .with("unresolved_calls", "http.HandleFunc")
+                    .with(
+                        "source",
+                        "http.HandleFunc(\"GET /users\", listUsers)\nhttp.ListenAndServe(\":8080\", nil)",
+                    ),
+            )
+            .unwrap();
+        let list_users = graph
+            .add_node(
+                NodeType::Function,
+                go_function("listUsers", "/srv/cmd/api/users.go"),
+            )
+            .unwrap();
+        // Same name in another package
+        let other = graph
+            .add_node(
+                NodeType::Function,
+                go_function("listUsers", "/srv/internal/admin/users.go"),
+            )
+            .unwrap();
+
+        assert_eq!(detect_route_handlers(&mut graph), 1);
+        let node = graph.get_node(list_users).unwrap();
+        assert_eq!(node.properties.get_string("route"), Some("/users"));
+        assert_eq!(node.properties.get_string("http_method"), Some("GET"));
+        assert!(graph
+            .get_node(other)
+            .unwrap()
+            .properties
+            .get_string("route")
+            .is_none());
+    }
+
+    #[test]
+    fn test_extract_url_paths_from_template_literals() {
+        let source = "const r = await fetch(`${API_URL}/api/users/${user.id}/posts?page=${page}`);";
+        let paths = extract_url_paths_from_source(source);
+        assert_eq!(paths, vec!["/api/users/{user.id}/posts".to_string()]);
+        assert!(route_pattern_matches(
+            "/api/users/:id/posts",
+            &normalize_route(&paths[0])
+        ));
+    }
+
+    #[test]
+    fn test_link_runtime_calls_matches_method_and_is_idempotent() {
+        let mut graph = CodeGraph::in_memory().unwrap();
+
+        let get_user = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "get_user")
+                    .with("attributes", "@app.get(\"/api/users/{user_id}\")"),
+            )
+            .unwrap();
+        let delete_user = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "delete_user")
+                    .with("attributes", "@app.delete(\"/api/users/{user_id}\")"),
+            )
+            .unwrap();
+        let client = graph
+            .add_node(
+                NodeType::Function,
+                PropertyMap::new()
+                    .with("name", "loadUser")
+                    .with("unresolved_calls", "axios.get")
+                    .with("source", "return axios.get(`${BASE}/api/users/${id}`);"),
+            )
+            .unwrap();
+
+        let stats = link_runtime_calls(&mut graph);
+        assert_eq!(stats.routes, 2);
+        assert_eq!(stats.clients, 1);
+        assert_eq!(stats.edges, 1);
+        assert!(has_runtime_call_edge(&graph, client, get_user));
+        assert!(!has_runtime_call_edge(&graph, client, delete_user));
+
+        // Re-running after an incremental update adds nothing
+        assert_eq!(link_runtime_calls(&mut graph).edges, 0);
+    }
 }
 
 #[test]
@@ -1,7 +1,10 @@
 //! File system watcher for incremental updates.
 
+use crate::cache::QueryCache;
+use crate::domain::node_props;
 use crate::memory::MemoryManager;
 use crate::parser_registry::ParserRegistry;
+use crate::workspace_walk::WorkspaceWalk;
 use codegraph::CodeGraph;
 use notify::{Config, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
@@ -15,9 +18,43 @@ use tower_lsp::Client;
 /// Default debounce interval in milliseconds.
 const DEFAULT_DEBOUNCE_MS: u64 = 300;
 
+/// A file the watcher re-indexed or dropped from the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchedChange {
+    pub path: PathBuf,
+    /// The file was created or deleted rather than modified.
+    pub added_or_removed: bool,
+    /// Number of memories auto-invalidated by the change.
+    pub invalidated_memories: usize,
+}
+
+type ChangeListener = Arc<std::sync::RwLock<Vec<mpsc::UnboundedSender<WatchedChange>>>>;
+
+/// Files the watcher does not re-index: those the workspace walk skips under
+/// the watched directories.
+#[derive(Default)]
+struct Exclusions {
+    walk: Option<WorkspaceWalk>,
+    roots: Vec<PathBuf>,
+}
+
+impl Exclusions {
+    fn excludes(&self, path: &Path) -> bool {
+        self.walk.as_ref().is_some_and(|walk| {
+            self.roots
+                .iter()
+                .any(|root| path.starts_with(root) && walk.excludes(root, path))
+        })
+    }
+}
+
 /// File system watcher that triggers re-parsing on changes.
 pub struct FileWatcher {
     _watcher: RecommendedWatcher,
+    /// Files that are not re-indexed on change.
+    exclusions: Arc<std::sync::RwLock<Exclusions>>,
+    /// Receives every change applied to the graph.
+    change_listener: ChangeListener,
 }
 
 impl FileWatcher {
@@ -27,6 +64,33 @@ impl FileWatcher {
         parsers: Arc<ParserRegistry>,
         client: Client,
         memory_manager: Arc<MemoryManager>,
+        query_cache: Arc<QueryCache>,
+    ) -> Result<Self, notify::Error> {
+        Self::spawn(
+            graph,
+            parsers,
+            Some(client),
+            memory_manager,
+            Some(query_cache),
+        )
+    }
+
+    /// Create a file watcher outside the language server (MCP mode): errors
+    /// are logged rather than sent to a client and there is no query cache.
+    pub fn headless(
+        graph: Arc<RwLock<CodeGraph>>,
+        parsers: Arc<ParserRegistry>,
+        memory_manager: Arc<MemoryManager>,
+    ) -> Result<Self, notify::Error> {
+        Self::spawn(graph, parsers, None, memory_manager, None)
+    }
+
+    fn spawn(
+        graph: Arc<RwLock<CodeGraph>>,
+        parsers: Arc<ParserRegistry>,
+        client: Option<Client>,
+        memory_manager: Arc<MemoryManager>,
+        query_cache: Option<Arc<QueryCache>>,
     ) -> Result<Self, notify::Error> {
         let (tx, mut rx) = mpsc::channel::<Event>(100);
 
@@ -43,8 +107,11 @@ impl FileWatcher {
         // Spawn event handler task with debouncing
         let graph_clone = Arc::clone(&graph);
         let parsers_clone = Arc::clone(&parsers);
-        let client_clone = client.clone();
         let memory_clone = Arc::clone(&memory_manager);
+        let exclusions = Arc::new(std::sync::RwLock::new(Exclusions::default()));
+        let exclusions_clone = Arc::clone(&exclusions);
+        let change_listener: ChangeListener = Arc::new(std::sync::RwLock::new(Vec::new()));
+        let listener_clone = Arc::clone(&change_listener);
 
         tokio::spawn(async move {
             let debounce_duration = Duration::from_millis(DEFAULT_DEBOUNCE_MS);
@@ -85,34 +152,82 @@ impl FileWatcher {
                                 paths: vec![path],
                                 attrs: Default::default(),
                             };
-                            Self::handle_event(&graph_clone, &parsers_clone, &client_clone, &memory_clone, event).await;
+                            Self::handle_event(&graph_clone, &parsers_clone, client.as_ref(), &memory_clone, query_cache.as_ref(), &exclusions_clone, &listener_clone, event).await;
                         }
                     }
                 }
             }
         });
 
-        Ok(Self { _watcher: watcher })
+        Ok(Self {
+            _watcher: watcher,
+            exclusions,
+            change_listener,
+        })
+    }
+
+    /// Also report the changes applied to the graph to `listener`, until it
+    /// is dropped.
+    pub fn add_change_listener(&self, listener: mpsc::UnboundedSender<WatchedChange>) {
+        if let Ok(mut guard) = self.change_listener.write() {
+            guard.push(listener);
+        }
+    }
+
+    /// Skip changed files that `walk` would not index from the watched
+    /// directories, e.g. after a configuration change.
+    pub fn set_workspace_walk(&self, walk: WorkspaceWalk) {
+        if let Ok(mut guard) = self.exclusions.write() {
+            guard.walk = Some(walk);
+        }
     }
 
     /// Start watching a directory.
     pub fn watch(&mut self, path: &Path) -> Result<(), notify::Error> {
-        self._watcher.watch(path, RecursiveMode::Recursive)
+        self._watcher.watch(path, RecursiveMode::Recursive)?;
+        if let Ok(mut guard) = self.exclusions.write() {
+            guard.roots.push(path.to_path_buf());
+        }
+        Ok(())
     }
 
     /// Stop watching a directory.
     pub fn unwatch(&mut self, path: &Path) -> Result<(), notify::Error> {
+        if let Ok(mut guard) = self.exclusions.write() {
+            guard.roots.retain(|root| root != path);
+        }
         self._watcher.unwatch(path)
     }
 
+    /// Log a failed update to the client, or to the log file without one.
+    async fn report_error(client: Option<&Client>, message: String) {
+        match client {
+            Some(client) => client.log_message(MessageType::WARNING, message).await,
+            None => tracing::warn!("{}", message),
+        }
+    }
+
+    /// Pass a change applied to the graph on to the listeners, forgetting
+    /// those that were dropped.
+    fn notify_change(listener: &ChangeListener, change: WatchedChange) {
+        if let Ok(mut guard) = listener.write() {
+            guard.retain(|listener| listener.send(change.clone()).is_ok());
+        }
+    }
+
     /// Handle a file system event.
+    #[allow(clippy::too_many_arguments)]
     async fn handle_event(
         graph: &Arc<RwLock<CodeGraph>>,
         parsers: &Arc<ParserRegistry>,
-        client: &Client,
+        client: Option<&Client>,
         memory_manager: &Arc<MemoryManager>,
+        query_cache: Option<&Arc<QueryCache>>,
+        exclusions: &std::sync::RwLock<Exclusions>,
+        change_listener: &ChangeListener,
         event: Event,
     ) {
+        let created = matches!(event.kind, EventKind::Create(_));
         match event.kind {
             EventKind::Create(_) | EventKind::Modify(_) => {
                 for path in event.paths {
@@ -120,32 +235,60 @@ impl FileWatcher {
                     if !parsers.can_parse(&path) {
                         continue;
                     }
+                    // Skip files excluded from indexing
+                    if exclusions.read().is_ok_and(|e| e.excludes(&path)) {
+                        continue;
+                    }
 
-                    if let Err(e) =
-                        Self::handle_file_change(graph, parsers, memory_manager, &path).await
-                    {
-                        client
-                            .log_message(
-                                MessageType::WARNING,
+                    match Self::handle_file_change(graph, parsers, memory_manager, &path).await {
+                        Ok(invalidated_memories) => {
+                            if let Some(query_cache) = query_cache {
+                                query_cache.invalidate_file(&path);
+                            }
+                            tracing::debug!("Re-indexed: {}", path.display());
+                            Self::notify_change(
+                                change_listener,
+                                WatchedChange {
+                                    path,
+                                    added_or_removed: created,
+                                    invalidated_memories,
+                                },
+                            );
+                        }
+                        Err(e) => {
+                            Self::report_error(
+                                client,
                                 format!("Error processing {}: {}", path.display(), e),
                             )
-                            .await;
-                    } else {
-                        tracing::debug!("Re-indexed: {}", path.display());
+                            .await
+                        }
                     }
                 }
             }
             EventKind::Remove(_) => {
                 for path in event.paths {
-                    if let Err(e) = Self::handle_file_remove(graph, memory_manager, &path).await {
-                        client
-                            .log_message(
-                                MessageType::WARNING,
+                    match Self::handle_file_remove(graph, memory_manager, &path).await {
+                        Ok(invalidated_memories) => {
+                            if let Some(query_cache) = query_cache {
+                                query_cache.invalidate_file(&path);
+                            }
+                            tracing::debug!("Removed from index: {}", path.display());
+                            Self::notify_change(
+                                change_listener,
+                                WatchedChange {
+                                    path,
+                                    added_or_removed: true,
+                                    invalidated_memories,
+                                },
+                            );
+                        }
+                        Err(e) => {
+                            Self::report_error(
+                                client,
                                 format!("Error removing {}: {}", path.display(), e),
                             )
-                            .await;
-                    } else {
-                        tracing::debug!("Removed from index: {}", path.display());
+                            .await
+                        }
                     }
                 }
             }
@@ -153,17 +296,18 @@ impl FileWatcher {
         }
     }
 
-    /// Handle a file creation or modification.
+    /// Handle a file creation or modification. Returns the number of
+    /// memories invalidated.
     async fn handle_file_change(
         graph: &Arc<RwLock<CodeGraph>>,
         parsers: &Arc<ParserRegistry>,
         memory_manager: &Arc<MemoryManager>,
         path: &Path,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         // Skip non-parseable files
         let parser = match parsers.parser_for_path(path) {
             Some(p) => p,
-            None => return Ok(()),
+            None => return Ok(0),
         };
 
         // Read file content
@@ -183,32 +327,25 @@ impl FileWatcher {
             Self::remove_file_nodes(&mut graph, path)?;
 
             // Parse and add new nodes
-            parser.parse_source(&content, path, &mut graph)?;
+            let file_info = parser.parse_source(&content, path, &mut graph)?;
+            parsers.enrich_parsed_file(&mut graph, &file_info, path, &content);
 
             // Resolve cross-file imports after parsing
             GraphUpdater::resolve_cross_file_imports(&mut graph);
+            crate::runtime_deps::link_runtime_calls(&mut graph);
         }
 
         // Auto-invalidate memories linked to changed nodes (after releasing graph lock)
-        if !node_id_strings.is_empty() {
-            let reason = format!("File modified: {}", path_str);
-            if let Err(e) = memory_manager
-                .invalidate_for_code_nodes(&node_id_strings, &reason)
-                .await
-            {
-                tracing::warn!("Failed to invalidate memories for {}: {}", path_str, e);
-            }
-        }
-
-        Ok(())
+        let reason = format!("File modified: {}", path_str);
+        Ok(Self::invalidate_memories(memory_manager, &node_id_strings, &reason).await)
     }
 
-    /// Handle a file removal.
+    /// Handle a file removal. Returns the number of memories invalidated.
     async fn handle_file_remove(
         graph: &Arc<RwLock<CodeGraph>>,
         memory_manager: &Arc<MemoryManager>,
         path: &Path,
-    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    ) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
         let path_str = path.to_string_lossy().to_string();
         let node_id_strings: Vec<String>;
 
@@ -223,17 +360,30 @@ impl FileWatcher {
         }
 
         // Auto-invalidate memories linked to deleted nodes
-        if !node_id_strings.is_empty() {
-            let reason = format!("File deleted: {}", path_str);
-            if let Err(e) = memory_manager
-                .invalidate_for_code_nodes(&node_id_strings, &reason)
-                .await
-            {
-                tracing::warn!("Failed to invalidate memories for {}: {}", path_str, e);
+        let reason = format!("File deleted: {}", path_str);
+        Ok(Self::invalidate_memories(memory_manager, &node_id_strings, &reason).await)
+    }
+
+    /// Auto-invalidate the memories linked to `node_ids`, returning how many
+    /// were invalidated.
+    async fn invalidate_memories(
+        memory_manager: &MemoryManager,
+        node_ids: &[String],
+        reason: &str,
+    ) -> usize {
+        if node_ids.is_empty() {
+            return 0;
+        }
+        match memory_manager
+            .invalidate_for_code_nodes(node_ids, reason)
+            .await
+        {
+            Ok(invalidated) => invalidated.len(),
+            Err(e) => {
+                tracing::warn!("Failed to invalidate memories ({}): {}", reason, e);
+                0
             }
         }
-
-        Ok(())
     }
 
     /// Collect all node IDs for a file (for memory invalidation).
@@ -292,7 +442,10 @@ impl GraphUpdater {
 
                 // Parse new content
                 match parser.parse_source(content, path, &mut graph_guard) {
-                    Ok(info) => succeeded.push((path.clone(), info)),
+                    Ok(info) => {
+                        parsers.enrich_parsed_file(&mut graph_guard, &info, path, content);
+                        succeeded.push((path.clone(), info));
+                    }
                     Err(e) => failed.push((path.clone(), e.to_string())),
                 }
             }
@@ -300,6 +453,7 @@ impl GraphUpdater {
 
         // Post-process: resolve cross-file import edges to actual symbol nodes
         Self::resolve_cross_file_imports(&mut graph_guard);
+        crate::runtime_deps::link_runtime_calls(&mut graph_guard);
 
         BatchUpdateResult { succeeded, failed }
     }
@@ -502,10 +656,13 @@ impl GraphUpdater {
                                     .get_edges_between(func_id, type_id)
                                     .map(|edges| {
                                         edges.iter().any(|e| {
-                                            graph
-                                                .get_edge(*e)
-                                                .map(|edge| edge.edge_type == EdgeType::References)
-                                                .unwrap_or(false)
+                                            graph.get_edge(*e).is_ok_and(|edge| {
+                                                edge.edge_type == EdgeType::References
+                                                    && !node_props::is_co_change(
+                                                        edge.edge_type,
+                                                        &edge.properties,
+                                                    )
+                                            })
                                         })
                                     })
                                     .unwrap_or(false);
@@ -526,6 +683,9 @@ impl GraphUpdater {
         for (from_id, to_id, props) in ref_edges_to_add {
             let _ = graph.add_edge(from_id, to_id, EdgeType::References, props);
         }
+
+        // Phase 4: C/C++ #include targets and header declarations
+        crate::header_links::link_headers(graph);
     }
 }
 
@@ -0,0 +1,375 @@
+//! Files visited when indexing a workspace directory.
+//!
+//! The walk honors `.gitignore`, `.ignore`, `.git/info/exclude` and the global
+//! git excludes, so dependencies and generated output are skipped wherever the
+//! project keeps them. Hidden files and directories, and the common dependency
+//! and build output folders in [`DEFAULT_EXCLUDED_DIRS`], are skipped even when
+//! no ignore file lists them. Both the LSP and the MCP server index through
+//! [`WorkspaceWalk`], adding their own configured exclusions on top, and their
+//! file watchers skip what the walk skips.
+
+use globset::GlobSet;
+use ignore::gitignore::GitignoreBuilder;
+use ignore::WalkBuilder;
+use std::path::{Path, PathBuf};
+
+/// Directories skipped wherever they are, whether or not an ignore file lists
+/// them.
+pub const DEFAULT_EXCLUDED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    "__pycache__",
+    "venv",
+    "vendor",
+    "DerivedData",
+    "tmp",
+    "coverage",
+    "htmlcov",
+    "results",
+    "logs",
+];
+
+/// Ignore files read in each directory, besides the git excludes.
+const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore"];
+
+/// Options for walking a directory for indexable files.
+#[derive(Debug, Clone)]
+pub struct WorkspaceWalk {
+    /// Extensions of the files to return, without the leading dot.
+    extensions: Vec<String>,
+    /// Deepest directory level to descend into below the root.
+    max_depth: Option<usize>,
+    /// Files larger than this many bytes are skipped.
+    max_file_size: Option<u64>,
+    /// Paths (or file names) matching these globs are skipped.
+    exclude: GlobSet,
+    /// File and directory names that are skipped.
+    exclude_names: Vec<String>,
+}
+
+impl WorkspaceWalk {
+    /// Walk for files with one of `extensions`, given with or without the
+    /// leading dot.
+    pub fn new(extensions: &[&str]) -> Self {
+        Self {
+            extensions: extensions
+                .iter()
+                .map(|ext| ext.trim_start_matches('.').to_string())
+                .collect(),
+            max_depth: None,
+            max_file_size: None,
+            exclude: GlobSet::empty(),
+            exclude_names: Vec::new(),
+        }
+    }
+
+    /// Descend at most `depth` directories below the root.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Skip files larger than `bytes`.
+    pub fn max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Skip files and directories whose path or name matches `exclude`.
+    pub fn exclude(mut self, exclude: GlobSet) -> Self {
+        self.exclude = exclude;
+        self
+    }
+
+    /// Skip files and directories with any of these names.
+    pub fn exclude_names(mut self, names: &[String]) -> Self {
+        self.exclude_names = names.to_vec();
+        self
+    }
+
+    /// The indexable files under `root`, in directory order.
+    pub fn files<'a>(&'a self, root: &Path) -> impl Iterator<Item = PathBuf> + 'a {
+        let mut builder = WalkBuilder::new(root);
+        builder
+            .hidden(true)
+            .ignore(true)
+            .git_ignore(true)
+            .git_exclude(true)
+            .git_global(true)
+            // Honor .gitignore files even when the workspace is not a repository
+            .require_git(false)
+            .max_depth(self.max_depth.map(|depth| depth + 1));
+
+        let exclude = self.exclude.clone();
+        let exclude_names = self.exclude_names.clone();
+        builder.filter_entry(move |entry| {
+            let name = entry.file_name().to_string_lossy();
+            if exclude_names.iter().any(|n| *n == name) {
+                return false;
+            }
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            if is_dir && entry.depth() > 0 && DEFAULT_EXCLUDED_DIRS.contains(&name.as_ref()) {
+                return false;
+            }
+            if exclude.is_match(entry.path()) || exclude.is_match(name.as_ref()) {
+                tracing::info!("Skipping {:?}: matched exclude pattern", entry.path());
+                return false;
+            }
+            true
+        });
+
+        builder
+            .build()
+            .filter_map(|entry| match entry {
+                Ok(entry) => Some(entry),
+                Err(e) => {
+                    tracing::warn!("Failed to walk workspace entry: {}", e);
+                    None
+                }
+            })
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(ignore::DirEntry::into_path)
+            .filter(move |path| self.is_supported(path) && self.within_size_limit(path))
+    }
+
+    /// Whether the walk from `root` skips `path`, a file under it. Used by
+    /// file watchers, which see one path at a time. Ignore files are read
+    /// from `root` down to the file's directory; the global git excludes are
+    /// not consulted.
+    pub fn excludes(&self, root: &Path, path: &Path) -> bool {
+        let Ok(relative) = path.strip_prefix(root) else {
+            return false;
+        };
+        let names: Vec<String> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().into_owned())
+            .collect();
+        let Some((file_name, dirs)) = names.split_last() else {
+            return false;
+        };
+        if self.max_depth.is_some_and(|depth| dirs.len() > depth) {
+            return true;
+        }
+        if names.iter().any(|name| name.starts_with('.')) {
+            return true;
+        }
+        if names.iter().any(|name| self.exclude_names.contains(name))
+            || dirs
+                .iter()
+                .any(|dir| DEFAULT_EXCLUDED_DIRS.contains(&dir.as_str()))
+        {
+            return true;
+        }
+        let mut ancestor = root.to_path_buf();
+        for dir in dirs {
+            ancestor.push(dir);
+            if self.exclude.is_match(&ancestor) || self.exclude.is_match(dir) {
+                return true;
+            }
+        }
+        if self.exclude.is_match(path) || self.exclude.is_match(file_name) {
+            return true;
+        }
+        if is_ignored(root, dirs, path) {
+            return true;
+        }
+        !self.is_supported(path) || !self.within_size_limit(path)
+    }
+
+    fn is_supported(&self, path: &Path) -> bool {
+        path.extension()
+            .map(|ext| ext.to_string_lossy())
+            .is_some_and(|ext| self.extensions.iter().any(|e| *e == ext))
+    }
+
+    fn within_size_limit(&self, path: &Path) -> bool {
+        let Some(limit) = self.max_file_size else {
+            return true;
+        };
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() > limit => {
+                tracing::info!(
+                    "Skipping {:?}: file size {} exceeds limit of {}",
+                    path,
+                    metadata.len(),
+                    limit
+                );
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Whether the ignore files in `root` and its subdirectories `dirs`, leading
+/// to `path`, ignore it. Deeper ignore files take precedence.
+fn is_ignored(root: &Path, dirs: &[String], path: &Path) -> bool {
+    let mut ancestors = vec![root.to_path_buf()];
+    for dir in dirs {
+        let next = ancestors[ancestors.len() - 1].join(dir);
+        ancestors.push(next);
+    }
+    for dir in ancestors.iter().rev() {
+        let mut builder = GitignoreBuilder::new(dir);
+        for file in IGNORE_FILES {
+            let ignore_file = dir.join(file);
+            if ignore_file.is_file() {
+                if let Some(e) = builder.add(ignore_file) {
+                    tracing::warn!("Failed to read ignore file in {:?}: {}", dir, e);
+                }
+            }
+        }
+        let Ok(gitignore) = builder.build() else {
+            continue;
+        };
+        let matched = gitignore.matched_path_or_any_parents(path, false);
+        if matched.is_ignore() {
+            return true;
+        }
+        if matched.is_whitelist() {
+            return false;
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(root: &Path, rel: &str, content: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn walk(walk: &WorkspaceWalk, root: &Path) -> Vec<String> {
+        let mut files: Vec<String> = walk
+            .files(root)
+            .map(|p| {
+                p.strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn test_honors_gitignore_without_repository() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".gitignore", "generated/\n*.gen.rs\n");
+        write(root, "src/main.rs", "fn main() {}");
+        write(root, "src/schema.gen.rs", "");
+        write(root, "generated/api.rs", "");
+        write(root, "nested/.ignore", "fixtures/\n");
+        write(root, "nested/fixtures/a.rs", "");
+        write(root, "nested/lib.rs", "");
+
+        let files = walk(&WorkspaceWalk::new(&[".rs"]), root);
+        assert_eq!(files, vec!["nested/lib.rs", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_skips_default_directories_without_ignore_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "node_modules/lodash/index.js", "");
+        write(root, "web/dist/bundle.js", "");
+        write(root, "venv/lib/site.py", "");
+        write(root, "src/build.js", "");
+        write(root, "src/app.js", "");
+
+        let files = walk(&WorkspaceWalk::new(&["js", "py"]), root);
+        assert_eq!(files, vec!["src/app.js", "src/build.js"]);
+    }
+
+    #[test]
+    fn test_excludes_matches_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".gitignore", "generated/\n");
+        write(root, "src/.ignore", "*.gen.py\n!keep.gen.py\n");
+        for rel in [
+            "app.py",
+            "src/lib.py",
+            "src/schema.gen.py",
+            "src/keep.gen.py",
+            "generated/api.py",
+            "node_modules/pkg/setup.py",
+            ".cache/a.py",
+            "legacy/old.py",
+            "notes.txt",
+        ] {
+            write(root, rel, "");
+        }
+        let walker = WorkspaceWalk::new(&["py"]).exclude_names(&["legacy".to_string()]);
+
+        let walked = walk(&walker, root);
+        assert_eq!(walked, vec!["app.py", "src/keep.gen.py", "src/lib.py"]);
+        for rel in [
+            "app.py",
+            "src/lib.py",
+            "src/schema.gen.py",
+            "src/keep.gen.py",
+            "generated/api.py",
+            "node_modules/pkg/setup.py",
+            ".cache/a.py",
+            "legacy/old.py",
+            "notes.txt",
+        ] {
+            let excluded = walker.excludes(root, &root.join(rel));
+            assert_eq!(excluded, !walked.contains(&rel.to_string()), "{rel}");
+        }
+        assert!(!walker.excludes(root, Path::new("/elsewhere/app.py")));
+    }
+
+    #[test]
+    fn test_skips_hidden_and_unsupported_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".cache/a.py", "");
+        write(root, ".hidden.py", "");
+        write(root, "app.py", "");
+        write(root, "README.md", "");
+
+        let files = walk(&WorkspaceWalk::new(&["py"]), root);
+        assert_eq!(files, vec!["app.py"]);
+    }
+
+    #[test]
+    fn test_configured_exclusions() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "vendor/dep.go", "");
+        write(root, "web/bundle.min.js", "");
+        write(root, "web/app.js", "");
+        write(root, "main.go", "");
+
+        let mut globs = globset::GlobSetBuilder::new();
+        globs.add(globset::Glob::new("*.min.js").unwrap());
+        let walker = WorkspaceWalk::new(&["go", "js"])
+            .exclude(globs.build().unwrap())
+            .exclude_names(&["vendor".to_string()]);
+        assert_eq!(walk(&walker, root), vec!["main.go", "web/app.js"]);
+    }
+
+    #[test]
+    fn test_depth_and_size_limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a/b/c/deep.ts", "");
+        write(root, "a/shallow.ts", "");
+        write(root, "big.ts", &"x".repeat(2048));
+
+        let walker = WorkspaceWalk::new(&["ts"]).max_depth(1).max_file_size(1024);
+        assert_eq!(walk(&walker, root), vec!["a/shallow.ts"]);
+    }
+}
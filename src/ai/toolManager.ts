@@ -1273,8 +1273,17 @@ export class CodeGraphToolManager {
                         mineReverts?: boolean;
                         mineFeatures?: boolean;
                         mineDeprecations?: boolean;
+                        mineReleases?: boolean;
+                        mineTrailers?: boolean;
                         includeHotspots?: boolean;
+                        hotspotsSince?: string;
+                        hotspotsUntil?: string;
+                        hotspotsByDirectory?: boolean;
                         includeCoupling?: boolean;
+                        includeOwnership?: boolean;
+                        enrichPullRequests?: boolean;
+                        branch?: string;
+                        dryRun?: boolean;
                     };
 
                     try {
@@ -1309,6 +1318,8 @@ export class CodeGraphToolManager {
                     const input = options.input as {
                         uri: string;
                         maxCommits?: number;
+                        branch?: string;
+                        dryRun?: boolean;
                     };
 
                     try {
@@ -2584,6 +2595,30 @@ export class CodeGraphToolManager {
         output += `- **Commits Processed**: ${response.commitsProcessed}\n`;
         output += `- **Memories Created**: ${response.memoriesCreated}\n`;
         output += `- **Commits Skipped**: ${response.commitsSkipped}\n`;
+        if (response.commitsGrouped) {
+            output += `- **Grouped Into Earlier Fixes**: ${response.commitsGrouped}\n`;
+        }
+        if (response.pullRequestsEnriched) {
+            output += `- **Pull Requests Added**: ${response.pullRequestsEnriched}\n`;
+        }
+        if (response.memoriesReverted) {
+            output += `- **Invalidated by Reverts**: ${response.memoriesReverted}\n`;
+        }
+        if (response.memoriesRestored) {
+            output += `- **Restored by Reverted Reverts**: ${response.memoriesRestored}\n`;
+        }
+        if (response.trailersMined) {
+            output += `- **From Memory/Decision Trailers**: ${response.trailersMined}\n`;
+        }
+        if (response.squashMergesSplit) {
+            output += `- **Squash Merges Split Into Their Commits**: ${response.squashMergesSplit}\n`;
+        }
+        if (response.releasesMined) {
+            output += `- **Releases**: ${response.releasesMined}\n`;
+        }
+        if (response.sinceCommit) {
+            output += `- **Since Commit**: ${response.sinceCommit.slice(0, 7)}\n`;
+        }
         
         if (response.hotspotsDetected !== undefined && response.hotspotsDetected > 0) {
             output += `- **Hotspots Detected**: ${response.hotspotsDetected}\n`;
@@ -2593,8 +2628,23 @@ export class CodeGraphToolManager {
             output += `- **Couplings Detected**: ${response.couplingsDetected}\n`;
         }
         
+        if (response.ownershipDetected !== undefined && response.ownershipDetected > 0) {
+            output += `- **Modules With Owners**: ${response.ownershipDetected}\n`;
+        }
+        
         output += '\n';
 
+        if (response.candidates) {
+            output += `## Dry Run: ${response.candidates.length} Candidate Memories\n`;
+            output += 'Nothing was stored. Tune the patterns or minConfidence, then mine again without dryRun.\n\n';
+            response.candidates.forEach((candidate, i) => {
+                const commits = candidate.commits.map(hash => hash.slice(0, 7)).join(', ');
+                output += `${i + 1}. **${candidate.title}** (${candidate.kind}, confidence ${candidate.confidence.toFixed(2)})`;
+                output += commits ? ` — ${commits}\n` : '\n';
+            });
+            output += '\n';
+        }
+
         if (response.memoriesCreated > 0) {
             output += '## Created Memories\n';
             output += 'The following memories were extracted from git history:\n\n';
@@ -2605,7 +2655,7 @@ export class CodeGraphToolManager {
             output += '\n';
         }
 
-        if (response.memoriesCreated === 0) {
+        if (response.memoriesCreated === 0 && !response.candidates) {
             output += '## No Memories Created\n';
             output += 'No commits matched the mining criteria (bug fixes, architectural decisions, breaking changes, or reverts).\n';
             output += '\n';
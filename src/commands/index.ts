@@ -6,6 +6,7 @@ import {
     DependencyGraphResponse,
     CallGraphParams,
     CallGraphResponse,
+    ExportGraphResponse,
     ImpactAnalysisParams,
     ImpactAnalysisResponse,
     ParserMetricsParams,
@@ -188,6 +189,67 @@ export function registerCommands(
             }
     });
 
+    // Export Graph as DOT or Mermaid text
+    safeRegisterCommand('codegraph.exportGraph', async () => {
+            const editor = vscode.window.activeTextEditor;
+            if (!editor) {
+                vscode.window.showWarningMessage('CodeGraph: No active editor');
+                return;
+            }
+
+            const format = await vscode.window.showQuickPick(
+                [
+                    { label: 'Mermaid', value: 'mermaid', description: 'For Markdown docs and PR descriptions' },
+                    { label: 'DOT', value: 'dot', description: 'For Graphviz' },
+                ],
+                { placeHolder: 'Select export format' }
+            );
+            if (!format) {
+                return;
+            }
+            const scope = await vscode.window.showQuickPick(
+                [
+                    { label: 'Calls around this symbol', edgeTypes: ['calls'], symbol: true },
+                    { label: 'Imports of this file', edgeTypes: ['imports'], symbol: false },
+                    { label: 'Everything around this symbol', edgeTypes: [], symbol: true },
+                ],
+                { placeHolder: 'Select what to export' }
+            );
+            if (!scope) {
+                return;
+            }
+
+            try {
+                const response = await client.sendRequest('workspace/executeCommand', {
+                    command: 'codegraph.exportGraphVisualization',
+                    arguments: [{
+                        uri: editor.document.uri.toString(),
+                        position: scope.symbol ? {
+                            line: editor.selection.active.line,
+                            character: editor.selection.active.character,
+                        } : undefined,
+                        format: format.value,
+                        depth: vscode.workspace.getConfiguration('codegraph')
+                            .get<number>('visualization.defaultDepth', 3),
+                        edgeTypes: scope.edgeTypes,
+                    }]
+                }) as ExportGraphResponse;
+
+                const document = await vscode.workspace.openTextDocument({
+                    content: response.content,
+                    language: format.value === 'dot' ? 'dot' : 'mermaid',
+                });
+                await vscode.window.showTextDocument(document);
+                if (response.truncated) {
+                    vscode.window.showInformationMessage(
+                        `CodeGraph: Graph truncated to ${response.nodeCount} nodes`
+                    );
+                }
+            } catch (error) {
+                vscode.window.showErrorMessage(`CodeGraph: Failed to export graph: ${error}`);
+            }
+    });
+
     // Open AI Chat - Opens VS Code's chat with @codegraph participant
     safeRegisterCommand('codegraph.openAIChat', async () => {
             // Open the VS Code chat view and suggest using @codegraph
@@ -238,6 +300,21 @@ export function registerCommands(
             }
     });
 
+    // Show References - opens server-provided locations (code lens targets) in the peek view
+    safeRegisterCommand('codegraph.showReferences', async (
+        uri: string,
+        position: { line: number; character: number },
+        locations: { uri: string; range: { start: { line: number; character: number }; end: { line: number; character: number } } }[]
+    ) => {
+            const converter = client.protocol2CodeConverter;
+            await vscode.commands.executeCommand(
+                'editor.action.showReferences',
+                converter.asUri(uri),
+                converter.asPosition(position),
+                locations.map(location => converter.asLocation(location))
+            );
+    });
+
     // Index Directory - pick folders to index on demand
     safeRegisterCommand('codegraph.indexDirectory', async () => {
             const uris = await vscode.window.showOpenDialog({
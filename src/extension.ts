@@ -1,6 +1,7 @@
 import * as vscode from 'vscode';
 import * as os from 'os';
 import {
+    DidChangeConfigurationNotification,
     LanguageClient,
     LanguageClientOptions,
     ServerOptions,
@@ -16,6 +17,54 @@ let client: LanguageClient;
 let aiProvider: CodeGraphAIProvider;
 let toolManager: CodeGraphToolManager;
 
+/**
+ * Server settings from the `codegraph` section, in the shape of the server's
+ * `CodeGraphConfig`. Unset values are left out so the server uses its defaults.
+ */
+/**
+ * Exclude patterns overridden in individual workspace folders, keyed by folder
+ * path. The server applies each set only to files inside its folder.
+ */
+function folderExcludes(): Record<string, string[]> {
+    const excludes: Record<string, string[]> = {};
+    for (const folder of vscode.workspace.workspaceFolders ?? []) {
+        const patterns = vscode.workspace
+            .getConfiguration('codegraph', folder.uri)
+            .inspect<string[]>('excludePatterns')?.workspaceFolderValue;
+        if (patterns?.length) {
+            excludes[folder.uri.fsPath] = patterns;
+        }
+    }
+    return excludes;
+}
+
+function serverSettings(config: vscode.WorkspaceConfiguration) {
+    return {
+        indexOnStartup: config.get<boolean>('indexOnStartup'),
+        excludePatterns: config.get<string[]>('excludePatterns'),
+        folderExcludes: folderExcludes(),
+        indexPaths: config.get<string[]>('indexPaths'),
+        maxFileSizeKB: config.get<number>('maxFileSizeKB'),
+        queryTimeoutMs: config.get<number>('queryTimeoutMs'),
+        inlayHints: {
+            callers: config.get<boolean>('inlayHints.callers'),
+            complexity: config.get<boolean>('inlayHints.complexity'),
+            complexityThreshold: config.get<number>('inlayHints.complexityThreshold'),
+        },
+        search: {
+            bm25Weight: config.get<number>('search.bm25Weight'),
+            semanticWeight: config.get<number>('search.semanticWeight'),
+        },
+        memory: {
+            autoInvalidate: config.get<boolean>('memory.autoInvalidate'),
+            gitMining: {
+                maxCommits: config.get<number>('memory.gitMining.maxCommits'),
+                minConfidence: config.get<number>('memory.gitMining.minConfidence'),
+            },
+        },
+    };
+}
+
 export async function activate(context: vscode.ExtensionContext): Promise<void> {
     const config = vscode.workspace.getConfiguration('codegraph', vscode.workspace.workspaceFolders?.[0]?.uri);
 
@@ -51,9 +100,10 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
 
     // Server options - add Windows-specific spawn options
     const isWindows = os.platform() === 'win32';
+    const mcpListen = config.get<string>('mcp.listen', '').trim();
     const serverOptions: ServerOptions = {
         command: serverModule,
-        args: [],
+        args: mcpListen ? ['--mcp-listen', mcpListen] : [],
         transport: TransportKind.stdio,
         options: {
             // On Windows, we need shell: true to properly spawn .exe files
@@ -78,10 +128,15 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
             { scheme: 'file', language: 'cpp' },
             { scheme: 'file', language: 'kotlin' },
             { scheme: 'file', language: 'csharp' },
+            // Workspace completion is offered for these, which lack native tooling
+            { scheme: 'file', language: 'cobol' },
+            { scheme: 'file', language: 'fortran' },
+            { scheme: 'file', language: 'FortranFreeForm' },
+            { scheme: 'file', language: 'tcl' },
+            { scheme: 'file', language: 'verilog' },
+            { scheme: 'file', language: 'systemverilog' },
         ],
-        synchronize: {
-            fileEvents: vscode.workspace.createFileSystemWatcher('**/*'),
-        },
+        // File events come from the server's own didChangeWatchedFiles registration
         outputChannel: vscode.window.createOutputChannel('CodeGraph'),
         traceOutputChannel: vscode.window.createOutputChannel('CodeGraph Trace'),
         initializationOptions: () => {
@@ -91,12 +146,9 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
             const latestConfig = vscode.workspace.getConfiguration('codegraph', wsFolder);
             const opts = {
                 extensionPath: context.extensionPath,
-                indexOnStartup: latestConfig.get<boolean>('indexOnStartup'),
-                excludePatterns: latestConfig.get<string[]>('excludePatterns'),
-                indexPaths: latestConfig.get<string[]>('indexPaths'),
-                maxFileSizeKB: latestConfig.get<number>('maxFileSizeKB'),
                 embeddingModel: latestConfig.get<string>('embeddingModel'),
                 fullBodyEmbedding: latestConfig.get<boolean>('fullBodyEmbedding'),
+                ...serverSettings(latestConfig),
             };
             console.log('[CodeGraph] Initialization options:', JSON.stringify(opts));
             return opts;
@@ -135,28 +187,30 @@ export async function activate(context: vscode.ExtensionContext): Promise<void>
     }
 
     // Watch for settings changes and push to LSP server
+    const pushSettings = async () => {
+        if (!client) {
+            return;
+        }
+        const wsFolder = vscode.workspace.workspaceFolders?.[0]?.uri;
+        const updated = vscode.workspace.getConfiguration('codegraph', wsFolder);
+        const settings = serverSettings(updated);
+        try {
+            await client.sendNotification(DidChangeConfigurationNotification.type, {
+                settings: { codegraph: settings },
+            });
+            console.log('[CodeGraph] Configuration updated:', JSON.stringify(settings));
+        } catch (error) {
+            console.error('[CodeGraph] Failed to update configuration:', error);
+        }
+    };
     context.subscriptions.push(
         vscode.workspace.onDidChangeConfiguration(async (e) => {
-            if (e.affectsConfiguration('codegraph') && client) {
-                const wsFolder = vscode.workspace.workspaceFolders?.[0]?.uri;
-                const updated = vscode.workspace.getConfiguration('codegraph', wsFolder);
-                const newConfig = {
-                    indexOnStartup: updated.get<boolean>('indexOnStartup'),
-                    excludePatterns: updated.get<string[]>('excludePatterns'),
-                    indexPaths: updated.get<string[]>('indexPaths'),
-                    maxFileSizeKB: updated.get<number>('maxFileSizeKB'),
-                };
-                try {
-                    await client.sendRequest('workspace/executeCommand', {
-                        command: 'codegraph.updateConfiguration',
-                        arguments: [newConfig],
-                    });
-                    console.log('[CodeGraph] Configuration updated:', JSON.stringify(newConfig));
-                } catch (error) {
-                    console.error('[CodeGraph] Failed to update configuration:', error);
-                }
+            if (e.affectsConfiguration('codegraph')) {
+                await pushSettings();
             }
-        })
+        }),
+        // Folder-specific excludes change with the set of folders
+        vscode.workspace.onDidChangeWorkspaceFolders(pushSettings)
     );
 
     // Register commands, tree providers, etc.
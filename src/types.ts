@@ -71,6 +71,25 @@ export interface CallGraphResponse {
     edges: CallEdge[];
 }
 
+export interface ExportGraphParams {
+    uri: string;
+    position?: Position;
+    format?: 'dot' | 'mermaid';
+    depth?: number;
+    direction?: 'outgoing' | 'incoming' | 'both';
+    nodeTypes?: string[];
+    edgeTypes?: string[];
+    maxNodes?: number;
+}
+
+export interface ExportGraphResponse {
+    format: 'dot' | 'mermaid';
+    content: string;
+    nodeCount: number;
+    edgeCount: number;
+    truncated: boolean;
+}
+
 // ==========================================
 // AI Context Types
 // ==========================================
@@ -271,6 +290,39 @@ export interface ParserMetricsResponse {
     };
 }
 
+// ==========================================
+// Workspace Statistics Types
+// ==========================================
+
+export interface WorkspaceStatisticsResponse {
+    nodeCount: number;
+    edgeCount: number;
+    nodesByType: Record<string, number>;
+    edgesByType: Record<string, number>;
+    filesByLanguage: Record<string, number>;
+    symbolIndex: {
+        symbols: number;
+        files: number;
+        uniqueNames: number;
+    };
+    queryIndexes: {
+        textDocuments: number;
+        textTokens: number;
+        importedModules: number;
+        functionsWithCallers: number;
+        functionsWithCallees: number;
+        symbolVectors: number;
+    };
+    parseErrors: Record<string, number>;
+    totalParseErrors: number;
+    /** Null when the memory store is not initialized */
+    memory: MemoryStatsResponse | null;
+    /** Unix seconds */
+    lastFullIndex: number | null;
+    /** Unix seconds */
+    lastIndexUpdate: number | null;
+}
+
 // ==========================================
 // Graph Visualization Types (for webview)
 // ==========================================
@@ -696,13 +748,31 @@ export interface MemoryStatsResponse {
 // Git Mining Types
 // ==========================================
 
+export interface GitMiningCandidate {
+    kind: string;
+    title: string;
+    confidence: number;
+    commits: string[];
+}
+
 export interface GitMiningResponse {
     file?: string;
     commitsProcessed: number;
     memoriesCreated: number;
     commitsSkipped: number;
+    commitsGrouped?: number;
+    pullRequestsEnriched?: number;
+    memoriesReverted?: number;
+    memoriesRestored?: number;
+    releasesMined?: number;
+    squashMergesSplit?: number;
+    trailersMined?: number;
+    dryRun?: boolean;
+    candidates?: GitMiningCandidate[] | null;
     memoryIds: string[];
     warnings: string[];
     hotspotsDetected?: number;
     couplingsDetected?: number;
+    ownershipDetected?: number;
+    sinceCommit?: string;
 }
@@ -296,6 +296,17 @@ export function registerMemoryTreeView(
         })
     );
 
+    // Register review stale memories command (stale memory hint quick fix)
+    context.subscriptions.push(
+        vscode.commands.registerCommand('codegraph.reviewStaleMemories', async (stale?: { memoryId: string; title: string }) => {
+            memoryProvider.setShowInvalidated(true);
+            await vscode.commands.executeCommand('codegraphMemories.focus');
+            if (stale?.memoryId) {
+                await vscode.commands.executeCommand('codegraph.showMemory', stale.memoryId);
+            }
+        })
+    );
+
     // Register show memory command
     context.subscriptions.push(
         vscode.commands.registerCommand('codegraph.showMemory', async (memoryId: string) => {
@@ -321,6 +332,24 @@ export function registerMemoryTreeView(
         })
     );
 
+    // Register show linked memories command (code lens target)
+    context.subscriptions.push(
+        vscode.commands.registerCommand('codegraph.showLinkedMemories', async (memories: { id: string; title: string }[]) => {
+            if (memories.length === 1) {
+                await vscode.commands.executeCommand('codegraph.showMemory', memories[0].id);
+                return;
+            }
+
+            const selection = await vscode.window.showQuickPick(
+                memories.map(m => ({ label: m.title, id: m.id })),
+                { placeHolder: 'Select a memory to show' }
+            );
+            if (selection) {
+                await vscode.commands.executeCommand('codegraph.showMemory', selection.id);
+            }
+        })
+    );
+
     // Register invalidate memory command
     context.subscriptions.push(
         vscode.commands.registerCommand('codegraph.invalidateMemory', async (item: MemoryTreeItem) => {
@@ -345,9 +374,33 @@ export function registerMemoryTreeView(
         })
     );
 
+    // Register revalidate memory command (tree item or stale memory hint)
+    context.subscriptions.push(
+        vscode.commands.registerCommand('codegraph.revalidateMemory', async (target: MemoryTreeItem | { memoryId: string }) => {
+            const id = target instanceof MemoryTreeItem ? target.memory.id : target.memoryId;
+            try {
+                const response = await client.sendRequest<{ success: boolean; relinked: number }>(
+                    'workspace/executeCommand',
+                    {
+                        command: 'codegraph.memoryRevalidate',
+                        arguments: [{ id }],
+                    }
+                );
+                if (!response.success) {
+                    vscode.window.showWarningMessage('Memory not found');
+                    return;
+                }
+                memoryProvider.refresh();
+                vscode.window.showInformationMessage('Memory marked as still valid');
+            } catch (error) {
+                vscode.window.showErrorMessage(`Failed to revalidate memory: ${error}`);
+            }
+        })
+    );
+
     // Register store memory command
     context.subscriptions.push(
-        vscode.commands.registerCommand('codegraph.storeMemory', async () => {
+        vscode.commands.registerCommand('codegraph.storeMemory', async (prefill?: { codeLinks?: { nodeId: string; nodeType: string }[] }) => {
             // Select memory kind
             const kindItems: vscode.QuickPickItem[] = [
                 { label: 'Debug Context', description: 'Bug fix or debugging information' },
@@ -415,6 +468,7 @@ export function registerMemoryTreeView(
                             title,
                             content,
                             tags,
+                            codeLinks: prefill?.codeLinks ?? [],
                         }],
                     }
                 );